@@ -0,0 +1,102 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use hedgex_lib::api::ticker::{parse_packets, DepthSide, TickData, DEPTH_LEVELS};
+
+/// A message of full-mode packets as the ticker sends them
+fn full_mode_message(packets: u16) -> Vec<u8> {
+    let mut data = packets.to_be_bytes().to_vec();
+    for i in 0..packets as u32 {
+        let mut packet = vec![0u8; 184];
+        packet[0..4].copy_from_slice(&(408065 + i * 256).to_be_bytes());
+        packet[4..8].copy_from_slice(&150_050u32.to_be_bytes());
+        packet[16..20].copy_from_slice(&1_000_000u32.to_be_bytes());
+        packet[60..64].copy_from_slice(&1_706_155_200u32.to_be_bytes());
+        data.extend_from_slice(&184u16.to_be_bytes());
+        data.extend_from_slice(&packet);
+    }
+    data
+}
+
+/// Full-mode tick decoded eagerly into owned fields, as a copying parser produces
+struct CopiedTick {
+    instrument_token: u32,
+    last_price: f64,
+    volume: u32,
+    timestamp: u32,
+    bids: Vec<(u32, f64, u16)>,
+    asks: Vec<(u32, f64, u16)>,
+}
+
+/// Baseline: copy each packet out of the message, then decode every field of the copy
+fn parse_copying(data: &[u8]) -> Vec<CopiedTick> {
+    let read_u32 = |bytes: &[u8], offset: usize| u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap());
+    let read_level = |bytes: &[u8], offset: usize| (
+        read_u32(bytes, offset),
+        read_u32(bytes, offset + 4) as i32 as f64 / 100.0,
+        u16::from_be_bytes([bytes[offset + 8], bytes[offset + 9]]),
+    );
+
+    let count = u16::from_be_bytes([data[0], data[1]]) as usize;
+    let mut offset = 2;
+    let mut ticks = Vec::with_capacity(count);
+    for _ in 0..count {
+        let length = u16::from_be_bytes([data[offset], data[offset + 1]]) as usize;
+        let packet = data[offset + 2..offset + 2 + length].to_vec();
+        offset += 2 + length;
+
+        ticks.push(CopiedTick {
+            instrument_token: read_u32(&packet, 0),
+            last_price: read_u32(&packet, 4) as i32 as f64 / 100.0,
+            volume: read_u32(&packet, 16),
+            timestamp: read_u32(&packet, 60),
+            bids: (0..DEPTH_LEVELS).map(|level| read_level(&packet, 64 + level * 12)).collect(),
+            asks: (0..DEPTH_LEVELS).map(|level| read_level(&packet, 64 + (DEPTH_LEVELS + level) * 12)).collect(),
+        });
+    }
+    ticks
+}
+
+fn bench_full_mode(c: &mut Criterion) {
+    let message = full_mode_message(100);
+    let mut group = c.benchmark_group("full_mode_100_ticks");
+    group.throughput(Throughput::Elements(100));
+
+    // Baseline the borrowed view is measured against: the same reads after copying every packet
+    group.bench_function("copied", |b| b.iter(|| {
+        let mut sum = 0.0;
+        for tick in parse_copying(black_box(&message)) {
+            sum += tick.last_price;
+            for (_, price, _) in &tick.bids {
+                sum += price;
+            }
+            black_box((tick.instrument_token, tick.volume, tick.timestamp, &tick.asks));
+        }
+        sum
+    }));
+
+    // Reading fields straight from the buffer, as the depth and price consumers do
+    group.bench_function("borrowed", |b| b.iter(|| {
+        let mut sum = 0.0;
+        for packet in parse_packets(black_box(&message)).flatten() {
+            sum += packet.last_price();
+            for level in 0..DEPTH_LEVELS {
+                if let Some(entry) = packet.depth(DepthSide::Bid, level) {
+                    sum += entry.price;
+                }
+            }
+        }
+        sum
+    }));
+
+    // Building the owned tick sent to listeners
+    group.bench_function("owned", |b| b.iter(|| {
+        parse_packets(black_box(&message))
+            .flatten()
+            .map(|packet| TickData::from_packet(&packet))
+            .count()
+    }));
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_full_mode);
+criterion_main!(benches);
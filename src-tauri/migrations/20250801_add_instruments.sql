@@ -0,0 +1,23 @@
+-- Instruments master populated from the Kite instruments dump
+
+CREATE TABLE IF NOT EXISTS instruments (
+    instrument_token INTEGER PRIMARY KEY,
+    exchange_token INTEGER NOT NULL,
+    tradingsymbol TEXT NOT NULL,
+    name TEXT,
+    last_price REAL NOT NULL DEFAULT 0.0,
+    expiry DATE,
+    strike REAL,
+    tick_size REAL NOT NULL DEFAULT 0.05,
+    lot_size INTEGER NOT NULL DEFAULT 1,
+    instrument_type TEXT NOT NULL,
+    segment TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    UNIQUE(exchange, tradingsymbol)
+);
+
+CREATE INDEX IF NOT EXISTS idx_instruments_tradingsymbol ON instruments(tradingsymbol);
+CREATE INDEX IF NOT EXISTS idx_instruments_name ON instruments(name);
+CREATE INDEX IF NOT EXISTS idx_instruments_segment ON instruments(segment);
+CREATE INDEX IF NOT EXISTS idx_instruments_exchange ON instruments(exchange);
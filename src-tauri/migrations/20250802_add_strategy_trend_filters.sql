@@ -0,0 +1,11 @@
+-- Optional index trend (market regime) filter per strategy
+CREATE TABLE IF NOT EXISTS strategy_trend_filters (
+    strategy_id TEXT PRIMARY KEY,
+    index_symbol TEXT NOT NULL DEFAULT 'NIFTY 50',
+    ma_period INTEGER NOT NULL DEFAULT 20,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (strategy_id) REFERENCES strategy_params(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_strategy_trend_filters_index ON strategy_trend_filters(index_symbol);
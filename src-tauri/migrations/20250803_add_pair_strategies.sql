@@ -0,0 +1,17 @@
+-- Two-leg (pair trading) configuration attached to a strategy
+CREATE TABLE IF NOT EXISTS pair_strategies (
+    strategy_id TEXT PRIMARY KEY,
+    symbol_a TEXT NOT NULL,
+    symbol_b TEXT NOT NULL,
+    exchange TEXT NOT NULL DEFAULT 'NSE',
+    hedge_ratio REAL NOT NULL DEFAULT 1.0,
+    lookback INTEGER NOT NULL DEFAULT 60,
+    entry_z REAL NOT NULL DEFAULT 2.0,
+    exit_z REAL NOT NULL DEFAULT 0.5,
+    stop_z REAL NOT NULL DEFAULT 4.0,
+    updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (strategy_id) REFERENCES strategy_params(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_pair_strategies_symbol_a ON pair_strategies(symbol_a);
+CREATE INDEX IF NOT EXISTS idx_pair_strategies_symbol_b ON pair_strategies(symbol_b);
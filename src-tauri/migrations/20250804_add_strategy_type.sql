@@ -0,0 +1,3 @@
+-- Built-in strategy logic selected per strategy
+
+ALTER TABLE strategy_params ADD COLUMN strategy_type TEXT NOT NULL DEFAULT 'BollingerMeanReversion';
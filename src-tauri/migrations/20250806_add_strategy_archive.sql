@@ -0,0 +1,5 @@
+-- Soft-delete support so trades keep referencing archived strategies
+
+ALTER TABLE strategy_params ADD COLUMN archived_at TIMESTAMP;
+
+CREATE INDEX IF NOT EXISTS idx_strategy_params_archived_at ON strategy_params(archived_at);
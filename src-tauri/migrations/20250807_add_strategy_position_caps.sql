@@ -0,0 +1,3 @@
+-- Optional per-order caps so a misconfigured risk percentage can't size an absurd order
+ALTER TABLE strategy_params ADD COLUMN max_capital REAL;
+ALTER TABLE strategy_params ADD COLUMN max_quantity_per_trade INTEGER;
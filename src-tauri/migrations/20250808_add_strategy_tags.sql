@@ -0,0 +1,2 @@
+-- Free-form strategy tags stored as a JSON array of lowercase strings
+ALTER TABLE strategy_params ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
@@ -0,0 +1,3 @@
+-- Maximum holding period per strategy and exit reasons on trades
+ALTER TABLE strategy_params ADD COLUMN max_holding_minutes INTEGER;
+ALTER TABLE trades ADD COLUMN exit_reason TEXT;
@@ -0,0 +1,9 @@
+-- Monte Carlo robustness simulations, one per backtest run
+
+CREATE TABLE IF NOT EXISTS backtest_monte_carlo (
+    backtest_id TEXT PRIMARY KEY,
+    iterations INTEGER NOT NULL,
+    result TEXT NOT NULL,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    FOREIGN KEY (backtest_id) REFERENCES backtest_runs(id) ON DELETE CASCADE
+);
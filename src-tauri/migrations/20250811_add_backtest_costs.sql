@@ -0,0 +1,4 @@
+-- Trading costs applied to simulated trades
+ALTER TABLE backtest_runs ADD COLUMN total_charges REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE backtest_runs ADD COLUMN cost_model TEXT;
+ALTER TABLE backtest_trades ADD COLUMN charges REAL NOT NULL DEFAULT 0.0;
@@ -0,0 +1,2 @@
+-- Buy-and-hold NIFTY comparison stored with each backtest run
+ALTER TABLE backtest_runs ADD COLUMN benchmark TEXT;
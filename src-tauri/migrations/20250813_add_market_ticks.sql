@@ -0,0 +1,14 @@
+-- Every tick received from the market feed, kept for tick-level backtests
+
+CREATE TABLE IF NOT EXISTS market_ticks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL DEFAULT 'NSE',
+    timestamp TIMESTAMP NOT NULL,
+    ltp REAL NOT NULL,
+    bid REAL NOT NULL,
+    ask REAL NOT NULL,
+    volume INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_ticks_symbol_time ON market_ticks(symbol, exchange, timestamp);
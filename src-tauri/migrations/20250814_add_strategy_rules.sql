@@ -0,0 +1,2 @@
+-- Entry and exit conditions for custom strategies stored as JSON
+ALTER TABLE strategy_params ADD COLUMN rules TEXT;
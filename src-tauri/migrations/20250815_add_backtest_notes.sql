@@ -0,0 +1,3 @@
+-- User-supplied label and notes for backtest runs
+ALTER TABLE backtest_runs ADD COLUMN label TEXT;
+ALTER TABLE backtest_runs ADD COLUMN notes TEXT;
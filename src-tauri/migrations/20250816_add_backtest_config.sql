@@ -0,0 +1,5 @@
+-- Configuration snapshot and its hash, so stored backtests can be re-run and verified
+ALTER TABLE backtest_runs ADD COLUMN config TEXT;
+ALTER TABLE backtest_runs ADD COLUMN config_hash TEXT;
+
+CREATE INDEX IF NOT EXISTS idx_backtest_runs_config_hash ON backtest_runs(config_hash);
@@ -0,0 +1,18 @@
+-- Backtests and optimizations queued to run in the background
+
+CREATE TABLE IF NOT EXISTS backtest_jobs (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    kind TEXT NOT NULL,
+    request TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'queued',
+    backtest_id TEXT,
+    result TEXT,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+    started_at TIMESTAMP,
+    finished_at TIMESTAMP
+);
+
+CREATE INDEX IF NOT EXISTS idx_backtest_jobs_status ON backtest_jobs(status, created_at);
+CREATE INDEX IF NOT EXISTS idx_backtest_jobs_user ON backtest_jobs(user_id, created_at);
@@ -0,0 +1,7 @@
+-- Extended equity-curve statistics stored with each backtest run
+ALTER TABLE backtest_runs ADD COLUMN cagr REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE backtest_runs ADD COLUMN calmar_ratio REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE backtest_runs ADD COLUMN sortino_ratio REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE backtest_runs ADD COLUMN exposure_percent REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE backtest_runs ADD COLUMN longest_flat_minutes INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE backtest_runs ADD COLUMN max_consecutive_losses INTEGER NOT NULL DEFAULT 0;
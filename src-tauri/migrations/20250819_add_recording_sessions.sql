@@ -0,0 +1,27 @@
+-- Ticks and depth captured for chosen symbols during a recording session
+
+CREATE TABLE IF NOT EXISTS recording_sessions (
+    id TEXT PRIMARY KEY,
+    user_id TEXT NOT NULL,
+    name TEXT NOT NULL,
+    symbols TEXT NOT NULL,
+    started_at TIMESTAMP NOT NULL,
+    stopped_at TIMESTAMP,
+    tick_count INTEGER NOT NULL DEFAULT 0
+);
+
+CREATE TABLE IF NOT EXISTS recording_session_ticks (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    session_id TEXT NOT NULL,
+    symbol TEXT NOT NULL,
+    timestamp TIMESTAMP NOT NULL,
+    ltp REAL NOT NULL,
+    bid REAL NOT NULL,
+    ask REAL NOT NULL,
+    volume INTEGER NOT NULL,
+    depth TEXT,
+    FOREIGN KEY (session_id) REFERENCES recording_sessions(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_recording_sessions_user ON recording_sessions(user_id, started_at);
+CREATE INDEX IF NOT EXISTS idx_recording_session_ticks_session ON recording_session_ticks(session_id, id);
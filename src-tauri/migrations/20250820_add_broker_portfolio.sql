@@ -0,0 +1,29 @@
+-- Positions and holdings as last reported by the broker, including ones HedgeX did not open
+
+CREATE TABLE IF NOT EXISTS broker_positions (
+    exchange TEXT NOT NULL,
+    tradingsymbol TEXT NOT NULL,
+    product TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    average_price REAL NOT NULL,
+    last_price REAL NOT NULL,
+    realized_pnl REAL NOT NULL,
+    unrealized_pnl REAL NOT NULL,
+    synced_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (exchange, tradingsymbol, product)
+);
+
+CREATE TABLE IF NOT EXISTS broker_holdings (
+    exchange TEXT NOT NULL,
+    tradingsymbol TEXT NOT NULL,
+    isin TEXT NOT NULL,
+    quantity INTEGER NOT NULL,
+    t1_quantity INTEGER NOT NULL,
+    average_price REAL NOT NULL,
+    last_price REAL NOT NULL,
+    close_price REAL NOT NULL,
+    pnl REAL NOT NULL,
+    day_change REAL NOT NULL,
+    synced_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (exchange, tradingsymbol)
+);
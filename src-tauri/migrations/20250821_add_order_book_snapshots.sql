@@ -0,0 +1,23 @@
+-- Daily snapshots of the broker's order book and trade book, manual orders included
+
+CREATE TABLE IF NOT EXISTS order_book_orders (
+    session_date DATE NOT NULL,
+    order_id TEXT NOT NULL,
+    tradingsymbol TEXT NOT NULL,
+    order_timestamp TIMESTAMP,
+    data TEXT NOT NULL,
+    synced_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (session_date, order_id)
+);
+
+CREATE TABLE IF NOT EXISTS order_book_trades (
+    session_date DATE NOT NULL,
+    trade_id TEXT NOT NULL,
+    order_id TEXT NOT NULL,
+    fill_timestamp TIMESTAMP,
+    data TEXT NOT NULL,
+    synced_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (session_date, trade_id)
+);
+
+CREATE INDEX IF NOT EXISTS idx_order_book_trades_order ON order_book_trades(order_id);
@@ -0,0 +1,13 @@
+-- GTT triggers resting with the broker, flagged when HedgeX placed them
+
+CREATE TABLE IF NOT EXISTS gtt_triggers (
+    trigger_id INTEGER PRIMARY KEY,
+    exchange TEXT NOT NULL,
+    tradingsymbol TEXT NOT NULL,
+    status TEXT NOT NULL,
+    data TEXT NOT NULL,
+    placed_by_hedgex BOOLEAN NOT NULL DEFAULT 0,
+    synced_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_gtt_triggers_symbol ON gtt_triggers(exchange, tradingsymbol);
@@ -0,0 +1,8 @@
+-- Estimated brokerage, taxes and fees on each trade, from the backtesting cost model
+ALTER TABLE trades ADD COLUMN brokerage REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE trades ADD COLUMN stt REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE trades ADD COLUMN exchange_charges REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE trades ADD COLUMN sebi_fee REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE trades ADD COLUMN gst REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE trades ADD COLUMN stamp_duty REAL NOT NULL DEFAULT 0.0;
+ALTER TABLE trades ADD COLUMN charges REAL NOT NULL DEFAULT 0.0;
@@ -0,0 +1,3 @@
+-- Signal price and order type of live trades, to measure fill slippage
+ALTER TABLE trades ADD COLUMN signal_price REAL;
+ALTER TABLE trades ADD COLUMN order_type TEXT;
@@ -0,0 +1,22 @@
+-- End-of-day summary reports and the channels they are delivered on
+
+CREATE TABLE IF NOT EXISTS eod_reports (
+    user_id TEXT NOT NULL,
+    report_date TEXT NOT NULL,
+    report TEXT NOT NULL,
+    -- Comma-separated channels the report reached
+    delivered_to TEXT,
+    created_at TIMESTAMP NOT NULL,
+    PRIMARY KEY (user_id, report_date)
+);
+
+-- Bot token and email API key are stored encrypted
+CREATE TABLE IF NOT EXISTS notification_channels (
+    user_id TEXT PRIMARY KEY,
+    telegram_bot_token TEXT,
+    telegram_chat_id TEXT,
+    email_api_url TEXT,
+    email_api_key TEXT,
+    email_to TEXT,
+    updated_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,4 @@
+-- Entry price and maximum adverse and favorable excursions of the position a live trade exits
+ALTER TABLE trades ADD COLUMN entry_price REAL;
+ALTER TABLE trades ADD COLUMN mae_percentage REAL;
+ALTER TABLE trades ADD COLUMN mfe_percentage REAL;
@@ -0,0 +1,25 @@
+-- Cloud backup destination and the uploads made to it
+
+-- Single row; the settings JSON holds destination secrets and is stored encrypted
+CREATE TABLE IF NOT EXISTS cloud_backup_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    settings TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
+
+CREATE TABLE IF NOT EXISTS cloud_backup_uploads (
+    id TEXT PRIMARY KEY,
+    backup_id TEXT NOT NULL,
+    -- 's3' or 'google_drive'
+    destination TEXT NOT NULL,
+    -- Object key or Drive file ID; unset when the upload failed
+    remote_id TEXT,
+    remote_name TEXT NOT NULL,
+    size INTEGER NOT NULL,
+    -- Uploaded, Failed or Deleted
+    status TEXT NOT NULL,
+    error TEXT,
+    created_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_cloud_backup_uploads_destination ON cloud_backup_uploads(destination, status, created_at);
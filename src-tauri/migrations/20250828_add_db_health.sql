@@ -0,0 +1,17 @@
+-- Results of the scheduled database maintenance runs
+
+CREATE TABLE IF NOT EXISTS db_health (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    checked_at TIMESTAMP NOT NULL,
+    integrity_ok BOOLEAN NOT NULL,
+    -- 'ok', or the first problems integrity_check reported
+    integrity_message TEXT NOT NULL,
+    size_bytes INTEGER NOT NULL,
+    -- Space held by free pages, reclaimable with VACUUM
+    free_bytes INTEGER NOT NULL,
+    -- Pages moved from the WAL into the database; -1 outside WAL mode
+    wal_pages_checkpointed INTEGER NOT NULL,
+    duration_ms INTEGER NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_db_health_checked_at ON db_health(checked_at);
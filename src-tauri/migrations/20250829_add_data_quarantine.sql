@@ -0,0 +1,12 @@
+-- Rows moved aside by the data integrity repair instead of being deleted
+
+CREATE TABLE IF NOT EXISTS data_quarantine (
+    id INTEGER PRIMARY KEY AUTOINCREMENT,
+    source_table TEXT NOT NULL,
+    -- The row as a JSON object of its columns
+    row_data TEXT NOT NULL,
+    reason TEXT NOT NULL,
+    quarantined_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_data_quarantine_source_table ON data_quarantine(source_table);
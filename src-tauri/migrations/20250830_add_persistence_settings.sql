@@ -0,0 +1,8 @@
+-- Retention and backup settings changed from the app
+
+-- Single row holding DataPersistenceConfig as JSON
+CREATE TABLE IF NOT EXISTS persistence_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    settings TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
@@ -0,0 +1,8 @@
+-- Passphrase local backups are encrypted with
+
+-- Single row holding the passphrase, itself encrypted
+CREATE TABLE IF NOT EXISTS backup_encryption (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    passphrase TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
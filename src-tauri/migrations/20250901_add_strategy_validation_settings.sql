@@ -0,0 +1,8 @@
+-- Validation backtest settings changed from the app
+
+-- Single row holding EnableValidationConfig as JSON
+CREATE TABLE IF NOT EXISTS strategy_validation_settings (
+    id INTEGER PRIMARY KEY CHECK (id = 1),
+    settings TEXT NOT NULL,
+    updated_at TIMESTAMP NOT NULL
+);
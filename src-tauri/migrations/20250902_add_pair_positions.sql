@@ -0,0 +1,19 @@
+-- Open pair positions, so both legs are still tracked as one after a restart
+CREATE TABLE IF NOT EXISTS pair_positions (
+    id TEXT PRIMARY KEY,
+    strategy_id TEXT NOT NULL UNIQUE,
+    user_id TEXT NOT NULL,
+    symbol_a TEXT NOT NULL,
+    symbol_b TEXT NOT NULL,
+    exchange TEXT NOT NULL,
+    side TEXT NOT NULL,
+    quantity_a INTEGER NOT NULL,
+    quantity_b INTEGER NOT NULL,
+    entry_price_a REAL NOT NULL,
+    entry_price_b REAL NOT NULL,
+    entry_z REAL NOT NULL,
+    opened_at TIMESTAMP NOT NULL,
+    FOREIGN KEY (strategy_id) REFERENCES strategy_params(id) ON DELETE CASCADE
+);
+
+CREATE INDEX IF NOT EXISTS idx_pair_positions_user ON pair_positions(user_id);
@@ -0,0 +1,15 @@
+-- Every tick received from the market feed, kept for tick-level backtests
+-- PostgreSQL version of ../20250813_add_market_ticks.sql
+
+CREATE TABLE IF NOT EXISTS market_ticks (
+    id BIGSERIAL PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    exchange TEXT NOT NULL DEFAULT 'NSE',
+    timestamp TIMESTAMPTZ NOT NULL,
+    ltp DOUBLE PRECISION NOT NULL,
+    bid DOUBLE PRECISION NOT NULL,
+    ask DOUBLE PRECISION NOT NULL,
+    volume BIGINT NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS idx_market_ticks_symbol_time ON market_ticks(symbol, exchange, timestamp);
@@ -0,0 +1,532 @@
+use crate::error::{ApiResult, HedgeXError, Result};
+use crate::api::middleware::auth_middleware;
+use crate::models::trading::{Trade, TradeStatus, TradeType};
+use crate::services::{AuthService, EnhancedDatabaseService, WebSocketManager};
+use crate::services::pnl_statement::{PnlCalendar, PnlStatementService};
+use crate::services::time_breakdown::TimeBreakdown;
+use crate::services::strategy_correlation::CorrelationMatrix;
+use crate::services::trade_streaks::StreakReport;
+use crate::services::slippage::{SlippageReport, SlippageService};
+use crate::services::excursions::{ExcursionReport, ExcursionService};
+use crate::services::report_builder::{ReportSpec, ReportTable};
+use crate::services::pnl_split::PnlSplit;
+use crate::services::rolling_performance::{RollingPerformance, SharpeInputs, DEFAULT_ROLLING_WINDOWS};
+use crate::trading::analytics_filter::{page_bounds, PageInfo, TradeFilter, DEFAULT_PAGE_LIMIT};
+use crate::trading::performance::{
+    daily_pnl_series, load_daily_pnl, load_trade_pnl, max_drawdown, risk_ratios, Drawdown, RiskRatios,
+    TRADING_DAYS_PER_YEAR,
+};
+use crate::trading::session::session_date;
+use axum::{
+    extract::{Extension, Query, State},
+    middleware,
+    response::Json,
+    routing::{get, post},
+    Router,
+};
+use serde::Serialize;
+use sqlx::Row;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::task::JoinHandle;
+use tracing::{error, info};
+
+/// Shared state of the analytics endpoints
+pub struct AnalyticsState {
+    database: Arc<EnhancedDatabaseService>,
+    /// Source of the last traded prices open positions are marked to
+    websocket_manager: Arc<WebSocketManager>,
+    pnl_statement: PnlStatementService,
+    slippage: SlippageService,
+    excursions: ExcursionService,
+}
+
+impl AnalyticsState {
+    pub fn new(database: Arc<EnhancedDatabaseService>, websocket_manager: Arc<WebSocketManager>) -> Self {
+        Self {
+            pnl_statement: PnlStatementService::new(Arc::clone(&database)),
+            slippage: SlippageService::new(Arc::clone(&database)),
+            excursions: ExcursionService::new(Arc::clone(&database)),
+            database,
+            websocket_manager,
+        }
+    }
+}
+
+/// Analytics routes under `/api/analytics`, for sessions authenticated by `auth_service`
+pub fn analytics_routes(state: Arc<AnalyticsState>, auth_service: Arc<AuthService>) -> Router {
+    Router::new()
+        .route("/api/analytics/trades", get(get_trade_history))
+        .route("/api/analytics/performance", get(get_analytics_performance))
+        .route("/api/analytics/calendar", get(get_analytics_calendar))
+        .route("/api/analytics/time-breakdown", get(get_analytics_time_breakdown))
+        .route("/api/analytics/correlation", get(get_analytics_correlation))
+        .route("/api/analytics/streaks", get(get_analytics_streaks))
+        .route("/api/analytics/slippage", get(get_analytics_slippage))
+        .route("/api/analytics/excursions", get(get_analytics_excursions))
+        .route("/api/analytics/pnl-split", get(get_analytics_pnl_split))
+        .route("/api/analytics/rolling", get(get_analytics_rolling_performance))
+        .route("/api/analytics/reports", post(run_custom_report))
+        .route_layer(middleware::from_fn_with_state(auth_service, auth_middleware))
+        .with_state(state)
+}
+
+/// Serve `routes` on their own listener, for dashboards and scripts outside the app
+pub async fn start_api_listener(addr: SocketAddr, routes: Router) -> Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("Serving the HTTP API on {}", addr);
+
+    Ok(tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, routes).await {
+            error!("HTTP API listener stopped: {}", e);
+        }
+    }))
+}
+
+/// A `trades` row as a trade, skipping rows with an unknown type or status
+pub(crate) fn trade_from_row(row: &sqlx::sqlite::SqliteRow) -> Option<Trade> {
+    let trade_type_str: String = row.get("trade_type");
+    let status_str: String = row.get("status");
+    let price_f64: f64 = row.get("price");
+    
+    let trade_type = match trade_type_str.as_str() {
+        "Buy" => TradeType::Buy,
+        "Sell" => TradeType::Sell,
+        _ => return None,
+    };
+    
+    let status = match status_str.as_str() {
+        "Pending" => TradeStatus::Pending,
+        "Executed" => TradeStatus::Executed,
+        "Cancelled" => TradeStatus::Cancelled,
+        "Failed" => TradeStatus::Failed,
+        "PartiallyFilled" => TradeStatus::PartiallyFilled,
+        _ => return None,
+    };
+    
+    Some(Trade {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        symbol: row.get("symbol"),
+        exchange: row.get("exchange"),
+        order_id: row.get("order_id"),
+        trade_type,
+        quantity: row.get("quantity"),
+        price: rust_decimal::Decimal::from_f64_retain(price_f64).unwrap_or(rust_decimal::Decimal::ZERO),
+        status,
+        executed_at: row.get("executed_at"),
+        strategy_id: row.get("strategy_id"),
+        exit_reason: row.get("exit_reason"),
+        created_at: row.get("created_at"),
+        updated_at: row.get("updated_at"),
+    })
+}
+
+/// Date range and filters of an analytics request
+///
+/// `from` and `to` are IST dates; without `from` the range covers `days` days, or
+/// `default_days`, ending at `to`, and the whole history when neither is set.
+fn analytics_filter(params: &HashMap<String, String>, default_days: Option<i64>) -> Result<TradeFilter> {
+    let days = params.get("days")
+        .and_then(|s| s.parse::<i64>().ok())
+        .or(default_days);
+    let param = |name: &str| params.get(name).map(String::as_str);
+    TradeFilter::from_params(
+        session_date(chrono::Utc::now()),
+        days,
+        param("from"),
+        param("to"),
+        param("strategy_id"),
+        param("symbol"),
+    )
+}
+
+/// `limit` and `offset` of a paginated request
+fn page_params(params: &HashMap<String, String>, default_limit: i64) -> (i64, i64) {
+    let param = |name: &str| params.get(name).and_then(|s| s.parse::<i64>().ok());
+    page_bounds(param("limit"), param("offset"), default_limit)
+}
+
+#[derive(Serialize)]
+struct TradeHistoryPage {
+    trades: Vec<Trade>,
+    pagination: PageInfo,
+}
+
+/// Trades matching the date range and filters, newest first, a page at a time
+async fn get_trade_history(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResult<TradeHistoryPage>> {
+    let filter = match analytics_filter(&params, None) {
+        Ok(filter) => filter,
+        Err(e) => return Json(ApiResult::from_error(e)),
+    };
+    let (limit, offset) = page_params(&params, DEFAULT_PAGE_LIMIT);
+    let (conditions, binds) = filter.sql_conditions("");
+    
+    let database = state.database.get_database();
+    let db_pool = database.get_pool();
+    
+    let count_query = format!("SELECT COUNT(*) as total FROM trades WHERE user_id = ?{}", conditions);
+    let mut count = sqlx::query(&count_query).bind(&user_id);
+    for value in &binds {
+        count = count.bind(value);
+    }
+    let total: i64 = match count.fetch_one(db_pool).await {
+        Ok(row) => row.get("total"),
+        Err(e) => {
+            error!("Failed to count trade history: {}", e);
+            return Json(ApiResult::from_error(HedgeXError::DatabaseError(e)));
+        }
+    };
+    
+    let query = format!("
+        SELECT id, user_id, symbol, exchange, order_id, trade_type, quantity, 
+               price, status, executed_at, strategy_id, exit_reason, created_at, updated_at
+        FROM trades 
+        WHERE user_id = ?{}
+        ORDER BY executed_at DESC 
+        LIMIT ? OFFSET ?
+    ", conditions);
+    let mut page = sqlx::query(&query).bind(&user_id);
+    for value in &binds {
+        page = page.bind(value);
+    }
+    
+    match page.bind(limit).bind(offset).fetch_all(db_pool).await {
+        Ok(rows) => Json(ApiResult::success(TradeHistoryPage {
+            trades: rows.iter().filter_map(trade_from_row).collect(),
+            pagination: PageInfo::new(total, limit, offset),
+        })),
+        Err(e) => {
+            error!("Failed to get trade history: {}", e);
+            Json(ApiResult::from_error(HedgeXError::DatabaseError(e)))
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct AnalyticsPerformanceResponse {
+    total_trades: i32,
+    profitable_trades: i32,
+    total_pnl: String,
+    gross_pnl: String,
+    total_charges: String,
+    net_pnl: String,
+    realized_pnl: String,
+    unrealized_pnl: String,
+    win_rate: f64,
+    max_drawdown: String,
+    max_drawdown_percent: f64,
+    sharpe_ratio: f64,
+    sortino_ratio: f64,
+    calmar_ratio: f64,
+    profit_factor: f64,
+    #[serde(flatten)]
+    filter: TradeFilter,
+}
+
+async fn get_analytics_performance(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResult<AnalyticsPerformanceResponse>> {
+    let filter = match analytics_filter(&params, Some(30)) {
+        Ok(filter) => filter,
+        Err(e) => return Json(ApiResult::from_error(e)),
+    };
+    let float_param = |name: &str| params.get(name).and_then(|s| s.parse::<f64>().ok());
+    let risk_free_rate = float_param("risk_free_rate").unwrap_or(0.0);
+    let capital = float_param("capital").unwrap_or(0.0);
+    let periods_per_year = float_param("periods_per_year").unwrap_or(TRADING_DAYS_PER_YEAR);
+    
+    // Calculate performance metrics from database
+    let (conditions, binds) = filter.sql_conditions("");
+    let query = format!("
+        SELECT 
+            COUNT(*) as total_trades,
+            COUNT(CASE WHEN price > 0 THEN 1 END) as profitable_trades,
+            SUM(charges) as total_charges
+        FROM trades 
+        WHERE user_id = ? 
+        AND status = 'Executed'{}
+    ", conditions);
+    let mut metrics = sqlx::query(&query).bind(&user_id);
+    for value in &binds {
+        metrics = metrics.bind(value);
+    }
+    
+    let database = state.database.get_database();
+    let db_pool = database.get_pool();
+    
+    match metrics.fetch_one(db_pool).await {
+        Ok(row) => {
+            let total_trades: i32 = row.get("total_trades");
+            let profitable_trades: i32 = row.get("profitable_trades");
+            let total_charges: f64 = row.get::<Option<f64>, _>("total_charges").unwrap_or(0.0);
+            
+            // Closed quantity only; open positions are marked to live prices separately
+            let prices = state.websocket_manager.get_last_prices().await;
+            let (total_pnl, unrealized_pnl) = match state.pnl_statement.pnl_split(&user_id, &filter, &prices).await {
+                Ok(split) => (split.realized_pnl, split.unrealized_pnl),
+                Err(e) => {
+                    error!("Failed to split realized and unrealized P&L: {}", e);
+                    (0.0, 0.0)
+                }
+            };
+            
+            let win_rate = if total_trades > 0 {
+                (profitable_trades as f64 / total_trades as f64) * 100.0
+            } else {
+                0.0
+            };
+            
+            let ratios = match load_daily_pnl(db_pool, &user_id, &filter).await {
+                Ok(pnl_by_day) => {
+                    let daily_pnl = daily_pnl_series(&pnl_by_day, filter.from.unwrap_or(filter.to), filter.to);
+                    risk_ratios(&daily_pnl, capital, risk_free_rate, periods_per_year)
+                }
+                Err(e) => {
+                    error!("Failed to load daily P&L: {}", e);
+                    RiskRatios::default()
+                }
+            };
+            
+            let drawdown = match load_trade_pnl(db_pool, &user_id, &filter).await {
+                Ok(trades) => {
+                    let pnls: Vec<f64> = trades.iter().map(|(_, pnl)| *pnl).collect();
+                    max_drawdown(capital, &pnls)
+                }
+                Err(e) => {
+                    error!("Failed to load trade P&L: {}", e);
+                    Drawdown::default()
+                }
+            };
+            
+            let response = AnalyticsPerformanceResponse {
+                total_trades,
+                profitable_trades,
+                total_pnl: total_pnl.to_string(),
+                gross_pnl: total_pnl.to_string(),
+                total_charges: total_charges.to_string(),
+                net_pnl: (total_pnl - total_charges).to_string(),
+                realized_pnl: total_pnl.to_string(),
+                unrealized_pnl: unrealized_pnl.to_string(),
+                win_rate,
+                max_drawdown: drawdown.max_drawdown.to_string(),
+                max_drawdown_percent: drawdown.max_drawdown_percent,
+                sharpe_ratio: ratios.sharpe_ratio,
+                sortino_ratio: ratios.sortino_ratio,
+                calmar_ratio: ratios.calmar_ratio,
+                profit_factor: if total_trades > 0 { total_pnl / total_trades as f64 } else { 0.0 },
+                filter,
+            };
+            
+            Json(ApiResult::success(response))
+        }
+        Err(e) => {
+            error!("Failed to get analytics performance: {}", e);
+            Json(ApiResult::from_error(HedgeXError::DatabaseError(e)))
+        }
+    }
+}
+
+/// Daily realized P&L of a month for the calendar heatmap, the current IST month by default
+async fn get_analytics_calendar(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResult<PnlCalendar>> {
+    let today = session_date(chrono::Utc::now());
+    let year = params.get("year")
+        .and_then(|s| s.parse::<i32>().ok())
+        .unwrap_or_else(|| chrono::Datelike::year(&today));
+    let month = params.get("month")
+        .and_then(|s| s.parse::<u32>().ok())
+        .unwrap_or_else(|| chrono::Datelike::month(&today));
+    
+    match state.pnl_statement.calendar(&user_id, year, month).await {
+        Ok(calendar) => Json(ApiResult::success(calendar)),
+        Err(e) => {
+            error!("Failed to get P&L calendar: {}", e);
+            Json(ApiResult::from_error(e))
+        }
+    }
+}
+
+/// Win rate and average P&L by entry hour and weekday, over the last 30 days by default
+async fn get_analytics_time_breakdown(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResult<TimeBreakdown>> {
+    let filter = match analytics_filter(&params, Some(30)) {
+        Ok(filter) => filter,
+        Err(e) => return Json(ApiResult::from_error(e)),
+    };
+    
+    match state.pnl_statement.time_breakdown(&user_id, &filter).await {
+        Ok(breakdown) => Json(ApiResult::success(breakdown)),
+        Err(e) => {
+            error!("Failed to get time breakdown: {}", e);
+            Json(ApiResult::from_error(e))
+        }
+    }
+}
+
+/// Correlation of strategies' daily P&L, over the last 90 days by default
+async fn get_analytics_correlation(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResult<CorrelationMatrix>> {
+    let filter = match analytics_filter(&params, Some(90)) {
+        Ok(filter) => filter,
+        Err(e) => return Json(ApiResult::from_error(e)),
+    };
+    
+    match state.pnl_statement.strategy_correlation(&user_id, &filter).await {
+        Ok(matrix) => Json(ApiResult::success(matrix)),
+        Err(e) => {
+            error!("Failed to get strategy correlation: {}", e);
+            Json(ApiResult::from_error(e))
+        }
+    }
+}
+
+/// Current and longest win and loss streaks, overall and by strategy, over the whole history by default
+async fn get_analytics_streaks(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResult<StreakReport>> {
+    let filter = match analytics_filter(&params, None) {
+        Ok(filter) => filter,
+        Err(e) => return Json(ApiResult::from_error(e)),
+    };
+    
+    match state.pnl_statement.streaks(&user_id, &filter).await {
+        Ok(report) => Json(ApiResult::success(report)),
+        Err(e) => {
+            error!("Failed to get trade streaks: {}", e);
+            Json(ApiResult::from_error(e))
+        }
+    }
+}
+
+/// Slippage of live fills against their signal prices, over the last 30 days by default
+async fn get_analytics_slippage(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResult<SlippageReport>> {
+    let filter = match analytics_filter(&params, Some(30)) {
+        Ok(filter) => filter,
+        Err(e) => return Json(ApiResult::from_error(e)),
+    };
+    
+    match state.slippage.report(&user_id, &filter).await {
+        Ok(report) => Json(ApiResult::success(report)),
+        Err(e) => {
+            error!("Failed to get slippage analysis: {}", e);
+            Json(ApiResult::from_error(e))
+        }
+    }
+}
+
+/// Maximum adverse and favorable excursions of live exits, over the last 30 days by default
+async fn get_analytics_excursions(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResult<ExcursionReport>> {
+    let filter = match analytics_filter(&params, Some(30)) {
+        Ok(filter) => filter,
+        Err(e) => return Json(ApiResult::from_error(e)),
+    };
+    
+    match state.excursions.report(&user_id, &filter).await {
+        Ok(report) => Json(ApiResult::success(report)),
+        Err(e) => {
+            error!("Failed to get trade excursions: {}", e);
+            Json(ApiResult::from_error(e))
+        }
+    }
+}
+
+/// Realized P&L of closed quantity and unrealized P&L of open positions, today by default
+async fn get_analytics_pnl_split(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResult<PnlSplit>> {
+    let filter = match analytics_filter(&params, Some(1)) {
+        Ok(filter) => filter,
+        Err(e) => return Json(ApiResult::from_error(e)),
+    };
+    
+    let prices = state.websocket_manager.get_last_prices().await;
+    match state.pnl_statement.pnl_split(&user_id, &filter, &prices).await {
+        Ok(split) => Json(ApiResult::success(split)),
+        Err(e) => {
+            error!("Failed to split realized and unrealized P&L: {}", e);
+            Json(ApiResult::from_error(e))
+        }
+    }
+}
+
+/// Rolling P&L, win rate and Sharpe ratio over `windows` trading days, 7, 30 and 90 by default
+async fn get_analytics_rolling_performance(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Query(params): Query<HashMap<String, String>>,
+) -> Json<ApiResult<RollingPerformance>> {
+    let filter = match analytics_filter(&params, Some(365)) {
+        Ok(filter) => filter,
+        Err(e) => return Json(ApiResult::from_error(e)),
+    };
+    let windows: Vec<usize> = match params.get("windows") {
+        Some(windows) => match windows.split(',').map(|w| w.trim().parse::<usize>()).collect() {
+            Ok(windows) => windows,
+            Err(_) => {
+                return Json(ApiResult::from_error(
+                    HedgeXError::ValidationError(format!("Invalid rolling windows: {}", windows))
+                ));
+            }
+        },
+        None => DEFAULT_ROLLING_WINDOWS.to_vec(),
+    };
+    let float_param = |name: &str| params.get(name).and_then(|s| s.parse::<f64>().ok());
+    let sharpe = SharpeInputs {
+        capital: float_param("capital").unwrap_or(0.0),
+        risk_free_rate: float_param("risk_free_rate").unwrap_or(0.0),
+        periods_per_year: float_param("periods_per_year").unwrap_or(TRADING_DAYS_PER_YEAR),
+    };
+    
+    match state.pnl_statement.rolling_performance(&user_id, &filter, &windows, sharpe).await {
+        Ok(performance) => Json(ApiResult::success(performance)),
+        Err(e) => {
+            error!("Failed to get rolling performance: {}", e);
+            Json(ApiResult::from_error(e))
+        }
+    }
+}
+
+/// Run a declarative report spec over the user's closed round trips
+async fn run_custom_report(
+    State(state): State<Arc<AnalyticsState>>,
+    Extension(user_id): Extension<String>,
+    Json(spec): Json<ReportSpec>,
+) -> Json<ApiResult<ReportTable>> {
+    match state.pnl_statement.custom_report(&user_id, &spec, session_date(chrono::Utc::now())).await {
+        Ok(table) => Json(ApiResult::success(table)),
+        Err(e) => {
+            error!("Failed to run custom report: {}", e);
+            Json(ApiResult::from_error(e))
+        }
+    }
+}
@@ -0,0 +1,52 @@
+use crate::api::middleware::auth_middleware;
+use crate::error::ApiResult;
+use crate::models::backtesting::BacktestTradePage;
+use crate::services::AuthService;
+use crate::services::backtest_engine::{BacktestEngine, DEFAULT_CONTEXT_CANDLES, DEFAULT_TRADE_PAGE_SIZE};
+use axum::{
+    extract::{Path, Query, State},
+    middleware,
+    response::Json,
+    routing::get,
+    Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+use tracing::error;
+
+/// Backtest routes under `/api/backtests`, for sessions authenticated by `auth_service`
+pub fn backtest_routes(backtest_engine: Arc<BacktestEngine>, auth_service: Arc<AuthService>) -> Router {
+    Router::new()
+        .route("/api/backtests/:id/trades", get(get_backtest_trades))
+        .route_layer(middleware::from_fn_with_state(auth_service, auth_middleware))
+        .with_state(backtest_engine)
+}
+
+/// Page of a backtest's trades, and how much price history to show around each
+#[derive(Debug, Deserialize)]
+struct BacktestTradesQuery {
+    /// One-based page number
+    page: Option<usize>,
+    page_size: Option<usize>,
+    /// Candles either side of each trade
+    context_candles: Option<usize>,
+}
+
+/// A page of a backtest's trades with their MAE/MFE and surrounding candles
+async fn get_backtest_trades(
+    State(backtest_engine): State<Arc<BacktestEngine>>,
+    Path(backtest_id): Path<String>,
+    Query(query): Query<BacktestTradesQuery>,
+) -> Json<ApiResult<BacktestTradePage>> {
+    let page = query.page.unwrap_or(1);
+    let page_size = query.page_size.unwrap_or(DEFAULT_TRADE_PAGE_SIZE);
+    let context_candles = query.context_candles.unwrap_or(DEFAULT_CONTEXT_CANDLES);
+    
+    match backtest_engine.get_backtest_trades(&backtest_id, page, page_size, context_candles).await {
+        Ok(trades) => Json(ApiResult::success(trades)),
+        Err(e) => {
+            error!("Failed to get trades of backtest {}: {}", backtest_id, e);
+            Json(ApiResult::from_error(e))
+        }
+    }
+}
@@ -1,6 +1,9 @@
 use crate::error::{ApiResult, HedgeXError, Result};
-use crate::services::{AppService, AuthService, WebSocketManager, StrategyService};
+use crate::services::{AppService, AuthService, WebSocketManager, StrategyService, PnlStatementService};
 use crate::trading::TradingEngine;
+use crate::trading::analytics_filter::TradeFilter;
+use crate::trading::session::session_date;
+use crate::api::analytics_routes::{analytics_routes, trade_from_row, AnalyticsState};
 use crate::api::middleware::auth_middleware;
 use axum::{
     extract::{Path, Query, Request, State},
@@ -11,6 +14,7 @@ use axum::{
     Router,
 };
 use serde::{Deserialize, Serialize};
+use sqlx::Row;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -29,8 +33,8 @@ pub struct HttpServerState {
 impl HttpServerState {
     pub fn new(app_service: Arc<AppService>) -> Self {
         Self {
+            trading_engines: app_service.get_trading_engines(),
             app_service,
-            trading_engines: Arc::new(RwLock::new(HashMap::new())),
         }
     }
 }
@@ -86,22 +90,28 @@ pub fn create_server(state: HttpServerState) -> Router {
         .route("/api/stocks/selections/:symbol", delete(remove_stock_selection))
         .route("/api/stocks/selections/bulk", post(bulk_add_stock_selections))
         .route("/api/stocks/selections/bulk", delete(bulk_remove_stock_selections))
-        
-        // Analytics endpoints
-        .route("/api/analytics/trades", get(get_trade_history))
-        .route("/api/analytics/performance", get(get_analytics_performance))
         .layer(middleware::from_fn_with_state(
             state.app_service.get_auth_service(),
             auth_middleware,
         ));
 
+    // Analytics routes are shared with the standalone API listener
+    let analytics = analytics_routes(
+        Arc::new(AnalyticsState::new(
+            state.app_service.get_enhanced_database_service(),
+            state.app_service.get_websocket_manager(),
+        )),
+        state.app_service.get_auth_service(),
+    );
+
     // Combine routes
     Router::new()
         .merge(public_routes)
         .merge(protected_routes)
+        .with_state(state)
+        .merge(analytics)
         .layer(cors)
         .layer(TraceLayer::new_for_http())
-        .with_state(state)
 }
 
 // ============================================================================
@@ -440,6 +450,25 @@ struct TradingStatusResponse {
     is_active: bool,
     is_emergency_stop_active: bool,
     last_execution_time_ms: Option<u64>,
+    /// Today's P&L of closed quantity, net of charges
+    realized_pnl: f64,
+    /// P&L of open positions at live prices
+    unrealized_pnl: f64,
+    total_pnl: f64,
+}
+
+/// Today's net realized P&L and the unrealized P&L of open positions
+async fn today_pnl(state: &HttpServerState, user_id: &str) -> (f64, f64) {
+    let filter = TradeFilter::last_days(session_date(chrono::Utc::now()), 1);
+    let prices = state.app_service.get_websocket_manager().get_last_prices().await;
+    let service = PnlStatementService::new(state.app_service.get_enhanced_database_service());
+    match service.pnl_split(user_id, &filter, &prices).await {
+        Ok(split) => (split.net_realized_pnl, split.unrealized_pnl),
+        Err(e) => {
+            error!("Failed to split realized and unrealized P&L: {}", e);
+            (0.0, 0.0)
+        }
+    }
 }
 
 async fn get_trading_status(
@@ -451,6 +480,8 @@ async fn get_trading_status(
         Err(e) => return Ok(Json(ApiResult::from_error(e))),
     };
     
+    let (realized_pnl, unrealized_pnl) = today_pnl(&state, &user_id).await;
+    
     let trading_engines = state.trading_engines.read().await;
     if let Some(trading_engine) = trading_engines.get(&user_id) {
         let is_active = trading_engine.is_trading_active().await;
@@ -462,6 +493,9 @@ async fn get_trading_status(
             is_active,
             is_emergency_stop_active,
             last_execution_time_ms,
+            realized_pnl,
+            unrealized_pnl,
+            total_pnl: realized_pnl + unrealized_pnl,
         };
         
         Ok(Json(ApiResult::success(response)))
@@ -470,6 +504,9 @@ async fn get_trading_status(
             is_active: false,
             is_emergency_stop_active: false,
             last_execution_time_ms: None,
+            realized_pnl,
+            unrealized_pnl,
+            total_pnl: realized_pnl + unrealized_pnl,
         };
         Ok(Json(ApiResult::success(response)))
     }
@@ -518,7 +555,7 @@ async fn get_trades(
     
     let query = "
         SELECT id, user_id, symbol, exchange, order_id, trade_type, quantity, 
-               price, status, executed_at, strategy_id, created_at, updated_at
+               price, status, executed_at, strategy_id, exit_reason, created_at, updated_at
         FROM trades 
         WHERE user_id = ? 
         ORDER BY created_at DESC 
@@ -535,46 +572,7 @@ async fn get_trades(
         .await
     {
         Ok(rows) => {
-            let mut trades = Vec::new();
-            
-            for row in rows {
-                let trade_type_str: String = row.get("trade_type");
-                let status_str: String = row.get("status");
-                let price_f64: f64 = row.get("price");
-                
-                let trade_type = match trade_type_str.as_str() {
-                    "Buy" => crate::models::trading::TradeType::Buy,
-                    "Sell" => crate::models::trading::TradeType::Sell,
-                    _ => continue,
-                };
-                
-                let status = match status_str.as_str() {
-                    "Pending" => crate::models::trading::TradeStatus::Pending,
-                    "Executed" => crate::models::trading::TradeStatus::Executed,
-                    "Cancelled" => crate::models::trading::TradeStatus::Cancelled,
-                    "Failed" => crate::models::trading::TradeStatus::Failed,
-                    "PartiallyFilled" => crate::models::trading::TradeStatus::PartiallyFilled,
-                    _ => continue,
-                };
-                
-                let trade = crate::models::trading::Trade {
-                    id: row.get("id"),
-                    user_id: row.get("user_id"),
-                    symbol: row.get("symbol"),
-                    exchange: row.get("exchange"),
-                    order_id: row.get("order_id"),
-                    trade_type,
-                    quantity: row.get("quantity"),
-                    price: rust_decimal::Decimal::from_f64_retain(price_f64).unwrap_or(rust_decimal::Decimal::ZERO),
-                    status,
-                    executed_at: row.get("executed_at"),
-                    strategy_id: row.get("strategy_id"),
-                    created_at: row.get("created_at"),
-                    updated_at: row.get("updated_at"),
-                };
-                
-                trades.push(trade);
-            }
+            let trades: Vec<crate::models::trading::Trade> = rows.iter().filter_map(trade_from_row).collect();
             
             Ok(Json(ApiResult::success(trades)))
         }
@@ -664,6 +662,11 @@ async fn get_or_create_trading_engine(
         user_id,
     ).await?);
     
+    // Order status changes arrive on the ticker connection as soon as Kite sends them
+    trading_engine.start_order_update_listener(
+        state.app_service.get_websocket_manager().subscribe_to_order_updates()
+    );
+    
     trading_engines.insert(user_id.to_string(), Arc::clone(&trading_engine));
     
     Ok(trading_engine)
@@ -694,6 +697,13 @@ async fn health_check(
         }
     }
     
+    // Check the market data feed
+    let feed = state.app_service.get_websocket_manager().get_feed_health().await;
+    health_info.insert("market_data_feed".to_string(), serde_json::json!({
+        "healthy": !feed.stalled,
+        "feed": feed
+    }));
+    
     // Check active trading engines
     let trading_engines = state.trading_engines.read().await;
     health_info.insert("active_trading_engines".to_string(), 
@@ -712,6 +722,7 @@ async fn health_check(
 async fn get_strategies(
     State(state): State<HttpServerState>,
     headers: HeaderMap,
+    Query(query): Query<crate::services::StrategyListQuery>,
 ) -> Result<Json<ApiResult<Vec<crate::models::trading::StrategyParams>>>, StatusCode> {
     let user_id = match extract_user_id_from_headers(&headers, &state.app_service.get_auth_service()).await {
         Ok(id) => id,
@@ -727,7 +738,7 @@ async fn get_strategies(
     
     match strategy_service {
         Ok(service) => {
-            match service.get_strategies(&user_id).await {
+            match service.query_strategies(&user_id, &query).await {
                 Ok(strategies) => Ok(Json(ApiResult::success(strategies))),
                 Err(e) => {
                     error!("Failed to get strategies: {}", e);
@@ -1293,90 +1304,6 @@ async fn get_symbol_market_data(
 // Analytics Endpoints
 // ============================================================================
 
-async fn get_trade_history(
-    State(state): State<HttpServerState>,
-    headers: HeaderMap,
-    Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<ApiResult<Vec<crate::models::trading::Trade>>>, StatusCode> {
-    // This is the same as get_trades but with different filtering options
-    get_trades(State(state), headers, Query(params)).await
-}
-
-#[derive(Serialize)]
-struct AnalyticsPerformanceResponse {
-    total_trades: i32,
-    profitable_trades: i32,
-    total_pnl: String,
-    win_rate: f64,
-    max_drawdown: String,
-    sharpe_ratio: f64,
-    profit_factor: f64,
-}
-
-async fn get_analytics_performance(
-    State(state): State<HttpServerState>,
-    headers: HeaderMap,
-    Query(params): Query<HashMap<String, String>>,
-) -> Result<Json<ApiResult<AnalyticsPerformanceResponse>>, StatusCode> {
-    let user_id = match extract_user_id_from_headers(&headers, &state.app_service.get_auth_service()).await {
-        Ok(id) => id,
-        Err(e) => return Ok(Json(ApiResult::from_error(e))),
-    };
-    
-    let days = params.get("days")
-        .and_then(|s| s.parse::<i32>().ok())
-        .unwrap_or(30);
-    
-    // Calculate performance metrics from database
-    let query = "
-        SELECT 
-            COUNT(*) as total_trades,
-            COUNT(CASE WHEN price > 0 THEN 1 END) as profitable_trades,
-            SUM(price * quantity * CASE WHEN trade_type = 'Sell' THEN 1 ELSE -1 END) as total_pnl
-        FROM trades 
-        WHERE user_id = ? 
-        AND status = 'Executed'
-        AND created_at >= datetime('now', '-' || ? || ' days')
-    ";
-    
-    let db_pool = state.app_service.get_enhanced_database_service().get_database().get_pool();
-    
-    match sqlx::query(query)
-        .bind(&user_id)
-        .bind(days)
-        .fetch_one(db_pool)
-        .await
-    {
-        Ok(row) => {
-            let total_trades: i32 = row.get("total_trades");
-            let profitable_trades: i32 = row.get("profitable_trades");
-            let total_pnl: f64 = row.get::<Option<f64>, _>("total_pnl").unwrap_or(0.0);
-            
-            let win_rate = if total_trades > 0 {
-                (profitable_trades as f64 / total_trades as f64) * 100.0
-            } else {
-                0.0
-            };
-            
-            let response = AnalyticsPerformanceResponse {
-                total_trades,
-                profitable_trades,
-                total_pnl: total_pnl.to_string(),
-                win_rate,
-                max_drawdown: "0.0".to_string(), // TODO: Calculate actual max drawdown
-                sharpe_ratio: 0.0, // TODO: Calculate actual Sharpe ratio
-                profit_factor: if total_trades > 0 { total_pnl / total_trades as f64 } else { 0.0 },
-            };
-            
-            Ok(Json(ApiResult::success(response)))
-        }
-        Err(e) => {
-            error!("Failed to get analytics performance: {}", e);
-            Ok(Json(ApiResult::from_error(HedgeXError::DatabaseError(e))))
-        }
-    }
-}
-
 #[derive(Serialize)]
 struct LogEntry {
     timestamp: String,
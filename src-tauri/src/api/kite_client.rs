@@ -1,18 +1,21 @@
+use crate::api::rate_limiter::{KiteRateLimiter, RateCategory, RateLimitPolicy};
 use crate::error::{HedgeXError, Result};
+use crate::utils::error_recovery::{ErrorRecoveryManager, ExponentialBackoff};
 use crate::models::kite::{
     KiteApiCredentials, KiteOrderRequest, KiteOrderResponse, KitePosition, 
-    KiteOrder, KiteHolding, KiteMarginResponse, KiteProfile, KiteQuote,
+    KiteOrder, KiteTrade, KiteHolding, KiteMarginResponse, KiteProfile, KiteQuote,
     KiteHistoricalDataParams, KiteOHLCV, KiteInstrument, KiteOrderStatus,
     KiteOrderType, KiteOrderVariety, KiteExchange, KiteProduct, KiteValidity,
-    KiteTransactionType, KiteTriggerType, KiteDiscloseQuantity,
+    KiteTransactionType, KiteTriggerType, KiteDiscloseQuantity, KiteGtt, KiteGttRequest,
+    KiteGttResponse, KiteMarginOrder, KiteBasketMargin, KiteOhlcQuote, KiteLtpQuote,
 };
 use reqwest::{Client, StatusCode, header};
 use serde::{Serialize, Deserialize};
 use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
-use tokio::sync::{Mutex, RwLock};
+use std::time::Duration;
+use tokio::sync::RwLock;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn, instrument, Span, span, Level};
 use sha2::{Sha256, Digest};
@@ -34,6 +37,9 @@ const KITE_API_URL: &str = "https://api.kite.trade";
 /// Base URL for Kite Connect API
 const KITE_CONNECT_URL: &str = "https://kite.zerodha.com/connect";
 
+/// Circuit breaker name of the Kite REST API
+pub const KITE_API_SERVICE: &str = "kite_api";
+
 /// Trait for Kite API client operations
 #[async_trait]
 pub trait KiteApiClient: Send + Sync {
@@ -58,6 +64,9 @@ pub trait KiteApiClient: Send + Sync {
     /// Get account margins
     async fn get_margins(&self) -> Result<KiteMarginResponse>;
     
+    /// Get the combined margin of a basket of orders, optionally netted against open positions
+    async fn get_basket_margins(&self, orders: &[KiteMarginOrder], consider_positions: bool) -> Result<KiteBasketMargin>;
+    
     /// Get order book
     async fn get_orders(&self) -> Result<Vec<KiteOrder>>;
     
@@ -65,7 +74,7 @@ pub trait KiteApiClient: Send + Sync {
     async fn get_order_history(&self, order_id: &str) -> Result<Vec<KiteOrder>>;
     
     /// Get trades
-    async fn get_trades(&self) -> Result<Vec<KiteOrder>>;
+    async fn get_trades(&self) -> Result<Vec<KiteTrade>>;
     
     /// Get positions
     async fn get_positions(&self) -> Result<KitePosition>;
@@ -79,6 +88,12 @@ pub trait KiteApiClient: Send + Sync {
     /// Get quotes
     async fn get_quote(&self, instruments: &[String]) -> Result<HashMap<String, KiteQuote>>;
     
+    /// Get OHLC and last price quotes
+    async fn get_ohlc(&self, instruments: &[String]) -> Result<HashMap<String, KiteOhlcQuote>>;
+    
+    /// Get last price quotes
+    async fn get_ltp(&self, instruments: &[String]) -> Result<HashMap<String, KiteLtpQuote>>;
+    
     /// Get historical data
     async fn get_historical_data(&self, params: KiteHistoricalDataParams) -> Result<Vec<KiteOHLCV>>;
     
@@ -90,6 +105,37 @@ pub trait KiteApiClient: Send + Sync {
     
     /// Cancel order
     async fn cancel_order(&self, order_id: &str, variety: KiteOrderVariety) -> Result<KiteOrderResponse>;
+    
+    /// Get GTT triggers
+    async fn get_gtts(&self) -> Result<Vec<KiteGtt>>;
+    
+    /// Get a GTT trigger
+    async fn get_gtt(&self, trigger_id: u64) -> Result<KiteGtt>;
+    
+    /// Place GTT trigger
+    async fn place_gtt(&self, gtt: KiteGttRequest) -> Result<KiteGttResponse>;
+    
+    /// Modify GTT trigger
+    async fn modify_gtt(&self, trigger_id: u64, gtt: KiteGttRequest) -> Result<KiteGttResponse>;
+    
+    /// Delete GTT trigger
+    async fn delete_gtt(&self, trigger_id: u64) -> Result<KiteGttResponse>;
+}
+
+/// A failed request attempt and whether it is worth retrying
+struct RequestFailure {
+    error: HedgeXError,
+    transient: bool,
+}
+
+impl RequestFailure {
+    fn permanent(error: HedgeXError) -> Self {
+        Self { error, transient: false }
+    }
+    
+    fn transient(error: HedgeXError) -> Self {
+        Self { error, transient: true }
+    }
 }
 
 /// Kite API client implementation
@@ -103,11 +149,11 @@ pub struct KiteClient {
     /// Access token for authenticated requests
     access_token: RwLock<Option<String>>,
     
-    /// Last API call timestamp for rate limiting
-    last_api_call: Mutex<Instant>,
+    /// Keeps each endpoint group under Kite's rate limits
+    rate_limiter: KiteRateLimiter,
     
-    /// Minimum time between API calls in milliseconds
-    rate_limit_ms: u64,
+    /// Shared circuit breakers and backoff, once the app has set them up
+    error_recovery: RwLock<Option<Arc<ErrorRecoveryManager>>>,
     
     /// Base URL for API requests
     base_url: String,
@@ -116,11 +162,11 @@ pub struct KiteClient {
 impl KiteClient {
     /// Create a new Kite API client
     pub fn new(api_key: &str) -> Result<Self> {
-        Self::new_with_config(api_key, KITE_API_URL, 200)
+        Self::new_with_config(api_key, KITE_API_URL, RateLimitPolicy::default())
     }
     
     /// Create a new Kite API client with custom configuration
-    pub fn new_with_config(api_key: &str, base_url: &str, rate_limit_ms: u64) -> Result<Self> {
+    pub fn new_with_config(api_key: &str, base_url: &str, rate_limit_policy: RateLimitPolicy) -> Result<Self> {
         // Create HTTP client with appropriate timeouts and headers
         let client = Client::builder()
             .timeout(Duration::from_secs(30))
@@ -134,12 +180,17 @@ impl KiteClient {
             client,
             api_key: api_key.to_string(),
             access_token: RwLock::new(None),
-            last_api_call: Mutex::new(Instant::now()),
-            rate_limit_ms,
+            rate_limiter: KiteRateLimiter::new(rate_limit_policy),
+            error_recovery: RwLock::new(None),
             base_url: base_url.to_string(),
         })
     }
     
+    /// Route requests through the shared circuit breaker and backoff
+    pub async fn set_error_recovery(&self, error_recovery: Arc<ErrorRecoveryManager>) {
+        *self.error_recovery.write().await = Some(error_recovery);
+    }
+    
     /// Create API request URL
     fn create_url(&self, endpoint: &str) -> String {
         format!("{}{}", self.base_url, endpoint)
@@ -180,35 +231,72 @@ impl KiteClient {
         Ok(headers)
     }
     
-    /// Apply rate limiting to API calls
-    async fn apply_rate_limit(&self) {
-        let mut last_call = self.last_api_call.lock().await;
-        let elapsed = last_call.elapsed();
-        let min_interval = Duration::from_millis(self.rate_limit_ms);
-        
-        // If we've made a request too recently, sleep for the remaining time
-        if elapsed < min_interval {
-            let sleep_duration = min_interval - elapsed;
-            debug!("Rate limiting: sleeping for {}ms", sleep_duration.as_millis());
-            sleep(sleep_duration).await;
+    /// Make API request with retry and backoff
+    ///
+    /// Once error recovery is set, transient failures retry through the shared
+    /// exponential backoff and count towards the Kite API circuit breaker.
+    #[instrument(skip(self, body), fields(endpoint = %endpoint, method = %method))]
+    async fn make_request<T, U>(&self, method: &str, endpoint: &str, body: Option<&T>) -> Result<U>
+    where
+        T: Serialize + Send + Sync,
+        U: for<'de> Deserialize<'de> + Send + Sync,
+    {
+        let error_recovery = self.error_recovery.read().await.clone();
+        if let Some(error_recovery) = error_recovery {
+            // Only transient failures are retried or trip the circuit; the rest
+            // are the caller's to handle and pass straight through
+            return error_recovery.execute_with_recovery(
+                KITE_API_SERVICE,
+                || async {
+                    match self.send_request(method, endpoint, body).await {
+                        Ok(data) => Ok(Ok(data)),
+                        Err(failure) if failure.transient => Err(failure.error),
+                        Err(failure) => Ok(Err(failure.error)),
+                    }
+                },
+                ExponentialBackoff::default_api(),
+            ).await?;
         }
         
-        // Update last call time
-        *last_call = Instant::now();
+        // Execute request with retry and exponential backoff
+        let mut attempt = 0;
+        loop {
+            let failure = match self.send_request(method, endpoint, body).await {
+                Ok(data) => return Ok(data),
+                Err(failure) if !failure.transient => return Err(failure.error),
+                Err(failure) => failure,
+            };
+            
+            // Increment attempt counter
+            attempt += 1;
+            
+            // If this was the last attempt, return the last error
+            if attempt >= MAX_RETRY_ATTEMPTS {
+                error!("Max retry attempts reached for API request");
+                return Err(failure.error);
+            }
+            
+            // Calculate backoff duration with jitter
+            let backoff_ms = self.calculate_backoff_ms(attempt);
+            warn!("Retrying API request in {}ms (attempt {}/{})", backoff_ms, attempt + 1, MAX_RETRY_ATTEMPTS);
+            
+            // Sleep for backoff duration
+            sleep(Duration::from_millis(backoff_ms)).await;
+        }
     }
     
-    /// Make API request with retry and backoff
-    #[instrument(skip(self, body), fields(endpoint = %endpoint, method = %method))]
-    async fn make_request<T, U>(&self, method: &str, endpoint: &str, body: Option<&T>) -> Result<U>
+    /// Send an API request once, telling transient failures apart from the rest
+    async fn send_request<T, U>(&self, method: &str, endpoint: &str, body: Option<&T>) -> std::result::Result<U, RequestFailure>
     where
         T: Serialize + Send + Sync,
         U: for<'de> Deserialize<'de> + Send + Sync,
     {
-        // Apply rate limiting
-        self.apply_rate_limit().await;
+        // Wait for a slot within the endpoint's rate limit, or give up
+        self.rate_limiter.acquire(RateCategory::of(method, endpoint)).await
+            .map_err(RequestFailure::permanent)?;
         
         let url = self.create_url(endpoint);
-        let headers = self.create_headers().await?;
+        let headers = self.create_headers().await.map_err(RequestFailure::permanent)?;
         
         // Create request builder based on method
         let mut request_builder = match method {
@@ -216,7 +304,9 @@ impl KiteClient {
             "POST" => self.client.post(&url),
             "PUT" => self.client.put(&url),
             "DELETE" => self.client.delete(&url),
-            _ => return Err(HedgeXError::ValidationError(format!("Unsupported HTTP method: {}", method))),
+            _ => return Err(RequestFailure::permanent(
+                HedgeXError::ValidationError(format!("Unsupported HTTP method: {}", method))
+            )),
         };
         
         // Add headers and body
@@ -225,106 +315,84 @@ impl KiteClient {
             request_builder = request_builder.json(data);
         }
         
-        // Execute request with retry and exponential backoff
-        let mut attempt = 0;
-        let mut last_error = None;
-        
-        while attempt < MAX_RETRY_ATTEMPTS {
-            match request_builder.try_clone() {
-                Some(builder) => {
-                    // Execute request
-                    match builder.send().await {
-                        Ok(response) => {
-                            let status = response.status();
-                            
-                            // Handle response based on status code
-                            if status.is_success() {
-                                // Parse successful response
-                                match response.json::<KiteApiResponse<U>>().await {
-                                    Ok(api_response) => {
-                                        if api_response.status == "success" {
-                                            debug!("API request successful: {}", endpoint);
-                                            return Ok(api_response.data);
-                                        } else {
-                                            let error_msg = format!(
-                                                "API error: {} ({})",
-                                                api_response.error_message.clone().unwrap_or_else(|| "Unknown error".to_string()),
-                                                api_response.error_type.clone().unwrap_or_else(|| "unknown".to_string())
-                                            );
-                                            error!("{}", error_msg);
-                                            return Err(self.map_api_error(&api_response));
-                                        }
-                                    }
-                                    Err(e) => {
-                                        error!("Failed to parse API response: {}", e);
-                                        last_error = Some(HedgeXError::NetworkError(e));
-                                    }
-                                }
-                            } else {
-                                // Handle error response
-                                match response.json::<KiteApiResponse<Value>>().await {
-                                    Ok(api_response) => {
-                                        let error_msg = format!(
-                                            "API error ({}): {} ({})",
-                                            status.as_u16(),
-                                            api_response.error_message.clone().unwrap_or_else(|| "Unknown error".to_string()),
-                                            api_response.error_type.clone().unwrap_or_else(|| "unknown".to_string())
-                                        );
-                                        error!("{}", error_msg);
-                                        
-                                        // Check if we should retry based on status code
-                                        if Self::should_retry(status) {
-                                            last_error = Some(self.map_api_error(&api_response));
-                                        } else {
-                                            return Err(self.map_api_error(&api_response));
-                                        }
-                                    }
-                                    Err(e) => {
-                                        error!("Failed to parse error response: {}", e);
-                                        last_error = Some(HedgeXError::NetworkError(e));
-                                    }
-                                }
-                            }
-                        }
-                        Err(e) => {
-                            error!("Request failed: {}", e);
-                            
-                            // Check if we should retry based on error
-                            if e.is_timeout() || e.is_connect() {
-                                last_error = Some(HedgeXError::NetworkError(e));
-                            } else {
-                                return Err(HedgeXError::NetworkError(e));
-                            }
-                        }
-                    }
+        let response = match request_builder.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                error!("Request failed: {}", e);
+                
+                // Timeouts and refused connections are worth another try
+                let transient = e.is_timeout() || e.is_connect();
+                return Err(RequestFailure { error: HedgeXError::NetworkError(e), transient });
+            }
+        };
+        
+        let status = response.status();
+        if status.is_success() {
+            // Parse successful response
+            match response.json::<KiteApiResponse<U>>().await {
+                Ok(api_response) if api_response.status == "success" => {
+                    debug!("API request successful: {}", endpoint);
+                    Ok(api_response.data)
+                }
+                Ok(api_response) => {
+                    error!(
+                        "API error: {} ({})",
+                        api_response.error_message.as_deref().unwrap_or("Unknown error"),
+                        api_response.error_type.as_deref().unwrap_or("unknown")
+                    );
+                    Err(RequestFailure::permanent(self.map_api_error(&api_response)))
                 }
-                None => {
-                    error!("Failed to clone request builder");
-                    return Err(HedgeXError::InternalError("Failed to clone request builder".to_string()));
+                Err(e) => {
+                    error!("Failed to parse API response: {}", e);
+                    Err(RequestFailure::transient(HedgeXError::NetworkError(e)))
                 }
             }
-            
-            // Increment attempt counter
-            attempt += 1;
-            
-            // If this was the last attempt, return the last error
-            if attempt >= MAX_RETRY_ATTEMPTS {
-                error!("Max retry attempts reached for API request");
-                return Err(last_error.unwrap_or_else(|| {
-                    HedgeXError::ApiError("Max retry attempts reached".to_string())
-                }));
+        } else {
+            // Handle error response
+            match response.json::<KiteApiResponse<Value>>().await {
+                Ok(api_response) => {
+                    error!(
+                        "API error ({}): {} ({})",
+                        status.as_u16(),
+                        api_response.error_message.as_deref().unwrap_or("Unknown error"),
+                        api_response.error_type.as_deref().unwrap_or("unknown")
+                    );
+                    Err(RequestFailure {
+                        error: self.map_api_error(&api_response),
+                        transient: Self::should_retry(status),
+                    })
+                }
+                Err(e) => {
+                    error!("Failed to parse error response: {}", e);
+                    Err(RequestFailure::transient(HedgeXError::NetworkError(e)))
+                }
             }
+        }
+    }
+    
+    /// Make API request that returns a plain-text body (e.g. CSV dumps)
+    async fn make_raw_request(&self, endpoint: &str) -> Result<String> {
+        // Wait for a slot within the endpoint's rate limit, or give up
+        self.rate_limiter.acquire(RateCategory::of("GET", endpoint)).await?;
+        
+        let url = self.create_url(endpoint);
+        let headers = self.create_headers().await?;
+        
+        let response = self.client.get(&url)
+            .headers(headers)
+            .send()
+            .await
+            .map_err(HedgeXError::NetworkError)?;
             
-            // Calculate backoff duration with jitter
-            let backoff_ms = self.calculate_backoff_ms(attempt);
-            warn!("Retrying API request in {}ms (attempt {}/{})", backoff_ms, attempt + 1, MAX_RETRY_ATTEMPTS);
-            
-            // Sleep for backoff duration
-            sleep(Duration::from_millis(backoff_ms)).await;
+        let status = response.status();
+        if !status.is_success() {
+            return match response.json::<KiteApiResponse<Value>>().await {
+                Ok(api_response) => Err(self.map_api_error(&api_response)),
+                Err(_) => Err(HedgeXError::ApiError(format!("API error ({})", status.as_u16()))),
+            };
         }
         
-        // This should never be reached due to the return in the loop
-        Err(HedgeXError::InternalError("Unexpected error in API request".to_string()))
+        response.text().await.map_err(HedgeXError::NetworkError)
     }
     
     /// Calculate backoff duration with exponential backoff and jitter
@@ -357,17 +425,22 @@ impl KiteClient {
     }
     
     /// Map API error response to HedgeXError
+    ///
+    /// Kite's `error_type` names the exception class; the ones a user can act
+    /// on get their own variant so the message says what to do next.
     fn map_api_error<T>(&self, response: &KiteApiResponse<T>) -> HedgeXError {
         let error_type = response.error_type.as_deref().unwrap_or("unknown");
         let error_message = response.error_message.as_deref().unwrap_or("Unknown error");
         
         match error_type {
-            "TokenException" => HedgeXError::AuthenticationError(error_message.to_string()),
+            "TokenException" => HedgeXError::TokenExpired(error_message.to_string()),
             "PermissionException" => HedgeXError::PermissionError(error_message.to_string()),
             "InputException" => HedgeXError::ValidationError(error_message.to_string()),
-            "OrderException" => HedgeXError::TradingError(error_message.to_string()),
+            "MarginException" => HedgeXError::InsufficientMargin(error_message.to_string()),
+            "HoldingException" => HedgeXError::InsufficientHoldings(error_message.to_string()),
+            "OrderException" => HedgeXError::OrderRejected(error_message.to_string()),
             "DataException" => HedgeXError::DataIntegrityError(error_message.to_string()),
-            "NetworkException" => HedgeXError::ExternalServiceError(error_message.to_string()),
+            "NetworkException" => HedgeXError::BrokerUnavailable(error_message.to_string()),
             "GeneralException" => HedgeXError::ApiError(error_message.to_string()),
             "TooManyRequestsException" => HedgeXError::RateLimitError(error_message.to_string()),
             _ => HedgeXError::ApiError(format!("{}: {}", error_type, error_message)),
@@ -389,6 +462,21 @@ impl KiteClient {
     }
 }
 
+/// Quote endpoint with one `i` parameter per instrument, as Kite expects
+///
+/// Instruments are "EXCHANGE:SYMBOL" keys or instrument tokens; index names
+/// such as "NSE:NIFTY 50" contain spaces, so each is URL-encoded.
+fn quote_endpoint(path: &str, instruments: &[String]) -> Result<String> {
+    if instruments.is_empty() {
+        return Err(HedgeXError::ValidationError("No instruments provided".to_string()));
+    }
+    
+    let query = url::form_urlencoded::Serializer::new(String::new())
+        .extend_pairs(instruments.iter().map(|instrument| ("i", instrument)))
+        .finish();
+    Ok(format!("{}?{}", path, query))
+}
+
 #[async_trait]
 impl KiteApiClient for KiteClient {
     async fn set_access_token(&self, access_token: String) {
@@ -452,6 +540,11 @@ impl KiteApiClient for KiteClient {
         self.make_request("GET", "/user/margins", None::<&()>).await
     }
     
+    async fn get_basket_margins(&self, orders: &[KiteMarginOrder], consider_positions: bool) -> Result<KiteBasketMargin> {
+        let endpoint = format!("/margins/basket?consider_positions={}&mode=compact", consider_positions);
+        self.make_request("POST", &endpoint, Some(&orders)).await
+    }
+    
     async fn get_orders(&self) -> Result<Vec<KiteOrder>> {
         self.make_request("GET", "/orders", None::<&()>).await
     }
@@ -461,7 +554,7 @@ impl KiteApiClient for KiteClient {
         self.make_request("GET", &endpoint, None::<&()>).await
     }
     
-    async fn get_trades(&self) -> Result<Vec<KiteOrder>> {
+    async fn get_trades(&self) -> Result<Vec<KiteTrade>> {
         self.make_request("GET", "/trades", None::<&()>).await
     }
     
@@ -479,17 +572,23 @@ impl KiteApiClient for KiteClient {
             None => "/instruments".to_string(),
         };
         
-        self.make_request("GET", &endpoint, None::<&()>).await
+        // The instruments dump is served as gzipped CSV rather than JSON
+        let body = self.make_raw_request(&endpoint).await?;
+        parse_instruments_csv(&body)
     }
     
     async fn get_quote(&self, instruments: &[String]) -> Result<HashMap<String, KiteQuote>> {
-        if instruments.is_empty() {
-            return Err(HedgeXError::ValidationError("No instruments provided".to_string()));
-        }
-        
-        let instruments_str = instruments.join(",");
-        let endpoint = format!("/quote?i={}", instruments_str);
-        
+        let endpoint = quote_endpoint("/quote", instruments)?;
+        self.make_request("GET", &endpoint, None::<&()>).await
+    }
+    
+    async fn get_ohlc(&self, instruments: &[String]) -> Result<HashMap<String, KiteOhlcQuote>> {
+        let endpoint = quote_endpoint("/quote/ohlc", instruments)?;
+        self.make_request("GET", &endpoint, None::<&()>).await
+    }
+    
+    async fn get_ltp(&self, instruments: &[String]) -> Result<HashMap<String, KiteLtpQuote>> {
+        let endpoint = quote_endpoint("/quote/ltp", instruments)?;
         self.make_request("GET", &endpoint, None::<&()>).await
     }
     
@@ -516,6 +615,123 @@ impl KiteApiClient for KiteClient {
         let endpoint = format!("/orders/{}/{}", variety, order_id);
         self.make_request("DELETE", &endpoint, None::<&()>).await
     }
+    
+    async fn get_gtts(&self) -> Result<Vec<KiteGtt>> {
+        self.make_request("GET", "/gtt/triggers", None::<&()>).await
+    }
+    
+    async fn get_gtt(&self, trigger_id: u64) -> Result<KiteGtt> {
+        let endpoint = format!("/gtt/triggers/{}", trigger_id);
+        self.make_request("GET", &endpoint, None::<&()>).await
+    }
+    
+    async fn place_gtt(&self, gtt: KiteGttRequest) -> Result<KiteGttResponse> {
+        self.make_request("POST", "/gtt/triggers", Some(&gtt)).await
+    }
+    
+    async fn modify_gtt(&self, trigger_id: u64, gtt: KiteGttRequest) -> Result<KiteGttResponse> {
+        let endpoint = format!("/gtt/triggers/{}", trigger_id);
+        self.make_request("PUT", &endpoint, Some(&gtt)).await
+    }
+    
+    async fn delete_gtt(&self, trigger_id: u64) -> Result<KiteGttResponse> {
+        let endpoint = format!("/gtt/triggers/{}", trigger_id);
+        self.make_request("DELETE", &endpoint, None::<&()>).await
+    }
+}
+
+/// Parse the Kite instruments CSV dump
+///
+/// Rows with an exchange this client doesn't know about are skipped.
+pub fn parse_instruments_csv(body: &str) -> Result<Vec<KiteInstrument>> {
+    let mut lines = body.lines();
+    
+    let header = lines.next()
+        .ok_or_else(|| HedgeXError::ValidationError("Instruments dump is empty".to_string()))?;
+    let columns = split_csv_line(header);
+    let column = |name: &str| -> Result<usize> {
+        columns.iter()
+            .position(|c| c == name)
+            .ok_or_else(|| HedgeXError::ValidationError(format!("Instruments dump is missing column: {}", name)))
+    };
+    
+    let token_idx = column("instrument_token")?;
+    let exchange_token_idx = column("exchange_token")?;
+    let symbol_idx = column("tradingsymbol")?;
+    let name_idx = column("name")?;
+    let last_price_idx = column("last_price")?;
+    let expiry_idx = column("expiry")?;
+    let strike_idx = column("strike")?;
+    let tick_size_idx = column("tick_size")?;
+    let lot_size_idx = column("lot_size")?;
+    let instrument_type_idx = column("instrument_type")?;
+    let segment_idx = column("segment")?;
+    let exchange_idx = column("exchange")?;
+    
+    let mut instruments = Vec::new();
+    
+    for (line_no, line) in lines.enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        
+        let fields = split_csv_line(line);
+        if fields.len() < columns.len() {
+            warn!("Skipping malformed instruments row {}", line_no + 2);
+            continue;
+        }
+        
+        let exchange = match KiteExchange::from_str(&fields[exchange_idx]) {
+            Ok(exchange) => exchange,
+            Err(_) => continue,
+        };
+        
+        let parse_err = |field: &str| {
+            HedgeXError::ValidationError(format!("Invalid {} on instruments row {}", field, line_no + 2))
+        };
+        
+        let strike = fields[strike_idx].parse::<f64>().unwrap_or(0.0);
+        
+        instruments.push(KiteInstrument {
+            instrument_token: fields[token_idx].parse().map_err(|_| parse_err("instrument_token"))?,
+            exchange_token: fields[exchange_token_idx].parse().map_err(|_| parse_err("exchange_token"))?,
+            tradingsymbol: fields[symbol_idx].clone(),
+            name: fields[name_idx].clone(),
+            last_price: fields[last_price_idx].parse().unwrap_or(0.0),
+            expiry: chrono::NaiveDate::parse_from_str(&fields[expiry_idx], "%Y-%m-%d").ok(),
+            strike: if strike > 0.0 { Some(strike) } else { None },
+            tick_size: fields[tick_size_idx].parse().unwrap_or(0.05),
+            lot_size: fields[lot_size_idx].parse().unwrap_or(1),
+            instrument_type: fields[instrument_type_idx].clone(),
+            segment: fields[segment_idx].clone(),
+            exchange,
+        });
+    }
+    
+    Ok(instruments)
+}
+
+/// Split a CSV line, honouring double-quoted fields
+fn split_csv_line(line: &str) -> Vec<String> {
+    let mut fields = Vec::new();
+    let mut current = String::new();
+    let mut in_quotes = false;
+    let mut chars = line.trim_end_matches('\r').chars().peekable();
+    
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                current.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => fields.push(std::mem::take(&mut current)),
+            _ => current.push(c),
+        }
+    }
+    fields.push(current);
+    
+    fields
 }
 
 /// Kite API response structure
@@ -532,6 +748,7 @@ struct KiteApiResponse<T> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::kite::{KiteGttStatus, KiteGttType};
     use mockito::{mock, server_url};
     use serde_json::json;
     
@@ -614,10 +831,38 @@ mod tests {
         
         let error = client.map_api_error(&response);
         match error {
-            HedgeXError::AuthenticationError(msg) => assert_eq!(msg, "Invalid token"),
-            _ => panic!("Expected AuthenticationError"),
+            HedgeXError::TokenExpired(msg) => assert_eq!(msg, "Invalid token"),
+            _ => panic!("Expected TokenExpired"),
         }
         
+        // Margin and holding shortfalls tell the user what to fix
+        let response = KiteApiResponse::<()> {
+            status: "error".to_string(),
+            data: (),
+            error_type: Some("MarginException".to_string()),
+            error_message: Some("Insufficient funds. Required margin is 1500.00".to_string()),
+        };
+        
+        let error = client.map_api_error(&response);
+        assert!(matches!(error, HedgeXError::InsufficientMargin(_)));
+        assert!(error.to_string().contains("add funds or reduce the order size"));
+        
+        let response = KiteApiResponse::<()> {
+            status: "error".to_string(),
+            data: (),
+            error_type: Some("HoldingException".to_string()),
+            error_message: Some("Insufficient holdings".to_string()),
+        };
+        assert!(matches!(client.map_api_error(&response), HedgeXError::InsufficientHoldings(_)));
+        
+        let response = KiteApiResponse::<()> {
+            status: "error".to_string(),
+            data: (),
+            error_type: Some("NetworkException".to_string()),
+            error_message: Some("Exchange unreachable".to_string()),
+        };
+        assert!(matches!(client.map_api_error(&response), HedgeXError::BrokerUnavailable(_)));
+        
         // Test rate limit error
         let response = KiteApiResponse::<()> {
             status: "error".to_string(),
@@ -633,13 +878,34 @@ mod tests {
         }
     }
     
+    #[test]
+    fn test_parse_instruments_csv() {
+        let csv = "instrument_token,exchange_token,tradingsymbol,name,last_price,expiry,strike,tick_size,lot_size,instrument_type,segment,exchange\n\
+            408065,1594,INFY,\"INFOSYS, LTD\",0,,0,0.05,1,EQ,NSE,NSE\n\
+            12345678,48225,NIFTY24JAN21000CE,NIFTY,0,2024-01-25,21000,0.05,50,CE,NFO-OPT,NFO\n\
+            999,1,GOLD,GOLD,0,,0,1,1,FUT,UNKNOWN,XYZ\n";
+        
+        let instruments = parse_instruments_csv(csv).unwrap();
+        assert_eq!(instruments.len(), 2);
+        
+        assert_eq!(instruments[0].instrument_token, 408065);
+        assert_eq!(instruments[0].name, "INFOSYS, LTD");
+        assert_eq!(instruments[0].expiry, None);
+        assert_eq!(instruments[0].strike, None);
+        
+        assert_eq!(instruments[1].exchange, KiteExchange::NFO);
+        assert_eq!(instruments[1].strike, Some(21000.0));
+        assert_eq!(instruments[1].lot_size, 50);
+        assert!(instruments[1].expiry.is_some());
+    }
+    
     #[tokio::test]
     async fn test_generate_session() {
         let mut server = mockito::Server::new();
         let mock_url = server.url();
         
         // Create client with mock server URL
-        let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+        let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
         
         // Setup mock response
         let _m = server.mock("POST", "/session/token")
@@ -665,7 +931,7 @@ mod tests {
         let mock_url = server.url();
         
         // Create client with mock server URL
-        let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+        let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
         
         // Set access token
         client.set_access_token("test_access_token".to_string()).await;
@@ -693,7 +959,7 @@ mod tests {
         let mock_url = server.url();
         
         // Create client with mock server URL
-        let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+        let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
         
         // Set access token
         client.set_access_token("test_access_token".to_string()).await;
@@ -722,7 +988,7 @@ mod tests {
         let mock_url = server.url();
         
         // Create client with mock server URL
-        let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+        let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
         
         // Set access token
         client.set_access_token("test_access_token".to_string()).await;
@@ -752,7 +1018,7 @@ mod tests {
         let mock_url = server.url();
         
         // Create client with mock server URL
-        let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+        let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
         
         // Set access token
         client.set_access_token("test_access_token".to_string()).await;
@@ -797,7 +1063,7 @@ mod tests {
         let mock_url = server.url();
         
         // Create client with mock server URL
-        let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+        let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
         
         // Set access token
         client.set_access_token("test_access_token".to_string()).await;
@@ -815,10 +1081,10 @@ mod tests {
         // Verify error
         assert!(result.is_err());
         match result.unwrap_err() {
-            HedgeXError::AuthenticationError(msg) => {
+            HedgeXError::TokenExpired(msg) => {
                 assert_eq!(msg, "Invalid API key or access token");
             }
-            err => panic!("Expected AuthenticationError, got {:?}", err),
+            err => panic!("Expected TokenExpired, got {:?}", err),
         }
     }
     
@@ -828,7 +1094,7 @@ mod tests {
         let mock_url = server.url();
         
         // Create client with mock server URL
-        let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+        let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
         
         // Set access token
         client.set_access_token("test_access_token".to_string()).await;
@@ -852,4 +1118,115 @@ mod tests {
             err => panic!("Expected RateLimitError, got {:?}", err),
         }
     }
+    
+    #[tokio::test]
+    async fn test_get_gtts() {
+        let mut server = mockito::Server::new();
+        let mock_url = server.url();
+        
+        // Create client with mock server URL
+        let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
+        
+        // Set access token
+        client.set_access_token("test_access_token".to_string()).await;
+        
+        // Setup mock response with a two-leg exit on a long position
+        let _m = server.mock("GET", "/gtt/triggers")
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status":"success","data":[{"id":112127,"type":"two-leg","status":"active","created_at":null,"updated_at":null,"expires_at":null,"condition":{"exchange":"NSE","tradingsymbol":"INFY","trigger_values":[1400.0,1600.0],"last_price":1500.0},"orders":[{"exchange":"NSE","tradingsymbol":"INFY","transaction_type":"SELL","quantity":10,"order_type":"LIMIT","product":"CNC","price":1399.0},{"exchange":"NSE","tradingsymbol":"INFY","transaction_type":"SELL","quantity":10,"order_type":"LIMIT","product":"CNC","price":1601.0}]}]}"#)
+            .create();
+        
+        // Call get_gtts
+        let gtts = client.get_gtts().await.unwrap();
+        
+        // Verify result
+        assert_eq!(gtts.len(), 1);
+        assert_eq!(gtts[0].id, 112127);
+        assert_eq!(gtts[0].trigger_type, KiteGttType::TwoLeg);
+        assert_eq!(gtts[0].status, KiteGttStatus::Active);
+        assert_eq!(gtts[0].condition.trigger_values, vec![1400.0, 1600.0]);
+        assert_eq!(gtts[0].orders[1].transaction_type, KiteTransactionType::Sell);
+    }
+    
+    #[tokio::test]
+    async fn test_get_basket_margins() {
+        let mut server = mockito::Server::new();
+        let mock_url = server.url();
+        
+        // Create client with mock server URL
+        let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
+        
+        // Set access token
+        client.set_access_token("test_access_token".to_string()).await;
+        
+        // Setup mock response for a short straddle hedged with a long call
+        let _m = server.mock("POST", "/margins/basket")
+            .match_query(mockito::Matcher::UrlEncoded("consider_positions".to_string(), "true".to_string()))
+            .match_body(mockito::Matcher::PartialJsonString(r#"[{"tradingsymbol":"NIFTY24JAN21000CE","transaction_type":"SELL"}]"#.to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status":"success","data":{"initial":{"type":"","tradingsymbol":"","exchange":"","span":240000.0,"exposure":60000.0,"option_premium":1500.0,"additional":0,"bo":0,"cash":0,"var":0,"pnl":{"realised":0,"unrealised":0},"leverage":0,"charges":{},"total":301500.0},"final":{"type":"","tradingsymbol":"","exchange":"","span":40000.0,"exposure":60000.0,"option_premium":1500.0,"additional":0,"bo":0,"cash":0,"var":0,"pnl":{"realised":0,"unrealised":0},"leverage":0,"charges":{},"total":101500.0},"orders":[{"type":"equity","tradingsymbol":"NIFTY24JAN21000CE","exchange":"NFO","span":120000.0,"exposure":30000.0,"option_premium":0,"total":150000.0}]}}"#)
+            .create();
+        
+        let order = KiteMarginOrder {
+            exchange: KiteExchange::NFO,
+            tradingsymbol: "NIFTY24JAN21000CE".to_string(),
+            transaction_type: KiteTransactionType::Sell,
+            variety: KiteOrderVariety::Regular,
+            product: KiteProduct::NRML,
+            order_type: KiteOrderType::Market,
+            quantity: 50,
+            price: 0.0,
+            trigger_price: 0.0,
+        };
+        
+        // Call get_basket_margins
+        let margins = client.get_basket_margins(&[order], true).await.unwrap();
+        
+        // Verify the hedge benefit shows in the final margin
+        assert_eq!(margins.initial.total, 301500.0);
+        assert_eq!(margins.final_margin.total, 101500.0);
+        assert_eq!(margins.orders.len(), 1);
+        assert_eq!(margins.orders[0].tradingsymbol, "NIFTY24JAN21000CE");
+    }
+    
+    #[test]
+    fn test_quote_endpoint_repeats_instruments() {
+        let instruments = vec!["NSE:INFY".to_string(), "NSE:NIFTY 50".to_string(), "408065".to_string()];
+        assert_eq!(
+            quote_endpoint("/quote/ltp", &instruments).unwrap(),
+            "/quote/ltp?i=NSE%3AINFY&i=NSE%3ANIFTY+50&i=408065"
+        );
+        assert!(quote_endpoint("/quote", &[]).is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_get_ohlc() {
+        let mut server = mockito::Server::new();
+        let mock_url = server.url();
+        
+        // Create client with mock server URL
+        let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
+        
+        // Set access token
+        client.set_access_token("test_access_token".to_string()).await;
+        
+        // Setup mock response
+        let _m = server.mock("GET", "/quote/ohlc")
+            .match_query(mockito::Matcher::UrlEncoded("i".to_string(), "NSE:INFY".to_string()))
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status":"success","data":{"NSE:INFY":{"instrument_token":408065,"last_price":1512.5,"ohlc":{"open":1500.0,"high":1520.0,"low":1495.0,"close":1505.0}}}}"#)
+            .create();
+        
+        // Call get_ohlc
+        let quotes = client.get_ohlc(&["NSE:INFY".to_string()]).await.unwrap();
+        
+        // Verify result
+        let quote = quotes.get("NSE:INFY").unwrap();
+        assert_eq!(quote.instrument_token, 408065);
+        assert_eq!(quote.last_price, 1512.5);
+        assert_eq!(quote.ohlc.close, 1505.0);
+    }
 }
\ No newline at end of file
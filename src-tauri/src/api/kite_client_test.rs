@@ -1,4 +1,5 @@
 use crate::api::kite_client::{KiteApiClient, KiteClient};
+use crate::api::rate_limiter::RateLimitPolicy;
 use crate::models::kite::{
     KiteOrderRequest, KiteOrderResponse, KitePosition, 
     KiteOrder, KiteHolding, KiteMarginResponse, KiteProfile, KiteQuote,
@@ -18,7 +19,7 @@ async fn test_generate_session() {
     let mock_url = server.url();
     
     // Create client with mock server URL
-    let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+    let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
     
     // Setup mock response
     let _m = server.mock("POST", "/session/token")
@@ -44,7 +45,7 @@ async fn test_invalidate_session() {
     let mock_url = server.url();
     
     // Create client with mock server URL
-    let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+    let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
     
     // Set access token
     client.set_access_token("test_access_token".to_string()).await;
@@ -72,7 +73,7 @@ async fn test_get_profile() {
     let mock_url = server.url();
     
     // Create client with mock server URL
-    let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+    let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
     
     // Set access token
     client.set_access_token("test_access_token".to_string()).await;
@@ -103,7 +104,7 @@ async fn test_get_margins() {
     let mock_url = server.url();
     
     // Create client with mock server URL
-    let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+    let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
     
     // Set access token
     client.set_access_token("test_access_token".to_string()).await;
@@ -132,7 +133,7 @@ async fn test_get_orders() {
     let mock_url = server.url();
     
     // Create client with mock server URL
-    let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+    let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
     
     // Set access token
     client.set_access_token("test_access_token".to_string()).await;
@@ -164,7 +165,7 @@ async fn test_place_order() {
     let mock_url = server.url();
     
     // Create client with mock server URL
-    let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+    let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
     
     // Set access token
     client.set_access_token("test_access_token".to_string()).await;
@@ -209,7 +210,7 @@ async fn test_error_handling() {
     let mock_url = server.url();
     
     // Create client with mock server URL
-    let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+    let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
     
     // Set access token
     client.set_access_token("test_access_token".to_string()).await;
@@ -236,7 +237,7 @@ async fn test_rate_limit_error() {
     let mock_url = server.url();
     
     // Create client with mock server URL
-    let client = KiteClient::new_with_config("test_api_key", &mock_url, 0).unwrap();
+    let client = KiteClient::new_with_config("test_api_key", &mock_url, RateLimitPolicy::default()).unwrap();
     
     // Set access token
     client.set_access_token("test_access_token".to_string()).await;
@@ -1,12 +1,14 @@
 use reqwest::Client;
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use crate::models::backtesting::{OHLCV, Timeframe, HistoricalDataParams};
 use crate::error::{HedgeXError, Result};
+use crate::api::rate_limiter::{KiteRateLimiter, RateCategory};
 use tracing::{info, warn, error, debug};
 use rust_decimal::Decimal;
 use std::str::FromStr;
+use std::sync::Arc;
 
 /// Kite Historical Data API client
 pub struct KiteHistoricalClient {
@@ -14,6 +16,17 @@ pub struct KiteHistoricalClient {
     base_url: String,
     api_key: String,
     access_token: Option<String>,
+    rate_limiter: Arc<KiteRateLimiter>,
+}
+
+/// Progress of a historical fetch split into several requests
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct HistoricalFetchProgress {
+    /// Requests completed so far
+    pub chunks_done: usize,
+    pub total_chunks: usize,
+    /// Candles received so far, overlaps included
+    pub candles: usize,
 }
 
 /// Kite API historical data response
@@ -53,9 +66,16 @@ impl KiteHistoricalClient {
             base_url: "https://api.kite.trade".to_string(),
             api_key: api_key.to_string(),
             access_token: None,
+            rate_limiter: Arc::new(KiteRateLimiter::default()),
         }
     }
     
+    /// Share a rate limiter, so historical requests count against the same limits as the main client's
+    pub fn with_rate_limiter(mut self, rate_limiter: Arc<KiteRateLimiter>) -> Self {
+        self.rate_limiter = rate_limiter;
+        self
+    }
+    
     /// Set access token for authenticated requests
     pub fn set_access_token(&mut self, access_token: &str) {
         self.access_token = Some(access_token.to_string());
@@ -63,6 +83,18 @@ impl KiteHistoricalClient {
     
     /// Fetch historical data from Kite API
     pub async fn fetch_historical_data(&self, params: &HistoricalDataParams) -> Result<Vec<OHLCV>> {
+        self.fetch_historical_data_with_progress(params, |_| {}).await
+    }
+    
+    /// Fetch historical data, reporting progress after each request
+    ///
+    /// Kite caps the days of candles per request by interval, so longer ranges are
+    /// fetched in chunks and stitched together, with candles on shared boundaries
+    /// kept once.
+    pub async fn fetch_historical_data_with_progress<F>(&self, params: &HistoricalDataParams, on_progress: F) -> Result<Vec<OHLCV>>
+    where
+        F: Fn(HistoricalFetchProgress) + Send + Sync,
+    {
         info!("Fetching historical data for {} from Kite API", params.symbol);
         
         let access_token = self.access_token.as_ref()
@@ -72,11 +104,49 @@ impl KiteHistoricalClient {
         let instrument_token = self.get_instrument_token(&params.symbol, &params.exchange).await?;
         
         // Convert timeframe to Kite API format
-        let interval = self.timeframe_to_kite_interval(&params.timeframe);
+        let interval = self.timeframe_to_kite_interval(&params.timeframe)?;
+        
+        let chunks = chunk_range(params.from_date, params.to_date, max_days_per_request(&params.timeframe));
+        let mut progress = HistoricalFetchProgress {
+            chunks_done: 0,
+            total_chunks: chunks.len(),
+            candles: 0,
+        };
+        
+        let mut ohlcv_data = Vec::new();
+        for (from, to) in chunks {
+            let candles = self.fetch_chunk(access_token, instrument_token, &interval, from, to).await?;
+            
+            progress.chunks_done += 1;
+            progress.candles += candles.len();
+            debug!("Fetched chunk {}/{} of {}: {} candles", progress.chunks_done, progress.total_chunks, params.symbol, candles.len());
+            on_progress(progress);
+            
+            ohlcv_data.extend(candles);
+        }
+        
+        ohlcv_data.sort_by(|a, b| a.timestamp.cmp(&b.timestamp));
+        ohlcv_data.dedup_by_key(|candle| candle.timestamp);
+        
+        info!("Successfully fetched {} historical data points", ohlcv_data.len());
+        Ok(ohlcv_data)
+    }
+    
+    /// Fetch one range no longer than Kite allows for the interval
+    async fn fetch_chunk(
+        &self,
+        access_token: &str,
+        instrument_token: u64,
+        interval: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<Vec<OHLCV>> {
+        // Wait for a slot within the historical rate limit, or give up
+        self.rate_limiter.acquire(RateCategory::Historical).await?;
         
         // Format dates for API
-        let from_date = params.from_date.format("%Y-%m-%d").to_string();
-        let to_date = params.to_date.format("%Y-%m-%d").to_string();
+        let from_date = from.format("%Y-%m-%d").to_string();
+        let to_date = to.format("%Y-%m-%d").to_string();
         
         // Build request URL
         let url = format!(
@@ -86,7 +156,7 @@ impl KiteHistoricalClient {
             interval
         );
         
-        debug!("Requesting historical data from: {}", url);
+        debug!("Requesting historical data from: {} ({} to {})", url, from_date, to_date);
         
         // Make API request
         let response = self.client
@@ -124,10 +194,7 @@ impl KiteHistoricalClient {
         }
         
         // Convert API response to OHLCV data
-        let ohlcv_data = self.convert_kite_candles_to_ohlcv(api_response.data.candles)?;
-        
-        info!("Successfully fetched {} historical data points", ohlcv_data.len());
-        Ok(ohlcv_data)
+        self.convert_kite_candles_to_ohlcv(api_response.data.candles)
     }
     
     /// Get instrument token for a symbol
@@ -160,14 +227,17 @@ impl KiteHistoricalClient {
     }
     
     /// Convert timeframe to Kite API interval format
-    fn timeframe_to_kite_interval(&self, timeframe: &Timeframe) -> String {
+    fn timeframe_to_kite_interval(&self, timeframe: &Timeframe) -> Result<String> {
         match timeframe {
-            Timeframe::Minute1 => "minute".to_string(),
-            Timeframe::Minute5 => "5minute".to_string(),
-            Timeframe::Minute15 => "15minute".to_string(),
-            Timeframe::Minute30 => "30minute".to_string(),
-            Timeframe::Hour1 => "60minute".to_string(),
-            Timeframe::Day1 => "day".to_string(),
+            Timeframe::Minute1 => Ok("minute".to_string()),
+            Timeframe::Minute5 => Ok("5minute".to_string()),
+            Timeframe::Minute15 => Ok("15minute".to_string()),
+            Timeframe::Minute30 => Ok("30minute".to_string()),
+            Timeframe::Hour1 => Ok("60minute".to_string()),
+            Timeframe::Day1 => Ok("day".to_string()),
+            Timeframe::Tick => Err(HedgeXError::ValidationError(
+                "Kite API does not provide historical ticks".to_string()
+            )),
         }
     }
     
@@ -317,6 +387,36 @@ impl KiteHistoricalClient {
     }
 }
 
+/// Most days of candles Kite returns per request for a timeframe
+pub fn max_days_per_request(timeframe: &Timeframe) -> i64 {
+    match timeframe {
+        Timeframe::Minute1 => 60,
+        Timeframe::Minute5 => 100,
+        Timeframe::Minute15 | Timeframe::Minute30 => 200,
+        Timeframe::Hour1 => 400,
+        Timeframe::Day1 | Timeframe::Tick => 2000,
+    }
+}
+
+/// Split a range into consecutive chunks of at most `max_days` days
+///
+/// Adjacent chunks share their boundary, so a candle on it may be returned twice.
+pub fn chunk_range(from: DateTime<Utc>, to: DateTime<Utc>, max_days: i64) -> Vec<(DateTime<Utc>, DateTime<Utc>)> {
+    let mut chunks = Vec::new();
+    let mut start = from;
+    
+    loop {
+        let end = (start + Duration::days(max_days)).min(to);
+        chunks.push((start, end));
+        if end >= to {
+            break;
+        }
+        start = end;
+    }
+    
+    chunks
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -326,9 +426,10 @@ mod tests {
     async fn test_timeframe_conversion() {
         let client = KiteHistoricalClient::new("test_api_key");
         
-        assert_eq!(client.timeframe_to_kite_interval(&Timeframe::Minute1), "minute");
-        assert_eq!(client.timeframe_to_kite_interval(&Timeframe::Minute5), "5minute");
-        assert_eq!(client.timeframe_to_kite_interval(&Timeframe::Day1), "day");
+        assert_eq!(client.timeframe_to_kite_interval(&Timeframe::Minute1).unwrap(), "minute");
+        assert_eq!(client.timeframe_to_kite_interval(&Timeframe::Minute5).unwrap(), "5minute");
+        assert_eq!(client.timeframe_to_kite_interval(&Timeframe::Day1).unwrap(), "day");
+        assert!(client.timeframe_to_kite_interval(&Timeframe::Tick).is_err());
     }
     
     #[tokio::test]
@@ -356,4 +457,57 @@ mod tests {
         assert_eq!(candle.close, Decimal::from(103));
         assert_eq!(candle.volume, 1000);
     }
-}
\ No newline at end of file
+    
+    #[test]
+    fn test_long_ranges_are_chunked() {
+        let from = DateTime::parse_from_rfc3339("2024-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        
+        // 150 days of minute candles take three requests of at most 60 days
+        let chunks = chunk_range(from, from + Duration::days(150), max_days_per_request(&Timeframe::Minute1));
+        assert_eq!(chunks.len(), 3);
+        assert_eq!(chunks[0], (from, from + Duration::days(60)));
+        assert_eq!(chunks[1].0, chunks[0].1);
+        assert_eq!(chunks[2], (from + Duration::days(120), from + Duration::days(150)));
+        
+        // Daily candles fit in one request
+        assert_eq!(chunk_range(from, from + Duration::days(150), max_days_per_request(&Timeframe::Day1)).len(), 1);
+    }
+    
+    #[tokio::test]
+    async fn test_chunks_are_stitched_without_overlaps() {
+        let mut server = Server::new_async().await;
+        let mut client = KiteHistoricalClient::new("test_api_key");
+        client.base_url = server.url();
+        client.set_access_token("test_access_token");
+        
+        // Both chunks return the candle on their shared boundary
+        let mock: Mock = server.mock("GET", mockito::Matcher::Regex("^/instruments/historical/408065/day".to_string()))
+            .match_query(mockito::Matcher::Any)
+            .with_status(200)
+            .with_header("content-type", "application/json")
+            .with_body(r#"{"status":"success","data":{"candles":[["2024-01-01T00:00:00+05:30",100,105,99,103,1000],["2024-01-02T00:00:00+05:30",103,106,101,104,1200]]}}"#)
+            .expect(2)
+            .create_async()
+            .await;
+        
+        let from = DateTime::parse_from_rfc3339("2020-01-01T00:00:00Z").unwrap().with_timezone(&Utc);
+        let params = HistoricalDataParams {
+            symbol: "INFY".to_string(),
+            exchange: "NSE".to_string(),
+            from_date: from,
+            to_date: from + Duration::days(2500),
+            timeframe: Timeframe::Day1,
+        };
+        
+        let progress = std::sync::Mutex::new(Vec::new());
+        let data = client.fetch_historical_data_with_progress(&params, |p| progress.lock().unwrap().push(p)).await.unwrap();
+        
+        mock.assert_async().await;
+        assert_eq!(data.len(), 2);
+        assert!(data[0].timestamp < data[1].timestamp);
+        
+        let progress = progress.into_inner().unwrap();
+        assert_eq!(progress.len(), 2);
+        assert_eq!(progress[1], HistoricalFetchProgress { chunks_done: 2, total_chunks: 2, candles: 4 });
+    }
+}
@@ -1,10 +1,14 @@
 use crate::error::{ApiResult, HedgeXError, Result};
 use crate::services::kite_service::KiteService;
+use crate::services::order_book::{OrderBook, OrderBookService};
+use crate::services::option_chain::{OptionChain, OptionChainService};
+use crate::api::kite_client::KiteApiClient;
+use crate::trading::session::{market_status, session_date};
 use crate::models::kite::{
     KiteOrderRequest, KiteOrderResponse, KitePosition, 
-    KiteOrder, KiteHolding, KiteMarginResponse, KiteProfile, KiteQuote,
+    KiteOrder, KiteTrade, KiteHolding, KiteMarginResponse, KiteProfile, KiteQuote,
     KiteHistoricalDataParams, KiteOHLCV, KiteInstrument, KiteExchange,
-    KiteOrderVariety,
+    KiteOrderVariety, KiteMarginOrder, KiteBasketMargin, KiteOhlcQuote, KiteLtpQuote,
 };
 use crate::api::middleware::extract_user_id;
 use axum::{
@@ -14,6 +18,7 @@ use axum::{
     routing::{get, post, put, delete},
     Json, Router,
 };
+use chrono::{NaiveDate, Utc};
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -27,6 +32,7 @@ pub fn kite_routes(kite_service: Arc<KiteService>) -> Router {
         .route("/session/token", delete(invalidate_session))
         .route("/profile", get(get_profile))
         .route("/margins", get(get_margins))
+        .route("/margins/basket", post(get_basket_margins))
         .route("/orders", get(get_orders))
         .route("/orders", post(place_order))
         .route("/orders/:order_id", get(get_order_history))
@@ -38,10 +44,34 @@ pub fn kite_routes(kite_service: Arc<KiteService>) -> Router {
         .route("/instruments", get(get_instruments))
         .route("/instruments/:exchange", get(get_instruments_by_exchange))
         .route("/quote", get(get_quote))
+        .route("/quote/ohlc", get(get_ohlc))
+        .route("/quote/ltp", get(get_ltp))
         .route("/historical", post(get_historical_data))
         .with_state(kite_service)
 }
 
+/// Account routes, served under `/api/account`
+pub fn account_routes(kite_service: Arc<KiteService>) -> Router {
+    Router::new()
+        .route("/margins", get(get_margins))
+        .with_state(kite_service)
+}
+
+/// Order book routes, served under `/api/trading`
+pub fn order_book_routes(order_book: Arc<OrderBookService>) -> Router {
+    Router::new()
+        .route("/orderbook", get(get_order_book))
+        .with_state(order_book)
+}
+
+/// Market routes, served under `/api/market`
+pub fn market_routes(option_chain: Arc<OptionChainService>, client: Arc<dyn KiteApiClient + Send + Sync>) -> Router {
+    Router::new()
+        .route("/status", get(get_market_status))
+        .route("/optionchain/:symbol", get(get_option_chain))
+        .with_state((option_chain, client))
+}
+
 /// Request for generating session URL
 #[derive(Debug, Deserialize)]
 struct SessionUrlRequest {
@@ -79,6 +109,20 @@ struct QuoteRequest {
     instruments: String, // Comma-separated list of instruments
 }
 
+/// Request for an order book snapshot
+#[derive(Debug, Deserialize)]
+struct OrderBookRequest {
+    /// Session date; today when omitted
+    date: Option<NaiveDate>,
+}
+
+/// Request for an option chain
+#[derive(Debug, Deserialize)]
+struct OptionChainRequest {
+    /// Expiry; the nearest one when omitted
+    expiry: Option<NaiveDate>,
+}
+
 /// Generate session URL
 #[instrument(skip(kite_service))]
 async fn generate_session_url(
@@ -196,6 +240,45 @@ async fn get_margins(
     }
 }
 
+/// Request for the combined margin of a basket of orders
+#[derive(Debug, Deserialize)]
+struct BasketMarginRequest {
+    orders: Vec<KiteMarginOrder>,
+    /// Net the basket against open positions, as Kite does by default
+    #[serde(default = "default_consider_positions")]
+    consider_positions: bool,
+}
+
+fn default_consider_positions() -> bool {
+    true
+}
+
+/// Get basket margins
+#[instrument(skip(kite_service, request))]
+async fn get_basket_margins(
+    State(kite_service): State<Arc<KiteService>>,
+    Json(request): Json<BasketMarginRequest>,
+) -> impl IntoResponse {
+    debug!("Getting margins for a basket of {} orders", request.orders.len());
+    
+    match kite_service.get_basket_margins(&request.orders, request.consider_positions).await {
+        Ok(margins) => {
+            debug!("Basket margins retrieved successfully");
+            (
+                StatusCode::OK,
+                Json(ApiResult::success(margins)),
+            )
+        }
+        Err(err) => {
+            error!("Failed to get basket margins: {}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResult::<KiteBasketMargin>::from_error(err)),
+            )
+        }
+    }
+}
+
 /// Get order book
 #[instrument(skip(kite_service))]
 async fn get_orders(
@@ -266,7 +349,7 @@ async fn get_trades(
             error!("Failed to get trades: {}", err);
             (
                 StatusCode::INTERNAL_SERVER_ERROR,
-                Json(ApiResult::<Vec<KiteOrder>>::from_error(err)),
+                Json(ApiResult::<Vec<KiteTrade>>::from_error(err)),
             )
         }
     }
@@ -429,6 +512,78 @@ async fn get_quote(
     }
 }
 
+/// Get OHLC quotes
+#[instrument(skip(kite_service))]
+async fn get_ohlc(
+    State(kite_service): State<Arc<KiteService>>,
+    Query(query): Query<QuoteRequest>,
+) -> impl IntoResponse {
+    let instruments: Vec<String> = query.instruments
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+        
+    debug!("Getting OHLC quotes for {} instruments", instruments.len());
+    
+    if instruments.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResult::<HashMap<String, KiteOhlcQuote>>::error(
+                "No instruments provided".to_string(),
+                Some("VALIDATION_ERROR".to_string()),
+            )),
+        );
+    }
+    
+    match kite_service.get_ohlc(&instruments).await {
+        Ok(quotes) => (StatusCode::OK, Json(ApiResult::success(quotes))),
+        Err(err) => {
+            error!("Failed to get OHLC quotes: {}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResult::<HashMap<String, KiteOhlcQuote>>::from_error(err)),
+            )
+        }
+    }
+}
+
+/// Get last prices
+#[instrument(skip(kite_service))]
+async fn get_ltp(
+    State(kite_service): State<Arc<KiteService>>,
+    Query(query): Query<QuoteRequest>,
+) -> impl IntoResponse {
+    let instruments: Vec<String> = query.instruments
+        .split(',')
+        .map(|s| s.trim().to_string())
+        .filter(|s| !s.is_empty())
+        .collect();
+        
+    debug!("Getting last prices for {} instruments", instruments.len());
+    
+    if instruments.is_empty() {
+        return (
+            StatusCode::BAD_REQUEST,
+            Json(ApiResult::<HashMap<String, KiteLtpQuote>>::error(
+                "No instruments provided".to_string(),
+                Some("VALIDATION_ERROR".to_string()),
+            )),
+        );
+    }
+    
+    match kite_service.get_ltp(&instruments).await {
+        Ok(quotes) => (StatusCode::OK, Json(ApiResult::success(quotes))),
+        Err(err) => {
+            error!("Failed to get last prices: {}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResult::<HashMap<String, KiteLtpQuote>>::from_error(err)),
+            )
+        }
+    }
+}
+
 /// Get historical data
 #[instrument(skip(kite_service, params))]
 async fn get_historical_data(
@@ -550,4 +705,72 @@ async fn cancel_order(
             )
         }
     }
-}
\ No newline at end of file
+}
+
+/// Get the order book and trade book snapshot of a day
+#[instrument(skip(order_book))]
+async fn get_order_book(
+    State(order_book): State<Arc<OrderBookService>>,
+    Query(query): Query<OrderBookRequest>,
+) -> impl IntoResponse {
+    let date = query.date.unwrap_or_else(|| session_date(Utc::now()));
+    debug!("Getting order book of {}", date);
+    
+    match order_book.get_order_book(date).await {
+        Ok(book) => {
+            debug!("Order book retrieved successfully: {} orders", book.orders.len());
+            (
+                StatusCode::OK,
+                Json(ApiResult::success(book)),
+            )
+        }
+        Err(err) => {
+            error!("Failed to get order book: {}", err);
+            (
+                StatusCode::INTERNAL_SERVER_ERROR,
+                Json(ApiResult::<OrderBook>::from_error(err)),
+            )
+        }
+    }
+}
+
+/// Get whether the market is open and the time to the next open or close
+async fn get_market_status() -> impl IntoResponse {
+    (
+        StatusCode::OK,
+        Json(ApiResult::success(market_status(Utc::now()))),
+    )
+}
+
+/// Get the option chain of an underlying for one expiry
+#[instrument(skip(state))]
+async fn get_option_chain(
+    State(state): State<(Arc<OptionChainService>, Arc<dyn KiteApiClient + Send + Sync>)>,
+    Path(symbol): Path<String>,
+    Query(query): Query<OptionChainRequest>,
+) -> impl IntoResponse {
+    let (option_chain, client) = state;
+    debug!("Getting option chain of {}", symbol);
+    
+    match option_chain.get_chain(client.as_ref(), &symbol, query.expiry).await {
+        Ok(chain) => {
+            debug!("Option chain retrieved successfully: {} strikes", chain.rows.len());
+            (
+                StatusCode::OK,
+                Json(ApiResult::success(chain)),
+            )
+        }
+        Err(err) => {
+            error!("Failed to get option chain: {}", err);
+            let status = match err {
+                HedgeXError::NotFoundError(_) => StatusCode::NOT_FOUND,
+                HedgeXError::ValidationError(_) => StatusCode::BAD_REQUEST,
+                _ => StatusCode::INTERNAL_SERVER_ERROR,
+            };
+            (
+                status,
+                Json(ApiResult::<OptionChain>::from_error(err)),
+            )
+        }
+    }
+}
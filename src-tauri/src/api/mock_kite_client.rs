@@ -0,0 +1,832 @@
+use crate::api::kite_client::KiteApiClient;
+use crate::error::{HedgeXError, Result};
+use crate::models::kite::{
+    KiteAvailableMargin, KiteBasketMargin, KiteDepthItem, KiteExchange, KiteGtt, KiteGttRequest,
+    KiteGttResponse, KiteGttStatus, KiteHistoricalDataParams, KiteHolding, KiteInstrument,
+    KiteInterval, KiteLtpQuote, KiteMargin, KiteMarginOrder, KiteMarginResponse, KiteMarketDepth,
+    KiteOHLC, KiteOHLCV, KiteOhlcQuote, KiteOrder, KiteOrderMargin, KiteOrderRequest,
+    KiteOrderResponse, KiteOrderStatus, KiteOrderType, KiteOrderVariety, KitePosition,
+    KitePositionItem, KiteProduct, KiteProfile, KiteQuote, KiteTrade, KiteTransactionType,
+    KiteUsedMargin,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, Duration, NaiveDate, NaiveDateTime, NaiveTime, TimeZone, Utc, Weekday};
+use chrono_tz::Asia::Kolkata;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::str::FromStr;
+use tokio::sync::{Mutex, RwLock};
+use tracing::info;
+
+/// Access token the demo client starts with, so background services run
+pub const DEMO_ACCESS_TOKEN: &str = "demo_access_token";
+
+/// Cash the demo account starts with
+const DEMO_CASH: f64 = 1_000_000.0;
+
+/// Largest adverse slippage of a demo fill, in basis points
+const DEFAULT_MAX_SLIPPAGE_BPS: f64 = 5.0;
+
+/// Largest move of a demo price between two quotes, as a fraction
+const QUOTE_STEP: f64 = 0.001;
+
+const TICK_SIZE: f64 = 0.05;
+
+/// Symbols the demo account trades, with their tokens and starting prices
+const DEMO_INSTRUMENTS: &[(&str, u64, f64)] = &[
+    ("RELIANCE", 738561, 2500.0),
+    ("TCS", 2953217, 3500.0),
+    ("INFY", 408065, 1500.0),
+    ("HDFCBANK", 341249, 1600.0),
+    ("ICICIBANK", 1270529, 950.0),
+];
+
+/// Kite client for demo and offline use, backed by synthetic data
+///
+/// Quotes random-walk from each symbol's starting price, marketable orders fill
+/// at once with random adverse slippage, and historical candles are replayed
+/// from a fixed price curve so the same range always gets the same candles.
+pub struct MockKiteClient {
+    access_token: RwLock<Option<String>>,
+    max_slippage_bps: f64,
+    book: Mutex<DemoBook>,
+}
+
+#[derive(Default)]
+struct DemoBook {
+    prices: HashMap<(KiteExchange, String), DemoPrice>,
+    orders: Vec<KiteOrder>,
+    trades: Vec<KiteTrade>,
+    positions: HashMap<(KiteExchange, String, KiteProduct), DemoPosition>,
+    gtts: Vec<KiteGtt>,
+    next_id: u64,
+}
+
+struct DemoPrice {
+    token: u64,
+    open: f64,
+    high: f64,
+    low: f64,
+    last: f64,
+    volume: u64,
+}
+
+#[derive(Default)]
+struct DemoPosition {
+    buy_quantity: i32,
+    buy_value: f64,
+    sell_quantity: i32,
+    sell_value: f64,
+}
+
+impl MockKiteClient {
+    pub fn new() -> Self {
+        Self::with_slippage(DEFAULT_MAX_SLIPPAGE_BPS)
+    }
+
+    /// Demo client whose fills slip by up to `max_slippage_bps` against the order
+    pub fn with_slippage(max_slippage_bps: f64) -> Self {
+        Self {
+            access_token: RwLock::new(Some(DEMO_ACCESS_TOKEN.to_string())),
+            max_slippage_bps: max_slippage_bps.max(0.0),
+            book: Mutex::new(DemoBook { next_id: 1, ..Default::default() }),
+        }
+    }
+}
+
+impl Default for MockKiteClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DemoBook {
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    /// The symbol's price, starting it at its base price on first use
+    fn price(&mut self, exchange: KiteExchange, symbol: &str) -> &mut DemoPrice {
+        self.prices.entry((exchange, symbol.to_string())).or_insert_with(|| {
+            let (token, base) = demo_instrument(symbol);
+            DemoPrice { token, open: base, high: base, low: base, last: base, volume: 0 }
+        })
+    }
+
+    /// Fill an order if it is marketable, recording the trade and the position change
+    fn try_fill(&mut self, index: usize, max_slippage_bps: f64) {
+        let (exchange, symbol) = (self.orders[index].exchange, self.orders[index].tradingsymbol.clone());
+        let last = self.price(exchange, &symbol).last;
+        let order = &self.orders[index];
+
+        let marketable = match (order.order_type, order.transaction_type) {
+            (KiteOrderType::Market, _) => true,
+            (KiteOrderType::Limit, KiteTransactionType::Buy) => order.price >= last,
+            (KiteOrderType::Limit, KiteTransactionType::Sell) => order.price <= last,
+            // Stop orders rest until triggered, which the demo never does
+            _ => false,
+        };
+        if !marketable {
+            return;
+        }
+
+        let slippage = last * rand::thread_rng().gen_range(0.0..=max_slippage_bps) / 10_000.0;
+        let mut fill_price = match order.transaction_type {
+            KiteTransactionType::Buy => last + slippage,
+            KiteTransactionType::Sell => last - slippage,
+        };
+        // A limit never fills beyond its price
+        if order.order_type == KiteOrderType::Limit {
+            fill_price = match order.transaction_type {
+                KiteTransactionType::Buy => fill_price.min(order.price),
+                KiteTransactionType::Sell => fill_price.max(order.price),
+            };
+        }
+        let fill_price = round_to_tick(fill_price);
+        let now = Utc::now();
+        let trade_id = self.next_id();
+
+        let order = &mut self.orders[index];
+        let quantity = order.pending_quantity;
+        order.status = KiteOrderStatus::Complete;
+        order.average_price = fill_price;
+        order.filled_quantity += quantity;
+        order.pending_quantity = 0;
+        order.exchange_update_timestamp = Some(now);
+        order.exchange_timestamp = Some(now);
+
+        let trade = KiteTrade {
+            trade_id: format!("DEMO-T{}", trade_id),
+            order_id: order.order_id.clone(),
+            exchange_order_id: order.exchange_order_id.clone(),
+            tradingsymbol: order.tradingsymbol.clone(),
+            exchange: order.exchange,
+            instrument_token: order.instrument_token,
+            product: order.product,
+            average_price: fill_price,
+            quantity,
+            transaction_type: order.transaction_type,
+            fill_timestamp: Some(now),
+            exchange_timestamp: Some(now),
+        };
+
+        let position = self.positions
+            .entry((trade.exchange, trade.tradingsymbol.clone(), trade.product))
+            .or_default();
+        match trade.transaction_type {
+            KiteTransactionType::Buy => {
+                position.buy_quantity += quantity as i32;
+                position.buy_value += fill_price * quantity as f64;
+            }
+            KiteTransactionType::Sell => {
+                position.sell_quantity += quantity as i32;
+                position.sell_value += fill_price * quantity as f64;
+            }
+        }
+        self.price(trade.exchange, &trade.tradingsymbol).volume += quantity as u64;
+
+        info!("Demo fill: {} {} {} @ {}", trade.transaction_type, quantity, trade.tradingsymbol, fill_price);
+        self.trades.push(trade);
+    }
+
+    fn find_order(&mut self, order_id: &str) -> Result<usize> {
+        self.orders.iter()
+            .position(|order| order.order_id == order_id)
+            .ok_or_else(|| HedgeXError::NotFoundError(format!("Order {} not found", order_id)))
+    }
+}
+
+/// Token and starting price of a symbol; unknown symbols get stable made-up ones
+fn demo_instrument(symbol: &str) -> (u64, f64) {
+    if let Some((_, token, base)) = DEMO_INSTRUMENTS.iter().find(|(s, _, _)| *s == symbol) {
+        return (*token, *base);
+    }
+
+    let mut hasher = DefaultHasher::new();
+    symbol.hash(&mut hasher);
+    let hash = hasher.finish();
+    (hash % 10_000_000 + 10_000_000, round_to_tick(100.0 + (hash % 1900) as f64))
+}
+
+fn demo_symbol(token: u64) -> Option<&'static str> {
+    DEMO_INSTRUMENTS.iter().find(|(_, t, _)| *t == token).map(|(symbol, _, _)| *symbol)
+}
+
+fn round_to_tick(price: f64) -> f64 {
+    (price / TICK_SIZE).round() * TICK_SIZE
+}
+
+fn is_pending(order: &KiteOrder) -> bool {
+    matches!(order.status, KiteOrderStatus::Open | KiteOrderStatus::TriggerPending)
+}
+
+/// Parse a historical range bound in IST; a bare date means the start or end of that day
+fn parse_ist(value: &str, end_of_day: bool) -> Result<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%d %H:%M:%S")
+        .or_else(|_| NaiveDate::parse_from_str(value, "%Y-%m-%d").map(|date| {
+            date.and_time(if end_of_day { NaiveTime::from_hms_opt(23, 59, 59).unwrap() } else { NaiveTime::MIN })
+        }))
+        .map_err(|_| HedgeXError::ValidationError(format!("Invalid date: {}", value)))
+}
+
+fn interval_minutes(interval: KiteInterval) -> Option<i64> {
+    match interval {
+        KiteInterval::Minute1 => Some(1),
+        KiteInterval::Minute3 => Some(3),
+        KiteInterval::Minute5 => Some(5),
+        KiteInterval::Minute10 => Some(10),
+        KiteInterval::Minute15 => Some(15),
+        KiteInterval::Minute30 => Some(30),
+        KiteInterval::Minute60 => Some(60),
+        KiteInterval::Day1 => None,
+    }
+}
+
+/// Price of the demo curve at a moment: slow swings plus noise fixed by the moment
+fn curve_price(token: u64, base: f64, at: DateTime<Utc>) -> f64 {
+    let days = at.timestamp() as f64 / 86_400.0;
+    let noise = StdRng::seed_from_u64(token ^ at.timestamp() as u64).gen_range(-0.002..=0.002);
+    base * (1.0 + 0.08 * (days / 23.0).sin() + 0.03 * (days / 5.3).sin() + noise)
+}
+
+/// Candles of the demo curve over trading hours between two IST moments
+fn replay_candles(token: u64, base: f64, from: NaiveDateTime, to: NaiveDateTime, interval: KiteInterval) -> Vec<KiteOHLCV> {
+    let step = interval_minutes(interval);
+    let session_open = NaiveTime::from_hms_opt(9, 15, 0).unwrap();
+    let session_close = NaiveTime::from_hms_opt(15, 30, 0).unwrap();
+    let mut candles = Vec::new();
+
+    let mut date = from.date();
+    while date <= to.date() {
+        if !matches!(date.weekday(), Weekday::Sat | Weekday::Sun) {
+            // Kite stamps daily candles at midnight IST
+            let starts: Vec<NaiveDateTime> = match step {
+                Some(minutes) => {
+                    let mut starts = Vec::new();
+                    let mut start = date.and_time(session_open);
+                    while start.time() < session_close {
+                        starts.push(start);
+                        start += Duration::minutes(minutes);
+                    }
+                    starts
+                }
+                None => vec![date.and_time(NaiveTime::MIN)],
+            };
+
+            for start in starts.into_iter().filter(|start| *start >= from && *start <= to) {
+                let Some(open_at) = Kolkata.from_local_datetime(&start).single() else { continue };
+                let open_at = open_at.with_timezone(&Utc);
+                let close_at = open_at + Duration::minutes(step.unwrap_or(375));
+
+                let open = curve_price(token, base, open_at);
+                let close = curve_price(token, base, close_at);
+                let mut rng = StdRng::seed_from_u64(token.rotate_left(17) ^ open_at.timestamp() as u64);
+                candles.push(KiteOHLCV {
+                    date: open_at,
+                    open: round_to_tick(open),
+                    high: round_to_tick(open.max(close) * (1.0 + rng.gen_range(0.0..0.002))),
+                    low: round_to_tick(open.min(close) * (1.0 - rng.gen_range(0.0..0.002))),
+                    close: round_to_tick(close),
+                    volume: rng.gen_range(1_000..50_000) * step.unwrap_or(375) as u64,
+                });
+            }
+        }
+        date = match date.succ_opt() {
+            Some(next) => next,
+            None => break,
+        };
+    }
+
+    candles
+}
+
+#[async_trait]
+impl KiteApiClient for MockKiteClient {
+    async fn set_access_token(&self, access_token: String) {
+        *self.access_token.write().await = Some(access_token);
+    }
+
+    async fn get_access_token(&self) -> Option<String> {
+        self.access_token.read().await.clone()
+    }
+
+    /// Demo mode has no login page
+    fn generate_session_url(&self, _api_key: &str) -> String {
+        String::new()
+    }
+
+    async fn generate_session(&self, _request_token: &str, _api_secret: &str) -> Result<String> {
+        self.set_access_token(DEMO_ACCESS_TOKEN.to_string()).await;
+        Ok(DEMO_ACCESS_TOKEN.to_string())
+    }
+
+    async fn invalidate_session(&self) -> Result<()> {
+        *self.access_token.write().await = None;
+        Ok(())
+    }
+
+    async fn get_profile(&self) -> Result<KiteProfile> {
+        Ok(KiteProfile {
+            user_id: "DEMO01".to_string(),
+            user_name: "Demo User".to_string(),
+            user_shortname: "Demo".to_string(),
+            email: "demo@example.com".to_string(),
+            user_type: "individual".to_string(),
+            broker: "DEMO".to_string(),
+            products: vec!["CNC".to_string(), "MIS".to_string(), "NRML".to_string()],
+            order_types: vec!["MARKET".to_string(), "LIMIT".to_string(), "SL".to_string(), "SL-M".to_string()],
+            exchanges: vec!["NSE".to_string(), "BSE".to_string()],
+        })
+    }
+
+    async fn get_margins(&self) -> Result<KiteMarginResponse> {
+        let positions = self.get_positions().await?;
+        let realised: f64 = positions.net.iter().map(|p| p.realized_pnl).sum();
+        let unrealised: f64 = positions.net.iter().map(|p| p.unrealized_pnl).sum();
+        let exposure: f64 = positions.net.iter().map(|p| (p.quantity as f64 * p.average_price).abs()).sum();
+
+        let margin = |enabled: bool, cash: f64, debits: f64| KiteMargin {
+            enabled,
+            net: cash - debits,
+            available: KiteAvailableMargin { adhoc_margin: 0.0, cash, collateral: 0.0, intraday_payin: 0.0 },
+            used: KiteUsedMargin {
+                debits,
+                exposure: 0.0,
+                m2m_realised: if enabled { realised } else { 0.0 },
+                m2m_unrealised: if enabled { unrealised } else { 0.0 },
+                option_premium: 0.0,
+                payout: 0.0,
+                span: 0.0,
+                holding_sales: 0.0,
+                turnover: 0.0,
+            },
+        };
+
+        Ok(KiteMarginResponse {
+            equity: margin(true, DEMO_CASH + realised, exposure),
+            commodity: margin(false, 0.0, 0.0),
+        })
+    }
+
+    async fn get_basket_margins(&self, orders: &[KiteMarginOrder], consider_positions: bool) -> Result<KiteBasketMargin> {
+        let mut book = self.book.lock().await;
+        let mut legs = Vec::with_capacity(orders.len());
+        // Signed value per symbol; buys and sells of the same symbol offset each other
+        let mut net: HashMap<(KiteExchange, String), (f64, f64)> = HashMap::new();
+
+        for order in orders {
+            let last = book.price(order.exchange, &order.tradingsymbol).last;
+            let price = if order.price > 0.0 { order.price } else { last };
+            let value = price * order.quantity as f64;
+            let signed = match order.transaction_type {
+                KiteTransactionType::Buy => value,
+                KiteTransactionType::Sell => -value,
+            };
+            net.entry((order.exchange, order.tradingsymbol.clone())).or_default().1 += signed;
+
+            // Demo orders are fully cash-funded, like the demo account's margins
+            legs.push(KiteOrderMargin {
+                exchange: order.exchange.to_string(),
+                tradingsymbol: order.tradingsymbol.clone(),
+                var: value,
+                leverage: 1.0,
+                total: value,
+                ..Default::default()
+            });
+        }
+
+        if consider_positions {
+            for ((exchange, symbol, _), position) in &book.positions {
+                if let Some(entry) = net.get_mut(&(*exchange, symbol.clone())) {
+                    let quantity = position.buy_quantity - position.sell_quantity;
+                    entry.0 += quantity as f64 * book.prices.get(&(*exchange, symbol.clone())).map_or(0.0, |price| price.last);
+                }
+            }
+        }
+
+        let initial_total: f64 = legs.iter().map(|leg| leg.total).sum();
+        // Only the part of the basket that adds to the position's size needs margin
+        let final_total: f64 = net.values()
+            .map(|(position, basket)| ((position + basket).abs() - position.abs()).max(0.0))
+            .sum();
+
+        Ok(KiteBasketMargin {
+            initial: KiteOrderMargin { var: initial_total, total: initial_total, ..Default::default() },
+            final_margin: KiteOrderMargin { var: final_total, total: final_total, ..Default::default() },
+            orders: legs,
+        })
+    }
+
+    async fn get_orders(&self) -> Result<Vec<KiteOrder>> {
+        Ok(self.book.lock().await.orders.clone())
+    }
+
+    async fn get_order_history(&self, order_id: &str) -> Result<Vec<KiteOrder>> {
+        let mut book = self.book.lock().await;
+        let index = book.find_order(order_id)?;
+        Ok(vec![book.orders[index].clone()])
+    }
+
+    async fn get_trades(&self) -> Result<Vec<KiteTrade>> {
+        Ok(self.book.lock().await.trades.clone())
+    }
+
+    async fn get_positions(&self) -> Result<KitePosition> {
+        let mut book = self.book.lock().await;
+        let keys: Vec<(KiteExchange, String, KiteProduct)> = book.positions.keys().cloned().collect();
+
+        let mut net = Vec::new();
+        for (exchange, symbol, product) in keys {
+            let last_price = book.price(exchange, &symbol).last;
+            let position = &book.positions[&(exchange, symbol.clone(), product)];
+
+            let quantity = position.buy_quantity - position.sell_quantity;
+            let buy_price = if position.buy_quantity > 0 { position.buy_value / position.buy_quantity as f64 } else { 0.0 };
+            let sell_price = if position.sell_quantity > 0 { position.sell_value / position.sell_quantity as f64 } else { 0.0 };
+            let closed = position.buy_quantity.min(position.sell_quantity) as f64;
+            let realized_pnl = closed * (sell_price - buy_price);
+            let average_price = match quantity {
+                q if q > 0 => buy_price,
+                q if q < 0 => sell_price,
+                _ => 0.0,
+            };
+            let unrealized_pnl = quantity as f64 * (last_price - average_price);
+
+            net.push(KitePositionItem {
+                tradingsymbol: symbol,
+                exchange,
+                product,
+                quantity,
+                average_price,
+                last_price,
+                pnl: realized_pnl + unrealized_pnl,
+                realized_pnl,
+                unrealized_pnl,
+                value: position.sell_value - position.buy_value,
+                buy_quantity: position.buy_quantity,
+                buy_price,
+                buy_value: position.buy_value,
+                sell_quantity: position.sell_quantity,
+                sell_price,
+                sell_value: position.sell_value,
+                day_buy_quantity: position.buy_quantity,
+                day_sell_quantity: position.sell_quantity,
+                day_buy_price: buy_price,
+                day_sell_price: sell_price,
+                day_buy_value: position.buy_value,
+                day_sell_value: position.sell_value,
+            });
+        }
+        net.sort_by(|a, b| a.tradingsymbol.cmp(&b.tradingsymbol));
+
+        Ok(KitePosition { day: net.clone(), net })
+    }
+
+    async fn get_holdings(&self) -> Result<Vec<KiteHolding>> {
+        Ok(Vec::new())
+    }
+
+    async fn get_instruments(&self, exchange: Option<KiteExchange>) -> Result<Vec<KiteInstrument>> {
+        if exchange.is_some_and(|exchange| exchange != KiteExchange::NSE) {
+            return Ok(Vec::new());
+        }
+
+        Ok(DEMO_INSTRUMENTS.iter()
+            .map(|(symbol, token, base)| KiteInstrument {
+                instrument_token: *token,
+                exchange_token: token / 256,
+                tradingsymbol: symbol.to_string(),
+                name: symbol.to_string(),
+                last_price: *base,
+                expiry: None,
+                strike: None,
+                tick_size: TICK_SIZE,
+                lot_size: 1,
+                instrument_type: "EQ".to_string(),
+                segment: "NSE".to_string(),
+                exchange: KiteExchange::NSE,
+            })
+            .collect())
+    }
+
+    async fn get_quote(&self, instruments: &[String]) -> Result<HashMap<String, KiteQuote>> {
+        let mut book = self.book.lock().await;
+        let mut quotes = HashMap::new();
+
+        for key in instruments {
+            // Instruments are either "EXCHANGE:SYMBOL" or an instrument token
+            let (exchange, symbol) = match key.split_once(':') {
+                Some((exchange, symbol)) => (
+                    KiteExchange::from_str(exchange).map_err(HedgeXError::ValidationError)?,
+                    symbol.to_string(),
+                ),
+                None => {
+                    let token: u64 = key.parse()
+                        .map_err(|_| HedgeXError::ValidationError(format!("Invalid instrument: {}", key)))?;
+                    (KiteExchange::NSE, demo_symbol(token).map(str::to_string).unwrap_or_else(|| key.clone()))
+                }
+            };
+
+            let step = rand::thread_rng().gen_range(-QUOTE_STEP..=QUOTE_STEP);
+            let traded = rand::thread_rng().gen_range(1..500u64);
+            let price = book.price(exchange, &symbol);
+            price.last = round_to_tick(price.last * (1.0 + step));
+            price.high = price.high.max(price.last);
+            price.low = price.low.min(price.last);
+            price.volume += traded;
+
+            let level = |offset: u32, side: f64| KiteDepthItem {
+                price: round_to_tick(price.last + side * TICK_SIZE * offset as f64),
+                quantity: 100 * offset,
+                orders: offset,
+            };
+            quotes.insert(key.clone(), KiteQuote {
+                instrument_token: price.token,
+                last_price: price.last,
+                last_quantity: traded as u32,
+                average_price: round_to_tick((price.open + price.last) / 2.0),
+                volume: price.volume,
+                buy_quantity: 1_500,
+                sell_quantity: 1_500,
+                ohlc: KiteOHLC { open: price.open, high: price.high, low: price.low, close: price.open },
+                net_change: price.last - price.open,
+                lower_circuit_limit: round_to_tick(price.open * 0.8),
+                upper_circuit_limit: round_to_tick(price.open * 1.2),
+                oi: 0,
+                depth: KiteMarketDepth {
+                    buy: (1..=5).map(|offset| level(offset, -1.0)).collect(),
+                    sell: (1..=5).map(|offset| level(offset, 1.0)).collect(),
+                },
+            });
+        }
+
+        Ok(quotes)
+    }
+
+    async fn get_ohlc(&self, instruments: &[String]) -> Result<HashMap<String, KiteOhlcQuote>> {
+        Ok(self.get_quote(instruments).await?.into_iter()
+            .map(|(key, quote)| (key, KiteOhlcQuote {
+                instrument_token: quote.instrument_token,
+                last_price: quote.last_price,
+                ohlc: quote.ohlc,
+            }))
+            .collect())
+    }
+
+    async fn get_ltp(&self, instruments: &[String]) -> Result<HashMap<String, KiteLtpQuote>> {
+        Ok(self.get_quote(instruments).await?.into_iter()
+            .map(|(key, quote)| (key, KiteLtpQuote {
+                instrument_token: quote.instrument_token,
+                last_price: quote.last_price,
+            }))
+            .collect())
+    }
+
+    async fn get_historical_data(&self, params: KiteHistoricalDataParams) -> Result<Vec<KiteOHLCV>> {
+        let from = parse_ist(&params.from_date, false)?;
+        let to = parse_ist(&params.to_date, true)?;
+        if from > to {
+            return Err(HedgeXError::ValidationError("Start date must be before end date".to_string()));
+        }
+
+        let (_, base) = demo_instrument(&params.symbol);
+        Ok(replay_candles(params.instrument_token, base, from, to, params.interval))
+    }
+
+    async fn place_order(&self, order: KiteOrderRequest) -> Result<KiteOrderResponse> {
+        if order.quantity == 0 {
+            return Err(HedgeXError::ValidationError("Order quantity must be positive".to_string()));
+        }
+        if order.order_type == KiteOrderType::Limit && order.price.unwrap_or(0.0) <= 0.0 {
+            return Err(HedgeXError::ValidationError("Limit orders need a price".to_string()));
+        }
+
+        let mut book = self.book.lock().await;
+        let id = book.next_id();
+        let token = book.price(order.exchange, &order.tradingsymbol).token;
+        let now = Utc::now();
+
+        book.orders.push(KiteOrder {
+            order_id: format!("DEMO-{}", id),
+            exchange_order_id: Some(format!("DEMO-X{}", id)),
+            parent_order_id: None,
+            status: match order.order_type {
+                KiteOrderType::StopLoss | KiteOrderType::StopLossMarket => KiteOrderStatus::TriggerPending,
+                _ => KiteOrderStatus::Open,
+            },
+            status_message: None,
+            order_timestamp: Some(now),
+            exchange_update_timestamp: Some(now),
+            exchange_timestamp: Some(now),
+            variety: order.variety,
+            exchange: order.exchange,
+            tradingsymbol: order.tradingsymbol,
+            instrument_token: token,
+            transaction_type: order.transaction_type,
+            order_type: order.order_type,
+            product: order.product,
+            validity: order.validity,
+            price: order.price.unwrap_or(0.0),
+            trigger_price: order.trigger_price.unwrap_or(0.0),
+            average_price: 0.0,
+            filled_quantity: 0,
+            pending_quantity: order.quantity,
+            cancelled_quantity: 0,
+            disclosed_quantity: order.disclosed_quantity.map(|q| q.get()).unwrap_or(0),
+            market_protection: false,
+            tag: Some("demo".to_string()),
+            tags: None,
+        });
+        let index = book.orders.len() - 1;
+        book.try_fill(index, self.max_slippage_bps);
+
+        Ok(KiteOrderResponse { order_id: format!("DEMO-{}", id) })
+    }
+
+    async fn modify_order(&self, order_id: &str, order: KiteOrderRequest) -> Result<KiteOrderResponse> {
+        let mut book = self.book.lock().await;
+        let index = book.find_order(order_id)?;
+        if !is_pending(&book.orders[index]) {
+            return Err(HedgeXError::OrderRejected(format!("Order {} can no longer be modified", order_id)));
+        }
+
+        let existing = &mut book.orders[index];
+        existing.order_type = order.order_type;
+        existing.pending_quantity = order.quantity;
+        existing.price = order.price.unwrap_or(existing.price);
+        existing.trigger_price = order.trigger_price.unwrap_or(existing.trigger_price);
+        existing.exchange_update_timestamp = Some(Utc::now());
+        book.try_fill(index, self.max_slippage_bps);
+
+        Ok(KiteOrderResponse { order_id: order_id.to_string() })
+    }
+
+    async fn cancel_order(&self, order_id: &str, _variety: KiteOrderVariety) -> Result<KiteOrderResponse> {
+        let mut book = self.book.lock().await;
+        let index = book.find_order(order_id)?;
+        if !is_pending(&book.orders[index]) {
+            return Err(HedgeXError::OrderRejected(format!("Order {} can no longer be cancelled", order_id)));
+        }
+
+        let order = &mut book.orders[index];
+        order.status = KiteOrderStatus::Cancelled;
+        order.cancelled_quantity = order.pending_quantity;
+        order.pending_quantity = 0;
+        order.exchange_update_timestamp = Some(Utc::now());
+
+        Ok(KiteOrderResponse { order_id: order_id.to_string() })
+    }
+
+    async fn get_gtts(&self) -> Result<Vec<KiteGtt>> {
+        Ok(self.book.lock().await.gtts.clone())
+    }
+
+    async fn get_gtt(&self, trigger_id: u64) -> Result<KiteGtt> {
+        self.book.lock().await.gtts.iter()
+            .find(|gtt| gtt.id == trigger_id)
+            .cloned()
+            .ok_or_else(|| HedgeXError::NotFoundError(format!("GTT trigger {} not found", trigger_id)))
+    }
+
+    async fn place_gtt(&self, gtt: KiteGttRequest) -> Result<KiteGttResponse> {
+        let mut book = self.book.lock().await;
+        let id = book.next_id();
+        let now = Utc::now();
+
+        book.gtts.push(KiteGtt {
+            id,
+            trigger_type: gtt.trigger_type,
+            status: KiteGttStatus::Active,
+            condition: gtt.condition,
+            orders: gtt.orders,
+            created_at: Some(now),
+            updated_at: Some(now),
+            expires_at: Some(now + Duration::days(365)),
+        });
+
+        Ok(KiteGttResponse { trigger_id: id })
+    }
+
+    async fn modify_gtt(&self, trigger_id: u64, gtt: KiteGttRequest) -> Result<KiteGttResponse> {
+        let mut book = self.book.lock().await;
+        let existing = book.gtts.iter_mut()
+            .find(|existing| existing.id == trigger_id && existing.status == KiteGttStatus::Active)
+            .ok_or_else(|| HedgeXError::NotFoundError(format!("GTT trigger {} not found", trigger_id)))?;
+
+        existing.trigger_type = gtt.trigger_type;
+        existing.condition = gtt.condition;
+        existing.orders = gtt.orders;
+        existing.updated_at = Some(Utc::now());
+
+        Ok(KiteGttResponse { trigger_id })
+    }
+
+    async fn delete_gtt(&self, trigger_id: u64) -> Result<KiteGttResponse> {
+        let mut book = self.book.lock().await;
+        let before = book.gtts.len();
+        book.gtts.retain(|gtt| gtt.id != trigger_id);
+        if book.gtts.len() == before {
+            return Err(HedgeXError::NotFoundError(format!("GTT trigger {} not found", trigger_id)));
+        }
+
+        Ok(KiteGttResponse { trigger_id })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::kite::KiteValidity;
+
+    fn order(transaction_type: KiteTransactionType, order_type: KiteOrderType, price: Option<f64>) -> KiteOrderRequest {
+        KiteOrderRequest {
+            tradingsymbol: "INFY".to_string(),
+            exchange: KiteExchange::NSE,
+            transaction_type,
+            order_type,
+            quantity: 10,
+            price,
+            product: KiteProduct::MIS,
+            validity: KiteValidity::Day,
+            disclosed_quantity: None,
+            trigger_price: None,
+            squareoff: None,
+            stoploss: None,
+            trailing_stoploss: None,
+            variety: KiteOrderVariety::Regular,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_orders_fill_with_bounded_slippage() {
+        let client = MockKiteClient::with_slippage(10.0);
+        assert_eq!(client.get_access_token().await.as_deref(), Some(DEMO_ACCESS_TOKEN));
+
+        let buy = client.place_order(order(KiteTransactionType::Buy, KiteOrderType::Market, None)).await.unwrap();
+        let filled = &client.get_order_history(&buy.order_id).await.unwrap()[0];
+        assert_eq!(filled.status, KiteOrderStatus::Complete);
+        // INFY starts at 1500; a buy slips up by at most 10 bps
+        assert!(filled.average_price >= 1500.0 && filled.average_price <= 1501.5 + TICK_SIZE);
+
+        // A buy limit far below the market rests until cancelled
+        let resting = client.place_order(order(KiteTransactionType::Buy, KiteOrderType::Limit, Some(1000.0))).await.unwrap();
+        assert_eq!(client.get_order_history(&resting.order_id).await.unwrap()[0].status, KiteOrderStatus::Open);
+        client.cancel_order(&resting.order_id, KiteOrderVariety::Regular).await.unwrap();
+        assert!(client.cancel_order(&resting.order_id, KiteOrderVariety::Regular).await.is_err());
+
+        let positions = client.get_positions().await.unwrap();
+        assert_eq!(positions.net.len(), 1);
+        assert_eq!(positions.net[0].quantity, 10);
+        assert_eq!(client.get_trades().await.unwrap().len(), 1);
+
+        let margins = client.get_margins().await.unwrap();
+        assert!(margins.equity.net < DEMO_CASH);
+    }
+
+    #[tokio::test]
+    async fn test_basket_margin_nets_offsetting_legs() {
+        let client = MockKiteClient::with_slippage(0.0);
+        let leg = |side, price| KiteMarginOrder::from(&order(side, KiteOrderType::Limit, Some(price)));
+
+        // Buying and selling the same quantity leaves nothing to margin once netted
+        let basket = client.get_basket_margins(&[leg(KiteTransactionType::Buy, 1500.0), leg(KiteTransactionType::Sell, 1500.0)], false).await.unwrap();
+        assert_eq!(basket.orders.len(), 2);
+        assert_eq!(basket.initial.total, 30_000.0);
+        assert_eq!(basket.final_margin.total, 0.0);
+
+        // Selling out of an open long only needs margin when positions are ignored
+        client.place_order(order(KiteTransactionType::Buy, KiteOrderType::Market, None)).await.unwrap();
+        let exit = [leg(KiteTransactionType::Sell, 1500.0)];
+        assert_eq!(client.get_basket_margins(&exit, false).await.unwrap().final_margin.total, 15_000.0);
+        assert_eq!(client.get_basket_margins(&exit, true).await.unwrap().final_margin.total, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_historical_replay_is_repeatable() {
+        let client = MockKiteClient::new();
+        let params = |from: &str, to: &str| KiteHistoricalDataParams {
+            instrument_token: 408065,
+            symbol: "INFY".to_string(),
+            exchange: KiteExchange::NSE,
+            from_date: from.to_string(),
+            to_date: to.to_string(),
+            interval: KiteInterval::Minute5,
+        };
+
+        // Friday to Monday: two trading days of 75 five-minute candles
+        let candles = client.get_historical_data(params("2024-01-26", "2024-01-29")).await.unwrap();
+        assert_eq!(candles.len(), 150);
+        assert!(candles.iter().all(|c| c.low <= c.open.min(c.close) && c.high >= c.open.max(c.close)));
+
+        // An overlapping range gets the same candles where they overlap
+        let monday = client.get_historical_data(params("2024-01-29", "2024-01-29")).await.unwrap();
+        assert_eq!(monday.len(), 75);
+        assert_eq!(monday[0].date, candles[75].date);
+        assert_eq!(monday[0].close, candles[75].close);
+    }
+}
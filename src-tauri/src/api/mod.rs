@@ -1,10 +1,15 @@
 pub mod kite_client;
+pub mod mock_kite_client;
+pub mod rate_limiter;
 pub mod middleware;
 pub mod kite_routes;
 pub mod websocket_routes;
 pub mod ticker;
 // pub mod http_server;
 pub mod kite_historical;
+pub mod postback;
+pub mod analytics_routes;
+pub mod backtest_routes;
 #[cfg(test)]
 mod http_server_test;
 #[cfg(test)]
@@ -12,8 +17,13 @@ mod http_server_integration;
 
 // Re-export important types
 pub use kite_client::{KiteApiClient, KiteClient};
-pub use kite_routes::kite_routes;
+pub use mock_kite_client::MockKiteClient;
+pub use rate_limiter::{KiteRateLimiter, RateCategory, RateLimitPolicy};
+pub use kite_routes::{account_routes, kite_routes, market_routes, order_book_routes};
 pub use websocket_routes::websocket_routes;
 pub use ticker::KiteTickerClient;
 // pub use http_server::{HttpServerState, create_server};
-pub use kite_historical::KiteHistoricalClient;
\ No newline at end of file
+pub use kite_historical::{HistoricalFetchProgress, KiteHistoricalClient};
+pub use postback::{PostbackState, postback_routes, start_postback_listener};
+pub use analytics_routes::{AnalyticsState, analytics_routes, start_api_listener};
+pub use backtest_routes::backtest_routes;
\ No newline at end of file
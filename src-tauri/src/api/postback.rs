@@ -0,0 +1,169 @@
+use crate::error::{HedgeXError, Result};
+use crate::models::backtesting::hex_digest;
+use crate::models::kite::KiteOrderUpdate;
+use axum::{
+    extract::State,
+    http::StatusCode,
+    routing::post,
+    Router,
+};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio::sync::broadcast;
+use tokio::task::JoinHandle;
+use tracing::{debug, error, info, warn};
+
+/// Path Kite Connect posts order updates to, relative to the listener's address
+pub const POSTBACK_PATH: &str = "/kite/postback";
+
+/// Fields of a postback needed to check it came from Kite
+#[derive(Debug, Deserialize)]
+struct PostbackEnvelope {
+    order_id: String,
+    order_timestamp: String,
+    checksum: String,
+}
+
+/// Shared state of the postback endpoint
+pub struct PostbackState {
+    api_secret: String,
+    /// Same channel the ticker publishes order updates on
+    order_updates: broadcast::Sender<KiteOrderUpdate>,
+}
+
+impl PostbackState {
+    pub fn new(api_secret: &str, order_updates: broadcast::Sender<KiteOrderUpdate>) -> Self {
+        Self {
+            api_secret: api_secret.to_string(),
+            order_updates,
+        }
+    }
+}
+
+/// Whether a postback checksum matches SHA-256 of order ID, order timestamp and API secret
+pub fn verify_postback_checksum(order_id: &str, order_timestamp: &str, api_secret: &str, checksum: &str) -> bool {
+    let message = format!("{}{}{}", order_id, order_timestamp, api_secret);
+    let expected = hex_digest(Sha256::digest(message.as_bytes()).as_slice());
+    expected.eq_ignore_ascii_case(checksum)
+}
+
+/// Check a raw postback body and parse the order update in it
+pub fn parse_postback(body: &str, api_secret: &str) -> Result<KiteOrderUpdate> {
+    let payload: serde_json::Value = serde_json::from_str(body)?;
+    let envelope: PostbackEnvelope = serde_json::from_value(payload.clone())
+        .map_err(|e| HedgeXError::ValidationError(format!("Postback is missing checksum fields: {}", e)))?;
+
+    if !verify_postback_checksum(&envelope.order_id, &envelope.order_timestamp, api_secret, &envelope.checksum) {
+        return Err(HedgeXError::PermissionError(format!("Invalid checksum on postback for order {}", envelope.order_id)));
+    }
+
+    Ok(serde_json::from_value(payload)?)
+}
+
+/// Route accepting Kite Connect order postbacks
+pub fn postback_routes(state: Arc<PostbackState>) -> Router {
+    Router::new()
+        .route(POSTBACK_PATH, post(receive_postback))
+        .with_state(state)
+}
+
+/// Serve the postback route on its own listener, as a backup to the ticker's order updates
+pub async fn start_postback_listener(addr: SocketAddr, state: Arc<PostbackState>) -> Result<JoinHandle<()>> {
+    let listener = TcpListener::bind(addr).await?;
+    info!("Listening for Kite order postbacks on {}{}", addr, POSTBACK_PATH);
+
+    Ok(tokio::spawn(async move {
+        if let Err(e) = axum::serve(listener, postback_routes(state)).await {
+            error!("Postback listener stopped: {}", e);
+        }
+    }))
+}
+
+/// Kite posts the order as a raw JSON body, whatever the content type
+async fn receive_postback(
+    State(state): State<Arc<PostbackState>>,
+    body: String,
+) -> StatusCode {
+    match parse_postback(&body, &state.api_secret) {
+        Ok(update) => {
+            debug!("Postback for order {}: {:?}", update.order_id, update.status);
+            // The ticker may deliver the same update; applying it twice changes nothing
+            let _ = state.order_updates.send(update);
+            StatusCode::OK
+        }
+        Err(HedgeXError::PermissionError(msg)) => {
+            warn!("Rejected postback: {}", msg);
+            StatusCode::UNAUTHORIZED
+        }
+        Err(e) => {
+            warn!("Malformed postback: {}", e);
+            StatusCode::BAD_REQUEST
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::kite::KiteOrderStatus;
+
+    const SECRET: &str = "test_api_secret";
+
+    fn postback_body(checksum: &str) -> String {
+        serde_json::json!({
+            "user_id": "AB1234",
+            "order_id": "220303000308932",
+            "exchange_order_id": "1000000001482421",
+            "order_timestamp": "2022-03-03 09:24:25",
+            "status": "COMPLETE",
+            "status_message": null,
+            "tradingsymbol": "SBIN",
+            "exchange": "NSE",
+            "transaction_type": "BUY",
+            "quantity": 1,
+            "average_price": 470.0,
+            "filled_quantity": 1,
+            "pending_quantity": 0,
+            "checksum": checksum
+        }).to_string()
+    }
+
+    fn valid_checksum() -> String {
+        let message = format!("220303000308932{}{}", "2022-03-03 09:24:25", SECRET);
+        hex_digest(Sha256::digest(message.as_bytes()).as_slice())
+    }
+
+    #[test]
+    fn test_parse_postback_checks_checksum() {
+        let update = parse_postback(&postback_body(&valid_checksum()), SECRET).unwrap();
+        assert_eq!(update.order_id, "220303000308932");
+        assert_eq!(update.status, KiteOrderStatus::Complete);
+        assert_eq!(update.filled_quantity, 1);
+
+        match parse_postback(&postback_body(&valid_checksum()), "other_secret") {
+            Err(HedgeXError::PermissionError(_)) => {}
+            other => panic!("Expected PermissionError, got {:?}", other),
+        }
+        assert!(parse_postback("not json", SECRET).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_verified_postbacks_feed_order_updates() {
+        let (tx, mut rx) = broadcast::channel(10);
+        let state = Arc::new(PostbackState::new(SECRET, tx));
+
+        let status = receive_postback(State(Arc::clone(&state)), postback_body("bad")).await;
+        assert_eq!(status, StatusCode::UNAUTHORIZED);
+        assert!(rx.try_recv().is_err());
+
+        let status = receive_postback(State(Arc::clone(&state)), "{}".to_string()).await;
+        assert_eq!(status, StatusCode::BAD_REQUEST);
+
+        let status = receive_postback(State(state), postback_body(&valid_checksum())).await;
+        assert_eq!(status, StatusCode::OK);
+        assert_eq!(rx.try_recv().unwrap().order_id, "220303000308932");
+    }
+}
@@ -0,0 +1,226 @@
+use crate::error::{HedgeXError, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::time::{Duration, Instant};
+use tokio::sync::Mutex;
+use tokio::time::sleep;
+use tracing::debug;
+
+/// Longest a request is held back by default before it is rejected instead
+pub const DEFAULT_MAX_QUEUE_WAIT: Duration = Duration::from_secs(2);
+
+const SECOND: Duration = Duration::from_secs(1);
+const MINUTE: Duration = Duration::from_secs(60);
+
+/// Kite Connect endpoint groups that are rate limited separately
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum RateCategory {
+    /// Full, OHLC and LTP quotes
+    Quote,
+    /// Historical candles
+    Historical,
+    /// Placing, modifying and cancelling orders
+    Orders,
+    /// Every other endpoint
+    Other,
+}
+
+impl RateCategory {
+    /// The category of a request, from its method and endpoint path
+    pub fn of(method: &str, endpoint: &str) -> Self {
+        if endpoint.starts_with("/quote") {
+            RateCategory::Quote
+        } else if endpoint.starts_with("/instruments/historical") {
+            RateCategory::Historical
+        } else if endpoint.starts_with("/orders") && method != "GET" {
+            RateCategory::Orders
+        } else {
+            RateCategory::Other
+        }
+    }
+
+    /// Zerodha's documented limits as (requests, window) pairs; all must hold
+    pub fn limits(&self) -> &'static [(usize, Duration)] {
+        match self {
+            RateCategory::Quote => &[(1, SECOND)],
+            RateCategory::Historical => &[(3, SECOND)],
+            RateCategory::Orders => &[(10, SECOND), (200, MINUTE)],
+            RateCategory::Other => &[(10, SECOND)],
+        }
+    }
+}
+
+impl fmt::Display for RateCategory {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RateCategory::Quote => write!(f, "quote"),
+            RateCategory::Historical => write!(f, "historical"),
+            RateCategory::Orders => write!(f, "orders"),
+            RateCategory::Other => write!(f, "other"),
+        }
+    }
+}
+
+/// What happens to a request over its category's limit
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RateLimitPolicy {
+    /// Hold the request until a slot frees, rejecting it if that takes longer than this
+    Queue(Duration),
+    /// Reject the request straight away
+    Reject,
+}
+
+impl Default for RateLimitPolicy {
+    fn default() -> Self {
+        RateLimitPolicy::Queue(DEFAULT_MAX_QUEUE_WAIT)
+    }
+}
+
+/// Client-side limiter keeping each endpoint group under Kite's limits
+///
+/// Every admitted request takes a slot in its category's sliding windows, so
+/// the broker never sees a burst it would answer with a ban.
+pub struct KiteRateLimiter {
+    policy: RateLimitPolicy,
+    /// Start times of recent and queued requests, per category
+    slots: Mutex<HashMap<RateCategory, VecDeque<Instant>>>,
+}
+
+impl KiteRateLimiter {
+    pub fn new(policy: RateLimitPolicy) -> Self {
+        Self {
+            policy,
+            slots: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub fn policy(&self) -> RateLimitPolicy {
+        self.policy
+    }
+
+    /// Wait for a slot in the category, or fail with `RateLimitExceeded`
+    pub async fn acquire(&self, category: RateCategory) -> Result<()> {
+        let wait = self.reserve(category, Instant::now()).await?;
+        if !wait.is_zero() {
+            debug!("Rate limiting {} request: waiting {}ms", category, wait.as_millis());
+            sleep(wait).await;
+        }
+        Ok(())
+    }
+
+    /// Take the earliest slot free at or after `now`, returning how long until it starts
+    async fn reserve(&self, category: RateCategory, now: Instant) -> Result<Duration> {
+        let mut slots = self.slots.lock().await;
+        let taken = slots.entry(category).or_default();
+        let limits = category.limits();
+
+        // Slots older than the longest window no longer count against any limit
+        let longest = limits.iter().map(|(_, window)| *window).max().unwrap_or_default();
+        while taken.front().is_some_and(|start| now.duration_since(*start) >= longest) {
+            taken.pop_front();
+        }
+
+        // A request may start once enough slots have left every window; moving
+        // the start for one window can matter for another, so repeat until settled
+        let mut start = now;
+        loop {
+            let settled = limits.iter().fold(start, |start, (max, window)| {
+                let in_window: Vec<&Instant> = taken.iter().filter(|slot| **slot + *window > start).collect();
+                if in_window.len() < *max {
+                    start
+                } else {
+                    start.max(*in_window[in_window.len() - max] + *window)
+                }
+            });
+            if settled == start {
+                break;
+            }
+            start = settled;
+        }
+        let wait = start - now;
+
+        let allowed = match self.policy {
+            RateLimitPolicy::Queue(max_wait) => wait <= max_wait,
+            RateLimitPolicy::Reject => wait.is_zero(),
+        };
+        if !allowed {
+            return Err(HedgeXError::RateLimitExceeded {
+                category: category.to_string(),
+                retry_after_ms: wait.as_millis() as u64,
+            });
+        }
+
+        taken.push_back(start);
+        Ok(wait)
+    }
+}
+
+impl Default for KiteRateLimiter {
+    fn default() -> Self {
+        Self::new(RateLimitPolicy::default())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_endpoints_map_to_categories() {
+        assert_eq!(RateCategory::of("GET", "/quote?i=NSE:INFY"), RateCategory::Quote);
+        assert_eq!(RateCategory::of("GET", "/quote/ltp?i=NSE:INFY"), RateCategory::Quote);
+        assert_eq!(RateCategory::of("GET", "/instruments/historical/NSE/INFY/408065?from=x"), RateCategory::Historical);
+        assert_eq!(RateCategory::of("POST", "/orders"), RateCategory::Orders);
+        assert_eq!(RateCategory::of("DELETE", "/orders/regular/1"), RateCategory::Orders);
+        assert_eq!(RateCategory::of("GET", "/orders"), RateCategory::Other);
+        assert_eq!(RateCategory::of("GET", "/instruments"), RateCategory::Other);
+    }
+
+    #[tokio::test]
+    async fn test_excess_requests_are_queued_then_rejected() {
+        let limiter = KiteRateLimiter::new(RateLimitPolicy::Queue(Duration::from_millis(1500)));
+        let now = Instant::now();
+
+        // One quote a second: the second waits a second, the third would wait two
+        assert_eq!(limiter.reserve(RateCategory::Quote, now).await.unwrap(), Duration::ZERO);
+        assert_eq!(limiter.reserve(RateCategory::Quote, now).await.unwrap(), Duration::from_secs(1));
+        match limiter.reserve(RateCategory::Quote, now).await {
+            Err(HedgeXError::RateLimitExceeded { category, retry_after_ms }) => {
+                assert_eq!(category, "quote");
+                assert_eq!(retry_after_ms, 2000);
+            }
+            other => panic!("Expected RateLimitExceeded, got {:?}", other),
+        }
+
+        // Other categories are unaffected, and the slot frees once the window passes
+        assert_eq!(limiter.reserve(RateCategory::Historical, now).await.unwrap(), Duration::ZERO);
+        let later = now + Duration::from_secs(2);
+        assert_eq!(limiter.reserve(RateCategory::Quote, later).await.unwrap(), Duration::ZERO);
+    }
+
+    #[tokio::test]
+    async fn test_order_limits_hold_per_second_and_per_minute() {
+        let limiter = KiteRateLimiter::new(RateLimitPolicy::Reject);
+        let start = Instant::now();
+
+        for _ in 0..10 {
+            limiter.reserve(RateCategory::Orders, start).await.unwrap();
+        }
+        assert!(limiter.reserve(RateCategory::Orders, start).await.is_err());
+
+        // Ten a second for twenty seconds uses up the 200 a minute
+        for second in 1..20 {
+            let now = start + Duration::from_secs(second);
+            for _ in 0..10 {
+                limiter.reserve(RateCategory::Orders, now).await.unwrap();
+            }
+        }
+        let now = start + Duration::from_secs(20);
+        match limiter.reserve(RateCategory::Orders, now).await {
+            Err(HedgeXError::RateLimitExceeded { retry_after_ms, .. }) => assert_eq!(retry_after_ms, 40_000),
+            other => panic!("Expected RateLimitExceeded, got {:?}", other),
+        }
+        assert!(limiter.reserve(RateCategory::Orders, start + Duration::from_secs(60)).await.is_ok());
+    }
+}
@@ -9,6 +9,22 @@ use std::thread;
 
 const KITE_TICKER_URL: &str = "wss://ws.kite.trade";
 
+/// Packet lengths of the Kite ticker binary protocol
+const LTP_PACKET_LENGTH: usize = 8;
+const INDEX_QUOTE_PACKET_LENGTH: usize = 28;
+const INDEX_FULL_PACKET_LENGTH: usize = 32;
+const QUOTE_PACKET_LENGTH: usize = 44;
+const FULL_PACKET_LENGTH: usize = 184;
+
+/// Offset of the first depth level in a full packet
+const DEPTH_OFFSET: usize = 64;
+
+/// Bytes per depth level: quantity, price, orders and two bytes of padding
+const DEPTH_LEVEL_LENGTH: usize = 12;
+
+/// Levels on each side of the book in a full packet
+pub const DEPTH_LEVELS: usize = 5;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct TickData {
     pub instrument_token: u32,
@@ -90,13 +106,22 @@ impl KiteTickerClient {
             for message in receiver.incoming_messages() {
                 match message {
                     Ok(OwnedMessage::Binary(data)) => {
-                        // Parse binary message according to Kite's format
-                        // This is a simplified version and actual parsing will be more complex
-                        if let Ok(tick_data) = parse_binary_message(&data) {
-                            if ticker_tx.send(tick_data).is_err() {
-                                break;
+                        // Decode each packet in place and send listeners an owned copy
+                        let mut listening = true;
+                        for packet in parse_packets(&data) {
+                            match packet {
+                                Ok(packet) => {
+                                    if ticker_tx.send(TickData::from_packet(&packet)).is_err() {
+                                        listening = false;
+                                        break;
+                                    }
+                                }
+                                Err(e) => println!("Error parsing tick packet: {:?}", e),
                             }
                         }
+                        if !listening {
+                            break;
+                        }
                     },
                     Ok(OwnedMessage::Close(_)) => {
                         // WebSocket closed
@@ -156,22 +181,301 @@ impl KiteTickerClient {
     }
 }
 
-// This is a placeholder for the actual binary message parsing logic
-// The actual implementation would need to follow Kite's binary protocol specification
-fn parse_binary_message(_data: &[u8]) -> Result<TickData> {
-    // This is just a placeholder - real implementation would decode the binary format
-    Ok(TickData {
-        instrument_token: 12345, // Placeholder
-        last_price: 100.0,       // Placeholder
-        volume: 1000,            // Placeholder
-        buy_quantity: 500,       // Placeholder
-        sell_quantity: 500,      // Placeholder
-        ohlc: Some(Ohlc {
-            open: 95.0,          // Placeholder
-            high: 105.0,         // Placeholder
-            low: 90.0,           // Placeholder
-            close: 100.0,        // Placeholder
-        }),
-        timestamp: chrono::Utc::now().to_rfc3339(),
-    })
+/// Side of the order book in a full packet
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DepthSide {
+    Bid,
+    Ask,
+}
+
+/// One order book level read from a full packet
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DepthEntry {
+    pub quantity: u32,
+    pub price: f64,
+    pub orders: u16,
+}
+
+/// View of one tick packet that decodes fields straight from the received buffer
+///
+/// Nothing is copied or allocated; each accessor reads its bytes on demand.
+/// Prices are sent in paise (or smaller units for currency segments) and are
+/// returned in rupees.
+#[derive(Debug, Clone, Copy)]
+pub struct TickPacket<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> TickPacket<'a> {
+    /// Wrap a packet, checking it has one of the protocol's lengths
+    pub fn new(data: &'a [u8]) -> Result<Self> {
+        match data.len() {
+            LTP_PACKET_LENGTH | INDEX_QUOTE_PACKET_LENGTH | INDEX_FULL_PACKET_LENGTH
+            | QUOTE_PACKET_LENGTH | FULL_PACKET_LENGTH => Ok(Self { data }),
+            length => Err(anyhow!("Unexpected tick packet length: {}", length)),
+        }
+    }
+
+    pub fn instrument_token(&self) -> u32 {
+        self.read_u32(0)
+    }
+
+    /// Indices carry OHLC and change but no volume or depth
+    pub fn is_index(&self) -> bool {
+        matches!(self.data.len(), INDEX_QUOTE_PACKET_LENGTH | INDEX_FULL_PACKET_LENGTH)
+    }
+
+    pub fn is_full(&self) -> bool {
+        matches!(self.data.len(), INDEX_FULL_PACKET_LENGTH | FULL_PACKET_LENGTH)
+    }
+
+    pub fn last_price(&self) -> f64 {
+        self.read_price(4)
+    }
+
+    pub fn last_quantity(&self) -> Option<u32> {
+        self.quote_field(8)
+    }
+
+    pub fn average_price(&self) -> Option<f64> {
+        self.quote_field(12).map(|raw| raw as f64 / self.divisor())
+    }
+
+    pub fn volume(&self) -> Option<u32> {
+        self.quote_field(16)
+    }
+
+    pub fn total_buy_quantity(&self) -> Option<u32> {
+        self.quote_field(20)
+    }
+
+    pub fn total_sell_quantity(&self) -> Option<u32> {
+        self.quote_field(24)
+    }
+
+    /// Day OHLC, absent in LTP mode
+    pub fn ohlc(&self) -> Option<Ohlc> {
+        if self.is_index() {
+            Some(Ohlc {
+                high: self.read_price(8),
+                low: self.read_price(12),
+                open: self.read_price(16),
+                close: self.read_price(20),
+            })
+        } else if self.data.len() >= QUOTE_PACKET_LENGTH {
+            Some(Ohlc {
+                open: self.read_price(28),
+                high: self.read_price(32),
+                low: self.read_price(36),
+                close: self.read_price(40),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Exchange timestamp in Unix seconds, sent in full mode
+    pub fn exchange_timestamp(&self) -> Option<u32> {
+        match self.data.len() {
+            INDEX_FULL_PACKET_LENGTH => Some(self.read_u32(28)),
+            FULL_PACKET_LENGTH => Some(self.read_u32(60)),
+            _ => None,
+        }
+    }
+
+    /// One of the five levels on a side of the book, best first, sent in full mode
+    pub fn depth(&self, side: DepthSide, level: usize) -> Option<DepthEntry> {
+        if self.data.len() != FULL_PACKET_LENGTH || level >= DEPTH_LEVELS {
+            return None;
+        }
+
+        let index = match side {
+            DepthSide::Bid => level,
+            DepthSide::Ask => DEPTH_LEVELS + level,
+        };
+        let offset = DEPTH_OFFSET + index * DEPTH_LEVEL_LENGTH;
+        Some(DepthEntry {
+            quantity: self.read_u32(offset),
+            price: self.read_price(offset + 4),
+            orders: u16::from_be_bytes([self.data[offset + 8], self.data[offset + 9]]),
+        })
+    }
+
+    /// Quote-mode field of a non-index packet
+    fn quote_field(&self, offset: usize) -> Option<u32> {
+        if self.is_index() || self.data.len() < QUOTE_PACKET_LENGTH {
+            return None;
+        }
+        Some(self.read_u32(offset))
+    }
+
+    /// Price units per rupee, by the exchange segment in the token's low byte
+    fn divisor(&self) -> f64 {
+        match self.instrument_token() & 0xff {
+            3 => 10_000_000.0, // CDS
+            6 => 10_000.0,     // BCD
+            _ => 100.0,
+        }
+    }
+
+    fn read_price(&self, offset: usize) -> f64 {
+        self.read_i32(offset) as f64 / self.divisor()
+    }
+
+    fn read_u32(&self, offset: usize) -> u32 {
+        u32::from_be_bytes([self.data[offset], self.data[offset + 1], self.data[offset + 2], self.data[offset + 3]])
+    }
+
+    fn read_i32(&self, offset: usize) -> i32 {
+        i32::from_be_bytes([self.data[offset], self.data[offset + 1], self.data[offset + 2], self.data[offset + 3]])
+    }
+}
+
+/// Iterator over the packets of one binary message
+///
+/// A message is a packet count followed by length-prefixed packets. A truncated
+/// message yields an error and ends the iteration.
+pub struct TickPackets<'a> {
+    data: &'a [u8],
+    offset: usize,
+    remaining: u16,
+}
+
+impl<'a> Iterator for TickPackets<'a> {
+    type Item = Result<TickPacket<'a>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let header_end = self.offset + 2;
+        if header_end > self.data.len() {
+            self.remaining = 0;
+            return Some(Err(anyhow!("Tick message ends before packet length")));
+        }
+        let length = u16::from_be_bytes([self.data[self.offset], self.data[self.offset + 1]]) as usize;
+
+        let packet_end = header_end + length;
+        if packet_end > self.data.len() {
+            self.remaining = 0;
+            return Some(Err(anyhow!("Tick message ends inside a {} byte packet", length)));
+        }
+        self.offset = packet_end;
+
+        Some(TickPacket::new(&self.data[header_end..packet_end]))
+    }
+}
+
+/// Packets of a binary ticker message; a one-byte heartbeat has none
+pub fn parse_packets(data: &[u8]) -> TickPackets<'_> {
+    let remaining = if data.len() >= 2 { u16::from_be_bytes([data[0], data[1]]) } else { 0 };
+    TickPackets { data, offset: 2, remaining }
+}
+
+impl TickData {
+    /// Owned copy of the fields sent to tick listeners
+    pub fn from_packet(packet: &TickPacket<'_>) -> Self {
+        let timestamp = packet.exchange_timestamp()
+            .and_then(|seconds| chrono::DateTime::from_timestamp(seconds as i64, 0))
+            .unwrap_or_else(chrono::Utc::now);
+
+        Self {
+            instrument_token: packet.instrument_token(),
+            last_price: packet.last_price(),
+            volume: packet.volume().unwrap_or(0),
+            buy_quantity: packet.total_buy_quantity().unwrap_or(0),
+            sell_quantity: packet.total_sell_quantity().unwrap_or(0),
+            ohlc: packet.ohlc(),
+            timestamp: timestamp.to_rfc3339(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn put(packet: &mut [u8], offset: usize, value: u32) {
+        packet[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    }
+
+    /// Full-mode packet for an NSE equity with prices in paise
+    fn full_packet(token: u32) -> Vec<u8> {
+        let mut packet = vec![0u8; FULL_PACKET_LENGTH];
+        let p = &mut packet;
+        put(p, 0, token);
+        put(p, 4, 150_050); // LTP 1500.50
+        put(p, 8, 25); // last quantity
+        put(p, 12, 149_900); // average price
+        put(p, 16, 1_000_000); // volume
+        put(p, 20, 40_000);
+        put(p, 24, 35_000);
+        put(p, 28, 149_000); // open
+        put(p, 32, 151_000); // high
+        put(p, 36, 148_500); // low
+        put(p, 40, 149_500); // close
+        put(p, 60, 1_706_155_200); // exchange timestamp
+        for level in 0..DEPTH_LEVELS as u32 {
+            let bid = DEPTH_OFFSET + level as usize * DEPTH_LEVEL_LENGTH;
+            let ask = bid + DEPTH_LEVELS * DEPTH_LEVEL_LENGTH;
+            put(p, bid, 100 + level);
+            put(p, bid + 4, 150_045 - level * 5);
+            put(p, ask, 200 + level);
+            put(p, ask + 4, 150_055 + level * 5);
+            p[bid + 9] = 3;
+            p[ask + 9] = 4;
+        }
+        packet
+    }
+
+    fn message(packets: &[Vec<u8>]) -> Vec<u8> {
+        let mut data = (packets.len() as u16).to_be_bytes().to_vec();
+        for packet in packets {
+            data.extend_from_slice(&(packet.len() as u16).to_be_bytes());
+            data.extend_from_slice(packet);
+        }
+        data
+    }
+
+    #[test]
+    fn test_parse_full_packet() {
+        let ltp = [&408065u32.to_be_bytes()[..], &150_100i32.to_be_bytes()[..]].concat();
+        let data = message(&[full_packet(408065), ltp]);
+        let packets: Vec<TickPacket> = parse_packets(&data).collect::<Result<_>>().unwrap();
+        assert_eq!(packets.len(), 2);
+
+        let full = packets[0];
+        assert_eq!(full.instrument_token(), 408065);
+        assert!(full.is_full() && !full.is_index());
+        assert_eq!(full.last_price(), 1500.5);
+        assert_eq!(full.volume(), Some(1_000_000));
+        assert_eq!(full.ohlc().unwrap().high, 1510.0);
+        assert_eq!(full.exchange_timestamp(), Some(1_706_155_200));
+        assert_eq!(full.depth(DepthSide::Bid, 0), Some(DepthEntry { quantity: 100, price: 1500.45, orders: 3 }));
+        assert_eq!(full.depth(DepthSide::Ask, 4).unwrap().price, 1500.75);
+        assert!(full.depth(DepthSide::Ask, 5).is_none());
+
+        // LTP packets have nothing beyond the price
+        assert_eq!(packets[1].last_price(), 1501.0);
+        assert!(packets[1].volume().is_none() && packets[1].ohlc().is_none());
+
+        let tick = TickData::from_packet(&full);
+        assert_eq!(tick.buy_quantity, 40_000);
+        assert_eq!(tick.timestamp, "2024-01-25T04:00:00+00:00");
+    }
+
+    #[test]
+    fn test_heartbeat_and_truncated_messages() {
+        assert_eq!(parse_packets(&[0]).count(), 0);
+
+        let mut data = message(&[full_packet(408065)]);
+        data.truncate(100);
+        let results: Vec<Result<TickPacket>> = parse_packets(&data).collect();
+        assert_eq!(results.len(), 1);
+        assert!(results[0].is_err());
+
+        assert!(TickPacket::new(&[0u8; 12]).is_err());
+    }
 }
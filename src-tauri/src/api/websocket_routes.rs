@@ -1,11 +1,15 @@
 use crate::error::{ApiResult, HedgeXError, Result};
-use crate::services::{WebSocketManager, MarketData, SubscriptionMode, ConnectionStatus};
+use crate::models::backtesting::{Timeframe, OHLCV};
+use crate::models::trading::MarketDepth;
+use crate::trading::SymbolMetrics;
+use crate::services::{ConnectionHealth, FeedHealth, WebSocketManager, MarketData, SubscriptionMode, SubscriptionInfo, ConnectionStatus};
 use axum::{
-    extract::{State, Path},
+    extract::{State, Path, Query},
     routing::{get, post},
     Json, Router,
 };
 use serde::{Deserialize, Serialize};
+use chrono::Utc;
 use std::sync::Arc;
 use tracing::{debug, info};
 
@@ -27,18 +31,37 @@ pub struct SubscriptionRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MarketDataResponse {
     pub data: Vec<MarketData>,
+    /// Symbols whose cached price is older than the stale data threshold
+    pub stale_symbols: Vec<String>,
+}
+
+/// Live candle query parameters
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CandleQuery {
+    #[serde(default)]
+    pub timeframe: Option<String>,
+    #[serde(default)]
+    pub count: Option<usize>,
 }
 
 /// Create WebSocket routes
 pub fn websocket_routes() -> Router<Arc<WebSocketManager>> {
     Router::new()
         .route("/status", get(get_websocket_status))
+        .route("/health", get(get_feed_health))
+        .route("/connections", get(get_connection_health))
         .route("/connect", post(connect_websocket))
         .route("/disconnect", post(disconnect_websocket))
         .route("/subscribe", post(subscribe_instruments))
         .route("/unsubscribe", post(unsubscribe_instruments))
+        .route("/mode", post(set_subscription_mode))
+        .route("/subscriptions", get(list_subscriptions))
         .route("/market-data", get(get_market_data))
         .route("/market-data/:instrument_token", get(get_instrument_market_data))
+        .route("/depth/:symbol", get(get_market_depth))
+        .route("/candles/:symbol", get(get_recent_candles))
+        .route("/metrics", get(get_all_live_metrics))
+        .route("/metrics/:symbol", get(get_live_metrics))
 }
 
 /// Get WebSocket connection status
@@ -68,6 +91,24 @@ async fn get_websocket_status(
     Json(ApiResult::success(response))
 }
 
+/// Get tick rate, tick ages, parse errors and reconnects of the feed
+async fn get_feed_health(
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> Json<ApiResult<FeedHealth>> {
+    debug!("Getting feed health");
+    
+    Json(ApiResult::success(ws_manager.get_feed_health().await))
+}
+
+/// Get the status, load and message counts of each ticker connection
+async fn get_connection_health(
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> Json<ApiResult<Vec<ConnectionHealth>>> {
+    debug!("Getting ticker connection health");
+    
+    Json(ApiResult::success(ws_manager.get_connection_health().await))
+}
+
 /// Connect to WebSocket
 async fn connect_websocket(
     State(ws_manager): State<Arc<WebSocketManager>>,
@@ -100,15 +141,9 @@ async fn subscribe_instruments(
     info!("Subscribing to {} instruments", request.instrument_tokens.len());
     
     // Convert mode string to enum
-    let mode = match request.mode.to_lowercase().as_str() {
-        "ltp" => SubscriptionMode::LTP,
-        "quote" => SubscriptionMode::Quote,
-        "full" => SubscriptionMode::Full,
-        _ => {
-            return Json(ApiResult::from_error(HedgeXError::ValidationError(
-                "Invalid subscription mode. Must be one of: ltp, quote, full".to_string(),
-            )));
-        }
+    let mode = match request.mode.parse::<SubscriptionMode>() {
+        Ok(mode) => mode,
+        Err(e) => return Json(ApiResult::from_error(HedgeXError::ValidationError(e))),
     };
     
     match ws_manager.subscribe_to_instruments(request.instrument_tokens, mode).await {
@@ -130,6 +165,33 @@ async fn unsubscribe_instruments(
     }
 }
 
+/// Switch subscribed instruments to another mode
+async fn set_subscription_mode(
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    Json(request): Json<SubscriptionRequest>,
+) -> Json<ApiResult<Vec<u64>>> {
+    info!("Switching {} instruments to {} mode", request.instrument_tokens.len(), request.mode);
+    
+    let mode = match request.mode.parse::<SubscriptionMode>() {
+        Ok(mode) => mode,
+        Err(e) => return Json(ApiResult::from_error(HedgeXError::ValidationError(e))),
+    };
+    
+    match ws_manager.set_instruments_mode(&request.instrument_tokens, mode).await {
+        Ok(changed) => Json(ApiResult::success(changed)),
+        Err(e) => Json(ApiResult::from_error(e)),
+    }
+}
+
+/// List current subscriptions
+async fn list_subscriptions(
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> Json<ApiResult<Vec<SubscriptionInfo>>> {
+    debug!("Listing subscriptions");
+    
+    Json(ApiResult::success(ws_manager.list_subscriptions().await))
+}
+
 /// Get all cached market data
 async fn get_market_data(
     State(ws_manager): State<Arc<WebSocketManager>>,
@@ -138,8 +200,9 @@ async fn get_market_data(
     
     let market_data = ws_manager.get_all_cached_market_data().await;
     let data: Vec<MarketData> = market_data.values().cloned().collect();
+    let stale_symbols = ws_manager.get_price_freshness().await.stale_symbols(Utc::now());
     
-    Json(ApiResult::success(MarketDataResponse { data }))
+    Json(ApiResult::success(MarketDataResponse { data, stale_symbols }))
 }
 
 /// Get market data for a specific instrument
@@ -151,4 +214,57 @@ async fn get_instrument_market_data(
     
     let market_data = ws_manager.get_cached_market_data(instrument_token).await;
     Json(ApiResult::success(market_data))
-}
\ No newline at end of file
+}
+
+/// Get the latest order book depth for a symbol
+async fn get_market_depth(
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    Path(symbol): Path<String>,
+) -> Json<ApiResult<Option<MarketDepth>>> {
+    debug!("Getting market depth for {}", symbol);
+    
+    let depth = ws_manager.get_market_depth(&symbol).await;
+    Json(ApiResult::success(depth))
+}
+
+/// Get spread, VWAP and rolling volume of every symbol that has ticked
+async fn get_all_live_metrics(
+    State(ws_manager): State<Arc<WebSocketManager>>,
+) -> Json<ApiResult<Vec<SymbolMetrics>>> {
+    debug!("Getting live metrics");
+    
+    Json(ApiResult::success(ws_manager.get_all_live_metrics().await))
+}
+
+/// Get spread, VWAP and rolling volume of a symbol
+async fn get_live_metrics(
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    Path(symbol): Path<String>,
+) -> Json<ApiResult<SymbolMetrics>> {
+    debug!("Getting live metrics for {}", symbol);
+    
+    match ws_manager.get_live_metrics(&symbol).await {
+        Some(metrics) => Json(ApiResult::success(metrics)),
+        None => Json(ApiResult::from_error(HedgeXError::NotFoundError(format!("No live metrics for {}", symbol)))),
+    }
+}
+
+/// Get recent candles built from live ticks for a symbol
+async fn get_recent_candles(
+    State(ws_manager): State<Arc<WebSocketManager>>,
+    Path(symbol): Path<String>,
+    Query(query): Query<CandleQuery>,
+) -> Json<ApiResult<Vec<OHLCV>>> {
+    debug!("Getting live candles for {}", symbol);
+    
+    let timeframe = match query.timeframe.as_deref().unwrap_or("1m").parse::<Timeframe>() {
+        Ok(timeframe) => timeframe,
+        Err(e) => return Json(ApiResult::from_error(HedgeXError::ValidationError(e))),
+    };
+    
+    let count = query.count.unwrap_or(100).min(crate::trading::bars::MAX_BARS);
+    match ws_manager.get_recent_candles(&symbol, timeframe, count).await {
+        Ok(candles) => Json(ApiResult::success(candles)),
+        Err(e) => Json(ApiResult::from_error(e)),
+    }
+}
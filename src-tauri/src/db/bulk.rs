@@ -0,0 +1,66 @@
+use crate::error::{HedgeXError, Result};
+use sqlx::query_builder::Separated;
+use sqlx::{QueryBuilder, Sqlite, SqliteConnection};
+
+/// Most bind parameters SQLite accepts in one statement
+const SQLITE_MAX_BIND_PARAMS: usize = 32_766;
+
+/// Insert rows with as few multi-row statements as the bind parameter limit allows
+///
+/// `insert` is the statement up to `VALUES`, e.g. `"INSERT INTO trades (id, symbol) "`, and
+/// `bind` pushes the `columns` values of one row. Run it on a transaction so a batch spanning
+/// several statements is stored whole or not at all. Returns the number of rows written.
+pub async fn insert_rows<'a, T, F>(
+    conn: &mut SqliteConnection,
+    insert: &str,
+    columns: usize,
+    rows: &'a [T],
+    mut bind: F,
+) -> Result<u64>
+where
+    T: Sync,
+    F: FnMut(&mut Separated<'_, 'a, Sqlite, &'static str>, &'a T) + Send,
+{
+    let rows_per_statement = (SQLITE_MAX_BIND_PARAMS / columns.max(1)).max(1);
+    let mut written = 0;
+
+    for chunk in rows.chunks(rows_per_statement) {
+        let mut query = QueryBuilder::<Sqlite>::new(insert);
+        query.push_values(chunk, |mut values, row| bind(&mut values, row));
+        written += query.build()
+            .execute(&mut *conn)
+            .await
+            .map_err(HedgeXError::DatabaseError)?
+            .rows_affected();
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Executor;
+
+    #[tokio::test]
+    async fn test_insert_rows_spans_statements() {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        pool.execute("CREATE TABLE quotes (symbol TEXT NOT NULL, price REAL NOT NULL, volume INTEGER NOT NULL)").await.unwrap();
+
+        // More rows than fit in one statement at three binds each
+        let rows: Vec<(String, f64, i64)> = (0..12_000).map(|i| (format!("SYM{}", i % 50), i as f64 / 4.0, i)).collect();
+        let mut tx = pool.begin().await.unwrap();
+        let written = insert_rows(&mut tx, "INSERT INTO quotes (symbol, price, volume) ", 3, &rows, |values, (symbol, price, volume)| {
+            values.push_bind(symbol).push_bind(*price).push_bind(*volume);
+        }).await.unwrap();
+        tx.commit().await.unwrap();
+
+        assert_eq!(written, 12_000);
+        let (count, total): (i64, i64) = sqlx::query_as("SELECT COUNT(*), SUM(volume) FROM quotes").fetch_one(&pool).await.unwrap();
+        assert_eq!(count, 12_000);
+        assert_eq!(total, (0..12_000).sum::<i64>());
+
+        let mut tx = pool.begin().await.unwrap();
+        let none: &[(String, f64, i64)] = &[];
+        assert_eq!(insert_rows(&mut tx, "INSERT INTO quotes (symbol, price, volume) ", 3, none, |_, _| {}).await.unwrap(), 0);
+    }
+}
@@ -0,0 +1,266 @@
+use crate::error::{HedgeXError, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::migrate::Migrator;
+use sqlx::{Pool, Row, Sqlite};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// File name prefix of the snapshots taken before migrations run at startup
+pub const PRE_MIGRATION_SNAPSHOT_PREFIX: &str = "hedgex_pre_migration_";
+
+/// Where a migration stands against the database
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MigrationState {
+    Pending,
+    Applied,
+    /// Recorded as started but never finished
+    Failed,
+    /// Applied, but the file has changed since
+    ChecksumMismatch,
+    /// Applied by a newer build whose migration file is not present
+    MissingFile,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MigrationInfo {
+    pub version: i64,
+    pub description: String,
+    pub state: MigrationState,
+    pub installed_on: Option<DateTime<Utc>>,
+    /// Whether a down script can revert it
+    pub reversible: bool,
+}
+
+/// What a repair changed in the migration history
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MigrationRepair {
+    /// Applied migrations whose recorded checksum now matches the file
+    pub checksums_updated: Vec<i64>,
+    /// Unfinished runs removed so the migrations run again
+    pub failed_cleared: Vec<i64>,
+}
+
+struct AppliedMigration {
+    version: i64,
+    description: String,
+    installed_on: DateTime<Utc>,
+    success: bool,
+    checksum: Vec<u8>,
+}
+
+/// Migrations recorded in `_sqlx_migrations`, empty before the first run
+async fn applied_migrations(pool: &Pool<Sqlite>) -> Result<Vec<AppliedMigration>> {
+    let has_table = sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'")
+        .fetch_optional(pool)
+        .await
+        .map_err(HedgeXError::DatabaseError)?
+        .is_some();
+    if !has_table {
+        return Ok(Vec::new());
+    }
+
+    let rows = sqlx::query("SELECT version, description, installed_on, success, checksum FROM _sqlx_migrations ORDER BY version")
+        .fetch_all(pool)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+    Ok(rows.iter()
+        .map(|row| AppliedMigration {
+            version: row.get("version"),
+            description: row.get("description"),
+            installed_on: row.get("installed_on"),
+            success: row.get("success"),
+            checksum: row.get("checksum"),
+        })
+        .collect())
+}
+
+fn is_reversible(migrator: &Migrator, version: i64) -> bool {
+    migrator.iter().any(|m| m.version == version && m.migration_type.is_down_migration())
+}
+
+/// Every migration known to the files or the database, oldest first
+pub async fn migration_status(pool: &Pool<Sqlite>, migrator: &Migrator) -> Result<Vec<MigrationInfo>> {
+    let applied = applied_migrations(pool).await?;
+    let mut status: Vec<MigrationInfo> = migrator.iter()
+        .filter(|m| !m.migration_type.is_down_migration())
+        .map(|m| {
+            let record = applied.iter().find(|a| a.version == m.version);
+            let state = match record {
+                None => MigrationState::Pending,
+                Some(a) if !a.success => MigrationState::Failed,
+                Some(a) if a.checksum != m.checksum.as_ref() => MigrationState::ChecksumMismatch,
+                Some(_) => MigrationState::Applied,
+            };
+            MigrationInfo {
+                version: m.version,
+                description: m.description.to_string(),
+                state,
+                installed_on: record.map(|a| a.installed_on),
+                reversible: is_reversible(migrator, m.version),
+            }
+        })
+        .collect();
+
+    for a in &applied {
+        if !status.iter().any(|m| m.version == a.version) {
+            status.push(MigrationInfo {
+                version: a.version,
+                description: a.description.clone(),
+                state: MigrationState::MissingFile,
+                installed_on: Some(a.installed_on),
+                reversible: false,
+            });
+        }
+    }
+    status.sort_by_key(|m| m.version);
+    Ok(status)
+}
+
+/// Versions not yet applied
+pub async fn pending_migrations(pool: &Pool<Sqlite>, migrator: &Migrator) -> Result<Vec<i64>> {
+    Ok(migration_status(pool, migrator).await?
+        .into_iter()
+        .filter(|m| m.state == MigrationState::Pending)
+        .map(|m| m.version)
+        .collect())
+}
+
+/// Revert applied migrations newer than `target`, newest first, returning the reverted versions
+///
+/// Refuses when any of them has no down script; restoring the pre-migration backup is the way
+/// back from those.
+pub async fn migrate_down(pool: &Pool<Sqlite>, migrator: &Migrator, target: i64) -> Result<Vec<i64>> {
+    let to_revert: Vec<i64> = applied_migrations(pool).await?
+        .into_iter()
+        .filter(|a| a.version > target)
+        .map(|a| a.version)
+        .collect();
+
+    let irreversible: Vec<String> = to_revert.iter()
+        .filter(|version| !is_reversible(migrator, **version))
+        .map(|version| version.to_string())
+        .collect();
+    if !irreversible.is_empty() {
+        return Err(HedgeXError::ValidationError(format!(
+            "Migrations {} have no down script; restore the pre-migration backup instead",
+            irreversible.join(", ")
+        )));
+    }
+
+    migrator.undo(pool, target).await
+        .map_err(|e| HedgeXError::DatabaseError(sqlx::Error::Migrate(Box::new(e))))?;
+    info!("Reverted migrations {:?}", to_revert);
+    Ok(to_revert.into_iter().rev().collect())
+}
+
+/// Accept edited migration files and clear unfinished runs so migrations can run again
+pub async fn repair_migrations(pool: &Pool<Sqlite>, migrator: &Migrator) -> Result<MigrationRepair> {
+    let mut repair = MigrationRepair::default();
+    for a in applied_migrations(pool).await? {
+        if !a.success {
+            sqlx::query("DELETE FROM _sqlx_migrations WHERE version = ?")
+                .bind(a.version)
+                .execute(pool)
+                .await
+                .map_err(HedgeXError::DatabaseError)?;
+            repair.failed_cleared.push(a.version);
+            continue;
+        }
+
+        let file = migrator.iter().find(|m| m.version == a.version && !m.migration_type.is_down_migration());
+        if let Some(file) = file.filter(|file| file.checksum.as_ref() != a.checksum) {
+            sqlx::query("UPDATE _sqlx_migrations SET checksum = ? WHERE version = ?")
+                .bind(file.checksum.as_ref())
+                .bind(a.version)
+                .execute(pool)
+                .await
+                .map_err(HedgeXError::DatabaseError)?;
+            repair.checksums_updated.push(a.version);
+        }
+    }
+    Ok(repair)
+}
+
+/// Copy the database into `backup_dir` when migrations are about to change an existing schema
+///
+/// A fresh database has nothing to lose, so no snapshot is taken before its first migrations.
+pub async fn snapshot_before_migrations(pool: &Pool<Sqlite>, migrator: &Migrator, backup_dir: &Path) -> Result<Option<PathBuf>> {
+    let has_applied = !applied_migrations(pool).await?.is_empty();
+    if !has_applied || pending_migrations(pool, migrator).await?.is_empty() {
+        return Ok(None);
+    }
+
+    std::fs::create_dir_all(backup_dir)
+        .map_err(|e| HedgeXError::InternalError(format!("Failed to create backup directory: {}", e)))?;
+    let path = backup_dir.join(format!("{}{}.db", PRE_MIGRATION_SNAPSHOT_PREFIX, Utc::now().format("%Y%m%d_%H%M%S")));
+    sqlx::query(&format!("VACUUM INTO '{}'", path.to_string_lossy().replace('\'', "''")))
+        .execute(pool)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+    Ok(Some(path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    async fn migrator_for(files: &[(&str, &str)]) -> (Migrator, TempDir) {
+        let dir = TempDir::new().unwrap();
+        for (name, sql) in files {
+            std::fs::write(dir.path().join(name), sql).unwrap();
+        }
+        let migrator = Migrator::new(dir.path()).await.unwrap();
+        (migrator, dir)
+    }
+
+    #[tokio::test]
+    async fn test_migration_status_and_repair() {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        let (migrator, dir) = migrator_for(&[
+            ("1_create_notes.sql", "CREATE TABLE notes (id INTEGER PRIMARY KEY);"),
+        ]).await;
+        assert_eq!(pending_migrations(&pool, &migrator).await.unwrap(), vec![1]);
+
+        migrator.run(&pool).await.unwrap();
+        let status = migration_status(&pool, &migrator).await.unwrap();
+        assert_eq!(status[0].state, MigrationState::Applied);
+        assert!(!status[0].reversible);
+
+        // Editing an applied migration is flagged, and a repair accepts the new file
+        std::fs::write(dir.path().join("1_create_notes.sql"), "CREATE TABLE notes (id INTEGER PRIMARY KEY); -- edited").unwrap();
+        std::fs::write(dir.path().join("2_add_body.sql"), "ALTER TABLE notes ADD COLUMN body TEXT;").unwrap();
+        let edited = Migrator::new(dir.path()).await.unwrap();
+        let states: Vec<MigrationState> = migration_status(&pool, &edited).await.unwrap().into_iter().map(|m| m.state).collect();
+        assert_eq!(states, vec![MigrationState::ChecksumMismatch, MigrationState::Pending]);
+
+        let repair = repair_migrations(&pool, &edited).await.unwrap();
+        assert_eq!(repair.checksums_updated, vec![1]);
+        edited.run(&pool).await.unwrap();
+        assert!(pending_migrations(&pool, &edited).await.unwrap().is_empty());
+
+        // Neither migration has a down script
+        assert!(migrate_down(&pool, &edited, 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_migrate_down_reversible() {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        let (migrator, _dir) = migrator_for(&[
+            ("1_create_notes.up.sql", "CREATE TABLE notes (id INTEGER PRIMARY KEY);"),
+            ("1_create_notes.down.sql", "DROP TABLE notes;"),
+            ("2_create_tags.up.sql", "CREATE TABLE tags (id INTEGER PRIMARY KEY);"),
+            ("2_create_tags.down.sql", "DROP TABLE tags;"),
+        ]).await;
+        migrator.run(&pool).await.unwrap();
+
+        assert_eq!(migrate_down(&pool, &migrator, 1).await.unwrap(), vec![2]);
+        let states: Vec<MigrationState> = migration_status(&pool, &migrator).await.unwrap().into_iter().map(|m| m.state).collect();
+        assert_eq!(states, vec![MigrationState::Applied, MigrationState::Pending]);
+
+        let tags = sqlx::query("SELECT 1 FROM sqlite_master WHERE name = 'tags'").fetch_optional(&pool).await.unwrap();
+        assert!(tags.is_none());
+    }
+}
@@ -1,13 +1,23 @@
-use sqlx::{sqlite::SqlitePoolOptions, Pool, Sqlite, Executor, migrate::Migrator};
+use sqlx::{sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous}, Pool, Sqlite, Executor, migrate::Migrator};
+use std::str::FromStr;
 use std::path::{Path, PathBuf};
 use anyhow::{Result, Context};
 use std::fs as std_fs;
 use tracing::{info, warn, error, debug};
 use crate::error::{HedgeXError, Result as HedgeXResult};
 
+pub mod bulk;
+pub mod migrations;
+pub mod storage;
+
+pub use migrations::{MigrationInfo, MigrationRepair, MigrationState};
+pub use storage::{StorageBackend, TickStore};
+
 /// Enhanced database connection pool for SQLite with migration support
 pub struct Database {
     pub pool: Pool<Sqlite>,
+    /// Read-only pool for analytics and exports, the write pool itself when none is configured
+    pub read_pool: Pool<Sqlite>,
     pub migrator: Option<Migrator>,
 }
 
@@ -15,6 +25,7 @@ impl Clone for Database {
     fn clone(&self) -> Self {
         Self {
             pool: self.pool.clone(),
+            read_pool: self.read_pool.clone(),
             migrator: None, // Migrator doesn't need to be cloned for most use cases
         }
     }
@@ -29,24 +40,112 @@ impl std::fmt::Debug for Database {
     }
 }
 
+/// Environment variable choosing the database tuning preset, `durability` or `throughput`
+pub const DB_PROFILE_VAR: &str = "HEDGEX_DB_PROFILE";
+
+/// Environment variable overriding the write pool size of the preset
+pub const DB_WRITE_CONNECTIONS_VAR: &str = "HEDGEX_DB_WRITE_CONNECTIONS";
+
+/// Environment variable overriding the read pool size of the preset
+pub const DB_READ_CONNECTIONS_VAR: &str = "HEDGEX_DB_READ_CONNECTIONS";
+
 /// Database configuration options
 #[derive(Debug, Clone)]
 pub struct DatabaseConfig {
+    /// Connections in the pool the trading engine writes through
     pub max_connections: u32,
+    /// Connections in the read-only pool analytics and exports query, 0 to share the write pool
+    pub read_connections: u32,
     pub connection_timeout_secs: u64,
     pub idle_timeout_secs: u64,
     pub enable_wal_mode: bool,
     pub enable_foreign_keys: bool,
+    pub synchronous: SqliteSynchronous,
+    /// Page cache per connection
+    pub cache_size_kib: u32,
+    /// How long a connection waits on a locked database before failing
+    pub busy_timeout_ms: u64,
 }
 
 impl Default for DatabaseConfig {
     fn default() -> Self {
+        Self::durability()
+    }
+}
+
+impl DatabaseConfig {
+    /// Every commit reaches the disk before it returns, surviving power loss
+    pub fn durability() -> Self {
         Self {
-            max_connections: 10,
+            // SQLite takes one writer at a time, so more write connections only queue on its lock
+            max_connections: 1,
+            read_connections: 4,
             connection_timeout_secs: 30,
             idle_timeout_secs: 600, // 10 minutes
             enable_wal_mode: true,
             enable_foreign_keys: true,
+            synchronous: SqliteSynchronous::Full,
+            cache_size_kib: 8 * 1024,
+            busy_timeout_ms: 5_000,
+        }
+    }
+    
+    /// Faster commits for tick ingestion; a power loss can drop the last commits but never corrupts the database
+    pub fn throughput() -> Self {
+        Self {
+            synchronous: SqliteSynchronous::Normal,
+            cache_size_kib: 64 * 1024,
+            busy_timeout_ms: 15_000,
+            ..Self::durability()
+        }
+    }
+    
+    /// Preset named by `HEDGEX_DB_PROFILE`, durability when it is unset or unknown, with pool sizes
+    /// from `HEDGEX_DB_WRITE_CONNECTIONS` and `HEDGEX_DB_READ_CONNECTIONS` when set
+    pub fn from_env() -> Self {
+        let mut config = match std::env::var(DB_PROFILE_VAR).ok().as_deref().map(str::trim) {
+            Some("throughput") => Self::throughput(),
+            Some("durability") | None => Self::durability(),
+            Some(other) => {
+                warn!("Unknown {} '{}', using durability", DB_PROFILE_VAR, other);
+                Self::durability()
+            }
+        };
+        if let Some(connections) = pool_size_from_env(DB_WRITE_CONNECTIONS_VAR) {
+            config.max_connections = connections.max(1);
+        }
+        if let Some(connections) = pool_size_from_env(DB_READ_CONNECTIONS_VAR) {
+            config.read_connections = connections;
+        }
+        config
+    }
+    
+    /// Connection options for a database URL with the configured pragmas applied on connect
+    pub fn connect_options(&self, db_url: &str) -> std::result::Result<SqliteConnectOptions, sqlx::Error> {
+        let journal_mode = if self.enable_wal_mode { SqliteJournalMode::Wal } else { SqliteJournalMode::Delete };
+        Ok(SqliteConnectOptions::from_str(db_url)?
+            .journal_mode(journal_mode)
+            .foreign_keys(self.enable_foreign_keys)
+            .synchronous(self.synchronous)
+            .busy_timeout(std::time::Duration::from_millis(self.busy_timeout_ms))
+            // A negative cache_size is in KiB rather than pages
+            .pragma("cache_size", format!("-{}", self.cache_size_kib)))
+    }
+    
+    /// Connection options for the read pool, which cannot write even by mistake
+    pub fn read_options(&self, db_url: &str) -> std::result::Result<SqliteConnectOptions, sqlx::Error> {
+        Ok(self.connect_options(db_url)?.read_only(true))
+    }
+}
+
+/// Pool size set in an environment variable, ignoring values that are not a number
+fn pool_size_from_env(var: &str) -> Option<u32> {
+    let value = std::env::var(var).ok()?;
+    match value.trim().parse() {
+        Ok(connections) => Some(connections),
+        Err(_) => {
+            warn!("Ignoring {} '{}', expected a number of connections", var, value);
+            None
         }
     }
 }
@@ -54,7 +153,7 @@ impl Default for DatabaseConfig {
 impl Database {
     /// Initialize a new database connection with default configuration
     pub async fn new(app_data_dir: &Path) -> Result<Self> {
-        Self::new_with_config(app_data_dir, DatabaseConfig::default()).await
+        Self::new_with_config(app_data_dir, DatabaseConfig::from_env()).await
     }
     
     /// Initialize a new database connection with custom configuration
@@ -128,10 +227,12 @@ impl Database {
         // Create connection pool with minimal configuration for debugging
         info!("Attempting to connect to SQLite database with URL: {}", db_url);
         
+        let connect_options = config.connect_options(&db_url)
+            .with_context(|| format!("Invalid database URL: {}", db_url))?;
         let pool = match SqlitePoolOptions::new()
-            .max_connections(1)
+            .max_connections(config.max_connections)
             .acquire_timeout(std::time::Duration::from_secs(10))
-            .connect(&db_url)
+            .connect_with(connect_options)
             .await
         {
             Ok(pool) => {
@@ -164,6 +265,13 @@ impl Database {
 
         // Run migrations if migrator is available
         if let Some(ref m) = migrator {
+            // Keep a copy of an existing database before migrations change its schema
+            match migrations::snapshot_before_migrations(&pool, m, &app_data_dir.join("backups")).await {
+                Ok(Some(path)) => info!("Saved pre-migration snapshot to {:?}", path),
+                Ok(None) => {}
+                Err(e) => warn!("Failed to save pre-migration snapshot: {}", e),
+            }
+            
             info!("Running database migrations...");
             m.run(&pool).await
                 .map_err(|e| anyhow::anyhow!("Failed to run database migrations: {}", e))?;
@@ -181,21 +289,37 @@ impl Database {
         // Verify database integrity
         Self::verify_database_integrity(&pool).await?;
 
-        Ok(Self { pool, migrator })
+        // Opened after migrations so readers never see a half-migrated schema
+        let read_pool = if config.read_connections == 0 {
+            pool.clone()
+        } else {
+            SqlitePoolOptions::new()
+                .max_connections(config.read_connections)
+                .acquire_timeout(std::time::Duration::from_secs(config.connection_timeout_secs))
+                .idle_timeout(std::time::Duration::from_secs(config.idle_timeout_secs))
+                .connect_with(config.read_options(&db_url)?)
+                .await
+                .context("Failed to open the read-only database pool")?
+        };
+        info!("Database pools ready: {} write, {} read connections", config.max_connections, config.read_connections);
+
+        Ok(Self { pool, read_pool, migrator })
     }
     
-    /// Initialize the migration system
-    async fn initialize_migrator() -> Result<Option<Migrator>> {
-        // Try to find migrations in different locations
-        let migration_paths: Vec<PathBuf> = vec![
+    /// Places the migrations directory is looked for, in order
+    pub(crate) fn migration_paths() -> Vec<PathBuf> {
+        vec![
             PathBuf::from("./migrations"),                 // Root project dir
             PathBuf::from("./src-tauri/migrations"),       // Src-tauri dir
             PathBuf::from("../migrations"),                // One level up (for debug builds)
             PathBuf::from(std::env::var("CARGO_MANIFEST_DIR").unwrap_or_default()).join("migrations"), // Cargo manifest dir
-        ];
-
+        ]
+    }
+    
+    /// Initialize the migration system
+    pub(crate) async fn initialize_migrator() -> Result<Option<Migrator>> {
         // Try each migration path until one works
-        for path in migration_paths {
+        for path in Self::migration_paths() {
             if path.exists() {
                 debug!("Found migrations at: {:?}", path);
                 match Migrator::new(&*path).await {
@@ -247,6 +371,11 @@ impl Database {
         &self.pool
     }
     
+    /// Pool for analytics and export queries, kept apart from the trading engine's writes
+    pub fn get_read_pool(&self) -> &Pool<Sqlite> {
+        &self.read_pool
+    }
+    
     /// Get the migrator if available
     pub fn get_migrator(&self) -> Option<&Migrator> {
         self.migrator.as_ref()
@@ -283,6 +412,7 @@ impl Database {
             
         Ok(DatabaseStats {
             pool_size: pool_stats,
+            read_pool_size: self.read_pool.size(),
             database_size_bytes: db_size.0,
             table_count: table_count.0,
         })
@@ -311,6 +441,7 @@ impl Database {
     /// Close the database connection pool gracefully
     pub async fn close(self) {
         info!("Closing database connection pool...");
+        self.read_pool.close().await;
         self.pool.close().await;
         info!("Database connection pool closed");
     }
@@ -320,6 +451,7 @@ impl Database {
 #[derive(Debug, Clone)]
 pub struct DatabaseStats {
     pub pool_size: u32,
+    pub read_pool_size: u32,
     pub database_size_bytes: i64,
     pub table_count: i64,
 }
@@ -339,3 +471,59 @@ pub enum DatabaseError {
     #[error("Record not found")]
     NotFound,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[tokio::test]
+    async fn test_connect_options_apply_pragmas() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_url = format!("sqlite:{}?mode=rwc", temp_dir.path().join("tuning.db").to_string_lossy());
+        let config = DatabaseConfig { enable_foreign_keys: false, ..DatabaseConfig::throughput() };
+        let pool = SqlitePoolOptions::new()
+            .connect_with(config.connect_options(&db_url).unwrap())
+            .await
+            .unwrap();
+
+        let journal_mode: String = sqlx::query_scalar("PRAGMA journal_mode").fetch_one(&pool).await.unwrap();
+        let synchronous: i64 = sqlx::query_scalar("PRAGMA synchronous").fetch_one(&pool).await.unwrap();
+        let cache_size: i64 = sqlx::query_scalar("PRAGMA cache_size").fetch_one(&pool).await.unwrap();
+        let busy_timeout: i64 = sqlx::query_scalar("PRAGMA busy_timeout").fetch_one(&pool).await.unwrap();
+        let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys").fetch_one(&pool).await.unwrap();
+
+        assert_eq!(journal_mode, "wal");
+        assert_eq!(synchronous, 1); // NORMAL
+        assert_eq!(cache_size, -64 * 1024);
+        assert_eq!(busy_timeout, 15_000);
+        assert_eq!(foreign_keys, 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_pool_is_read_only() {
+        let temp_dir = TempDir::new().unwrap();
+        let db_url = format!("sqlite:{}?mode=rwc", temp_dir.path().join("read.db").to_string_lossy());
+        let config = DatabaseConfig::default();
+        let write_pool = SqlitePoolOptions::new()
+            .max_connections(config.max_connections)
+            .connect_with(config.connect_options(&db_url).unwrap())
+            .await
+            .unwrap();
+        write_pool.execute("CREATE TABLE trades (id TEXT PRIMARY KEY); INSERT INTO trades VALUES ('t1');").await.unwrap();
+
+        let read_pool = SqlitePoolOptions::new()
+            .max_connections(config.read_connections)
+            .connect_with(config.read_options(&db_url).unwrap())
+            .await
+            .unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM trades").fetch_one(&read_pool).await.unwrap();
+        assert_eq!(count, 1);
+        assert!(sqlx::query("INSERT INTO trades VALUES ('t2')").execute(&read_pool).await.is_err());
+
+        // Commits on the write pool are visible to readers
+        write_pool.execute("INSERT INTO trades VALUES ('t3')").await.unwrap();
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM trades").fetch_one(&read_pool).await.unwrap();
+        assert_eq!(count, 2);
+    }
+}
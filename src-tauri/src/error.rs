@@ -47,6 +47,9 @@ pub enum HedgeXError {
     #[error("Rate limit exceeded: {0}")]
     RateLimitError(String),
     
+    #[error("Client rate limit for {category} requests exceeded, retry in {retry_after_ms}ms")]
+    RateLimitExceeded { category: String, retry_after_ms: u64 },
+    
     #[error("Session expired or invalid")]
     SessionError,
     
@@ -83,6 +86,24 @@ pub enum HedgeXError {
     #[error("External service error: {0}")]
     ExternalServiceError(String),
     
+    #[error("Circuit breaker is open: {0}")]
+    CircuitOpen(String),
+    
+    #[error("Kite session expired or invalid, log in to Zerodha again ({0})")]
+    TokenExpired(String),
+    
+    #[error("Insufficient margin, add funds or reduce the order size ({0})")]
+    InsufficientMargin(String),
+    
+    #[error("Holdings not available to sell, check your demat holdings or authorise the sale with your CDSL TPIN ({0})")]
+    InsufficientHoldings(String),
+    
+    #[error("Order rejected by Kite: {0}")]
+    OrderRejected(String),
+    
+    #[error("Kite could not reach the exchange, try again shortly ({0})")]
+    BrokerUnavailable(String),
+    
     #[error("Compression error: {0}")]
     CompressionError(String),
 }
@@ -139,6 +160,7 @@ impl<T> ApiResult<T> {
             HedgeXError::SerializationError(_) => Some("SERIALIZATION_ERROR".to_string()),
             HedgeXError::ValidationError(_) => Some("VALIDATION_ERROR".to_string()),
             HedgeXError::RateLimitError(_) => Some("RATE_LIMIT_ERROR".to_string()),
+            HedgeXError::RateLimitExceeded { .. } => Some("RATE_LIMIT_EXCEEDED".to_string()),
             HedgeXError::SessionError => Some("SESSION_ERROR".to_string()),
             HedgeXError::PermissionError(_) => Some("PERMISSION_ERROR".to_string()),
             HedgeXError::NotFoundError(_) => Some("NOT_FOUND_ERROR".to_string()),
@@ -151,6 +173,12 @@ impl<T> ApiResult<T> {
             HedgeXError::ConcurrencyError(_) => Some("CONCURRENCY_ERROR".to_string()),
             HedgeXError::DataIntegrityError(_) => Some("DATA_INTEGRITY_ERROR".to_string()),
             HedgeXError::ExternalServiceError(_) => Some("EXTERNAL_SERVICE_ERROR".to_string()),
+            HedgeXError::CircuitOpen(_) => Some("CIRCUIT_OPEN".to_string()),
+            HedgeXError::TokenExpired(_) => Some("TOKEN_EXPIRED".to_string()),
+            HedgeXError::InsufficientMargin(_) => Some("INSUFFICIENT_MARGIN".to_string()),
+            HedgeXError::InsufficientHoldings(_) => Some("INSUFFICIENT_HOLDINGS".to_string()),
+            HedgeXError::OrderRejected(_) => Some("ORDER_REJECTED".to_string()),
+            HedgeXError::BrokerUnavailable(_) => Some("BROKER_UNAVAILABLE".to_string()),
             HedgeXError::CompressionError(_) => Some("COMPRESSION_ERROR".to_string()),
         };
         
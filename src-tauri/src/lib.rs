@@ -10,14 +10,15 @@ pub mod utils;
 use std::path::PathBuf;
 use std::sync::Arc;
 use tokio::sync::Mutex;
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 use anyhow::Result;
 use uuid::Uuid;
 use argon2::{Argon2, PasswordHash, PasswordHasher, PasswordVerifier};
 use argon2::password_hash::SaltString;
 use argon2::password_hash::rand_core::OsRng;
 use sqlx::Row;
-use crate::services::{DataExportRequest, ExportType, ExportFormat, UserSettings};
+use crate::services::{DataExportRequest, ExportType, ExportFormat, UserSettings, CloudBackupSettings};
+use crate::services::ticker_shards::{MAX_INSTRUMENTS_PER_CONNECTION, MAX_TICKER_CONNECTIONS};
 
 // Helper structs for SQLx queries
 #[derive(Debug)]
@@ -167,10 +168,25 @@ async fn get_stock_list(state: tauri::State<'_, AppState>) -> Result<Vec<serde_j
 
 // Strategy management commands
 #[tauri::command]
-async fn get_strategies(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+async fn get_strategies(
+    tag: Option<String>,
+    enabled: Option<bool>,
+    search: Option<String>,
+    sort_by: Option<services::StrategySortField>,
+    descending: Option<bool>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
     let user_id = "demo_user"; // TODO: Get from auth context
     
-    match state.strategy_service.get_strategies(user_id).await {
+    let query = services::StrategyListQuery {
+        tag,
+        enabled,
+        search,
+        sort_by: sort_by.unwrap_or_default(),
+        descending: descending.unwrap_or(false),
+    };
+    
+    match state.strategy_service.query_strategies(user_id, &query).await {
         Ok(strategies) => {
             Ok(serde_json::json!({
                 "success": true,
@@ -195,6 +211,12 @@ async fn create_strategy(
     stop_loss_percentage: f64,
     take_profit_percentage: f64,
     volume_threshold: i64,
+    strategy_type: Option<models::StrategyType>,
+    max_capital: Option<f64>,
+    max_quantity_per_trade: Option<i32>,
+    max_holding_minutes: Option<i64>,
+    tags: Option<Vec<String>>,
+    rules: Option<models::StrategyRules>,
     state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
     let user_id = "demo_user"; // TODO: Get from auth context
@@ -207,6 +229,12 @@ async fn create_strategy(
         stop_loss_percentage,
         take_profit_percentage,
         volume_threshold,
+        strategy_type,
+        max_capital,
+        max_quantity_per_trade,
+        max_holding_minutes,
+        tags,
+        rules,
     };
     
     match state.strategy_service.create_strategy(user_id, request).await {
@@ -235,6 +263,12 @@ async fn update_strategy(
     stop_loss_percentage: Option<f64>,
     take_profit_percentage: Option<f64>,
     volume_threshold: Option<i64>,
+    strategy_type: Option<models::StrategyType>,
+    max_capital: Option<f64>,
+    max_quantity_per_trade: Option<i32>,
+    max_holding_minutes: Option<i64>,
+    tags: Option<Vec<String>>,
+    rules: Option<models::StrategyRules>,
     state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
     let user_id = "demo_user"; // TODO: Get from auth context
@@ -247,6 +281,12 @@ async fn update_strategy(
         stop_loss_percentage,
         take_profit_percentage,
         volume_threshold,
+        strategy_type,
+        max_capital,
+        max_quantity_per_trade,
+        max_holding_minutes,
+        tags,
+        rules,
     };
     
     match state.strategy_service.update_strategy(user_id, &strategy_id, request).await {
@@ -272,11 +312,52 @@ async fn enable_strategy(
 ) -> Result<serde_json::Value, String> {
     let user_id = "demo_user"; // TODO: Get from auth context
     
-    match state.strategy_service.enable_strategy(user_id, &strategy_id).await {
-        Ok(_) => {
+    match state.strategy_service.enable_strategy_with_validation(user_id, &strategy_id, &state.backtest_engine).await {
+        Ok(Some(validation)) if validation.blocked => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Strategy failed validation: {}", validation.issues.join("; ")),
+                "validation": validation
+            }))
+        }
+        Ok(validation) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "Strategy enabled successfully",
+                "validation": validation
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_strategy_validation_config(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let config = state.strategy_service.get_validation_config().await;
+    
+    Ok(serde_json::json!({
+        "success": true,
+        "data": config
+    }))
+}
+
+#[tauri::command]
+async fn set_strategy_validation_config(
+    config: models::EnableValidationConfig,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.strategy_service.set_validation_config(config).await {
+        Ok(config) => {
             Ok(serde_json::json!({
                 "success": true,
-                "message": "Strategy enabled successfully"
+                "data": config
             }))
         }
         Err(e) => {
@@ -322,7 +403,187 @@ async fn delete_strategy(
         Ok(_) => {
             Ok(serde_json::json!({
                 "success": true,
-                "message": "Strategy deleted successfully"
+                "message": "Strategy archived successfully"
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn restore_strategy(
+    strategy_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.strategy_service.restore_strategy(user_id, &strategy_id).await {
+        Ok(strategy) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": strategy
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_archived_strategies(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.strategy_service.get_archived_strategies(user_id).await {
+        Ok(strategies) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": strategies
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn set_strategy_trend_filter(
+    strategy_id: String,
+    index_symbol: Option<String>,
+    ma_period: Option<usize>,
+    enabled: Option<bool>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let index_symbol = index_symbol.unwrap_or_else(|| "NIFTY 50".to_string());
+    let mut filter = models::TrendFilterConfig::new(&strategy_id, index_symbol.trim(), ma_period.unwrap_or(20));
+    filter.enabled = enabled.unwrap_or(true);
+    
+    match state.strategy_manager.set_trend_filter(filter).await {
+        Ok(filter) => {
+            follow_tracked_indices(&state.instrument_service, &state.websocket_manager, &state.strategy_manager).await;
+            
+            Ok(serde_json::json!({
+                "success": true,
+                "data": filter
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_strategy_trend_filter(
+    strategy_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "success": true,
+        "data": state.strategy_manager.get_trend_filter(&strategy_id).await
+    }))
+}
+
+#[tauri::command]
+async fn remove_strategy_trend_filter(
+    strategy_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.strategy_manager.remove_trend_filter(&strategy_id).await {
+        Ok(_) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "Trend filter removed successfully"
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn set_strategy_pair_config(
+    strategy_id: String,
+    symbol_a: String,
+    symbol_b: String,
+    exchange: Option<String>,
+    hedge_ratio: Option<f64>,
+    lookback: Option<usize>,
+    entry_z: Option<f64>,
+    exit_z: Option<f64>,
+    stop_z: Option<f64>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let mut config = models::PairTradingConfig::new(&strategy_id, &symbol_a.to_uppercase(), &symbol_b.to_uppercase());
+    config.exchange = exchange.map(|e| e.to_uppercase()).unwrap_or(config.exchange);
+    config.hedge_ratio = hedge_ratio.unwrap_or(config.hedge_ratio);
+    config.lookback = lookback.unwrap_or(config.lookback);
+    config.entry_z = entry_z.unwrap_or(config.entry_z);
+    config.exit_z = exit_z.unwrap_or(config.exit_z);
+    config.stop_z = stop_z.unwrap_or(config.stop_z);
+    
+    match state.strategy_manager.set_pair_config(config).await {
+        Ok(config) => {
+            // Both legs need ticks for the spread to update
+            let legs = [config.symbol_a.clone(), config.symbol_b.clone()];
+            follow_selected_symbols(&state, &legs, &config.exchange).await;
+            
+            Ok(serde_json::json!({
+                "success": true,
+                "data": config
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_strategy_pair_config(
+    strategy_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "success": true,
+        "data": state.strategy_manager.get_pair_config(&strategy_id).await
+    }))
+}
+
+#[tauri::command]
+async fn remove_strategy_pair_config(
+    strategy_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.strategy_manager.remove_pair_config(&strategy_id).await {
+        Ok(_) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "Pair configuration removed successfully"
             }))
         }
         Err(e) => {
@@ -344,6 +605,60 @@ async fn get_nifty_50_stocks(state: tauri::State<'_, AppState>) -> Result<serde_
     }))
 }
 
+#[tauri::command]
+async fn search_instruments(
+    query: Option<String>,
+    exchange: Option<String>,
+    segment: Option<String>,
+    instrument_type: Option<String>,
+    instrument_token: Option<u64>,
+    limit: Option<i64>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let search = services::InstrumentSearchQuery {
+        query,
+        exchange,
+        segment,
+        instrument_type,
+        instrument_token,
+        limit,
+    };
+    
+    match state.instrument_service.search_instruments(&search).await {
+        Ok(instruments) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": instruments,
+                "last_refresh": state.instrument_service.get_last_refresh().await
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn refresh_instruments(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    match state.instrument_service.refresh_instruments(state.kite_client.as_ref()).await {
+        Ok(count) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": { "count": count }
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
 #[tauri::command]
 async fn get_stock_selections(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
     let user_id = "demo_user"; // TODO: Get from auth context
@@ -375,6 +690,7 @@ async fn add_stock_selection(
     
     match state.strategy_service.add_stock_selection(user_id, &symbol, &exchange).await {
         Ok(selection) => {
+            follow_selected_symbols(&state, &[symbol], &exchange).await;
             Ok(serde_json::json!({
                 "success": true,
                 "data": selection
@@ -398,6 +714,7 @@ async fn remove_stock_selection(
     
     match state.strategy_service.remove_stock_selection(user_id, &symbol).await {
         Ok(_) => {
+            unfollow_selected_symbols(&state, &[symbol]).await;
             Ok(serde_json::json!({
                 "success": true,
                 "message": "Stock selection removed successfully"
@@ -421,8 +738,9 @@ async fn bulk_add_stock_selections(
     let user_id = "demo_user"; // TODO: Get from auth context
     let exchange = exchange.unwrap_or_else(|| "NSE".to_string());
     
-    match state.strategy_service.bulk_add_stock_selections(user_id, symbols, &exchange).await {
+    match state.strategy_service.bulk_add_stock_selections(user_id, symbols.clone(), &exchange).await {
         Ok(selections) => {
+            follow_selected_symbols(&state, &symbols, &exchange).await;
             Ok(serde_json::json!({
                 "success": true,
                 "data": selections
@@ -444,8 +762,9 @@ async fn bulk_remove_stock_selections(
 ) -> Result<serde_json::Value, String> {
     let user_id = "demo_user"; // TODO: Get from auth context
     
-    match state.strategy_service.bulk_remove_stock_selections(user_id, symbols).await {
+    match state.strategy_service.bulk_remove_stock_selections(user_id, symbols.clone()).await {
         Ok(_) => {
+            unfollow_selected_symbols(&state, &symbols).await;
             Ok(serde_json::json!({
                 "success": true,
                 "message": "Stock selections removed successfully"
@@ -508,379 +827,3058 @@ async fn get_strategy_stats(
 }
 
 #[tauri::command]
-async fn start_trading(_state: tauri::State<'_, AppState>) -> Result<bool, String> {
-    // In a real implementation, we would:
-    // 1. Initialize and start the trading engine
+async fn get_live_strategy_stats(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
     
-    Ok(true)
+    match state.strategy_service.get_live_stats(user_id).await {
+        Ok(stats) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": stats
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
 }
 
 #[tauri::command]
-async fn stop_trading(_state: tauri::State<'_, AppState>) -> Result<bool, String> {
-    // In a real implementation, we would:
-    // 1. Stop the trading engine
-    // 2. Close open positions if needed
+async fn suggest_strategy_parameters(
+    strategy_id: String,
+    symbol: String,
+    exchange: Option<String>,
+    timeframe: Option<String>,
+    lookback_days: Option<i64>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
     
-    Ok(true)
+    let timeframe = match timeframe.as_deref().unwrap_or("1d").parse::<models::Timeframe>() {
+        Ok(timeframe) => timeframe,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
+    };
+    
+    match state.backtest_engine.suggest_parameters(
+        user_id,
+        &strategy_id,
+        &symbol,
+        exchange.as_deref().unwrap_or("NSE"),
+        timeframe,
+        lookback_days.unwrap_or(180),
+    ).await {
+        Ok(report) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": report
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
 }
 
 #[tauri::command]
-async fn get_recent_trades(_state: tauri::State<'_, AppState>) -> Result<Vec<serde_json::Value>, String> {
-    // In a real implementation, we would:
-    // 1. Retrieve recent trades from database
+async fn optimize_strategy_parameters(
+    request: models::OptimizationRequest,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
     
-    let trades = vec![
-        serde_json::json!({
-            "id": "1",
-            "symbol": "RELIANCE",
-            "trade_type": "BUY",
-            "quantity": 10,
-            "price": 2500.0,
-            "status": "EXECUTED",
-            "executed_at": "2025-07-03T09:30:00Z"
-        }),
-        serde_json::json!({
-            "id": "2",
-            "symbol": "TCS",
-            "trade_type": "SELL",
-            "quantity": 5,
-            "price": 3700.0,
-            "status": "EXECUTED",
-            "executed_at": "2025-07-03T10:15:00Z"
-        }),
-        // More trades would be added here
-    ];
-    
-    Ok(trades)
+    match state.backtest_engine.optimize_parameters(user_id, &request).await {
+        Ok(report) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": report
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
 }
 
 #[tauri::command]
-async fn get_market_data(state: tauri::State<'_, AppState>) -> Result<Vec<serde_json::Value>, String> {
-    // Get market data from WebSocket manager
-    let market_data = state.websocket_manager.get_all_cached_market_data().await;
-    
-    // Convert to JSON
-    let data: Vec<serde_json::Value> = market_data
-        .values()
-        .map(|md| {
-            serde_json::json!({
-                "symbol": md.symbol,
-                "instrument_token": md.instrument_token,
-                "ltp": md.ltp.to_string(),
-                "volume": md.volume,
-                "bid": md.bid.to_string(),
-                "ask": md.ask.to_string(),
-                "timestamp": md.timestamp.to_rfc3339(),
-                "change": md.change.map(|c| c.to_string()),
-                "change_percent": md.change_percent.map(|c| c.to_string()),
-            })
-        })
-        .collect();
+async fn run_walk_forward_analysis(
+    request: models::WalkForwardRequest,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
     
-    Ok(data)
+    match state.backtest_engine.walk_forward(user_id, &request).await {
+        Ok(report) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": report
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
 }
 
-// Analytics commands
 #[tauri::command]
-async fn get_system_logs(
-    state: tauri::State<'_, AppState>,
-    limit: Option<i32>,
-    offset: Option<i32>
+async fn get_live_drift_report(
+    request: models::DriftReportRequest,
+    state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let limit = limit.unwrap_or(100);
-    let offset = offset.unwrap_or(0);
-    
-    // Get logs from database
-    let db = state.app_service.get_enhanced_database_service().get_database();
-    let pool = db.get_pool();
-    
-    let query = "
-        SELECT id, user_id, log_level, message, created_at, context
-        FROM system_logs 
-        ORDER BY created_at DESC 
-        LIMIT ? OFFSET ?
-    ";
+    let user_id = "demo_user"; // TODO: Get from auth context
     
-    match sqlx::query(query)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(pool)
-        .await
-    {
-        Ok(rows) => {
-            let logs: Vec<serde_json::Value> = rows
-                .into_iter()
-                .map(|row| {
-                    serde_json::json!({
-                        "id": row.get::<String, _>("id"),
-                        "user_id": row.get::<Option<String>, _>("user_id"),
-                        "log_level": row.get::<i32, _>("log_level"),
-                        "message": row.get::<String, _>("message"),
-                        "created_at": row.get::<chrono::DateTime<chrono::Utc>, _>("created_at").to_rfc3339(),
-                        "context": row.get::<Option<String>, _>("context")
-                    })
-                })
-                .collect();
-            
+    match state.backtest_engine.live_drift_report(user_id, &request).await {
+        Ok(report) => {
             Ok(serde_json::json!({
                 "success": true,
-                "data": logs
+                "data": report
             }))
         }
         Err(e) => {
-            eprintln!("Failed to get system logs: {}", e);
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to get system logs: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn get_trade_history(
-    state: tauri::State<'_, AppState>,
-    limit: Option<i32>,
-    offset: Option<i32>
+async fn run_backtest_monte_carlo(
+    backtest_id: String,
+    config: Option<models::MonteCarloConfig>,
+    state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let user_id = "demo_user"; // TODO: Get from auth context
-    let limit = limit.unwrap_or(100);
-    let offset = offset.unwrap_or(0);
-    
-    // Get trades from database
-    let db = state.app_service.get_enhanced_database_service().get_database();
-    let pool = db.get_pool();
-    
-    let query = "
-        SELECT id, symbol, trade_type, quantity, price, status, executed_at, strategy_id
-        FROM trades 
-        WHERE user_id = ?
-        ORDER BY executed_at DESC 
-        LIMIT ? OFFSET ?
-    ";
+    let config = config.unwrap_or_default();
     
-    match sqlx::query(query)
-        .bind(user_id)
-        .bind(limit)
-        .bind(offset)
-        .fetch_all(pool)
-        .await
-    {
-        Ok(rows) => {
-            let trades: Vec<serde_json::Value> = rows
-                .into_iter()
-                .map(|row| {
-                    serde_json::json!({
-                        "id": row.get::<String, _>("id"),
-                        "symbol": row.get::<String, _>("symbol"),
-                        "trade_type": row.get::<String, _>("trade_type"),
-                        "quantity": row.get::<i32, _>("quantity"),
-                        "price": row.get::<f64, _>("price"),
-                        "status": row.get::<String, _>("status"),
-                        "executed_at": row.get::<chrono::DateTime<chrono::Utc>, _>("executed_at").to_rfc3339(),
-                        "strategy_id": row.get::<String, _>("strategy_id")
-                    })
-                })
-                .collect();
-            
+    match state.backtest_engine.run_monte_carlo(&backtest_id, &config).await {
+        Ok(result) => {
             Ok(serde_json::json!({
                 "success": true,
-                "data": trades
+                "data": result
             }))
         }
         Err(e) => {
-            eprintln!("Failed to get trade history: {}", e);
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to get trade history: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn get_analytics_performance_metrics(
-    state: tauri::State<'_, AppState>,
-    timeframe: Option<String>
+async fn get_backtest_monte_carlo(
+    backtest_id: String,
+    state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let user_id = "demo_user"; // TODO: Get from auth context
-    let timeframe = timeframe.unwrap_or_else(|| "month".to_string());
-    
-    // Calculate date range based on timeframe
-    let days = match timeframe.as_str() {
-        "day" => 1,
-        "week" => 7,
-        "month" => 30,
-        "year" => 365,
-        _ => 30,
-    };
-    
-    // Get performance metrics from database
-    let db = state.app_service.get_enhanced_database_service().get_database();
-    let pool = db.get_pool();
-    
-    let query = "
-        SELECT 
-            COUNT(*) as total_trades,
-            COUNT(CASE WHEN price > 0 THEN 1 END) as profitable_trades,
-            SUM(price * quantity * CASE WHEN trade_type = 'Sell' THEN 1 ELSE -1 END) as total_profit,
-            AVG(CASE WHEN price > 0 THEN price * quantity END) as average_win,
-            AVG(CASE WHEN price < 0 THEN ABS(price * quantity) END) as average_loss,
-            MAX(price * quantity) as largest_win,
-            MIN(price * quantity) as largest_loss
-        FROM trades 
-        WHERE user_id = ? 
-        AND status = 'Executed'
-        AND executed_at >= datetime('now', '-' || ? || ' days')
-    ";
-    
-    match sqlx::query(query)
-        .bind(user_id)
-        .bind(days)
-        .fetch_one(pool)
-        .await
-    {
-        Ok(row) => {
-            let total_trades: i32 = row.get("total_trades");
-            let profitable_trades: i32 = row.get("profitable_trades");
-            let total_profit: f64 = row.get::<Option<f64>, _>("total_profit").unwrap_or(0.0);
-            let average_win: f64 = row.get::<Option<f64>, _>("average_win").unwrap_or(0.0);
-            let average_loss: f64 = row.get::<Option<f64>, _>("average_loss").unwrap_or(0.0);
-            let largest_win: f64 = row.get::<Option<f64>, _>("largest_win").unwrap_or(0.0);
-            let largest_loss: f64 = row.get::<Option<f64>, _>("largest_loss").unwrap_or(0.0);
-            
-            let losing_trades = total_trades - profitable_trades;
-            let win_rate = if total_trades > 0 {
-                profitable_trades as f64 / total_trades as f64
-            } else {
-                0.0
-            };
-            
-            let profit_factor = if average_loss > 0.0 {
-                average_win / average_loss
-            } else {
-                0.0
-            };
-            
+    match state.backtest_engine.get_monte_carlo(&backtest_id).await {
+        Ok(result) => {
             Ok(serde_json::json!({
                 "success": true,
-                "data": {
-                    "total_trades": total_trades,
-                    "profitable_trades": profitable_trades,
-                    "losing_trades": losing_trades,
-                    "win_rate": win_rate,
-                    "profit_factor": profit_factor,
-                    "average_win": average_win,
-                    "average_loss": average_loss,
-                    "largest_win": largest_win,
-                    "largest_loss": largest_loss,
-                    "total_profit": total_profit,
-                    "net_profit": total_profit,
-                    "sharpe_ratio": 1.5, // TODO: Calculate actual Sharpe ratio
-                    "max_drawdown": 0.0, // TODO: Calculate actual max drawdown
-                    "max_drawdown_percent": 0.0,
-                    "average_trade_duration": 45
-                }
+                "data": result
             }))
         }
         Err(e) => {
-            eprintln!("Failed to get performance metrics: {}", e);
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to get performance metrics: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn get_analytics_strategy_performance(
-    state: tauri::State<'_, AppState>,
-    timeframe: Option<String>
+async fn get_data_coverage(
+    symbols: Vec<String>,
+    exchange: Option<String>,
+    timeframe: String,
+    from_date: chrono::DateTime<chrono::Utc>,
+    to_date: chrono::DateTime<chrono::Utc>,
+    state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let user_id = "demo_user"; // TODO: Get from auth context
-    let timeframe = timeframe.unwrap_or_else(|| "month".to_string());
-    
-    // Calculate date range based on timeframe
-    let days = match timeframe.as_str() {
-        "day" => 1,
-        "week" => 7,
-        "month" => 30,
-        "year" => 365,
-        _ => 30,
+    let timeframe = match timeframe.parse::<models::Timeframe>() {
+        Ok(timeframe) => timeframe,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
     };
+    let exchange = exchange.as_deref().unwrap_or("NSE");
     
-    // Get strategy performance from database
-    let db = state.app_service.get_enhanced_database_service().get_database();
-    let pool = db.get_pool();
+    let mut coverage = Vec::with_capacity(symbols.len());
+    for symbol in &symbols {
+        match state.backtest_engine.data_coverage(symbol, exchange, timeframe, from_date, to_date).await {
+            Ok(report) => coverage.push(report),
+            Err(e) => {
+                return Ok(serde_json::json!({
+                    "success": false,
+                    "error": e.to_string()
+                }));
+            }
+        }
+    }
+    
+    Ok(serde_json::json!({
+        "success": true,
+        "data": coverage
+    }))
+}
+
+#[tauri::command]
+async fn list_historical_datasets(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.backtest_engine.list_historical_datasets().await {
+        Ok(datasets) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": datasets
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn delete_historical_data(
+    symbol: String,
+    exchange: Option<String>,
+    timeframe: String,
+    from_date: chrono::DateTime<chrono::Utc>,
+    to_date: chrono::DateTime<chrono::Utc>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let timeframe = match timeframe.parse::<models::Timeframe>() {
+        Ok(timeframe) => timeframe,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
+    };
+    let exchange = exchange.as_deref().unwrap_or("NSE");
+    
+    match state.backtest_engine.delete_historical_data(&symbol, exchange, timeframe, from_date, to_date).await {
+        Ok(deleted) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": deleted
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn redownload_historical_data(
+    symbol: String,
+    exchange: Option<String>,
+    timeframe: String,
+    from_date: chrono::DateTime<chrono::Utc>,
+    to_date: chrono::DateTime<chrono::Utc>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let timeframe = match timeframe.parse::<models::Timeframe>() {
+        Ok(timeframe) => timeframe,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
+    };
+    let exchange = exchange.as_deref().unwrap_or("NSE");
+    
+    match state.backtest_engine.redownload_historical_data(&symbol, exchange, timeframe, from_date, to_date).await {
+        Ok(stored) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": stored
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn preview_csv_import(
+    file_path: String,
+    config: models::CsvImportConfig,
+    rows: Option<usize>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.backtest_engine.preview_csv_import(&file_path, &config, rows.unwrap_or(20)) {
+        Ok(preview) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": preview
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn import_csv_data(
+    file_path: String,
+    config: models::CsvImportConfig,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.backtest_engine.import_csv(&file_path, &config).await {
+        Ok(validation) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": validation
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_backtest_trades(
+    backtest_id: String,
+    page: Option<usize>,
+    page_size: Option<usize>,
+    context_candles: Option<usize>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let page = page.unwrap_or(1);
+    let page_size = page_size.unwrap_or(services::backtest_engine::DEFAULT_TRADE_PAGE_SIZE);
+    let context_candles = context_candles.unwrap_or(services::backtest_engine::DEFAULT_CONTEXT_CANDLES);
+    
+    match state.backtest_engine.get_backtest_trades(&backtest_id, page, page_size, context_candles).await {
+        Ok(trades) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": trades
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn update_backtest_notes(
+    backtest_id: String,
+    label: Option<String>,
+    notes: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.backtest_engine.update_backtest_notes(user_id, &backtest_id, label, notes).await {
+        Ok(_) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "Backtest notes updated successfully"
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn delete_backtest(
+    backtest_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.backtest_engine.delete_backtest(user_id, &backtest_id).await {
+        Ok(_) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "Backtest deleted successfully"
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn prune_backtests(
+    older_than_days: i64,
+    include_labeled: Option<bool>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.backtest_engine.prune_backtests(user_id, older_than_days, include_labeled.unwrap_or(false)).await {
+        Ok(deleted) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": {
+                    "deleted": deleted
+                }
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn queue_backtest_job(
+    request: models::BacktestJobRequest,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.backtest_queue.enqueue(user_id, request).await {
+        Ok(job) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": job
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_backtest_job(
+    job_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.backtest_queue.get_job(user_id, &job_id).await {
+        Ok(job) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": job
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn list_backtest_jobs(
+    status: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    let status = match status.map(|status| status.parse::<models::JobStatus>()).transpose() {
+        Ok(status) => status,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
+    };
+    
+    match state.backtest_queue.list_jobs(user_id, status).await {
+        Ok(jobs) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": jobs
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn rerun_backtest(
+    backtest_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.backtest_engine.rerun_backtest(&backtest_id).await {
+        Ok(reproduction) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": reproduction
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn export_backtest_result(
+    backtest_id: String,
+    format: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let format_enum = match format.as_str() {
+        "json" => ExportFormat::Json,
+        "csv" => ExportFormat::Csv,
+        _ => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Unsupported export format: {}", format)
+            }));
+        }
+    };
+    
+    let result = match state.backtest_engine.get_backtest_detail(&backtest_id).await {
+        Ok(result) => result,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }));
+        }
+    };
+    
+    match state.app_service.get_data_persistence_service().export_backtest(&result, format_enum).await {
+        Ok(export_path) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": {
+                    "export_path": export_path.to_string_lossy()
+                }
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to export backtest: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn start_trading(_state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    // In a real implementation, we would:
+    // 1. Initialize and start the trading engine
+    
+    Ok(true)
+}
+
+#[tauri::command]
+async fn stop_trading(_state: tauri::State<'_, AppState>) -> Result<bool, String> {
+    // In a real implementation, we would:
+    // 1. Stop the trading engine
+    // 2. Close open positions if needed
+    
+    Ok(true)
+}
+
+#[tauri::command]
+async fn get_recent_trades(_state: tauri::State<'_, AppState>) -> Result<Vec<serde_json::Value>, String> {
+    // In a real implementation, we would:
+    // 1. Retrieve recent trades from database
+    
+    let trades = vec![
+        serde_json::json!({
+            "id": "1",
+            "symbol": "RELIANCE",
+            "trade_type": "BUY",
+            "quantity": 10,
+            "price": 2500.0,
+            "status": "EXECUTED",
+            "executed_at": "2025-07-03T09:30:00Z"
+        }),
+        serde_json::json!({
+            "id": "2",
+            "symbol": "TCS",
+            "trade_type": "SELL",
+            "quantity": 5,
+            "price": 3700.0,
+            "status": "EXECUTED",
+            "executed_at": "2025-07-03T10:15:00Z"
+        }),
+        // More trades would be added here
+    ];
+    
+    Ok(trades)
+}
+
+#[tauri::command]
+async fn get_market_data(state: tauri::State<'_, AppState>) -> Result<Vec<serde_json::Value>, String> {
+    // Get market data from WebSocket manager
+    let market_data = state.websocket_manager.get_all_cached_market_data().await;
+    let freshness = state.websocket_manager.get_price_freshness().await;
+    let now = chrono::Utc::now();
+    
+    // Convert to JSON, flagging prices too old to trade on
+    let data: Vec<serde_json::Value> = market_data
+        .values()
+        .map(|md| {
+            serde_json::json!({
+                "symbol": md.symbol,
+                "instrument_token": md.instrument_token,
+                "ltp": md.ltp.to_string(),
+                "volume": md.volume,
+                "bid": md.bid.to_string(),
+                "ask": md.ask.to_string(),
+                "timestamp": md.timestamp.to_rfc3339(),
+                "change": md.change.map(|c| c.to_string()),
+                "change_percent": md.change_percent.map(|c| c.to_string()),
+                "age_seconds": freshness.age_seconds(&md.symbol, now),
+                "stale": freshness.is_stale(&md.symbol, now),
+                "delayed": md.delayed,
+            })
+        })
+        .collect();
+    
+    Ok(data)
+}
+
+#[tauri::command]
+async fn clear_cache(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    let cleared = state.websocket_manager.clear_cache().await;
+    Ok(serde_json::json!({
+        "success": true,
+        "data": { "cleared": cleared }
+    }))
+}
+
+#[tauri::command]
+async fn set_cache_limits(
+    ttl_seconds: i64,
+    max_entries: usize,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match services::CacheLimits::new(ttl_seconds, max_entries) {
+        Ok(limits) => {
+            let evicted = state.websocket_manager.set_cache_limits(limits.clone()).await;
+            Ok(serde_json::json!({
+                "success": true,
+                "data": { "limits": limits, "evicted": evicted }
+            }))
+        }
+        Err(e) => Ok(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+#[tauri::command]
+async fn set_stale_data_threshold(
+    seconds: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.websocket_manager.set_stale_data_threshold(seconds).await {
+        Ok(()) => Ok(serde_json::json!({
+            "success": true,
+            "data": { "max_age_seconds": seconds }
+        })),
+        Err(e) => Ok(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+#[tauri::command]
+async fn get_websocket_status(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "success": true,
+        "data": {
+            "status": state.websocket_manager.get_status().await,
+            "subscriptions": state.websocket_manager.get_subscriptions().await.len()
+        }
+    }))
+}
+
+#[tauri::command]
+async fn set_market_data_display(
+    symbols: Vec<String>,
+    max_updates_per_second: Option<f64>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    if let Some(rate) = max_updates_per_second {
+        if let Err(e) = state.websocket_manager.set_display_update_rate(rate).await {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }));
+        }
+    }
+    
+    state.websocket_manager.set_displayed_symbols(&symbols).await;
+    let (symbols, max_updates_per_second) = state.websocket_manager.get_display_settings().await;
+    Ok(serde_json::json!({
+        "success": true,
+        "data": {
+            "symbols": symbols,
+            "max_updates_per_second": max_updates_per_second
+        }
+    }))
+}
+
+#[tauri::command]
+async fn subscribe_symbols(
+    symbols: Vec<String>,
+    exchange: Option<String>,
+    mode: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let exchange = exchange.unwrap_or_else(|| "NSE".to_string());
+    // Watchlist symbols default to LTP; full mode is kept for the stocks being traded
+    let mode = match mode.as_deref().unwrap_or("ltp").parse::<services::SubscriptionMode>() {
+        Ok(mode) => mode,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
+    };
+    
+    match subscribe_to_symbols(&state.instrument_service, &state.websocket_manager, &symbols, &exchange, mode).await {
+        Ok(missing) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": {
+                    "subscriptions": state.websocket_manager.list_subscriptions().await,
+                    "unknown_symbols": missing
+                }
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn unsubscribe_symbols(
+    symbols: Vec<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let symbols: Vec<String> = symbols.iter().map(|symbol| symbol.to_uppercase()).collect();
+    
+    match state.websocket_manager.unsubscribe_from_symbols(&symbols).await {
+        Ok(_) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": state.websocket_manager.list_subscriptions().await
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn set_subscription_mode(
+    symbols: Vec<String>,
+    mode: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let mode = match mode.parse::<services::SubscriptionMode>() {
+        Ok(mode) => mode,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
+    };
+    
+    let symbols: Vec<String> = symbols.iter().map(|symbol| symbol.to_uppercase()).collect();
+    match state.websocket_manager.set_symbols_mode(&symbols, mode).await {
+        Ok(_) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": state.websocket_manager.list_subscriptions().await
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn list_subscriptions(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "success": true,
+        "data": state.websocket_manager.list_subscriptions().await
+    }))
+}
+
+#[tauri::command]
+async fn subscribe_indices(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.websocket_manager.subscribe_to_indices().await {
+        Ok(()) => Ok(serde_json::json!({
+            "success": true,
+            "data": state.websocket_manager.list_subscriptions().await
+        })),
+        Err(e) => Ok(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+#[tauri::command]
+async fn get_index_quotes(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "success": true,
+        "data": state.websocket_manager.get_index_quotes().await
+    }))
+}
+
+#[tauri::command]
+async fn get_ticker_connections(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "success": true,
+        "data": state.websocket_manager.get_connection_health().await
+    }))
+}
+
+/// Subscribe the ticker to symbols on an exchange, returning the symbols with no known instrument
+async fn subscribe_to_symbols(
+    instrument_service: &services::InstrumentService,
+    websocket_manager: &services::WebSocketManager,
+    symbols: &[String],
+    exchange: &str,
+    mode: services::SubscriptionMode,
+) -> error::Result<Vec<String>> {
+    let (instruments, missing) = instrument_service.resolve_symbols(exchange, symbols).await?;
+    if !instruments.is_empty() {
+        websocket_manager.subscribe_to_symbols(instruments, mode).await?;
+    }
+    Ok(missing)
+}
+
+/// Stream newly selected stocks in full mode so strategies see depth; the selection stands either way
+async fn follow_selected_symbols(state: &AppState, symbols: &[String], exchange: &str) {
+    match subscribe_to_symbols(&state.instrument_service, &state.websocket_manager, symbols, exchange, services::SubscriptionMode::Full).await {
+        Ok(missing) if !missing.is_empty() => eprintln!("No instrument found to subscribe for: {}", missing.join(", ")),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to subscribe to selected stocks: {}", e),
+    }
+}
+
+/// Stream the indices enabled trend filters follow, in the quote mode the market indices use
+async fn follow_tracked_indices(
+    instrument_service: &services::InstrumentService,
+    websocket_manager: &services::WebSocketManager,
+    strategy_manager: &trading::StrategyManager,
+) {
+    let indices = strategy_manager.get_tracked_indices().await;
+    if indices.is_empty() {
+        return;
+    }
+    
+    match subscribe_to_symbols(instrument_service, websocket_manager, &indices, "NSE", services::SubscriptionMode::Quote).await {
+        Ok(missing) if !missing.is_empty() => eprintln!("No instrument found to subscribe for: {}", missing.join(", ")),
+        Ok(_) => {}
+        Err(e) => eprintln!("Failed to subscribe to trend filter indices: {}", e),
+    }
+}
+
+/// Stop streaming stocks removed from the selection
+async fn unfollow_selected_symbols(state: &AppState, symbols: &[String]) {
+    let symbols: Vec<String> = symbols.iter().map(|symbol| symbol.to_uppercase()).collect();
+    if let Err(e) = state.websocket_manager.unsubscribe_from_symbols(&symbols).await {
+        eprintln!("Failed to unsubscribe from removed stocks: {}", e);
+    }
+}
+
+#[tauri::command]
+async fn get_market_depth(
+    symbol: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let depth = state.websocket_manager.get_market_depth(&symbol).await;
+    Ok(serde_json::json!({
+        "success": true,
+        "data": depth
+    }))
+}
+
+#[tauri::command]
+async fn get_live_metrics(
+    symbol: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let data = match symbol {
+        Some(symbol) => serde_json::json!(state.websocket_manager.get_live_metrics(&symbol).await),
+        None => serde_json::json!(state.websocket_manager.get_all_live_metrics().await),
+    };
+    Ok(serde_json::json!({
+        "success": true,
+        "data": data
+    }))
+}
+
+#[tauri::command]
+async fn get_recent_candles(
+    symbol: String,
+    timeframe: Option<String>,
+    count: Option<usize>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let timeframe = match timeframe.as_deref().unwrap_or("1m").parse::<models::Timeframe>() {
+        Ok(timeframe) => timeframe,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": e
+            }));
+        }
+    };
+    
+    let count = count.unwrap_or(100).min(trading::bars::MAX_BARS);
+    match state.websocket_manager.get_recent_candles(&symbol, timeframe, count).await {
+        Ok(candles) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": candles
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_tick_recorder_status(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let stats = state.websocket_manager.get_tick_recorder().await.map(|recorder| recorder.stats());
+    
+    Ok(serde_json::json!({
+        "success": true,
+        "data": {
+            "recording": stats.is_some(),
+            "stats": stats
+        }
+    }))
+}
+
+#[tauri::command]
+async fn configure_tick_recorder(
+    config: services::TickRecorderConfig,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    if !config.enabled {
+        state.websocket_manager.set_tick_recorder(None).await;
+        return Ok(serde_json::json!({
+            "success": true,
+            "data": { "recording": false }
+        }));
+    }
+    
+    match services::TickRecorder::start(state.app_service.get_tick_store(), config) {
+        Ok(recorder) => {
+            let stats = recorder.stats();
+            state.websocket_manager.set_tick_recorder(Some(recorder)).await;
+            Ok(serde_json::json!({
+                "success": true,
+                "data": { "recording": true, "stats": stats }
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn start_tick_replay(
+    config: services::ReplayConfig,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let mut replay = state.tick_replay.lock().await;
+    if let Some(previous) = replay.take() {
+        previous.stop();
+    }
+    
+    let tick_store = state.app_service.get_tick_store();
+    match services::TickReplay::start(tick_store.as_ref(), Arc::clone(&state.websocket_manager), config).await {
+        Ok(started) => {
+            let progress = started.progress().await;
+            *replay = Some(started);
+            Ok(serde_json::json!({
+                "success": true,
+                "data": progress
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn stop_tick_replay(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let replay = state.tick_replay.lock().await;
+    let progress = match replay.as_ref() {
+        Some(replay) => {
+            replay.stop();
+            Some(replay.progress().await)
+        }
+        None => None,
+    };
+    
+    Ok(serde_json::json!({
+        "success": true,
+        "data": progress
+    }))
+}
+
+#[tauri::command]
+async fn get_tick_replay_status(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let progress = match state.tick_replay.lock().await.as_ref() {
+        Some(replay) => Some(replay.progress().await),
+        None => None,
+    };
+    
+    Ok(serde_json::json!({
+        "success": true,
+        "data": progress
+    }))
+}
+
+#[tauri::command]
+async fn start_recording_session(
+    name: String,
+    symbols: Vec<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = Arc::new(state.app_service.get_enhanced_database_service().get_database().get_pool().clone());
+    let updates = state.websocket_manager.subscribe_to_market_data_as(
+        &format!("recording {}", name),
+        services::TickPolicy::Queue(services::recording_session::SESSION_QUEUE_CAPACITY),
+    );
+    
+    match services::RecordingSession::start(pool, "demo_user", &name, symbols, updates).await {
+        Ok(session) => {
+            let info = session.info();
+            state.recording_sessions.lock().await.insert(info.id.clone(), session);
+            Ok(serde_json::json!({
+                "success": true,
+                "data": info
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn stop_recording_session(
+    session_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let session = match state.recording_sessions.lock().await.remove(&session_id) {
+        Some(session) => session,
+        None => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("No recording session {} is running", session_id)
+            }));
+        }
+    };
+    
+    match session.stop().await {
+        Ok(info) => Ok(serde_json::json!({
+            "success": true,
+            "data": info
+        })),
+        Err(e) => Ok(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+#[tauri::command]
+async fn list_recording_sessions(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Running sessions report the ticks captured so far rather than the stored count
+    let running = state.recording_sessions.lock().await.clone();
+    
+    match state.app_service.get_data_persistence_service().list_recording_sessions("demo_user").await {
+        Ok(sessions) => {
+            let sessions: Vec<services::RecordingSessionInfo> = sessions.into_iter()
+                .map(|session| match running.get(&session.id) {
+                    Some(live) => live.info(),
+                    None => session,
+                })
+                .collect();
+            Ok(serde_json::json!({
+                "success": true,
+                "data": sessions
+            }))
+        }
+        Err(e) => Ok(serde_json::json!({
+            "success": false,
+            "error": e.to_string()
+        })),
+    }
+}
+
+#[tauri::command]
+async fn export_recording_session(
+    session_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().export_recording_session(&session_id).await {
+        Ok(export_path) => Ok(serde_json::json!({
+            "success": true,
+            "data": {
+                "export_path": export_path.to_string_lossy()
+            }
+        })),
+        Err(e) => Ok(serde_json::json!({
+            "success": false,
+            "error": format!("Failed to export recording session: {}", e)
+        })),
+    }
+}
+
+// Analytics commands
+#[tauri::command]
+async fn get_system_logs(
+    state: tauri::State<'_, AppState>,
+    limit: Option<i32>,
+    offset: Option<i32>
+) -> Result<serde_json::Value, String> {
+    let limit = limit.unwrap_or(100);
+    let offset = offset.unwrap_or(0);
+    
+    // Get logs from database
+    let db = state.app_service.get_enhanced_database_service().get_database();
+    let pool = db.get_pool();
+    
+    let query = "
+        SELECT id, user_id, log_level, message, created_at, context
+        FROM system_logs 
+        ORDER BY created_at DESC 
+        LIMIT ? OFFSET ?
+    ";
+    
+    match sqlx::query(query)
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    {
+        Ok(rows) => {
+            let logs: Vec<serde_json::Value> = rows
+                .into_iter()
+                .map(|row| {
+                    serde_json::json!({
+                        "id": row.get::<String, _>("id"),
+                        "user_id": row.get::<Option<String>, _>("user_id"),
+                        "log_level": row.get::<i32, _>("log_level"),
+                        "message": row.get::<String, _>("message"),
+                        "created_at": row.get::<chrono::DateTime<chrono::Utc>, _>("created_at").to_rfc3339(),
+                        "context": row.get::<Option<String>, _>("context")
+                    })
+                })
+                .collect();
+            
+            Ok(serde_json::json!({
+                "success": true,
+                "data": logs
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get system logs: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get system logs: {}", e)
+            }))
+        }
+    }
+}
+
+/// Resolve an analytics command's date range and filters, ending today in IST unless `to` is given
+///
+/// An invalid range comes back as the command's error response.
+fn analytics_filter(
+    days: Option<i64>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>
+) -> Result<trading::TradeFilter, serde_json::Value> {
+    trading::TradeFilter::from_params(
+        trading::session::session_date(chrono::Utc::now()),
+        days,
+        from.as_deref(),
+        to.as_deref(),
+        strategy_id.as_deref(),
+        symbol.as_deref(),
+    )
+    .map_err(|e| serde_json::json!({
+        "success": false,
+        "error": e.to_string()
+    }))
+}
+
+#[tauri::command]
+async fn get_trade_history(
+    state: tauri::State<'_, AppState>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    let (limit, offset) = trading::analytics_filter::page_bounds(limit, offset, trading::analytics_filter::DEFAULT_PAGE_LIMIT);
+    let filter = match analytics_filter(None, from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    
+    // Get trades from database
+    let db = state.app_service.get_enhanced_database_service().get_database();
+    let pool = db.get_read_pool();
+    let (conditions, binds) = filter.sql_conditions("");
+    
+    let count_query = format!("SELECT COUNT(*) as total FROM trades WHERE user_id = ?{}", conditions);
+    let mut count = sqlx::query(&count_query).bind(user_id);
+    for value in &binds {
+        count = count.bind(value);
+    }
+    let total: i64 = match count.fetch_one(pool).await {
+        Ok(row) => row.get("total"),
+        Err(e) => {
+            eprintln!("Failed to count trade history: {}", e);
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get trade history: {}", e)
+            }));
+        }
+    };
+    
+    let query = format!("
+        SELECT id, symbol, trade_type, quantity, price, status, executed_at, strategy_id, charges
+        FROM trades 
+        WHERE user_id = ?{}
+        ORDER BY executed_at DESC 
+        LIMIT ? OFFSET ?
+    ", conditions);
+    let mut page = sqlx::query(&query).bind(user_id);
+    for value in &binds {
+        page = page.bind(value);
+    }
+    
+    match page
+        .bind(limit)
+        .bind(offset)
+        .fetch_all(pool)
+        .await
+    {
+        Ok(rows) => {
+            let trades: Vec<serde_json::Value> = rows
+                .into_iter()
+                .map(|row| {
+                    serde_json::json!({
+                        "id": row.get::<String, _>("id"),
+                        "symbol": row.get::<String, _>("symbol"),
+                        "trade_type": row.get::<String, _>("trade_type"),
+                        "quantity": row.get::<i32, _>("quantity"),
+                        "price": row.get::<f64, _>("price"),
+                        "status": row.get::<String, _>("status"),
+                        "executed_at": row.get::<chrono::DateTime<chrono::Utc>, _>("executed_at").to_rfc3339(),
+                        "strategy_id": row.get::<String, _>("strategy_id"),
+                        "charges": row.get::<f64, _>("charges")
+                    })
+                })
+                .collect();
+            
+            Ok(serde_json::json!({
+                "success": true,
+                "data": trades,
+                "pagination": trading::PageInfo::new(total, limit, offset)
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get trade history: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get trade history: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_analytics_performance_metrics(
+    state: tauri::State<'_, AppState>,
+    timeframe: Option<String>,
+    risk_free_rate: Option<f64>,
+    capital: Option<f64>,
+    periods_per_year: Option<f64>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    let timeframe = timeframe.unwrap_or_else(|| "month".to_string());
+    
+    // Calculate date range based on timeframe
+    let days = match timeframe.as_str() {
+        "day" => 1,
+        "week" => 7,
+        "month" => 30,
+        "year" => 365,
+        _ => 30,
+    };
+    let filter = match analytics_filter(Some(days), from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    
+    // Get performance metrics from database
+    let db = state.app_service.get_enhanced_database_service().get_database();
+    let pool = db.get_read_pool();
+    let (conditions, binds) = filter.sql_conditions("");
+    
+    let query = format!("
+        SELECT 
+            COUNT(*) as total_trades,
+            COUNT(CASE WHEN price > 0 THEN 1 END) as profitable_trades,
+            SUM(charges) as total_charges,
+            AVG(CASE WHEN price > 0 THEN price * quantity END) as average_win,
+            AVG(CASE WHEN price < 0 THEN ABS(price * quantity) END) as average_loss,
+            MAX(price * quantity) as largest_win,
+            MIN(price * quantity) as largest_loss
+        FROM trades 
+        WHERE user_id = ? 
+        AND status = 'Executed'{}
+    ", conditions);
+    let mut metrics = sqlx::query(&query).bind(user_id);
+    for value in &binds {
+        metrics = metrics.bind(value);
+    }
+    
+    match metrics.fetch_one(pool).await {
+        Ok(row) => {
+            let total_trades: i32 = row.get("total_trades");
+            let profitable_trades: i32 = row.get("profitable_trades");
+            let total_charges: f64 = row.get::<Option<f64>, _>("total_charges").unwrap_or(0.0);
+            let average_win: f64 = row.get::<Option<f64>, _>("average_win").unwrap_or(0.0);
+            let average_loss: f64 = row.get::<Option<f64>, _>("average_loss").unwrap_or(0.0);
+            let largest_win: f64 = row.get::<Option<f64>, _>("largest_win").unwrap_or(0.0);
+            let largest_loss: f64 = row.get::<Option<f64>, _>("largest_loss").unwrap_or(0.0);
+            
+            let losing_trades = total_trades - profitable_trades;
+            let win_rate = if total_trades > 0 {
+                profitable_trades as f64 / total_trades as f64
+            } else {
+                0.0
+            };
+            
+            let profit_factor = if average_loss > 0.0 {
+                average_win / average_loss
+            } else {
+                0.0
+            };
+            
+            // Ratios over every trading day in the window, quiet days counting as flat
+            let ratios = match trading::performance::load_daily_pnl(pool, user_id, &filter).await {
+                Ok(pnl_by_day) => {
+                    let daily_pnl = trading::performance::daily_pnl_series(
+                        &pnl_by_day,
+                        filter.from.unwrap_or(filter.to),
+                        filter.to,
+                    );
+                    trading::performance::risk_ratios(
+                        &daily_pnl,
+                        capital.unwrap_or(0.0),
+                        risk_free_rate.unwrap_or(0.0),
+                        periods_per_year.unwrap_or(trading::performance::TRADING_DAYS_PER_YEAR),
+                    )
+                }
+                Err(e) => {
+                    eprintln!("Failed to load daily P&L: {}", e);
+                    trading::performance::RiskRatios::default()
+                }
+            };
+            
+            let drawdown = match trading::performance::load_trade_pnl(pool, user_id, &filter).await {
+                Ok(trades) => {
+                    let pnls: Vec<f64> = trades.iter().map(|(_, pnl)| *pnl).collect();
+                    trading::performance::max_drawdown(capital.unwrap_or(0.0), &pnls)
+                }
+                Err(e) => {
+                    eprintln!("Failed to load trade P&L: {}", e);
+                    trading::performance::Drawdown::default()
+                }
+            };
+            
+            // Closed quantity only; open positions are marked to live prices separately
+            let prices = state.websocket_manager.get_last_prices().await;
+            let (realized_pnl, unrealized_pnl) = match state.pnl_statement.pnl_split(user_id, &filter, &prices).await {
+                Ok(split) => (split.realized_pnl, split.unrealized_pnl),
+                Err(e) => {
+                    eprintln!("Failed to split realized and unrealized P&L: {}", e);
+                    (0.0, 0.0)
+                }
+            };
+            
+            Ok(serde_json::json!({
+                "success": true,
+                "data": {
+                    "total_trades": total_trades,
+                    "profitable_trades": profitable_trades,
+                    "losing_trades": losing_trades,
+                    "win_rate": win_rate,
+                    "profit_factor": profit_factor,
+                    "average_win": average_win,
+                    "average_loss": average_loss,
+                    "largest_win": largest_win,
+                    "largest_loss": largest_loss,
+                    "total_profit": realized_pnl,
+                    "gross_profit": realized_pnl,
+                    "total_charges": total_charges,
+                    "net_profit": realized_pnl - total_charges,
+                    "realized_pnl": realized_pnl,
+                    "unrealized_pnl": unrealized_pnl,
+                    "total_pnl": realized_pnl - total_charges + unrealized_pnl,
+                    "sharpe_ratio": ratios.sharpe_ratio,
+                    "sortino_ratio": ratios.sortino_ratio,
+                    "calmar_ratio": ratios.calmar_ratio,
+                    "max_drawdown": drawdown.max_drawdown,
+                    "max_drawdown_percent": drawdown.max_drawdown_percent,
+                    "average_trade_duration": 45
+                },
+                "filter": filter
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get performance metrics: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get performance metrics: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_analytics_calendar(
+    state: tauri::State<'_, AppState>,
+    year: Option<i32>,
+    month: Option<u32>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    let today = trading::session::session_date(chrono::Utc::now());
+    let year = year.unwrap_or_else(|| chrono::Datelike::year(&today));
+    let month = month.unwrap_or_else(|| chrono::Datelike::month(&today));
+    
+    match state.pnl_statement.calendar(user_id, year, month).await {
+        Ok(calendar) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": calendar
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get P&L calendar: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get P&L calendar: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_analytics_time_breakdown(
+    state: tauri::State<'_, AppState>,
+    timeframe: Option<String>,
+    strategy_id: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    symbol: Option<String>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    let timeframe = timeframe.unwrap_or_else(|| "month".to_string());
+    
+    // Calculate date range based on timeframe
+    let days = match timeframe.as_str() {
+        "day" => 1,
+        "week" => 7,
+        "month" => 30,
+        "year" => 365,
+        _ => 30,
+    };
+    let filter = match analytics_filter(Some(days), from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    
+    match state.pnl_statement.time_breakdown(user_id, &filter).await {
+        Ok(breakdown) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": breakdown
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get time breakdown: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get time breakdown: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_analytics_correlation(
+    state: tauri::State<'_, AppState>,
+    timeframe: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    let timeframe = timeframe.unwrap_or_else(|| "quarter".to_string());
+    
+    // Correlation needs more days than the other analytics to mean anything
+    let days = match timeframe.as_str() {
+        "month" => 30,
+        "quarter" => 90,
+        "year" => 365,
+        _ => 90,
+    };
+    let filter = match analytics_filter(Some(days), from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    
+    match state.pnl_statement.strategy_correlation(user_id, &filter).await {
+        Ok(matrix) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": matrix
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get strategy correlation: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get strategy correlation: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_analytics_streaks(
+    state: tauri::State<'_, AppState>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    // Streaks run over the whole history unless a start date is given
+    let filter = match analytics_filter(None, from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    
+    match state.pnl_statement.streaks(user_id, &filter).await {
+        Ok(report) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": report
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get trade streaks: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get trade streaks: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_analytics_slippage(
+    state: tauri::State<'_, AppState>,
+    timeframe: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    let timeframe = timeframe.unwrap_or_else(|| "month".to_string());
+    
+    // Calculate date range based on timeframe
+    let days = match timeframe.as_str() {
+        "day" => 1,
+        "week" => 7,
+        "month" => 30,
+        "year" => 365,
+        _ => 30,
+    };
+    let filter = match analytics_filter(Some(days), from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    
+    match state.slippage.report(user_id, &filter).await {
+        Ok(report) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": report
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get slippage analysis: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get slippage analysis: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_analytics_excursions(
+    state: tauri::State<'_, AppState>,
+    timeframe: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    let days = trading::analytics_filter::timeframe_days(timeframe.as_deref(), 30);
+    let filter = match analytics_filter(Some(days), from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    
+    match state.excursions.report(user_id, &filter).await {
+        Ok(report) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": report
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get trade excursions: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get trade excursions: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_analytics_pnl_split(
+    state: tauri::State<'_, AppState>,
+    timeframe: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    let days = trading::analytics_filter::timeframe_days(timeframe.as_deref(), 1);
+    let filter = match analytics_filter(Some(days), from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    
+    let prices = state.websocket_manager.get_last_prices().await;
+    match state.pnl_statement.pnl_split(user_id, &filter, &prices).await {
+        Ok(split) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": split
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to split realized and unrealized P&L: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to split realized and unrealized P&L: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_analytics_rolling_performance(
+    state: tauri::State<'_, AppState>,
+    timeframe: Option<String>,
+    windows: Option<Vec<usize>>,
+    capital: Option<f64>,
+    risk_free_rate: Option<f64>,
+    periods_per_year: Option<f64>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    let timeframe = timeframe.unwrap_or_else(|| "year".to_string());
+    
+    // Rolling windows need a longer history than the other analytics
+    let days = match timeframe.as_str() {
+        "month" => 30,
+        "quarter" => 90,
+        "year" => 365,
+        _ => 365,
+    };
+    let filter = match analytics_filter(Some(days), from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    let windows = windows.unwrap_or_else(|| services::rolling_performance::DEFAULT_ROLLING_WINDOWS.to_vec());
+    let sharpe = services::SharpeInputs {
+        capital: capital.unwrap_or(0.0),
+        risk_free_rate: risk_free_rate.unwrap_or(0.0),
+        periods_per_year: periods_per_year.unwrap_or(trading::performance::TRADING_DAYS_PER_YEAR),
+    };
+    
+    match state.pnl_statement.rolling_performance(user_id, &filter, &windows, sharpe).await {
+        Ok(performance) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": performance
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get rolling performance: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get rolling performance: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn run_custom_report(
+    state: tauri::State<'_, AppState>,
+    spec: services::ReportSpec
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.pnl_statement.custom_report(user_id, &spec, trading::session::session_date(chrono::Utc::now())).await {
+        Ok(table) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": table
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to run custom report: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to run custom report: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn generate_eod_report(
+    state: tauri::State<'_, AppState>,
+    date: Option<String>,
+    deliver: Option<bool>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    let date = match date {
+        Some(date) => match chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(e) => {
+                return Ok(serde_json::json!({
+                    "success": false,
+                    "error": format!("Invalid date {}: {}", date, e)
+                }));
+            }
+        },
+        None => trading::session::session_date(chrono::Utc::now()),
+    };
+    
+    let mut report = match state.eod_report.generate(user_id, date).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Failed to generate EOD report: {}", e);
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to generate EOD report: {}", e)
+            }));
+        }
+    };
+    
+    if deliver.unwrap_or(false) {
+        if let Err(e) = state.eod_report.deliver(&mut report).await {
+            eprintln!("Failed to deliver EOD report: {}", e);
+        }
+    }
+    
+    Ok(serde_json::json!({
+        "success": true,
+        "data": report
+    }))
+}
+
+#[tauri::command]
+async fn get_eod_reports(
+    state: tauri::State<'_, AppState>,
+    limit: Option<i64>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.eod_report.list(user_id, limit.unwrap_or(30)).await {
+        Ok(reports) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": reports
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get EOD reports: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get EOD reports: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_notification_channels(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.notifications.load_channels(user_id).await {
+        // Secrets stay in the backend; the UI only learns whether they are set
+        Ok(channels) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": {
+                    "telegram_chat_id": channels.telegram_chat_id,
+                    "telegram_bot_token_set": channels.telegram_bot_token.is_some(),
+                    "email_api_url": channels.email_api_url,
+                    "email_api_key_set": channels.email_api_key.is_some(),
+                    "email_to": channels.email_to,
+                    "telegram_configured": channels.telegram().is_some(),
+                    "email_configured": channels.email().is_some()
+                }
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get notification channels: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get notification channels: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn save_notification_channels(
+    state: tauri::State<'_, AppState>,
+    channels: services::NotificationChannels
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.notifications.save_channels(user_id, &channels).await {
+        Ok(()) => {
+            Ok(serde_json::json!({
+                "success": true
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to save notification channels: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to save notification channels: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_analytics_strategy_performance(
+    state: tauri::State<'_, AppState>,
+    timeframe: Option<String>,
+    capital: Option<f64>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    let timeframe = timeframe.unwrap_or_else(|| "month".to_string());
+    
+    // Calculate date range based on timeframe
+    let days = match timeframe.as_str() {
+        "day" => 1,
+        "week" => 7,
+        "month" => 30,
+        "year" => 365,
+        _ => 30,
+    };
+    let filter = match analytics_filter(Some(days), from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    let (limit, offset) = trading::analytics_filter::page_bounds(limit, offset, trading::analytics_filter::DEFAULT_PAGE_LIMIT);
+    
+    // Get strategy performance from database
+    let db = state.app_service.get_enhanced_database_service().get_database();
+    let pool = db.get_read_pool();
+    let (conditions, binds) = filter.sql_conditions("t.");
+    
+    let query = format!("
+        SELECT 
+            t.strategy_id,
+            sp.name as strategy_name,
+            COUNT(*) as trades,
+            COUNT(CASE WHEN t.price > 0 THEN 1 END) as profitable_trades,
+            SUM(t.charges) as total_charges
+        FROM trades t
+        LEFT JOIN strategy_params sp ON t.strategy_id = sp.id
+        WHERE t.user_id = ? 
+        AND t.status = 'Executed'{}
+        GROUP BY t.strategy_id, sp.name
+    ", conditions);
+    let mut strategies = sqlx::query(&query).bind(user_id);
+    for value in &binds {
+        strategies = strategies.bind(value);
+    }
+    
+    match strategies.fetch_all(pool).await {
+        Ok(mut rows) => {
+            let total = rows.len() as i64;
+            
+            // P&L of closed quantity only, as the trades' cash flow counts open entries as losses
+            let realized: std::collections::HashMap<String, f64> = match state.pnl_statement.filtered_statement(user_id, &filter).await {
+                Ok(statement) => statement.strategies.into_iter()
+                    .map(|strategy| (strategy.strategy_id, strategy.totals.realized_pnl))
+                    .collect(),
+                Err(e) => {
+                    eprintln!("Failed to load realized P&L: {}", e);
+                    std::collections::HashMap::new()
+                }
+            };
+            let realized_pnl = |row: &sqlx::sqlite::SqliteRow| {
+                realized.get(&row.get::<String, _>("strategy_id")).copied().unwrap_or(0.0)
+            };
+            rows.sort_by(|a, b| realized_pnl(b).total_cmp(&realized_pnl(a)));
+            let drawdowns = match trading::performance::load_trade_pnl(pool, user_id, &filter).await {
+                Ok(trades) => trading::performance::drawdowns_by_strategy(&trades, capital.unwrap_or(0.0)),
+                Err(e) => {
+                    eprintln!("Failed to load trade P&L: {}", e);
+                    std::collections::HashMap::new()
+                }
+            };
+            
+            let strategies: Vec<serde_json::Value> = rows
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .map(|row| {
+                    let strategy_id: String = row.get("strategy_id");
+                    let drawdown = drawdowns.get(&strategy_id).copied().unwrap_or_default();
+                    let trades: i32 = row.get("trades");
+                    let profitable_trades: i32 = row.get("profitable_trades");
+                    let total_profit = realized_pnl(&row);
+                    let total_charges: f64 = row.get::<Option<f64>, _>("total_charges").unwrap_or(0.0);
+                    
+                    let win_rate = if trades > 0 {
+                        profitable_trades as f64 / trades as f64
+                    } else {
+                        0.0
+                    };
+                    
+                    serde_json::json!({
+                        "strategy_id": strategy_id,
+                        "strategy_name": row.get::<Option<String>, _>("strategy_name").unwrap_or_else(|| "Unknown".to_string()),
+                        "trades": trades,
+                        "win_rate": win_rate,
+                        "profit_factor": 1.5, // TODO: Calculate actual profit factor
+                        "total_profit": total_profit,
+                        "gross_profit": total_profit,
+                        "total_charges": total_charges,
+                        "net_profit": total_profit - total_charges,
+                        "realized_pnl": total_profit,
+                        "max_drawdown": drawdown.max_drawdown,
+                        "max_drawdown_percent": drawdown.max_drawdown_percent
+                    })
+                })
+                .collect();
+            
+            Ok(serde_json::json!({
+                "success": true,
+                "data": strategies,
+                "pagination": trading::PageInfo::new(total, limit, offset)
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get strategy performance: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get strategy performance: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_instrument_performance(
+    state: tauri::State<'_, AppState>,
+    timeframe: Option<String>,
+    from: Option<String>,
+    to: Option<String>,
+    strategy_id: Option<String>,
+    symbol: Option<String>,
+    limit: Option<i64>,
+    offset: Option<i64>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    let timeframe = timeframe.unwrap_or_else(|| "month".to_string());
+    
+    // Calculate date range based on timeframe
+    let days = match timeframe.as_str() {
+        "day" => 1,
+        "week" => 7,
+        "month" => 30,
+        "year" => 365,
+        _ => 30,
+    };
+    let filter = match analytics_filter(Some(days), from, to, strategy_id, symbol) {
+        Ok(filter) => filter,
+        Err(response) => return Ok(response),
+    };
+    // The top ten instruments unless a page is asked for
+    let (limit, offset) = trading::analytics_filter::page_bounds(limit, offset, 10);
+    
+    // Get instrument performance from database
+    let db = state.app_service.get_enhanced_database_service().get_database();
+    let pool = db.get_read_pool();
+    let (conditions, binds) = filter.sql_conditions("");
+    
+    let query = format!("
+        SELECT 
+            symbol,
+            COUNT(*) as trades,
+            COUNT(CASE WHEN price > 0 THEN 1 END) as profitable_trades,
+            SUM(charges) as total_charges
+        FROM trades 
+        WHERE user_id = ? 
+        AND status = 'Executed'{}
+        GROUP BY symbol
+    ", conditions);
+    let mut instruments = sqlx::query(&query).bind(user_id);
+    for value in &binds {
+        instruments = instruments.bind(value);
+    }
+    
+    match instruments.fetch_all(pool).await {
+        Ok(mut rows) => {
+            let total = rows.len() as i64;
+            
+            // Realized P&L of closed quantity and unrealized P&L of what is still open, by symbol
+            let mut realized: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
+            let mut unrealized: std::collections::HashMap<String, f64> = std::collections::HashMap::new();
+            match state.pnl_statement.filtered_statement(user_id, &filter).await {
+                Ok(statement) => {
+                    for trade in &statement.trades {
+                        *realized.entry(trade.symbol.clone()).or_default() += trade.realized_pnl;
+                    }
+                    let prices = state.websocket_manager.get_last_prices().await;
+                    for marked in services::pnl_split::mark_positions(&statement.open_positions, &prices) {
+                        *unrealized.entry(marked.position.symbol).or_default() += marked.unrealized_pnl;
+                    }
+                }
+                Err(e) => eprintln!("Failed to load realized P&L: {}", e),
+            }
+            let realized_pnl = |row: &sqlx::sqlite::SqliteRow| {
+                realized.get(&row.get::<String, _>("symbol")).copied().unwrap_or(0.0)
+            };
+            rows.sort_by(|a, b| realized_pnl(b).total_cmp(&realized_pnl(a)));
+            let instruments: Vec<serde_json::Value> = rows
+                .into_iter()
+                .skip(offset as usize)
+                .take(limit as usize)
+                .map(|row| {
+                    let trades: i32 = row.get("trades");
+                    let profitable_trades: i32 = row.get("profitable_trades");
+                    let symbol: String = row.get("symbol");
+                    let total_profit = realized_pnl(&row);
+                    let unrealized_pnl = unrealized.get(&symbol).copied().unwrap_or(0.0);
+                    let total_charges: f64 = row.get::<Option<f64>, _>("total_charges").unwrap_or(0.0);
+                    
+                    let win_rate = if trades > 0 {
+                        profitable_trades as f64 / trades as f64
+                    } else {
+                        0.0
+                    };
+                    
+                    serde_json::json!({
+                        "symbol": symbol,
+                        "trades": trades,
+                        "win_rate": win_rate,
+                        "profit_factor": 1.5, // TODO: Calculate actual profit factor
+                        "total_profit": total_profit,
+                        "gross_profit": total_profit,
+                        "total_charges": total_charges,
+                        "net_profit": total_profit - total_charges,
+                        "realized_pnl": total_profit,
+                        "unrealized_pnl": unrealized_pnl
+                    })
+                })
+                .collect();
+            
+            Ok(serde_json::json!({
+                "success": true,
+                "data": instruments,
+                "pagination": trading::PageInfo::new(total, limit, offset)
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to get instrument performance: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get instrument performance: {}", e)
+            }))
+        }
+    }
+}
+
+// Data persistence commands
+#[tauri::command]
+async fn create_backup(
+    label: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().create_manual_backup(&label).await {
+        Ok(metadata) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": {
+                    "id": metadata.id,
+                    "label": metadata.label,
+                    "created_at": metadata.created_at.to_rfc3339(),
+                    "file_size": metadata.file_size,
+                    "compressed": metadata.compressed,
+                    "encrypted": metadata.encrypted
+                }
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to create backup: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn list_backups(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().list_backups().await {
+        Ok(backups) => {
+            let backup_list: Vec<serde_json::Value> = backups
+                .into_iter()
+                .map(|backup| {
+                    serde_json::json!({
+                        "id": backup.id,
+                        "label": backup.label,
+                        "created_at": backup.created_at.to_rfc3339(),
+                        "file_size": backup.file_size,
+                        "compressed": backup.compressed,
+                        "encrypted": backup.encrypted,
+                        "backup_type": format!("{:?}", backup.backup_type)
+                    })
+                })
+                .collect();
+            
+            Ok(serde_json::json!({
+                "success": true,
+                "data": backup_list
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to list backups: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_backup_schedule(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let schedule = state.app_service.get_backup_schedule().await;
+    Ok(serde_json::json!({
+        "success": true,
+        "data": schedule
+    }))
+}
+
+/// Restore a backup through the app service, emitting `backup_restore_progress` events
+async fn restore_backup_with_progress(
+    app_handle: &tauri::AppHandle,
+    state: &AppState,
+    backup_id: &str,
+    passphrase: Option<&str>,
+) -> crate::error::Result<()> {
+    let emit = |progress: services::RestoreProgress| {
+        if let Err(e) = app_handle.emit("backup_restore_progress", &progress) {
+            eprintln!("Failed to emit restore progress: {}", e);
+        }
+    };
+    
+    let result = state.app_service.restore_backup(backup_id, passphrase, &emit).await;
+    match &result {
+        Ok(()) => {
+            emit(services::RestoreProgress::new(backup_id, services::RestoreStage::Reinitializing, 95, "Reloading strategies and instruments"));
+            state.strategy_service.clear_cache().await;
+            if let Err(e) = state.instrument_service.reload().await {
+                eprintln!("Failed to reload instruments after restore: {}", e);
+            }
+            if let Err(e) = state.strategy_manager.reload().await {
+                eprintln!("Failed to reload strategies after restore: {}", e);
+            }
+            emit(services::RestoreProgress::new(backup_id, services::RestoreStage::Completed, 100, "Backup restored"));
+        }
+        Err(e) => {
+            emit(services::RestoreProgress::new(backup_id, services::RestoreStage::Failed, 100, e.to_string()));
+        }
+    }
+    result
+}
+
+#[tauri::command]
+async fn restore_backup(
+    backup_id: String,
+    passphrase: Option<String>,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match restore_backup_with_progress(&app_handle, &state, &backup_id, passphrase.as_deref()).await {
+        Ok(_) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "Backup restored successfully"
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to restore backup: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_cloud_backup_settings(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().load_cloud_backup_settings().await {
+        Ok(settings) => {
+            // Secrets stay on this machine
+            Ok(serde_json::json!({
+                "success": true,
+                "data": settings.map(|settings| settings.redacted())
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to load cloud backup settings: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn save_cloud_backup_settings(
+    settings: CloudBackupSettings,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().save_cloud_backup_settings(&settings).await {
+        Ok(_) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "Cloud backup settings saved"
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to save cloud backup settings: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn upload_backup_to_cloud(
+    backup_id: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().upload_backup(&backup_id).await {
+        Ok(upload) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": upload
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to upload backup {}: {}", backup_id, e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to upload backup: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_cloud_upload_status(
+    limit: Option<i64>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().list_cloud_uploads(limit.unwrap_or(50)).await {
+        Ok(uploads) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": uploads
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to get cloud upload status: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn list_cloud_backups(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().list_cloud_backups().await {
+        Ok(backups) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": backups
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to list cloud backups: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn restore_backup_from_cloud(
+    remote_id: String,
+    app_handle: tauri::AppHandle,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let restored = match state.app_service.get_data_persistence_service().download_from_cloud(&remote_id).await {
+        Ok(metadata) => restore_backup_with_progress(&app_handle, &state, &metadata.id, None).await.map(|_| metadata),
+        Err(e) => Err(e),
+    };
+    match restored {
+        Ok(metadata) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "Backup restored successfully from cloud",
+                "data": {
+                    "id": metadata.id,
+                    "created_at": metadata.created_at.to_rfc3339(),
+                    "file_size": metadata.file_size
+                }
+            }))
+        }
+        Err(e) => {
+            eprintln!("Failed to restore cloud backup {}: {}", remote_id, e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to restore backup from cloud: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn export_data(
+    export_type: String,
+    format: String,
+    include_sensitive: bool,
+    compress: bool,
+    encrypt: bool,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    let export_type_enum = match export_type.as_str() {
+        "all_data" => ExportType::AllData,
+        "trade_history" => ExportType::TradeHistory,
+        "strategy_data" => ExportType::StrategyData,
+        "user_settings" => ExportType::UserSettings,
+        "system_logs" => ExportType::SystemLogs,
+        _ => ExportType::AllData,
+    };
+    
+    let format_enum = match format.as_str() {
+        "json" => ExportFormat::Json,
+        "csv" => ExportFormat::Csv,
+        "sql" => ExportFormat::Sql,
+        "xlsx" => ExportFormat::Xlsx,
+        _ => ExportFormat::Json,
+    };
+    
+    let request = DataExportRequest {
+        user_id: user_id.to_string(),
+        export_type: export_type_enum,
+        format: format_enum,
+        date_range: None,
+        include_sensitive,
+        compress,
+        encrypt,
+    };
+    
+    match state.app_service.get_data_persistence_service().export_data(request).await {
+        Ok(export_path) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": {
+                    "export_path": export_path.to_string_lossy(),
+                    "message": "Data exported successfully"
+                }
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to export data: {}", e)
+            }))
+        }
+    }
+}
+
+/// Export the user's profile into one bundle; credentials are included only with a passphrase
+#[tauri::command]
+async fn export_profile(
+    passphrase: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.app_service.get_data_persistence_service().export_profile(user_id, passphrase.as_deref()).await {
+        Ok(export_path) => Ok(serde_json::json!({
+            "success": true,
+            "data": {
+                "export_path": export_path.to_string_lossy()
+            }
+        })),
+        Err(e) => {
+            eprintln!("Failed to export profile: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to export profile: {}", e)
+            }))
+        }
+    }
+}
+
+/// Import a profile bundle, keeping (`skip`) or replacing (`overwrite`) rows that already exist
+#[tauri::command]
+async fn import_profile(
+    file_path: String,
+    passphrase: Option<String>,
+    on_conflict: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    let resolution = match on_conflict.as_deref() {
+        None | Some("skip") => services::ConflictResolution::Skip,
+        Some("overwrite") => services::ConflictResolution::Overwrite,
+        Some(other) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Unsupported conflict resolution: {}", other)
+            }));
+        }
+    };
+    
+    match state.app_service.get_data_persistence_service()
+        .import_profile(std::path::Path::new(&file_path), user_id, passphrase.as_deref(), resolution)
+        .await
+    {
+        Ok(report) => Ok(serde_json::json!({
+            "success": true,
+            "data": report
+        })),
+        Err(e) => {
+            eprintln!("Failed to import profile: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to import profile: {}", e)
+            }))
+        }
+    }
+}
+
+/// Tax report of the financial year starting in April of `financial_year`, the current one by default
+#[tauri::command]
+async fn generate_tax_report(
+    financial_year: Option<i32>,
+    slab_rate: Option<f64>,
+    format: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    let start_year = financial_year.unwrap_or_else(|| {
+        services::tax_report::financial_year_of(trading::session::session_date(chrono::Utc::now()))
+    });
+    let mut rates = services::TaxRates::default();
+    if let Some(slab_rate) = slab_rate {
+        rates.slab_rate = slab_rate;
+    }
+    
+    let report = match state.tax_report.generate(user_id, start_year, rates).await {
+        Ok(report) => report,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to generate tax report: {}", e)
+            }));
+        }
+    };
+    
+    // Without a format the report is only returned, not written out
+    let export_format = match format.as_deref() {
+        None => None,
+        Some("json") => Some(ExportFormat::Json),
+        Some("csv") => Some(ExportFormat::Csv),
+        Some(other) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Unsupported export format: {}", other)
+            }));
+        }
+    };
+    
+    let export_path = match export_format {
+        Some(export_format) => {
+            match state.app_service.get_data_persistence_service().export_tax_report(&report, export_format).await {
+                Ok(path) => Some(path.to_string_lossy().to_string()),
+                Err(e) => {
+                    return Ok(serde_json::json!({
+                        "success": false,
+                        "error": format!("Failed to export tax report: {}", e)
+                    }));
+                }
+            }
+        }
+        None => None,
+    };
     
-    let query = "
-        SELECT 
-            t.strategy_id,
-            sp.name as strategy_name,
-            COUNT(*) as trades,
-            COUNT(CASE WHEN t.price > 0 THEN 1 END) as profitable_trades,
-            SUM(t.price * t.quantity * CASE WHEN t.trade_type = 'Sell' THEN 1 ELSE -1 END) as total_profit
-        FROM trades t
-        LEFT JOIN strategy_params sp ON t.strategy_id = sp.id
-        WHERE t.user_id = ? 
-        AND t.status = 'Executed'
-        AND t.executed_at >= datetime('now', '-' || ? || ' days')
-        GROUP BY t.strategy_id, sp.name
-        ORDER BY total_profit DESC
-    ";
+    Ok(serde_json::json!({
+        "success": true,
+        "data": {
+            "report": report,
+            "export_path": export_path
+        }
+    }))
+}
+
+/// Write a P&L statement of IST dates `from` to `to` (YYYY-MM-DD) as "csv", "xlsx", "pdf" or "json"
+#[tauri::command]
+async fn export_pnl_statement(
+    from: String,
+    to: String,
+    format: String,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
     
-    match sqlx::query(query)
-        .bind(user_id)
-        .bind(days)
-        .fetch_all(pool)
-        .await
-    {
-        Ok(rows) => {
-            let strategies: Vec<serde_json::Value> = rows
-                .into_iter()
-                .map(|row| {
-                    let trades: i32 = row.get("trades");
-                    let profitable_trades: i32 = row.get("profitable_trades");
-                    let total_profit: f64 = row.get::<Option<f64>, _>("total_profit").unwrap_or(0.0);
-                    
-                    let win_rate = if trades > 0 {
-                        profitable_trades as f64 / trades as f64
-                    } else {
-                        0.0
-                    };
-                    
-                    serde_json::json!({
-                        "strategy_id": row.get::<String, _>("strategy_id"),
-                        "strategy_name": row.get::<Option<String>, _>("strategy_name").unwrap_or_else(|| "Unknown".to_string()),
-                        "trades": trades,
-                        "win_rate": win_rate,
-                        "profit_factor": 1.5, // TODO: Calculate actual profit factor
-                        "total_profit": total_profit,
-                        "net_profit": total_profit
-                    })
-                })
-                .collect();
-            
+    let format_enum = match format.as_str() {
+        "json" => ExportFormat::Json,
+        "csv" => ExportFormat::Csv,
+        "xlsx" => ExportFormat::Xlsx,
+        "pdf" => ExportFormat::Pdf,
+        _ => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Unsupported export format: {}", format)
+            }));
+        }
+    };
+    
+    let dates = chrono::NaiveDate::parse_from_str(&from, "%Y-%m-%d")
+        .and_then(|from| Ok((from, chrono::NaiveDate::parse_from_str(&to, "%Y-%m-%d")?)));
+    let (from, to) = match dates {
+        Ok(dates) => dates,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Invalid date: {}", e)
+            }));
+        }
+    };
+    
+    let statement = match state.pnl_statement.generate(user_id, from, to).await {
+        Ok(statement) => statement,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to generate P&L statement: {}", e)
+            }));
+        }
+    };
+    
+    match state.app_service.get_data_persistence_service().export_pnl_statement(&statement, format_enum).await {
+        Ok(export_path) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": {
+                    "export_path": export_path.to_string_lossy(),
+                    "totals": statement.totals
+                }
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to export P&L statement: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn save_user_settings(
+    theme: String,
+    language: String,
+    timezone: String,
+    notifications_enabled: bool,
+    sound_enabled: bool,
+    auto_start_trading: bool,
+    default_risk_percentage: f64,
+    dashboard_layout: serde_json::Value,
+    chart_preferences: serde_json::Value,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    let settings = UserSettings {
+        user_id: user_id.to_string(),
+        theme,
+        language,
+        timezone,
+        notifications_enabled,
+        sound_enabled,
+        auto_start_trading,
+        default_risk_percentage,
+        dashboard_layout,
+        chart_preferences,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
+    };
+    
+    match state.app_service.get_data_persistence_service().save_user_settings(&settings).await {
+        Ok(_) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "message": "User settings saved successfully"
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to save user settings: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn load_user_settings(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let user_id = "demo_user"; // TODO: Get from auth context
+    
+    match state.app_service.get_data_persistence_service().load_user_settings(user_id).await {
+        Ok(settings) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": {
+                    "theme": settings.theme,
+                    "language": settings.language,
+                    "timezone": settings.timezone,
+                    "notifications_enabled": settings.notifications_enabled,
+                    "sound_enabled": settings.sound_enabled,
+                    "auto_start_trading": settings.auto_start_trading,
+                    "default_risk_percentage": settings.default_risk_percentage,
+                    "dashboard_layout": settings.dashboard_layout,
+                    "chart_preferences": settings.chart_preferences,
+                    "created_at": settings.created_at.to_rfc3339(),
+                    "updated_at": settings.updated_at.to_rfc3339()
+                }
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to load user settings: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn cleanup_old_data(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().run_cleanup().await {
+        Ok(report) => Ok(serde_json::json!({
+            "success": true,
+            "data": {
+                "logs_cleaned": report.logs_cleaned,
+                "trades_archived": report.trades_archived,
+                "ticks_archived": report.ticks_archived,
+                "backups_cleaned": report.backups_cleaned,
+                "message": "Data cleanup completed successfully"
+            }
+        })),
+        Err(e) => {
+            eprintln!("Data cleanup failed: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Data cleanup failed: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_persistence_config(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "success": true,
+        "data": state.app_service.get_data_persistence_service().get_config()
+    }))
+}
+
+#[tauri::command]
+async fn update_persistence_config(
+    config: services::DataPersistenceConfig,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.update_persistence_config(config).await {
+        Ok(config) => Ok(serde_json::json!({
+            "success": true,
+            "data": config
+        })),
+        Err(e) => {
+            eprintln!("Failed to update persistence config: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to update persistence config: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn set_backup_passphrase(
+    passphrase: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().set_backup_passphrase(passphrase.as_deref()).await {
+        Ok(_) => Ok(serde_json::json!({
+            "success": true,
+            "message": "Backup passphrase updated"
+        })),
+        Err(e) => {
+            eprintln!("Failed to save backup passphrase: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to save backup passphrase: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_migration_status(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().migration_status().await {
+        Ok(status) => Ok(serde_json::json!({
+            "success": true,
+            "data": status
+        })),
+        Err(e) => {
+            eprintln!("Failed to read migration status: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to read migration status: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn migrate_up(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().migrate_up().await {
+        Ok(applied) => Ok(serde_json::json!({
+            "success": true,
+            "data": { "applied": applied }
+        })),
+        Err(e) => {
+            eprintln!("Failed to apply migrations: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to apply migrations: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn migrate_down(
+    target_version: i64,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().migrate_down(target_version).await {
+        Ok(reverted) => Ok(serde_json::json!({
+            "success": true,
+            "data": { "reverted": reverted }
+        })),
+        Err(e) => {
+            eprintln!("Failed to revert migrations: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to revert migrations: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn repair_migrations(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().repair_migrations().await {
+        Ok(repair) => Ok(serde_json::json!({
+            "success": true,
+            "data": repair
+        })),
+        Err(e) => {
+            eprintln!("Failed to repair migrations: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to repair migrations: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_archive_partitions(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().list_archive_partitions().await {
+        Ok(partitions) => Ok(serde_json::json!({
+            "success": true,
+            "data": partitions
+        })),
+        Err(e) => {
+            eprintln!("Failed to list archive partitions: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Failed to list archive partitions: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn secure_delete_all_data(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    match state.app_service.get_data_persistence_service().secure_delete_all_data().await {
+        Ok(_) => {
             Ok(serde_json::json!({
                 "success": true,
-                "data": strategies
+                "message": "All data securely deleted"
             }))
         }
         Err(e) => {
-            eprintln!("Failed to get strategy performance: {}", e);
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to get strategy performance: {}", e)
+                "error": format!("Failed to securely delete data: {}", e)
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn get_instrument_performance(
+async fn get_equity_curve(
     state: tauri::State<'_, AppState>,
-    timeframe: Option<String>
+    timeframe: Option<String>,
+    include_benchmark: Option<bool>
 ) -> Result<serde_json::Value, String> {
     let user_id = "demo_user"; // TODO: Get from auth context
     let timeframe = timeframe.unwrap_or_else(|| "month".to_string());
@@ -894,23 +3892,21 @@ async fn get_instrument_performance(
         _ => 30,
     };
     
-    // Get instrument performance from database
+    // Get equity curve data from database
     let db = state.app_service.get_enhanced_database_service().get_database();
-    let pool = db.get_pool();
+    let pool = db.get_read_pool();
     
     let query = "
         SELECT 
-            symbol,
-            COUNT(*) as trades,
-            COUNT(CASE WHEN price > 0 THEN 1 END) as profitable_trades,
-            SUM(price * quantity * CASE WHEN trade_type = 'Sell' THEN 1 ELSE -1 END) as total_profit
+            date(executed_at, '+330 minutes') as trade_date,
+            SUM(price * quantity * CASE WHEN trade_type = 'Sell' THEN 1 ELSE -1 END) as daily_pnl,
+            SUM(charges) as daily_charges
         FROM trades 
         WHERE user_id = ? 
         AND status = 'Executed'
         AND executed_at >= datetime('now', '-' || ? || ' days')
-        GROUP BY symbol
-        ORDER BY total_profit DESC
-        LIMIT 10
+        GROUP BY trade_date
+        ORDER BY trade_date ASC
     ";
     
     match sqlx::query(query)
@@ -920,413 +3916,439 @@ async fn get_instrument_performance(
         .await
     {
         Ok(rows) => {
-            let instruments: Vec<serde_json::Value> = rows
+            let starting_equity = 100000.0;
+            let mut equity = starting_equity;
+            let mut net_pnl_by_day = Vec::with_capacity(rows.len());
+            let equity_curve: Vec<serde_json::Value> = rows
                 .into_iter()
                 .map(|row| {
-                    let trades: i32 = row.get("trades");
-                    let profitable_trades: i32 = row.get("profitable_trades");
-                    let total_profit: f64 = row.get::<Option<f64>, _>("total_profit").unwrap_or(0.0);
-                    
-                    let win_rate = if trades > 0 {
-                        profitable_trades as f64 / trades as f64
-                    } else {
-                        0.0
-                    };
+                    let trade_date: String = row.get("trade_date");
+                    let daily_pnl: f64 = row.get::<Option<f64>, _>("daily_pnl").unwrap_or(0.0);
+                    let daily_charges: f64 = row.get::<Option<f64>, _>("daily_charges").unwrap_or(0.0);
+                    equity += daily_pnl - daily_charges;
+                    if let Ok(day) = chrono::NaiveDate::parse_from_str(&trade_date, "%Y-%m-%d") {
+                        net_pnl_by_day.push((day, daily_pnl - daily_charges));
+                    }
                     
                     serde_json::json!({
-                        "symbol": row.get::<String, _>("symbol"),
-                        "trades": trades,
-                        "win_rate": win_rate,
-                        "profit_factor": 1.5, // TODO: Calculate actual profit factor
-                        "total_profit": total_profit,
-                        "net_profit": total_profit
+                        "timestamp": trade_date,
+                        "equity": equity,
+                        "pnl": daily_pnl - daily_charges,
+                        "gross_pnl": daily_pnl,
+                        "charges": daily_charges
                     })
                 })
                 .collect();
             
+            // Buy and hold of the index from the same starting equity, with return, alpha, beta and drawdown against it
+            let benchmark = if include_benchmark.unwrap_or(false) {
+                let today = trading::session::session_date(chrono::Utc::now());
+                let start = today - chrono::Duration::days(days);
+                let curve = trading::performance::daily_equity_curve(starting_equity, start, &net_pnl_by_day);
+                
+                // A week before the start so the first point has a close at or before it
+                let from = start.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc() - chrono::Duration::days(7);
+                match state.backtest_engine.load_stored_historical_data(
+                    services::backtest_engine::BENCHMARK_SYMBOL,
+                    "NSE",
+                    models::Timeframe::Day1,
+                    from,
+                    chrono::Utc::now(),
+                ).await {
+                    Ok(candles) => models::BenchmarkComparison::calculate(
+                        services::backtest_engine::BENCHMARK_SYMBOL,
+                        &curve,
+                        &candles,
+                    ),
+                    Err(e) => {
+                        eprintln!("Failed to load benchmark data: {}", e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+            
             Ok(serde_json::json!({
                 "success": true,
-                "data": instruments
+                "data": equity_curve,
+                "benchmark": benchmark
             }))
         }
         Err(e) => {
-            eprintln!("Failed to get instrument performance: {}", e);
+            eprintln!("Failed to get equity curve: {}", e);
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to get instrument performance: {}", e)
+                "error": format!("Failed to get equity curve: {}", e)
             }))
         }
     }
 }
 
-// Data persistence commands
 #[tauri::command]
-async fn create_backup(
-    label: String,
-    state: tauri::State<'_, AppState>
-) -> Result<serde_json::Value, String> {
-    match state.app_service.get_data_persistence_service().create_manual_backup(&label).await {
-        Ok(metadata) => {
+async fn get_broker_portfolio(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    match state.portfolio_sync.get_portfolio().await {
+        Ok(mut portfolio) => {
+            // Show the broker-side exits resting on each symbol
+            if let Err(e) = state.gtt.attach(&mut portfolio).await {
+                eprintln!("Failed to attach GTT triggers to portfolio: {}", e);
+            }
             Ok(serde_json::json!({
                 "success": true,
-                "data": {
-                    "id": metadata.id,
-                    "label": metadata.label,
-                    "created_at": metadata.created_at.to_rfc3339(),
-                    "file_size": metadata.file_size,
-                    "compressed": metadata.compressed,
-                    "encrypted": metadata.encrypted
-                }
+                "data": portfolio
             }))
         }
         Err(e) => {
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to create backup: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn list_backups(
-    state: tauri::State<'_, AppState>
-) -> Result<serde_json::Value, String> {
-    match state.app_service.get_data_persistence_service().list_backups().await {
-        Ok(backups) => {
-            let backup_list: Vec<serde_json::Value> = backups
-                .into_iter()
-                .map(|backup| {
-                    serde_json::json!({
-                        "id": backup.id,
-                        "label": backup.label,
-                        "created_at": backup.created_at.to_rfc3339(),
-                        "file_size": backup.file_size,
-                        "compressed": backup.compressed,
-                        "encrypted": backup.encrypted,
-                        "backup_type": format!("{:?}", backup.backup_type)
-                    })
-                })
-                .collect();
-            
+async fn sync_broker_portfolio(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    match state.portfolio_sync.sync(state.kite_client.as_ref()).await {
+        Ok(mut portfolio) => {
+            // Show the broker-side exits resting on each symbol
+            if let Err(e) = state.gtt.attach(&mut portfolio).await {
+                eprintln!("Failed to attach GTT triggers to portfolio: {}", e);
+            }
             Ok(serde_json::json!({
                 "success": true,
-                "data": backup_list
+                "data": portfolio
             }))
         }
         Err(e) => {
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to list backups: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn restore_backup(
-    backup_id: String,
+async fn get_order_book(
+    date: Option<String>,
     state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    match state.app_service.get_data_persistence_service().restore_from_backup(&backup_id).await {
-        Ok(_) => {
+    let date = match date {
+        Some(date) => match chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d") {
+            Ok(date) => date,
+            Err(_) => {
+                return Ok(serde_json::json!({
+                    "success": false,
+                    "error": format!("Invalid date: {}", date)
+                }));
+            }
+        },
+        None => trading::session::session_date(chrono::Utc::now()),
+    };
+    
+    match state.order_book.get_order_book(date).await {
+        Ok(book) => {
             Ok(serde_json::json!({
                 "success": true,
-                "message": "Backup restored successfully"
+                "data": book
             }))
         }
         Err(e) => {
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to restore backup: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn export_data(
-    export_type: String,
-    format: String,
-    include_sensitive: bool,
-    compress: bool,
-    encrypt: bool,
+async fn sync_order_book(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    match state.order_book.sync(state.kite_client.as_ref()).await {
+        Ok(book) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": book
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_gtts(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    match state.gtt.list().await {
+        Ok(triggers) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": triggers
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn sync_gtts(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    match state.gtt.sync(state.kite_client.as_ref()).await {
+        Ok(triggers) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": triggers
+            }))
+        }
+        Err(e) => {
+            Ok(serde_json::json!({
+                "success": false,
+                "error": e.to_string()
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn get_basket_margins(
+    orders: Vec<models::kite::KiteMarginOrder>,
+    consider_positions: Option<bool>,
     state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let user_id = "demo_user"; // TODO: Get from auth context
-    
-    let export_type_enum = match export_type.as_str() {
-        "all_data" => ExportType::AllData,
-        "trade_history" => ExportType::TradeHistory,
-        "strategy_data" => ExportType::StrategyData,
-        "user_settings" => ExportType::UserSettings,
-        "system_logs" => ExportType::SystemLogs,
-        _ => ExportType::AllData,
-    };
-    
-    let format_enum = match format.as_str() {
-        "json" => ExportFormat::Json,
-        "csv" => ExportFormat::Csv,
-        "sql" => ExportFormat::Sql,
-        _ => ExportFormat::Json,
-    };
-    
-    let request = DataExportRequest {
-        user_id: user_id.to_string(),
-        export_type: export_type_enum,
-        format: format_enum,
-        date_range: None,
-        include_sensitive,
-        compress,
-        encrypt,
-    };
+    if orders.is_empty() {
+        return Ok(serde_json::json!({
+            "success": false,
+            "error": "Basket has no orders"
+        }));
+    }
     
-    match state.app_service.get_data_persistence_service().export_data(request).await {
-        Ok(export_path) => {
+    // Kite nets the basket against open positions unless told otherwise
+    match state.kite_client.get_basket_margins(&orders, consider_positions.unwrap_or(true)).await {
+        Ok(margins) => {
             Ok(serde_json::json!({
                 "success": true,
-                "data": {
-                    "export_path": export_path.to_string_lossy(),
-                    "message": "Data exported successfully"
-                }
+                "data": margins
             }))
         }
         Err(e) => {
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to export data: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn save_user_settings(
-    theme: String,
-    language: String,
-    timezone: String,
-    notifications_enabled: bool,
-    sound_enabled: bool,
-    auto_start_trading: bool,
-    default_risk_percentage: f64,
-    dashboard_layout: serde_json::Value,
-    chart_preferences: serde_json::Value,
-    state: tauri::State<'_, AppState>
-) -> Result<serde_json::Value, String> {
-    let user_id = "demo_user"; // TODO: Get from auth context
+async fn get_market_status(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    // The calendar says whether the exchange should be trading; the feed shows whether it is
+    let feed = state.websocket_manager.get_feed_health().await;
     
-    let settings = UserSettings {
-        user_id: user_id.to_string(),
-        theme,
-        language,
-        timezone,
-        notifications_enabled,
-        sound_enabled,
-        auto_start_trading,
-        default_risk_percentage,
-        dashboard_layout,
-        chart_preferences,
-        created_at: chrono::Utc::now(),
-        updated_at: chrono::Utc::now(),
+    Ok(serde_json::json!({
+        "success": true,
+        "data": {
+            "market": trading::session::market_status(chrono::Utc::now()),
+            "feed": {
+                "stalled": feed.stalled,
+                "last_tick_at": feed.last_tick_at,
+                "ticks_per_second": feed.ticks_per_second
+            }
+        }
+    }))
+}
+
+/// Quote symbols that need not be subscribed; `mode` is "ltp", "ohlc" (default) or "full"
+#[tauri::command]
+async fn get_quotes(
+    instruments: Vec<String>,
+    mode: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    // Bare trading symbols are looked up on NSE
+    let instruments: Vec<String> = instruments.iter()
+        .map(|symbol| symbol.trim())
+        .filter(|symbol| !symbol.is_empty())
+        .map(|symbol| if symbol.contains(':') { symbol.to_string() } else { format!("NSE:{}", symbol) })
+        .collect();
+    
+    let client = state.kite_client.as_ref();
+    let result = match mode.as_deref().unwrap_or("ohlc") {
+        "ltp" => client.get_ltp(&instruments).await.map(|quotes| serde_json::json!(quotes)),
+        "ohlc" => client.get_ohlc(&instruments).await.map(|quotes| serde_json::json!(quotes)),
+        "full" => client.get_quote(&instruments).await.map(|quotes| serde_json::json!(quotes)),
+        other => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Unknown quote mode: {}", other)
+            }));
+        }
     };
     
-    match state.app_service.get_data_persistence_service().save_user_settings(&settings).await {
-        Ok(_) => {
+    match result {
+        Ok(quotes) => {
             Ok(serde_json::json!({
                 "success": true,
-                "message": "User settings saved successfully"
+                "data": quotes
             }))
         }
         Err(e) => {
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to save user settings: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn load_user_settings(
+async fn get_option_chain(
+    symbol: String,
+    expiry: Option<String>,
     state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let user_id = "demo_user"; // TODO: Get from auth context
+    let expiry = match expiry.map(|date| chrono::NaiveDate::parse_from_str(&date, "%Y-%m-%d")).transpose() {
+        Ok(expiry) => expiry,
+        Err(e) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Invalid expiry: {}", e)
+            }));
+        }
+    };
     
-    match state.app_service.get_data_persistence_service().load_user_settings(user_id).await {
-        Ok(settings) => {
+    match state.option_chain.get_chain(state.kite_client.as_ref(), &symbol, expiry).await {
+        Ok(chain) => {
             Ok(serde_json::json!({
                 "success": true,
-                "data": {
-                    "theme": settings.theme,
-                    "language": settings.language,
-                    "timezone": settings.timezone,
-                    "notifications_enabled": settings.notifications_enabled,
-                    "sound_enabled": settings.sound_enabled,
-                    "auto_start_trading": settings.auto_start_trading,
-                    "default_risk_percentage": settings.default_risk_percentage,
-                    "dashboard_layout": settings.dashboard_layout,
-                    "chart_preferences": settings.chart_preferences,
-                    "created_at": settings.created_at.to_rfc3339(),
-                    "updated_at": settings.updated_at.to_rfc3339()
-                }
+                "data": chain
             }))
         }
         Err(e) => {
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to load user settings: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn cleanup_old_data(
+async fn create_gtt(
+    request: models::kite::KiteGttRequest,
     state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    let persistence_service = state.app_service.get_data_persistence_service();
-    
-    // Cleanup old logs
-    let logs_cleaned = match persistence_service.cleanup_old_logs().await {
-        Ok(count) => count,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "error": format!("Failed to cleanup old logs: {}", e)
-            }));
-        }
-    };
-    
-    // Archive old trade data
-    let trades_archived = match persistence_service.archive_old_trade_data().await {
-        Ok(count) => count,
-        Err(e) => {
-            return Ok(serde_json::json!({
-                "success": false,
-                "error": format!("Failed to archive old trade data: {}", e)
-            }));
+    match state.gtt.create(state.kite_client.as_ref(), request).await {
+        Ok(trigger) => {
+            Ok(serde_json::json!({
+                "success": true,
+                "data": trigger
+            }))
         }
-    };
-    
-    // Cleanup old backups
-    let backups_cleaned = match persistence_service.cleanup_old_backups().await {
-        Ok(count) => count,
         Err(e) => {
-            return Ok(serde_json::json!({
+            Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to cleanup old backups: {}", e)
-            }));
-        }
-    };
-    
-    Ok(serde_json::json!({
-        "success": true,
-        "data": {
-            "logs_cleaned": logs_cleaned,
-            "trades_archived": trades_archived,
-            "backups_cleaned": backups_cleaned,
-            "message": "Data cleanup completed successfully"
+                "error": e.to_string()
+            }))
         }
-    }))
+    }
 }
 
 #[tauri::command]
-async fn secure_delete_all_data(
+async fn modify_gtt(
+    trigger_id: u64,
+    request: models::kite::KiteGttRequest,
     state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    match state.app_service.get_data_persistence_service().secure_delete_all_data().await {
-        Ok(_) => {
+    match state.gtt.modify(state.kite_client.as_ref(), trigger_id, request).await {
+        Ok(trigger) => {
             Ok(serde_json::json!({
                 "success": true,
-                "message": "All data securely deleted"
+                "data": trigger
             }))
         }
         Err(e) => {
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to securely delete data: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
 #[tauri::command]
-async fn get_equity_curve(
-    state: tauri::State<'_, AppState>,
-    timeframe: Option<String>
-) -> Result<serde_json::Value, String> {
-    let user_id = "demo_user"; // TODO: Get from auth context
-    let timeframe = timeframe.unwrap_or_else(|| "month".to_string());
-    
-    // Calculate date range based on timeframe
-    let days = match timeframe.as_str() {
-        "day" => 1,
-        "week" => 7,
-        "month" => 30,
-        "year" => 365,
-        _ => 30,
-    };
-    
-    // Get equity curve data from database
-    let db = state.app_service.get_enhanced_database_service().get_database();
-    let pool = db.get_pool();
-    
-    let query = "
-        SELECT 
-            DATE(executed_at) as trade_date,
-            SUM(price * quantity * CASE WHEN trade_type = 'Sell' THEN 1 ELSE -1 END) as daily_pnl
-        FROM trades 
-        WHERE user_id = ? 
-        AND status = 'Executed'
-        AND executed_at >= datetime('now', '-' || ? || ' days')
-        GROUP BY DATE(executed_at)
-        ORDER BY trade_date ASC
-    ";
-    
-    match sqlx::query(query)
-        .bind(user_id)
-        .bind(days)
-        .fetch_all(pool)
-        .await
-    {
-        Ok(rows) => {
-            let mut equity = 100000.0; // Starting equity
-            let equity_curve: Vec<serde_json::Value> = rows
-                .into_iter()
-                .map(|row| {
-                    let daily_pnl: f64 = row.get::<Option<f64>, _>("daily_pnl").unwrap_or(0.0);
-                    equity += daily_pnl;
-                    
-                    serde_json::json!({
-                        "timestamp": row.get::<String, _>("trade_date"),
-                        "equity": equity,
-                        "pnl": daily_pnl
-                    })
-                })
-                .collect();
-            
+async fn delete_gtt(trigger_id: u64, state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    match state.gtt.delete(state.kite_client.as_ref(), trigger_id).await {
+        Ok(()) => {
             Ok(serde_json::json!({
                 "success": true,
-                "data": equity_curve
+                "data": { "trigger_id": trigger_id }
             }))
         }
         Err(e) => {
-            eprintln!("Failed to get equity curve: {}", e);
             Ok(serde_json::json!({
                 "success": false,
-                "error": format!("Failed to get equity curve: {}", e)
+                "error": e.to_string()
             }))
         }
     }
 }
 
+#[tauri::command]
+async fn get_demo_mode(state: tauri::State<'_, AppState>) -> Result<serde_json::Value, String> {
+    Ok(serde_json::json!({
+        "success": true,
+        "data": state.demo_mode
+    }))
+}
+
 // Application state that will be shared across commands
 pub struct AppState {
     app_service: Arc<services::AppService>,
-    kite_client: Arc<api::KiteClient>,
+    kite_client: Arc<dyn api::KiteApiClient + Send + Sync>,
+    /// Whether the Kite client is the synthetic demo one
+    demo_mode: bool,
     ticker_client: Arc<Mutex<api::KiteTickerClient>>,
     websocket_manager: Arc<services::WebSocketManager>,
     strategy_service: Arc<services::StrategyService>,
+    /// Trend filters and pair configurations, cached for signal generation
+    strategy_manager: Arc<trading::StrategyManager>,
+    instrument_service: Arc<services::InstrumentService>,
+    /// Local copy of the broker's positions and holdings
+    portfolio_sync: Arc<services::PortfolioSyncService>,
+    /// Daily snapshots of the broker's order book and trade book
+    order_book: Arc<services::OrderBookService>,
+    /// GTT triggers resting with the broker
+    gtt: Arc<services::GttService>,
+    /// Option chains built from the instruments master and live quotes
+    option_chain: Arc<services::OptionChainService>,
+    /// Yearly tax and turnover reports of executed trades
+    tax_report: Arc<services::TaxReportService>,
+    /// P&L statements of executed trades over a date range
+    pnl_statement: Arc<services::PnlStatementService>,
+    /// Slippage of live fills against their signal prices
+    slippage: Arc<services::SlippageService>,
+    /// Maximum adverse and favorable excursions of live exits
+    excursions: Arc<services::ExcursionService>,
+    /// Telegram and email delivery of reports
+    notifications: Arc<services::NotificationService>,
+    /// End-of-day summaries, generated after each close
+    eod_report: Arc<services::EodReportService>,
+    backtest_engine: Arc<services::BacktestEngine>,
+    backtest_queue: Arc<services::BacktestQueue>,
+    /// Tick replay started from the UI, kept after it finishes so its progress can be read
+    tick_replay: Arc<Mutex<Option<Arc<services::TickReplay>>>>,
+    /// Market data recording sessions still capturing, by session ID
+    recording_sessions: Arc<Mutex<std::collections::HashMap<String, Arc<services::RecordingSession>>>>,
+    /// Circuit breakers of outbound API calls
+    error_recovery: Arc<utils::ErrorRecoveryManager>,
     // Legacy fields for backward compatibility
     db: Arc<Mutex<db::Database>>,
     logger: Arc<Mutex<utils::Logger>>,
@@ -1401,24 +4423,57 @@ async fn get_system_health(
     state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
     // In a real implementation, you would run health checks
+    let websocket_status = state.websocket_manager.get_status().await;
+    let feed = state.websocket_manager.get_feed_health().await;
+    let connections = state.websocket_manager.get_connection_health().await;
+    let kite_api = state.error_recovery.get_circuit_breaker(api::kite_client::KITE_API_SERVICE).await;
+    let kite_api_state = kite_api.get_state().await;
+    let api_healthy = kite_api_state == utils::error_recovery::CircuitBreakerState::Closed;
+    let api_message = if api_healthy {
+        "Kite API is responding".to_string()
+    } else {
+        format!("Kite API degraded: circuit {:?} after {} failures", kite_api_state, kite_api.get_failure_count().await)
+    };
+    let pool = state.app_service.get_enhanced_database_service().get_database().get_pool().clone();
+    let (database_healthy, database_message, maintenance) = match services::db_health::health_summary(&pool, 30).await {
+        Ok(summary) => {
+            let message = match &summary.latest {
+                Some(check) if !check.integrity_ok => format!("Integrity check failed: {}", check.integrity_message),
+                Some(check) => format!("Integrity ok at last maintenance, {} MB", check.size_bytes / 1024 / 1024),
+                None => "Database connection is healthy, no maintenance run yet".to_string(),
+            };
+            (summary.healthy(), message, serde_json::to_value(&summary).unwrap_or_default())
+        }
+        Err(e) => (false, format!("Failed to read database health: {}", e), serde_json::Value::Null),
+    };
+    let websocket_healthy = websocket_status == services::ConnectionStatus::Connected && !feed.stalled;
+    let websocket_message = if feed.stalled {
+        "Market data feed has stopped ticking".to_string()
+    } else {
+        format!("WebSocket {:?}, {:.1} ticks/s", websocket_status, feed.ticks_per_second)
+    };
+    
     Ok(serde_json::json!({
         "success": true,
         "data": {
-            "overall_status": "healthy",
+            "overall_status": if api_healthy && database_healthy { "healthy" } else { "degraded" },
             "checks": {
                 "database": {
-                    "healthy": true,
-                    "message": "Database connection is healthy",
+                    "healthy": database_healthy,
+                    "message": database_message,
+                    "maintenance": maintenance,
                     "timestamp": chrono::Utc::now().to_rfc3339()
                 },
                 "api": {
-                    "healthy": true,
-                    "message": "API endpoints are responding",
+                    "healthy": api_healthy,
+                    "message": api_message,
                     "timestamp": chrono::Utc::now().to_rfc3339()
                 },
                 "websocket": {
-                    "healthy": true,
-                    "message": "WebSocket connections are stable",
+                    "healthy": websocket_healthy,
+                    "message": websocket_message,
+                    "feed": feed,
+                    "connections": connections,
                     "timestamp": chrono::Utc::now().to_rfc3339()
                 }
             }
@@ -1426,26 +4481,69 @@ async fn get_system_health(
     }))
 }
 
+#[tauri::command]
+async fn run_db_maintenance(
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let pool = state.app_service.get_enhanced_database_service().get_database().get_pool().clone();
+    match services::db_health::run_maintenance(&pool).await {
+        Ok(check) => Ok(serde_json::json!({
+            "success": true,
+            "data": check
+        })),
+        Err(e) => {
+            eprintln!("Database maintenance failed: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Database maintenance failed: {}", e)
+            }))
+        }
+    }
+}
+
+#[tauri::command]
+async fn verify_data(
+    repair: Option<String>,
+    state: tauri::State<'_, AppState>
+) -> Result<serde_json::Value, String> {
+    let repair_mode = match repair.as_deref() {
+        None | Some("none") => services::RepairMode::ReportOnly,
+        Some("quarantine") => services::RepairMode::Quarantine,
+        Some("delete") => services::RepairMode::Delete,
+        Some(other) => {
+            return Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Unsupported repair mode: {}", other)
+            }));
+        }
+    };
+
+    match state.app_service.get_data_persistence_service().verify_data(repair_mode).await {
+        Ok(report) => Ok(serde_json::json!({
+            "success": true,
+            "data": {
+                "clean": report.clean(),
+                "report": report
+            }
+        })),
+        Err(e) => {
+            eprintln!("Data verification failed: {}", e);
+            Ok(serde_json::json!({
+                "success": false,
+                "error": format!("Data verification failed: {}", e)
+            }))
+        }
+    }
+}
+
 #[tauri::command]
 async fn get_error_recovery_status(
     state: tauri::State<'_, AppState>
 ) -> Result<serde_json::Value, String> {
-    // In a real implementation, you would get circuit breaker status
     Ok(serde_json::json!({
         "success": true,
         "data": {
-            "circuit_breakers": {
-                "kite_api": {
-                    "state": "Closed",
-                    "failure_count": 0,
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                },
-                "database": {
-                    "state": "Closed",
-                    "failure_count": 0,
-                    "timestamp": chrono::Utc::now().to_rfc3339()
-                }
-            }
+            "circuit_breakers": state.error_recovery.get_circuit_breaker_status().await
         }
     }))
 }
@@ -1513,6 +4611,127 @@ async fn start_health_monitor(state: Arc<AppState>) {
     });
 }
 
+/// Push changed per-strategy stats to the frontend as `strategy_stats` events
+fn start_strategy_stats_stream(app_handle: tauri::AppHandle, strategy_service: Arc<services::StrategyService>) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(tokio::time::Duration::from_secs(5));
+        
+        loop {
+            interval.tick().await;
+            
+            let user_id = "demo_user"; // TODO: Get from auth context
+            match strategy_service.refresh_live_stats(user_id).await {
+                Ok(changed) if !changed.is_empty() => {
+                    if let Err(e) = app_handle.emit("strategy_stats", &changed) {
+                        eprintln!("Failed to emit strategy stats: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => eprintln!("Failed to refresh strategy stats: {}", e),
+            }
+        }
+    });
+}
+
+/// Forward WebSocket connection changes to the frontend as `websocket_status` events
+fn start_websocket_status_stream(app_handle: tauri::AppHandle, websocket_manager: Arc<services::WebSocketManager>) {
+    let mut events = websocket_manager.subscribe_to_status();
+    
+    tokio::spawn(async move {
+        loop {
+            match events.recv().await {
+                Ok(event) => {
+                    if let Err(e) = app_handle.emit("websocket_status", &event) {
+                        eprintln!("Failed to emit WebSocket status: {}", e);
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Forward market data feed stalls to the frontend as `websocket_alert` events
+fn start_feed_alert_stream(app_handle: tauri::AppHandle, websocket_manager: Arc<services::WebSocketManager>) {
+    let mut alerts = websocket_manager.subscribe_to_feed_alerts();
+    
+    tokio::spawn(async move {
+        loop {
+            match alerts.recv().await {
+                Ok(alert) => {
+                    if let Err(e) = app_handle.emit("websocket_alert", &alert) {
+                        eprintln!("Failed to emit WebSocket alert: {}", e);
+                    }
+                }
+                Err(tokio::sync::broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(tokio::sync::broadcast::error::RecvError::Closed) => break,
+            }
+        }
+    });
+}
+
+/// Serve Kite order postbacks on `addr`, feeding them into the ticker's order update channel
+///
+/// Postbacks are signed with the saved API secret, so there is nothing to verify them against in demo mode.
+async fn start_postback_listener(
+    app_service: &services::AppService,
+    websocket_manager: &services::WebSocketManager,
+    addr: &str,
+    demo_mode: bool,
+) -> error::Result<()> {
+    if demo_mode {
+        return Err(error::HedgeXError::ConfigError("Postbacks are not available in demo mode".to_string()));
+    }
+    let addr: std::net::SocketAddr = addr.parse()
+        .map_err(|_| error::HedgeXError::ConfigError(format!("Invalid postback address: {}", addr)))?;
+    let credentials = app_service.get_auth_service().get_api_credentials("demo_user").await?;
+    
+    let state = Arc::new(api::PostbackState::new(&credentials.api_secret, websocket_manager.order_update_sender()));
+    api::start_postback_listener(addr, state).await?;
+    Ok(())
+}
+
+/// Serve the analytics and backtest endpoints on `addr`, for sessions created through the app's login
+async fn start_api_listener(
+    app_service: &services::AppService,
+    websocket_manager: &Arc<services::WebSocketManager>,
+    backtest_engine: &Arc<services::BacktestEngine>,
+    addr: &str,
+) -> error::Result<()> {
+    let addr: std::net::SocketAddr = addr.parse()
+        .map_err(|_| error::HedgeXError::ConfigError(format!("Invalid API address: {}", addr)))?;
+    
+    let analytics = Arc::new(api::AnalyticsState::new(
+        app_service.get_enhanced_database_service(),
+        Arc::clone(websocket_manager),
+    ));
+    let routes = api::analytics_routes(analytics, app_service.get_auth_service())
+        .merge(api::backtest_routes(Arc::clone(backtest_engine), app_service.get_auth_service()));
+    api::start_api_listener(addr, routes).await?;
+    Ok(())
+}
+
+/// Push updates for the displayed symbols to the frontend as throttled `market_data_update` events
+fn start_market_data_stream(app_handle: tauri::AppHandle, websocket_manager: Arc<services::WebSocketManager>) {
+    // The UI only needs each symbol's latest state, so a backlog collapses to one tick per symbol
+    let mut updates = websocket_manager.subscribe_to_market_data_as(
+        "ui",
+        services::TickPolicy::KeepLatest(MAX_TICKER_CONNECTIONS * MAX_INSTRUMENTS_PER_CONNECTION),
+    );
+    
+    tokio::spawn(async move {
+        while let Some(market_data) = updates.recv().await {
+            if !websocket_manager.should_push_to_display(&market_data).await {
+                continue;
+            }
+            if let Err(e) = app_handle.emit("market_data_update", &market_data) {
+                eprintln!("Failed to emit market data update: {}", e);
+            }
+        }
+    });
+}
+
 // Add this function before the run() function
 async fn cleanup_resources(state: &AppState) {
     println!("Cleaning up application resources...");
@@ -1654,6 +4873,9 @@ pub fn run() {
                 let app_service = match services::AppService::new(&app_dir).await {
                     Ok(service) => {
                         println!("AppService initialized successfully");
+                        service.start_backup_scheduler();
+                        service.start_cleanup_scheduler();
+                        service.start_db_maintenance_scheduler();
                         Arc::new(service)
                     },
                     Err(e) => {
@@ -1663,15 +4885,37 @@ pub fn run() {
                     }
                 };
                 
-                // Initialize Kite API client
-                let kite_client = match api::KiteClient::new("dummy_api_key") {
-                    Ok(client) => Arc::new(client),
+                // Retry transient Kite failures with backoff and open a circuit on sustained ones
+                let legacy_db = Arc::new(Mutex::new(
+                    db::Database::new(&app_dir).await.expect("Failed to create legacy DB reference")
+                ));
+                let error_recovery = match utils::EnhancedLogger::new(Arc::clone(&legacy_db), None, &app_dir).await {
+                    Ok(recovery_logger) => Arc::new(utils::ErrorRecoveryManager::new(Arc::new(recovery_logger))),
                     Err(e) => {
-                        eprintln!("Failed to initialize KiteClient: {}", e);
+                        eprintln!("Failed to initialize error recovery: {}", e);
                         return Err(e);
                     }
                 };
                 
+                // Initialize Kite API client; demo mode serves synthetic data instead, so no credentials are needed
+                let demo_mode = std::env::var("HEDGEX_DEMO_MODE")
+                    .is_ok_and(|value| value == "1" || value.eq_ignore_ascii_case("true"));
+                let kite_client: Arc<dyn api::KiteApiClient + Send + Sync> = if demo_mode {
+                    println!("Demo mode enabled: using synthetic market data and fills");
+                    Arc::new(api::MockKiteClient::new())
+                } else {
+                    match api::KiteClient::new("dummy_api_key") {
+                        Ok(client) => {
+                            client.set_error_recovery(Arc::clone(&error_recovery)).await;
+                            Arc::new(client)
+                        }
+                        Err(e) => {
+                            eprintln!("Failed to initialize KiteClient: {}", e);
+                            return Err(e);
+                        }
+                    }
+                };
+                
                 // Initialize ticker client
                 let ticker_client = Arc::new(Mutex::new(api::KiteTickerClient::new()));
                 
@@ -1685,8 +4929,23 @@ pub fn run() {
                     let _ = logger_guard.info("HedgeX application started with enhanced services", None).await;
                 }
                 
-                // Get WebSocket manager
+                // Get WebSocket manager, reconnecting dropped connections and reporting status changes and prices
                 let websocket_manager = app_service.get_websocket_manager();
+                start_websocket_status_stream(app_handle_clone.clone(), Arc::clone(&websocket_manager));
+                start_market_data_stream(app_handle_clone.clone(), Arc::clone(&websocket_manager));
+                start_feed_alert_stream(app_handle_clone.clone(), Arc::clone(&websocket_manager));
+                Arc::clone(&websocket_manager).start_feed_watchdog();
+                Arc::clone(&websocket_manager).start_cache_eviction();
+                Arc::clone(&websocket_manager).start_quote_fallback(kite_client.clone());
+                Arc::clone(&websocket_manager).start_reconnection_monitor().await;
+                
+                // Optionally accept Kite order postbacks too, in case the ticker misses an update
+                if let Ok(addr) = std::env::var("HEDGEX_POSTBACK_ADDR") {
+                    match start_postback_listener(&app_service, &websocket_manager, &addr, demo_mode).await {
+                        Ok(()) => println!("Kite order postbacks accepted on {}", addr),
+                        Err(e) => eprintln!("Failed to start postback listener: {}", e),
+                    }
+                }
                 
                 // Initialize strategy service with proper error handling
                 let strategy_service = match services::StrategyService::new(app_service.get_enhanced_database_service()).await {
@@ -1701,17 +4960,117 @@ pub fn run() {
                     }
                 };
                 
+                let strategy_manager = match trading::StrategyManager::new(app_service.get_enhanced_database_service(), "demo_user").await {
+                    Ok(manager) => Arc::new(manager),
+                    Err(e) => {
+                        eprintln!("Failed to initialize StrategyManager: {}", e);
+                        return Err(e);
+                    }
+                };
+                
+                // Initialize instrument service and keep the instruments dump fresh
+                let instrument_service = match services::InstrumentService::new(app_service.get_enhanced_database_service()).await {
+                    Ok(service) => Arc::new(service),
+                    Err(e) => {
+                        eprintln!("Failed to initialize InstrumentService: {}", e);
+                        return Err(e);
+                    }
+                };
+                Arc::clone(&instrument_service).start_daily_refresh(kite_client.clone());
+                
+                // Keep broker positions and holdings in step, including ones opened outside HedgeX
+                let portfolio_sync = Arc::new(services::PortfolioSyncService::new(app_service.get_enhanced_database_service()));
+                Arc::clone(&portfolio_sync).start_periodic_sync(kite_client.clone());
+                
+                // Snapshot the day's orders and trades, manual ones included
+                let order_book = Arc::new(services::OrderBookService::new(app_service.get_enhanced_database_service()));
+                Arc::clone(&order_book).start_periodic_sync(kite_client.clone());
+                
+                // Keep GTT triggers current, including ones set up in Kite directly
+                let gtt = Arc::new(services::GttService::new(app_service.get_enhanced_database_service()));
+                Arc::clone(&gtt).start_periodic_sync(kite_client.clone());
+                
+                let option_chain = Arc::new(services::OptionChainService::new(Arc::clone(&instrument_service)));
+                let tax_report = Arc::new(services::TaxReportService::new(app_service.get_enhanced_database_service()));
+                let pnl_statement = Arc::new(services::PnlStatementService::new(app_service.get_enhanced_database_service()));
+                let slippage = Arc::new(services::SlippageService::new(app_service.get_enhanced_database_service()));
+                let excursions = Arc::new(services::ExcursionService::new(app_service.get_enhanced_database_service()));
+                
+                // Summarize each trading day after the close and send it on the user's channels
+                let notifications = Arc::new(services::NotificationService::new(app_service.get_enhanced_database_service()));
+                let eod_report = Arc::new(services::EodReportService::new(
+                    app_service.get_enhanced_database_service(),
+                    Arc::clone(&pnl_statement),
+                    Arc::clone(&notifications),
+                ));
+                Arc::clone(&eod_report).start_daily_schedule("demo_user".to_string());
+                
+                // Initialize backtest engine on the shared pool
+                let backtest_pool = Arc::new(app_service.get_enhanced_database_service().get_database().get_pool().clone());
+                let backtest_engine = Arc::new(services::BacktestEngine::new(Arc::clone(&backtest_pool)));
+                
+                // Record live ticks for tick-level backtests in batched writes
+                match services::TickRecorder::start(app_service.get_tick_store(), services::TickRecorderConfig::default()) {
+                    Ok(recorder) => websocket_manager.set_tick_recorder(Some(recorder)).await,
+                    Err(e) => eprintln!("Failed to start tick recorder: {}", e),
+                }
+                
+                // Queued backtests run one at a time; each already uses every core
+                let backtest_queue = Arc::new(services::BacktestQueue::new(backtest_pool, Arc::clone(&backtest_engine), 1));
+                Arc::clone(&backtest_queue).start();
+                
+                // Optionally serve analytics and backtest results over HTTP, for dashboards outside the app
+                if let Ok(addr) = std::env::var("HEDGEX_API_ADDR") {
+                    match start_api_listener(&app_service, &websocket_manager, &backtest_engine, &addr).await {
+                        Ok(()) => println!("HTTP API served on {}", addr),
+                        Err(e) => eprintln!("Failed to start HTTP API listener: {}", e),
+                    }
+                }
+                
+                // Stream live strategy stats instead of per-strategy polling
+                start_strategy_stats_stream(app_handle_clone.clone(), strategy_service.clone());
+                
+                // Stream the user's active stock selections; selection commands keep this in step
+                match strategy_service.get_active_stock_selections("demo_user").await {
+                    Ok(selections) => {
+                        for selection in selections {
+                            let symbols = [selection.symbol];
+                            if let Err(e) = subscribe_to_symbols(&instrument_service, &websocket_manager, &symbols, &selection.exchange, services::SubscriptionMode::Full).await {
+                                eprintln!("Failed to subscribe to {}: {}", symbols[0], e);
+                            }
+                        }
+                    }
+                    Err(e) => eprintln!("Failed to load stock selections for subscription: {}", e),
+                }
+                follow_tracked_indices(&instrument_service, &websocket_manager, &strategy_manager).await;
+                
                 // Create and manage application state
                 let state = AppState {
                     app_service,
                     kite_client,
+                    demo_mode,
                     ticker_client,
                     websocket_manager,
                     strategy_service,
+                    strategy_manager,
+                    instrument_service,
+                    portfolio_sync,
+                    order_book,
+                    gtt,
+                    option_chain,
+                    tax_report,
+                    pnl_statement,
+                    slippage,
+                    excursions,
+                    notifications,
+                    eod_report,
+                    backtest_engine,
+                    backtest_queue,
+                    tick_replay: Arc::new(Mutex::new(None)),
+                    recording_sessions: Arc::new(Mutex::new(std::collections::HashMap::new())),
+                    error_recovery,
                     // Legacy fields for backward compatibility
-                    db: Arc::new(Mutex::new(
-                        db::Database::new(&app_dir).await.expect("Failed to create legacy DB reference")
-                    )),
+                    db: legacy_db,
                     logger,
                     app_path: app_dir,
                 };
@@ -1735,14 +5094,64 @@ pub fn run() {
             stop_trading,
             get_recent_trades,
             get_market_data,
+            set_stale_data_threshold,
+            clear_cache,
+            set_cache_limits,
+            get_websocket_status,
+            get_market_depth,
+            set_market_data_display,
+            subscribe_symbols,
+            unsubscribe_symbols,
+            set_subscription_mode,
+            list_subscriptions,
+            get_ticker_connections,
+            subscribe_indices,
+            get_index_quotes,
+            get_recent_candles,
+            get_live_metrics,
+            get_tick_recorder_status,
+            configure_tick_recorder,
+            start_tick_replay,
+            stop_tick_replay,
+            get_tick_replay_status,
+            start_recording_session,
+            stop_recording_session,
+            list_recording_sessions,
+            export_recording_session,
             // Strategy management commands
             get_strategies,
             create_strategy,
             update_strategy,
             enable_strategy,
+            get_strategy_validation_config,
+            set_strategy_validation_config,
             disable_strategy,
             delete_strategy,
+            restore_strategy,
+            get_archived_strategies,
+            set_strategy_trend_filter,
+            get_strategy_trend_filter,
+            remove_strategy_trend_filter,
+            set_strategy_pair_config,
+            get_strategy_pair_config,
+            remove_strategy_pair_config,
             get_nifty_50_stocks,
+            search_instruments,
+            refresh_instruments,
+            get_broker_portfolio,
+            sync_broker_portfolio,
+            get_order_book,
+            sync_order_book,
+            get_gtts,
+            sync_gtts,
+            create_gtt,
+            modify_gtt,
+            delete_gtt,
+            get_demo_mode,
+            get_basket_margins,
+            get_option_chain,
+            get_market_status,
+            get_quotes,
             get_stock_selections,
             add_stock_selection,
             remove_stock_selection,
@@ -1750,10 +5159,45 @@ pub fn run() {
             bulk_remove_stock_selections,
             get_strategy_performance,
             get_strategy_stats,
+            suggest_strategy_parameters,
+            optimize_strategy_parameters,
+            run_walk_forward_analysis,
+            get_live_drift_report,
+            run_backtest_monte_carlo,
+            get_backtest_monte_carlo,
+            get_backtest_trades,
+            update_backtest_notes,
+            delete_backtest,
+            prune_backtests,
+            rerun_backtest,
+            queue_backtest_job,
+            get_backtest_job,
+            list_backtest_jobs,
+            export_backtest_result,
+            get_data_coverage,
+            list_historical_datasets,
+            delete_historical_data,
+            redownload_historical_data,
+            preview_csv_import,
+            import_csv_data,
+            get_live_strategy_stats,
             // Analytics commands
             get_system_logs,
             get_trade_history,
             get_analytics_performance_metrics,
+            get_analytics_calendar,
+            get_analytics_time_breakdown,
+            get_analytics_correlation,
+            get_analytics_streaks,
+            get_analytics_slippage,
+            get_analytics_excursions,
+            get_analytics_pnl_split,
+            get_analytics_rolling_performance,
+            run_custom_report,
+            generate_eod_report,
+            get_eod_reports,
+            get_notification_channels,
+            save_notification_channels,
             get_analytics_strategy_performance,
             get_instrument_performance,
             get_equity_curve,
@@ -1761,16 +5205,37 @@ pub fn run() {
             log_frontend_error,
             get_performance_metrics,
             get_system_health,
+            run_db_maintenance,
+            verify_data,
             get_error_recovery_status,
             reset_circuit_breaker,
             // Data persistence commands
             create_backup,
             list_backups,
+            get_backup_schedule,
+            get_cloud_backup_settings,
+            save_cloud_backup_settings,
+            upload_backup_to_cloud,
+            get_cloud_upload_status,
+            list_cloud_backups,
+            restore_backup_from_cloud,
             restore_backup,
             export_data,
+            export_profile,
+            import_profile,
+            generate_tax_report,
+            export_pnl_statement,
             save_user_settings,
             load_user_settings,
             cleanup_old_data,
+            get_persistence_config,
+            update_persistence_config,
+            set_backup_passphrase,
+            get_archive_partitions,
+            get_migration_status,
+            migrate_up,
+            migrate_down,
+            repair_migrations,
             secure_delete_all_data,
         ])
         .run(tauri::generate_context!())
@@ -1,10 +1,13 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Utc, Weekday};
+use chrono_tz::Asia::Kolkata;
 use rust_decimal::Decimal;
-use rust_decimal::prelude::ToPrimitive;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use uuid::Uuid;
 use std::collections::HashMap;
-use crate::models::trading::{TradeType, StrategyParams};
+use crate::models::trading::{TradeType, StrategyParams, TrendFilterConfig};
+use crate::trading::session;
 
 /// Timeframe enumeration for backtesting
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
@@ -15,6 +18,8 @@ pub enum Timeframe {
     Minute30,
     Hour1,
     Day1,
+    /// Stored ticks replayed one by one
+    Tick,
 }
 
 impl std::fmt::Display for Timeframe {
@@ -26,6 +31,7 @@ impl std::fmt::Display for Timeframe {
             Timeframe::Minute30 => write!(f, "30m"),
             Timeframe::Hour1 => write!(f, "1h"),
             Timeframe::Day1 => write!(f, "1d"),
+            Timeframe::Tick => write!(f, "tick"),
         }
     }
 }
@@ -40,8 +46,14 @@ impl Timeframe {
             Timeframe::Minute30 => 30,
             Timeframe::Hour1 => 60,
             Timeframe::Day1 => 1440, // 24 * 60
+            Timeframe::Tick => 0,
         }
     }
+    
+    /// Whether candles of this size fall within a single session
+    pub fn is_intraday(&self) -> bool {
+        !matches!(self, Timeframe::Day1)
+    }
 }
 
 impl std::str::FromStr for Timeframe {
@@ -55,6 +67,7 @@ impl std::str::FromStr for Timeframe {
             "30m" => Ok(Timeframe::Minute30),
             "1h" => Ok(Timeframe::Hour1),
             "1d" => Ok(Timeframe::Day1),
+            "tick" => Ok(Timeframe::Tick),
             _ => Err(format!("Invalid Timeframe: {}", s)),
         }
     }
@@ -65,6 +78,7 @@ impl std::str::FromStr for Timeframe {
 pub enum DataSource {
     KiteAPI,
     CSVFile(String), // Path to uploaded CSV file
+    Database,        // Candles previously stored in historical_data
 }
 
 /// OHLCV data structure for historical data
@@ -119,6 +133,150 @@ impl OHLCV {
     }
 }
 
+/// Stored market tick with the quote at the time of the trade
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketTick {
+    pub timestamp: DateTime<Utc>,
+    pub ltp: Decimal,
+    pub bid: Decimal,
+    pub ask: Decimal,
+    /// Cumulative traded volume for the day
+    pub volume: i64,
+}
+
+impl MarketTick {
+    /// Price an order on the given side would fill at, falling back to the LTP without a quote
+    pub fn quote(&self, side: TradeType) -> Decimal {
+        let quote = match side {
+            TradeType::Buy => self.ask,
+            TradeType::Sell => self.bid,
+        };
+        if quote > Decimal::ZERO { quote } else { self.ltp }
+    }
+}
+
+/// Adverse price movement applied to every simulated fill
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum Slippage {
+    /// Whole ticks of the instrument's tick size
+    Ticks(u32),
+    /// Basis points of the fill price
+    BasisPoints(f64),
+}
+
+/// Charges and slippage applied to simulated orders
+///
+/// Defaults follow Zerodha's intraday equity rates on NSE.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostModel {
+    pub enabled: bool,
+    /// Brokerage as a percentage of turnover, capped per order
+    pub brokerage_percentage: f64,
+    pub brokerage_cap: f64,
+    /// Securities transaction tax on sell orders, percentage of turnover
+    pub stt_sell_percentage: f64,
+    /// Exchange transaction charges, percentage of turnover
+    pub exchange_charges_percentage: f64,
+    /// SEBI turnover fee in rupees per crore
+    pub sebi_fee_per_crore: f64,
+    /// GST on brokerage, exchange charges and SEBI fees
+    pub gst_percentage: f64,
+    /// Stamp duty on buy orders, percentage of turnover
+    pub stamp_duty_buy_percentage: f64,
+    pub slippage: Slippage,
+    pub tick_size: f64,
+}
+
+impl Default for CostModel {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            brokerage_percentage: 0.03,
+            brokerage_cap: 20.0,
+            stt_sell_percentage: 0.025,
+            exchange_charges_percentage: 0.00297,
+            sebi_fee_per_crore: 10.0,
+            gst_percentage: 18.0,
+            stamp_duty_buy_percentage: 0.003,
+            slippage: Slippage::Ticks(1),
+            tick_size: 0.05,
+        }
+    }
+}
+
+impl CostModel {
+    /// Cost model that charges nothing and fills at the quoted price
+    pub fn disabled() -> Self {
+        Self { enabled: false, ..Self::default() }
+    }
+    
+    /// Price an order on the given side actually fills at after slippage
+    pub fn fill_price(&self, side: TradeType, price: Decimal) -> Decimal {
+        if !self.enabled {
+            return price;
+        }
+        
+        let shift = match self.slippage {
+            Slippage::Ticks(ticks) => Decimal::from_f64(self.tick_size * ticks as f64).unwrap_or_default(),
+            Slippage::BasisPoints(bps) => price * Decimal::from_f64(bps / 10_000.0).unwrap_or_default(),
+        };
+        
+        match side {
+            TradeType::Buy => price + shift,
+            TradeType::Sell => (price - shift).max(Decimal::ZERO),
+        }
+    }
+    
+    /// Brokerage, taxes and fees for one order, rounded to the paisa
+    pub fn charges(&self, side: TradeType, price: Decimal, quantity: i32) -> Decimal {
+        self.breakdown(side, price, quantity).total
+    }
+    
+    /// Each charge on one order, rounded to the paisa
+    ///
+    /// `total` is rounded from the unrounded charges, so it can differ from the sum of the parts by a paisa.
+    pub fn breakdown(&self, side: TradeType, price: Decimal, quantity: i32) -> ChargesBreakdown {
+        if !self.enabled {
+            return ChargesBreakdown::default();
+        }
+        
+        let turnover = (price * Decimal::from(quantity)).to_f64().unwrap_or(0.0);
+        let brokerage = (turnover * self.brokerage_percentage / 100.0).min(self.brokerage_cap);
+        let exchange_charges = turnover * self.exchange_charges_percentage / 100.0;
+        let sebi_fee = turnover * self.sebi_fee_per_crore / 10_000_000.0;
+        let gst = (brokerage + exchange_charges + sebi_fee) * self.gst_percentage / 100.0;
+        let (stt, stamp_duty) = match side {
+            TradeType::Buy => (0.0, turnover * self.stamp_duty_buy_percentage / 100.0),
+            TradeType::Sell => (turnover * self.stt_sell_percentage / 100.0, 0.0),
+        };
+        
+        let paisa = |amount: f64| Decimal::from_f64(amount).unwrap_or_default().round_dp(2);
+        ChargesBreakdown {
+            brokerage: paisa(brokerage),
+            stt: paisa(stt),
+            exchange_charges: paisa(exchange_charges),
+            sebi_fee: paisa(sebi_fee),
+            gst: paisa(gst),
+            stamp_duty: paisa(stamp_duty),
+            total: paisa(brokerage + exchange_charges + sebi_fee + gst + stt + stamp_duty),
+        }
+    }
+}
+
+/// Charges on one order as estimated by a `CostModel`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct ChargesBreakdown {
+    pub brokerage: Decimal,
+    /// Securities transaction tax, charged on sells
+    pub stt: Decimal,
+    pub exchange_charges: Decimal,
+    pub sebi_fee: Decimal,
+    pub gst: Decimal,
+    /// Charged on buys
+    pub stamp_duty: Decimal,
+    pub total: Decimal,
+}
+
 /// Backtest parameters
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestParams {
@@ -132,6 +290,8 @@ pub struct BacktestParams {
     pub timeframe: Timeframe,
     pub initial_capital: Decimal,
     pub data_source: DataSource,
+    #[serde(default)]
+    pub cost_model: CostModel,
     pub created_at: DateTime<Utc>,
 }
 
@@ -159,11 +319,88 @@ impl BacktestParams {
             timeframe,
             initial_capital,
             data_source,
+            cost_model: CostModel::default(),
             created_at: Utc::now(),
         }
     }
 }
 
+/// Everything that determines a backtest's outcome, stored with the run so it can be re-run
+///
+/// Candle and tick simulations draw no random numbers, so the same
+/// configuration over data with the same checksum gives the same trades.
+/// Monte Carlo runs keep their seed in their own stored configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestConfig {
+    /// Strategy as it was when the run started; `updated_at` identifies the version
+    pub strategy: StrategyParams,
+    /// Index trend filter that was enabled for the strategy, if any
+    pub trend_filter: Option<TrendFilterConfig>,
+    pub symbol: String,
+    pub exchange: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub timeframe: Timeframe,
+    pub initial_capital: Decimal,
+    pub data_source: DataSource,
+    pub cost_model: CostModel,
+    /// SHA-256 of the candles or ticks the simulation consumed
+    pub data_checksum: String,
+}
+
+impl BacktestConfig {
+    /// Capture the configuration of a run
+    pub fn new(
+        strategy: &StrategyParams,
+        trend_filter: Option<TrendFilterConfig>,
+        params: &BacktestParams,
+        data_checksum: String,
+    ) -> Self {
+        Self {
+            strategy: strategy.clone(),
+            trend_filter,
+            symbol: params.symbol.clone(),
+            exchange: params.exchange.clone(),
+            start_date: params.start_date,
+            end_date: params.end_date,
+            timeframe: params.timeframe,
+            initial_capital: params.initial_capital,
+            data_source: params.data_source.clone(),
+            cost_model: params.cost_model.clone(),
+            data_checksum,
+        }
+    }
+    
+    /// SHA-256 of the configuration, equal for runs that must produce equal results
+    pub fn hash(&self) -> String {
+        // Struct fields serialize in declaration order, so the JSON is stable
+        let json = serde_json::to_string(self).unwrap_or_default();
+        hex_digest(Sha256::digest(json.as_bytes()).as_slice())
+    }
+    
+    /// Parameters for a new run of this configuration
+    pub fn params(&self, user_id: &str) -> BacktestParams {
+        let mut params = BacktestParams::new(
+            user_id,
+            &self.strategy.id,
+            &self.symbol,
+            &self.exchange,
+            self.start_date,
+            self.end_date,
+            self.timeframe,
+            self.initial_capital,
+            self.data_source.clone(),
+        );
+        params.cost_model = self.cost_model.clone();
+        params
+    }
+}
+
+/// Lowercase hex encoding of a digest
+pub fn hex_digest(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
 /// Backtest trade result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BacktestTrade {
@@ -176,8 +413,12 @@ pub struct BacktestTrade {
     pub quantity: i32,
     pub exit_time: Option<DateTime<Utc>>,
     pub exit_price: Option<Decimal>,
+    /// Net of `charges` once the trade is closed
     pub pnl: Option<Decimal>,
     pub exit_reason: Option<String>,
+    /// Brokerage, taxes and fees for both legs of the trade
+    #[serde(default)]
+    pub charges: Decimal,
 }
 
 impl BacktestTrade {
@@ -202,6 +443,7 @@ impl BacktestTrade {
             exit_price: None,
             pnl: None,
             exit_reason: None,
+            charges: Decimal::ZERO,
         }
     }
     
@@ -217,7 +459,15 @@ impl BacktestTrade {
                 TradeType::Buy => exit_price - self.entry_price,
                 TradeType::Sell => self.entry_price - exit_price,
             };
-            self.pnl = Some(price_diff * Decimal::from(self.quantity));
+            self.pnl = Some(price_diff * Decimal::from(self.quantity) - self.charges);
+        }
+    }
+    
+    /// Add charges to the trade, reducing its P&L if already closed
+    pub fn add_charges(&mut self, charges: Decimal) {
+        self.charges += charges;
+        if let Some(pnl) = self.pnl.as_mut() {
+            *pnl -= charges;
         }
     }
     
@@ -234,6 +484,84 @@ impl BacktestTrade {
             None
         }
     }
+    
+    /// Minutes of market session the trade was held, excluding nights, weekends and holidays
+    pub fn holding_minutes(&self) -> Option<i64> {
+        self.exit_time.map(|exit_time| session::session_minutes_between(self.entry_time, exit_time))
+    }
+}
+
+/// Closed backtest trade with its price excursions and the candles around it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeDrillDown {
+    pub trade: BacktestTrade,
+    /// Session minutes the trade was held
+    pub holding_minutes: Option<i64>,
+    /// Worst and best open P&L of the position while held, in rupees
+    pub max_adverse_excursion: Decimal,
+    pub max_favorable_excursion: Decimal,
+    /// Excursions as a percentage of the entry price
+    pub mae_percent: f64,
+    pub mfe_percent: f64,
+    /// Candles from before the entry to after the exit
+    pub candles: Vec<OHLCV>,
+}
+
+impl TradeDrillDown {
+    /// Measure a trade over the candles that closed while it was open
+    ///
+    /// The entry candle's range is left out since the trade fills at its close.
+    pub fn new(trade: BacktestTrade, candles: Vec<OHLCV>) -> Self {
+        let exit_time = trade.exit_time.unwrap_or(trade.entry_time);
+        let exit_price = trade.exit_price.unwrap_or(trade.entry_price);
+        
+        let held = candles.iter()
+            .filter(|candle| candle.timestamp > trade.entry_time && candle.timestamp <= exit_time);
+        let (mut high, mut low) = (exit_price, exit_price);
+        for candle in held {
+            high = high.max(candle.high);
+            low = low.min(candle.low);
+        }
+        
+        // Per-share moves against and in favour of the position
+        let (adverse, favorable) = match trade.trade_type {
+            TradeType::Buy => (trade.entry_price - low, high - trade.entry_price),
+            TradeType::Sell => (high - trade.entry_price, trade.entry_price - low),
+        };
+        let adverse = adverse.max(Decimal::ZERO);
+        let favorable = favorable.max(Decimal::ZERO);
+        
+        let percent = |amount: Decimal| {
+            if trade.entry_price > Decimal::ZERO {
+                (amount / trade.entry_price * Decimal::from(100)).to_f64().unwrap_or(0.0)
+            } else {
+                0.0
+            }
+        };
+        let quantity = Decimal::from(trade.quantity);
+        
+        Self {
+            holding_minutes: trade.holding_minutes(),
+            max_adverse_excursion: adverse * quantity,
+            max_favorable_excursion: favorable * quantity,
+            mae_percent: percent(adverse),
+            mfe_percent: percent(favorable),
+            candles,
+            trade,
+        }
+    }
+}
+
+/// One page of a backtest's closed trades, oldest entry first
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestTradePage {
+    pub backtest_id: String,
+    /// One-based page number
+    pub page: usize,
+    pub page_size: usize,
+    pub total_trades: usize,
+    pub total_pages: usize,
+    pub trades: Vec<TradeDrillDown>,
 }
 
 /// Equity curve point
@@ -263,6 +591,43 @@ pub struct BacktestResult {
     pub sharpe_ratio: f64,
     pub win_rate: f64,
     pub profit_factor: f64,
+    /// Total brokerage, taxes and fees across all trades
+    #[serde(default)]
+    pub total_charges: Decimal,
+    /// Comparison with buying and holding the index, when its data was available
+    #[serde(default)]
+    pub benchmark: Option<BenchmarkComparison>,
+    /// Exchange trading days between the start and end dates
+    #[serde(default)]
+    pub trading_days: usize,
+    /// Average session minutes closed trades were held
+    #[serde(default)]
+    pub average_holding_minutes: f64,
+    /// Compound annual growth rate over the trading days, in percent
+    #[serde(default)]
+    pub cagr: f64,
+    /// CAGR over the maximum drawdown as a percentage of the peak
+    #[serde(default)]
+    pub calmar_ratio: f64,
+    /// Annualized return over downside deviation
+    #[serde(default)]
+    pub sortino_ratio: f64,
+    /// Share of session time a position was open, in percent
+    #[serde(default)]
+    pub exposure_percent: f64,
+    /// Longest stretch of session minutes without a new equity high
+    #[serde(default)]
+    pub longest_flat_minutes: i64,
+    #[serde(default)]
+    pub max_consecutive_losses: i32,
+    /// Short name and free-form notes attached by the user
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
+    /// Configuration needed to re-run the backtest; absent for older runs
+    #[serde(default)]
+    pub config: Option<BacktestConfig>,
     pub trades: Vec<BacktestTrade>,
     pub equity_curve: Vec<EquityPoint>,
     pub created_at: DateTime<Utc>,
@@ -282,6 +647,19 @@ impl BacktestResult {
             sharpe_ratio: 0.0,
             win_rate: 0.0,
             profit_factor: 0.0,
+            total_charges: Decimal::ZERO,
+            benchmark: None,
+            trading_days: 0,
+            average_holding_minutes: 0.0,
+            cagr: 0.0,
+            calmar_ratio: 0.0,
+            sortino_ratio: 0.0,
+            exposure_percent: 0.0,
+            longest_flat_minutes: 0,
+            max_consecutive_losses: 0,
+            label: None,
+            notes: None,
+            config: None,
             trades: Vec::new(),
             equity_curve: Vec::new(),
             created_at: Utc::now(),
@@ -290,11 +668,14 @@ impl BacktestResult {
     
     /// Calculate performance metrics from trades
     pub fn calculate_metrics(&mut self) {
+        self.calculate_duration_metrics();
+        
         if self.trades.is_empty() {
             return;
         }
         
         self.total_trades = self.trades.len() as i32;
+        self.total_charges = self.trades.iter().map(|trade| trade.charges).sum();
         
         let mut total_profit = Decimal::ZERO;
         let mut total_loss = Decimal::ZERO;
@@ -333,6 +714,20 @@ impl BacktestResult {
         
         // Calculate Sharpe ratio
         self.calculate_sharpe_ratio();
+        
+        self.calculate_equity_statistics();
+    }
+    
+    /// Calculate trading days and holding time, counting only exchange sessions
+    pub fn calculate_duration_metrics(&mut self) {
+        self.trading_days = session::trading_days_between(self.params.start_date, self.params.end_date);
+        
+        let holding: Vec<i64> = self.trades.iter().filter_map(BacktestTrade::holding_minutes).collect();
+        self.average_holding_minutes = if holding.is_empty() {
+            0.0
+        } else {
+            holding.iter().sum::<i64>() as f64 / holding.len() as f64
+        };
     }
     
     /// Calculate maximum drawdown from equity curve
@@ -392,6 +787,748 @@ impl BacktestResult {
             self.sharpe_ratio = mean_return / std_dev * (252.0_f64).sqrt(); // Annualized
         }
     }
+    
+    /// Calculate CAGR, Calmar, Sortino, exposure, flat periods and losing streaks
+    fn calculate_equity_statistics(&mut self) {
+        let initial = self.params.initial_capital.to_f64().unwrap_or(0.0);
+        let last = (self.params.initial_capital + self.final_pnl).to_f64().unwrap_or(0.0);
+        let years = self.trading_days as f64 / 252.0;
+        if initial > 0.0 && years > 0.0 {
+            self.cagr = if last > 0.0 { ((last / initial).powf(1.0 / years) - 1.0) * 100.0 } else { -100.0 };
+        }
+        
+        let equity: Vec<f64> = self.equity_curve.iter().map(|point| point.equity.to_f64().unwrap_or(0.0)).collect();
+        let drawdown = max_drawdown_percentage(&equity);
+        if drawdown > 0.0 {
+            self.calmar_ratio = self.cagr / drawdown;
+        }
+        
+        // Only returns below zero count towards the deviation
+        let returns = period_returns(&equity);
+        if !returns.is_empty() {
+            let mean_return = returns.iter().sum::<f64>() / returns.len() as f64;
+            let downside = (returns.iter().map(|r| r.min(0.0).powi(2)).sum::<f64>() / returns.len() as f64).sqrt();
+            if downside > 0.0 {
+                self.sortino_ratio = mean_return / downside * (252.0_f64).sqrt();
+            }
+        }
+        
+        let session_minutes = session::session_minutes_between(self.params.start_date, self.params.end_date);
+        if session_minutes > 0 {
+            let held: i64 = self.trades.iter().filter_map(BacktestTrade::holding_minutes).sum();
+            self.exposure_percent = (held as f64 / session_minutes as f64 * 100.0).min(100.0);
+        }
+        
+        // A flat period runs from one equity high to the next, or to the end of the curve
+        let mut longest = 0;
+        if let Some(first) = self.equity_curve.first() {
+            let (mut peak, mut peak_time) = (first.equity, first.timestamp);
+            for point in &self.equity_curve {
+                longest = longest.max(session::session_minutes_between(peak_time, point.timestamp));
+                if point.equity > peak {
+                    peak = point.equity;
+                    peak_time = point.timestamp;
+                }
+            }
+        }
+        self.longest_flat_minutes = longest;
+        
+        let mut streak = 0;
+        let mut max_streak = 0;
+        for pnl in self.trades.iter().filter_map(|trade| trade.pnl) {
+            if pnl < Decimal::ZERO {
+                streak += 1;
+                max_streak = max_streak.max(streak);
+            } else {
+                streak = 0;
+            }
+        }
+        self.max_consecutive_losses = max_streak;
+    }
+}
+
+/// Strategy performance against buying and holding a benchmark index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BenchmarkComparison {
+    pub symbol: String,
+    /// Buy-and-hold equity scaled to the strategy's starting equity
+    pub equity_curve: Vec<EquityPoint>,
+    /// Returns over the whole period, in percent
+    pub strategy_return: f64,
+    pub benchmark_return: f64,
+    /// Strategy return not explained by its exposure to the benchmark, in percent
+    pub alpha: f64,
+    pub beta: f64,
+    /// Maximum drawdowns as a percentage of the running peak
+    pub strategy_max_drawdown: f64,
+    pub benchmark_max_drawdown: f64,
+    /// Strategy drawdown minus benchmark drawdown; negative is shallower
+    pub relative_drawdown: f64,
+}
+
+impl BenchmarkComparison {
+    /// Compare an equity curve with the benchmark's closes at the same points in time
+    ///
+    /// Each point uses the latest benchmark close at or before it.
+    pub fn calculate(symbol: &str, strategy_curve: &[EquityPoint], benchmark: &[OHLCV]) -> Option<Self> {
+        if strategy_curve.len() < 2 || benchmark.is_empty() {
+            return None;
+        }
+        
+        let mut index = 0;
+        let prices: Vec<f64> = strategy_curve.iter()
+            .map(|point| {
+                while index + 1 < benchmark.len() && benchmark[index + 1].timestamp <= point.timestamp {
+                    index += 1;
+                }
+                benchmark[index].close.to_f64().unwrap_or(0.0)
+            })
+            .collect();
+            
+        let first_price = prices[0];
+        if first_price <= 0.0 {
+            return None;
+        }
+        
+        let initial_equity = strategy_curve[0].equity;
+        let equity_curve = strategy_curve.iter()
+            .zip(&prices)
+            .map(|(point, price)| {
+                let growth = Decimal::from_f64(price / first_price).unwrap_or(Decimal::ONE);
+                EquityPoint::new(point.timestamp, (initial_equity * growth).round_dp(2))
+            })
+            .collect();
+            
+        let equity: Vec<f64> = strategy_curve.iter().map(|point| point.equity.to_f64().unwrap_or(0.0)).collect();
+        let beta = beta(&period_returns(&equity), &period_returns(&prices));
+        let strategy_return = total_return(&equity);
+        let benchmark_return = total_return(&prices);
+        let strategy_max_drawdown = max_drawdown_percentage(&equity);
+        let benchmark_max_drawdown = max_drawdown_percentage(&prices);
+        
+        Some(Self {
+            symbol: symbol.to_string(),
+            equity_curve,
+            strategy_return,
+            benchmark_return,
+            alpha: strategy_return - beta * benchmark_return,
+            beta,
+            strategy_max_drawdown,
+            benchmark_max_drawdown,
+            relative_drawdown: strategy_max_drawdown - benchmark_max_drawdown,
+        })
+    }
+}
+
+/// Fractional change between consecutive values
+fn period_returns(values: &[f64]) -> Vec<f64> {
+    values.windows(2)
+        .map(|w| if w[0] > 0.0 { w[1] / w[0] - 1.0 } else { 0.0 })
+        .collect()
+}
+
+/// Percentage change from the first to the last value
+fn total_return(values: &[f64]) -> f64 {
+    match (values.first(), values.last()) {
+        (Some(first), Some(last)) if *first > 0.0 => (last / first - 1.0) * 100.0,
+        _ => 0.0,
+    }
+}
+
+/// Covariance of the returns with the benchmark over the benchmark's variance
+fn beta(returns: &[f64], benchmark_returns: &[f64]) -> f64 {
+    let n = returns.len().min(benchmark_returns.len());
+    if n < 2 {
+        return 0.0;
+    }
+    
+    let mean = returns[..n].iter().sum::<f64>() / n as f64;
+    let benchmark_mean = benchmark_returns[..n].iter().sum::<f64>() / n as f64;
+    let covariance = (0..n).map(|i| (returns[i] - mean) * (benchmark_returns[i] - benchmark_mean)).sum::<f64>();
+    let variance = benchmark_returns[..n].iter().map(|r| (r - benchmark_mean).powi(2)).sum::<f64>();
+    
+    if variance > 0.0 { covariance / variance } else { 0.0 }
+}
+
+/// Largest fall from a running peak, in percent
+fn max_drawdown_percentage(values: &[f64]) -> f64 {
+    let mut peak = f64::MIN;
+    let mut drawdown: f64 = 0.0;
+    
+    for value in values {
+        peak = peak.max(*value);
+        if peak > 0.0 {
+            drawdown = drawdown.max((peak - value) / peak * 100.0);
+        }
+    }
+    drawdown
+}
+
+/// Backtested metrics for one stop-loss/take-profit/volume combination
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterSuggestion {
+    pub stop_loss_percentage: f64,
+    pub take_profit_percentage: f64,
+    pub volume_threshold: i64,
+    pub total_trades: i32,
+    pub win_rate: f64,
+    pub final_pnl: Decimal,
+    pub max_drawdown: Decimal,
+    pub sharpe_ratio: f64,
+    pub profit_factor: f64,
+}
+
+impl ParameterSuggestion {
+    /// Build a suggestion from the parameters and result of a backtest
+    pub fn from_result(strategy: &StrategyParams, result: &BacktestResult) -> Self {
+        Self {
+            stop_loss_percentage: strategy.stop_loss_percentage,
+            take_profit_percentage: strategy.take_profit_percentage,
+            volume_threshold: strategy.volume_threshold,
+            total_trades: result.total_trades,
+            win_rate: result.win_rate,
+            final_pnl: result.final_pnl,
+            max_drawdown: result.max_drawdown,
+            sharpe_ratio: result.sharpe_ratio,
+            profit_factor: result.profit_factor,
+        }
+    }
+}
+
+/// Outcome of a parameter sweep for a strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ParameterSweepReport {
+    pub strategy_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub candles: usize,
+    pub combinations_tested: usize,
+    pub current: ParameterSuggestion,
+    pub suggestions: Vec<ParameterSuggestion>,
+}
+
+/// Inclusive range of parameter values swept by the optimizer
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ParameterRange {
+    pub min: f64,
+    pub max: f64,
+    pub step: f64,
+}
+
+impl ParameterRange {
+    /// Create a new range
+    pub fn new(min: f64, max: f64, step: f64) -> Self {
+        Self { min, max, step }
+    }
+    
+    /// Whether the range is finite, positive and non-empty
+    pub fn is_valid(&self) -> bool {
+        self.min.is_finite() && self.max.is_finite() && self.step.is_finite()
+            && self.min > 0.0 && self.max >= self.min && self.step > 0.0
+    }
+    
+    /// Values from min to max in step increments, rounded to avoid float drift
+    pub fn values(&self) -> Vec<f64> {
+        if !self.is_valid() {
+            return Vec::new();
+        }
+        
+        let count = ((self.max - self.min) / self.step + 1e-9).floor() as usize + 1;
+        (0..count)
+            .map(|i| ((self.min + i as f64 * self.step) * 10_000.0).round() / 10_000.0)
+            .collect()
+    }
+}
+
+/// Metric used to rank optimization results
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum OptimizationMetric {
+    #[default]
+    SharpeRatio,
+    FinalPnl,
+    WinRate,
+    ProfitFactor,
+}
+
+impl OptimizationMetric {
+    /// Score of a backtested combination under this metric, higher is better
+    pub fn score(&self, suggestion: &ParameterSuggestion) -> f64 {
+        match self {
+            OptimizationMetric::SharpeRatio => suggestion.sharpe_ratio,
+            OptimizationMetric::FinalPnl => suggestion.final_pnl.to_f64().unwrap_or(0.0),
+            OptimizationMetric::WinRate => suggestion.win_rate,
+            OptimizationMetric::ProfitFactor => suggestion.profit_factor,
+        }
+    }
+}
+
+fn default_max_concurrency() -> usize {
+    4
+}
+
+/// Grid of stop-loss, take-profit and volume threshold values to backtest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationGrid {
+    pub stop_loss: ParameterRange,
+    pub take_profit: ParameterRange,
+    /// Volume thresholds to try; the strategy's current threshold when empty
+    #[serde(default)]
+    pub volume_thresholds: Vec<i64>,
+    #[serde(default)]
+    pub rank_by: OptimizationMetric,
+    /// Combinations with fewer trades are left out of the ranking
+    #[serde(default)]
+    pub min_trades: i32,
+    /// Number of backtests run at once
+    #[serde(default = "default_max_concurrency")]
+    pub max_concurrency: usize,
+}
+
+/// Grid search over historical data for a strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationRequest {
+    pub strategy_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    #[serde(flatten)]
+    pub grid: OptimizationGrid,
+}
+
+/// Best score per stop-loss/take-profit cell across volume thresholds
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationHeatmap {
+    pub metric: OptimizationMetric,
+    pub stop_loss_values: Vec<f64>,
+    pub take_profit_values: Vec<f64>,
+    /// Scores indexed by take profit, then stop loss; None where untested
+    pub scores: Vec<Vec<Option<f64>>>,
+}
+
+impl OptimizationHeatmap {
+    /// Build the heatmap from every tested combination
+    pub fn from_results(
+        metric: OptimizationMetric,
+        stop_loss_values: Vec<f64>,
+        take_profit_values: Vec<f64>,
+        results: &[ParameterSuggestion],
+    ) -> Self {
+        let mut scores = vec![vec![None; stop_loss_values.len()]; take_profit_values.len()];
+        let position = |values: &[f64], value: f64| values.iter().position(|v| (v - value).abs() < 1e-9);
+        
+        for result in results {
+            let (x, y) = match (
+                position(&stop_loss_values, result.stop_loss_percentage),
+                position(&take_profit_values, result.take_profit_percentage),
+            ) {
+                (Some(x), Some(y)) => (x, y),
+                _ => continue,
+            };
+            
+            let score = metric.score(result);
+            let cell = &mut scores[y][x];
+            if cell.map_or(true, |best| score > best) {
+                *cell = Some(score);
+            }
+        }
+        
+        Self { metric, stop_loss_values, take_profit_values, scores }
+    }
+}
+
+/// Ranked outcome of a grid search
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptimizationReport {
+    pub strategy_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub candles: usize,
+    pub combinations_tested: usize,
+    pub rank_by: OptimizationMetric,
+    /// Combinations with enough trades, best first
+    pub results: Vec<ParameterSuggestion>,
+    pub heatmap: OptimizationHeatmap,
+}
+
+/// Walk-forward analysis: optimize in-sample, test on the following window, roll forward
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardRequest {
+    pub strategy_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    /// Candles in each optimization window
+    pub in_sample_candles: usize,
+    /// Candles in each test window; windows roll forward by this amount
+    pub out_of_sample_candles: usize,
+    #[serde(flatten)]
+    pub grid: OptimizationGrid,
+}
+
+/// One optimize-then-test step of a walk-forward analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardWindow {
+    pub in_sample_start: DateTime<Utc>,
+    pub in_sample_end: DateTime<Utc>,
+    pub out_of_sample_start: DateTime<Utc>,
+    pub out_of_sample_end: DateTime<Utc>,
+    /// Best combination and its metrics on the in-sample window
+    pub in_sample: ParameterSuggestion,
+    /// The same combination's metrics on the out-of-sample window
+    pub out_of_sample: ParameterSuggestion,
+}
+
+/// Aggregate out-of-sample results of a walk-forward analysis
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WalkForwardReport {
+    pub strategy_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    pub candles: usize,
+    pub rank_by: OptimizationMetric,
+    pub windows: Vec<WalkForwardWindow>,
+    /// Windows where no combination had enough in-sample trades
+    pub skipped_windows: usize,
+    pub total_trades: i32,
+    pub win_rate: f64,
+    pub final_pnl: Decimal,
+    pub max_drawdown: Decimal,
+    pub profitable_windows: usize,
+    pub average_in_sample_score: f64,
+    pub average_out_of_sample_score: f64,
+    /// Out-of-sample score as a fraction of in-sample; well below 1 suggests overfitting
+    pub efficiency: Option<f64>,
+}
+
+impl WalkForwardReport {
+    /// Aggregate the out-of-sample metrics of every window
+    pub fn calculate_metrics(&mut self) {
+        self.total_trades = self.windows.iter().map(|w| w.out_of_sample.total_trades).sum();
+        self.final_pnl = self.windows.iter().map(|w| w.out_of_sample.final_pnl).sum();
+        self.max_drawdown = self.windows.iter().map(|w| w.out_of_sample.max_drawdown).max().unwrap_or_default();
+        self.profitable_windows = self.windows.iter().filter(|w| w.out_of_sample.final_pnl > Decimal::ZERO).count();
+        
+        // Window win rates weighted by their trade counts
+        if self.total_trades > 0 {
+            self.win_rate = self.windows.iter()
+                .map(|w| w.out_of_sample.win_rate * w.out_of_sample.total_trades as f64)
+                .sum::<f64>() / self.total_trades as f64;
+        }
+        
+        if self.windows.is_empty() {
+            return;
+        }
+        
+        let count = self.windows.len() as f64;
+        self.average_in_sample_score = self.windows.iter().map(|w| self.rank_by.score(&w.in_sample)).sum::<f64>() / count;
+        self.average_out_of_sample_score = self.windows.iter().map(|w| self.rank_by.score(&w.out_of_sample)).sum::<f64>() / count;
+        
+        if self.average_in_sample_score > 0.0 {
+            self.efficiency = Some(self.average_out_of_sample_score / self.average_in_sample_score);
+        }
+    }
+}
+
+/// Backtest submitted to the job queue
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestRunRequest {
+    pub strategy_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub initial_capital: Decimal,
+    pub data_source: DataSource,
+    #[serde(default)]
+    pub cost_model: CostModel,
+}
+
+impl BacktestRunRequest {
+    /// Parameters for running the request as a user
+    pub fn params(&self, user_id: &str) -> BacktestParams {
+        let mut params = BacktestParams::new(
+            user_id,
+            &self.strategy_id,
+            &self.symbol,
+            &self.exchange,
+            self.start_date,
+            self.end_date,
+            self.timeframe,
+            self.initial_capital,
+            self.data_source.clone(),
+        );
+        params.cost_model = self.cost_model.clone();
+        params
+    }
+}
+
+/// Work a queued job performs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "request", rename_all = "snake_case")]
+pub enum BacktestJobRequest {
+    Backtest(BacktestRunRequest),
+    Optimization(OptimizationRequest),
+    WalkForward(WalkForwardRequest),
+}
+
+impl BacktestJobRequest {
+    /// Short name stored alongside the request
+    pub fn kind(&self) -> &'static str {
+        match self {
+            BacktestJobRequest::Backtest(_) => "backtest",
+            BacktestJobRequest::Optimization(_) => "optimization",
+            BacktestJobRequest::WalkForward(_) => "walk_forward",
+        }
+    }
+}
+
+/// Lifecycle of a queued job
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Done,
+    Failed,
+}
+
+impl std::fmt::Display for JobStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            JobStatus::Queued => write!(f, "queued"),
+            JobStatus::Running => write!(f, "running"),
+            JobStatus::Done => write!(f, "done"),
+            JobStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+impl std::str::FromStr for JobStatus {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "queued" => Ok(JobStatus::Queued),
+            "running" => Ok(JobStatus::Running),
+            "done" => Ok(JobStatus::Done),
+            "failed" => Ok(JobStatus::Failed),
+            _ => Err(format!("Invalid JobStatus: {}", s)),
+        }
+    }
+}
+
+/// Backtest or optimization queued to run in the background
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestJob {
+    pub id: String,
+    pub user_id: String,
+    #[serde(flatten)]
+    pub request: BacktestJobRequest,
+    pub status: JobStatus,
+    /// Stored run produced by a backtest job
+    pub backtest_id: Option<String>,
+    /// Report produced by an optimization or walk-forward job
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+/// Settings for a Monte Carlo robustness simulation of a backtest
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloConfig {
+    pub iterations: usize,
+    /// Draw trades with replacement instead of only reordering them
+    pub resample: bool,
+    /// Maximum adverse fill shift per entry and exit, in basis points
+    pub slippage_bps: f64,
+    /// Confidence level of the reported intervals, e.g. 0.95
+    pub confidence: f64,
+    /// Seed for reproducible runs
+    #[serde(default)]
+    pub seed: Option<u64>,
+}
+
+impl Default for MonteCarloConfig {
+    fn default() -> Self {
+        Self {
+            iterations: 1000,
+            resample: true,
+            slippage_bps: 5.0,
+            confidence: 0.95,
+            seed: None,
+        }
+    }
+}
+
+/// Bucket of a simulated distribution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistogramBin {
+    pub lower: f64,
+    pub upper: f64,
+    pub count: usize,
+}
+
+/// Summary statistics of a simulated distribution
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DistributionSummary {
+    pub mean: f64,
+    pub std_dev: f64,
+    pub min: f64,
+    pub max: f64,
+    pub median: f64,
+    /// Bounds of the central confidence interval
+    pub lower: f64,
+    pub upper: f64,
+    pub histogram: Vec<HistogramBin>,
+}
+
+/// Distributions of final P&L and max drawdown from a Monte Carlo simulation
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MonteCarloResult {
+    pub backtest_id: String,
+    pub config: MonteCarloConfig,
+    pub trades: usize,
+    pub final_pnl: DistributionSummary,
+    pub max_drawdown: DistributionSummary,
+    /// Share of simulations ending below the initial capital
+    pub probability_of_loss: f64,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What happens when a strategy fails its pre-enable validation
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum ValidationMode {
+    /// Enable anyway and report the issues
+    #[default]
+    Warn,
+    /// Refuse to enable the strategy
+    Block,
+}
+
+/// Settings for the validation backtest run when a strategy is enabled
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EnableValidationConfig {
+    pub enabled: bool,
+    pub mode: ValidationMode,
+    /// Number of most recent trading sessions replayed
+    pub sessions: usize,
+    pub timeframe: Timeframe,
+    /// Loss or drawdown, as a percentage of capital, treated as catastrophic
+    pub max_loss_percentage: f64,
+}
+
+impl Default for EnableValidationConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            mode: ValidationMode::Warn,
+            sessions: 5,
+            timeframe: Timeframe::Minute5,
+            max_loss_percentage: 5.0,
+        }
+    }
+}
+
+/// Outcome of the validation backtest run before enabling a strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyValidationSummary {
+    pub strategy_id: String,
+    pub mode: ValidationMode,
+    pub sessions: usize,
+    pub timeframe: Timeframe,
+    pub symbols_tested: Vec<String>,
+    pub candles: usize,
+    pub total_trades: i32,
+    pub capital: Decimal,
+    pub final_pnl: Decimal,
+    pub max_drawdown: Decimal,
+    pub issues: Vec<String>,
+    pub passed: bool,
+    pub blocked: bool,
+}
+
+impl StrategyValidationSummary {
+    /// Create an empty summary for a strategy
+    pub fn new(strategy_id: &str, config: &EnableValidationConfig) -> Self {
+        Self {
+            strategy_id: strategy_id.to_string(),
+            mode: config.mode,
+            sessions: config.sessions,
+            timeframe: config.timeframe,
+            symbols_tested: Vec::new(),
+            candles: 0,
+            total_trades: 0,
+            capital: Decimal::ZERO,
+            final_pnl: Decimal::ZERO,
+            max_drawdown: Decimal::ZERO,
+            issues: Vec::new(),
+            passed: true,
+            blocked: false,
+        }
+    }
+    
+    /// Fold a per-symbol backtest into the summary
+    pub fn add_result(&mut self, result: &BacktestResult, candles: usize) {
+        self.symbols_tested.push(result.params.symbol.clone());
+        self.candles += candles;
+        self.total_trades += result.total_trades;
+        self.capital += result.params.initial_capital;
+        self.final_pnl += result.final_pnl;
+        self.max_drawdown = self.max_drawdown.max(result.max_drawdown);
+    }
+    
+    /// Flag zero-trade and catastrophic-loss runs and decide whether to block
+    ///
+    /// A run without any stored data is reported but never blocks.
+    pub fn evaluate(&mut self, config: &EnableValidationConfig) {
+        if self.symbols_tested.is_empty() {
+            self.issues.push(format!(
+                "No stored {} data for the last {} sessions; validation skipped",
+                config.timeframe, config.sessions
+            ));
+            self.passed = false;
+            return;
+        }
+        
+        if self.total_trades == 0 {
+            self.issues.push(format!("No trades over the last {} sessions", config.sessions));
+        }
+        
+        let percent_of_capital = |amount: Decimal| {
+            if self.capital > Decimal::ZERO {
+                (amount / self.capital * Decimal::from(100)).to_f64().unwrap_or(0.0)
+            } else {
+                0.0
+            }
+        };
+        
+        let loss = percent_of_capital(-self.final_pnl);
+        if loss >= config.max_loss_percentage {
+            self.issues.push(format!("Lost {:.2}% of capital over the last {} sessions", loss, config.sessions));
+        }
+        
+        let drawdown = percent_of_capital(self.max_drawdown);
+        if drawdown >= config.max_loss_percentage {
+            self.issues.push(format!("Drawdown reached {:.2}% of capital", drawdown));
+        }
+        
+        self.passed = self.issues.is_empty();
+        self.blocked = !self.passed && config.mode == ValidationMode::Block;
+    }
 }
 
 /// Backtest summary for listing
@@ -406,6 +1543,10 @@ pub struct BacktestSummary {
     pub total_trades: i32,
     pub final_pnl: Decimal,
     pub win_rate: f64,
+    #[serde(default)]
+    pub label: Option<String>,
+    #[serde(default)]
+    pub notes: Option<String>,
     pub created_at: DateTime<Utc>,
 }
 
@@ -416,6 +1557,286 @@ pub struct BacktestComparison {
     pub metrics_comparison: HashMap<String, Vec<f64>>,
 }
 
+/// Outcome of re-running a stored backtest from its saved configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BacktestReproduction {
+    pub backtest_id: String,
+    pub config_hash: String,
+    /// Checksum of the data the re-run consumed
+    pub data_checksum: String,
+    /// Whether that data matches the checksum stored with the run
+    pub data_matches: bool,
+    /// Whether the re-run produced the same trades and metrics
+    pub identical: bool,
+    /// One entry per mismatch between the stored run and the re-run
+    pub differences: Vec<String>,
+    pub total_trades: i32,
+    pub final_pnl: Decimal,
+}
+
+impl BacktestReproduction {
+    /// Compare a re-run with the stored run it reproduces
+    ///
+    /// Stored prices round-trip through floating point, so values are
+    /// compared within `tolerance` rather than exactly.
+    pub fn new(
+        original: &BacktestResult,
+        rerun: &BacktestResult,
+        config: &BacktestConfig,
+        data_checksum: String,
+        tolerance: f64,
+    ) -> Self {
+        let close = |a: Decimal, b: Decimal| {
+            (a.to_f64().unwrap_or(0.0) - b.to_f64().unwrap_or(0.0)).abs() <= tolerance
+        };
+        let close_opt = |a: Option<Decimal>, b: Option<Decimal>| match (a, b) {
+            (Some(a), Some(b)) => close(a, b),
+            (None, None) => true,
+            _ => false,
+        };
+        
+        let mut differences = Vec::new();
+        if data_checksum != config.data_checksum {
+            differences.push("Input data has changed since the run".to_string());
+        }
+        if original.total_trades != rerun.total_trades {
+            differences.push(format!("Total trades: {} stored, {} re-run", original.total_trades, rerun.total_trades));
+        }
+        if !close(original.final_pnl, rerun.final_pnl) {
+            differences.push(format!("Final P&L: {} stored, {} re-run", original.final_pnl, rerun.final_pnl));
+        }
+        if !close(original.max_drawdown, rerun.max_drawdown) {
+            differences.push(format!("Max drawdown: {} stored, {} re-run", original.max_drawdown, rerun.max_drawdown));
+        }
+        if !close(original.total_charges, rerun.total_charges) {
+            differences.push(format!("Total charges: {} stored, {} re-run", original.total_charges, rerun.total_charges));
+        }
+        if (original.sharpe_ratio - rerun.sharpe_ratio).abs() > tolerance {
+            differences.push(format!("Sharpe ratio: {} stored, {} re-run", original.sharpe_ratio, rerun.sharpe_ratio));
+        }
+        
+        // Report the first trade that diverges; everything after it usually follows
+        let diverged = original.trades.iter().zip(&rerun.trades).position(|(a, b)| {
+            a.trade_type != b.trade_type
+                || a.entry_time != b.entry_time
+                || a.exit_time != b.exit_time
+                || a.quantity != b.quantity
+                || !close(a.entry_price, b.entry_price)
+                || !close_opt(a.exit_price, b.exit_price)
+                || !close_opt(a.pnl, b.pnl)
+        });
+        if let Some(index) = diverged {
+            differences.push(format!(
+                "Trade {} differs: entered {} stored, {} re-run",
+                index + 1, original.trades[index].entry_time, rerun.trades[index].entry_time
+            ));
+        } else if original.trades.len() != rerun.trades.len() {
+            differences.push(format!(
+                "Trade list: {} stored, {} re-run", original.trades.len(), rerun.trades.len()
+            ));
+        }
+        
+        Self {
+            backtest_id: original.id.clone(),
+            config_hash: config.hash(),
+            data_matches: data_checksum == config.data_checksum,
+            identical: differences.is_empty(),
+            differences,
+            data_checksum,
+            total_trades: rerun.total_trades,
+            final_pnl: rerun.final_pnl,
+        }
+    }
+}
+
+/// Live trading period to compare with a backtest of the same strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftReportRequest {
+    pub strategy_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    /// Capital the backtest sizes positions from
+    pub initial_capital: Decimal,
+    /// How far a live fill may be from the simulated one and still match; two bars by default
+    #[serde(default)]
+    pub match_window_minutes: Option<i64>,
+}
+
+impl DriftReportRequest {
+    /// Match window in minutes, defaulting to two bars of the timeframe
+    pub fn match_window(&self) -> i64 {
+        self.match_window_minutes.unwrap_or(self.timeframe.duration_minutes().max(1) * 2)
+    }
+}
+
+/// Buy or sell at a price and time, either live or simulated
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftFill {
+    pub trade_type: TradeType,
+    pub time: DateTime<Utc>,
+    pub price: Decimal,
+    pub quantity: i32,
+}
+
+impl DriftFill {
+    /// Entry and, once closed, exit fills of backtest trades in time order
+    pub fn from_backtest(trades: &[BacktestTrade]) -> Vec<Self> {
+        let mut fills = Vec::new();
+        for trade in trades {
+            fills.push(Self {
+                trade_type: trade.trade_type,
+                time: trade.entry_time,
+                price: trade.entry_price,
+                quantity: trade.quantity,
+            });
+            if let (Some(time), Some(price)) = (trade.exit_time, trade.exit_price) {
+                fills.push(Self { trade_type: trade.trade_type.opposite(), time, price, quantity: trade.quantity });
+            }
+        }
+        fills.sort_by_key(|fill| fill.time);
+        fills
+    }
+}
+
+/// Simulated fill paired with the live fill that carried it out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FillComparison {
+    pub backtest: DriftFill,
+    pub live: DriftFill,
+    /// Per-unit price the live fill lost against the simulated one; negative is an improvement
+    pub slippage: Decimal,
+    pub slippage_bps: f64,
+    /// Seconds the live fill came after the simulated one
+    pub latency_seconds: i64,
+}
+
+/// Live fills of a strategy compared with a backtest over the same period
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DriftReport {
+    pub strategy_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub start_date: DateTime<Utc>,
+    pub end_date: DateTime<Utc>,
+    pub matched: Vec<FillComparison>,
+    /// Simulated fills with no live fill inside the match window
+    pub missed_signals: Vec<DriftFill>,
+    /// Live fills the backtest did not produce
+    pub unexpected_fills: Vec<DriftFill>,
+    pub average_slippage_bps: f64,
+    /// Slippage over matched fills times the live quantity, in rupees
+    pub slippage_cost: Decimal,
+    pub average_latency_seconds: f64,
+    pub max_latency_seconds: i64,
+    /// Realized P&L of completed round trips, before charges
+    pub backtest_pnl: Decimal,
+    pub live_pnl: Decimal,
+    /// Backtest P&L the live strategy failed to capture
+    pub implementation_shortfall: Decimal,
+}
+
+impl DriftReport {
+    /// Pair each simulated fill with the nearest unused live fill on the same side
+    ///
+    /// Fills more than `match_window_minutes` apart are never paired.
+    pub fn new(request: &DriftReportRequest, backtest: &[DriftFill], live: &[DriftFill]) -> Self {
+        let window = Duration::minutes(request.match_window());
+        let mut used = vec![false; live.len()];
+        let mut matched = Vec::new();
+        let mut missed_signals = Vec::new();
+        
+        for fill in backtest {
+            let nearest = live.iter().enumerate()
+                .filter(|(i, candidate)| {
+                    !used[*i]
+                        && candidate.trade_type == fill.trade_type
+                        && (candidate.time - fill.time).abs() <= window
+                })
+                .min_by_key(|(_, candidate)| (candidate.time - fill.time).abs())
+                .map(|(i, _)| i);
+            
+            match nearest {
+                Some(i) => {
+                    used[i] = true;
+                    let slippage = match fill.trade_type {
+                        TradeType::Buy => live[i].price - fill.price,
+                        TradeType::Sell => fill.price - live[i].price,
+                    };
+                    let slippage_bps = if fill.price > Decimal::ZERO {
+                        (slippage / fill.price).to_f64().unwrap_or(0.0) * 10_000.0
+                    } else {
+                        0.0
+                    };
+                    matched.push(FillComparison {
+                        backtest: fill.clone(),
+                        live: live[i].clone(),
+                        slippage,
+                        slippage_bps,
+                        latency_seconds: (live[i].time - fill.time).num_seconds(),
+                    });
+                }
+                None => missed_signals.push(fill.clone()),
+            }
+        }
+        
+        let unexpected_fills = live.iter().zip(&used)
+            .filter(|(_, used)| !**used)
+            .map(|(fill, _)| fill.clone())
+            .collect();
+        
+        let count = matched.len().max(1) as f64;
+        let backtest_pnl = realized_pnl(backtest);
+        let live_pnl = realized_pnl(live);
+        
+        Self {
+            strategy_id: request.strategy_id.clone(),
+            symbol: request.symbol.clone(),
+            exchange: request.exchange.clone(),
+            start_date: request.start_date,
+            end_date: request.end_date,
+            average_slippage_bps: matched.iter().map(|m| m.slippage_bps).sum::<f64>() / count,
+            slippage_cost: matched.iter().map(|m| m.slippage * Decimal::from(m.live.quantity)).sum(),
+            average_latency_seconds: matched.iter().map(|m| m.latency_seconds as f64).sum::<f64>() / count,
+            max_latency_seconds: matched.iter().map(|m| m.latency_seconds).max().unwrap_or(0),
+            matched,
+            missed_signals,
+            unexpected_fills,
+            backtest_pnl,
+            live_pnl,
+            implementation_shortfall: backtest_pnl - live_pnl,
+        }
+    }
+}
+
+/// Cash from fills over the round trips that end flat; a position still open is left out
+fn realized_pnl(fills: &[DriftFill]) -> Decimal {
+    let mut realized = Decimal::ZERO;
+    let mut cash = Decimal::ZERO;
+    let mut position: i64 = 0;
+    
+    for fill in fills {
+        let value = fill.price * Decimal::from(fill.quantity);
+        match fill.trade_type {
+            TradeType::Buy => {
+                cash -= value;
+                position += fill.quantity as i64;
+            }
+            TradeType::Sell => {
+                cash += value;
+                position -= fill.quantity as i64;
+            }
+        }
+        if position == 0 {
+            realized += cash;
+            cash = Decimal::ZERO;
+        }
+    }
+    realized
+}
+
 /// Historical data parameters for Kite API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HistoricalDataParams {
@@ -437,6 +1858,130 @@ pub struct HistoricalDataFetchParams {
     pub timeframe: Timeframe,
 }
 
+/// Stretch of weekdays with no stored candles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataGap {
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    /// Weekdays in the gap, exchange holidays included
+    pub missing_days: usize,
+}
+
+/// Stored candle coverage of a symbol and timeframe over a date range
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataCoverage {
+    pub symbol: String,
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    pub from_date: DateTime<Utc>,
+    pub to_date: DateTime<Utc>,
+    pub candles: usize,
+    pub first_candle: Option<DateTime<Utc>>,
+    pub last_candle: Option<DateTime<Utc>>,
+    /// Weekdays in the range up to today (IST)
+    pub expected_days: usize,
+    pub covered_days: usize,
+    pub coverage_percent: f64,
+    pub gaps: Vec<DataGap>,
+}
+
+impl DataCoverage {
+    /// Work out coverage from the timestamps of candles stored in the range
+    ///
+    /// A trading day counts as covered when it has any candle. Missing days
+    /// separated only by a weekend are reported as one gap.
+    pub fn from_candles(
+        symbol: &str,
+        exchange: &str,
+        timeframe: Timeframe,
+        from_date: DateTime<Utc>,
+        to_date: DateTime<Utc>,
+        timestamps: &[DateTime<Utc>],
+    ) -> Self {
+        let stored_days: std::collections::BTreeSet<NaiveDate> = timestamps.iter()
+            .map(|timestamp| timestamp.with_timezone(&Kolkata).date_naive())
+            .collect();
+        
+        let first_day = from_date.with_timezone(&Kolkata).date_naive();
+        let last_day = to_date.with_timezone(&Kolkata).date_naive()
+            .min(Utc::now().with_timezone(&Kolkata).date_naive());
+        
+        let mut expected_days = 0;
+        let mut covered_days = 0;
+        let mut missing: Vec<(NaiveDate, NaiveDate, usize)> = Vec::new();
+        let mut in_gap = false;
+        
+        for day in first_day.iter_days().take_while(|day| *day <= last_day) {
+            if matches!(day.weekday(), Weekday::Sat | Weekday::Sun) {
+                continue;
+            }
+            expected_days += 1;
+            
+            if stored_days.contains(&day) {
+                covered_days += 1;
+                in_gap = false;
+            } else {
+                match missing.last_mut() {
+                    Some((_, end, count)) if in_gap => {
+                        *end = day;
+                        *count += 1;
+                    }
+                    _ => missing.push((day, day, 1)),
+                }
+                in_gap = true;
+            }
+        }
+        
+        let gaps = missing.into_iter()
+            .map(|(start, end, missing_days)| DataGap {
+                from: ist_day_start(start).max(from_date),
+                to: (ist_day_start(end) + Duration::days(1) - Duration::seconds(1)).min(to_date),
+                missing_days,
+            })
+            .collect();
+        
+        Self {
+            symbol: symbol.to_string(),
+            exchange: exchange.to_string(),
+            timeframe,
+            from_date,
+            to_date,
+            candles: timestamps.len(),
+            first_candle: timestamps.iter().min().copied(),
+            last_candle: timestamps.iter().max().copied(),
+            expected_days,
+            covered_days,
+            coverage_percent: if expected_days > 0 {
+                covered_days as f64 / expected_days as f64 * 100.0
+            } else {
+                100.0
+            },
+            gaps,
+        }
+    }
+}
+
+/// Midnight IST of a day, in UTC
+fn ist_day_start(day: NaiveDate) -> DateTime<Utc> {
+    let midnight = day.and_hms_opt(0, 0, 0).unwrap_or_default();
+    match Kolkata.from_local_datetime(&midnight).single() {
+        Some(start) => start.with_timezone(&Utc),
+        None => Utc.from_utc_datetime(&midnight),
+    }
+}
+
+/// Stored candles or ticks for one symbol and timeframe
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoricalDataset {
+    pub symbol: String,
+    pub exchange: String,
+    pub timeframe: Timeframe,
+    pub first_timestamp: DateTime<Utc>,
+    pub last_timestamp: DateTime<Utc>,
+    /// Candles, or ticks for the tick timeframe
+    pub rows: usize,
+}
+
 /// CSV import validation result
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CsvValidationResult {
@@ -447,6 +1992,58 @@ pub struct CsvValidationResult {
     pub valid_rows: usize,
 }
 
+/// Dry-run of a CSV import: the first parsed rows and validation of the whole file
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvPreview {
+    /// Header fields, when the file has a header row
+    pub header: Option<Vec<String>>,
+    pub rows: Vec<OHLCV>,
+    pub validation: CsvValidationResult,
+}
+
+/// A CSV column, by zero-based position or by header name
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum CsvColumn {
+    Index(usize),
+    Name(String),
+}
+
+/// Which CSV columns hold each OHLCV field
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CsvColumnMapping {
+    pub timestamp: CsvColumn,
+    pub open: CsvColumn,
+    pub high: CsvColumn,
+    pub low: CsvColumn,
+    pub close: CsvColumn,
+    /// Files without volume import with zero volume
+    pub volume: Option<CsvColumn>,
+}
+
+impl Default for CsvColumnMapping {
+    fn default() -> Self {
+        Self {
+            timestamp: CsvColumn::Index(0),
+            open: CsvColumn::Index(1),
+            high: CsvColumn::Index(2),
+            low: CsvColumn::Index(3),
+            close: CsvColumn::Index(4),
+            volume: Some(CsvColumn::Index(5)),
+        }
+    }
+}
+
+/// How numbers are written in a CSV file
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum DecimalFormat {
+    /// `1,234.56`
+    #[default]
+    Point,
+    /// `1.234,56`
+    Comma,
+}
+
 /// CSV import configuration
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CsvImportConfig {
@@ -456,6 +2053,16 @@ pub struct CsvImportConfig {
     pub has_header: bool,
     pub date_format: String,
     pub timezone: String,
+    #[serde(default)]
+    pub columns: CsvColumnMapping,
+    #[serde(default = "default_csv_delimiter")]
+    pub delimiter: char,
+    #[serde(default)]
+    pub decimal_format: DecimalFormat,
+}
+
+fn default_csv_delimiter() -> char {
+    ','
 }
 
 impl Default for CsvImportConfig {
@@ -467,6 +2074,9 @@ impl Default for CsvImportConfig {
             has_header: true,
             date_format: "%Y-%m-%d %H:%M:%S".to_string(),
             timezone: "Asia/Kolkata".to_string(),
+            columns: CsvColumnMapping::default(),
+            delimiter: default_csv_delimiter(),
+            decimal_format: DecimalFormat::Point,
         }
     }
 }
\ No newline at end of file
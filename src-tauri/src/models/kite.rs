@@ -237,6 +237,172 @@ pub struct KiteOrder {
     pub tags: Option<Vec<String>>,
 }
 
+/// Order update pushed on the ticker connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteOrderUpdate {
+    /// Order ID
+    pub order_id: String,
+    
+    /// Exchange order ID
+    pub exchange_order_id: Option<String>,
+    
+    /// Status
+    pub status: KiteOrderStatus,
+    
+    /// Status message, the reason for a rejection
+    pub status_message: Option<String>,
+    
+    /// Trading symbol
+    pub tradingsymbol: String,
+    
+    /// Average price
+    #[serde(default)]
+    pub average_price: f64,
+    
+    /// Filled quantity
+    #[serde(default)]
+    pub filled_quantity: u32,
+    
+    /// Pending quantity
+    #[serde(default)]
+    pub pending_quantity: u32,
+}
+
+/// Kite trade, one fill of an order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteTrade {
+    /// Trade ID
+    pub trade_id: String,
+    
+    /// Order ID
+    pub order_id: String,
+    
+    /// Exchange order ID
+    pub exchange_order_id: Option<String>,
+    
+    /// Trading symbol
+    pub tradingsymbol: String,
+    
+    /// Exchange
+    pub exchange: KiteExchange,
+    
+    /// Instrument token
+    pub instrument_token: u64,
+    
+    /// Product
+    pub product: KiteProduct,
+    
+    /// Fill price
+    pub average_price: f64,
+    
+    /// Filled quantity
+    pub quantity: u32,
+    
+    /// Transaction type
+    pub transaction_type: KiteTransactionType,
+    
+    /// Fill timestamp
+    pub fill_timestamp: Option<DateTime<Utc>>,
+    
+    /// Exchange timestamp
+    pub exchange_timestamp: Option<DateTime<Utc>>,
+}
+
+/// Condition a GTT trigger watches
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteGttCondition {
+    /// Exchange
+    pub exchange: KiteExchange,
+    
+    /// Trading symbol
+    pub tradingsymbol: String,
+    
+    /// Trigger prices; one for a single trigger, the stoploss and target for a two-leg one
+    pub trigger_values: Vec<f64>,
+    
+    /// Last price when the trigger was placed
+    pub last_price: f64,
+    
+    /// Instrument token
+    #[serde(default)]
+    pub instrument_token: Option<u64>,
+}
+
+/// Order a GTT trigger places once its condition is met
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteGttOrder {
+    /// Exchange
+    pub exchange: KiteExchange,
+    
+    /// Trading symbol
+    pub tradingsymbol: String,
+    
+    /// Transaction type
+    pub transaction_type: KiteTransactionType,
+    
+    /// Quantity
+    pub quantity: u32,
+    
+    /// Order type
+    pub order_type: KiteOrderType,
+    
+    /// Product
+    pub product: KiteProduct,
+    
+    /// Limit price
+    pub price: f64,
+}
+
+/// GTT trigger to create or modify
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteGttRequest {
+    /// Trigger type
+    #[serde(rename = "type")]
+    pub trigger_type: KiteGttType,
+    
+    /// Condition to watch
+    pub condition: KiteGttCondition,
+    
+    /// Orders to place, one per trigger value
+    pub orders: Vec<KiteGttOrder>,
+}
+
+/// GTT trigger resting with the broker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteGtt {
+    /// Trigger ID
+    pub id: u64,
+    
+    /// Trigger type
+    #[serde(rename = "type")]
+    pub trigger_type: KiteGttType,
+    
+    /// Status
+    pub status: KiteGttStatus,
+    
+    /// Condition being watched
+    pub condition: KiteGttCondition,
+    
+    /// Orders placed once triggered
+    pub orders: Vec<KiteGttOrder>,
+    
+    /// Created timestamp
+    pub created_at: Option<DateTime<Utc>>,
+    
+    /// Updated timestamp
+    pub updated_at: Option<DateTime<Utc>>,
+    
+    /// Expiry timestamp
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+/// Response to creating, modifying or deleting a GTT trigger
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteGttResponse {
+    /// Trigger ID
+    pub trigger_id: u64,
+}
+
 /// Kite holding
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KiteHolding {
@@ -371,6 +537,123 @@ pub struct KiteUsedMargin {
     pub turnover: f64,
 }
 
+/// Order leg to evaluate the margin of, before it is placed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteMarginOrder {
+    /// Exchange
+    pub exchange: KiteExchange,
+    
+    /// Trading symbol
+    pub tradingsymbol: String,
+    
+    /// Transaction type
+    pub transaction_type: KiteTransactionType,
+    
+    /// Order variety
+    pub variety: KiteOrderVariety,
+    
+    /// Product
+    pub product: KiteProduct,
+    
+    /// Order type
+    pub order_type: KiteOrderType,
+    
+    /// Quantity
+    pub quantity: u32,
+    
+    /// Price, zero for market orders
+    #[serde(default)]
+    pub price: f64,
+    
+    /// Trigger price, zero unless a stop-loss order
+    #[serde(default)]
+    pub trigger_price: f64,
+}
+
+impl From<&KiteOrderRequest> for KiteMarginOrder {
+    fn from(order: &KiteOrderRequest) -> Self {
+        Self {
+            exchange: order.exchange,
+            tradingsymbol: order.tradingsymbol.clone(),
+            transaction_type: order.transaction_type,
+            variety: order.variety,
+            product: order.product,
+            order_type: order.order_type,
+            quantity: order.quantity,
+            price: order.price.unwrap_or(0.0),
+            trigger_price: order.trigger_price.unwrap_or(0.0),
+        }
+    }
+}
+
+/// Realised and unrealised P&L counted in a margin requirement
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KiteMarginPnl {
+    /// Realised
+    #[serde(default)]
+    pub realised: f64,
+    
+    /// Unrealised
+    #[serde(default)]
+    pub unrealised: f64,
+}
+
+/// Margin required by an order, or by a set of orders taken together
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct KiteOrderMargin {
+    /// Exchange
+    pub exchange: String,
+    
+    /// Trading symbol, empty for a basket total
+    pub tradingsymbol: String,
+    
+    /// SPAN margin
+    pub span: f64,
+    
+    /// Exposure margin
+    pub exposure: f64,
+    
+    /// Option premium
+    pub option_premium: f64,
+    
+    /// Additional margin
+    pub additional: f64,
+    
+    /// Bracket order margin
+    pub bo: f64,
+    
+    /// Cash credit
+    pub cash: f64,
+    
+    /// VAR margin
+    pub var: f64,
+    
+    /// P&L
+    pub pnl: KiteMarginPnl,
+    
+    /// Leverage
+    pub leverage: f64,
+    
+    /// Total margin
+    pub total: f64,
+}
+
+/// Combined margin of a basket of orders
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteBasketMargin {
+    /// Sum of the legs' individual margins
+    pub initial: KiteOrderMargin,
+    
+    /// Margin after hedges between legs (and open positions, if considered) offset each other
+    #[serde(rename = "final")]
+    pub final_margin: KiteOrderMargin,
+    
+    /// Margin of each leg on its own
+    #[serde(default)]
+    pub orders: Vec<KiteOrderMargin>,
+}
+
 /// Kite profile
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KiteProfile {
@@ -438,10 +721,37 @@ pub struct KiteQuote {
     /// Upper circuit limit
     pub upper_circuit_limit: f64,
     
+    /// Open interest, zero for instruments without any
+    #[serde(default)]
+    pub oi: u64,
+    
     /// Depth
     pub depth: KiteMarketDepth,
 }
 
+/// Kite OHLC quote, the lighter `/quote/ohlc` form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteOhlcQuote {
+    /// Instrument token
+    pub instrument_token: u64,
+    
+    /// Last price
+    pub last_price: f64,
+    
+    /// Day's OHLC, with the previous close
+    pub ohlc: KiteOHLC,
+}
+
+/// Kite LTP quote, the lightest `/quote/ltp` form
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KiteLtpQuote {
+    /// Instrument token
+    pub instrument_token: u64,
+    
+    /// Last price
+    pub last_price: f64,
+}
+
 /// Kite OHLC
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct KiteOHLC {
@@ -565,6 +875,21 @@ pub struct KiteInstrument {
     pub exchange: KiteExchange,
 }
 
+impl KiteInstrument {
+    /// Round a price to the nearest tick the exchange accepts
+    pub fn round_to_tick(&self, price: Decimal) -> Decimal {
+        match Decimal::try_from(self.tick_size) {
+            Ok(tick) if tick > Decimal::ZERO => (price / tick).round() * tick,
+            _ => price,
+        }
+    }
+    
+    /// Whether a quantity is a whole number of lots
+    pub fn is_whole_lots(&self, quantity: u32) -> bool {
+        quantity > 0 && quantity % self.lot_size.max(1) == 0
+    }
+}
+
 /// Kite exchange enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum KiteExchange {
@@ -574,6 +899,12 @@ pub enum KiteExchange {
     /// Bombay Stock Exchange
     BSE,
     
+    /// Futures & Options segment of NSE
+    NFO,
+    
+    /// Futures & Options segment of BSE
+    BFO,
+    
     /// Multi Commodity Exchange
     MCX,
     
@@ -595,6 +926,8 @@ impl Serialize for KiteExchange {
         serializer.serialize_str(match self {
             KiteExchange::NSE => "NSE",
             KiteExchange::BSE => "BSE",
+            KiteExchange::NFO => "NFO",
+            KiteExchange::BFO => "BFO",
             KiteExchange::MCX => "MCX",
             KiteExchange::NCDEX => "NCDEX",
             KiteExchange::CDS => "CDS",
@@ -624,6 +957,8 @@ impl<'de> Deserialize<'de> for KiteExchange {
                 match value {
                     "NSE" => Ok(KiteExchange::NSE),
                     "BSE" => Ok(KiteExchange::BSE),
+                    "NFO" => Ok(KiteExchange::NFO),
+                    "BFO" => Ok(KiteExchange::BFO),
                     "MCX" => Ok(KiteExchange::MCX),
                     "NCDEX" => Ok(KiteExchange::NCDEX),
                     "CDS" => Ok(KiteExchange::CDS),
@@ -644,6 +979,8 @@ impl FromStr for KiteExchange {
         match s {
             "NSE" => Ok(KiteExchange::NSE),
             "BSE" => Ok(KiteExchange::BSE),
+            "NFO" => Ok(KiteExchange::NFO),
+            "BFO" => Ok(KiteExchange::BFO),
             "MCX" => Ok(KiteExchange::MCX),
             "NCDEX" => Ok(KiteExchange::NCDEX),
             "CDS" => Ok(KiteExchange::CDS),
@@ -658,6 +995,8 @@ impl fmt::Display for KiteExchange {
         match self {
             KiteExchange::NSE => write!(f, "NSE"),
             KiteExchange::BSE => write!(f, "BSE"),
+            KiteExchange::NFO => write!(f, "NFO"),
+            KiteExchange::BFO => write!(f, "BFO"),
             KiteExchange::MCX => write!(f, "MCX"),
             KiteExchange::NCDEX => write!(f, "NCDEX"),
             KiteExchange::CDS => write!(f, "CDS"),
@@ -912,6 +1251,18 @@ impl<'de> Deserialize<'de> for KiteProduct {
     }
 }
 
+impl fmt::Display for KiteProduct {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KiteProduct::CNC => write!(f, "CNC"),
+            KiteProduct::MIS => write!(f, "MIS"),
+            KiteProduct::NRML => write!(f, "NRML"),
+            KiteProduct::CO => write!(f, "CO"),
+            KiteProduct::BO => write!(f, "BO"),
+        }
+    }
+}
+
 /// Kite validity enum
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum KiteValidity {
@@ -1257,6 +1608,67 @@ impl fmt::Display for KiteInterval {
     }
 }
 
+/// Kite GTT trigger type enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum KiteGttType {
+    /// One trigger price and one order
+    #[serde(rename = "single")]
+    Single,
+    
+    /// Stoploss and target prices, one order each (OCO)
+    #[serde(rename = "two-leg")]
+    TwoLeg,
+}
+
+impl std::fmt::Display for KiteGttType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KiteGttType::Single => write!(f, "single"),
+            KiteGttType::TwoLeg => write!(f, "two-leg"),
+        }
+    }
+}
+
+/// Kite GTT trigger status enum
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KiteGttStatus {
+    /// Resting, watching its condition
+    Active,
+    
+    /// Condition met and orders placed
+    Triggered,
+    
+    /// Disabled
+    Disabled,
+    
+    /// Expired unmet
+    Expired,
+    
+    /// Cancelled
+    Cancelled,
+    
+    /// Orders rejected once triggered
+    Rejected,
+    
+    /// Deleted
+    Deleted,
+}
+
+impl std::fmt::Display for KiteGttStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            KiteGttStatus::Active => write!(f, "active"),
+            KiteGttStatus::Triggered => write!(f, "triggered"),
+            KiteGttStatus::Disabled => write!(f, "disabled"),
+            KiteGttStatus::Expired => write!(f, "expired"),
+            KiteGttStatus::Cancelled => write!(f, "cancelled"),
+            KiteGttStatus::Rejected => write!(f, "rejected"),
+            KiteGttStatus::Deleted => write!(f, "deleted"),
+        }
+    }
+}
+
 /// Kite disclosed quantity
 #[derive(Debug, Clone, Copy)]
 pub struct KiteDiscloseQuantity(u32);
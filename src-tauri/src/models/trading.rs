@@ -1,5 +1,6 @@
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 use std::collections::HashMap;
@@ -20,6 +21,16 @@ impl std::fmt::Display for TradeType {
     }
 }
 
+impl TradeType {
+    /// Trade type that closes a position opened with this type
+    pub fn opposite(&self) -> TradeType {
+        match self {
+            TradeType::Buy => TradeType::Sell,
+            TradeType::Sell => TradeType::Buy,
+        }
+    }
+}
+
 impl std::str::FromStr for TradeType {
     type Err = String;
     
@@ -54,6 +65,12 @@ impl std::fmt::Display for TradeStatus {
     }
 }
 
+/// Exit reason recorded when a position outlives its strategy's holding limit
+pub const TIME_EXIT_REASON: &str = "Time exit";
+
+/// Exit reason recorded when an intraday position is closed at the end of the session
+pub const SQUARE_OFF_REASON: &str = "Session square-off";
+
 /// Trade model representing a single trade
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Trade {
@@ -68,6 +85,9 @@ pub struct Trade {
     pub status: TradeStatus,
     pub executed_at: DateTime<Utc>,
     pub strategy_id: String,
+    /// Why the trade closed a position, if it was an exit
+    #[serde(default)]
+    pub exit_reason: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -96,6 +116,7 @@ impl Trade {
             status: TradeStatus::Pending,
             executed_at: now,
             strategy_id: strategy_id.to_string(),
+            exit_reason: None,
             created_at: now,
             updated_at: now,
         }
@@ -123,8 +144,17 @@ pub struct Position {
     pub pnl: Decimal,
     pub pnl_percentage: Decimal,
     pub trade_type: TradeType,
+    /// Strategy that opened the position, if known
+    #[serde(default)]
+    pub strategy_id: Option<String>,
     pub entry_time: DateTime<Utc>,
     pub last_updated: DateTime<Utc>,
+    /// Maximum adverse excursion: the deepest loss since entry, as a positive percentage of the average price
+    #[serde(default)]
+    pub max_adverse_excursion: Decimal,
+    /// Maximum favorable excursion: the highest profit since entry, as a percentage of the average price
+    #[serde(default)]
+    pub max_favorable_excursion: Decimal,
 }
 
 impl Position {
@@ -146,8 +176,11 @@ impl Position {
             pnl: Decimal::ZERO,
             pnl_percentage: Decimal::ZERO,
             trade_type,
+            strategy_id: None,
             entry_time: now,
             last_updated: now,
+            max_adverse_excursion: Decimal::ZERO,
+            max_favorable_excursion: Decimal::ZERO,
         }
     }
     
@@ -170,6 +203,9 @@ impl Position {
         if self.average_price != Decimal::ZERO {
             self.pnl_percentage = (price_diff / self.average_price) * Decimal::from(100);
         }
+        
+        self.max_adverse_excursion = self.max_adverse_excursion.max(-self.pnl_percentage);
+        self.max_favorable_excursion = self.max_favorable_excursion.max(self.pnl_percentage);
     }
     
     /// Add to position (for averaging)
@@ -193,6 +229,174 @@ impl Position {
     }
 }
 
+/// Built-in strategy logic used to generate signals
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default, Serialize, Deserialize)]
+pub enum StrategyType {
+    #[default]
+    BollingerMeanReversion,
+    MomentumBreakout,
+    /// Entry and exit conditions from the strategy's `rules`
+    Custom,
+}
+
+impl std::fmt::Display for StrategyType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            StrategyType::BollingerMeanReversion => write!(f, "BollingerMeanReversion"),
+            StrategyType::MomentumBreakout => write!(f, "MomentumBreakout"),
+            StrategyType::Custom => write!(f, "Custom"),
+        }
+    }
+}
+
+impl std::str::FromStr for StrategyType {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "BollingerMeanReversion" => Ok(StrategyType::BollingerMeanReversion),
+            "MomentumBreakout" => Ok(StrategyType::MomentumBreakout),
+            "Custom" => Ok(StrategyType::Custom),
+            _ => Err(format!("Invalid StrategyType: {}", s)),
+        }
+    }
+}
+
+/// Smoothed indicators are given this many periods of history before they are trusted
+pub const SMOOTHING_WARM_UP_PERIODS: usize = 3;
+
+/// Longest indicator period a custom rule may use
+pub const MAX_RULE_PERIOD: usize = 200;
+
+/// Most conditions allowed in a custom rule list
+pub const MAX_RULE_CONDITIONS: usize = 10;
+
+/// Value compared by a custom rule, evaluated on the latest bar
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "indicator", rename_all = "snake_case")]
+pub enum IndicatorSource {
+    Close,
+    Volume,
+    Constant { value: f64 },
+    Sma { period: usize },
+    Ema { period: usize },
+    Rsi { period: usize },
+    Atr { period: usize },
+    BollingerUpper { period: usize, std_dev: f64 },
+    BollingerLower { period: usize, std_dev: f64 },
+    /// Highest high of the `period` bars before the latest
+    Highest { period: usize },
+    /// Lowest low of the `period` bars before the latest
+    Lowest { period: usize },
+    RateOfChange { period: usize },
+}
+
+impl IndicatorSource {
+    /// Bars needed before the value is reliable, including smoothing warm-up
+    pub fn warm_up(&self) -> usize {
+        match *self {
+            IndicatorSource::Close | IndicatorSource::Volume | IndicatorSource::Constant { .. } => 1,
+            IndicatorSource::Sma { period }
+            | IndicatorSource::BollingerUpper { period, .. }
+            | IndicatorSource::BollingerLower { period, .. } => period,
+            IndicatorSource::Ema { period } => period * SMOOTHING_WARM_UP_PERIODS,
+            IndicatorSource::Rsi { period } | IndicatorSource::Atr { period } => period * SMOOTHING_WARM_UP_PERIODS + 1,
+            IndicatorSource::Highest { period }
+            | IndicatorSource::Lowest { period }
+            | IndicatorSource::RateOfChange { period } => period + 1,
+        }
+    }
+
+    fn validate(&self) -> std::result::Result<(), String> {
+        let (period, std_dev) = match *self {
+            IndicatorSource::Close | IndicatorSource::Volume => return Ok(()),
+            IndicatorSource::Constant { value } => {
+                return if value.is_finite() { Ok(()) } else { Err("Rule constants must be finite".to_string()) };
+            }
+            IndicatorSource::BollingerUpper { period, std_dev }
+            | IndicatorSource::BollingerLower { period, std_dev } => (period, Some(std_dev)),
+            IndicatorSource::Sma { period }
+            | IndicatorSource::Ema { period }
+            | IndicatorSource::Rsi { period }
+            | IndicatorSource::Atr { period }
+            | IndicatorSource::Highest { period }
+            | IndicatorSource::Lowest { period }
+            | IndicatorSource::RateOfChange { period } => (period, None),
+        };
+
+        if period == 0 || period > MAX_RULE_PERIOD {
+            return Err(format!("Indicator periods must be between 1 and {}", MAX_RULE_PERIOD));
+        }
+        match std_dev {
+            Some(std_dev) if !(std_dev.is_finite() && std_dev > 0.0) => {
+                Err("Bollinger band width must be positive".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// How the two sides of a custom rule are compared
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RuleComparison {
+    Above,
+    Below,
+    /// At or below on the previous bar, above on the latest
+    CrossesAbove,
+    /// At or above on the previous bar, below on the latest
+    CrossesBelow,
+}
+
+/// Single condition of a custom strategy
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RuleCondition {
+    pub left: IndicatorSource,
+    pub comparison: RuleComparison,
+    pub right: IndicatorSource,
+}
+
+impl RuleCondition {
+    /// Bars needed before the condition can be evaluated
+    pub fn warm_up(&self) -> usize {
+        let bars = self.left.warm_up().max(self.right.warm_up());
+        match self.comparison {
+            RuleComparison::CrossesAbove | RuleComparison::CrossesBelow => bars + 1,
+            RuleComparison::Above | RuleComparison::Below => bars,
+        }
+    }
+}
+
+/// Entry and exit conditions of a custom strategy; every condition in a list must hold
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyRules {
+    pub entry: Vec<RuleCondition>,
+    #[serde(default)]
+    pub exit: Vec<RuleCondition>,
+}
+
+impl StrategyRules {
+    /// Bars needed before any condition can be evaluated
+    pub fn warm_up(&self) -> usize {
+        self.entry.iter().chain(&self.exit).map(RuleCondition::warm_up).max().unwrap_or(1)
+    }
+
+    /// Check condition counts and indicator settings
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.entry.is_empty() {
+            return Err("Custom rules need at least one entry condition".to_string());
+        }
+        if self.entry.len() > MAX_RULE_CONDITIONS || self.exit.len() > MAX_RULE_CONDITIONS {
+            return Err(format!("Rule lists can have at most {} conditions", MAX_RULE_CONDITIONS));
+        }
+        for condition in self.entry.iter().chain(&self.exit) {
+            condition.left.validate()?;
+            condition.right.validate()?;
+        }
+        Ok(())
+    }
+}
+
 /// Strategy parameters model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyParams {
@@ -206,6 +410,25 @@ pub struct StrategyParams {
     pub stop_loss_percentage: f64,
     pub take_profit_percentage: f64,
     pub volume_threshold: i64,
+    #[serde(default)]
+    pub strategy_type: StrategyType,
+    /// Conditions evaluated by custom strategies
+    #[serde(default)]
+    pub rules: Option<StrategyRules>,
+    /// Maximum order value in rupees, if capped
+    #[serde(default)]
+    pub max_capital: Option<f64>,
+    /// Maximum shares per order, if capped
+    #[serde(default)]
+    pub max_quantity_per_trade: Option<i32>,
+    /// Positions held longer than this are closed regardless of price
+    #[serde(default)]
+    pub max_holding_minutes: Option<i64>,
+    /// Free-form labels, stored lowercase
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub archived_at: Option<DateTime<Utc>>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -234,6 +457,13 @@ impl StrategyParams {
             stop_loss_percentage,
             take_profit_percentage,
             volume_threshold,
+            strategy_type: StrategyType::default(),
+            rules: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: Vec::new(),
+            archived_at: None,
             created_at: now,
             updated_at: now,
         }
@@ -284,6 +514,77 @@ impl StrategyParams {
         self.enabled = false;
         self.updated_at = Utc::now();
     }
+    
+    /// Whether the strategy has been archived
+    pub fn is_archived(&self) -> bool {
+        self.archived_at.is_some()
+    }
+    
+    /// Check whether the strategy carries a tag (case-insensitive)
+    pub fn has_tag(&self, tag: &str) -> bool {
+        let tag = tag.trim().to_lowercase();
+        self.tags.iter().any(|t| *t == tag)
+    }
+    
+    /// Check whether the name, description or tags contain the text (case-insensitive)
+    pub fn matches_search(&self, text: &str) -> bool {
+        let text = text.trim().to_lowercase();
+        self.name.to_lowercase().contains(&text)
+            || self.description.as_deref().map_or(false, |d| d.to_lowercase().contains(&text))
+            || self.tags.iter().any(|t| t.contains(&text))
+    }
+    
+    /// Whether a position opened at `entry_time` has outlived the holding limit
+    pub fn holding_period_exceeded(&self, entry_time: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+        self.max_holding_minutes
+            .map_or(false, |minutes| now - entry_time >= chrono::Duration::minutes(minutes))
+    }
+    
+    /// Limit an order size to the strategy's quantity and capital caps
+    pub fn cap_quantity(&self, quantity: i32, price: Decimal) -> i32 {
+        let mut quantity = quantity;
+        
+        if let Some(max_quantity) = self.max_quantity_per_trade {
+            quantity = quantity.min(max_quantity);
+        }
+        
+        if let Some(max_capital) = self.max_capital {
+            let affordable = Decimal::from_f64(max_capital)
+                .filter(|_| price > Decimal::ZERO)
+                .and_then(|capital| (capital / price).floor().to_i32())
+                .unwrap_or(0);
+            quantity = quantity.min(affordable);
+        }
+        
+        quantity.max(0)
+    }
+    
+    /// Archive strategy, disabling it
+    pub fn archive(&mut self) {
+        let now = Utc::now();
+        self.enabled = false;
+        self.archived_at = Some(now);
+        self.updated_at = now;
+    }
+    
+    /// Restore an archived strategy (left disabled)
+    pub fn restore(&mut self) {
+        self.archived_at = None;
+        self.updated_at = Utc::now();
+    }
+}
+
+/// Live per-strategy stats for the current trading day
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StrategyStatsSnapshot {
+    pub strategy_id: String,
+    pub enabled: bool,
+    pub trades_today: i32,
+    /// Cash flow of executed trades with open quantity marked at the last fill
+    pub pnl_today: f64,
+    pub open_positions: i32,
+    pub max_trades_per_day: i32,
+    pub remaining_trades: i32,
 }
 
 /// Stock selection model
@@ -321,6 +622,240 @@ impl StockSelection {
     }
 }
 
+/// Index trend filter attached to a strategy
+///
+/// Long entries are only taken while the index trades above its moving
+/// average, short entries only while it trades below.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrendFilterConfig {
+    pub strategy_id: String,
+    pub index_symbol: String,
+    pub ma_period: usize,
+    pub enabled: bool,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TrendFilterConfig {
+    /// Create new enabled trend filter
+    pub fn new(strategy_id: &str, index_symbol: &str, ma_period: usize) -> Self {
+        Self {
+            strategy_id: strategy_id.to_string(),
+            index_symbol: index_symbol.to_string(),
+            ma_period,
+            enabled: true,
+            updated_at: Utc::now(),
+        }
+    }
+}
+
+/// Two-leg (pair trading) strategy configuration
+///
+/// The spread is `ln(A) - hedge_ratio * ln(B)`; entries are taken when its
+/// z-score crosses `entry_z` and closed when it reverts inside `exit_z` or
+/// blows out past `stop_z`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairTradingConfig {
+    pub strategy_id: String,
+    pub symbol_a: String,
+    pub symbol_b: String,
+    pub exchange: String,
+    pub hedge_ratio: f64,
+    pub lookback: usize,
+    pub entry_z: f64,
+    pub exit_z: f64,
+    pub stop_z: f64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl PairTradingConfig {
+    /// Create new pair configuration with default thresholds
+    pub fn new(strategy_id: &str, symbol_a: &str, symbol_b: &str) -> Self {
+        Self {
+            strategy_id: strategy_id.to_string(),
+            symbol_a: symbol_a.to_string(),
+            symbol_b: symbol_b.to_string(),
+            exchange: "NSE".to_string(),
+            hedge_ratio: 1.0,
+            lookback: 60,
+            entry_z: 2.0,
+            exit_z: 0.5,
+            stop_z: 4.0,
+            updated_at: Utc::now(),
+        }
+    }
+    
+    /// Check whether a symbol is one of the legs
+    pub fn has_leg(&self, symbol: &str) -> bool {
+        self.symbol_a == symbol || self.symbol_b == symbol
+    }
+}
+
+/// Direction of a pair position
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PairSide {
+    /// Long A / short B (spread expected to rise)
+    LongSpread,
+    /// Short A / long B (spread expected to fall)
+    ShortSpread,
+}
+
+impl std::fmt::Display for PairSide {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PairSide::LongSpread => write!(f, "LongSpread"),
+            PairSide::ShortSpread => write!(f, "ShortSpread"),
+        }
+    }
+}
+
+impl std::str::FromStr for PairSide {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "LongSpread" => Ok(PairSide::LongSpread),
+            "ShortSpread" => Ok(PairSide::ShortSpread),
+            _ => Err(format!("Invalid PairSide: {}", s)),
+        }
+    }
+}
+
+impl PairSide {
+    /// Trade types for legs A and B when opening this side
+    pub fn entry_trade_types(&self) -> (TradeType, TradeType) {
+        match self {
+            PairSide::LongSpread => (TradeType::Buy, TradeType::Sell),
+            PairSide::ShortSpread => (TradeType::Sell, TradeType::Buy),
+        }
+    }
+    
+    /// Trade types for legs A and B when closing this side
+    pub fn exit_trade_types(&self) -> (TradeType, TradeType) {
+        let (a, b) = self.entry_trade_types();
+        (a.opposite(), b.opposite())
+    }
+}
+
+/// Pair trading signal type
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PairSignalType {
+    Enter(PairSide),
+    Exit,
+    StopLoss,
+}
+
+/// Pair trading signal covering both legs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairSignal {
+    pub strategy_id: String,
+    pub signal_type: PairSignalType,
+    pub z_score: f64,
+    pub price_a: Decimal,
+    pub price_b: Decimal,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Open pair position tracked as a single unit
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PairPosition {
+    pub id: String,
+    pub strategy_id: String,
+    pub symbol_a: String,
+    pub symbol_b: String,
+    pub exchange: String,
+    pub side: PairSide,
+    pub quantity_a: i32,
+    pub quantity_b: i32,
+    pub entry_price_a: Decimal,
+    pub entry_price_b: Decimal,
+    pub entry_z: f64,
+    pub opened_at: DateTime<Utc>,
+}
+
+impl PairPosition {
+    /// Combined P&L of both legs at the given prices
+    pub fn combined_pnl(&self, price_a: Decimal, price_b: Decimal) -> Decimal {
+        let pnl_a = (price_a - self.entry_price_a) * Decimal::from(self.quantity_a);
+        let pnl_b = (price_b - self.entry_price_b) * Decimal::from(self.quantity_b);
+        
+        match self.side {
+            PairSide::LongSpread => pnl_a - pnl_b,
+            PairSide::ShortSpread => pnl_b - pnl_a,
+        }
+    }
+}
+
+/// One price level of the order book
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DepthLevel {
+    pub price: Decimal,
+    pub quantity: u64,
+    pub orders: u32,
+}
+
+/// Best five bid and ask levels, best price first
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct MarketDepth {
+    pub bids: Vec<DepthLevel>,
+    pub asks: Vec<DepthLevel>,
+}
+
+impl MarketDepth {
+    /// Best bid, ignoring empty levels
+    pub fn best_bid(&self) -> Option<&DepthLevel> {
+        self.bids.iter().find(|level| level.quantity > 0)
+    }
+    
+    /// Best ask, ignoring empty levels
+    pub fn best_ask(&self) -> Option<&DepthLevel> {
+        self.asks.iter().find(|level| level.quantity > 0)
+    }
+    
+    /// Best ask minus best bid
+    pub fn spread(&self) -> Option<Decimal> {
+        Some(self.best_ask()?.price - self.best_bid()?.price)
+    }
+    
+    /// Spread in basis points of the mid price
+    pub fn spread_bps(&self) -> Option<f64> {
+        let bid = self.best_bid()?.price;
+        let ask = self.best_ask()?.price;
+        let mid = (bid + ask) / Decimal::from(2);
+        if mid <= Decimal::ZERO {
+            return None;
+        }
+        ((ask - bid) / mid * Decimal::from(10_000)).to_f64()
+    }
+    
+    /// Total quantity resting on the bid side
+    pub fn total_bid_quantity(&self) -> u64 {
+        self.bids.iter().map(|level| level.quantity).sum()
+    }
+    
+    /// Total quantity resting on the ask side
+    pub fn total_ask_quantity(&self) -> u64 {
+        self.asks.iter().map(|level| level.quantity).sum()
+    }
+    
+    /// Quantity a market order on this side can take: asks for a buy, bids for a sell
+    pub fn available_quantity(&self, trade_type: TradeType) -> u64 {
+        match trade_type {
+            TradeType::Buy => self.total_ask_quantity(),
+            TradeType::Sell => self.total_bid_quantity(),
+        }
+    }
+    
+    /// Bid share of the quantity shown, from -1.0 (all asks) to 1.0 (all bids)
+    pub fn imbalance(&self) -> Option<f64> {
+        let bids = self.total_bid_quantity() as f64;
+        let asks = self.total_ask_quantity() as f64;
+        if bids + asks == 0.0 {
+            return None;
+        }
+        Some((bids - asks) / (bids + asks))
+    }
+}
+
 /// Market data model
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketData {
@@ -337,6 +872,9 @@ pub struct MarketData {
     pub change_value: Option<Decimal>,
     pub change_percent: Option<Decimal>,
     pub timestamp: DateTime<Utc>,
+    /// Order book levels, present for instruments subscribed in full mode
+    #[serde(default)]
+    pub depth: Option<MarketDepth>,
 }
 
 impl MarketData {
@@ -363,6 +901,7 @@ impl MarketData {
             change_value: None,
             change_percent: None,
             timestamp: Utc::now(),
+            depth: None,
         }
     }
     
@@ -381,6 +920,12 @@ impl MarketData {
         
         self
     }
+    
+    /// Update with order book depth
+    pub fn with_depth(mut self, depth: MarketDepth) -> Self {
+        self.depth = Some(depth);
+        self
+    }
 }
 
 /// Order request model for internal use
@@ -394,6 +939,9 @@ pub struct OrderRequest {
     pub order_type: OrderType,
     pub strategy_id: String,
     pub user_id: String,
+    /// Reason recorded on the trade when the order closes a position
+    #[serde(default)]
+    pub exit_reason: Option<String>,
 }
 
 /// Order type enumeration
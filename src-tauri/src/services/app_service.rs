@@ -1,12 +1,26 @@
-use crate::db::DatabaseConfig;
+use crate::db::{DatabaseConfig, StorageBackend, TickStore};
 use crate::error::{HedgeXError, Result};
-use crate::services::{DatabaseService, EnhancedDatabaseService, DataPersistenceService, DataPersistenceConfig, AuthService, WebSocketManager};
+use crate::services::{DatabaseService, EnhancedDatabaseService, DataPersistenceService, PartitionedTickStore, DataPersistenceConfig, BackupSchedule, AuthService, WebSocketManager, RestoreProgress, RestoreStage};
+use crate::trading::TradingEngine;
 use crate::utils::{Logger, CryptoService};
+use chrono::Utc;
+use std::collections::HashMap;
 use std::path::Path;
 use std::sync::Arc;
-use tokio::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::{Mutex, RwLock};
 use tracing::{info, debug, error};
 
+/// How often the backup scheduler checks whether a backup is due
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// How often integrity checks, WAL checkpoints and ANALYZE run
+const DB_MAINTENANCE_INTERVAL: Duration = Duration::from_secs(6 * 3600);
+
+/// How long to wait after startup before the first cleanup and maintenance runs
+const ARCHIVE_STARTUP_DELAY: Duration = Duration::from_secs(300);
+
 /// Main application service that coordinates all core services
 pub struct AppService {
     database_service: Arc<DatabaseService>,
@@ -17,6 +31,15 @@ pub struct AppService {
     logger: Arc<Mutex<Logger>>,
     crypto_service: Arc<CryptoService>,
     app_data_dir: std::path::PathBuf,
+    /// Last and next automatic backup, kept by the backup scheduler
+    backup_schedule: Arc<RwLock<BackupSchedule>>,
+    /// Set while a backup is being restored, pausing automatic backups
+    restore_in_progress: Arc<AtomicBool>,
+    /// Trading engines by user, kept off while a backup is restored and reloaded after it
+    trading_engines: Arc<RwLock<HashMap<String, Arc<TradingEngine>>>>,
+    /// Database recorded ticks are stored in, chosen through `HEDGEX_DATABASE_URL`
+    storage_backend: StorageBackend,
+    tick_store: Arc<dyn TickStore>,
 }
 
 impl AppService {
@@ -70,6 +93,9 @@ impl AppService {
             ).await?
         );
         
+        let storage_backend = StorageBackend::from_env()?;
+        let tick_store = Self::connect_tick_store(&storage_backend, &enhanced_database_service, &data_persistence_service).await?;
+        
         let service = Self {
             database_service,
             enhanced_database_service: Arc::clone(&enhanced_database_service),
@@ -79,6 +105,11 @@ impl AppService {
             logger: legacy_logger.clone(),
             crypto_service,
             app_data_dir: app_data_dir.to_path_buf(),
+            backup_schedule: Arc::new(RwLock::new(BackupSchedule::default())),
+            restore_in_progress: Arc::new(AtomicBool::new(false)),
+            trading_engines: Arc::new(RwLock::new(HashMap::new())),
+            storage_backend,
+            tick_store,
         };
         
         // Log successful initialization
@@ -142,6 +173,9 @@ impl AppService {
             ).await?
         );
         
+        let storage_backend = StorageBackend::from_env()?;
+        let tick_store = Self::connect_tick_store(&storage_backend, &enhanced_database_service, &data_persistence_service).await?;
+        
         let service = Self {
             database_service,
             enhanced_database_service: Arc::clone(&enhanced_database_service),
@@ -151,6 +185,11 @@ impl AppService {
             logger: legacy_logger2,
             crypto_service,
             app_data_dir: app_data_dir.to_path_buf(),
+            backup_schedule: Arc::new(RwLock::new(BackupSchedule::default())),
+            restore_in_progress: Arc::new(AtomicBool::new(false)),
+            trading_engines: Arc::new(RwLock::new(HashMap::new())),
+            storage_backend,
+            tick_store,
         };
         
         // Log successful initialization
@@ -163,6 +202,29 @@ impl AppService {
         Ok(service)
     }
     
+    /// Tick store for the configured storage backend, the app database and its archives for SQLite
+    async fn connect_tick_store(
+        backend: &StorageBackend,
+        enhanced_database_service: &Arc<EnhancedDatabaseService>,
+        data_persistence_service: &Arc<DataPersistenceService>,
+    ) -> Result<Arc<dyn TickStore>> {
+        match backend {
+            StorageBackend::Sqlite => Ok(Arc::new(PartitionedTickStore::new(
+                enhanced_database_service.get_database().get_pool().clone(),
+                data_persistence_service.get_archive().clone(),
+            ))),
+            #[cfg(feature = "postgres")]
+            StorageBackend::Postgres { url } => {
+                info!("Storing ticks in PostgreSQL");
+                Ok(Arc::new(crate::db::storage::connect_postgres(url, 10).await?))
+            }
+            #[cfg(not(feature = "postgres"))]
+            StorageBackend::Postgres { .. } => Err(HedgeXError::ConfigError(
+                "HEDGEX_DATABASE_URL points at PostgreSQL, but this build has no PostgreSQL support".to_string()
+            )),
+        }
+    }
+    
     /// Get the database service
     pub fn get_database_service(&self) -> Arc<DatabaseService> {
         Arc::clone(&self.database_service)
@@ -188,6 +250,11 @@ impl AppService {
         Arc::clone(&self.websocket_manager)
     }
     
+    /// Get the trading engines by user
+    pub fn get_trading_engines(&self) -> Arc<RwLock<HashMap<String, Arc<TradingEngine>>>> {
+        Arc::clone(&self.trading_engines)
+    }
+    
     /// Get the logger
     pub fn get_logger(&self) -> Arc<Mutex<Logger>> {
         Arc::clone(&self.logger)
@@ -198,11 +265,194 @@ impl AppService {
         Arc::clone(&self.crypto_service)
     }
     
+    /// Get the configured storage backend
+    pub fn get_storage_backend(&self) -> &StorageBackend {
+        &self.storage_backend
+    }
+    
+    /// Get the store recorded ticks are written to and replayed from
+    pub fn get_tick_store(&self) -> Arc<dyn TickStore> {
+        Arc::clone(&self.tick_store)
+    }
+    
     /// Get the application data directory
     pub fn get_app_data_dir(&self) -> &Path {
         &self.app_data_dir
     }
     
+    /// Last and next automatic backup
+    pub async fn get_backup_schedule(&self) -> BackupSchedule {
+        self.backup_schedule.read().await.clone()
+    }
+    
+    /// Take automatic backups at the configured interval in the background, keeping only the newest backups
+    pub fn start_backup_scheduler(&self) {
+        let persistence = Arc::clone(&self.data_persistence_service);
+        let schedule = Arc::clone(&self.backup_schedule);
+        let restore_in_progress = Arc::clone(&self.restore_in_progress);
+        
+        tokio::spawn(async move {
+            // Carry on from the last automatic backup of a previous run
+            let last_backup_at = match persistence.last_automatic_backup_at().await {
+                Ok(last) => last,
+                Err(e) => {
+                    error!("Failed to find the last automatic backup: {}", e);
+                    None
+                }
+            };
+            *schedule.write().await = BackupSchedule::new(&persistence.get_config(), last_backup_at, Utc::now());
+            
+            let mut interval = tokio::time::interval(BACKUP_CHECK_INTERVAL);
+            loop {
+                interval.tick().await;
+                
+                let now = Utc::now();
+                if restore_in_progress.load(Ordering::SeqCst) || !schedule.read().await.is_due(now) {
+                    continue;
+                }
+                
+                match persistence.create_automatic_backup().await {
+                    Ok(metadata) => {
+                        info!("Automatic backup {} created", metadata.id);
+                        *schedule.write().await = BackupSchedule::new(&persistence.get_config(), Some(metadata.created_at), now);
+                        
+                        let upload_automatic = match persistence.load_cloud_backup_settings().await {
+                            Ok(settings) => settings.is_some_and(|settings| settings.upload_automatic),
+                            Err(e) => {
+                                error!("Failed to load cloud backup settings: {}", e);
+                                false
+                            }
+                        };
+                        if upload_automatic {
+                            if let Err(e) = persistence.upload_backup(&metadata.id).await {
+                                error!("Failed to upload automatic backup {}: {}", metadata.id, e);
+                            }
+                        }
+                        
+                        if let Err(e) = persistence.cleanup_old_backups().await {
+                            error!("Failed to clean up old backups: {}", e);
+                        }
+                    }
+                    Err(e) => {
+                        error!("Automatic backup failed: {}", e);
+                        schedule.write().await.record_failure(e.to_string(), now);
+                    }
+                }
+            }
+        });
+    }
+    
+    /// Run the data cleanup in the background at the configured interval
+    ///
+    /// The configuration is read before every run, so changes apply from the next one.
+    pub fn start_cleanup_scheduler(&self) {
+        let persistence = Arc::clone(&self.data_persistence_service);
+        let restore_in_progress = Arc::clone(&self.restore_in_progress);
+        
+        tokio::spawn(async move {
+            tokio::time::sleep(ARCHIVE_STARTUP_DELAY).await;
+            loop {
+                let config = persistence.get_config();
+                if config.auto_cleanup_enabled && !restore_in_progress.load(Ordering::SeqCst) {
+                    if let Err(e) = persistence.run_cleanup().await {
+                        error!("Scheduled data cleanup failed: {}", e);
+                    }
+                }
+                tokio::time::sleep(Duration::from_secs(config.cleanup_interval_hours.max(1) * 3600)).await;
+            }
+        });
+    }
+    
+    /// Save a new data persistence configuration and reschedule automatic backups to match it
+    pub async fn update_persistence_config(&self, config: DataPersistenceConfig) -> Result<DataPersistenceConfig> {
+        self.data_persistence_service.update_config(config).await?;
+        
+        let config = self.data_persistence_service.get_config();
+        let last_backup_at = self.backup_schedule.read().await.last_backup_at;
+        *self.backup_schedule.write().await = BackupSchedule::new(&config, last_backup_at, Utc::now());
+        Ok(config)
+    }
+    
+    /// Run database maintenance in the background, recording each result in `db_health`
+    pub fn start_db_maintenance_scheduler(&self) {
+        let pool = self.enhanced_database_service.get_database().get_pool().clone();
+        let restore_in_progress = Arc::clone(&self.restore_in_progress);
+        
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval_at(tokio::time::Instant::now() + ARCHIVE_STARTUP_DELAY, DB_MAINTENANCE_INTERVAL);
+            loop {
+                interval.tick().await;
+                if restore_in_progress.load(Ordering::SeqCst) {
+                    continue;
+                }
+                
+                match crate::services::db_health::run_maintenance(&pool).await {
+                    Ok(check) if !check.integrity_ok => error!("Database integrity check failed: {}", check.integrity_message),
+                    Ok(_) => {}
+                    Err(e) => error!("Database maintenance failed: {}", e),
+                }
+            }
+        });
+    }
+    
+    /// Restore a backup while the app keeps running
+    ///
+    /// Refused while any trading engine is trading, and the engines stay locked until it is done
+    /// so none starts. Automatic backups and tick recording are paused while the database contents
+    /// are replaced and migrations bring an older backup up to date. The engines then reload their
+    /// strategies, trades and positions; callers reload the services they own before reporting
+    /// completion.
+    pub async fn restore_backup(
+        &self,
+        backup_id: &str,
+        passphrase: Option<&str>,
+        progress: &(dyn Fn(RestoreProgress) + Send + Sync),
+    ) -> Result<()> {
+        if self.restore_in_progress.swap(true, Ordering::SeqCst) {
+            return Err(HedgeXError::ConcurrencyError("A backup restore is already running".to_string()));
+        }
+        
+        let trading_engines = self.trading_engines.write().await;
+        for (user_id, engine) in trading_engines.iter() {
+            if engine.is_trading_active().await {
+                self.restore_in_progress.store(false, Ordering::SeqCst);
+                return Err(HedgeXError::ValidationError(format!(
+                    "Stop trading for {} before restoring a backup", user_id
+                )));
+            }
+        }
+        
+        progress(RestoreProgress::new(backup_id, RestoreStage::Quiescing, 0, "Pausing background writers"));
+        let tick_recorder = self.websocket_manager.get_tick_recorder().await;
+        if tick_recorder.is_some() {
+            self.websocket_manager.set_tick_recorder(None).await;
+        }
+        
+        let result = async {
+            self.data_persistence_service.restore_from_backup_with_progress(backup_id, passphrase, progress).await?;
+            
+            progress(RestoreProgress::new(backup_id, RestoreStage::Migrating, 90, "Updating the database schema"));
+            self.enhanced_database_service.run_migrations().await?;
+            
+            progress(RestoreProgress::new(backup_id, RestoreStage::Reinitializing, 92, "Reloading trading engines"));
+            for engine in trading_engines.values() {
+                engine.reload().await?;
+            }
+            Ok(())
+        }.await;
+        
+        if let Some(recorder) = tick_recorder {
+            self.websocket_manager.set_tick_recorder(Some(recorder)).await;
+        }
+        self.restore_in_progress.store(false, Ordering::SeqCst);
+        
+        match &result {
+            Ok(()) => info!("Backup {} restored", backup_id),
+            Err(e) => error!("Failed to restore backup {}: {}", backup_id, e),
+        }
+        result
+    }
+    
     /// Perform comprehensive health check of all services
     pub async fn health_check(&self) -> Result<HealthStatus> {
         debug!("Performing comprehensive health check");
@@ -1,28 +1,105 @@
 use std::sync::Arc;
+use std::str::FromStr;
 use sqlx::{Pool, Sqlite, Row};
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
 use std::collections::HashMap;
-use tracing::{info, warn, error, debug};
+use tokio::task::JoinSet;
+use sha2::{Digest, Sha256};
+use tracing::{info, warn, error};
 
 use crate::models::backtesting::{
     BacktestParams, BacktestResult, BacktestTrade, BacktestSummary, BacktestComparison,
     OHLCV, EquityPoint, HistoricalDataParams, HistoricalDataFetchParams,
-    CsvImportConfig, CsvValidationResult, Timeframe, DataSource
+    CsvImportConfig, CsvValidationResult, Timeframe, DataSource, DataCoverage,
+    ParameterSuggestion, ParameterSweepReport, EnableValidationConfig, StrategyValidationSummary,
+    BenchmarkComparison, CostModel, OptimizationGrid, OptimizationHeatmap, OptimizationReport, OptimizationRequest,
+    WalkForwardRequest, WalkForwardReport, WalkForwardWindow, MonteCarloConfig, MonteCarloResult, MarketTick,
+    BacktestTradePage, TradeDrillDown, BacktestConfig, BacktestReproduction, hex_digest, HistoricalDataset, CsvPreview,
+    DriftReportRequest, DriftReport, DriftFill,
 };
-use crate::models::trading::{StrategyParams, TradeType, SignalType, TradingSignal};
+use crate::models::trading::{StrategyParams, TradeType, SignalType, TradingSignal, TrendFilterConfig, SQUARE_OFF_REASON, TIME_EXIT_REASON};
+use crate::db::bulk::insert_rows;
 use crate::error::{HedgeXError, Result};
 use crate::utils::csv_parser::CsvParser;
 use crate::api::kite_historical::KiteHistoricalClient;
-use crate::trading::strategy_manager::StrategyManager;
+use crate::services::monte_carlo::{self, TradeOutcome};
+use crate::trading::bars::{self, BarSeries};
+use crate::trading::market_regime::MarketRegimeTracker;
+use crate::trading::session;
+use crate::trading::strategies::{evaluate_strategy, required_bars};
+use crate::trading::strategy_manager::{SignalGate, LIVE_BAR_SECONDS};
+
+/// Index used as the buy-and-hold benchmark for backtests and live equity
+pub const BENCHMARK_SYMBOL: &str = "NIFTY 50";
+
+/// Stop-loss percentages tried by the parameter sweep
+const SWEEP_STOP_LOSS: [f64; 5] = [0.5, 1.0, 1.5, 2.0, 3.0];
+
+/// Take-profit multiples of the stop loss tried by the parameter sweep
+const SWEEP_REWARD_RATIO: [f64; 3] = [1.5, 2.0, 3.0];
+
+/// Candle volume percentiles used as volume threshold candidates
+const SWEEP_VOLUME_PERCENTILES: [f64; 3] = [0.25, 0.5, 0.75];
+
+/// Minimum trades for a combination to be suggested
+const MIN_SUGGESTION_TRADES: i32 = 3;
+
+/// Number of suggestions returned by the parameter sweep
+const MAX_SUGGESTIONS: usize = 5;
+
+/// Capital used for sweep simulations
+const SWEEP_CAPITAL: i64 = 100_000;
+
+/// Upper bound on combinations in one grid search
+const MAX_OPTIMIZATION_COMBINATIONS: usize = 2000;
+
+/// Upper bound on backtests run at once
+const MAX_CONCURRENCY: usize = 16;
+
+/// Environment variable limiting how many backtests sweeps and validation run at once, the CPU count when unset
+pub const BACKTEST_CONCURRENCY_VAR: &str = "HEDGEX_BACKTEST_CONCURRENCY";
+
+/// Upper bound on windows in one walk-forward analysis
+const MAX_WALK_FORWARD_WINDOWS: usize = 50;
+
+/// Largest page of trades returned by the drill-down
+const MAX_TRADE_PAGE_SIZE: usize = 100;
+
+/// Most candles shown either side of a trade in the drill-down
+const MAX_CONTEXT_CANDLES: usize = 200;
+
+/// Page size of the drill-down when none is given
+pub const DEFAULT_TRADE_PAGE_SIZE: usize = 20;
+
+/// Candles shown either side of a trade in the drill-down when no count is given
+pub const DEFAULT_CONTEXT_CANDLES: usize = 20;
+
+/// Longest backtest label, in characters
+const MAX_LABEL_LENGTH: usize = 100;
+
+/// Longest backtest notes, in characters
+const MAX_NOTES_LENGTH: usize = 5000;
+
+/// Most rows returned by a CSV import preview
+const MAX_PREVIEW_ROWS: usize = 100;
+
+/// Largest difference between stored and re-run values still treated as equal
+const REPRODUCTION_TOLERANCE: f64 = 1e-6;
+
+/// Capital per symbol used for pre-enable validation runs
+const VALIDATION_CAPITAL: i64 = 100_000;
 
 /// Backtesting engine for strategy simulation
 pub struct BacktestEngine {
     db: Arc<Pool<Sqlite>>,
-    strategy_manager: Arc<StrategyManager>,
     kite_client: Option<KiteHistoricalClient>,
 }
 
+/// Strategy, parameters and candles for one independent simulation
+type SimulationRun = (StrategyParams, BacktestParams, Arc<Vec<OHLCV>>);
+
 /// Backtesting context for strategy execution
 #[derive(Debug, Clone)]
 struct BacktestContext {
@@ -46,14 +123,57 @@ struct BacktestPosition {
     entry_time: DateTime<Utc>,
     current_price: Decimal,
     unrealized_pnl: Decimal,
+    /// Charges paid when the position was opened
+    entry_charges: Decimal,
+}
+
+/// Index candles replayed into a regime tracker alongside a backtest
+struct TrendFilterReplay {
+    filter: TrendFilterConfig,
+    tracker: MarketRegimeTracker,
+    candles: Vec<OHLCV>,
+    next: usize,
+}
+
+impl TrendFilterReplay {
+    /// Feed the index candles up to the given time, returning the filter and tracker to gate signals with
+    async fn advance(&mut self, time: DateTime<Utc>) -> (&TrendFilterConfig, &MarketRegimeTracker) {
+        while let Some(candle) = self.candles.get(self.next).filter(|candle| candle.timestamp <= time) {
+            self.tracker.update(&self.filter.index_symbol, candle.close, candle.timestamp).await;
+            self.next += 1;
+        }
+        
+        (&self.filter, &self.tracker)
+    }
+}
+
+impl BacktestPosition {
+    /// Close the position at a quoted price, returning the trade and the cash released
+    fn close(&self, exit_time: DateTime<Utc>, price: Decimal, exit_reason: &str, cost_model: &CostModel) -> (BacktestTrade, Decimal) {
+        let exit_side = self.trade_type.opposite();
+        let exit_price = cost_model.fill_price(exit_side, price);
+        let exit_charges = cost_model.charges(exit_side, exit_price, self.quantity);
+        
+        let mut trade = BacktestTrade::new(
+            "",  // Will be set when storing
+            &self.symbol,
+            self.trade_type,
+            self.entry_time,
+            self.entry_price,
+            self.quantity,
+        );
+        trade.close(exit_time, exit_price, exit_reason);
+        trade.add_charges(self.entry_charges + exit_charges);
+        
+        (trade, exit_price * Decimal::from(self.quantity) - exit_charges)
+    }
 }
 
 impl BacktestEngine {
     /// Create new backtest engine
-    pub fn new(db: Arc<Pool<Sqlite>>, strategy_manager: Arc<StrategyManager>) -> Self {
+    pub fn new(db: Arc<Pool<Sqlite>>) -> Self {
         Self {
             db,
-            strategy_manager,
             kite_client: None,
         }
     }
@@ -69,25 +189,180 @@ impl BacktestEngine {
         
         // Get strategy parameters
         let strategy = self.get_strategy_params(&params.strategy_id).await?;
+        let trend_filter = self.get_trend_filter(&strategy.id).await?;
+        
+        let (mut result, data_checksum) = self.simulate_run(&strategy, trend_filter.clone(), &params).await?;
+        result.benchmark = self.compare_with_benchmark(&params, &result).await;
+        result.config = Some(BacktestConfig::new(&strategy, trend_filter, &params, data_checksum));
+        
+        // Store backtest result in database
+        self.store_backtest_result(&result).await?;
+        
+        info!("Backtest completed: {} trades, final P&L: {}", result.total_trades, result.final_pnl);
+        Ok(result)
+    }
+    
+    /// Re-run a stored backtest from its saved configuration and compare the outcome
+    ///
+    /// The stored strategy snapshot is used rather than the strategy's current
+    /// parameters. The re-run is only for verification and is not stored.
+    pub async fn rerun_backtest(&self, backtest_id: &str) -> Result<BacktestReproduction> {
+        let original = self.get_backtest_detail(backtest_id).await?;
+        let config = match original.config.clone() {
+            Some(config) => config,
+            None => return Err(HedgeXError::ValidationError(format!(
+                "Backtest {} was stored without its configuration and cannot be re-run", backtest_id
+            ))),
+        };
+        
+        let params = config.params(&original.params.user_id);
+        let (rerun, data_checksum) = self.simulate_run(&config.strategy, config.trend_filter.clone(), &params).await?;
+        let reproduction = BacktestReproduction::new(&original, &rerun, &config, data_checksum, REPRODUCTION_TOLERANCE);
+        
+        info!("Re-ran backtest {}: identical {}, {} differences",
+              backtest_id, reproduction.identical, reproduction.differences.len());
+        Ok(reproduction)
+    }
+    
+    /// Compare an enabled strategy's live fills with a backtest over the same period
+    ///
+    /// Live fills carry no charges, so the backtest is simulated without costs.
+    pub async fn live_drift_report(&self, user_id: &str, request: &DriftReportRequest) -> Result<DriftReport> {
+        if request.start_date >= request.end_date {
+            return Err(HedgeXError::ValidationError("Start date must be before end date".to_string()));
+        }
+        
+        if request.initial_capital <= Decimal::ZERO {
+            return Err(HedgeXError::ValidationError("Initial capital must be positive".to_string()));
+        }
         
-        // Load historical data
-        let historical_data = self.load_historical_data(&params).await?;
+        if request.match_window() <= 0 {
+            return Err(HedgeXError::ValidationError("Match window must be positive".to_string()));
+        }
+        
+        let strategy = self.get_strategy_params(&request.strategy_id).await?;
+        if strategy.user_id != user_id {
+            return Err(HedgeXError::NotFoundError(format!("Strategy not found: {}", request.strategy_id)));
+        }
         
-        if historical_data.is_empty() {
-            return Err(HedgeXError::ConfigError("No historical data available for backtesting".to_string()));
+        if !strategy.enabled {
+            return Err(HedgeXError::ValidationError(format!(
+                "Strategy {} is not enabled and has no live fills to compare", strategy.name
+            )));
         }
         
-        info!("Loaded {} historical data points for backtesting", historical_data.len());
+        let mut params = BacktestParams::new(
+            user_id, &request.strategy_id, &request.symbol, &request.exchange,
+            request.start_date, request.end_date, request.timeframe,
+            request.initial_capital, DataSource::Database,
+        );
+        params.cost_model = CostModel::disabled();
+        
+        let trend_filter = self.get_trend_filter(&strategy.id).await?;
+        let (backtest, _) = self.simulate_run(&strategy, trend_filter, &params).await?;
+        
+        let rows = sqlx::query(
+            "SELECT trade_type, quantity, price, executed_at FROM trades
+             WHERE user_id = ? AND strategy_id = ? AND symbol = ? AND exchange = ?
+               AND status = 'Executed' AND executed_at >= ? AND executed_at <= ?
+             ORDER BY executed_at"
+        )
+        .bind(user_id)
+        .bind(&request.strategy_id)
+        .bind(&request.symbol)
+        .bind(&request.exchange)
+        .bind(request.start_date)
+        .bind(request.end_date)
+        .fetch_all(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+        
+        let live: Vec<DriftFill> = rows.iter()
+            .map(|row| DriftFill {
+                trade_type: TradeType::from_str(row.get("trade_type")).unwrap_or(TradeType::Buy),
+                time: row.get("executed_at"),
+                price: to_decimal(row.get("price")),
+                quantity: row.get("quantity"),
+            })
+            .collect();
+        
+        let report = DriftReport::new(request, &DriftFill::from_backtest(&backtest.trades), &live);
+        
+        info!("Drift report for strategy {} on {}: {} matched, {} missed, {} unexpected, shortfall {}",
+              request.strategy_id, request.symbol, report.matched.len(), report.missed_signals.len(),
+              report.unexpected_fills.len(), report.implementation_shortfall);
+        Ok(report)
+    }
+    
+    /// Load a run's data and simulate it, returning the result and a checksum of the data used
+    async fn simulate_run(
+        &self,
+        strategy: &StrategyParams,
+        trend_filter: Option<TrendFilterConfig>,
+        params: &BacktestParams,
+    ) -> Result<(BacktestResult, String)> {
+        if params.timeframe == Timeframe::Tick {
+            // Ticks are only ever replayed from the tick history
+            if !matches!(params.data_source, DataSource::Database) {
+                return Err(HedgeXError::ValidationError("Tick backtests require the database data source".to_string()));
+            }
+            
+            let ticks = self.load_stored_ticks(&params.symbol, &params.exchange, params.start_date, params.end_date).await?;
+            if ticks.is_empty() {
+                return Err(HedgeXError::ConfigError("No stored ticks available for backtesting".to_string()));
+            }
+            
+            info!("Loaded {} ticks for backtesting", ticks.len());
+            let replay = self.replay_trend_filter(trend_filter, params).await;
+            let checksum = data_checksum(&[], &ticks, replay.as_ref().map(|r| r.candles.as_slice()).unwrap_or_default());
+            Ok((self.simulate_ticks_with(strategy, replay, params, &ticks).await?, checksum))
+        } else {
+            // Load historical data
+            let historical_data = self.load_historical_data(params).await?;
+            
+            if historical_data.is_empty() {
+                return Err(HedgeXError::ConfigError("No historical data available for backtesting".to_string()));
+            }
+            
+            info!("Loaded {} historical data points for backtesting", historical_data.len());
+            let replay = self.replay_trend_filter(trend_filter, params).await;
+            let checksum = data_checksum(&historical_data, &[], replay.as_ref().map(|r| r.candles.as_slice()).unwrap_or_default());
+            Ok((self.simulate_with(strategy, replay, params, historical_data).await?, checksum))
+        }
+    }
+    
+    /// Simulate a strategy over historical candles with its stored trend filter
+    async fn simulate(&self, strategy: &StrategyParams, params: &BacktestParams, historical_data: Vec<OHLCV>) -> Result<BacktestResult> {
+        let trend_filter = self.get_trend_filter(&strategy.id).await?;
+        let replay = self.replay_trend_filter(trend_filter, params).await;
+        self.simulate_with(strategy, replay, params, historical_data).await
+    }
+    
+    /// Simulate a strategy over historical candles, filtered by a trend filter replay, without storing the result
+    ///
+    /// Intraday timeframes only trade inside NSE sessions: candles outside them
+    /// are dropped and positions are squared off before the close.
+    async fn simulate_with(
+        &self,
+        strategy: &StrategyParams,
+        mut trend_filter: Option<TrendFilterReplay>,
+        params: &BacktestParams,
+        mut historical_data: Vec<OHLCV>,
+    ) -> Result<BacktestResult> {
+        let intraday = params.timeframe.is_intraday();
+        if intraday {
+            historical_data.retain(|candle| session::is_in_session(candle.timestamp));
+        }
         
         // Initialize backtest context
         let mut context = BacktestContext {
             current_time: params.start_date,
-            current_price: historical_data[0].close,
-            current_volume: historical_data[0].volume,
+            current_price: historical_data.first().map(|c| c.close).unwrap_or_default(),
+            current_volume: historical_data.first().map(|c| c.volume).unwrap_or_default(),
             portfolio_value: params.initial_capital,
             cash_balance: params.initial_capital,
             open_positions: HashMap::new(),
-            historical_data: historical_data.clone(),
+            historical_data,
             data_index: 0,
         };
         
@@ -100,7 +375,15 @@ impl BacktestEngine {
         
         // Run simulation through historical data
         while context.data_index < context.historical_data.len() {
-            let current_candle = &context.historical_data[context.data_index];
+            let current_candle = context.historical_data[context.data_index].clone();
+            
+            // Nothing is carried over the overnight gap, even if the session's last candles are missing
+            if intraday && context.data_index > 0
+                && session::session_date(context.current_time) != session::session_date(current_candle.timestamp)
+            {
+                trades.extend(self.close_remaining_positions(&mut context, SQUARE_OFF_REASON, &params.cost_model));
+            }
+            let square_off = intraday && session::is_square_off_time(current_candle.timestamp);
             
             // Update context with current candle data
             context.current_time = current_candle.timestamp;
@@ -108,21 +391,28 @@ impl BacktestEngine {
             context.current_volume = current_candle.volume;
             
             // Update open positions with current price
-            self.update_positions(&mut context, current_candle);
+            self.update_positions(&mut context, &current_candle);
             
-            // Generate trading signals using strategy
-            let signals = self.generate_signals(&strategy, &context, current_candle, &params).await?;
+            // Generate trading signals using strategy; none once the session is being squared off
+            let signals = if square_off {
+                Vec::new()
+            } else {
+                self.generate_signals(strategy, &context, &current_candle, params, trend_filter.as_mut()).await?
+            };
             
             // Execute trades based on signals
             for signal in signals {
-                if let Some(trade) = self.execute_signal(&mut context, &signal, current_candle, &strategy) {
+                if let Some(trade) = self.execute_signal(&mut context, &signal, strategy, &params.cost_model) {
                     trades.push(trade);
                 }
             }
             
             // Check for position exits (stop loss, take profit, etc.)
-            let exit_trades = self.check_position_exits(&mut context, &strategy, current_candle);
+            let exit_trades = self.check_position_exits(&mut context, strategy, &current_candle, &params.cost_model);
             trades.extend(exit_trades);
+            if square_off {
+                trades.extend(self.close_remaining_positions(&mut context, SQUARE_OFF_REASON, &params.cost_model));
+            }
             
             // Update portfolio value
             context.portfolio_value = self.calculate_portfolio_value(&context);
@@ -134,7 +424,7 @@ impl BacktestEngine {
         }
         
         // Close any remaining open positions
-        let final_trades = self.close_remaining_positions(&mut context);
+        let final_trades = self.close_remaining_positions(&mut context, "End of backtest", &params.cost_model);
         trades.extend(final_trades);
         
         // Update result with trades and equity curve
@@ -144,10 +434,126 @@ impl BacktestEngine {
         // Calculate performance metrics
         result.calculate_metrics();
         
-        // Store backtest result in database
-        self.store_backtest_result(&result).await?;
+        Ok(result)
+    }
+    
+    /// Replay ticks through the live bar and signal pipeline without storing the result
+    ///
+    /// Entries fill at the ask and exits at the bid (the reverse for shorts),
+    /// with the cost model's slippage and charges applied on top of the quote.
+    /// Ticks outside NSE sessions are ignored and positions are squared off
+    /// before the close, as in candle backtests.
+    async fn simulate_ticks_with(
+        &self,
+        strategy: &StrategyParams,
+        mut trend_filter: Option<TrendFilterReplay>,
+        params: &BacktestParams,
+        ticks: &[MarketTick],
+    ) -> Result<BacktestResult> {
+        let mut context = BacktestContext {
+            current_time: params.start_date,
+            current_price: ticks.first().map(|t| t.ltp).unwrap_or_default(),
+            current_volume: ticks.first().map(|t| t.volume).unwrap_or_default(),
+            portfolio_value: params.initial_capital,
+            cash_balance: params.initial_capital,
+            open_positions: HashMap::new(),
+            historical_data: Vec::new(),
+            data_index: 0,
+        };
+        
+        let mut series = BarSeries::new(LIVE_BAR_SECONDS);
+        let mut result = BacktestResult::new(params.clone());
+        let mut trades = Vec::new();
+        let mut equity_curve = vec![EquityPoint::new(context.current_time, context.portfolio_value)];
+        let mut equity_bar = None;
+        
+        for tick in ticks.iter().filter(|tick| session::is_in_session(tick.timestamp)) {
+            if session::session_date(context.current_time) != session::session_date(tick.timestamp) {
+                trades.extend(self.close_remaining_positions(&mut context, SQUARE_OFF_REASON, &params.cost_model));
+            }
+            let square_off = session::is_square_off_time(tick.timestamp);
+            
+            series.update(tick.ltp, tick.volume, tick.timestamp);
+            
+            // Open positions are marked and exited at the side of the book they close against
+            let exit_side = context.open_positions.values().next()
+                .map(|position| position.trade_type.opposite())
+                .unwrap_or(TradeType::Sell);
+            let exit_price = tick.quote(exit_side);
+            let quote = OHLCV::new(tick.timestamp, exit_price, exit_price, exit_price, exit_price, tick.volume);
+            
+            context.current_time = tick.timestamp;
+            context.current_price = exit_price;
+            context.current_volume = tick.volume;
+            self.update_positions(&mut context, &quote);
+            
+            let bars = series.bars();
+            let window_start = bars.len().saturating_sub(required_bars(strategy));
+            let evaluation = evaluate_strategy(strategy, &bars[window_start..]);
+            
+            // Long only: a buy opens the position and a sell closes it
+            let side = match evaluation.signal_type {
+                _ if square_off => None,
+                SignalType::Buy if context.open_positions.is_empty() => Some(TradeType::Buy),
+                SignalType::Sell if !context.open_positions.is_empty() => Some(TradeType::Sell),
+                _ => None,
+            };
+            
+            if let Some(side) = side {
+                // Live trading compares the threshold with the day's cumulative volume, as ticks carry
+                let gate = SignalGate {
+                    volume: tick.volume,
+                    depth: None,
+                    trend_filter: match trend_filter.as_mut() {
+                        Some(replay) => Some(replay.advance(tick.timestamp).await),
+                        None => None,
+                    },
+                    opens_position: side == TradeType::Buy,
+                };
+                
+                if gate.allows(strategy, evaluation.signal_type).await {
+                    let signal = TradingSignal {
+                        symbol: params.symbol.clone(),
+                        signal_type: evaluation.signal_type,
+                        strength: evaluation.strength,
+                        price: tick.quote(side),
+                        volume: tick.volume,
+                        timestamp: tick.timestamp,
+                        strategy_id: strategy.id.clone(),
+                    };
+                    if let Some(trade) = self.execute_signal(&mut context, &signal, strategy, &params.cost_model) {
+                        trades.push(trade);
+                    }
+                }
+            }
+            
+            let exit_trades = self.check_position_exits(&mut context, strategy, &quote, &params.cost_model);
+            trades.extend(exit_trades);
+            if square_off {
+                trades.extend(self.close_remaining_positions(&mut context, SQUARE_OFF_REASON, &params.cost_model));
+            }
+            
+            context.portfolio_value = self.calculate_portfolio_value(&context);
+            
+            // The curve keeps one point per bar, taken at its last tick
+            let bar_start = bars.last().map(|bar| bar.timestamp);
+            let point = EquityPoint::new(context.current_time, context.portfolio_value);
+            match equity_curve.last_mut() {
+                Some(last) if bar_start == equity_bar => *last = point,
+                _ => {
+                    equity_curve.push(point);
+                    equity_bar = bar_start;
+                }
+            }
+        }
+        
+        let final_trades = self.close_remaining_positions(&mut context, "End of backtest", &params.cost_model);
+        trades.extend(final_trades);
+        
+        result.trades = trades;
+        result.equity_curve = equity_curve;
+        result.calculate_metrics();
         
-        info!("Backtest completed: {} trades, final P&L: {}", result.total_trades, result.final_pnl);
         Ok(result)
     }
     
@@ -163,6 +569,7 @@ impl BacktestEngine {
                     has_header: true,
                     date_format: "%Y-%m-%d %H:%M:%S".to_string(),
                     timezone: "Asia/Kolkata".to_string(),
+                    ..CsvImportConfig::default()
                 };
                 
                 let parser = CsvParser::new(config);
@@ -177,164 +584,803 @@ impl BacktestEngine {
             }
             DataSource::KiteAPI => {
                 info!("Loading historical data from Kite API");
-                let kite_client = self.kite_client.as_ref()
-                    .ok_or_else(|| HedgeXError::ConfigError("Kite client not configured".to_string()))?;
-                
-                let hist_params = HistoricalDataParams {
-                    symbol: params.symbol.clone(),
-                    exchange: params.exchange.clone(),
-                    from_date: params.start_date,
-                    to_date: params.end_date,
-                    timeframe: params.timeframe,
-                };
-                
-                kite_client.fetch_historical_data(&hist_params).await
+                // Only days missing from the cache are requested from Kite, and
+                // none when cached 1-minute candles cover the range to resample
+                let minutes_cached = params.timeframe != Timeframe::Minute1
+                    && self.data_coverage(&params.symbol, &params.exchange, Timeframe::Minute1, params.start_date, params.end_date)
+                        .await?
+                        .gaps
+                        .is_empty();
+                if !minutes_cached {
+                    self.fill_gaps(&params.symbol, &params.exchange, params.timeframe, params.start_date, params.end_date).await?;
+                }
+                self.load_stored_historical_data(&params.symbol, &params.exchange, params.timeframe, params.start_date, params.end_date).await
+            }
+            DataSource::Database => {
+                info!("Loading historical data from database");
+                self.load_stored_historical_data(&params.symbol, &params.exchange, params.timeframe, params.start_date, params.end_date).await
             }
         }
     }
     
-    /// Get strategy parameters from database
-    async fn get_strategy_params(&self, strategy_id: &str) -> Result<StrategyParams> {
-        let row = sqlx::query!(
-            "SELECT * FROM strategy_params WHERE id = ?",
-            strategy_id
+    /// Load the strategy's enabled index trend filter, if any
+    async fn get_trend_filter(&self, strategy_id: &str) -> Result<Option<TrendFilterConfig>> {
+        let row = sqlx::query(
+            "SELECT index_symbol, ma_period, enabled, updated_at FROM strategy_trend_filters WHERE strategy_id = ? AND enabled = 1"
         )
-        .fetch_one(&*self.db)
+        .bind(strategy_id)
+        .fetch_optional(&*self.db)
         .await
-        .map_err(|e| HedgeXError::DatabaseError(e))?;
+        .map_err(HedgeXError::DatabaseError)?;
         
-        Ok(StrategyParams {
-            id: row.id,
-            user_id: row.user_id,
-            name: row.name,
-            description: row.description,
-            enabled: row.enabled,
-            max_trades_per_day: row.max_trades_per_day,
-            risk_percentage: row.risk_percentage,
-            stop_loss_percentage: row.stop_loss_percentage,
-            take_profit_percentage: row.take_profit_percentage,
-            volume_threshold: row.volume_threshold,
+        Ok(row.map(|row| TrendFilterConfig {
+            strategy_id: strategy_id.to_string(),
+            index_symbol: row.get("index_symbol"),
+            ma_period: row.get::<i64, _>("ma_period") as usize,
+            enabled: row.get("enabled"),
+            updated_at: row.get("updated_at"),
+        }))
+    }
+    
+    /// Load the index candles to replay a trend filter alongside a backtest
+    ///
+    /// As in live trading, entries are blocked while the index lacks the
+    /// history to establish a regime, including when no index data is stored.
+    async fn replay_trend_filter(&self, filter: Option<TrendFilterConfig>, params: &BacktestParams) -> Option<TrendFilterReplay> {
+        let filter = filter?;
+        
+        // Ticks are only kept for traded symbols, so the index uses minute candles
+        let timeframe = match params.timeframe {
+            Timeframe::Tick => Timeframe::Minute1,
+            timeframe => timeframe,
+        };
+        
+        // Start early enough for the moving average to be ready when the backtest begins
+        let warm_up = chrono::Duration::minutes(timeframe.duration_minutes() * filter.ma_period as i64 * 2)
+            + chrono::Duration::days(7);
+        let index_params = BacktestParams {
+            symbol: filter.index_symbol.clone(),
+            exchange: "NSE".to_string(),
+            data_source: match params.data_source {
+                DataSource::KiteAPI => DataSource::KiteAPI,
+                _ => DataSource::Database,
+            },
+            timeframe,
+            start_date: params.start_date - warm_up,
+            ..params.clone()
+        };
+        
+        let candles = match self.load_historical_data(&index_params).await {
+            Ok(candles) => candles,
+            Err(e) => {
+                warn!("Could not load {} data for the trend filter: {}", filter.index_symbol, e);
+                Vec::new()
+            }
+        };
+        
+        Some(TrendFilterReplay {
+            filter,
+            tracker: MarketRegimeTracker::new(timeframe.duration_minutes() * 60),
+            candles,
+            next: 0,
         })
     }
     
-    /// Update open positions with current market data
-    fn update_positions(&self, context: &mut BacktestContext, candle: &OHLCV) {
-        for position in context.open_positions.values_mut() {
-            position.current_price = candle.close;
-            
-            // Calculate unrealized P&L
-            let price_diff = match position.trade_type {
-                TradeType::Buy => candle.close - position.entry_price,
-                TradeType::Sell => position.entry_price - candle.close,
-            };
-            position.unrealized_pnl = price_diff * Decimal::from(position.quantity);
+    /// Compare a backtest with buying and holding the benchmark index
+    ///
+    /// The benchmark is optional, so missing index data only logs a warning.
+    async fn compare_with_benchmark(&self, params: &BacktestParams, result: &BacktestResult) -> Option<BenchmarkComparison> {
+        let benchmark_params = BacktestParams {
+            symbol: BENCHMARK_SYMBOL.to_string(),
+            exchange: "NSE".to_string(),
+            // CSV files only hold the traded symbol, so the index comes from stored data
+            data_source: match params.data_source {
+                DataSource::KiteAPI => DataSource::KiteAPI,
+                _ => DataSource::Database,
+            },
+            // Ticks are only kept for traded symbols, so the index uses minute candles
+            timeframe: match params.timeframe {
+                Timeframe::Tick => Timeframe::Minute1,
+                timeframe => timeframe,
+            },
+            ..params.clone()
+        };
+        
+        let benchmark_data = match self.load_historical_data(&benchmark_params).await {
+            Ok(data) => data,
+            Err(e) => {
+                warn!("Could not load {} benchmark data: {}", BENCHMARK_SYMBOL, e);
+                return None;
+            }
+        };
+        
+        let comparison = BenchmarkComparison::calculate(BENCHMARK_SYMBOL, &result.equity_curve, &benchmark_data);
+        if comparison.is_none() {
+            warn!("No {} data between {} and {} to benchmark against", BENCHMARK_SYMBOL, params.start_date, params.end_date);
         }
+        comparison
     }
     
-    /// Generate trading signals using strategy
-    async fn generate_signals(&self, strategy: &StrategyParams, context: &BacktestContext, candle: &OHLCV, params: &BacktestParams) -> Result<Vec<TradingSignal>> {
-        // This is a simplified signal generation - in a real implementation,
-        // you would use the strategy manager to generate signals based on
-        // technical indicators, market conditions, etc.
+    /// Load stored historical candles for a symbol within a date range
+    ///
+    /// Coarser timeframes are built from stored 1-minute candles when those
+    /// cover more of the range, so only the finest data needs downloading.
+    pub async fn load_stored_historical_data(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        timeframe: Timeframe,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> Result<Vec<OHLCV>> {
+        let candles = self.query_stored_candles(symbol, exchange, timeframe, start_date, end_date).await?;
+        if matches!(timeframe, Timeframe::Minute1 | Timeframe::Tick) {
+            return Ok(candles);
+        }
         
-        let mut signals = Vec::new();
+        let minutes = self.query_stored_candles(symbol, exchange, Timeframe::Minute1, start_date, end_date).await?;
+        let resampled = bars::resample(&minutes, timeframe);
+        Ok(if resampled.len() > candles.len() { resampled } else { candles })
+    }
+    
+    /// Load candles stored at exactly the given timeframe
+    async fn query_stored_candles(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        timeframe: Timeframe,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> Result<Vec<OHLCV>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT timestamp, open, high, low, close, volume
+            FROM historical_data
+            WHERE symbol = ? AND exchange = ? AND timeframe = ? AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp
+            "#
+        )
+        .bind(symbol)
+        .bind(exchange)
+        .bind(timeframe.to_string())
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
         
-        // Simple moving average crossover strategy example
-        if context.data_index >= 20 {
-            let sma_short = self.calculate_sma(&context.historical_data, context.data_index, 5);
-            let sma_long = self.calculate_sma(&context.historical_data, context.data_index, 20);
-            let prev_sma_short = self.calculate_sma(&context.historical_data, context.data_index - 1, 5);
-            let prev_sma_long = self.calculate_sma(&context.historical_data, context.data_index - 1, 20);
-            
-            // Buy signal: short MA crosses above long MA
-            if sma_short > sma_long && prev_sma_short <= prev_sma_long {
-                if context.open_positions.is_empty() && candle.volume > strategy.volume_threshold {
-                    signals.push(TradingSignal {
-                        symbol: params.symbol.clone(),
-                        signal_type: SignalType::Buy,
-                        strength: 0.8,
-                        price: candle.close,
-                        volume: candle.volume,
-                        timestamp: candle.timestamp,
-                        strategy_id: strategy.id.clone(),
-                    });
-                }
-            }
-            
-            // Sell signal: short MA crosses below long MA
-            if sma_short < sma_long && prev_sma_short >= prev_sma_long {
-                if !context.open_positions.is_empty() {
-                    signals.push(TradingSignal {
-                        symbol: params.symbol.clone(),
-                        signal_type: SignalType::Sell,
-                        strength: 0.8,
-                        price: candle.close,
-                        volume: candle.volume,
-                        timestamp: candle.timestamp,
-                        strategy_id: strategy.id.clone(),
-                    });
+        Ok(rows.iter().map(candle_from_row).collect())
+    }
+    
+    /// Load stored candles from `context` candles before `from` to `context` candles after `to`
+    async fn load_candles_around(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        timeframe: Timeframe,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+        context: usize,
+    ) -> Result<Vec<OHLCV>> {
+        let before = sqlx::query(
+            r#"
+            SELECT timestamp, open, high, low, close, volume FROM (
+                SELECT timestamp, open, high, low, close, volume
+                FROM historical_data
+                WHERE symbol = ? AND exchange = ? AND timeframe = ? AND timestamp < ?
+                ORDER BY timestamp DESC
+                LIMIT ?
+            )
+            ORDER BY timestamp
+            "#
+        )
+        .bind(symbol)
+        .bind(exchange)
+        .bind(timeframe.to_string())
+        .bind(from)
+        .bind(context as i64)
+        .fetch_all(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+        
+        let after = sqlx::query(
+            r#"
+            SELECT timestamp, open, high, low, close, volume
+            FROM historical_data
+            WHERE symbol = ? AND exchange = ? AND timeframe = ? AND timestamp > ?
+            ORDER BY timestamp
+            LIMIT ?
+            "#
+        )
+        .bind(symbol)
+        .bind(exchange)
+        .bind(timeframe.to_string())
+        .bind(to)
+        .bind(context as i64)
+        .fetch_all(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+        
+        let mut candles: Vec<OHLCV> = before.iter().map(candle_from_row).collect();
+        candles.extend(self.load_stored_historical_data(symbol, exchange, timeframe, from, to).await?);
+        candles.extend(after.iter().map(candle_from_row));
+        Ok(candles)
+    }
+    
+    /// Load stored ticks for a symbol within a date range
+    pub async fn load_stored_ticks(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> Result<Vec<MarketTick>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT timestamp, ltp, bid, ask, volume
+            FROM market_ticks
+            WHERE symbol = ? AND exchange = ? AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp, id
+            "#
+        )
+        .bind(symbol)
+        .bind(exchange)
+        .bind(start_date)
+        .bind(end_date)
+        .fetch_all(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+        
+        Ok(rows.into_iter()
+            .map(|row| MarketTick {
+                timestamp: row.get("timestamp"),
+                ltp: to_decimal(row.get("ltp")),
+                bid: to_decimal(row.get("bid")),
+                ask: to_decimal(row.get("ask")),
+                volume: row.get("volume"),
+            })
+            .collect())
+    }
+    
+    /// Sweep stop loss, take profit and volume threshold over recent stored data
+    ///
+    /// Combinations with too few trades are skipped; the rest are ranked by
+    /// Sharpe ratio, then P&L.
+    pub async fn suggest_parameters(
+        &self,
+        user_id: &str,
+        strategy_id: &str,
+        symbol: &str,
+        exchange: &str,
+        timeframe: Timeframe,
+        lookback_days: i64,
+    ) -> Result<ParameterSweepReport> {
+        self.sweep_parameters(user_id, strategy_id, symbol, exchange, timeframe, lookback_days, backtest_concurrency()).await
+    }
+    
+    /// Parameter sweep running at most `concurrency` backtests at once
+    #[allow(clippy::too_many_arguments)]
+    async fn sweep_parameters(
+        &self,
+        user_id: &str,
+        strategy_id: &str,
+        symbol: &str,
+        exchange: &str,
+        timeframe: Timeframe,
+        lookback_days: i64,
+        concurrency: usize,
+    ) -> Result<ParameterSweepReport> {
+        if lookback_days <= 0 || lookback_days > 3650 {
+            return Err(HedgeXError::ValidationError(
+                "Lookback must be between 1 and 3650 days".to_string()
+            ));
+        }
+        
+        let strategy = self.get_strategy_params(strategy_id).await?;
+        if strategy.user_id != user_id {
+            return Err(HedgeXError::NotFoundError(format!("Strategy not found: {}", strategy_id)));
+        }
+        
+        let end_date = Utc::now();
+        let start_date = end_date - chrono::Duration::days(lookback_days);
+        let historical_data = self.load_stored_historical_data(symbol, exchange, timeframe, start_date, end_date).await?;
+        
+        if historical_data.len() <= required_bars(&strategy) {
+            return Err(HedgeXError::ValidationError(format!(
+                "Not enough historical data for {}:{} ({} candles)", exchange, symbol, historical_data.len()
+            )));
+        }
+        
+        let params = BacktestParams::new(
+            user_id, strategy_id, symbol, exchange, start_date, end_date,
+            timeframe, Decimal::from(SWEEP_CAPITAL), DataSource::Database,
+        );
+        
+        let candles = historical_data.len();
+        let data = Arc::new(historical_data);
+        
+        // The current parameters run first so their result leads the output
+        let mut runs = vec![(strategy.clone(), params.clone(), Arc::clone(&data))];
+        for volume_threshold in volume_candidates(&data, strategy.volume_threshold) {
+            for stop_loss in SWEEP_STOP_LOSS {
+                for reward_ratio in SWEEP_REWARD_RATIO {
+                    let mut candidate = strategy.clone();
+                    candidate.stop_loss_percentage = stop_loss;
+                    candidate.take_profit_percentage = stop_loss * reward_ratio;
+                    candidate.volume_threshold = volume_threshold;
+                    runs.push((candidate, params.clone(), Arc::clone(&data)));
                 }
             }
         }
+        let combinations_tested = runs.len() - 1;
         
-        Ok(signals)
+        let mut results = self.simulate_all(runs, concurrency, |candidate, result| {
+            ParameterSuggestion::from_result(candidate, &result)
+        }).await?;
+        let current = results.remove(0);
+        
+        let mut suggestions: Vec<ParameterSuggestion> = results.into_iter()
+            .filter(|suggestion| suggestion.total_trades >= MIN_SUGGESTION_TRADES)
+            .collect();
+        suggestions.sort_by(|a, b| {
+            b.sharpe_ratio.partial_cmp(&a.sharpe_ratio)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then(b.final_pnl.cmp(&a.final_pnl))
+        });
+        suggestions.truncate(MAX_SUGGESTIONS);
+        
+        info!("Parameter sweep for strategy {} on {}: {} combinations, {} suggestions",
+              strategy_id, symbol, combinations_tested, suggestions.len());
+        
+        Ok(ParameterSweepReport {
+            strategy_id: strategy_id.to_string(),
+            symbol: symbol.to_string(),
+            exchange: exchange.to_string(),
+            timeframe,
+            start_date,
+            end_date,
+            candles,
+            combinations_tested,
+            current,
+            suggestions,
+        })
     }
     
-    /// Calculate simple moving average
-    fn calculate_sma(&self, data: &[OHLCV], current_index: usize, period: usize) -> Decimal {
-        if current_index < period - 1 {
-            return Decimal::ZERO;
+    /// Grid search stop loss, take profit and volume threshold over stored data
+    ///
+    /// Backtests run with bounded concurrency and are not stored; results are
+    /// ranked by the requested metric.
+    pub async fn optimize_parameters(&self, user_id: &str, request: &OptimizationRequest) -> Result<OptimizationReport> {
+        if request.start_date >= request.end_date {
+            return Err(HedgeXError::ValidationError("Start date must be before end date".to_string()));
         }
         
-        let start_index = current_index + 1 - period;
-        let sum: Decimal = data[start_index..=current_index]
-            .iter()
-            .map(|candle| candle.close)
-            .sum();
+        let strategy = self.get_strategy_params(&request.strategy_id).await?;
+        if strategy.user_id != user_id {
+            return Err(HedgeXError::NotFoundError(format!("Strategy not found: {}", request.strategy_id)));
+        }
         
-        sum / Decimal::from(period)
-    }
-    
-    /// Calculate position size based on risk management
-    fn calculate_position_size(&self, strategy: &StrategyParams, context: &BacktestContext, price: Decimal) -> i32 {
-        let risk_amount = context.cash_balance * Decimal::from(strategy.risk_percentage) / Decimal::from(100);
-        let position_value = risk_amount / Decimal::from(strategy.stop_loss_percentage) * Decimal::from(100);
-        let quantity = position_value / price;
+        let historical_data = self.load_stored_historical_data(
+            &request.symbol, &request.exchange, request.timeframe, request.start_date, request.end_date,
+        ).await?;
         
-        // Ensure we don't exceed available cash
-        let max_quantity = context.cash_balance / price;
+        if historical_data.len() <= required_bars(&strategy) {
+            return Err(HedgeXError::ValidationError(format!(
+                "Not enough historical data for {}:{} ({} candles)", request.exchange, request.symbol, historical_data.len()
+            )));
+        }
+        
+        let params = BacktestParams::new(
+            user_id, &request.strategy_id, &request.symbol, &request.exchange,
+            request.start_date, request.end_date, request.timeframe,
+            Decimal::from(SWEEP_CAPITAL), DataSource::Database,
+        );
         
-        std::cmp::min(quantity.to_i32().unwrap_or(0), max_quantity.to_i32().unwrap_or(0))
+        let candles = historical_data.len();
+        let tested = self.grid_search(&strategy, &params, historical_data, &request.grid).await?;
+        let combinations_tested = tested.len();
+        
+        let heatmap = OptimizationHeatmap::from_results(
+            request.grid.rank_by,
+            request.grid.stop_loss.values(),
+            request.grid.take_profit.values(),
+            &tested,
+        );
+        let results = rank_results(tested, &request.grid);
+        
+        info!("Grid search for strategy {} on {}: {} combinations, {} ranked",
+              request.strategy_id, request.symbol, combinations_tested, results.len());
+        
+        Ok(OptimizationReport {
+            strategy_id: request.strategy_id.clone(),
+            symbol: request.symbol.clone(),
+            exchange: request.exchange.clone(),
+            timeframe: request.timeframe,
+            start_date: request.start_date,
+            end_date: request.end_date,
+            candles,
+            combinations_tested,
+            rank_by: request.grid.rank_by,
+            results,
+            heatmap,
+        })
     }
     
-    /// Execute trading signal
-    fn execute_signal(&self, context: &mut BacktestContext, signal: &TradingSignal, candle: &OHLCV, strategy: &StrategyParams) -> Option<BacktestTrade> {
-        match signal.signal_type {
-            SignalType::Buy => {
-                let quantity = self.calculate_position_size(strategy, context, signal.price);
-                let trade_value = signal.price * Decimal::from(quantity);
+    /// Walk-forward analysis: optimize on each in-sample window, then test the
+    /// winning combination on the window that follows
+    ///
+    /// Each window is simulated on its own, so both window sizes must exceed
+    /// the strategy's warmup.
+    pub async fn walk_forward(&self, user_id: &str, request: &WalkForwardRequest) -> Result<WalkForwardReport> {
+        if request.start_date >= request.end_date {
+            return Err(HedgeXError::ValidationError("Start date must be before end date".to_string()));
+        }
+        
+        let strategy = self.get_strategy_params(&request.strategy_id).await?;
+        if strategy.user_id != user_id {
+            return Err(HedgeXError::NotFoundError(format!("Strategy not found: {}", request.strategy_id)));
+        }
+        
+        let warmup = required_bars(&strategy);
+        if request.in_sample_candles <= warmup || request.out_of_sample_candles <= warmup {
+            return Err(HedgeXError::ValidationError(format!(
+                "In-sample and out-of-sample windows must each be longer than {} candles", warmup
+            )));
+        }
+        
+        let historical_data = self.load_stored_historical_data(
+            &request.symbol, &request.exchange, request.timeframe, request.start_date, request.end_date,
+        ).await?;
+        
+        let window_size = request.in_sample_candles + request.out_of_sample_candles;
+        if historical_data.len() < window_size {
+            return Err(HedgeXError::ValidationError(format!(
+                "Not enough historical data for {}:{} ({} candles, {} needed)",
+                request.exchange, request.symbol, historical_data.len(), window_size
+            )));
+        }
+        
+        let window_count = (historical_data.len() - window_size) / request.out_of_sample_candles + 1;
+        if window_count > MAX_WALK_FORWARD_WINDOWS {
+            return Err(HedgeXError::ValidationError(format!(
+                "Analysis has {} windows, at most {} are allowed", window_count, MAX_WALK_FORWARD_WINDOWS
+            )));
+        }
+        
+        let mut report = WalkForwardReport {
+            strategy_id: request.strategy_id.clone(),
+            symbol: request.symbol.clone(),
+            exchange: request.exchange.clone(),
+            timeframe: request.timeframe,
+            candles: historical_data.len(),
+            rank_by: request.grid.rank_by,
+            windows: Vec::new(),
+            skipped_windows: 0,
+            total_trades: 0,
+            win_rate: 0.0,
+            final_pnl: Decimal::ZERO,
+            max_drawdown: Decimal::ZERO,
+            profitable_windows: 0,
+            average_in_sample_score: 0.0,
+            average_out_of_sample_score: 0.0,
+            efficiency: None,
+        };
+        
+        for index in 0..window_count {
+            let start = index * request.out_of_sample_candles;
+            let in_sample = historical_data[start..start + request.in_sample_candles].to_vec();
+            let out_of_sample = historical_data[start + request.in_sample_candles..start + window_size].to_vec();
+            
+            let in_sample_params = BacktestParams::new(
+                user_id, &request.strategy_id, &request.symbol, &request.exchange,
+                in_sample[0].timestamp, in_sample[in_sample.len() - 1].timestamp,
+                request.timeframe, Decimal::from(SWEEP_CAPITAL), DataSource::Database,
+            );
+            let out_of_sample_params = BacktestParams::new(
+                user_id, &request.strategy_id, &request.symbol, &request.exchange,
+                out_of_sample[0].timestamp, out_of_sample[out_of_sample.len() - 1].timestamp,
+                request.timeframe, Decimal::from(SWEEP_CAPITAL), DataSource::Database,
+            );
+            
+            let tested = self.grid_search(&strategy, &in_sample_params, in_sample, &request.grid).await?;
+            let best = match rank_results(tested, &request.grid).into_iter().next() {
+                Some(best) => best,
+                None => {
+                    report.skipped_windows += 1;
+                    continue;
+                }
+            };
+            
+            let mut candidate = strategy.clone();
+            candidate.stop_loss_percentage = best.stop_loss_percentage;
+            candidate.take_profit_percentage = best.take_profit_percentage;
+            candidate.volume_threshold = best.volume_threshold;
+            
+            let result = self.simulate(&candidate, &out_of_sample_params, out_of_sample).await?;
+            
+            report.windows.push(WalkForwardWindow {
+                in_sample_start: in_sample_params.start_date,
+                in_sample_end: in_sample_params.end_date,
+                out_of_sample_start: out_of_sample_params.start_date,
+                out_of_sample_end: out_of_sample_params.end_date,
+                in_sample: best,
+                out_of_sample: ParameterSuggestion::from_result(&candidate, &result),
+            });
+        }
+        
+        report.calculate_metrics();
+        
+        info!("Walk-forward analysis for strategy {} on {}: {} windows, {} skipped, out-of-sample P&L {}",
+              request.strategy_id, request.symbol, report.windows.len(), report.skipped_windows, report.final_pnl);
+        Ok(report)
+    }
+    
+    /// Backtest every combination of a grid, returning unranked results
+    async fn grid_search(
+        &self,
+        strategy: &StrategyParams,
+        params: &BacktestParams,
+        historical_data: Vec<OHLCV>,
+        grid: &OptimizationGrid,
+    ) -> Result<Vec<ParameterSuggestion>> {
+        let historical_data = Arc::new(historical_data);
+        let runs = grid_candidates(strategy, grid)?
+            .into_iter()
+            .map(|candidate| (candidate, params.clone(), Arc::clone(&historical_data)))
+            .collect();
+        
+        self.simulate_all(runs, grid.max_concurrency, |candidate, result| {
+            ParameterSuggestion::from_result(candidate, &result)
+        }).await
+    }
+    
+    /// Simulate independent runs on blocking threads, at most `concurrency` at once
+    ///
+    /// Trend filter index data is loaded here on the async side; only the CPU bound
+    /// replay runs on a blocking thread. Each result is reduced by `summarize` inside
+    /// its task so full trade lists and equity curves are not held for every run.
+    /// Output keeps input order.
+    async fn simulate_all<T, F>(&self, runs: Vec<SimulationRun>, concurrency: usize, summarize: F) -> Result<Vec<T>>
+    where
+        T: Send + 'static,
+        F: Fn(&StrategyParams, BacktestResult) -> T + Clone + Send + 'static,
+    {
+        let concurrency = concurrency.clamp(1, MAX_CONCURRENCY);
+        let mut results: Vec<Option<T>> = std::iter::repeat_with(|| None).take(runs.len()).collect();
+        let mut runs = runs.into_iter().enumerate();
+        let mut tasks = JoinSet::new();
+        
+        loop {
+            while tasks.len() < concurrency {
+                let (index, (strategy, params, data)) = match runs.next() {
+                    Some(run) => run,
+                    None => break,
+                };
+                
+                let trend_filter = self.get_trend_filter(&strategy.id).await?;
+                let replay = self.replay_trend_filter(trend_filter, &params).await;
+                
+                // The replay does no I/O; its only awaits are on its own uncontended locks
+                let worker = BacktestEngine::new(Arc::clone(&self.db));
+                let summarize = summarize.clone();
+                tasks.spawn_blocking(move || {
+                    let result = futures::executor::block_on(worker.simulate_with(&strategy, replay, &params, data.to_vec()))?;
+                    Ok::<_, HedgeXError>((index, summarize(&strategy, result)))
+                });
+            }
+            
+            match tasks.join_next().await {
+                Some(task) => {
+                    let (index, summary) = task??;
+                    results[index] = Some(summary);
+                }
+                None => break,
+            }
+        }
+        
+        Ok(results.into_iter().flatten().collect())
+    }
+    
+    /// Replay a strategy over the last few stored sessions of each symbol
+    ///
+    /// Used as a sanity check before enabling; results are not stored.
+    pub async fn validate_strategy(
+        &self,
+        strategy: &StrategyParams,
+        symbols: &[(String, String)],
+        config: &EnableValidationConfig,
+    ) -> Result<StrategyValidationSummary> {
+        let mut summary = StrategyValidationSummary::new(&strategy.id, config);
+        
+        // Calendar window wide enough to cover weekends and holidays
+        let end_date = Utc::now();
+        let start_date = end_date - chrono::Duration::days(config.sessions as i64 * 2 + 7);
+        
+        let mut runs = Vec::new();
+        for (symbol, exchange) in symbols {
+            let stored = self.load_stored_historical_data(symbol, exchange, config.timeframe, start_date, end_date).await?;
+            let data = last_sessions(stored, config.sessions);
+            if data.len() <= required_bars(strategy) {
+                continue;
+            }
+            
+            let params = BacktestParams::new(
+                &strategy.user_id, &strategy.id, symbol, exchange,
+                data[0].timestamp, data[data.len() - 1].timestamp,
+                config.timeframe, Decimal::from(VALIDATION_CAPITAL), DataSource::Database,
+            );
+            runs.push((strategy.clone(), params, Arc::new(data)));
+        }
+        
+        // Symbols are independent, so they are simulated in parallel
+        let candles: Vec<usize> = runs.iter().map(|(_, _, data)| data.len()).collect();
+        let results = self.simulate_all(runs, backtest_concurrency(), |_, result| result).await?;
+        for (result, candles) in results.iter().zip(candles) {
+            summary.add_result(result, candles);
+        }
+        
+        summary.evaluate(config);
+        
+        info!("Validation of strategy {} over {} symbols: {} trades, P&L {}, passed: {}",
+              strategy.id, summary.symbols_tested.len(), summary.total_trades, summary.final_pnl, summary.passed);
+        Ok(summary)
+    }
+    
+    /// Get strategy parameters from database
+    async fn get_strategy_params(&self, strategy_id: &str) -> Result<StrategyParams> {
+        let row = sqlx::query(
+            "SELECT id, user_id, name, description, enabled, max_trades_per_day,
+                    risk_percentage, stop_loss_percentage, take_profit_percentage,
+                    volume_threshold, strategy_type, max_capital, max_quantity_per_trade, max_holding_minutes, tags, rules,
+                    archived_at, created_at, updated_at
+             FROM strategy_params WHERE id = ?"
+        )
+        .bind(strategy_id)
+        .fetch_optional(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?
+        .ok_or_else(|| HedgeXError::NotFoundError(format!("Strategy not found: {}", strategy_id)))?;
+        
+        Ok(StrategyParams {
+            id: row.get("id"),
+            user_id: row.get("user_id"),
+            name: row.get("name"),
+            description: row.get("description"),
+            enabled: row.get("enabled"),
+            max_trades_per_day: row.get("max_trades_per_day"),
+            risk_percentage: row.get("risk_percentage"),
+            stop_loss_percentage: row.get("stop_loss_percentage"),
+            take_profit_percentage: row.get("take_profit_percentage"),
+            volume_threshold: row.get("volume_threshold"),
+            strategy_type: row.get::<String, _>("strategy_type").parse().unwrap_or_default(),
+            max_capital: row.get("max_capital"),
+            max_quantity_per_trade: row.get("max_quantity_per_trade"),
+            max_holding_minutes: row.get("max_holding_minutes"),
+            tags: serde_json::from_str(&row.get::<String, _>("tags")).unwrap_or_default(),
+            rules: row.get::<Option<String>, _>("rules").and_then(|json| serde_json::from_str(&json).ok()),
+            archived_at: row.get("archived_at"),
+            created_at: row.get("created_at"),
+            updated_at: row.get("updated_at"),
+        })
+    }
+    
+    /// Update open positions with current market data
+    fn update_positions(&self, context: &mut BacktestContext, candle: &OHLCV) {
+        for position in context.open_positions.values_mut() {
+            position.current_price = candle.close;
+            
+            // Calculate unrealized P&L
+            let price_diff = match position.trade_type {
+                TradeType::Buy => candle.close - position.entry_price,
+                TradeType::Sell => position.entry_price - candle.close,
+            };
+            position.unrealized_pnl = price_diff * Decimal::from(position.quantity);
+        }
+    }
+    
+    /// Generate trading signals using the strategy's built-in logic, gated as live trading gates them
+    async fn generate_signals(
+        &self,
+        strategy: &StrategyParams,
+        context: &BacktestContext,
+        candle: &OHLCV,
+        params: &BacktestParams,
+        trend_filter: Option<&mut TrendFilterReplay>,
+    ) -> Result<Vec<TradingSignal>> {
+        let mut signals = Vec::new();
+        
+        // Only the trailing window the strategy needs is evaluated
+        let window_start = (context.data_index + 1).saturating_sub(required_bars(strategy));
+        let evaluation = evaluate_strategy(strategy, &context.historical_data[window_start..=context.data_index]);
+        
+        // Long only: a buy opens the position and a sell closes it
+        let signal_type = match evaluation.signal_type {
+            SignalType::Buy if context.open_positions.is_empty() => SignalType::Buy,
+            SignalType::Sell if !context.open_positions.is_empty() => SignalType::Sell,
+            _ => return Ok(signals),
+        };
+        
+        // Candles carry no depth, so entries are gated on the candle's volume and the index regime
+        let gate = SignalGate {
+            volume: candle.volume,
+            depth: None,
+            trend_filter: match trend_filter {
+                Some(replay) => Some(replay.advance(candle.timestamp).await),
+                None => None,
+            },
+            opens_position: signal_type == SignalType::Buy,
+        };
+        if !gate.allows(strategy, signal_type).await {
+            return Ok(signals);
+        }
+        
+        signals.push(TradingSignal {
+            symbol: params.symbol.clone(),
+            signal_type,
+            strength: evaluation.strength,
+            price: candle.close,
+            volume: candle.volume,
+            timestamp: candle.timestamp,
+            strategy_id: strategy.id.clone(),
+        });
+        
+        Ok(signals)
+    }
+    
+    /// Calculate position size based on risk management
+    fn calculate_position_size(&self, strategy: &StrategyParams, context: &BacktestContext, price: Decimal) -> i32 {
+        let risk_amount = context.cash_balance * to_decimal(strategy.risk_percentage) / Decimal::from(100);
+        let stop_loss = to_decimal(strategy.stop_loss_percentage);
+        if stop_loss <= Decimal::ZERO || price <= Decimal::ZERO {
+            return 0;
+        }
+        let position_value = risk_amount / stop_loss * Decimal::from(100);
+        let quantity = position_value / price;
+        
+        // Ensure we don't exceed available cash
+        let max_quantity = context.cash_balance / price;
+        let quantity = std::cmp::min(quantity.to_i32().unwrap_or(0), max_quantity.to_i32().unwrap_or(0));
+        
+        strategy.cap_quantity(quantity, price)
+    }
+    
+    /// Execute trading signal
+    fn execute_signal(&self, context: &mut BacktestContext, signal: &TradingSignal, strategy: &StrategyParams, cost_model: &CostModel) -> Option<BacktestTrade> {
+        match signal.signal_type {
+            SignalType::Buy => {
+                let fill_price = cost_model.fill_price(TradeType::Buy, signal.price);
+                let mut quantity = self.calculate_position_size(strategy, context, fill_price);
+                let mut charges = cost_model.charges(TradeType::Buy, fill_price, quantity);
+                
+                // Leave room for charges when the position uses all available cash
+                if fill_price * Decimal::from(quantity) + charges > context.cash_balance && fill_price > Decimal::ZERO {
+                    quantity = ((context.cash_balance - charges) / fill_price).floor().to_i32().unwrap_or(0);
+                    charges = cost_model.charges(TradeType::Buy, fill_price, quantity);
+                }
+                let trade_value = fill_price * Decimal::from(quantity);
                 
-                if context.cash_balance >= trade_value && quantity > 0 {
+                if context.cash_balance >= trade_value + charges && quantity > 0 {
                     // Create new position
                     let position = BacktestPosition {
                         symbol: signal.symbol.clone(),
                         trade_type: TradeType::Buy,
                         quantity,
-                        entry_price: signal.price,
+                        entry_price: fill_price,
                         entry_time: signal.timestamp,
                         current_price: signal.price,
                         unrealized_pnl: Decimal::ZERO,
+                        entry_charges: charges,
                     };
                     
                     context.open_positions.insert(signal.symbol.clone(), position);
-                    context.cash_balance -= trade_value;
+                    context.cash_balance -= trade_value + charges;
                     
                     Some(BacktestTrade::new(
                         "",  // Will be set when storing
                         &signal.symbol,
                         TradeType::Buy,
                         signal.timestamp,
-                        signal.price,
+                        fill_price,
                         quantity,
                     ))
                 } else {
@@ -343,19 +1389,8 @@ impl BacktestEngine {
             }
             SignalType::Sell => {
                 if let Some(position) = context.open_positions.remove(&signal.symbol) {
-                    let exit_value = signal.price * Decimal::from(position.quantity);
-                    context.cash_balance += exit_value;
-                    
-                    let mut trade = BacktestTrade::new(
-                        "",  // Will be set when storing
-                        &signal.symbol,
-                        position.trade_type,
-                        position.entry_time,
-                        position.entry_price,
-                        position.quantity,
-                    );
-                    
-                    trade.close(signal.timestamp, signal.price, "Signal exit");
+                    let (trade, proceeds) = position.close(signal.timestamp, signal.price, "Signal exit", cost_model);
+                    context.cash_balance += proceeds;
                     Some(trade)
                 } else {
                     None
@@ -366,7 +1401,7 @@ impl BacktestEngine {
     }
     
     /// Check for position exits based on stop loss/take profit
-    fn check_position_exits(&self, context: &mut BacktestContext, strategy: &StrategyParams, candle: &OHLCV) -> Vec<BacktestTrade> {
+    fn check_position_exits(&self, context: &mut BacktestContext, strategy: &StrategyParams, candle: &OHLCV, cost_model: &CostModel) -> Vec<BacktestTrade> {
         let mut exit_trades = Vec::new();
         let mut positions_to_close = Vec::new();
         
@@ -377,14 +1412,14 @@ impl BacktestEngine {
             match position.trade_type {
                 TradeType::Buy => {
                     // Check stop loss
-                    let stop_loss_price = position.entry_price * (Decimal::from(100) - Decimal::from(strategy.stop_loss_percentage)) / Decimal::from(100);
+                    let stop_loss_price = position.entry_price * (Decimal::from(100) - to_decimal(strategy.stop_loss_percentage)) / Decimal::from(100);
                     if candle.low <= stop_loss_price {
                         should_exit = true;
                         exit_reason = "Stop loss".to_string();
                     }
                     
                     // Check take profit
-                    let take_profit_price = position.entry_price * (Decimal::from(100) + Decimal::from(strategy.take_profit_percentage)) / Decimal::from(100);
+                    let take_profit_price = position.entry_price * (Decimal::from(100) + to_decimal(strategy.take_profit_percentage)) / Decimal::from(100);
                     if candle.high >= take_profit_price {
                         should_exit = true;
                         exit_reason = "Take profit".to_string();
@@ -392,14 +1427,14 @@ impl BacktestEngine {
                 }
                 TradeType::Sell => {
                     // Check stop loss for short position
-                    let stop_loss_price = position.entry_price * (Decimal::from(100) + Decimal::from(strategy.stop_loss_percentage)) / Decimal::from(100);
+                    let stop_loss_price = position.entry_price * (Decimal::from(100) + to_decimal(strategy.stop_loss_percentage)) / Decimal::from(100);
                     if candle.high >= stop_loss_price {
                         should_exit = true;
                         exit_reason = "Stop loss".to_string();
                     }
                     
                     // Check take profit for short position
-                    let take_profit_price = position.entry_price * (Decimal::from(100) - Decimal::from(strategy.take_profit_percentage)) / Decimal::from(100);
+                    let take_profit_price = position.entry_price * (Decimal::from(100) - to_decimal(strategy.take_profit_percentage)) / Decimal::from(100);
                     if candle.low <= take_profit_price {
                         should_exit = true;
                         exit_reason = "Take profit".to_string();
@@ -407,6 +1442,12 @@ impl BacktestEngine {
                 }
             }
             
+            // Close positions held past the strategy's limit at the bar close
+            if !should_exit && strategy.holding_period_exceeded(position.entry_time, candle.timestamp) {
+                should_exit = true;
+                exit_reason = TIME_EXIT_REASON.to_string();
+            }
+            
             if should_exit {
                 positions_to_close.push(symbol.clone());
                 
@@ -422,21 +1463,11 @@ impl BacktestEngine {
                     _ => candle.close,
                 };
                 
-                let mut trade = BacktestTrade::new(
-                    "",  // Will be set when storing
-                    symbol,
-                    position.trade_type,
-                    position.entry_time,
-                    position.entry_price,
-                    position.quantity,
-                );
-                
-                trade.close(candle.timestamp, exit_price, &exit_reason);
+                let (trade, proceeds) = position.close(candle.timestamp, exit_price, &exit_reason, cost_model);
                 exit_trades.push(trade);
                 
                 // Update cash balance
-                let exit_value = exit_price * Decimal::from(position.quantity);
-                context.cash_balance += exit_value;
+                context.cash_balance += proceeds;
             }
         }
         
@@ -448,25 +1479,13 @@ impl BacktestEngine {
         exit_trades
     }
     
-    /// Close remaining open positions at the end of backtest
-    fn close_remaining_positions(&self, context: &mut BacktestContext) -> Vec<BacktestTrade> {
+    /// Close all open positions at the current price
+    fn close_remaining_positions(&self, context: &mut BacktestContext, reason: &str, cost_model: &CostModel) -> Vec<BacktestTrade> {
         let mut final_trades = Vec::new();
         
-        for (symbol, position) in context.open_positions.drain() {
-            let final_price = context.current_price;
-            let exit_value = final_price * Decimal::from(position.quantity);
-            context.cash_balance += exit_value;
-            
-            let mut trade = BacktestTrade::new(
-                "",  // Will be set when storing
-                &symbol,
-                position.trade_type,
-                position.entry_time,
-                position.entry_price,
-                position.quantity,
-            );
-            
-            trade.close(context.current_time, final_price, "End of backtest");
+        for (_, position) in context.open_positions.drain() {
+            let (trade, proceeds) = position.close(context.current_time, context.current_price, reason, cost_model);
+            context.cash_balance += proceeds;
             final_trades.push(trade);
         }
         
@@ -489,59 +1508,82 @@ impl BacktestEngine {
     async fn store_backtest_result(&self, result: &BacktestResult) -> Result<()> {
         let mut tx = self.db.begin().await.map_err(HedgeXError::DatabaseError)?;
         
+        let data_source = match &result.params.data_source {
+            DataSource::KiteAPI => "KiteAPI",
+            DataSource::CSVFile(_) => "CSVFile",
+            DataSource::Database => "Database",
+        };
+        
         // Insert backtest run
-        sqlx::query!(
+        sqlx::query(
             r#"
             INSERT INTO backtest_runs (
                 id, user_id, strategy_id, symbol, exchange, start_date, end_date,
-                timeframe, initial_capital, total_trades, winning_trades, losing_trades,
-                final_pnl, max_drawdown, sharpe_ratio, win_rate, profit_factor, created_at
-            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-            result.id,
-            result.params.user_id,
-            result.params.strategy_id,
-            result.params.symbol,
-            result.params.exchange,
-            result.params.start_date,
-            result.params.end_date,
-            result.params.timeframe.to_string(),
-            result.params.initial_capital,
-            result.total_trades,
-            result.winning_trades,
-            result.losing_trades,
-            result.final_pnl,
-            result.max_drawdown,
-            result.sharpe_ratio,
-            result.win_rate,
-            result.profit_factor,
-            result.created_at
+                timeframe, initial_capital, data_source, total_trades, winning_trades, losing_trades,
+                final_pnl, max_drawdown, sharpe_ratio, win_rate, profit_factor, total_charges,
+                cagr, calmar_ratio, sortino_ratio, exposure_percent, longest_flat_minutes, max_consecutive_losses,
+                cost_model, benchmark, label, notes, config, config_hash, created_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            "#
         )
+        .bind(&result.id)
+        .bind(&result.params.user_id)
+        .bind(&result.params.strategy_id)
+        .bind(&result.params.symbol)
+        .bind(&result.params.exchange)
+        .bind(result.params.start_date)
+        .bind(result.params.end_date)
+        .bind(result.params.timeframe.to_string())
+        .bind(to_f64(result.params.initial_capital))
+        .bind(data_source)
+        .bind(result.total_trades)
+        .bind(result.winning_trades)
+        .bind(result.losing_trades)
+        .bind(to_f64(result.final_pnl))
+        .bind(to_f64(result.max_drawdown))
+        .bind(result.sharpe_ratio)
+        .bind(result.win_rate)
+        .bind(result.profit_factor)
+        .bind(to_f64(result.total_charges))
+        .bind(result.cagr)
+        .bind(result.calmar_ratio)
+        .bind(result.sortino_ratio)
+        .bind(result.exposure_percent)
+        .bind(result.longest_flat_minutes)
+        .bind(result.max_consecutive_losses)
+        .bind(serde_json::to_string(&result.params.cost_model)?)
+        .bind(result.benchmark.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(&result.label)
+        .bind(&result.notes)
+        .bind(result.config.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(result.config.as_ref().map(BacktestConfig::hash))
+        .bind(result.created_at)
         .execute(&mut *tx)
         .await
         .map_err(HedgeXError::DatabaseError)?;
         
         // Insert backtest trades
         for trade in &result.trades {
-            sqlx::query!(
+            sqlx::query(
                 r#"
                 INSERT INTO backtest_trades (
                     id, backtest_id, symbol, trade_type, entry_time, entry_price,
-                    quantity, exit_time, exit_price, pnl, exit_reason
-                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-                "#,
-                trade.id,
-                result.id,
-                trade.symbol,
-                trade.trade_type.to_string(),
-                trade.entry_time,
-                trade.entry_price,
-                trade.quantity,
-                trade.exit_time,
-                trade.exit_price,
-                trade.pnl,
-                trade.exit_reason
+                    quantity, exit_time, exit_price, pnl, exit_reason, charges
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                "#
             )
+            .bind(&trade.id)
+            .bind(&result.id)
+            .bind(&trade.symbol)
+            .bind(trade.trade_type.to_string())
+            .bind(trade.entry_time)
+            .bind(to_f64(trade.entry_price))
+            .bind(trade.quantity)
+            .bind(trade.exit_time)
+            .bind(trade.exit_price.map(to_f64))
+            .bind(trade.pnl.map(to_f64))
+            .bind(&trade.exit_reason)
+            .bind(to_f64(trade.charges))
             .execute(&mut *tx)
             .await
             .map_err(HedgeXError::DatabaseError)?;
@@ -549,15 +1591,12 @@ impl BacktestEngine {
         
         // Insert equity curve
         for point in &result.equity_curve {
-            sqlx::query!(
-                r#"
-                INSERT INTO backtest_equity_curve (backtest_id, timestamp, equity)
-                VALUES (?, ?, ?)
-                "#,
-                result.id,
-                point.timestamp,
-                point.equity
+            sqlx::query(
+                "INSERT INTO backtest_equity_curve (backtest_id, timestamp, equity) VALUES (?, ?, ?)"
             )
+            .bind(&result.id)
+            .bind(point.timestamp)
+            .bind(to_f64(point.equity))
             .execute(&mut *tx)
             .await
             .map_err(HedgeXError::DatabaseError)?;
@@ -571,18 +1610,22 @@ impl BacktestEngine {
     
     /// Import CSV data for backtesting
     pub async fn import_csv_data(&self, file_path: &str, symbol: &str) -> Result<CsvValidationResult> {
-        info!("Importing CSV data from: {}", file_path);
-        
         let config = CsvImportConfig {
             symbol: symbol.to_string(),
-            exchange: "NSE".to_string(),
-            timeframe: Timeframe::Day1,
-            has_header: true,
-            date_format: "%Y-%m-%d %H:%M:%S".to_string(),
-            timezone: "Asia/Kolkata".to_string(),
+            ..CsvImportConfig::default()
         };
+        self.import_csv(file_path, &config).await
+    }
+    
+    /// Validate a CSV file with a column mapping and number format, storing it if every row is valid
+    pub async fn import_csv(&self, file_path: &str, config: &CsvImportConfig) -> Result<CsvValidationResult> {
+        info!("Importing CSV data from: {}", file_path);
+        
+        if config.symbol.trim().is_empty() {
+            return Err(HedgeXError::ValidationError("Symbol is required".to_string()));
+        }
         
-        let parser = CsvParser::new(config);
+        let parser = CsvParser::new(config.clone());
         
         // Validate CSV first
         let validation_result = parser.validate_csv(file_path)?;
@@ -590,251 +1633,670 @@ impl BacktestEngine {
         if validation_result.is_valid {
             // Parse and store data
             let ohlcv_data = parser.parse_csv(file_path)?;
-            self.store_historical_data(symbol, "NSE", &ohlcv_data, Timeframe::Day1).await?;
+            self.store_historical_data(&config.symbol, &config.exchange, &ohlcv_data, config.timeframe).await?;
             
-            info!("Successfully imported {} records for {}", ohlcv_data.len(), symbol);
+            info!("Successfully imported {} records for {}", ohlcv_data.len(), config.symbol);
         }
         
         Ok(validation_result)
     }
     
+    /// Parse the first rows of a CSV file and validate it without storing anything
+    pub fn preview_csv_import(&self, file_path: &str, config: &CsvImportConfig, rows: usize) -> Result<CsvPreview> {
+        if rows == 0 || rows > MAX_PREVIEW_ROWS {
+            return Err(HedgeXError::ValidationError(format!(
+                "Preview rows must be between 1 and {}", MAX_PREVIEW_ROWS
+            )));
+        }
+        
+        CsvParser::new(config.clone()).preview(file_path, rows)
+    }
+    
     /// Store historical data in database
     async fn store_historical_data(&self, symbol: &str, exchange: &str, data: &[OHLCV], timeframe: Timeframe) -> Result<()> {
+        let timeframe = timeframe.to_string();
         let mut tx = self.db.begin().await.map_err(HedgeXError::DatabaseError)?;
-        
-        for candle in data {
-            sqlx::query!(
-                r#"
-                INSERT OR REPLACE INTO historical_data 
-                (symbol, exchange, timestamp, open, high, low, close, volume, timeframe)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
-                "#,
-                symbol,
-                exchange,
-                candle.timestamp,
-                candle.open,
-                candle.high,
-                candle.low,
-                candle.close,
-                candle.volume,
-                timeframe.to_string()
-            )
-            .execute(&mut *tx)
-            .await
-            .map_err(HedgeXError::DatabaseError)?;
-        }
-        
+        insert_rows(
+            &mut tx,
+            "INSERT OR REPLACE INTO historical_data (symbol, exchange, timestamp, open, high, low, close, volume, timeframe) ",
+            9,
+            data,
+            |values, candle| {
+                values.push_bind(symbol)
+                    .push_bind(exchange)
+                    .push_bind(candle.timestamp)
+                    .push_bind(to_f64(candle.open))
+                    .push_bind(to_f64(candle.high))
+                    .push_bind(to_f64(candle.low))
+                    .push_bind(to_f64(candle.close))
+                    .push_bind(candle.volume)
+                    .push_bind(timeframe.as_str());
+            },
+        ).await?;
         tx.commit().await.map_err(HedgeXError::DatabaseError)?;
         
         info!("Stored {} historical data points for {}:{}", data.len(), exchange, symbol);
         Ok(())
     }
     
-    /// Fetch historical data from Kite API and store in database
+    /// Fetch historical data missing from the database from Kite API and store it
     pub async fn fetch_historical_data(&self, params: HistoricalDataFetchParams) -> Result<()> {
-        let kite_client = self.kite_client.as_ref()
-            .ok_or_else(|| HedgeXError::ConfigError("Kite client not configured".to_string()))?;
+        if self.kite_client.is_none() {
+            return Err(HedgeXError::ConfigError("Kite client not configured".to_string()));
+        }
         
         for symbol in &params.symbols {
             info!("Fetching historical data for {}", symbol);
             
-            let hist_params = HistoricalDataParams {
-                symbol: symbol.clone(),
-                exchange: params.exchange.clone(),
-                from_date: params.from_date,
-                to_date: params.to_date,
-                timeframe: params.timeframe,
-            };
-            
-            match kite_client.fetch_historical_data(&hist_params).await {
-                Ok(data) => {
-                    self.store_historical_data(symbol, &params.exchange, &data, params.timeframe).await?;
-                    info!("Successfully fetched and stored {} data points for {}", data.len(), symbol);
+            match self.fill_gaps(symbol, &params.exchange, params.timeframe, params.from_date, params.to_date).await {
+                Ok(stored) => {
+                    info!("Successfully fetched and stored {} data points for {}", stored, symbol);
                 }
                 Err(e) => {
                     error!("Failed to fetch historical data for {}: {}", symbol, e);
                     // Continue with other symbols
                 }
             }
-            
-            // Add delay to respect API rate limits
-            tokio::time::sleep(tokio::time::Duration::from_millis(500)).await;
         }
         
         Ok(())
     }
     
+    /// Report stored candle coverage and gaps for a symbol and timeframe
+    pub async fn data_coverage(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        timeframe: Timeframe,
+        from_date: DateTime<Utc>,
+        to_date: DateTime<Utc>,
+    ) -> Result<DataCoverage> {
+        if timeframe == Timeframe::Tick {
+            return Err(HedgeXError::ValidationError("Ticks are not stored as candles".to_string()));
+        }
+        if from_date > to_date {
+            return Err(HedgeXError::ValidationError("Start date must be before end date".to_string()));
+        }
+        
+        let rows = sqlx::query(
+            r#"
+            SELECT timestamp FROM historical_data
+            WHERE symbol = ? AND exchange = ? AND timeframe = ? AND timestamp >= ? AND timestamp <= ?
+            ORDER BY timestamp
+            "#
+        )
+        .bind(symbol)
+        .bind(exchange)
+        .bind(timeframe.to_string())
+        .bind(from_date)
+        .bind(to_date)
+        .fetch_all(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+        
+        let timestamps: Vec<DateTime<Utc>> = rows.iter().map(|row| row.get("timestamp")).collect();
+        Ok(DataCoverage::from_candles(symbol, exchange, timeframe, from_date, to_date, &timestamps))
+    }
+    
+    /// Fetch the gaps in stored data from Kite API, returning the number of candles stored
+    async fn fill_gaps(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        timeframe: Timeframe,
+        from_date: DateTime<Utc>,
+        to_date: DateTime<Utc>,
+    ) -> Result<usize> {
+        let coverage = self.data_coverage(symbol, exchange, timeframe, from_date, to_date).await?;
+        if coverage.gaps.is_empty() {
+            info!("{}:{} {} data already cached", exchange, symbol, timeframe);
+            return Ok(0);
+        }
+        
+        let kite_client = self.kite_client.as_ref()
+            .ok_or_else(|| HedgeXError::ConfigError("Kite client not configured".to_string()))?;
+        
+        let mut stored = 0;
+        for gap in &coverage.gaps {
+            let hist_params = HistoricalDataParams {
+                symbol: symbol.to_string(),
+                exchange: exchange.to_string(),
+                from_date: gap.from,
+                to_date: gap.to,
+                timeframe,
+            };
+            
+            let data = kite_client.fetch_historical_data(&hist_params).await?;
+            self.store_historical_data(symbol, exchange, &data, timeframe).await?;
+            stored += data.len();
+        }
+        
+        Ok(stored)
+    }
+    
+    /// List stored candle and tick datasets by symbol and timeframe
+    pub async fn list_historical_datasets(&self) -> Result<Vec<HistoricalDataset>> {
+        let rows = sqlx::query(
+            r#"
+            SELECT symbol, exchange, timeframe, MIN(timestamp) as first_timestamp,
+                   MAX(timestamp) as last_timestamp, COUNT(*) as count
+            FROM historical_data
+            GROUP BY symbol, exchange, timeframe
+            UNION ALL
+            SELECT symbol, exchange, 'tick' as timeframe, MIN(timestamp) as first_timestamp,
+                   MAX(timestamp) as last_timestamp, COUNT(*) as count
+            FROM market_ticks
+            GROUP BY symbol, exchange
+            ORDER BY symbol, exchange, timeframe
+            "#
+        )
+        .fetch_all(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+        
+        let mut datasets = Vec::with_capacity(rows.len());
+        for row in rows {
+            let timeframe: String = row.get("timeframe");
+            let timeframe = match Timeframe::from_str(&timeframe) {
+                Ok(timeframe) => timeframe,
+                Err(e) => {
+                    warn!("Skipping stored data with {}", e);
+                    continue;
+                }
+            };
+            
+            datasets.push(HistoricalDataset {
+                symbol: row.get("symbol"),
+                exchange: row.get("exchange"),
+                timeframe,
+                first_timestamp: row.get("first_timestamp"),
+                last_timestamp: row.get("last_timestamp"),
+                rows: row.get::<i64, _>("count") as usize,
+            });
+        }
+        
+        Ok(datasets)
+    }
+    
+    /// Delete stored candles, or ticks for the tick timeframe, in a range, returning the rows removed
+    pub async fn delete_historical_data(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        timeframe: Timeframe,
+        from_date: DateTime<Utc>,
+        to_date: DateTime<Utc>,
+    ) -> Result<usize> {
+        if from_date > to_date {
+            return Err(HedgeXError::ValidationError("Start date must be before end date".to_string()));
+        }
+        
+        let deleted = if timeframe == Timeframe::Tick {
+            sqlx::query(
+                "DELETE FROM market_ticks WHERE symbol = ? AND exchange = ? AND timestamp >= ? AND timestamp <= ?"
+            )
+            .bind(symbol)
+            .bind(exchange)
+            .bind(from_date)
+            .bind(to_date)
+            .execute(&*self.db)
+            .await
+        } else {
+            sqlx::query(
+                "DELETE FROM historical_data WHERE symbol = ? AND exchange = ? AND timeframe = ? AND timestamp >= ? AND timestamp <= ?"
+            )
+            .bind(symbol)
+            .bind(exchange)
+            .bind(timeframe.to_string())
+            .bind(from_date)
+            .bind(to_date)
+            .execute(&*self.db)
+            .await
+        }
+        .map_err(HedgeXError::DatabaseError)?
+        .rows_affected() as usize;
+        
+        info!("Deleted {} {} rows of {}:{} stored data", deleted, timeframe, exchange, symbol);
+        Ok(deleted)
+    }
+    
+    /// Replace stored candles in a range with a fresh download from Kite, returning the number stored
+    ///
+    /// The download completes before anything is deleted, so a failed request
+    /// leaves the stored data as it was.
+    pub async fn redownload_historical_data(
+        &self,
+        symbol: &str,
+        exchange: &str,
+        timeframe: Timeframe,
+        from_date: DateTime<Utc>,
+        to_date: DateTime<Utc>,
+    ) -> Result<usize> {
+        if timeframe == Timeframe::Tick {
+            return Err(HedgeXError::ValidationError("Ticks are recorded from the live feed and cannot be downloaded".to_string()));
+        }
+        if from_date > to_date {
+            return Err(HedgeXError::ValidationError("Start date must be before end date".to_string()));
+        }
+        
+        let kite_client = self.kite_client.as_ref()
+            .ok_or_else(|| HedgeXError::ConfigError("Kite client not configured".to_string()))?;
+        
+        let hist_params = HistoricalDataParams {
+            symbol: symbol.to_string(),
+            exchange: exchange.to_string(),
+            from_date,
+            to_date,
+            timeframe,
+        };
+        let mut data = kite_client.fetch_historical_data(&hist_params).await?;
+        
+        // Kite returns whole days, so trim to the range being replaced
+        data.retain(|candle| candle.timestamp >= from_date && candle.timestamp <= to_date);
+        
+        self.delete_historical_data(symbol, exchange, timeframe, from_date, to_date).await?;
+        self.store_historical_data(symbol, exchange, &data, timeframe).await?;
+        Ok(data.len())
+    }
+    
     /// Get backtest results for a user
     pub async fn get_backtest_results(&self, user_id: &str) -> Result<Vec<BacktestSummary>> {
-        let rows = sqlx::query!(
+        let rows = sqlx::query(
             r#"
             SELECT br.id, br.user_id, sp.name as strategy_name, br.symbol,
                    br.start_date, br.end_date, br.total_trades, br.final_pnl,
-                   br.win_rate, br.created_at
+                   br.win_rate, br.label, br.notes, br.created_at
             FROM backtest_runs br
             JOIN strategy_params sp ON br.strategy_id = sp.id
             WHERE br.user_id = ?
             ORDER BY br.created_at DESC
-            "#,
-            user_id
+            "#
         )
+        .bind(user_id)
         .fetch_all(&*self.db)
         .await
         .map_err(HedgeXError::DatabaseError)?;
         
-        let mut results = Vec::new();
-        for row in rows {
-            results.push(BacktestSummary {
-                id: row.id,
-                user_id: row.user_id,
-                strategy_name: row.strategy_name,
-                symbol: row.symbol,
-                start_date: row.start_date,
-                end_date: row.end_date,
-                total_trades: row.total_trades,
-                final_pnl: row.final_pnl,
-                win_rate: row.win_rate,
-                created_at: row.created_at,
-            });
+        Ok(rows.iter().map(summary_from_row).collect())
+    }
+    
+    /// Set or clear the label and notes of a user's backtest
+    pub async fn update_backtest_notes(
+        &self,
+        user_id: &str,
+        backtest_id: &str,
+        label: Option<String>,
+        notes: Option<String>,
+    ) -> Result<()> {
+        // Blank values clear the field
+        let label = label.map(|label| label.trim().to_string()).filter(|label| !label.is_empty());
+        let notes = notes.map(|notes| notes.trim().to_string()).filter(|notes| !notes.is_empty());
+        
+        if label.as_ref().map_or(false, |label| label.chars().count() > MAX_LABEL_LENGTH) {
+            return Err(HedgeXError::ValidationError(format!(
+                "Labels can be at most {} characters", MAX_LABEL_LENGTH
+            )));
+        }
+        if notes.as_ref().map_or(false, |notes| notes.chars().count() > MAX_NOTES_LENGTH) {
+            return Err(HedgeXError::ValidationError(format!(
+                "Notes can be at most {} characters", MAX_NOTES_LENGTH
+            )));
         }
         
-        Ok(results)
+        let result = sqlx::query("UPDATE backtest_runs SET label = ?, notes = ? WHERE id = ? AND user_id = ?")
+            .bind(&label)
+            .bind(&notes)
+            .bind(backtest_id)
+            .bind(user_id)
+            .execute(&*self.db)
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+            
+        if result.rows_affected() == 0 {
+            return Err(HedgeXError::NotFoundError(format!("Backtest not found: {}", backtest_id)));
+        }
+        Ok(())
     }
     
-    /// Get detailed backtest result
-    pub async fn get_backtest_detail(&self, backtest_id: &str) -> Result<BacktestResult> {
-        // Get backtest run details
-        let run_row = sqlx::query!(
-            "SELECT * FROM backtest_runs WHERE id = ?",
-            backtest_id
-        )
-        .fetch_one(&*self.db)
-        .await
-        .map_err(HedgeXError::DatabaseError)?;
+    /// Delete a user's backtest with its trades, equity curve and Monte Carlo result
+    pub async fn delete_backtest(&self, user_id: &str, backtest_id: &str) -> Result<()> {
+        let deleted = self.delete_runs(user_id, &[backtest_id.to_string()]).await?;
+        if deleted == 0 {
+            return Err(HedgeXError::NotFoundError(format!("Backtest not found: {}", backtest_id)));
+        }
         
-        // Get backtest trades
-        let trade_rows = sqlx::query!(
-            "SELECT * FROM backtest_trades WHERE backtest_id = ? ORDER BY entry_time",
-            backtest_id
+        info!("Deleted backtest {}", backtest_id);
+        Ok(())
+    }
+    
+    /// Delete a user's backtests created more than `older_than_days` ago
+    ///
+    /// Labelled runs are kept unless `include_labeled` is set. Returns the
+    /// number of runs deleted.
+    pub async fn prune_backtests(&self, user_id: &str, older_than_days: i64, include_labeled: bool) -> Result<usize> {
+        if older_than_days < 1 {
+            return Err(HedgeXError::ValidationError("Only runs at least a day old can be pruned".to_string()));
+        }
+        
+        let cutoff = Utc::now() - chrono::Duration::days(older_than_days);
+        let ids: Vec<String> = sqlx::query(
+            "SELECT id FROM backtest_runs WHERE user_id = ? AND created_at < ? AND (? OR label IS NULL)"
         )
+        .bind(user_id)
+        .bind(cutoff)
+        .bind(include_labeled)
         .fetch_all(&*self.db)
         .await
-        .map_err(HedgeXError::DatabaseError)?;
+        .map_err(HedgeXError::DatabaseError)?
+        .iter()
+        .map(|row| row.get("id"))
+        .collect();
         
-        let mut trades = Vec::new();
-        for row in trade_rows {
-            let mut trade = BacktestTrade::new(
-                backtest_id,
-                &row.symbol,
-                TradeType::from_str(&row.trade_type).unwrap_or(TradeType::Buy),
-                row.entry_time,
-                row.entry_price,
-                row.quantity,
-            );
-            
-            if let (Some(exit_time), Some(exit_price), exit_reason) = (row.exit_time, row.exit_price, row.exit_reason) {
-                trade.close(exit_time, exit_price, &exit_reason.unwrap_or_default());
+        let deleted = self.delete_runs(user_id, &ids).await?;
+        info!("Pruned {} backtests older than {} days for user {}", deleted, older_than_days, user_id);
+        Ok(deleted)
+    }
+    
+    /// Delete runs and everything stored with them in one transaction
+    ///
+    /// Child rows are removed explicitly since SQLite only cascades when
+    /// foreign keys are enabled on the connection.
+    async fn delete_runs(&self, user_id: &str, backtest_ids: &[String]) -> Result<usize> {
+        let mut tx = self.db.begin().await.map_err(HedgeXError::DatabaseError)?;
+        let mut deleted = 0;
+        
+        for backtest_id in backtest_ids {
+            let run = sqlx::query("DELETE FROM backtest_runs WHERE id = ? AND user_id = ?")
+                .bind(backtest_id)
+                .bind(user_id)
+                .execute(&mut *tx)
+                .await
+                .map_err(HedgeXError::DatabaseError)?;
+            if run.rows_affected() == 0 {
+                continue;
             }
             
-            trades.push(trade);
+            for table in ["backtest_trades", "backtest_equity_curve", "backtest_monte_carlo"] {
+                sqlx::query(&format!("DELETE FROM {} WHERE backtest_id = ?", table))
+                    .bind(backtest_id)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(HedgeXError::DatabaseError)?;
+            }
+            deleted += 1;
         }
         
-        // Get equity curve
-        let equity_rows = sqlx::query!(
-            "SELECT * FROM backtest_equity_curve WHERE backtest_id = ? ORDER BY timestamp",
-            backtest_id
+        tx.commit().await.map_err(HedgeXError::DatabaseError)?;
+        Ok(deleted)
+    }
+    
+    /// Run a Monte Carlo robustness simulation on a stored backtest
+    ///
+    /// The result replaces any earlier simulation stored with the run.
+    pub async fn run_monte_carlo(&self, backtest_id: &str, config: &MonteCarloConfig) -> Result<MonteCarloResult> {
+        monte_carlo::validate_config(config)?;
+        
+        let backtest = self.get_backtest_detail(backtest_id).await?;
+        let trades: Vec<TradeOutcome> = backtest.trades.iter().filter_map(TradeOutcome::from_trade).collect();
+        if trades.is_empty() {
+            return Err(HedgeXError::ValidationError(format!("Backtest {} has no closed trades", backtest_id)));
+        }
+        
+        let initial_capital = to_f64(backtest.params.initial_capital);
+        let backtest_id = backtest_id.to_string();
+        let config = config.clone();
+        let result = tokio::task::spawn_blocking(move || {
+            monte_carlo::run_simulation(&backtest_id, &trades, initial_capital, &config)
+        }).await?;
+        
+        sqlx::query(
+            r#"
+            INSERT OR REPLACE INTO backtest_monte_carlo (backtest_id, iterations, result, created_at)
+            VALUES (?, ?, ?, ?)
+            "#
         )
-        .fetch_all(&*self.db)
+        .bind(&result.backtest_id)
+        .bind(result.config.iterations as i64)
+        .bind(serde_json::to_string(&result)?)
+        .bind(result.created_at)
+        .execute(&*self.db)
         .await
         .map_err(HedgeXError::DatabaseError)?;
         
-        let equity_curve = equity_rows.into_iter()
-            .map(|row| EquityPoint::new(row.timestamp, row.equity))
-            .collect();
-        
+        info!("Monte Carlo simulation of backtest {}: {} iterations, P&L {:.2} to {:.2}",
+              result.backtest_id, result.config.iterations, result.final_pnl.lower, result.final_pnl.upper);
+        Ok(result)
+    }
+    
+    /// Get the stored Monte Carlo simulation of a backtest, if one was run
+    pub async fn get_monte_carlo(&self, backtest_id: &str) -> Result<Option<MonteCarloResult>> {
+        let row = sqlx::query("SELECT result FROM backtest_monte_carlo WHERE backtest_id = ?")
+            .bind(backtest_id)
+            .fetch_optional(&*self.db)
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+            
+        match row {
+            Some(row) => Ok(Some(serde_json::from_str(&row.get::<String, _>("result"))?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// Get detailed backtest result
+    pub async fn get_backtest_detail(&self, backtest_id: &str) -> Result<BacktestResult> {
+        // Get backtest run details
+        let run_row = sqlx::query("SELECT * FROM backtest_runs WHERE id = ?")
+            .bind(backtest_id)
+            .fetch_optional(&*self.db)
+            .await
+            .map_err(HedgeXError::DatabaseError)?
+            .ok_or_else(|| HedgeXError::NotFoundError(format!("Backtest not found: {}", backtest_id)))?;
+        
+        // Get backtest trades
+        let trade_rows = sqlx::query(
+            "SELECT * FROM backtest_trades WHERE backtest_id = ? ORDER BY entry_time"
+        )
+        .bind(backtest_id)
+        .fetch_all(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+        
+        let trades = trade_rows.iter().map(|row| trade_from_row(backtest_id, row)).collect();
+        
+        // Get equity curve
+        let equity_rows = sqlx::query(
+            "SELECT timestamp, equity FROM backtest_equity_curve WHERE backtest_id = ? ORDER BY timestamp"
+        )
+        .bind(backtest_id)
+        .fetch_all(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+        
+        let equity_curve = equity_rows.into_iter()
+            .map(|row| EquityPoint::new(row.get("timestamp"), to_decimal(row.get("equity"))))
+            .collect();
+        
         // Reconstruct backtest parameters
+        let timeframe: String = run_row.get("timeframe");
+        let cost_model: Option<String> = run_row.get("cost_model");
+        let benchmark: Option<String> = run_row.get("benchmark");
+        let config: Option<String> = run_row.get("config");
         let params = BacktestParams {
-            id: run_row.id.clone(),
-            user_id: run_row.user_id,
-            strategy_id: run_row.strategy_id,
-            symbol: run_row.symbol,
-            exchange: run_row.exchange,
-            start_date: run_row.start_date,
-            end_date: run_row.end_date,
-            timeframe: Timeframe::from_str(&run_row.timeframe).unwrap_or(Timeframe::Day1),
-            initial_capital: run_row.initial_capital,
+            id: run_row.get("id"),
+            user_id: run_row.get("user_id"),
+            strategy_id: run_row.get("strategy_id"),
+            symbol: run_row.get("symbol"),
+            exchange: run_row.get("exchange"),
+            start_date: run_row.get("start_date"),
+            end_date: run_row.get("end_date"),
+            timeframe: Timeframe::from_str(&timeframe).unwrap_or(Timeframe::Day1),
+            initial_capital: to_decimal(run_row.get("initial_capital")),
             data_source: DataSource::KiteAPI, // Default, could be stored in DB
-            created_at: run_row.created_at,
+            // Runs stored before the cost model existed were simulated without costs
+            cost_model: cost_model
+                .and_then(|json| serde_json::from_str(&json).ok())
+                .unwrap_or_else(CostModel::disabled),
+            created_at: run_row.get("created_at"),
         };
         
-        let result = BacktestResult {
-            id: run_row.id,
+        let mut result = BacktestResult {
+            id: run_row.get("id"),
             params,
-            total_trades: run_row.total_trades,
-            winning_trades: run_row.winning_trades,
-            losing_trades: run_row.losing_trades,
-            final_pnl: run_row.final_pnl,
-            max_drawdown: run_row.max_drawdown,
-            sharpe_ratio: run_row.sharpe_ratio,
-            win_rate: run_row.win_rate,
-            profit_factor: run_row.profit_factor,
+            total_trades: run_row.get("total_trades"),
+            winning_trades: run_row.get("winning_trades"),
+            losing_trades: run_row.get("losing_trades"),
+            final_pnl: to_decimal(run_row.get("final_pnl")),
+            max_drawdown: to_decimal(run_row.get("max_drawdown")),
+            sharpe_ratio: run_row.get("sharpe_ratio"),
+            win_rate: run_row.get("win_rate"),
+            profit_factor: run_row.get("profit_factor"),
+            total_charges: to_decimal(run_row.get("total_charges")),
+            benchmark: benchmark.map(|json| serde_json::from_str(&json)).transpose()?,
+            trading_days: 0,
+            average_holding_minutes: 0.0,
+            cagr: run_row.get("cagr"),
+            calmar_ratio: run_row.get("calmar_ratio"),
+            sortino_ratio: run_row.get("sortino_ratio"),
+            exposure_percent: run_row.get("exposure_percent"),
+            longest_flat_minutes: run_row.get("longest_flat_minutes"),
+            max_consecutive_losses: run_row.get("max_consecutive_losses"),
+            label: run_row.get("label"),
+            notes: run_row.get("notes"),
+            config: config.map(|json| serde_json::from_str(&json)).transpose()?,
             trades,
             equity_curve,
-            created_at: run_row.created_at,
+            created_at: run_row.get("created_at"),
         };
+        result.calculate_duration_metrics();
         
         Ok(result)
     }
     
+    /// Page through a backtest's closed trades with excursions and candle context
+    pub async fn get_backtest_trades(
+        &self,
+        backtest_id: &str,
+        page: usize,
+        page_size: usize,
+        context_candles: usize,
+    ) -> Result<BacktestTradePage> {
+        if page == 0 {
+            return Err(HedgeXError::ValidationError("Pages start at 1".to_string()));
+        }
+        if page_size == 0 || page_size > MAX_TRADE_PAGE_SIZE {
+            return Err(HedgeXError::ValidationError(format!(
+                "Page size must be between 1 and {}", MAX_TRADE_PAGE_SIZE
+            )));
+        }
+        if context_candles > MAX_CONTEXT_CANDLES {
+            return Err(HedgeXError::ValidationError(format!(
+                "At most {} context candles can be shown", MAX_CONTEXT_CANDLES
+            )));
+        }
+        
+        let run_row = sqlx::query("SELECT symbol, exchange, timeframe FROM backtest_runs WHERE id = ?")
+            .bind(backtest_id)
+            .fetch_optional(&*self.db)
+            .await
+            .map_err(HedgeXError::DatabaseError)?
+            .ok_or_else(|| HedgeXError::NotFoundError(format!("Backtest not found: {}", backtest_id)))?;
+        let symbol: String = run_row.get("symbol");
+        let exchange: String = run_row.get("exchange");
+        
+        // Tick runs are charted on one-minute candles
+        let timeframe = match Timeframe::from_str(&run_row.get::<String, _>("timeframe")) {
+            Ok(Timeframe::Tick) => Timeframe::Minute1,
+            Ok(timeframe) => timeframe,
+            Err(_) => Timeframe::Day1,
+        };
+        
+        // Entry records are superseded by the closed trade, so only closed trades are listed
+        let total_trades: i64 = sqlx::query(
+            "SELECT COUNT(*) as count FROM backtest_trades WHERE backtest_id = ? AND exit_time IS NOT NULL"
+        )
+        .bind(backtest_id)
+        .fetch_one(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?
+        .get("count");
+        
+        let trade_rows = sqlx::query(
+            r#"
+            SELECT * FROM backtest_trades
+            WHERE backtest_id = ? AND exit_time IS NOT NULL
+            ORDER BY entry_time
+            LIMIT ? OFFSET ?
+            "#
+        )
+        .bind(backtest_id)
+        .bind(page_size as i64)
+        .bind(((page - 1) * page_size) as i64)
+        .fetch_all(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+        
+        let mut trades = Vec::with_capacity(trade_rows.len());
+        for row in &trade_rows {
+            let trade = trade_from_row(backtest_id, row);
+            let candles = self.load_candles_around(
+                &symbol, &exchange, timeframe, trade.entry_time, trade.exit_time.unwrap_or(trade.entry_time), context_candles,
+            ).await?;
+            trades.push(TradeDrillDown::new(trade, candles));
+        }
+        
+        let total_trades = total_trades as usize;
+        Ok(BacktestTradePage {
+            backtest_id: backtest_id.to_string(),
+            page,
+            page_size,
+            total_trades,
+            total_pages: total_trades.div_ceil(page_size),
+            trades,
+        })
+    }
+    
     /// Compare multiple backtest results
     pub async fn compare_backtests(&self, backtest_ids: Vec<&str>) -> Result<BacktestComparison> {
         let mut backtests = Vec::new();
-        let mut metrics_comparison = HashMap::new();
+        let mut metrics_comparison: HashMap<String, Vec<f64>> = HashMap::new();
         
         for backtest_id in &backtest_ids {
-            let summary_row = sqlx::query!(
+            let summary_row = sqlx::query(
                 r#"
                 SELECT br.id, br.user_id, sp.name as strategy_name, br.symbol,
                        br.start_date, br.end_date, br.total_trades, br.final_pnl,
-                       br.win_rate, br.created_at, br.max_drawdown, br.sharpe_ratio,
-                       br.profit_factor
+                       br.win_rate, br.label, br.notes, br.created_at, br.max_drawdown,
+                       br.sharpe_ratio, br.profit_factor, br.cagr, br.calmar_ratio, br.sortino_ratio,
+                       br.exposure_percent, br.longest_flat_minutes, br.max_consecutive_losses
                 FROM backtest_runs br
                 JOIN strategy_params sp ON br.strategy_id = sp.id
                 WHERE br.id = ?
-                "#,
-                backtest_id
+                "#
             )
-            .fetch_one(&*self.db)
+            .bind(backtest_id)
+            .fetch_optional(&*self.db)
             .await
-            .map_err(HedgeXError::DatabaseError)?;
+            .map_err(HedgeXError::DatabaseError)?
+            .ok_or_else(|| HedgeXError::NotFoundError(format!("Backtest not found: {}", backtest_id)))?;
             
-            backtests.push(BacktestSummary {
-                id: summary_row.id,
-                user_id: summary_row.user_id,
-                strategy_name: summary_row.strategy_name,
-                symbol: summary_row.symbol,
-                start_date: summary_row.start_date,
-                end_date: summary_row.end_date,
-                total_trades: summary_row.total_trades,
-                final_pnl: summary_row.final_pnl,
-                win_rate: summary_row.win_rate,
-                created_at: summary_row.created_at,
-            });
+            let summary = summary_from_row(&summary_row);
             
             // Collect metrics for comparison
-            if !metrics_comparison.contains_key("final_pnl") {
-                metrics_comparison.insert("final_pnl".to_string(), Vec::new());
-                metrics_comparison.insert("win_rate".to_string(), Vec::new());
-                metrics_comparison.insert("max_drawdown".to_string(), Vec::new());
-                metrics_comparison.insert("sharpe_ratio".to_string(), Vec::new());
-                metrics_comparison.insert("profit_factor".to_string(), Vec::new());
-            }
+            metrics_comparison.entry("final_pnl".to_string()).or_default().push(to_f64(summary.final_pnl));
+            metrics_comparison.entry("win_rate".to_string()).or_default().push(summary.win_rate);
+            metrics_comparison.entry("max_drawdown".to_string()).or_default().push(summary_row.get("max_drawdown"));
+            metrics_comparison.entry("sharpe_ratio".to_string()).or_default().push(summary_row.get("sharpe_ratio"));
+            metrics_comparison.entry("profit_factor".to_string()).or_default().push(summary_row.get("profit_factor"));
+            metrics_comparison.entry("cagr".to_string()).or_default().push(summary_row.get("cagr"));
+            metrics_comparison.entry("calmar_ratio".to_string()).or_default().push(summary_row.get("calmar_ratio"));
+            metrics_comparison.entry("sortino_ratio".to_string()).or_default().push(summary_row.get("sortino_ratio"));
+            metrics_comparison.entry("exposure_percent".to_string()).or_default().push(summary_row.get("exposure_percent"));
+            metrics_comparison.entry("longest_flat_minutes".to_string()).or_default()
+                .push(summary_row.get::<i64, _>("longest_flat_minutes") as f64);
+            metrics_comparison.entry("max_consecutive_losses".to_string()).or_default()
+                .push(summary_row.get::<i32, _>("max_consecutive_losses") as f64);
             
-            metrics_comparison.get_mut("final_pnl").unwrap().push(summary_row.final_pnl.to_f64().unwrap_or(0.0));
-            metrics_comparison.get_mut("win_rate").unwrap().push(summary_row.win_rate);
-            metrics_comparison.get_mut("max_drawdown").unwrap().push(summary_row.max_drawdown.to_f64().unwrap_or(0.0));
-            metrics_comparison.get_mut("sharpe_ratio").unwrap().push(summary_row.sharpe_ratio);
-            metrics_comparison.get_mut("profit_factor").unwrap().push(summary_row.profit_factor);
+            backtests.push(summary);
         }
         
         Ok(BacktestComparison {
@@ -844,12 +2306,210 @@ impl BacktestEngine {
     }
 }
 
+/// Backtests run at once by parameter sweeps and multi-symbol validation, read on every call
+fn backtest_concurrency() -> usize {
+    let configured = std::env::var(BACKTEST_CONCURRENCY_VAR).ok().and_then(|value| match value.trim().parse() {
+        Ok(concurrency) => Some(concurrency),
+        Err(_) => {
+            warn!("Ignoring {} '{}', expected a number of backtests", BACKTEST_CONCURRENCY_VAR, value);
+            None
+        }
+    });
+    configured
+        .unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1))
+        .clamp(1, MAX_CONCURRENCY)
+}
+
+/// Volume threshold candidates from candle volume percentiles plus the current value
+fn volume_candidates(data: &[OHLCV], current: i64) -> Vec<i64> {
+    let mut volumes: Vec<i64> = data.iter().map(|c| c.volume).collect();
+    volumes.sort_unstable();
+    
+    let mut candidates: Vec<i64> = SWEEP_VOLUME_PERCENTILES.iter()
+        .map(|p| volumes[((volumes.len() - 1) as f64 * p) as usize].max(1))
+        .collect();
+    candidates.push(current);
+    candidates.sort_unstable();
+    candidates.dedup();
+    candidates
+}
+
+/// Strategy variants for every combination in a grid with take profit above stop loss
+fn grid_candidates(strategy: &StrategyParams, grid: &OptimizationGrid) -> Result<Vec<StrategyParams>> {
+    if !grid.stop_loss.is_valid() || grid.stop_loss.max > 50.0 {
+        return Err(HedgeXError::ValidationError(
+            "Stop loss range must be positive and at most 50%".to_string()
+        ));
+    }
+    
+    if !grid.take_profit.is_valid() || grid.take_profit.max > 100.0 {
+        return Err(HedgeXError::ValidationError(
+            "Take profit range must be positive and at most 100%".to_string()
+        ));
+    }
+    
+    if grid.volume_thresholds.iter().any(|volume| *volume <= 0) {
+        return Err(HedgeXError::ValidationError(
+            "Volume thresholds must be positive".to_string()
+        ));
+    }
+    
+    let mut volume_thresholds = grid.volume_thresholds.clone();
+    if volume_thresholds.is_empty() {
+        volume_thresholds.push(strategy.volume_threshold);
+    }
+    volume_thresholds.sort_unstable();
+    volume_thresholds.dedup();
+    
+    let stop_losses = grid.stop_loss.values();
+    let take_profits = grid.take_profit.values();
+    
+    let total = stop_losses.len() * take_profits.len() * volume_thresholds.len();
+    if total > MAX_OPTIMIZATION_COMBINATIONS {
+        return Err(HedgeXError::ValidationError(format!(
+            "Grid has {} combinations, at most {} are allowed", total, MAX_OPTIMIZATION_COMBINATIONS
+        )));
+    }
+    
+    let mut candidates = Vec::new();
+    for volume_threshold in &volume_thresholds {
+        for stop_loss in &stop_losses {
+            for take_profit in take_profits.iter().filter(|take_profit| *take_profit > stop_loss) {
+                let mut candidate = strategy.clone();
+                candidate.stop_loss_percentage = *stop_loss;
+                candidate.take_profit_percentage = *take_profit;
+                candidate.volume_threshold = *volume_threshold;
+                candidates.push(candidate);
+            }
+        }
+    }
+    
+    if candidates.is_empty() {
+        return Err(HedgeXError::ValidationError(
+            "No combination has take profit above stop loss".to_string()
+        ));
+    }
+    
+    Ok(candidates)
+}
+
+/// Drop combinations with too few trades and sort the rest best first
+fn rank_results(mut results: Vec<ParameterSuggestion>, grid: &OptimizationGrid) -> Vec<ParameterSuggestion> {
+    let metric = grid.rank_by;
+    results.retain(|result| result.total_trades >= grid.min_trades);
+    
+    // Ties are broken by P&L, then by parameters so the order is stable across runs
+    results.sort_by(|a, b| {
+        metric.score(b).partial_cmp(&metric.score(a))
+            .unwrap_or(std::cmp::Ordering::Equal)
+            .then(b.final_pnl.cmp(&a.final_pnl))
+            .then(a.stop_loss_percentage.total_cmp(&b.stop_loss_percentage))
+            .then(a.take_profit_percentage.total_cmp(&b.take_profit_percentage))
+            .then(a.volume_threshold.cmp(&b.volume_threshold))
+    });
+    results
+}
+
+/// Keep only candles from the last `sessions` trading days
+fn last_sessions(mut data: Vec<OHLCV>, sessions: usize) -> Vec<OHLCV> {
+    let mut days: Vec<_> = data.iter().map(|c| c.timestamp.date_naive()).collect();
+    days.dedup();
+    
+    if days.len() > sessions {
+        let first_day = days[days.len() - sessions];
+        data.retain(|c| c.timestamp.date_naive() >= first_day);
+    }
+    data
+}
+
+/// SHA-256 over the candles, ticks and index candles a simulation consumes, in order
+fn data_checksum(candles: &[OHLCV], ticks: &[MarketTick], index_candles: &[OHLCV]) -> String {
+    let mut hasher = Sha256::new();
+    for (source, candles) in [("candle", candles), ("index", index_candles)] {
+        for candle in candles {
+            hasher.update(format!(
+                "{}|{}|{}|{}|{}|{}|{}\n",
+                source, candle.timestamp.timestamp_millis(), candle.open.normalize(), candle.high.normalize(),
+                candle.low.normalize(), candle.close.normalize(), candle.volume
+            ));
+        }
+    }
+    for tick in ticks {
+        hasher.update(format!(
+            "tick|{}|{}|{}|{}|{}\n",
+            tick.timestamp.timestamp_millis(), tick.ltp.normalize(), tick.bid.normalize(), tick.ask.normalize(), tick.volume
+        ));
+    }
+    hex_digest(hasher.finalize().as_slice())
+}
+
+/// Build a candle from a historical_data row
+fn candle_from_row(row: &sqlx::sqlite::SqliteRow) -> OHLCV {
+    OHLCV::new(
+        row.get("timestamp"),
+        to_decimal(row.get("open")),
+        to_decimal(row.get("high")),
+        to_decimal(row.get("low")),
+        to_decimal(row.get("close")),
+        row.get("volume"),
+    )
+}
+
+/// Build a trade from a backtest_trades row
+fn trade_from_row(backtest_id: &str, row: &sqlx::sqlite::SqliteRow) -> BacktestTrade {
+    let mut trade = BacktestTrade::new(
+        backtest_id,
+        row.get("symbol"),
+        TradeType::from_str(row.get("trade_type")).unwrap_or(TradeType::Buy),
+        row.get("entry_time"),
+        to_decimal(row.get("entry_price")),
+        row.get("quantity"),
+    );
+    trade.id = row.get("id");
+    trade.charges = to_decimal(row.get("charges"));
+    
+    let exit_time: Option<DateTime<Utc>> = row.get("exit_time");
+    let exit_price: Option<f64> = row.get("exit_price");
+    if let (Some(exit_time), Some(exit_price)) = (exit_time, exit_price) {
+        let exit_reason: Option<String> = row.get("exit_reason");
+        trade.close(exit_time, to_decimal(exit_price), &exit_reason.unwrap_or_default());
+    }
+    trade
+}
+
+/// Build a backtest summary from a backtest_runs row joined with its strategy name
+fn summary_from_row(row: &sqlx::sqlite::SqliteRow) -> BacktestSummary {
+    BacktestSummary {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        strategy_name: row.get("strategy_name"),
+        symbol: row.get("symbol"),
+        start_date: row.get("start_date"),
+        end_date: row.get("end_date"),
+        total_trades: row.get("total_trades"),
+        final_pnl: to_decimal(row.get("final_pnl")),
+        win_rate: row.get("win_rate"),
+        label: row.get("label"),
+        notes: row.get("notes"),
+        created_at: row.get("created_at"),
+    }
+}
+
+/// Convert a stored REAL into a decimal amount
+fn to_decimal(value: f64) -> Decimal {
+    Decimal::from_f64(value).unwrap_or_default()
+}
+
+/// Convert a decimal amount into a REAL for storage
+fn to_f64(value: Decimal) -> f64 {
+    value.to_f64().unwrap_or(0.0)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::models::backtesting::*;
     use crate::models::trading::*;
-    use crate::trading::strategy_manager::StrategyManager;
     use chrono::{DateTime, Utc, TimeZone};
     use rust_decimal::Decimal;
     use sqlx::{Pool, Sqlite, SqlitePool};
@@ -873,6 +2533,13 @@ mod tests {
                 stop_loss_percentage REAL NOT NULL DEFAULT 2.0,
                 take_profit_percentage REAL NOT NULL DEFAULT 4.0,
                 volume_threshold INTEGER NOT NULL DEFAULT 1000,
+                strategy_type TEXT NOT NULL DEFAULT 'BollingerMeanReversion',
+                max_capital REAL,
+                max_quantity_per_trade INTEGER,
+                max_holding_minutes INTEGER,
+                tags TEXT NOT NULL DEFAULT '[]',
+                rules TEXT,
+                archived_at TIMESTAMP,
                 created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
                 updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
             )
@@ -889,6 +2556,7 @@ mod tests {
                 end_date TIMESTAMP NOT NULL,
                 timeframe TEXT NOT NULL,
                 initial_capital REAL NOT NULL,
+                data_source TEXT NOT NULL,
                 total_trades INTEGER NOT NULL,
                 winning_trades INTEGER NOT NULL,
                 losing_trades INTEGER NOT NULL,
@@ -897,6 +2565,19 @@ mod tests {
                 sharpe_ratio REAL NOT NULL,
                 win_rate REAL NOT NULL,
                 profit_factor REAL NOT NULL,
+                total_charges REAL NOT NULL DEFAULT 0.0,
+                cagr REAL NOT NULL DEFAULT 0.0,
+                calmar_ratio REAL NOT NULL DEFAULT 0.0,
+                sortino_ratio REAL NOT NULL DEFAULT 0.0,
+                exposure_percent REAL NOT NULL DEFAULT 0.0,
+                longest_flat_minutes INTEGER NOT NULL DEFAULT 0,
+                max_consecutive_losses INTEGER NOT NULL DEFAULT 0,
+                cost_model TEXT,
+                benchmark TEXT,
+                label TEXT,
+                notes TEXT,
+                config TEXT,
+                config_hash TEXT,
                 created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
             )
         "#).execute(&pool).await.unwrap();
@@ -913,7 +2594,8 @@ mod tests {
                 exit_time TIMESTAMP,
                 exit_price REAL,
                 pnl REAL,
-                exit_reason TEXT
+                exit_reason TEXT,
+                charges REAL NOT NULL DEFAULT 0.0
             )
         "#).execute(&pool).await.unwrap();
 
@@ -926,6 +2608,15 @@ mod tests {
             )
         "#).execute(&pool).await.unwrap();
 
+        sqlx::query(r#"
+            CREATE TABLE backtest_monte_carlo (
+                backtest_id TEXT PRIMARY KEY,
+                iterations INTEGER NOT NULL,
+                result TEXT NOT NULL,
+                created_at TIMESTAMP NOT NULL
+            )
+        "#).execute(&pool).await.unwrap();
+
         sqlx::query(r#"
             CREATE TABLE historical_data (
                 id INTEGER PRIMARY KEY AUTOINCREMENT,
@@ -942,30 +2633,70 @@ mod tests {
             )
         "#).execute(&pool).await.unwrap();
 
+        sqlx::query(r#"
+            CREATE TABLE strategy_trend_filters (
+                strategy_id TEXT PRIMARY KEY,
+                index_symbol TEXT NOT NULL DEFAULT 'NIFTY 50',
+                ma_period INTEGER NOT NULL DEFAULT 20,
+                enabled BOOLEAN NOT NULL DEFAULT true,
+                updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )
+        "#).execute(&pool).await.unwrap();
+
+        sqlx::query(r#"
+            CREATE TABLE market_ticks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                exchange TEXT NOT NULL DEFAULT 'NSE',
+                timestamp TIMESTAMP NOT NULL,
+                ltp REAL NOT NULL,
+                bid REAL NOT NULL,
+                ask REAL NOT NULL,
+                volume INTEGER NOT NULL
+            )
+        "#).execute(&pool).await.unwrap();
+
+        sqlx::query(r#"
+            CREATE TABLE trades (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                symbol TEXT NOT NULL,
+                exchange TEXT NOT NULL,
+                order_id TEXT,
+                trade_type TEXT NOT NULL,
+                quantity INTEGER NOT NULL,
+                price REAL NOT NULL,
+                status TEXT NOT NULL,
+                executed_at TIMESTAMP NOT NULL,
+                strategy_id TEXT NOT NULL
+            )
+        "#).execute(&pool).await.unwrap();
+
         pool
     }
 
     async fn create_test_strategy(pool: &Pool<Sqlite>) -> String {
         let strategy_id = uuid::Uuid::new_v4().to_string();
         
-        sqlx::query!(
+        sqlx::query(
             r#"
             INSERT INTO strategy_params (
                 id, user_id, name, description, enabled, max_trades_per_day,
                 risk_percentage, stop_loss_percentage, take_profit_percentage, volume_threshold
             ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
-            "#,
-            strategy_id,
-            "test_user",
-            "Test Strategy",
-            "A test strategy for backtesting",
-            true,
-            10,
-            2.0,
-            2.0,
-            4.0,
-            1000
-        ).execute(pool).await.unwrap();
+            "#
+        )
+        .bind(&strategy_id)
+        .bind("test_user")
+        .bind("Test Strategy")
+        .bind("A test strategy for backtesting")
+        .bind(true)
+        .bind(10)
+        .bind(2.0)
+        .bind(2.0)
+        .bind(4.0)
+        .bind(1000)
+        .execute(pool).await.unwrap();
 
         strategy_id
     }
@@ -980,7 +2711,7 @@ mod tests {
             let base_price = Decimal::from(1000) + Decimal::from(i) / Decimal::from(10); // Slight uptrend
             
             // Add some volatility
-            let volatility = Decimal::from(5) * Decimal::from((i % 10) as f64 - 5.0) / Decimal::from(10);
+            let volatility = Decimal::from(5) * Decimal::from((i % 10) - 5) / Decimal::from(10);
             
             let open = base_price + volatility;
             let high = open + Decimal::from(2);
@@ -1025,9 +2756,7 @@ mod tests {
     #[tokio::test]
     async fn test_backtest_engine_creation() {
         let pool = Arc::new(create_test_db().await);
-        let strategy_manager = Arc::new(StrategyManager::new(pool.clone()));
-        
-        let engine = BacktestEngine::new(pool, strategy_manager);
+        let engine = BacktestEngine::new(pool);
         
         // Test that engine is created successfully
         assert!(engine.kite_client.is_none());
@@ -1036,8 +2765,7 @@ mod tests {
     #[tokio::test]
     async fn test_csv_import_validation() {
         let pool = Arc::new(create_test_db().await);
-        let strategy_manager = Arc::new(StrategyManager::new(pool.clone()));
-        let engine = BacktestEngine::new(pool, strategy_manager);
+        let engine = BacktestEngine::new(pool);
 
         let temp_file = create_test_csv_file();
         let file_path = temp_file.path().to_str().unwrap();
@@ -1053,8 +2781,7 @@ mod tests {
     #[tokio::test]
     async fn test_position_size_calculation() {
         let pool = Arc::new(create_test_db().await);
-        let strategy_manager = Arc::new(StrategyManager::new(pool.clone()));
-        let engine = BacktestEngine::new(pool, strategy_manager);
+        let engine = BacktestEngine::new(pool);
 
         let strategy = StrategyParams {
             id: "test_strategy".to_string(),
@@ -1067,6 +2794,13 @@ mod tests {
             stop_loss_percentage: 1.0, // 1% stop loss
             take_profit_percentage: 2.0,
             volume_threshold: 1000,
+            strategy_type: StrategyType::BollingerMeanReversion,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: Vec::new(),
+            rules: None,
+            archived_at: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
@@ -1090,27 +2824,1275 @@ mod tests {
         // Position value = 2000 / 0.01 * 100 = 200000 (but limited by cash)
         // Quantity = min(200000 / 1000, 100000 / 1000) = min(200, 100) = 100
         assert_eq!(position_size, 100);
+
+        // Per-strategy caps take the smaller of the two limits
+        let mut capped = strategy.clone();
+        capped.max_quantity_per_trade = Some(60);
+        assert_eq!(engine.calculate_position_size(&capped, &context, price), 60);
+
+        capped.max_capital = Some(25_500.0);
+        assert_eq!(engine.calculate_position_size(&capped, &context, price), 25);
+
+        capped.max_capital = Some(500.0);
+        assert_eq!(engine.calculate_position_size(&capped, &context, price), 0);
+    }
+
+    #[tokio::test]
+    async fn test_time_exit_closes_stale_position() {
+        let pool = Arc::new(create_test_db().await);
+        let engine = BacktestEngine::new(pool);
+
+        let mut strategy = StrategyParams::new("test_user", "Test", None, 10, 2.0, 1.0, 2.0, 1000);
+        strategy.max_holding_minutes = Some(30);
+
+        let entry_time = Utc.with_ymd_and_hms(2024, 1, 1, 9, 15, 0).unwrap();
+        let mut context = BacktestContext {
+            current_time: entry_time,
+            current_price: Decimal::from(1000),
+            current_volume: 2000,
+            portfolio_value: Decimal::from(100000),
+            cash_balance: Decimal::from(0),
+            open_positions: HashMap::new(),
+            historical_data: Vec::new(),
+            data_index: 0,
+        };
+        context.open_positions.insert("RELIANCE".to_string(), BacktestPosition {
+            symbol: "RELIANCE".to_string(),
+            trade_type: TradeType::Buy,
+            quantity: 10,
+            entry_price: Decimal::from(1000),
+            entry_time,
+            current_price: Decimal::from(1000),
+            unrealized_pnl: Decimal::ZERO,
+            entry_charges: Decimal::ZERO,
+        });
+
+        // Price stays inside the stop loss and take profit band
+        let flat = |minutes: i64| OHLCV::new(
+            entry_time + chrono::Duration::minutes(minutes),
+            Decimal::from(1000), Decimal::from(1005), Decimal::from(995), Decimal::from(1002), 1000,
+        );
+
+        assert!(engine.check_position_exits(&mut context, &strategy, &flat(25), &CostModel::disabled()).is_empty());
+
+        let exits = engine.check_position_exits(&mut context, &strategy, &flat(30), &CostModel::disabled());
+        assert_eq!(exits.len(), 1);
+        assert_eq!(exits[0].exit_reason, Some(TIME_EXIT_REASON.to_string()));
+        assert_eq!(exits[0].exit_price, Some(Decimal::from(1002)));
+        assert!(context.open_positions.is_empty());
+    }
+
+    #[test]
+    fn test_cost_model_charges() {
+        let costs = CostModel::default();
+        let price = Decimal::from(1000);
+
+        // Brokerage capped at 20, GST on brokerage, exchange and SEBI, stamp duty on buys, STT on sells
+        assert_eq!(costs.charges(TradeType::Buy, price, 100), Decimal::new(3022, 2));
+        assert_eq!(costs.charges(TradeType::Sell, price, 100), Decimal::new(5222, 2));
+
+        assert_eq!(costs.fill_price(TradeType::Buy, price), Decimal::new(100005, 2));
+        assert_eq!(costs.fill_price(TradeType::Sell, price), Decimal::new(99995, 2));
+
+        let bps = CostModel { slippage: Slippage::BasisPoints(10.0), ..CostModel::default() };
+        assert_eq!(bps.fill_price(TradeType::Buy, price), Decimal::from(1001));
+
+        let buy = costs.breakdown(TradeType::Buy, price, 100);
+        assert_eq!(buy.brokerage, Decimal::from(20));
+        assert_eq!(buy.stamp_duty, Decimal::from(3));
+        assert_eq!(buy.stt, Decimal::ZERO);
+        assert_eq!(buy.gst, Decimal::new(415, 2));
+        let sell = costs.breakdown(TradeType::Sell, price, 100);
+        assert_eq!(sell.stt, Decimal::from(25));
+        assert_eq!(sell.stamp_duty, Decimal::ZERO);
+        assert_eq!(sell.total, Decimal::new(5222, 2));
+
+        let disabled = CostModel::disabled();
+        assert_eq!(disabled.charges(TradeType::Sell, price, 100), Decimal::ZERO);
+        assert_eq!(disabled.fill_price(TradeType::Buy, price), price);
     }
 
     #[tokio::test]
-    async fn test_sma_calculation() {
+    async fn test_costs_reduce_round_trip_pnl() {
         let pool = Arc::new(create_test_db().await);
-        let strategy_manager = Arc::new(StrategyManager::new(pool.clone()));
-        let engine = BacktestEngine::new(pool, strategy_manager);
+        let engine = BacktestEngine::new(pool);
+        let strategy = StrategyParams::new("test_user", "Test", None, 10, 2.0, 1.0, 2.0, 1000);
+        let now = Utc::now();
+
+        let signal = |signal_type: SignalType, price: i64| TradingSignal {
+            symbol: "RELIANCE".to_string(),
+            signal_type,
+            strength: 1.0,
+            price: Decimal::from(price),
+            volume: 2000,
+            timestamp: now,
+            strategy_id: strategy.id.clone(),
+        };
+
+        let round_trip = |cost_model: &CostModel| {
+            let mut context = BacktestContext {
+                current_time: now,
+                current_price: Decimal::from(1000),
+                current_volume: 2000,
+                portfolio_value: Decimal::from(100000),
+                cash_balance: Decimal::from(100000),
+                open_positions: HashMap::new(),
+                historical_data: Vec::new(),
+                data_index: 0,
+            };
+            engine.execute_signal(&mut context, &signal(SignalType::Buy, 1000), &strategy, cost_model).unwrap();
+            let trade = engine.execute_signal(&mut context, &signal(SignalType::Sell, 1010), &strategy, cost_model).unwrap();
+            (trade, context.cash_balance)
+        };
+
+        let (free, free_cash) = round_trip(&CostModel::disabled());
+        assert_eq!(free.charges, Decimal::ZERO);
+        assert_eq!(free_cash, Decimal::from(100000) + free.pnl.unwrap());
+
+        let (costed, costed_cash) = round_trip(&CostModel::default());
+        assert!(costed.charges > Decimal::ZERO);
+        assert!(costed.pnl.unwrap() < free.pnl.unwrap());
+        assert_eq!(costed_cash, Decimal::from(100000) + costed.pnl.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_tick_backtest_fills_at_quote() {
+        let pool = Arc::new(create_test_db().await);
+        let engine = BacktestEngine::new(pool);
+        let strategy_id = create_test_strategy(&engine.db).await;
+
+        // One tick a minute ranging 100-101, then a breakout and a final quote
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 9, 15, 0).unwrap();
+        let mut ticks: Vec<(i64, f64, f64, f64, i64)> = (0..20)
+            .map(|i| {
+                let ltp = if i % 2 == 0 { 100.0 } else { 101.0 };
+                (i, ltp, ltp - 0.1, ltp + 0.1, 1000 * (i + 1))
+            })
+            .collect();
+        ticks.push((20, 103.0, 102.8, 103.2, 23000));
+        ticks.push((21, 103.0, 102.9, 103.1, 23100));
+
+        for (minute, ltp, bid, ask, volume) in &ticks {
+            sqlx::query("INSERT INTO market_ticks (symbol, timestamp, ltp, bid, ask, volume) VALUES ('RELIANCE', ?, ?, ?, ?, ?)")
+                .bind(start + chrono::Duration::minutes(*minute))
+                .bind(ltp)
+                .bind(bid)
+                .bind(ask)
+                .bind(volume)
+                .execute(&*engine.db)
+                .await
+                .unwrap();
+        }
+
+        let end = start + chrono::Duration::minutes(30);
+        let stored = engine.load_stored_ticks("RELIANCE", "NSE", start, end).await.unwrap();
+        assert_eq!(stored.len(), 22);
+
+        let mut strategy = StrategyParams::new("test_user", "Scalper", None, 10, 2.0, 1.0, 2.0, 1000);
+        strategy.strategy_type = StrategyType::MomentumBreakout;
+
+        let mut params = BacktestParams::new(
+            "test_user", &strategy.id, "RELIANCE", "NSE", start, end,
+            Timeframe::Tick, Decimal::from(100000), DataSource::Database,
+        );
+        params.cost_model = CostModel::disabled();
+
+        let result = engine.simulate_ticks_with(&strategy, None, &params, &stored).await.unwrap();
+        let closed: Vec<_> = result.trades.iter().filter(|t| t.exit_price.is_some()).collect();
+        assert_eq!(closed.len(), 1);
+
+        // Bought at the ask, sold at the bid
+        assert_eq!(closed[0].entry_price, to_decimal(103.2));
+        assert_eq!(closed[0].exit_price, Some(to_decimal(102.9)));
+
+        // Initial point plus one per one-minute bar
+        assert_eq!(result.equity_curve.len(), 23);
+
+        // Kite has no tick history to replay
+        let kite = BacktestParams::new(
+            "test_user", &strategy_id, "RELIANCE", "NSE", start, end,
+            Timeframe::Tick, Decimal::from(100000), DataSource::KiteAPI,
+        );
+        assert!(engine.run_backtest(kite).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_data_coverage_reports_gaps() {
+        let pool = Arc::new(create_test_db().await);
+        let engine = BacktestEngine::new(pool);
+
+        // Daily candles at the 09:15 IST open on Jan 1, 2, 4, 9 and 10 2024 (Jan 1 is a Monday)
+        let data: Vec<OHLCV> = [1, 2, 4, 9, 10].iter()
+            .map(|day| {
+                let price = Decimal::from(100);
+                OHLCV::new(Utc.with_ymd_and_hms(2024, 1, *day, 3, 45, 0).unwrap(), price, price, price, price, 1000)
+            })
+            .collect();
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Day1).await.unwrap();
+
+        let from = Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap();
+        let to = Utc.with_ymd_and_hms(2024, 1, 12, 18, 0, 0).unwrap();
+        let coverage = engine.data_coverage("RELIANCE", "NSE", Timeframe::Day1, from, to).await.unwrap();
+
+        assert_eq!(coverage.candles, 5);
+        assert_eq!(coverage.expected_days, 10);
+        assert_eq!(coverage.covered_days, 5);
+        assert!((coverage.coverage_percent - 50.0).abs() < 1e-9);
+
+        // Jan 3, then Jan 5 and 8 across the weekend, then Jan 11-12
+        let missing: Vec<usize> = coverage.gaps.iter().map(|gap| gap.missing_days).collect();
+        assert_eq!(missing, vec![1, 2, 2]);
+        assert_eq!(coverage.gaps[1].from, Utc.with_ymd_and_hms(2024, 1, 4, 18, 30, 0).unwrap());
+        assert_eq!(coverage.gaps[1].to, Utc.with_ymd_and_hms(2024, 1, 8, 18, 29, 59).unwrap());
+        assert_eq!(coverage.gaps[2].to, to);
+
+        // Other timeframes are tracked separately
+        let minutes = engine.data_coverage("RELIANCE", "NSE", Timeframe::Minute1, from, to).await.unwrap();
+        assert_eq!(minutes.covered_days, 0);
+        assert_eq!(minutes.gaps.len(), 1);
+
+        // A fully cached range loads without a Kite client, a gap needs one
+        let cached = BacktestParams::new(
+            "test_user", "strategy", "RELIANCE", "NSE", from, Utc.with_ymd_and_hms(2024, 1, 2, 18, 0, 0).unwrap(),
+            Timeframe::Day1, Decimal::from(100000), DataSource::KiteAPI,
+        );
+        assert_eq!(engine.load_historical_data(&cached).await.unwrap().len(), 2);
+
+        let uncached = BacktestParams { end_date: to, ..cached };
+        assert!(engine.load_historical_data(&uncached).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_coarser_timeframes_resample_stored_minutes() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
 
         let data = create_test_historical_data();
-        
-        // Test SMA calculation
-        let sma_5 = engine.calculate_sma(&data, 10, 5);
-        let sma_20 = engine.calculate_sma(&data, 25, 20);
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Minute1).await.unwrap();
+        let (from, to) = (data[0].timestamp, data[99].timestamp);
+
+        let five = engine.load_stored_historical_data("RELIANCE", "NSE", Timeframe::Minute5, from, to).await.unwrap();
+        assert_eq!(five.len(), 20);
+        assert_eq!(five[0].open, data[0].open);
+        assert_eq!(five[0].close, data[4].close);
+        assert_eq!(five[0].volume, data[..5].iter().map(|c| c.volume).sum::<i64>());
+
+        // A sparser stored dataset loses to the resampled minutes, a complete one is used as is
+        engine.store_historical_data("RELIANCE", "NSE", &data[..2], Timeframe::Minute5).await.unwrap();
+        assert_eq!(engine.load_stored_historical_data("RELIANCE", "NSE", Timeframe::Minute5, from, to).await.unwrap().len(), 20);
 
-        // SMA should be calculated correctly
-        assert!(sma_5 > Decimal::ZERO);
-        assert!(sma_20 > Decimal::ZERO);
+        let hourly: Vec<OHLCV> = (0..3).map(|i| OHLCV { timestamp: from + chrono::Duration::minutes(i * 30), ..data[0].clone() }).collect();
+        engine.store_historical_data("RELIANCE", "NSE", &hourly, Timeframe::Hour1).await.unwrap();
+        let stored = engine.load_stored_historical_data("RELIANCE", "NSE", Timeframe::Hour1, from, to).await.unwrap();
+        assert_eq!(stored.len(), 3);
+        assert_eq!(stored[1].timestamp, from + chrono::Duration::minutes(30));
+
+        // Backtests on a coarser timeframe run from the minute data alone
+        let params = BacktestParams::new(
+            "test_user", &strategy_id, "RELIANCE", "NSE", from, to,
+            Timeframe::Minute15, Decimal::from(100000), DataSource::Database,
+        );
+        let result = engine.run_backtest(params).await.unwrap();
+        assert_eq!(result.params.timeframe, Timeframe::Minute15);
+    }
+
+    #[tokio::test]
+    async fn test_historical_dataset_management() {
+        let pool = Arc::new(create_test_db().await);
+        let engine = BacktestEngine::new(pool);
+
+        let data = create_test_historical_data();
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Minute1).await.unwrap();
+        engine.store_historical_data("RELIANCE", "NSE", &data[..10], Timeframe::Day1).await.unwrap();
+        for minute in 0..3 {
+            sqlx::query("INSERT INTO market_ticks (symbol, timestamp, ltp, bid, ask, volume) VALUES ('RELIANCE', ?, 100.0, 99.9, 100.1, 1000)")
+                .bind(data[0].timestamp + chrono::Duration::minutes(minute))
+                .execute(&*engine.db)
+                .await
+                .unwrap();
+        }
+
+        let datasets = engine.list_historical_datasets().await.unwrap();
+        let rows: Vec<(Timeframe, usize)> = datasets.iter().map(|d| (d.timeframe, d.rows)).collect();
+        assert_eq!(rows, vec![(Timeframe::Day1, 10), (Timeframe::Minute1, 100), (Timeframe::Tick, 3)]);
+        assert_eq!(datasets[1].first_timestamp, data[0].timestamp);
+        assert_eq!(datasets[1].last_timestamp, data[99].timestamp);
+
+        // Deleting a range only touches the requested timeframe
+        let deleted = engine.delete_historical_data("RELIANCE", "NSE", Timeframe::Minute1, data[0].timestamp, data[49].timestamp).await.unwrap();
+        assert_eq!(deleted, 50);
+        let datasets = engine.list_historical_datasets().await.unwrap();
+        assert_eq!(datasets[0].rows, 10);
+        assert_eq!(datasets[1].rows, 50);
+        assert_eq!(datasets[1].first_timestamp, data[50].timestamp);
+
+        let deleted = engine.delete_historical_data("RELIANCE", "NSE", Timeframe::Tick, data[0].timestamp, data[99].timestamp).await.unwrap();
+        assert_eq!(deleted, 3);
+        assert!(engine.delete_historical_data("RELIANCE", "NSE", Timeframe::Day1, data[1].timestamp, data[0].timestamp).await.is_err());
+
+        // Re-downloading needs Kite and never applies to ticks
+        assert!(engine.redownload_historical_data("RELIANCE", "NSE", Timeframe::Minute1, data[0].timestamp, data[99].timestamp).await.is_err());
+        assert!(engine.redownload_historical_data("RELIANCE", "NSE", Timeframe::Tick, data[0].timestamp, data[99].timestamp).await.is_err());
+        assert_eq!(engine.list_historical_datasets().await.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_benchmark_comparison() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 9, 15, 0).unwrap();
+        let closes = [100, 110, 99, 121];
+        let benchmark: Vec<OHLCV> = closes.iter().enumerate()
+            .map(|(i, close)| {
+                let close = Decimal::from(*close);
+                OHLCV::new(start + chrono::Duration::days(i as i64), close, close, close, close, 1000)
+            })
+            .collect();
+
+        // Strategy that tracks the index exactly
+        let tracking: Vec<EquityPoint> = closes.iter().enumerate()
+            .map(|(i, close)| EquityPoint::new(start + chrono::Duration::days(i as i64), Decimal::from(close * 1000)))
+            .collect();
+        let comparison = BenchmarkComparison::calculate("NIFTY 50", &tracking, &benchmark).unwrap();
+        assert!((comparison.benchmark_return - 21.0).abs() < 1e-9);
+        assert!((comparison.beta - 1.0).abs() < 1e-9);
+        assert!(comparison.alpha.abs() < 1e-9);
+        assert!((comparison.benchmark_max_drawdown - 10.0).abs() < 1e-9);
+        assert!(comparison.relative_drawdown.abs() < 1e-9);
+        assert_eq!(comparison.equity_curve[3].equity, Decimal::from(121000));
+
+        // Flat cash curve: no market exposure, all of its (zero) return is alpha
+        let flat: Vec<EquityPoint> = (0..4)
+            .map(|i| EquityPoint::new(start + chrono::Duration::days(i), Decimal::from(100000)))
+            .collect();
+        let comparison = BenchmarkComparison::calculate("NIFTY 50", &flat, &benchmark).unwrap();
+        assert_eq!(comparison.beta, 0.0);
+        assert_eq!(comparison.alpha, 0.0);
+        assert!((comparison.relative_drawdown + 10.0).abs() < 1e-9);
+
+        assert!(BenchmarkComparison::calculate("NIFTY 50", &flat, &[]).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_equity_statistics_are_stored_and_compared() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
+
+        // 09:15 IST on Monday 1 Jan 2024 through the Friday close
+        let open = Utc.with_ymd_and_hms(2024, 1, 1, 3, 45, 0).unwrap();
+        let params = BacktestParams::new(
+            "test_user", &strategy_id, "RELIANCE", "NSE", open, open + chrono::Duration::days(4) + chrono::Duration::minutes(375),
+            Timeframe::Minute15, Decimal::from(100000), DataSource::Database,
+        );
+        let mut result = BacktestResult::new(params);
+
+        // A win, two losses in a row, then a win, each held for an hour
+        for (day, exit_price) in [(0, 1100), (1, 950), (2, 970), (3, 1020)] {
+            let entry = open + chrono::Duration::days(day);
+            let mut trade = BacktestTrade::new(&result.id, "RELIANCE", TradeType::Buy, entry, Decimal::from(1000), 10);
+            trade.close(entry + chrono::Duration::minutes(60), Decimal::from(exit_price), "Signal exit");
+            result.trades.push(trade);
+        }
+        for (day, equity) in [100000, 101000, 100500, 100200, 100400].iter().enumerate() {
+            result.equity_curve.push(EquityPoint::new(open + chrono::Duration::days(day as i64), Decimal::from(*equity)));
+        }
+        result.calculate_metrics();
+
+        let cagr = ((100400.0_f64 / 100000.0).powf(252.0 / 5.0) - 1.0) * 100.0;
+        assert_eq!(result.trading_days, 5);
+        assert!((result.cagr - cagr).abs() < 1e-9);
+        assert!((result.calmar_ratio - cagr / (800.0 / 101000.0 * 100.0)).abs() < 1e-9);
+        assert!(result.sortino_ratio > result.sharpe_ratio && result.sharpe_ratio > 0.0);
+        assert!((result.exposure_percent - 240.0 / 1875.0 * 100.0).abs() < 1e-9);
+        // No new high after Tuesday's open: three full sessions
+        assert_eq!(result.longest_flat_minutes, 3 * 375);
+        assert_eq!(result.max_consecutive_losses, 2);
+
+        engine.store_backtest_result(&result).await.unwrap();
+        let detail = engine.get_backtest_detail(&result.id).await.unwrap();
+        assert_eq!(detail.cagr, result.cagr);
+        assert_eq!(detail.longest_flat_minutes, result.longest_flat_minutes);
+        assert_eq!(detail.max_consecutive_losses, 2);
+
+        let comparison = engine.compare_backtests(vec![result.id.as_str()]).await.unwrap();
+        assert_eq!(comparison.metrics_comparison["sortino_ratio"], vec![result.sortino_ratio]);
+        assert_eq!(comparison.metrics_comparison["max_consecutive_losses"], vec![2.0]);
+        assert_eq!(comparison.metrics_comparison["longest_flat_minutes"], vec![1125.0]);
+    }
+
+    #[tokio::test]
+    async fn test_run_backtest_includes_benchmark() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
+
+        let data = create_test_historical_data();
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Minute1).await.unwrap();
+        engine.store_historical_data("NIFTY 50", "NSE", &data, Timeframe::Minute1).await.unwrap();
+
+        let params = BacktestParams::new(
+            "test_user", &strategy_id, "RELIANCE", "NSE",
+            data[0].timestamp, data[data.len() - 1].timestamp,
+            Timeframe::Minute1, Decimal::from(100000), DataSource::Database,
+        );
+        let result = engine.run_backtest(params).await.unwrap();
+        let benchmark = result.benchmark.as_ref().unwrap();
+        assert_eq!(benchmark.symbol, "NIFTY 50");
+        assert_eq!(benchmark.equity_curve.len(), result.equity_curve.len());
+
+        let stored = engine.get_backtest_detail(&result.id).await.unwrap();
+        assert_eq!(stored.benchmark.unwrap().beta, benchmark.beta);
+
+        // Missing index data leaves the benchmark out instead of failing the run
+        let other = BacktestParams::new(
+            "test_user", &strategy_id, "RELIANCE", "NSE",
+            data[0].timestamp, data[data.len() - 1].timestamp,
+            Timeframe::Minute1, Decimal::from(100000), DataSource::Database,
+        );
+        sqlx::query("DELETE FROM historical_data WHERE symbol = 'NIFTY 50'").execute(&*engine.db).await.unwrap();
+        assert!(engine.run_backtest(other).await.unwrap().benchmark.is_none());
+    }
+
+    #[tokio::test]
+    async fn test_signals_follow_strategy_type() {
+        let pool = Arc::new(create_test_db().await);
+        let engine = BacktestEngine::new(pool);
+
+        let mut strategy = StrategyParams::new("test_user", "Breakout", None, 10, 2.0, 1.0, 2.0, 1000);
+        strategy.strategy_type = StrategyType::MomentumBreakout;
+
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 1, 9, 15, 0).unwrap();
+        let mut data: Vec<OHLCV> = (0..20)
+            .map(|i| {
+                let close = Decimal::from(100 + i % 2);
+                OHLCV::new(base_time + chrono::Duration::days(i), close, close + Decimal::ONE, close - Decimal::ONE, close, 1500)
+            })
+            .collect();
+        data.push(OHLCV::new(base_time + chrono::Duration::days(20), Decimal::from(101), Decimal::from(106), Decimal::from(101), Decimal::from(105), 3000));
+
+        let params = BacktestParams::new(
+            "test_user", &strategy.id, "RELIANCE", "NSE",
+            data[0].timestamp, data[20].timestamp,
+            Timeframe::Day1, Decimal::from(100000), DataSource::KiteAPI,
+        );
+
+        let mut context = BacktestContext {
+            current_time: data[20].timestamp,
+            current_price: data[20].close,
+            current_volume: data[20].volume,
+            portfolio_value: Decimal::from(100000),
+            cash_balance: Decimal::from(100000),
+            open_positions: HashMap::new(),
+            historical_data: data.clone(),
+            data_index: 19,
+        };
+
+        // No breakout inside the range
+        let signals = engine.generate_signals(&strategy, &context, &data[19], &params, None).await.unwrap();
+        assert!(signals.is_empty());
+
+        // Close above the 20-bar high triggers an entry
+        context.data_index = 20;
+        let signals = engine.generate_signals(&strategy, &context, &data[20], &params, None).await.unwrap();
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].signal_type, SignalType::Buy);
+
+        // Mean reversion sees no band re-entry on the same data
+        strategy.strategy_type = StrategyType::BollingerMeanReversion;
+        let signals = engine.generate_signals(&strategy, &context, &data[20], &params, None).await.unwrap();
+        assert!(signals.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_custom_rules_respect_warm_up() {
+        let pool = Arc::new(create_test_db().await);
+        let engine = BacktestEngine::new(pool);
+
+        let mut strategy = StrategyParams::new("test_user", "Trend", None, 10, 2.0, 1.0, 2.0, 1000);
+        strategy.strategy_type = StrategyType::Custom;
+        strategy.rules = Some(StrategyRules {
+            entry: vec![RuleCondition {
+                left: IndicatorSource::Close,
+                comparison: RuleComparison::Above,
+                right: IndicatorSource::Ema { period: 5 },
+            }],
+            exit: Vec::new(),
+        });
+
+        // Steady uptrend, so the close is above the EMA as soon as it has a value
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 1, 3, 45, 0).unwrap();
+        let data: Vec<OHLCV> = (0..30)
+            .map(|i| {
+                let close = Decimal::from(100 + i);
+                OHLCV::new(base_time + chrono::Duration::days(i), close, close + Decimal::ONE, close - Decimal::ONE, close, 1500)
+            })
+            .collect();
+
+        let params = BacktestParams::new(
+            "test_user", &strategy.id, "RELIANCE", "NSE",
+            data[0].timestamp, data[29].timestamp,
+            Timeframe::Day1, Decimal::from(100000), DataSource::Database,
+        );
+
+        let result = engine.simulate(&strategy, &params, data.clone()).await.unwrap();
+        assert!(result.total_trades > 0);
+
+        // Nothing is entered until the EMA has three periods of history
+        let first_entry = result.trades.iter().map(|t| t.entry_time).min().unwrap();
+        assert!(first_entry >= data[14].timestamp);
+    }
+
+    #[tokio::test]
+    async fn test_intraday_sessions_are_squared_off() {
+        let pool = Arc::new(create_test_db().await);
+        let engine = BacktestEngine::new(pool);
+
+        // Enters whenever it is flat and allowed to
+        let mut strategy = StrategyParams::new("test_user", "Always long", None, 10, 2.0, 1.0, 2.0, 1000);
+        strategy.strategy_type = StrategyType::Custom;
+        strategy.rules = Some(StrategyRules {
+            entry: vec![RuleCondition {
+                left: IndicatorSource::Close,
+                comparison: RuleComparison::Above,
+                right: IndicatorSource::Constant { value: 0.0 },
+            }],
+            exit: Vec::new(),
+        });
+
+        // A pre-open candle and a full Thursday session, candles on the Republic Day
+        // holiday, then the first hour of Monday and Tuesday
+        let ist = |day: u32, hour: u32, minute: u32| {
+            chrono_tz::Asia::Kolkata.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap().with_timezone(&Utc)
+        };
+        let mut times = vec![ist(25, 9, 0)];
+        for (day, bars) in [(25, 75), (26, 12), (29, 12), (30, 12)] {
+            times.extend((0..bars).map(|i| ist(day, 9, 15) + chrono::Duration::minutes(i * 5)));
+        }
+        let data: Vec<OHLCV> = times.into_iter()
+            .map(|time| {
+                let price = Decimal::from(100);
+                OHLCV::new(time, price, price, price, price, 2000)
+            })
+            .collect();
+
+        let mut params = BacktestParams::new(
+            "test_user", &strategy.id, "RELIANCE", "NSE",
+            data[0].timestamp, data[data.len() - 1].timestamp,
+            Timeframe::Minute5, Decimal::from(100000), DataSource::Database,
+        );
+        params.cost_model = CostModel::disabled();
+
+        let result = engine.simulate(&strategy, &params, data).await.unwrap();
+        let closed: Vec<_> = result.trades.iter().filter(|t| t.exit_time.is_some()).collect();
+        let exits: Vec<_> = closed.iter().map(|t| (t.exit_time.unwrap(), t.exit_reason.clone().unwrap())).collect();
+
+        // Squared off at 15:20, then on the gap before Tuesday when Monday's close is missing
+        assert_eq!(exits, vec![
+            (ist(25, 15, 20), SQUARE_OFF_REASON.to_string()),
+            (ist(29, 10, 10), SQUARE_OFF_REASON.to_string()),
+            (ist(30, 10, 10), "End of backtest".to_string()),
+        ]);
+        let entries: Vec<_> = closed.iter().map(|t| t.entry_time).collect();
+        assert_eq!(entries, vec![ist(25, 9, 15), ist(29, 9, 15), ist(30, 9, 15)]);
+
+        // Pre-open and holiday candles are skipped entirely
+        assert_eq!(result.equity_curve.len(), 1 + 75 + 12 + 12);
+
+        // Durations only count session time on trading days
+        assert_eq!(result.trading_days, 3);
+        assert!((result.average_holding_minutes - (365.0 + 55.0 + 55.0) / 3.0).abs() < 1e-9);
+    }
+
+    #[tokio::test]
+    async fn test_trend_filter_applies_to_backtests() {
+        let pool = Arc::new(create_test_db().await);
+        let engine = BacktestEngine::new(pool);
+
+        let mut strategy = StrategyParams::new("test_user", "Breakout", None, 10, 2.0, 1.0, 2.0, 1000);
+        strategy.strategy_type = StrategyType::MomentumBreakout;
+
+        // Range-bound closes, then a breakout on heavy volume
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 1, 3, 45, 0).unwrap();
+        let mut data: Vec<OHLCV> = (0..20)
+            .map(|i| {
+                let close = Decimal::from(100 + i % 2);
+                OHLCV::new(base_time + chrono::Duration::days(i), close, close + Decimal::ONE, close - Decimal::ONE, close, 1500)
+            })
+            .collect();
+        data.push(OHLCV::new(base_time + chrono::Duration::days(20), Decimal::from(101), Decimal::from(106), Decimal::from(101), Decimal::from(105), 3000));
+
+        let params = BacktestParams::new(
+            "test_user", &strategy.id, "RELIANCE", "NSE",
+            data[0].timestamp, data[20].timestamp,
+            Timeframe::Day1, Decimal::from(100000), DataSource::Database,
+        );
+
+        let unfiltered = engine.simulate(&strategy, &params, data.clone()).await.unwrap();
+        assert!(unfiltered.total_trades > 0);
+
+        sqlx::query("INSERT INTO strategy_trend_filters (strategy_id, index_symbol, ma_period) VALUES (?, 'NIFTY 50', 3)")
+            .bind(&strategy.id)
+            .execute(&*engine.db)
+            .await
+            .unwrap();
+
+        let index = |step: i64| -> Vec<OHLCV> {
+            (-10..21)
+                .map(|i| {
+                    let close = Decimal::from(20000 + step * i);
+                    OHLCV::new(base_time + chrono::Duration::days(i), close, close, close, close, 0)
+                })
+                .collect()
+        };
+
+        // A falling index blocks the long entry
+        engine.store_historical_data("NIFTY 50", "NSE", &index(-50), Timeframe::Day1).await.unwrap();
+        let bearish = engine.simulate(&strategy, &params, data.clone()).await.unwrap();
+        assert_eq!(bearish.total_trades, 0);
+
+        // A rising index lets it through
+        engine.store_historical_data("NIFTY 50", "NSE", &index(50), Timeframe::Day1).await.unwrap();
+        let bullish = engine.simulate(&strategy, &params, data).await.unwrap();
+        assert_eq!(bullish.total_trades, unfiltered.total_trades);
+    }
+
+    #[tokio::test]
+    async fn test_trend_filter_passes_exits() {
+        let pool = Arc::new(create_test_db().await);
+        let engine = BacktestEngine::new(pool);
+
+        // Exits on every bar
+        let mut strategy = StrategyParams::new("test_user", "Always exit", None, 10, 2.0, 1.0, 2.0, 1000);
+        strategy.strategy_type = StrategyType::Custom;
+        strategy.rules = Some(StrategyRules {
+            entry: Vec::new(),
+            exit: vec![RuleCondition {
+                left: IndicatorSource::Close,
+                comparison: RuleComparison::Above,
+                right: IndicatorSource::Constant { value: 0.0 },
+            }],
+        });
+
+        let base_time = Utc.with_ymd_and_hms(2024, 1, 1, 3, 45, 0).unwrap();
+        let data: Vec<OHLCV> = (0..5)
+            .map(|i| {
+                let close = Decimal::from(100 + i);
+                OHLCV::new(base_time + chrono::Duration::days(i), close, close, close, close, 1500)
+            })
+            .collect();
+
+        let params = BacktestParams::new(
+            "test_user", &strategy.id, "RELIANCE", "NSE",
+            data[0].timestamp, data[4].timestamp,
+            Timeframe::Day1, Decimal::from(100000), DataSource::Database,
+        );
+
+        // A rising index puts the market in a bullish regime
+        let filter = TrendFilterConfig::new(&strategy.id, "NIFTY 50", 3);
+        let mut replay = TrendFilterReplay {
+            tracker: MarketRegimeTracker::new(Timeframe::Day1.duration_minutes() * 60),
+            candles: (-5..5)
+                .map(|i| {
+                    let close = Decimal::from(20000 + 50 * i);
+                    OHLCV::new(base_time + chrono::Duration::days(i), close, close, close, close, 0)
+                })
+                .collect(),
+            next: 0,
+            filter,
+        };
+
+        let mut context = BacktestContext {
+            current_time: data[4].timestamp,
+            current_price: data[4].close,
+            current_volume: data[4].volume,
+            portfolio_value: Decimal::from(100000),
+            cash_balance: Decimal::from(90000),
+            open_positions: HashMap::new(),
+            historical_data: data.clone(),
+            data_index: 4,
+        };
+        context.open_positions.insert("RELIANCE".to_string(), BacktestPosition {
+            symbol: "RELIANCE".to_string(),
+            trade_type: TradeType::Buy,
+            quantity: 100,
+            entry_price: Decimal::from(100),
+            entry_time: data[0].timestamp,
+            current_price: data[4].close,
+            unrealized_pnl: Decimal::from(400),
+            entry_charges: Decimal::ZERO,
+        });
+
+        // The strategy's exit of the open long is not held back by the bullish regime
+        let signals = engine.generate_signals(&strategy, &context, &data[4], &params, Some(&mut replay)).await.unwrap();
+        assert_eq!(signals.len(), 1);
+        assert_eq!(signals[0].signal_type, SignalType::Sell);
+    }
+
+    #[tokio::test]
+    async fn test_parameter_suggestions() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
+
+        // Oscillating daily closes with periodic sharp dips below the bands
+        let start = Utc::now() - chrono::Duration::days(100);
+        let data: Vec<OHLCV> = (0..90)
+            .map(|i| {
+                let close = match i % 15 {
+                    13 => Decimal::from(92),
+                    _ => Decimal::from(100 + i % 2),
+                };
+                OHLCV::new(start + chrono::Duration::days(i), close, close + Decimal::ONE, close - Decimal::ONE, close, 1000 + i * 10)
+            })
+            .collect();
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Day1).await.unwrap();
+
+        let report = engine
+            .suggest_parameters("test_user", &strategy_id, "RELIANCE", "NSE", Timeframe::Day1, 120)
+            .await
+            .unwrap();
+
+        assert_eq!(report.candles, 90);
+        assert_eq!(report.combinations_tested % 15, 0);
+        assert!(report.suggestions.len() <= 5);
+        assert!(report.suggestions.iter().all(|s| s.total_trades >= 3));
+        assert!(report.suggestions.windows(2).all(|w| w[0].sharpe_ratio >= w[1].sharpe_ratio));
+
+        // Running one backtest at a time gives the same report
+        let sequential = engine
+            .sweep_parameters("test_user", &strategy_id, "RELIANCE", "NSE", Timeframe::Day1, 120, 1)
+            .await
+            .unwrap();
+        assert_eq!(sequential.combinations_tested, report.combinations_tested);
+        assert_eq!(sequential.current.final_pnl, report.current.final_pnl);
+        let pnls = |report: &ParameterSweepReport| report.suggestions.iter().map(|s| s.final_pnl).collect::<Vec<_>>();
+        assert_eq!(pnls(&sequential), pnls(&report));
+
+        // Other users' strategies and empty ranges are rejected
+        assert!(engine.suggest_parameters("other_user", &strategy_id, "RELIANCE", "NSE", Timeframe::Day1, 120).await.is_err());
+        assert!(engine.suggest_parameters("test_user", &strategy_id, "TCS", "NSE", Timeframe::Day1, 120).await.is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_optimize_parameters() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
+
+        let start = Utc::now() - chrono::Duration::days(100);
+        let data: Vec<OHLCV> = (0..90)
+            .map(|i| {
+                let close = match i % 15 {
+                    13 => Decimal::from(92),
+                    _ => Decimal::from(100 + i % 2),
+                };
+                OHLCV::new(start + chrono::Duration::days(i), close, close + Decimal::ONE, close - Decimal::ONE, close, 1000 + i * 10)
+            })
+            .collect();
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Day1).await.unwrap();
+
+        let mut request = OptimizationRequest {
+            strategy_id: strategy_id.clone(),
+            symbol: "RELIANCE".to_string(),
+            exchange: "NSE".to_string(),
+            timeframe: Timeframe::Day1,
+            start_date: start - chrono::Duration::days(1),
+            end_date: Utc::now(),
+            grid: OptimizationGrid {
+                stop_loss: ParameterRange::new(1.0, 3.0, 1.0),
+                take_profit: ParameterRange::new(2.0, 4.0, 1.0),
+                volume_thresholds: vec![1000, 1500],
+                rank_by: OptimizationMetric::FinalPnl,
+                min_trades: 0,
+                max_concurrency: 3,
+            },
+        };
+
+        let report = engine.optimize_parameters("test_user", &request).await.unwrap();
+
+        // Take profit must exceed stop loss: 6 of 9 cells per volume threshold
+        assert_eq!(report.candles, 90);
+        assert_eq!(report.combinations_tested, 12);
+        assert_eq!(report.results.len(), 12);
+        assert!(report.results.windows(2).all(|w| w[0].final_pnl >= w[1].final_pnl));
+
+        assert_eq!(report.heatmap.stop_loss_values, vec![1.0, 2.0, 3.0]);
+        assert_eq!(report.heatmap.take_profit_values, vec![2.0, 3.0, 4.0]);
+        assert!(report.heatmap.scores[0][0].is_some());
+        assert!(report.heatmap.scores[0][1].is_none());
+        assert!(report.heatmap.scores[2][2].is_some());
+
+        // Oversized grids and inverted ranges are rejected
+        request.grid.stop_loss = ParameterRange::new(0.01, 50.0, 0.01);
+        assert!(engine.optimize_parameters("test_user", &request).await.is_err());
+
+        request.grid.stop_loss = ParameterRange::new(3.0, 1.0, 1.0);
+        assert!(engine.optimize_parameters("test_user", &request).await.is_err());
+
+        request.grid.stop_loss = ParameterRange::new(1.0, 3.0, 1.0);
+        assert!(engine.optimize_parameters("other_user", &request).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_walk_forward() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
+
+        let start = Utc::now() - chrono::Duration::days(100);
+        let data: Vec<OHLCV> = (0..90)
+            .map(|i| {
+                let close = match i % 15 {
+                    13 => Decimal::from(92),
+                    _ => Decimal::from(100 + i % 2),
+                };
+                OHLCV::new(start + chrono::Duration::days(i), close, close + Decimal::ONE, close - Decimal::ONE, close, 1000 + i * 10)
+            })
+            .collect();
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Day1).await.unwrap();
+
+        let mut request = WalkForwardRequest {
+            strategy_id: strategy_id.clone(),
+            symbol: "RELIANCE".to_string(),
+            exchange: "NSE".to_string(),
+            timeframe: Timeframe::Day1,
+            start_date: start - chrono::Duration::days(1),
+            end_date: Utc::now(),
+            in_sample_candles: 40,
+            out_of_sample_candles: 25,
+            grid: OptimizationGrid {
+                stop_loss: ParameterRange::new(1.0, 2.0, 1.0),
+                take_profit: ParameterRange::new(3.0, 4.0, 1.0),
+                volume_thresholds: Vec::new(),
+                rank_by: OptimizationMetric::FinalPnl,
+                min_trades: 0,
+                max_concurrency: 2,
+            },
+        };
+
+        let report = engine.walk_forward("test_user", &request).await.unwrap();
+
+        // Windows 0..40 -> 40..65 and 25..65 -> 65..90
+        assert_eq!(report.candles, 90);
+        assert_eq!(report.windows.len() + report.skipped_windows, 2);
+        for window in &report.windows {
+            assert!(window.in_sample_end < window.out_of_sample_start);
+            assert_eq!(window.in_sample.stop_loss_percentage, window.out_of_sample.stop_loss_percentage);
+        }
+        assert_eq!(report.total_trades, report.windows.iter().map(|w| w.out_of_sample.total_trades).sum::<i32>());
+
+        // Windows must cover the warmup and fit in the data
+        request.out_of_sample_candles = 10;
+        assert!(engine.walk_forward("test_user", &request).await.is_err());
+
+        request.out_of_sample_candles = 60;
+        assert!(engine.walk_forward("test_user", &request).await.is_err());
+    }
+
+    #[test]
+    fn test_parameter_range_values() {
+        assert_eq!(ParameterRange::new(0.5, 1.5, 0.25).values(), vec![0.5, 0.75, 1.0, 1.25, 1.5]);
+        assert_eq!(ParameterRange::new(0.1, 0.3, 0.1).values(), vec![0.1, 0.2, 0.3]);
+        assert_eq!(ParameterRange::new(2.0, 2.0, 1.0).values(), vec![2.0]);
+        assert!(ParameterRange::new(0.0, 1.0, 0.5).values().is_empty());
+        assert!(ParameterRange::new(1.0, 2.0, 0.0).values().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_validate_strategy() {
+        let pool = Arc::new(create_test_db().await);
+        let engine = BacktestEngine::new(pool);
+        
+        let strategy = StrategyParams::new("test_user", "Test", None, 10, 2.0, 1.0, 2.0, 100);
+        let symbols = vec![("RELIANCE".to_string(), "NSE".to_string())];
+        let mut config = EnableValidationConfig::default();
+        config.mode = ValidationMode::Block;
+        
+        // Nothing stored yet: reported but never blocks
+        let summary = engine.validate_strategy(&strategy, &symbols, &config).await.unwrap();
+        assert!(summary.symbols_tested.is_empty());
+        assert!(!summary.passed);
+        assert!(!summary.blocked);
+        
+        // Flat prices for a week of sessions never trigger an entry
+        let start = (Utc::now() - chrono::Duration::days(7)).date_naive().and_hms_opt(3, 45, 0).unwrap().and_utc();
+        let data: Vec<OHLCV> = (0..7)
+            .flat_map(|day| (0..40).map(move |bar| {
+                let close = Decimal::from(100);
+                OHLCV::new(start + chrono::Duration::days(day) + chrono::Duration::minutes(bar * 5), close, close, close, close, 1000)
+            }))
+            .collect();
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Minute5).await.unwrap();
         
-        // For insufficient data, should return zero
-        let sma_insufficient = engine.calculate_sma(&data, 5, 20);
-        assert_eq!(sma_insufficient, Decimal::ZERO);
+        let summary = engine.validate_strategy(&strategy, &symbols, &config).await.unwrap();
+        assert_eq!(summary.symbols_tested, vec!["RELIANCE".to_string()]);
+        assert_eq!(summary.candles, 5 * 40);
+        assert_eq!(summary.total_trades, 0);
+        assert!(summary.blocked);
+        
+        config.mode = ValidationMode::Warn;
+        let summary = engine.validate_strategy(&strategy, &symbols, &config).await.unwrap();
+        assert!(!summary.passed);
+        assert!(!summary.blocked);
+    }
+    
+    #[test]
+    fn test_validation_flags_catastrophic_loss() {
+        let config = EnableValidationConfig::default();
+        let params = BacktestParams::new(
+            "test_user", "strategy", "RELIANCE", "NSE", Utc::now(), Utc::now(),
+            Timeframe::Minute5, Decimal::from(100000), DataSource::Database,
+        );
+        let mut result = BacktestResult::new(params);
+        result.total_trades = 4;
+        result.final_pnl = Decimal::from(-8000);
+        
+        let mut summary = StrategyValidationSummary::new("strategy", &config);
+        summary.add_result(&result, 300);
+        summary.evaluate(&config);
+        
+        assert!(!summary.passed);
+        assert_eq!(summary.issues.len(), 1);
+        assert!(!summary.blocked);
+    }
+
+    #[tokio::test]
+    async fn test_monte_carlo_is_stored_with_run() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 9, 15, 0).unwrap();
+        let params = BacktestParams::new(
+            "test_user", &strategy_id, "RELIANCE", "NSE", start, start + chrono::Duration::days(5),
+            Timeframe::Day1, Decimal::from(100000), DataSource::Database,
+        );
+        let mut result = BacktestResult::new(params);
+        for (i, exit_price) in [1050, 980, 1020, 990, 1030].iter().enumerate() {
+            let entry_time = start + chrono::Duration::days(i as i64);
+            let mut trade = BacktestTrade::new(&result.id, "RELIANCE", TradeType::Buy, entry_time, Decimal::from(1000), 10);
+            trade.close(entry_time + chrono::Duration::hours(2), Decimal::from(*exit_price), "Signal exit");
+            result.trades.push(trade);
+        }
+        result.calculate_metrics();
+        engine.store_backtest_result(&result).await.unwrap();
+
+        assert!(engine.get_monte_carlo(&result.id).await.unwrap().is_none());
+
+        let config = MonteCarloConfig { iterations: 200, seed: Some(7), ..MonteCarloConfig::default() };
+        let simulation = engine.run_monte_carlo(&result.id, &config).await.unwrap();
+        assert_eq!(simulation.trades, 5);
+        assert!(simulation.final_pnl.lower <= simulation.final_pnl.upper);
+        assert!(simulation.max_drawdown.min >= 0.0);
+
+        let stored = engine.get_monte_carlo(&result.id).await.unwrap().unwrap();
+        assert_eq!(stored.final_pnl.mean, simulation.final_pnl.mean);
+
+        // Invalid settings and unknown runs are rejected
+        let too_few = MonteCarloConfig { iterations: 5, ..config.clone() };
+        assert!(engine.run_monte_carlo(&result.id, &too_few).await.is_err());
+        assert!(engine.run_monte_carlo("missing", &config).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_backtest_trade_drill_down() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
+
+        // Flat daily closes with a spike up on day 2 and a dip on day 3
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 3, 45, 0).unwrap();
+        let day = |d: i64| start + chrono::Duration::days(d);
+        let data: Vec<OHLCV> = (0..10)
+            .map(|d| {
+                let (high, low) = match d {
+                    2 => (1040, 990),
+                    3 => (1010, 970),
+                    _ => (1005, 995),
+                };
+                OHLCV::new(day(d), Decimal::from(1000), Decimal::from(high), Decimal::from(low), Decimal::from(1000), 1000)
+            })
+            .collect();
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Day1).await.unwrap();
+
+        let params = BacktestParams::new(
+            "test_user", &strategy_id, "RELIANCE", "NSE", day(0), day(9),
+            Timeframe::Day1, Decimal::from(100000), DataSource::Database,
+        );
+        let mut result = BacktestResult::new(params);
+        let mut long = BacktestTrade::new(&result.id, "RELIANCE", TradeType::Buy, day(1), Decimal::from(1000), 10);
+        result.trades.push(long.clone());
+        long.close(day(4), Decimal::from(1020), "Take profit");
+        result.trades.push(long);
+        let mut short = BacktestTrade::new(&result.id, "RELIANCE", TradeType::Sell, day(6), Decimal::from(1000), 5);
+        short.close(day(7), Decimal::from(990), "Signal exit");
+        result.trades.push(short);
+        result.calculate_metrics();
+        engine.store_backtest_result(&result).await.unwrap();
+
+        // Only closed trades are listed, oldest first
+        let page = engine.get_backtest_trades(&result.id, 1, 1, 1).await.unwrap();
+        assert_eq!(page.total_trades, 2);
+        assert_eq!(page.total_pages, 2);
+        assert_eq!(page.trades.len(), 1);
+
+        // Held through days 2-4: up 40 and down 30 a share at the extremes
+        let drill_down = &page.trades[0];
+        assert_eq!(drill_down.trade.exit_reason, Some("Take profit".to_string()));
+        assert_eq!(drill_down.max_favorable_excursion, Decimal::from(400));
+        assert_eq!(drill_down.max_adverse_excursion, Decimal::from(300));
+        assert!((drill_down.mfe_percent - 4.0).abs() < 1e-9);
+        assert!((drill_down.mae_percent - 3.0).abs() < 1e-9);
+
+        // One candle of context either side of entry and exit
+        let times: Vec<_> = drill_down.candles.iter().map(|c| c.timestamp).collect();
+        assert_eq!(times, (0..6).map(day).collect::<Vec<_>>());
+
+        let page = engine.get_backtest_trades(&result.id, 2, 1, 0).await.unwrap();
+        assert_eq!(page.trades[0].trade.trade_type, TradeType::Sell);
+        assert_eq!(page.trades[0].candles.len(), 2);
+        assert!(engine.get_backtest_trades(&result.id, 3, 1, 0).await.unwrap().trades.is_empty());
+
+        // Bad paging and unknown runs are rejected
+        assert!(engine.get_backtest_trades(&result.id, 0, 10, 0).await.is_err());
+        assert!(engine.get_backtest_trades(&result.id, 1, 0, 0).await.is_err());
+        assert!(engine.get_backtest_trades(&result.id, 1, 10, 1000).await.is_err());
+        assert!(engine.get_backtest_trades("missing", 1, 10, 0).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_backtest_run_management() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
+
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 3, 45, 0).unwrap();
+        let mut ids = Vec::new();
+        for _ in 0..3 {
+            let params = BacktestParams::new(
+                "test_user", &strategy_id, "RELIANCE", "NSE", start, start + chrono::Duration::days(5),
+                Timeframe::Day1, Decimal::from(100000), DataSource::Database,
+            );
+            let mut result = BacktestResult::new(params);
+            let mut trade = BacktestTrade::new(&result.id, "RELIANCE", TradeType::Buy, start, Decimal::from(1000), 10);
+            trade.close(start + chrono::Duration::days(1), Decimal::from(1010), "Signal exit");
+            result.trades.push(trade);
+            result.equity_curve.push(EquityPoint::new(start, Decimal::from(100000)));
+            result.calculate_metrics();
+            engine.store_backtest_result(&result).await.unwrap();
+            ids.push(result.id);
+        }
+
+        // Labels and notes are trimmed, and blank values clear them
+        engine.update_backtest_notes("test_user", &ids[0], Some(" Baseline ".to_string()), Some("Before costs".to_string())).await.unwrap();
+        let detail = engine.get_backtest_detail(&ids[0]).await.unwrap();
+        assert_eq!(detail.label, Some("Baseline".to_string()));
+        assert_eq!(detail.notes, Some("Before costs".to_string()));
+
+        engine.update_backtest_notes("test_user", &ids[0], Some("Baseline".to_string()), Some("  ".to_string())).await.unwrap();
+        let summaries = engine.get_backtest_results("test_user").await.unwrap();
+        let summary = summaries.iter().find(|s| s.id == ids[0]).unwrap();
+        assert_eq!(summary.label, Some("Baseline".to_string()));
+        assert_eq!(summary.notes, None);
+
+        assert!(engine.update_backtest_notes("test_user", &ids[0], Some("x".repeat(101)), None).await.is_err());
+        assert!(engine.update_backtest_notes("other_user", &ids[0], None, None).await.is_err());
+
+        // Deleting removes the run and everything stored with it
+        engine.run_monte_carlo(&ids[1], &MonteCarloConfig { iterations: 100, seed: Some(1), ..MonteCarloConfig::default() }).await.unwrap();
+        assert!(engine.delete_backtest("other_user", &ids[1]).await.is_err());
+        engine.delete_backtest("test_user", &ids[1]).await.unwrap();
+        assert!(engine.get_backtest_detail(&ids[1]).await.is_err());
+        for table in ["backtest_trades", "backtest_equity_curve", "backtest_monte_carlo"] {
+            let count: i64 = sqlx::query(&format!("SELECT COUNT(*) as count FROM {} WHERE backtest_id = ?", table))
+                .bind(&ids[1])
+                .fetch_one(&*engine.db)
+                .await
+                .unwrap()
+                .get("count");
+            assert_eq!(count, 0);
+        }
+        assert!(engine.delete_backtest("test_user", &ids[1]).await.is_err());
+
+        // Pruning skips recent and labelled runs unless asked
+        sqlx::query("UPDATE backtest_runs SET created_at = ?")
+            .bind(Utc::now() - chrono::Duration::days(60))
+            .execute(&*engine.db)
+            .await
+            .unwrap();
+        assert_eq!(engine.prune_backtests("test_user", 90, false).await.unwrap(), 0);
+        assert_eq!(engine.prune_backtests("test_user", 30, false).await.unwrap(), 1);
+        assert!(engine.get_backtest_detail(&ids[0]).await.is_ok());
+        assert_eq!(engine.prune_backtests("test_user", 30, true).await.unwrap(), 1);
+        assert!(engine.get_backtest_results("test_user").await.unwrap().is_empty());
+        assert!(engine.prune_backtests("test_user", 0, true).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_rerun_backtest_from_stored_config() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
+
+        let data = create_test_historical_data();
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Minute1).await.unwrap();
+
+        let params = || BacktestParams::new(
+            "test_user", &strategy_id, "RELIANCE", "NSE",
+            data[0].timestamp, data[data.len() - 1].timestamp,
+            Timeframe::Minute1, Decimal::from(100000), DataSource::Database,
+        );
+        let result = engine.run_backtest(params()).await.unwrap();
+        let config = result.config.clone().unwrap();
+        assert_eq!(config.strategy.id, strategy_id);
+        assert_eq!(config.data_checksum.len(), 64);
+
+        // Identical configurations hash identically
+        let again = engine.run_backtest(params()).await.unwrap();
+        assert_eq!(again.config.unwrap().hash(), config.hash());
+
+        // The stored snapshot is replayed even after the strategy is edited
+        sqlx::query("UPDATE strategy_params SET stop_loss_percentage = 9.0 WHERE id = ?")
+            .bind(&strategy_id)
+            .execute(&*engine.db)
+            .await
+            .unwrap();
+        let reproduction = engine.rerun_backtest(&result.id).await.unwrap();
+        assert!(reproduction.data_matches);
+        assert!(reproduction.identical, "{:?}", reproduction.differences);
+        assert_eq!(reproduction.config_hash, config.hash());
+        assert_eq!(reproduction.total_trades, result.total_trades);
+
+        // Changed input data is detected
+        sqlx::query("UPDATE historical_data SET close = close + 1 WHERE symbol = 'RELIANCE'")
+            .execute(&*engine.db)
+            .await
+            .unwrap();
+        let reproduction = engine.rerun_backtest(&result.id).await.unwrap();
+        assert!(!reproduction.data_matches);
+        assert!(!reproduction.identical);
+
+        // Runs stored without a configuration cannot be re-run
+        let bare = BacktestResult::new(params());
+        engine.store_backtest_result(&bare).await.unwrap();
+        assert!(engine.rerun_backtest(&bare.id).await.is_err());
+        assert!(engine.rerun_backtest("missing").await.is_err());
+    }
+
+    #[test]
+    fn test_drift_report_matches_fills() {
+        let start = Utc.with_ymd_and_hms(2024, 1, 25, 4, 0, 0).unwrap();
+        let fill = |trade_type: TradeType, minutes: i64, price: i64| DriftFill {
+            trade_type,
+            time: start + chrono::Duration::minutes(minutes),
+            price: Decimal::from(price),
+            quantity: 10,
+        };
+        let request = DriftReportRequest {
+            strategy_id: "strategy".to_string(),
+            symbol: "RELIANCE".to_string(),
+            exchange: "NSE".to_string(),
+            timeframe: Timeframe::Minute5,
+            start_date: start,
+            end_date: start + chrono::Duration::hours(6),
+            initial_capital: Decimal::from(100000),
+            match_window_minutes: None,
+        };
+        assert_eq!(request.match_window(), 10);
+
+        // Two round trips simulated; live skips the second and trades once on its own
+        let backtest = [
+            fill(TradeType::Buy, 0, 1000), fill(TradeType::Sell, 60, 1020),
+            fill(TradeType::Buy, 120, 1010), fill(TradeType::Sell, 180, 1030),
+        ];
+        let live = [
+            fill(TradeType::Buy, 2, 1001), fill(TradeType::Sell, 61, 1018),
+            fill(TradeType::Sell, 125, 1040), fill(TradeType::Buy, 300, 1035),
+        ];
+        let report = DriftReport::new(&request, &backtest, &live);
+
+        assert_eq!(report.matched.len(), 2);
+        assert_eq!(report.matched[0].slippage, Decimal::from(1));
+        assert_eq!(report.matched[1].slippage, Decimal::from(2));
+        assert_eq!(report.matched[0].latency_seconds, 120);
+        assert_eq!(report.max_latency_seconds, 120);
+        assert!((report.average_slippage_bps - (10.0 + 2.0 / 1020.0 * 10_000.0) / 2.0).abs() < 1e-9);
+        assert_eq!(report.slippage_cost, Decimal::from(30));
+
+        // A sell 5 minutes after a simulated buy is the wrong side, not a match
+        assert_eq!(report.missed_signals.len(), 2);
+        assert_eq!(report.unexpected_fills.len(), 2);
+
+        assert_eq!(report.backtest_pnl, Decimal::from(400));
+        assert_eq!(report.live_pnl, Decimal::from(220));
+        assert_eq!(report.implementation_shortfall, Decimal::from(180));
+    }
+
+    #[tokio::test]
+    async fn test_live_drift_report() {
+        let pool = Arc::new(create_test_db().await);
+        let strategy_id = create_test_strategy(&pool).await;
+        let engine = BacktestEngine::new(pool);
+
+        let data = create_test_historical_data();
+        engine.store_historical_data("RELIANCE", "NSE", &data, Timeframe::Minute1).await.unwrap();
+
+        let request = DriftReportRequest {
+            strategy_id: strategy_id.clone(),
+            symbol: "RELIANCE".to_string(),
+            exchange: "NSE".to_string(),
+            timeframe: Timeframe::Minute1,
+            start_date: data[0].timestamp,
+            end_date: data[data.len() - 1].timestamp,
+            initial_capital: Decimal::from(100000),
+            match_window_minutes: None,
+        };
+
+        // Without live fills every simulated fill is missed
+        let simulated = engine.live_drift_report("test_user", &request).await.unwrap();
+        assert!(simulated.matched.is_empty());
+        assert!(simulated.unexpected_fills.is_empty());
+        assert_eq!(simulated.implementation_shortfall, simulated.backtest_pnl);
+
+        // Replay those fills live, 30 seconds late and a rupee worse each
+        let mut quantity = 0;
+        for fill in &simulated.missed_signals {
+            let price = match fill.trade_type {
+                TradeType::Buy => fill.price + Decimal::ONE,
+                TradeType::Sell => fill.price - Decimal::ONE,
+            };
+            quantity += fill.quantity as i64;
+            sqlx::query(
+                "INSERT INTO trades (id, user_id, symbol, exchange, trade_type, quantity, price, status, executed_at, strategy_id)
+                 VALUES (?, 'test_user', 'RELIANCE', 'NSE', ?, ?, ?, 'Executed', ?, ?)"
+            )
+            .bind(uuid::Uuid::new_v4().to_string())
+            .bind(fill.trade_type.to_string())
+            .bind(fill.quantity)
+            .bind(to_f64(price))
+            .bind(fill.time + chrono::Duration::seconds(30))
+            .bind(&strategy_id)
+            .execute(&*engine.db)
+            .await
+            .unwrap();
+        }
+
+        let report = engine.live_drift_report("test_user", &request).await.unwrap();
+        assert_eq!(report.matched.len(), simulated.missed_signals.len());
+        assert!(report.missed_signals.is_empty());
+        assert!(report.unexpected_fills.is_empty());
+        assert!(report.matched.iter().all(|m| m.latency_seconds == 30));
+        // Prices round-trip through REAL columns
+        assert!((to_f64(report.slippage_cost) - quantity as f64).abs() < 1e-6);
+        assert!(to_f64(report.implementation_shortfall - report.slippage_cost).abs() < 1e-6);
+
+        assert!(engine.live_drift_report("other_user", &request).await.is_err());
+        sqlx::query("UPDATE strategy_params SET enabled = 0 WHERE id = ?")
+            .bind(&strategy_id)
+            .execute(&*engine.db)
+            .await
+            .unwrap();
+        assert!(engine.live_drift_report("test_user", &request).await.is_err());
     }
 
     #[tokio::test]
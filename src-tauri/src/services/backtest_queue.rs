@@ -0,0 +1,336 @@
+use crate::error::{HedgeXError, Result};
+use crate::models::backtesting::{BacktestJob, BacktestJobRequest, JobStatus};
+use crate::services::backtest_engine::BacktestEngine;
+use chrono::Utc;
+use sqlx::{Pool, Row, Sqlite};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::Notify;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+/// Upper bound on jobs run at once
+const MAX_PARALLEL_JOBS: usize = 4;
+
+/// How long an idle worker waits before checking the queue again
+const IDLE_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Backtests and optimizations queued in the database and run in the background
+///
+/// Jobs survive restarts: anything still running when the app stopped is
+/// queued again when the workers start.
+pub struct BacktestQueue {
+    db: Arc<Pool<Sqlite>>,
+    engine: Arc<BacktestEngine>,
+    /// Jobs run at once; 1 runs them one after another
+    parallelism: usize,
+    /// Wakes an idle worker when a job is queued
+    queued: Notify,
+}
+
+impl BacktestQueue {
+    /// Create a queue running at most `parallelism` jobs at once
+    pub fn new(db: Arc<Pool<Sqlite>>, engine: Arc<BacktestEngine>, parallelism: usize) -> Self {
+        Self {
+            db,
+            engine,
+            parallelism: parallelism.clamp(1, MAX_PARALLEL_JOBS),
+            queued: Notify::new(),
+        }
+    }
+
+    /// Requeue interrupted jobs and start the background workers
+    pub fn start(self: Arc<Self>) {
+        tokio::spawn(async move {
+            match self.recover().await {
+                Ok(0) => {}
+                Ok(requeued) => info!("Requeued {} interrupted backtest jobs", requeued),
+                Err(e) => error!("Failed to requeue interrupted backtest jobs: {}", e),
+            }
+
+            for _ in 0..self.parallelism {
+                let worker = Arc::clone(&self);
+                tokio::spawn(async move {
+                    loop {
+                        match worker.run_next().await {
+                            Ok(true) => continue,
+                            Ok(false) => {}
+                            Err(e) => error!("Backtest job worker failed: {}", e),
+                        }
+                        let _ = tokio::time::timeout(IDLE_POLL_INTERVAL, worker.queued.notified()).await;
+                    }
+                });
+            }
+        });
+    }
+
+    /// Add a job to the end of the queue
+    pub async fn enqueue(&self, user_id: &str, request: BacktestJobRequest) -> Result<BacktestJob> {
+        let (start_date, end_date) = match &request {
+            BacktestJobRequest::Backtest(run) => (run.start_date, run.end_date),
+            BacktestJobRequest::Optimization(optimization) => (optimization.start_date, optimization.end_date),
+            BacktestJobRequest::WalkForward(walk_forward) => (walk_forward.start_date, walk_forward.end_date),
+        };
+        if start_date >= end_date {
+            return Err(HedgeXError::ValidationError("Start date must be before end date".to_string()));
+        }
+
+        let job = BacktestJob {
+            id: Uuid::new_v4().to_string(),
+            user_id: user_id.to_string(),
+            request,
+            status: JobStatus::Queued,
+            backtest_id: None,
+            result: None,
+            error: None,
+            created_at: Utc::now(),
+            started_at: None,
+            finished_at: None,
+        };
+
+        sqlx::query(
+            "INSERT INTO backtest_jobs (id, user_id, kind, request, status, created_at) VALUES (?, ?, ?, ?, ?, ?)"
+        )
+        .bind(&job.id)
+        .bind(&job.user_id)
+        .bind(job.request.kind())
+        .bind(serde_json::to_string(&job.request)?)
+        .bind(job.status.to_string())
+        .bind(job.created_at)
+        .execute(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+        self.queued.notify_one();
+        info!("Queued {} job {}", job.request.kind(), job.id);
+        Ok(job)
+    }
+
+    /// Get one of a user's jobs
+    pub async fn get_job(&self, user_id: &str, job_id: &str) -> Result<BacktestJob> {
+        let row = sqlx::query("SELECT * FROM backtest_jobs WHERE id = ? AND user_id = ?")
+            .bind(job_id)
+            .bind(user_id)
+            .fetch_optional(&*self.db)
+            .await
+            .map_err(HedgeXError::DatabaseError)?
+            .ok_or_else(|| HedgeXError::NotFoundError(format!("Backtest job not found: {}", job_id)))?;
+
+        job_from_row(&row)
+    }
+
+    /// List a user's jobs, newest first, optionally only those with a status
+    pub async fn list_jobs(&self, user_id: &str, status: Option<JobStatus>) -> Result<Vec<BacktestJob>> {
+        let rows = match status {
+            Some(status) => sqlx::query(
+                "SELECT * FROM backtest_jobs WHERE user_id = ? AND status = ? ORDER BY created_at DESC"
+            )
+            .bind(user_id)
+            .bind(status.to_string())
+            .fetch_all(&*self.db)
+            .await,
+            None => sqlx::query("SELECT * FROM backtest_jobs WHERE user_id = ? ORDER BY created_at DESC")
+                .bind(user_id)
+                .fetch_all(&*self.db)
+                .await,
+        }
+        .map_err(HedgeXError::DatabaseError)?;
+
+        rows.iter().map(job_from_row).collect()
+    }
+
+    /// Return jobs left running by a previous session to the queue
+    async fn recover(&self) -> Result<usize> {
+        let requeued = sqlx::query(
+            "UPDATE backtest_jobs SET status = 'queued', started_at = NULL WHERE status = 'running'"
+        )
+        .execute(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?
+        .rows_affected();
+
+        Ok(requeued as usize)
+    }
+
+    /// Claim and run the oldest queued job, returning whether there was one
+    async fn run_next(&self) -> Result<bool> {
+        let row = sqlx::query(
+            "SELECT * FROM backtest_jobs WHERE status = 'queued' ORDER BY created_at, rowid LIMIT 1"
+        )
+        .fetch_optional(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+        let job = match row {
+            Some(row) => job_from_row(&row)?,
+            None => return Ok(false),
+        };
+
+        // Another worker may have claimed the job since it was read
+        let claimed = sqlx::query(
+            "UPDATE backtest_jobs SET status = 'running', started_at = ? WHERE id = ? AND status = 'queued'"
+        )
+        .bind(Utc::now())
+        .bind(&job.id)
+        .execute(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?
+        .rows_affected();
+        if claimed == 0 {
+            return Ok(true);
+        }
+
+        info!("Running {} job {}", job.request.kind(), job.id);
+        let outcome = self.execute(&job).await;
+
+        let (status, backtest_id, result, error) = match outcome {
+            Ok((backtest_id, result)) => (JobStatus::Done, backtest_id, result, None),
+            Err(e) => {
+                warn!("Backtest job {} failed: {}", job.id, e);
+                (JobStatus::Failed, None, None, Some(e.to_string()))
+            }
+        };
+
+        sqlx::query(
+            "UPDATE backtest_jobs SET status = ?, backtest_id = ?, result = ?, error = ?, finished_at = ? WHERE id = ?"
+        )
+        .bind(status.to_string())
+        .bind(backtest_id)
+        .bind(result.as_ref().map(serde_json::to_string).transpose()?)
+        .bind(error)
+        .bind(Utc::now())
+        .bind(&job.id)
+        .execute(&*self.db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+        Ok(true)
+    }
+
+    /// Run a job's request, returning the stored backtest or the report it produced
+    async fn execute(&self, job: &BacktestJob) -> Result<(Option<String>, Option<serde_json::Value>)> {
+        match &job.request {
+            BacktestJobRequest::Backtest(run) => {
+                let result = self.engine.run_backtest(run.params(&job.user_id)).await?;
+                Ok((Some(result.id), None))
+            }
+            BacktestJobRequest::Optimization(request) => {
+                let report = self.engine.optimize_parameters(&job.user_id, request).await?;
+                Ok((None, Some(serde_json::to_value(report)?)))
+            }
+            BacktestJobRequest::WalkForward(request) => {
+                let report = self.engine.walk_forward(&job.user_id, request).await?;
+                Ok((None, Some(serde_json::to_value(report)?)))
+            }
+        }
+    }
+}
+
+fn job_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<BacktestJob> {
+    let status: String = row.get("status");
+    let result: Option<String> = row.get("result");
+
+    Ok(BacktestJob {
+        id: row.get("id"),
+        user_id: row.get("user_id"),
+        request: serde_json::from_str(&row.get::<String, _>("request"))?,
+        status: JobStatus::from_str(&status).map_err(HedgeXError::InternalError)?,
+        backtest_id: row.get("backtest_id"),
+        result: result.map(|json| serde_json::from_str(&json)).transpose()?,
+        error: row.get("error"),
+        created_at: row.get("created_at"),
+        started_at: row.get("started_at"),
+        finished_at: row.get("finished_at"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::backtesting::{BacktestRunRequest, CostModel, DataSource, Timeframe};
+    use chrono::{Duration, TimeZone};
+    use rust_decimal::Decimal;
+    use sqlx::SqlitePool;
+
+    async fn create_test_queue() -> BacktestQueue {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query(r#"
+            CREATE TABLE backtest_jobs (
+                id TEXT PRIMARY KEY,
+                user_id TEXT NOT NULL,
+                kind TEXT NOT NULL,
+                request TEXT NOT NULL,
+                status TEXT NOT NULL DEFAULT 'queued',
+                backtest_id TEXT,
+                result TEXT,
+                error TEXT,
+                created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
+                started_at TIMESTAMP,
+                finished_at TIMESTAMP
+            )
+        "#).execute(&pool).await.unwrap();
+
+        let pool = Arc::new(pool);
+        let engine = Arc::new(BacktestEngine::new(Arc::clone(&pool)));
+        BacktestQueue::new(pool, engine, 1)
+    }
+
+    fn backtest_request(days: i64) -> BacktestJobRequest {
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 3, 45, 0).unwrap();
+        BacktestJobRequest::Backtest(BacktestRunRequest {
+            strategy_id: "missing".to_string(),
+            symbol: "RELIANCE".to_string(),
+            exchange: "NSE".to_string(),
+            timeframe: Timeframe::Day1,
+            start_date: start,
+            end_date: start + Duration::days(days),
+            initial_capital: Decimal::from(100000),
+            data_source: DataSource::Database,
+            cost_model: CostModel::default(),
+        })
+    }
+
+    #[tokio::test]
+    async fn test_job_lifecycle() {
+        let queue = create_test_queue().await;
+
+        assert!(queue.enqueue("test_user", backtest_request(0)).await.is_err());
+
+        let job = queue.enqueue("test_user", backtest_request(30)).await.unwrap();
+        assert_eq!(job.status, JobStatus::Queued);
+        assert_eq!(queue.list_jobs("test_user", None).await.unwrap().len(), 1);
+        assert!(queue.get_job("other_user", &job.id).await.is_err());
+
+        // The strategy does not exist, so the run fails and records why
+        assert!(queue.run_next().await.unwrap());
+        let failed = queue.get_job("test_user", &job.id).await.unwrap();
+        assert_eq!(failed.status, JobStatus::Failed);
+        assert!(failed.error.is_some());
+        assert!(failed.started_at.is_some() && failed.finished_at.is_some());
+        assert!(matches!(failed.request, BacktestJobRequest::Backtest(_)));
+
+        assert!(!queue.run_next().await.unwrap());
+        assert_eq!(queue.list_jobs("test_user", Some(JobStatus::Failed)).await.unwrap().len(), 1);
+        assert!(queue.list_jobs("test_user", Some(JobStatus::Queued)).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_interrupted_jobs_are_requeued() {
+        let queue = create_test_queue().await;
+        let job = queue.enqueue("test_user", backtest_request(30)).await.unwrap();
+
+        // Simulate the app stopping while the job was running
+        sqlx::query("UPDATE backtest_jobs SET status = 'running', started_at = ? WHERE id = ?")
+            .bind(Utc::now())
+            .bind(&job.id)
+            .execute(&*queue.db)
+            .await
+            .unwrap();
+
+        assert_eq!(queue.recover().await.unwrap(), 1);
+        let requeued = queue.get_job("test_user", &job.id).await.unwrap();
+        assert_eq!(requeued.status, JobStatus::Queued);
+        assert!(requeued.started_at.is_none());
+    }
+}
@@ -0,0 +1,37 @@
+use crate::api::kite_client::KiteApiClient;
+use crate::error::Result;
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, error};
+
+/// Run `sync` against the Kite API every `period` on a background task
+///
+/// Ticks without an access token are skipped, since no session has been
+/// established yet. Failures are logged under `name` and retried on the next tick.
+pub fn spawn_periodic_sync<F, Fut>(
+    name: &'static str,
+    period: Duration,
+    client: Arc<dyn KiteApiClient + Send + Sync>,
+    sync: F,
+) where
+    F: Fn(Arc<dyn KiteApiClient + Send + Sync>) -> Fut + Send + 'static,
+    Fut: Future<Output = Result<()>> + Send,
+{
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(period);
+
+        loop {
+            interval.tick().await;
+
+            if client.get_access_token().await.is_none() {
+                debug!("Skipping {}: no access token", name);
+                continue;
+            }
+
+            if let Err(e) = sync(Arc::clone(&client)).await {
+                error!("{} failed: {}", name, e);
+            }
+        }
+    });
+}
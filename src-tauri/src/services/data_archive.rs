@@ -0,0 +1,509 @@
+use crate::db::TickStore;
+use crate::error::{HedgeXError, Result};
+use crate::services::data_persistence_service::quote_identifier;
+use crate::services::tick_replay::load_ticks;
+use crate::services::websocket_manager::MarketData;
+use async_trait::async_trait;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Connection, Pool, Row, Sqlite, SqliteConnection};
+use std::path::{Path, PathBuf};
+use tracing::info;
+
+/// Hot tables moved into the monthly archives, with the column rows are partitioned by
+const ARCHIVED_TABLES: &[(&str, &str)] = &[("market_ticks", "timestamp"), ("trades", "executed_at")];
+
+/// Table of a backup database carrying the archive partitions, one partition file per row
+pub const BACKUP_PARTITIONS_TABLE: &str = "backup_archive_partitions";
+
+/// One month of archived ticks and trades
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivePartition {
+    /// `YYYY-MM`
+    pub month: String,
+    pub path: PathBuf,
+    pub size_bytes: u64,
+}
+
+/// Monthly SQLite databases holding rows moved out of the hot tables
+///
+/// Each `hedgex_archive_YYYY_MM.db` holds the ticks and trades of one month, so intraday queries
+/// only scan recent data in `hedgex.db` while older months stay readable.
+#[derive(Debug, Clone)]
+pub struct DataArchive {
+    dir: PathBuf,
+}
+
+impl DataArchive {
+    pub fn new(dir: &Path) -> Self {
+        Self { dir: dir.to_path_buf() }
+    }
+
+    /// Database holding the month starting at `month`
+    pub fn partition_path(&self, month: DateTime<Utc>) -> PathBuf {
+        self.dir.join(format!("hedgex_archive_{:04}_{:02}.db", month.year(), month.month()))
+    }
+
+    /// Move rows of an archived table older than the cutoff into their monthly partitions
+    pub async fn archive_before(&self, pool: &Pool<Sqlite>, table: &str, cutoff: DateTime<Utc>) -> Result<usize> {
+        let (table, time_column) = ARCHIVED_TABLES.iter()
+            .copied()
+            .find(|(name, _)| *name == table)
+            .ok_or_else(|| HedgeXError::ValidationError(format!("{} is not an archived table", table)))?;
+
+        let mut oldest = oldest_row_time(pool, table, time_column, None, cutoff).await?;
+        if oldest.is_none() {
+            return Ok(0);
+        }
+
+        tokio::fs::create_dir_all(&self.dir).await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to create archive directory: {}", e)))?;
+
+        // Visit only months holding rows, so gaps in the data leave no empty partitions
+        let mut moved = 0;
+        while let Some(time) = oldest {
+            let month = month_start(time);
+            let next = next_month(month);
+            let mut conn = pool.acquire().await.map_err(HedgeXError::DatabaseError)?;
+            moved += self.move_month(&mut conn, table, time_column, month, next.min(cutoff)).await?;
+            oldest = match next < cutoff {
+                true => oldest_row_time(pool, table, time_column, Some(next), cutoff).await?,
+                false => None,
+            };
+        }
+
+        info!("Archived {} rows from {} older than {}", moved, table, cutoff);
+        Ok(moved)
+    }
+
+    /// Attach the month's partition to a pooled connection and move the rows in one transaction
+    async fn move_month(
+        &self,
+        conn: &mut SqliteConnection,
+        table: &str,
+        time_column: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> Result<usize> {
+        let path = self.partition_path(from);
+        sqlx::query("ATTACH DATABASE ? AS archive")
+            .bind(path.to_string_lossy().to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+
+        let moved = move_rows(conn, table, time_column, from, to).await;
+        // Detach even when the move failed so the pooled connection is reusable
+        let detached = sqlx::query("DETACH DATABASE archive").execute(&mut *conn).await;
+        let moved = moved?;
+        detached.map_err(HedgeXError::DatabaseError)?;
+        Ok(moved)
+    }
+
+    /// Archived months, oldest first
+    pub async fn list_partitions(&self) -> Result<Vec<ArchivePartition>> {
+        let mut partitions = Vec::new();
+        let mut entries = match tokio::fs::read_dir(&self.dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(partitions),
+            Err(e) => return Err(HedgeXError::InternalError(format!("Failed to read archive directory: {}", e))),
+        };
+
+        while let Some(entry) = entries.next_entry().await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to read archive directory: {}", e)))?
+        {
+            let name = entry.file_name().to_string_lossy().to_string();
+            let Some(month) = partition_month(&name) else {
+                continue;
+            };
+            let size_bytes = entry.metadata().await.map(|metadata| metadata.len()).unwrap_or(0);
+            partitions.push(ArchivePartition { month, path: entry.path(), size_bytes });
+        }
+
+        partitions.sort_by(|a, b| a.month.cmp(&b.month));
+        Ok(partitions)
+    }
+
+    /// Copy every partition into a backup database, so archived history is backed up with the hot tables
+    pub async fn embed_in_backup(&self, backup: &Path) -> Result<usize> {
+        let partitions = self.list_partitions().await?;
+        let mut conn = SqliteConnection::connect_with(&SqliteConnectOptions::new().filename(backup))
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+        sqlx::query(&format!("CREATE TABLE {} (file_name TEXT PRIMARY KEY, data BLOB NOT NULL)", BACKUP_PARTITIONS_TABLE))
+            .execute(&mut conn)
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+
+        let snapshot_dir = tempfile::tempdir()
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to create snapshot directory: {}", e)))?;
+        let snapshot = snapshot_dir.path().join("partition.db");
+        for partition in &partitions {
+            // A consistent copy even while the archival job writes to the partition
+            sqlx::query("ATTACH DATABASE ? AS archive")
+                .bind(partition.path.to_string_lossy().to_string())
+                .execute(&mut conn)
+                .await
+                .map_err(HedgeXError::DatabaseError)?;
+            let vacuumed = sqlx::query("VACUUM archive INTO ?")
+                .bind(snapshot.to_string_lossy().to_string())
+                .execute(&mut conn)
+                .await;
+            let detached = sqlx::query("DETACH DATABASE archive").execute(&mut conn).await;
+            vacuumed.map_err(HedgeXError::DatabaseError)?;
+            detached.map_err(HedgeXError::DatabaseError)?;
+
+            let data = tokio::fs::read(&snapshot).await
+                .map_err(|e| HedgeXError::InternalError(format!("Failed to read archive snapshot: {}", e)))?;
+            tokio::fs::remove_file(&snapshot).await
+                .map_err(|e| HedgeXError::InternalError(format!("Failed to remove archive snapshot: {}", e)))?;
+            let file_name = partition.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            sqlx::query(&format!("INSERT INTO {} (file_name, data) VALUES (?, ?)", BACKUP_PARTITIONS_TABLE))
+                .bind(file_name)
+                .bind(data)
+                .execute(&mut conn)
+                .await
+                .map_err(HedgeXError::DatabaseError)?;
+        }
+
+        conn.close().await.map_err(HedgeXError::DatabaseError)?;
+        Ok(partitions.len())
+    }
+
+    /// Replace the partitions with the ones a backup carries
+    ///
+    /// Returns `false` for backups taken before partitions were embedded, leaving the partitions as they are.
+    pub async fn restore_from_backup(&self, backup: &Path) -> Result<bool> {
+        let mut conn = SqliteConnection::connect_with(&SqliteConnectOptions::new().filename(backup).read_only(true))
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+        let embedded = sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?")
+            .bind(BACKUP_PARTITIONS_TABLE)
+            .fetch_optional(&mut conn)
+            .await
+            .map_err(HedgeXError::DatabaseError)?
+            .is_some();
+        let rows = match embedded {
+            true => sqlx::query(&format!("SELECT file_name, data FROM {}", BACKUP_PARTITIONS_TABLE))
+                .fetch_all(&mut conn)
+                .await
+                .map_err(HedgeXError::DatabaseError)?,
+            false => Vec::new(),
+        };
+        conn.close().await.map_err(HedgeXError::DatabaseError)?;
+        if !embedded {
+            return Ok(false);
+        }
+
+        tokio::fs::create_dir_all(&self.dir).await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to create archive directory: {}", e)))?;
+        let mut restored = Vec::new();
+        for row in &rows {
+            let file_name: String = row.get("file_name");
+            // Only partition names, so a crafted backup cannot write outside the archive directory
+            if partition_month(&file_name).is_none() {
+                return Err(HedgeXError::DataIntegrityError(format!("Backup holds an invalid archive partition: {}", file_name)));
+            }
+            let data: Vec<u8> = row.get("data");
+            let staged = self.dir.join(format!("{}.restore", file_name));
+            tokio::fs::write(&staged, &data).await
+                .map_err(|e| HedgeXError::InternalError(format!("Failed to write archive partition: {}", e)))?;
+            tokio::fs::rename(&staged, self.dir.join(&file_name)).await
+                .map_err(|e| HedgeXError::InternalError(format!("Failed to replace archive partition: {}", e)))?;
+            restored.push(file_name);
+        }
+
+        // Rows archived after the backup was taken are back in its hot tables
+        for partition in self.list_partitions().await? {
+            let file_name = partition.path.file_name().unwrap_or_default().to_string_lossy().to_string();
+            if !restored.contains(&file_name) {
+                tokio::fs::remove_file(&partition.path).await
+                    .map_err(|e| HedgeXError::InternalError(format!("Failed to remove archive partition: {}", e)))?;
+            }
+        }
+
+        info!("Restored {} archive partitions from backup", restored.len());
+        Ok(true)
+    }
+
+    /// Archived ticks for symbols in a time range, oldest first
+    pub async fn load_ticks(&self, symbols: &[String], start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<MarketData>> {
+        let mut ticks = Vec::new();
+        let mut month = month_start(start);
+        while month <= end {
+            let path = self.partition_path(month);
+            if path.exists() {
+                let options = SqliteConnectOptions::new().filename(&path).read_only(true);
+                let pool = SqlitePoolOptions::new()
+                    .max_connections(1)
+                    .connect_with(options)
+                    .await
+                    .map_err(HedgeXError::DatabaseError)?;
+
+                let has_ticks = sqlx::query("SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = 'market_ticks'")
+                    .fetch_optional(&pool)
+                    .await
+                    .map_err(HedgeXError::DatabaseError)?
+                    .is_some();
+                if has_ticks {
+                    ticks.extend(load_ticks(&pool, symbols, start, end).await?);
+                }
+                pool.close().await;
+            }
+            month = next_month(month);
+        }
+        Ok(ticks)
+    }
+}
+
+/// Time of the oldest row before the cutoff, optionally from a start time on
+async fn oldest_row_time(
+    pool: &Pool<Sqlite>,
+    table: &str,
+    time_column: &str,
+    from: Option<DateTime<Utc>>,
+    cutoff: DateTime<Utc>,
+) -> Result<Option<DateTime<Utc>>> {
+    let lower_bound = match from {
+        Some(_) => format!(" AND {} >= ?", time_column),
+        None => String::new(),
+    };
+    let sql = format!(
+        "SELECT MIN({column}) FROM {table} WHERE {column} < ?{lower_bound}",
+        column = time_column, table = table, lower_bound = lower_bound
+    );
+    let mut query = sqlx::query_scalar::<_, Option<DateTime<Utc>>>(&sql).bind(cutoff);
+    if let Some(from) = from {
+        query = query.bind(from);
+    }
+    query.fetch_one(pool).await.map_err(HedgeXError::DatabaseError)
+}
+
+/// Copy rows in `[from, to)` into the attached archive, then delete them from the hot table
+async fn move_rows(
+    conn: &mut SqliteConnection,
+    table: &str,
+    time_column: &str,
+    from: DateTime<Utc>,
+    to: DateTime<Utc>,
+) -> Result<usize> {
+    // Columns and declared types only: the archive has no rows to check foreign keys against
+    let columns = table_columns(conn, "main", table).await?;
+    let definitions: Vec<String> = columns.iter()
+        .map(|(name, declared_type)| format!("{} {}", quote_identifier(name), declared_type))
+        .collect();
+    sqlx::query(&format!("CREATE TABLE IF NOT EXISTS archive.{} ({})", table, definitions.join(", ")))
+        .execute(&mut *conn)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+    // Columns added to the hot table since the partition was created
+    let archived = table_columns(conn, "archive", table).await?;
+    for (name, declared_type) in &columns {
+        if !archived.iter().any(|(archived_name, _)| archived_name == name) {
+            sqlx::query(&format!("ALTER TABLE archive.{} ADD COLUMN {} {}", table, quote_identifier(name), declared_type))
+                .execute(&mut *conn)
+                .await
+                .map_err(HedgeXError::DatabaseError)?;
+        }
+    }
+    sqlx::query(&format!(
+        "CREATE INDEX IF NOT EXISTS archive.idx_{table}_{column} ON {table}({column})",
+        table = table, column = time_column
+    ))
+        .execute(&mut *conn)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+    let column_list = columns.iter()
+        .map(|(name, _)| quote_identifier(name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    let mut tx = conn.begin().await.map_err(HedgeXError::DatabaseError)?;
+    sqlx::query(&format!(
+        "INSERT INTO archive.{table} ({columns}) SELECT {columns} FROM main.{table} WHERE {time} >= ? AND {time} < ?",
+        table = table, columns = column_list, time = time_column
+    ))
+        .bind(from)
+        .bind(to)
+        .execute(&mut *tx)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+    let deleted = sqlx::query(&format!("DELETE FROM main.{table} WHERE {time} >= ? AND {time} < ?", table = table, time = time_column))
+        .bind(from)
+        .bind(to)
+        .execute(&mut *tx)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+    tx.commit().await.map_err(HedgeXError::DatabaseError)?;
+
+    Ok(deleted.rows_affected() as usize)
+}
+
+/// Names and declared types of a table's columns in a schema
+async fn table_columns(conn: &mut SqliteConnection, schema: &str, table: &str) -> Result<Vec<(String, String)>> {
+    let rows = sqlx::query(&format!("PRAGMA {}.table_info({})", schema, table))
+        .fetch_all(&mut *conn)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+    Ok(rows.iter().map(|row| (row.get("name"), row.get("type"))).collect())
+}
+
+fn month_start(time: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(time.year(), time.month(), 1, 0, 0, 0).unwrap()
+}
+
+fn next_month(month: DateTime<Utc>) -> DateTime<Utc> {
+    match month.month() {
+        12 => Utc.with_ymd_and_hms(month.year() + 1, 1, 1, 0, 0, 0).unwrap(),
+        m => Utc.with_ymd_and_hms(month.year(), m + 1, 1, 0, 0, 0).unwrap(),
+    }
+}
+
+/// `YYYY-MM` of a partition file name
+fn partition_month(file_name: &str) -> Option<String> {
+    let stamp = file_name.strip_prefix("hedgex_archive_")?.strip_suffix(".db")?;
+    let (year, month) = stamp.split_once('_')?;
+    let (year, month) = (year.parse::<i32>().ok()?, month.parse::<u32>().ok()?);
+    (1..=12).contains(&month).then(|| format!("{:04}-{:02}", year, month))
+}
+
+/// Tick store reading recent ticks from `hedgex.db` and older ones from the monthly archives
+pub struct PartitionedTickStore {
+    pool: Pool<Sqlite>,
+    archive: DataArchive,
+}
+
+impl PartitionedTickStore {
+    pub fn new(pool: Pool<Sqlite>, archive: DataArchive) -> Self {
+        Self { pool, archive }
+    }
+}
+
+#[async_trait]
+impl TickStore for PartitionedTickStore {
+    fn backend(&self) -> &'static str {
+        "sqlite"
+    }
+
+    async fn insert_ticks(&self, ticks: &[MarketData]) -> Result<u64> {
+        self.pool.insert_ticks(ticks).await
+    }
+
+    async fn load_ticks(&self, symbols: &[String], start: DateTime<Utc>, end: DateTime<Utc>) -> Result<Vec<MarketData>> {
+        let mut ticks = self.archive.load_ticks(symbols, start, end).await?;
+        ticks.extend(self.pool.load_ticks(symbols, start, end).await?);
+        ticks.sort_by_key(|tick| tick.timestamp);
+        Ok(ticks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+    use tempfile::TempDir;
+
+    /// A file database, since databases attached to an in-memory one stay in memory
+    async fn create_tick_pool(dir: &Path) -> Pool<Sqlite> {
+        let options = SqliteConnectOptions::new().filename(dir.join("hedgex.db")).create_if_missing(true);
+        let pool = sqlx::SqlitePool::connect_with(options).await.unwrap();
+        sqlx::query(r#"
+            CREATE TABLE market_ticks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                exchange TEXT NOT NULL DEFAULT 'NSE',
+                timestamp TIMESTAMP NOT NULL,
+                ltp REAL NOT NULL,
+                bid REAL NOT NULL,
+                ask REAL NOT NULL,
+                volume INTEGER NOT NULL
+            )
+        "#).execute(&pool).await.unwrap();
+        pool
+    }
+
+    #[test]
+    fn test_partition_month() {
+        assert_eq!(partition_month("hedgex_archive_2025_01.db"), Some("2025-01".to_string()));
+        assert_eq!(partition_month("hedgex_archive_2025_13.db"), None);
+        assert_eq!(partition_month("hedgex.db"), None);
+        assert_eq!(next_month(Utc.with_ymd_and_hms(2024, 12, 1, 0, 0, 0).unwrap()), Utc.with_ymd_and_hms(2025, 1, 1, 0, 0, 0).unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_archive_ticks_into_monthly_partitions() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = DataArchive::new(&temp_dir.path().join("archive"));
+        let pool = create_tick_pool(temp_dir.path()).await;
+
+        let january = Utc.with_ymd_and_hms(2025, 1, 20, 4, 0, 0).unwrap();
+        let february = Utc.with_ymd_and_hms(2025, 2, 3, 4, 0, 0).unwrap();
+        let recent = Utc::now();
+        for timestamp in [january, february, february + Duration::minutes(1), recent] {
+            sqlx::query("INSERT INTO market_ticks (symbol, timestamp, ltp, bid, ask, volume) VALUES ('INFY', ?, 100.0, 99.9, 100.1, 10)")
+                .bind(timestamp)
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let moved = archive.archive_before(&pool, "market_ticks", recent - Duration::days(1)).await.unwrap();
+        assert_eq!(moved, 3);
+
+        let hot: i64 = sqlx::query("SELECT COUNT(*) AS count FROM market_ticks").fetch_one(&pool).await.unwrap().get("count");
+        assert_eq!(hot, 1);
+
+        let months: Vec<String> = archive.list_partitions().await.unwrap().into_iter().map(|p| p.month).collect();
+        assert_eq!(months, vec!["2025-01", "2025-02"]);
+
+        // Replay reads across the archives and the hot table
+        let store = PartitionedTickStore::new(pool.clone(), archive.clone());
+        let symbols = vec!["INFY".to_string()];
+        let ticks = store.load_ticks(&symbols, january - Duration::days(1), recent + Duration::days(1)).await.unwrap();
+        assert_eq!(ticks.len(), 4);
+        assert_eq!(ticks[0].timestamp, january);
+        assert_eq!(ticks[3].timestamp, recent);
+
+        // Nothing is left to move on a second run
+        assert_eq!(archive.archive_before(&pool, "market_ticks", recent - Duration::days(1)).await.unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_backup_carries_partitions() {
+        let temp_dir = TempDir::new().unwrap();
+        let archive = DataArchive::new(&temp_dir.path().join("archive"));
+        let pool = create_tick_pool(temp_dir.path()).await;
+
+        let january = Utc.with_ymd_and_hms(2025, 1, 20, 4, 0, 0).unwrap();
+        sqlx::query("INSERT INTO market_ticks (symbol, timestamp, ltp, bid, ask, volume) VALUES ('INFY', ?, 100.0, 99.9, 100.1, 10)")
+            .bind(january)
+            .execute(&pool)
+            .await
+            .unwrap();
+        archive.archive_before(&pool, "market_ticks", january + Duration::days(1)).await.unwrap();
+
+        let backup = temp_dir.path().join("backup.db");
+        sqlx::query("VACUUM INTO ?").bind(backup.to_string_lossy().to_string()).execute(&pool).await.unwrap();
+        assert_eq!(archive.embed_in_backup(&backup).await.unwrap(), 1);
+
+        // A partition archived after the backup goes, the backed up one comes back
+        let february = Utc.with_ymd_and_hms(2025, 2, 3, 4, 0, 0).unwrap();
+        tokio::fs::remove_file(archive.partition_path(month_start(january))).await.unwrap();
+        tokio::fs::write(archive.partition_path(month_start(february)), b"").await.unwrap();
+        assert!(archive.restore_from_backup(&backup).await.unwrap());
+
+        let months: Vec<String> = archive.list_partitions().await.unwrap().into_iter().map(|p| p.month).collect();
+        assert_eq!(months, vec!["2025-01"]);
+        let symbols = vec!["INFY".to_string()];
+        let ticks = archive.load_ticks(&symbols, january - Duration::days(1), january + Duration::days(1)).await.unwrap();
+        assert_eq!(ticks.len(), 1);
+
+        // Backups without embedded partitions leave the archive alone
+        let legacy = temp_dir.path().join("legacy.db");
+        sqlx::query("VACUUM INTO ?").bind(legacy.to_string_lossy().to_string()).execute(&pool).await.unwrap();
+        assert!(!archive.restore_from_backup(&legacy).await.unwrap());
+        assert_eq!(archive.list_partitions().await.unwrap().len(), 1);
+    }
+}
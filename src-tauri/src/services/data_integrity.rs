@@ -0,0 +1,269 @@
+use crate::error::{HedgeXError, Result};
+use crate::services::data_persistence_service::quote_identifier;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite};
+use std::collections::HashSet;
+use tracing::{info, warn};
+
+/// References the check follows as (table, column, parent table), each pointing at the parent's `id`
+///
+/// Parents come before their children, so rows left without a parent by an earlier repair are
+/// caught by a later reference in the same pass.
+const REFERENCES: &[(&str, &str, &str)] = &[
+    ("strategy_params", "user_id", "users"),
+    ("performance_metrics", "user_id", "users"),
+    ("trades", "user_id", "users"),
+    ("trades", "strategy_id", "strategy_params"),
+    ("backtest_runs", "strategy_id", "strategy_params"),
+    ("strategy_trend_filters", "strategy_id", "strategy_params"),
+    ("pair_strategies", "strategy_id", "strategy_params"),
+    ("backtest_trades", "backtest_id", "backtest_runs"),
+    ("backtest_equity_curve", "backtest_id", "backtest_runs"),
+    ("backtest_monte_carlo", "backtest_id", "backtest_runs"),
+];
+
+/// Row ids listed per reference in a report
+const ORPHAN_SAMPLE_SIZE: usize = 20;
+
+/// What to do with rows whose parent is gone
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RepairMode {
+    ReportOnly,
+    /// Move the rows into `data_quarantine` so they can be inspected later
+    Quarantine,
+    Delete,
+}
+
+/// Rows of a table referring to a parent row that does not exist
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrphanedRows {
+    pub table: String,
+    pub column: String,
+    pub parent_table: String,
+    pub count: u64,
+    /// Row ids of the first orphans found
+    pub sample_rowids: Vec<i64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupStatus {
+    Verified,
+    Missing,
+    /// The file could not be read, decrypted or decompressed
+    Unreadable,
+    /// The backup is encrypted and no backup passphrase is set or could be read
+    Locked,
+    ChecksumMismatch,
+}
+
+/// A backup file checked against the checksum recorded when it was taken
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BackupVerification {
+    pub backup_id: String,
+    pub label: String,
+    pub created_at: DateTime<Utc>,
+    pub status: BackupStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DataIntegrityReport {
+    pub checked_at: DateTime<Utc>,
+    /// Orphans as found, before any repair
+    pub orphans: Vec<OrphanedRows>,
+    pub backups: Vec<BackupVerification>,
+    pub repair: RepairMode,
+    /// Rows deleted or quarantined by the repair
+    pub rows_repaired: u64,
+}
+
+impl DataIntegrityReport {
+    /// No orphans were found and every backup verified
+    pub fn clean(&self) -> bool {
+        self.orphans.is_empty() && self.backups.iter().all(|backup| backup.status == BackupStatus::Verified)
+    }
+}
+
+/// References whose tables both exist, older databases lacking some of them
+async fn present_references(pool: &Pool<Sqlite>) -> Result<Vec<(&'static str, &'static str, &'static str)>> {
+    let tables: HashSet<String> = sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table'")
+        .fetch_all(pool)
+        .await
+        .map_err(HedgeXError::DatabaseError)?
+        .into_iter()
+        .collect();
+    Ok(REFERENCES.iter()
+        .copied()
+        .filter(|(table, _, parent)| tables.contains(*table) && tables.contains(*parent))
+        .collect())
+}
+
+/// Condition matching the rows of `table` whose `column` points at no row of `parent`
+fn orphan_condition(table: &str, column: &str, parent: &str) -> String {
+    let (table, column, parent) = (quote_identifier(table), quote_identifier(column), quote_identifier(parent));
+    format!("{table}.{column} IS NOT NULL AND NOT EXISTS (SELECT 1 FROM {parent} WHERE {parent}.id = {table}.{column})")
+}
+
+/// Rows referring to users, strategies or backtests that no longer exist
+pub async fn find_orphans(pool: &Pool<Sqlite>) -> Result<Vec<OrphanedRows>> {
+    let mut orphans = Vec::new();
+    for (table, column, parent) in present_references(pool).await? {
+        let sql = format!(
+            "SELECT rowid FROM {} WHERE {} ORDER BY rowid",
+            quote_identifier(table),
+            orphan_condition(table, column, parent)
+        );
+        let rowids: Vec<i64> = sqlx::query_scalar(&sql)
+            .fetch_all(pool)
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+        if rowids.is_empty() {
+            continue;
+        }
+
+        warn!("{} rows of {} refer to a missing {} row", rowids.len(), table, parent);
+        orphans.push(OrphanedRows {
+            table: table.to_string(),
+            column: column.to_string(),
+            parent_table: parent.to_string(),
+            count: rowids.len() as u64,
+            sample_rowids: rowids.into_iter().take(ORPHAN_SAMPLE_SIZE).collect(),
+        });
+    }
+    Ok(orphans)
+}
+
+/// Quarantine or delete every orphaned row in one transaction, returning how many were removed
+///
+/// Foreign keys are switched off for the repair so removing an orphan does not cascade into its
+/// children unrecorded; those children are picked up as orphans themselves.
+pub async fn repair_orphans(pool: &Pool<Sqlite>, mode: RepairMode) -> Result<u64> {
+    if mode == RepairMode::ReportOnly {
+        return Ok(0);
+    }
+    let references = present_references(pool).await?;
+
+    let mut conn = pool.acquire().await.map_err(HedgeXError::DatabaseError)?;
+    sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await.map_err(HedgeXError::DatabaseError)?;
+
+    let result = async {
+        let mut tx = sqlx::Connection::begin(&mut *conn).await.map_err(HedgeXError::DatabaseError)?;
+        let now = Utc::now();
+        let mut repaired = 0;
+
+        for (table, column, parent) in references {
+            let condition = orphan_condition(table, column, parent);
+
+            if mode == RepairMode::Quarantine {
+                let columns: Vec<String> = sqlx::query("SELECT name FROM pragma_table_info(?)")
+                    .bind(table)
+                    .fetch_all(&mut *tx)
+                    .await
+                    .map_err(HedgeXError::DatabaseError)?
+                    .iter()
+                    .map(|row| row.get("name"))
+                    .collect();
+                // JSON cannot hold blobs, so they are kept as hex
+                let fields: Vec<String> = columns.iter()
+                    .map(|name| {
+                        let column = quote_identifier(name);
+                        format!("'{}', CASE WHEN typeof({column}) = 'blob' THEN hex({column}) ELSE {column} END", name.replace('\'', "''"))
+                    })
+                    .collect();
+
+                let sql = format!(
+                    "INSERT INTO data_quarantine (source_table, row_data, reason, quarantined_at)
+                     SELECT ?, json_object({}), ?, ? FROM {} WHERE {}",
+                    fields.join(", "),
+                    quote_identifier(table),
+                    condition
+                );
+                sqlx::query(&sql)
+                    .bind(table)
+                    .bind(format!("{} has no matching {} row", column, parent))
+                    .bind(now)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(HedgeXError::DatabaseError)?;
+            }
+
+            let sql = format!("DELETE FROM {} WHERE {}", quote_identifier(table), condition);
+            let removed = sqlx::query(&sql)
+                .execute(&mut *tx)
+                .await
+                .map_err(HedgeXError::DatabaseError)?
+                .rows_affected();
+            if removed > 0 {
+                info!("Repaired {} orphaned rows of {} ({:?})", removed, table, mode);
+            }
+            repaired += removed;
+        }
+
+        tx.commit().await.map_err(HedgeXError::DatabaseError)?;
+        Ok(repaired)
+    }.await;
+
+    sqlx::query("PRAGMA foreign_keys = ON").execute(&mut *conn).await.map_err(HedgeXError::DatabaseError)?;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Executor;
+
+    async fn pool_with_orphans() -> Pool<Sqlite> {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        pool.execute(
+            "CREATE TABLE users (id TEXT PRIMARY KEY);
+             CREATE TABLE strategy_params (id TEXT PRIMARY KEY, user_id TEXT NOT NULL);
+             CREATE TABLE trades (id TEXT PRIMARY KEY, user_id TEXT NOT NULL, strategy_id TEXT NOT NULL, price REAL NOT NULL);
+             INSERT INTO users VALUES ('alice');
+             INSERT INTO strategy_params VALUES ('kept', 'alice'), ('stray', 'bob');
+             INSERT INTO trades VALUES ('t1', 'alice', 'kept', 100.0), ('t2', 'alice', 'gone', 101.0), ('t3', 'alice', 'stray', 102.0);"
+        ).await.unwrap();
+        pool.execute(include_str!("../../migrations/20250829_add_data_quarantine.sql")).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_find_orphans() {
+        let pool = pool_with_orphans().await;
+
+        let orphans = find_orphans(&pool).await.unwrap();
+        let found: Vec<(&str, &str, u64)> = orphans.iter().map(|o| (o.table.as_str(), o.column.as_str(), o.count)).collect();
+        // t3 belongs to a strategy that exists until the strategy is repaired
+        assert_eq!(found, vec![("strategy_params", "user_id", 1), ("trades", "strategy_id", 1)]);
+
+        assert_eq!(repair_orphans(&pool, RepairMode::ReportOnly).await.unwrap(), 0);
+        assert_eq!(find_orphans(&pool).await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn test_quarantine_orphans() {
+        let pool = pool_with_orphans().await;
+
+        // The stray strategy, then both trades without a strategy
+        assert_eq!(repair_orphans(&pool, RepairMode::Quarantine).await.unwrap(), 3);
+        assert!(find_orphans(&pool).await.unwrap().is_empty());
+
+        let quarantined: Vec<(String, String)> = sqlx::query_as(
+            "SELECT source_table, json_extract(row_data, '$.id') FROM data_quarantine ORDER BY id"
+        )
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert_eq!(quarantined, vec![
+            ("strategy_params".to_string(), "stray".to_string()),
+            ("trades".to_string(), "t2".to_string()),
+            ("trades".to_string(), "t3".to_string()),
+        ]);
+        let price: f64 = sqlx::query_scalar("SELECT json_extract(row_data, '$.price') FROM data_quarantine WHERE source_table = 'trades' LIMIT 1")
+            .fetch_one(&pool)
+            .await
+            .unwrap();
+        assert_eq!(price, 101.0);
+    }
+}
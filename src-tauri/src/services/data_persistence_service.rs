@@ -1,6 +1,16 @@
-use crate::db::Database;
+use crate::db::{migrations, Database, MigrationInfo, MigrationRepair};
 use crate::error::{HedgeXError, Result};
-use crate::utils::{EnhancedCryptoService, EnhancedLogger};
+use crate::models::backtesting::BacktestResult;
+use crate::services::recording_session::RecordingSessionInfo;
+use crate::services::pnl_statement::PnlStatement;
+use crate::services::tax_report::TaxReport;
+use crate::services::data_archive::{ArchivePartition, DataArchive, BACKUP_PARTITIONS_TABLE};
+use crate::services::profile_bundle::{
+    self, ConflictResolution, ProfileBundle, ProfileCredentials, ProfileImportReport, StoredCredentials, PROFILE_BUNDLE_VERSION,
+};
+use crate::services::data_integrity::{self, BackupStatus, BackupVerification, DataIntegrityReport, RepairMode};
+use crate::services::cloud_backup::{open_backup, seal_backup, BackupDestination, CloudBackupSettings, RemoteBackup};
+use crate::utils::{render_text_pdf, render_xlsx, EnhancedCryptoService, EnhancedLogger, XlsxSheet};
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
 use tokio::sync::Mutex;
@@ -14,18 +24,27 @@ use flate2::read::GzDecoder;
 use flate2::Compression;
 use std::io::{Write, Read};
 use uuid::Uuid;
+use base64::{engine::general_purpose, Engine as _};
 use sqlx::Row;
 
+/// Tables describing backups rather than app data, kept as they are when a backup is restored
+const RESTORE_PRESERVED_TABLES: &[&str] = &["backup_metadata", "backup_encryption", "cloud_backup_settings", "cloud_backup_uploads"];
+
 /// Configuration for data persistence operations
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct DataPersistenceConfig {
     pub auto_backup_enabled: bool,
     pub backup_interval_hours: u64,
     pub max_backups_to_keep: usize,
     pub compress_backups: bool,
+    /// Encrypt new backups with the passphrase set through `set_backup_passphrase`
+    pub encrypt_backups: bool,
     pub encrypt_exports: bool,
     pub log_retention_days: i64,
     pub trade_data_retention_days: i64,
+    /// Ticks older than this move from `market_ticks` to the monthly archives
+    pub tick_data_hot_days: i64,
     pub auto_cleanup_enabled: bool,
     pub cleanup_interval_hours: u64,
 }
@@ -37,15 +56,101 @@ impl Default for DataPersistenceConfig {
             backup_interval_hours: 6, // Every 6 hours
             max_backups_to_keep: 10,
             compress_backups: true,
+            encrypt_backups: false,
             encrypt_exports: true,
             log_retention_days: 30,
             trade_data_retention_days: 365, // Keep trade data for 1 year
+            tick_data_hot_days: 30,
             auto_cleanup_enabled: true,
             cleanup_interval_hours: 24, // Daily cleanup
         }
     }
 }
 
+/// Longest backup or cleanup interval accepted
+const MAX_INTERVAL_HOURS: u64 = 24 * 365;
+
+/// Longest retention period accepted
+const MAX_RETENTION_DAYS: i64 = 3650;
+
+impl DataPersistenceConfig {
+    /// Reject intervals, counts and retention periods the schedulers cannot work with
+    pub fn validate(&self) -> Result<()> {
+        for (name, hours) in [("backup_interval_hours", self.backup_interval_hours), ("cleanup_interval_hours", self.cleanup_interval_hours)] {
+            if !(1..=MAX_INTERVAL_HOURS).contains(&hours) {
+                return Err(HedgeXError::ValidationError(format!("{} must be between 1 and {}", name, MAX_INTERVAL_HOURS)));
+            }
+        }
+        for (name, days) in [
+            ("log_retention_days", self.log_retention_days),
+            ("trade_data_retention_days", self.trade_data_retention_days),
+            ("tick_data_hot_days", self.tick_data_hot_days),
+        ] {
+            if !(1..=MAX_RETENTION_DAYS).contains(&days) {
+                return Err(HedgeXError::ValidationError(format!("{} must be between 1 and {}", name, MAX_RETENTION_DAYS)));
+            }
+        }
+        if self.max_backups_to_keep == 0 {
+            return Err(HedgeXError::ValidationError("max_backups_to_keep must be at least 1".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// What a cleanup run removed or archived
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CleanupReport {
+    pub logs_cleaned: usize,
+    pub trades_archived: usize,
+    pub ticks_archived: usize,
+    pub backups_cleaned: usize,
+}
+
+/// Wait before retrying a failed automatic backup
+const BACKUP_RETRY_MINUTES: i64 = 30;
+
+/// Timing of the background automatic backups
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BackupSchedule {
+    pub enabled: bool,
+    pub interval_hours: u64,
+    pub last_backup_at: Option<DateTime<Utc>>,
+    /// Unset while automatic backups are disabled
+    pub next_backup_at: Option<DateTime<Utc>>,
+    /// Error of the last attempt, cleared once a backup succeeds
+    pub last_error: Option<String>,
+}
+
+impl BackupSchedule {
+    /// Schedule following the last automatic backup; without one a backup is due at once
+    pub fn new(config: &DataPersistenceConfig, last_backup_at: Option<DateTime<Utc>>, now: DateTime<Utc>) -> Self {
+        let next_backup_at = config.auto_backup_enabled.then(|| match last_backup_at {
+            Some(last) => last + ChronoDuration::hours(config.backup_interval_hours.max(1) as i64),
+            None => now,
+        });
+        
+        Self {
+            enabled: config.auto_backup_enabled,
+            interval_hours: config.backup_interval_hours,
+            last_backup_at,
+            next_backup_at,
+            last_error: None,
+        }
+    }
+    
+    pub fn is_due(&self, now: DateTime<Utc>) -> bool {
+        self.next_backup_at.is_some_and(|next| next <= now)
+    }
+    
+    /// Record a failed backup, retrying after a short wait rather than on every check
+    pub fn record_failure(&mut self, error: String, now: DateTime<Utc>) {
+        self.last_error = Some(error);
+        if self.enabled {
+            self.next_backup_at = Some(now + ChronoDuration::minutes(BACKUP_RETRY_MINUTES));
+        }
+    }
+}
+
 /// User preferences and settings
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UserSettings {
@@ -104,12 +209,73 @@ pub enum BackupType {
     PreUpdate,
 }
 
+/// Step a restore has reached
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum RestoreStage {
+    Quiescing,
+    Verifying,
+    /// Backing up the current database before it is replaced
+    Snapshotting,
+    Copying,
+    Migrating,
+    Reinitializing,
+    Completed,
+    Failed,
+}
+
+/// Progress of a restore, reported as it moves through its stages
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RestoreProgress {
+    pub backup_id: String,
+    pub stage: RestoreStage,
+    pub percent: u8,
+    pub message: String,
+}
+
+impl RestoreProgress {
+    pub fn new(backup_id: &str, stage: RestoreStage, percent: u8, message: impl Into<String>) -> Self {
+        Self {
+            backup_id: backup_id.to_string(),
+            stage,
+            percent,
+            message: message.into(),
+        }
+    }
+}
+
+/// Outcome of uploading a backup to a cloud destination
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum CloudUploadStatus {
+    Uploaded,
+    Failed,
+    /// Removed from the destination by retention
+    Deleted,
+}
+
+/// A local backup uploaded, or attempted, to a cloud destination
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloudUpload {
+    pub id: String,
+    pub backup_id: String,
+    pub destination: String,
+    pub remote_id: Option<String>,
+    pub remote_name: String,
+    pub size: u64,
+    pub status: CloudUploadStatus,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 /// Export format options
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum ExportFormat {
     Json,
     Csv,
     Sql,
+    /// Printable statement; only P&L statements support it
+    Pdf,
+    /// Excel workbook with a sheet per table
+    Xlsx,
 }
 
 /// Data export request
@@ -141,7 +307,10 @@ pub struct DataPersistenceService {
     app_data_dir: PathBuf,
     backup_dir: PathBuf,
     export_dir: PathBuf,
-    config: DataPersistenceConfig,
+    archive: DataArchive,
+    /// Replaced whole by `update_config`, so readers take a copy
+    config: std::sync::RwLock<DataPersistenceConfig>,
+    http_client: reqwest::Client,
 }
 
 impl DataPersistenceService {
@@ -174,11 +343,15 @@ impl DataPersistenceService {
                 app_data_dir: app_data_dir.to_path_buf(),
                 backup_dir,
                 export_dir,
-                config,
+                archive: DataArchive::new(&app_data_dir.join("archive")),
+                config: std::sync::RwLock::new(config),
+                http_client: reqwest::Client::new(),
             };
             
             // Initialize user settings table if it doesn't exist
             service.initialize_settings_table().await?;
+            service.register_pre_migration_snapshots().await?;
+            service.load_stored_config().await;
             
             // Log successful initialization
             {
@@ -226,6 +399,15 @@ impl DataPersistenceService {
         self.create_backup("auto", BackupType::Automatic).await
     }
     
+    /// When the newest automatic backup was taken, if any
+    pub async fn last_automatic_backup_at(&self) -> Result<Option<DateTime<Utc>>> {
+        let backups = self.list_backups().await?;
+        Ok(backups.iter()
+            .filter(|backup| matches!(backup.backup_type, BackupType::Automatic))
+            .map(|backup| backup.created_at)
+            .max())
+    }
+    
     /// Create a manual database backup
     pub async fn create_manual_backup(&self, label: &str) -> Result<BackupMetadata> {
         self.create_backup(label, BackupType::Manual).await
@@ -239,12 +421,24 @@ impl DataPersistenceService {
             info!("Creating database backup with label: {}", label);
             
             let backup_id = Uuid::new_v4().to_string();
+            let config = self.get_config();
+            let compress = config.compress_backups;
+            let passphrase = if config.encrypt_backups {
+                Some(self.load_backup_passphrase().await?.ok_or_else(|| {
+                    HedgeXError::ValidationError("Backup encryption is on but no backup passphrase is set".to_string())
+                })?)
+            } else {
+                None
+            };
             let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
-            let filename = if self.config.compress_backups {
+            let mut filename = if compress {
                 format!("hedgex_{}_{}.db.gz", label, timestamp)
             } else {
                 format!("hedgex_{}_{}.db", label, timestamp)
             };
+            if passphrase.is_some() {
+                filename.push_str(".enc");
+            }
             let backup_path = self.backup_dir.join(&filename);
             
             // Create temporary backup using SQLite VACUUM INTO
@@ -255,21 +449,24 @@ impl DataPersistenceService {
                 .execute(self.database.get_pool())
                 .await
                 .map_err(|e| HedgeXError::DatabaseError(e))?;
+            // Archived ticks and trades live outside hedgex.db
+            self.archive.embed_in_backup(&temp_backup_path).await?;
             
             // Calculate checksum of the temporary backup
             let temp_data = tokio::fs::read(&temp_backup_path).await
                 .map_err(|e| HedgeXError::InternalError(format!("Failed to read temporary backup: {}", e)))?;
             let checksum = self.crypto_service.calculate_checksum(&temp_data)?;
             
-            let file_size = if self.config.compress_backups {
-                // Compress the backup
-                let compressed_data = self.compress_data(&temp_data)?;
-                tokio::fs::write(&backup_path, compressed_data).await
-                    .map_err(|e| HedgeXError::InternalError(format!("Failed to write compressed backup: {}", e)))?;
+            let file_size = if compress || passphrase.is_some() {
+                // Compress, then encrypt, the backup
+                let mut backup_data = if compress { self.compress_data(&temp_data)? } else { temp_data };
+                if let Some(passphrase) = &passphrase {
+                    backup_data = self.crypto_service.encrypt_with_passphrase(&backup_data, passphrase)?;
+                }
+                tokio::fs::write(&backup_path, &backup_data).await
+                    .map_err(|e| HedgeXError::InternalError(format!("Failed to write backup: {}", e)))?;
                 
-                tokio::fs::metadata(&backup_path).await
-                    .map_err(|e| HedgeXError::InternalError(format!("Failed to get backup file size: {}", e)))?
-                    .len()
+                backup_data.len() as u64
             } else {
                 // Move the temporary backup to final location
                 tokio::fs::rename(&temp_backup_path, &backup_path).await
@@ -289,8 +486,8 @@ impl DataPersistenceService {
                 created_at: Utc::now(),
                 file_path: backup_path,
                 file_size,
-                compressed: self.config.compress_backups,
-                encrypted: false, // Database backups are not encrypted by default
+                compressed: compress,
+                encrypted: passphrase.is_some(),
                 checksum,
                 backup_type,
             };
@@ -320,12 +517,107 @@ impl DataPersistenceService {
         .await
     }
     
+    /// List snapshots taken before startup migrations as pre-migration backups
+    async fn register_pre_migration_snapshots(&self) -> Result<()> {
+        let mut snapshots = Vec::new();
+        let mut entries = tokio::fs::read_dir(&self.backup_dir).await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to read backup directory: {}", e)))?;
+        while let Some(entry) = entries.next_entry().await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to read backup directory: {}", e)))?
+        {
+            if entry.file_name().to_string_lossy().starts_with(migrations::PRE_MIGRATION_SNAPSHOT_PREFIX) {
+                snapshots.push(entry.path());
+            }
+        }
+        if snapshots.is_empty() {
+            return Ok(());
+        }
+        
+        // The metadata table only exists once a first backup has been saved
+        let known: Vec<PathBuf> = self.list_backups().await
+            .map(|backups| backups.into_iter().map(|backup| backup.file_path).collect())
+            .unwrap_or_default();
+        
+        for path in snapshots.into_iter().filter(|path| !known.contains(path)) {
+            let data = tokio::fs::read(&path).await
+                .map_err(|e| HedgeXError::InternalError(format!("Failed to read pre-migration snapshot: {}", e)))?;
+            let metadata = BackupMetadata {
+                id: Uuid::new_v4().to_string(),
+                label: "pre_migration".to_string(),
+                created_at: Utc::now(),
+                file_path: path,
+                file_size: data.len() as u64,
+                compressed: false,
+                encrypted: false,
+                checksum: self.crypto_service.calculate_checksum(&data)?,
+                backup_type: BackupType::PreMigration,
+            };
+            self.save_backup_metadata(&metadata).await?;
+            info!("Registered pre-migration snapshot {:?}", metadata.file_path);
+        }
+        Ok(())
+    }
+    
+    async fn migrator(&self) -> Result<sqlx::migrate::Migrator> {
+        Database::initialize_migrator().await
+            .map_err(|e| HedgeXError::ConfigError(e.to_string()))?
+            .ok_or_else(|| HedgeXError::ConfigError("No migrations found".to_string()))
+    }
+    
+    /// Applied and pending migrations
+    pub async fn migration_status(&self) -> Result<Vec<MigrationInfo>> {
+        migrations::migration_status(self.database.get_pool(), &self.migrator().await?).await
+    }
+    
+    /// Back up the database, then apply pending migrations, returning their versions
+    pub async fn migrate_up(&self) -> Result<Vec<i64>> {
+        let migrator = self.migrator().await?;
+        let pending = migrations::pending_migrations(self.database.get_pool(), &migrator).await?;
+        if pending.is_empty() {
+            return Ok(pending);
+        }
+        
+        self.create_backup("pre_migration", BackupType::PreMigration).await?;
+        migrator.run(self.database.get_pool()).await
+            .map_err(|e| HedgeXError::DatabaseError(sqlx::Error::Migrate(Box::new(e))))?;
+        info!("Applied migrations {:?}", pending);
+        Ok(pending)
+    }
+    
+    /// Back up the database, then revert migrations newer than `target_version`
+    pub async fn migrate_down(&self, target_version: i64) -> Result<Vec<i64>> {
+        let migrator = self.migrator().await?;
+        self.create_backup("pre_migration", BackupType::PreMigration).await?;
+        migrations::migrate_down(self.database.get_pool(), &migrator, target_version).await
+    }
+    
+    /// Accept edited migration files and clear unfinished runs
+    pub async fn repair_migrations(&self) -> Result<MigrationRepair> {
+        migrations::repair_migrations(self.database.get_pool(), &self.migrator().await?).await
+    }
+    
     /// Restore database from backup
     pub async fn restore_from_backup(&self, backup_id: &str) -> Result<()> {
+        self.restore_from_backup_with_progress(backup_id, None, &|_| {}).await
+    }
+    
+    /// Restore database from backup, reporting each stage
+    ///
+    /// The restored contents are copied into the open database in one transaction, so pooled
+    /// connections stay valid and never see a half-restored database. Migrations are not run;
+    /// callers bring an older backup up to the current schema afterwards. Encrypted backups are
+    /// opened with `passphrase`, or the stored backup passphrase when none is given.
+    pub async fn restore_from_backup_with_progress(
+        &self,
+        backup_id: &str,
+        passphrase: Option<&str>,
+        progress: &(dyn Fn(RestoreProgress) + Send + Sync),
+    ) -> Result<()> {
         let span = span!(Level::INFO, "restore_backup", backup_id = %backup_id);
         
         async move {
             info!("Restoring database from backup: {}", backup_id);
+            progress(RestoreProgress::new(backup_id, RestoreStage::Verifying, 5, "Verifying backup"));
             
             // Get backup metadata
             let metadata = self.get_backup_metadata(backup_id).await?;
@@ -340,12 +632,12 @@ impl DataPersistenceService {
             let backup_data = tokio::fs::read(&metadata.file_path).await
                 .map_err(|e| HedgeXError::InternalError(format!("Failed to read backup file: {}", e)))?;
             
-            // Decompress if necessary
-            let db_data = if metadata.compressed {
-                self.decompress_data(&backup_data)?
-            } else {
-                backup_data
+            let passphrase = match passphrase {
+                Some(passphrase) => Some(passphrase.to_string()),
+                None if metadata.encrypted => self.load_backup_passphrase().await?,
+                None => None,
             };
+            let db_data = self.decode_backup(&metadata, backup_data, passphrase.as_deref())?;
             
             // Verify checksum
             let calculated_checksum = self.crypto_service.calculate_checksum(&db_data)?;
@@ -356,21 +648,20 @@ impl DataPersistenceService {
             }
             
             // Create a backup of current database before restore
+            progress(RestoreProgress::new(backup_id, RestoreStage::Snapshotting, 15, "Backing up the current database"));
             self.create_backup("pre_restore", BackupType::Manual).await?;
             
             // Write the backup data to a temporary file
-            let temp_restore_path = self.app_data_dir.join("temp_restore.db");
+            let temp_restore_path = self.backup_dir.join(format!("temp_restore_{}.db", backup_id));
             tokio::fs::write(&temp_restore_path, db_data).await
                 .map_err(|e| HedgeXError::InternalError(format!("Failed to write restore data: {}", e)))?;
             
-            // Close current database connections
-            // Note: This is a simplified approach. In a real implementation,
-            // you might need to coordinate with other services to close connections
-            
-            // Replace the main database file
-            let main_db_path = self.app_data_dir.join("hedgex.db");
-            tokio::fs::rename(&temp_restore_path, &main_db_path).await
-                .map_err(|e| HedgeXError::InternalError(format!("Failed to replace database file: {}", e)))?;
+            let mut result = self.replace_database_contents(&temp_restore_path, backup_id, progress).await;
+            if result.is_ok() {
+                result = self.archive.restore_from_backup(&temp_restore_path).await.map(|_| ());
+            }
+            let _ = remove_file(&temp_restore_path).await;
+            result?;
             
             // Log successful restore
             {
@@ -432,21 +723,88 @@ impl DataPersistenceService {
         Ok(backups)
     }
     
+    /// Check every backup file against the checksum recorded when it was taken
+    pub async fn verify_backups(&self) -> Result<Vec<BackupVerification>> {
+        // Without a readable passphrase encrypted backups are reported locked, the rest still verified
+        let passphrase = self.load_backup_passphrase().await.unwrap_or_else(|e| {
+            warn!("Failed to load the backup passphrase: {}", e);
+            None
+        });
+        let mut verified = Vec::new();
+        for backup in self.list_backups().await? {
+            let status = match tokio::fs::read(&backup.file_path).await {
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => BackupStatus::Missing,
+                Err(e) => {
+                    warn!("Failed to read backup {}: {}", backup.id, e);
+                    BackupStatus::Unreadable
+                }
+                Ok(_) if backup.encrypted && passphrase.is_none() => BackupStatus::Locked,
+                Ok(data) => {
+                    let data = self.decode_backup(&backup, data, passphrase.as_deref());
+                    match data.and_then(|data| self.crypto_service.calculate_checksum(&data)) {
+                        Ok(checksum) if checksum == backup.checksum => BackupStatus::Verified,
+                        Ok(_) => BackupStatus::ChecksumMismatch,
+                        Err(e) => {
+                            warn!("Failed to verify backup {}: {}", backup.id, e);
+                            BackupStatus::Unreadable
+                        }
+                    }
+                }
+            };
+            if status != BackupStatus::Verified {
+                warn!("Backup {} failed verification: {:?}", backup.id, status);
+            }
+
+            verified.push(BackupVerification {
+                backup_id: backup.id,
+                label: backup.label,
+                created_at: backup.created_at,
+                status,
+            });
+        }
+        Ok(verified)
+    }
+
+    /// Cross-check references between tables and verify backups, optionally repairing orphaned rows
+    ///
+    /// A backup is taken before orphans are quarantined or deleted.
+    pub async fn verify_data(&self, repair: RepairMode) -> Result<DataIntegrityReport> {
+        let pool = self.database.get_pool();
+        let orphans = data_integrity::find_orphans(pool).await?;
+        let backups = self.verify_backups().await?;
+
+        let rows_repaired = if repair == RepairMode::ReportOnly || orphans.is_empty() {
+            0
+        } else {
+            self.create_backup("pre_repair", BackupType::Manual).await?;
+            data_integrity::repair_orphans(pool, repair).await?
+        };
+
+        Ok(DataIntegrityReport {
+            checked_at: Utc::now(),
+            orphans,
+            backups,
+            repair,
+            rows_repaired,
+        })
+    }
+    
     /// Clean up old backups based on configuration
     pub async fn cleanup_old_backups(&self) -> Result<usize> {
         let span = span!(Level::INFO, "cleanup_old_backups");
         
         async move {
-            info!("Cleaning up old backups, keeping {} newest", self.config.max_backups_to_keep);
+            let keep = self.get_config().max_backups_to_keep;
+            info!("Cleaning up old backups, keeping {} newest", keep);
             
             let backups = self.list_backups().await?;
             
-            if backups.len() <= self.config.max_backups_to_keep {
-                debug!("No backups to clean up (have {}, keeping {})", backups.len(), self.config.max_backups_to_keep);
+            if backups.len() <= keep {
+                debug!("No backups to clean up (have {}, keeping {})", backups.len(), keep);
                 return Ok(0);
             }
             
-            let to_delete = &backups[self.config.max_backups_to_keep..];
+            let to_delete = &backups[keep..];
             let mut deleted_count = 0;
             
             for backup in to_delete {
@@ -476,6 +834,260 @@ impl DataPersistenceService {
         .await
     }   
  
+    /// Store the passphrase new backups are encrypted with, or remove it with `None`
+    ///
+    /// It is kept apart from the login password and encrypted like the cloud backup settings.
+    /// Backups taken under an earlier passphrase still need that one to be restored.
+    pub async fn set_backup_passphrase(&self, passphrase: Option<&str>) -> Result<()> {
+        match passphrase.filter(|passphrase| !passphrase.is_empty()) {
+            Some(passphrase) => {
+                let encrypted = self.crypto_service.encrypt_sensitive("backup_passphrase", passphrase).await?;
+                sqlx::query("INSERT OR REPLACE INTO backup_encryption (id, passphrase, updated_at) VALUES (1, ?, ?)")
+                    .bind(encrypted)
+                    .bind(Utc::now())
+                    .execute(self.database.get_pool())
+                    .await
+                    .map_err(HedgeXError::DatabaseError)?;
+                info!("Backup passphrase saved");
+            }
+            None => {
+                if self.get_config().encrypt_backups {
+                    return Err(HedgeXError::ValidationError("Turn off backup encryption before removing its passphrase".to_string()));
+                }
+                sqlx::query("DELETE FROM backup_encryption WHERE id = 1")
+                    .execute(self.database.get_pool())
+                    .await
+                    .map_err(HedgeXError::DatabaseError)?;
+                info!("Backup passphrase removed");
+            }
+        }
+        Ok(())
+    }
+    
+    /// The stored backup passphrase, decrypted
+    async fn load_backup_passphrase(&self) -> Result<Option<String>> {
+        let stored = sqlx::query_scalar::<_, String>("SELECT passphrase FROM backup_encryption WHERE id = 1")
+            .fetch_optional(self.database.get_pool())
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+        match stored {
+            Some(encrypted) => Ok(Some(self.crypto_service.decrypt_sensitive("backup_passphrase", &encrypted).await?)),
+            None => Ok(None),
+        }
+    }
+    
+    /// The database file held in a backup, decrypted and decompressed as its metadata says
+    fn decode_backup(&self, metadata: &BackupMetadata, data: Vec<u8>, passphrase: Option<&str>) -> Result<Vec<u8>> {
+        let data = if metadata.encrypted {
+            let passphrase = passphrase.ok_or_else(|| {
+                HedgeXError::ValidationError(format!("Backup {} is encrypted and needs its passphrase", metadata.id))
+            })?;
+            self.crypto_service.decrypt_with_passphrase(&data, passphrase)?
+        } else {
+            data
+        };
+        
+        if metadata.compressed {
+            self.decompress_data(&data)
+        } else {
+            Ok(data)
+        }
+    }
+    
+    /// Save the cloud backup destination, encrypted, keeping stored secrets left unset
+    pub async fn save_cloud_backup_settings(&self, settings: &CloudBackupSettings) -> Result<()> {
+        let mut settings = settings.clone();
+        if let Some(stored) = self.load_cloud_backup_settings().await? {
+            settings.keep_secrets(&stored);
+        }
+        settings.passphrase()?;
+        
+        let encrypted = self.crypto_service
+            .encrypt_sensitive("cloud_backup_settings", &serde_json::to_string(&settings)?)
+            .await?;
+        
+        sqlx::query("INSERT OR REPLACE INTO cloud_backup_settings (id, settings, updated_at) VALUES (1, ?, ?)")
+            .bind(encrypted)
+            .bind(Utc::now())
+            .execute(self.database.get_pool())
+            .await
+            .map_err(|e| HedgeXError::DatabaseError(e))?;
+        
+        info!("Cloud backup settings saved");
+        Ok(())
+    }
+    
+    /// Cloud backup settings with their secrets decrypted, if a destination is set
+    pub async fn load_cloud_backup_settings(&self) -> Result<Option<CloudBackupSettings>> {
+        let row = sqlx::query("SELECT settings FROM cloud_backup_settings WHERE id = 1")
+            .fetch_optional(self.database.get_pool())
+            .await
+            .map_err(|e| HedgeXError::DatabaseError(e))?;
+        
+        match row {
+            Some(row) => {
+                let settings = self.crypto_service
+                    .decrypt_sensitive("cloud_backup_settings", &row.get::<String, _>("settings"))
+                    .await?;
+                Ok(Some(serde_json::from_str(&settings)?))
+            }
+            None => Ok(None),
+        }
+    }
+    
+    /// Encrypt a local backup with the cloud passphrase and upload it, then delete uploads beyond the retention
+    pub async fn upload_backup(&self, backup_id: &str) -> Result<CloudUpload> {
+        let span = span!(Level::INFO, "upload_backup", backup_id = %backup_id);
+        
+        async move {
+            let settings = self.cloud_backup_settings().await?;
+            let destination = settings.destination.destination(self.http_client.clone())?;
+            let metadata = self.get_backup_metadata(backup_id).await?;
+            
+            let backup_data = tokio::fs::read(&metadata.file_path).await
+                .map_err(|e| HedgeXError::InternalError(format!("Failed to read backup file: {}", e)))?;
+            let sealed = seal_backup(&backup_data, settings.passphrase()?)?;
+            
+            let file_name = metadata.file_path.file_name()
+                .map(|name| name.to_string_lossy().to_string())
+                .unwrap_or_else(|| format!("{}.db", metadata.id));
+            let mut upload = CloudUpload {
+                id: Uuid::new_v4().to_string(),
+                backup_id: backup_id.to_string(),
+                destination: destination.name().to_string(),
+                remote_id: None,
+                remote_name: format!("{}.hxbk", file_name),
+                size: sealed.len() as u64,
+                status: CloudUploadStatus::Uploaded,
+                error: None,
+                created_at: Utc::now(),
+            };
+            
+            info!("Uploading backup {} to {}", backup_id, upload.destination);
+            let result = destination.upload(&upload.remote_name, sealed).await;
+            match &result {
+                Ok(remote_id) => upload.remote_id = Some(remote_id.clone()),
+                Err(e) => {
+                    upload.status = CloudUploadStatus::Failed;
+                    upload.error = Some(e.to_string());
+                }
+            }
+            self.save_cloud_upload(&upload).await?;
+            result?;
+            
+            match self.prune_cloud_backups(destination.as_ref(), settings.max_backups_to_keep).await {
+                Ok(deleted) if deleted > 0 => info!("Deleted {} old cloud backups", deleted),
+                Ok(_) => {}
+                Err(e) => warn!("Failed to prune cloud backups: {}", e),
+            }
+            
+            info!("Backup {} uploaded as {}", backup_id, upload.remote_name);
+            Ok(upload)
+        }
+        .instrument(span)
+        .await
+    }
+    
+    /// Upload attempts, newest first
+    pub async fn list_cloud_uploads(&self, limit: i64) -> Result<Vec<CloudUpload>> {
+        let rows = sqlx::query(
+            "SELECT id, backup_id, destination, remote_id, remote_name, size, status, error, created_at
+             FROM cloud_backup_uploads
+             ORDER BY created_at DESC
+             LIMIT ?"
+        )
+            .bind(limit)
+            .fetch_all(self.database.get_pool())
+            .await
+            .map_err(|e| HedgeXError::DatabaseError(e))?;
+        
+        Ok(rows.iter()
+            .map(|row| CloudUpload {
+                id: row.get("id"),
+                backup_id: row.get("backup_id"),
+                destination: row.get("destination"),
+                remote_id: row.get("remote_id"),
+                remote_name: row.get("remote_name"),
+                size: row.get::<i64, _>("size") as u64,
+                status: match row.get::<String, _>("status").as_str() {
+                    "Uploaded" => CloudUploadStatus::Uploaded,
+                    "Deleted" => CloudUploadStatus::Deleted,
+                    _ => CloudUploadStatus::Failed,
+                },
+                error: row.get("error"),
+                created_at: row.get("created_at"),
+            })
+            .collect())
+    }
+    
+    /// Backups stored at the configured destination, including those uploaded from other machines
+    pub async fn list_cloud_backups(&self) -> Result<Vec<RemoteBackup>> {
+        let settings = self.cloud_backup_settings().await?;
+        settings.destination.destination(self.http_client.clone())?.list().await
+    }
+    
+    /// Download a backup from the configured destination and keep it as a local backup to restore from
+    pub async fn download_from_cloud(&self, remote_id: &str) -> Result<BackupMetadata> {
+        let span = span!(Level::INFO, "download_from_cloud", remote_id = %remote_id);
+        
+        async move {
+            let settings = self.cloud_backup_settings().await?;
+            let destination = settings.destination.destination(self.http_client.clone())?;
+            
+            info!("Downloading cloud backup {} from {}", remote_id, destination.name());
+            let sealed = destination.download(remote_id).await?;
+            let backup_data = open_backup(&sealed, settings.passphrase()?)?;
+            
+            // Uploads carry the local backup file as it was, gzipped, encrypted or neither
+            let encrypted = EnhancedCryptoService::is_passphrase_encrypted(&backup_data);
+            let contents = if encrypted {
+                let passphrase = self.load_backup_passphrase().await?.ok_or_else(|| {
+                    HedgeXError::ValidationError("The cloud backup is encrypted; set its backup passphrase first".to_string())
+                })?;
+                self.crypto_service.decrypt_with_passphrase(&backup_data, &passphrase)?
+            } else {
+                backup_data.clone()
+            };
+            let compressed = contents.starts_with(&[0x1f, 0x8b]);
+            let checksum = if compressed {
+                self.crypto_service.calculate_checksum(&self.decompress_data(&contents)?)?
+            } else {
+                self.crypto_service.calculate_checksum(&contents)?
+            };
+            
+            let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+            let mut file_name = if compressed {
+                format!("hedgex_cloud_restore_{}.db.gz", timestamp)
+            } else {
+                format!("hedgex_cloud_restore_{}.db", timestamp)
+            };
+            if encrypted {
+                file_name.push_str(".enc");
+            }
+            let file_path = self.backup_dir.join(file_name);
+            tokio::fs::write(&file_path, &backup_data).await
+                .map_err(|e| HedgeXError::InternalError(format!("Failed to write downloaded backup: {}", e)))?;
+            
+            let metadata = BackupMetadata {
+                id: Uuid::new_v4().to_string(),
+                label: "cloud_restore".to_string(),
+                created_at: Utc::now(),
+                file_path,
+                file_size: backup_data.len() as u64,
+                compressed,
+                encrypted,
+                checksum,
+                backup_type: BackupType::Manual,
+            };
+            self.save_backup_metadata(&metadata).await?;
+            
+            info!("Cloud backup {} downloaded as backup {}", remote_id, metadata.id);
+            Ok(metadata)
+        }
+        .instrument(span)
+        .await
+    }
+    
     /// Export user data in specified format
     pub async fn export_data(&self, request: DataExportRequest) -> Result<PathBuf> {
         let span = span!(Level::INFO, "export_data", user_id = %request.user_id);
@@ -491,8 +1103,17 @@ impl DataPersistenceService {
                 ExportFormat::Json => format!("{}.json", base_filename),
                 ExportFormat::Csv => format!("{}.csv", base_filename),
                 ExportFormat::Sql => format!("{}.sql", base_filename),
+                ExportFormat::Xlsx => format!("{}.xlsx", base_filename),
+                ExportFormat::Pdf => {
+                    return Err(HedgeXError::ValidationError("Data exports cannot be written as PDF".to_string()));
+                }
             };
             
+            // Encryption works on text, which would corrupt a workbook
+            if matches!(request.format, ExportFormat::Xlsx) && request.encrypt && request.include_sensitive {
+                return Err(HedgeXError::ValidationError("Encrypted data exports cannot be written as XLSX".to_string()));
+            }
+            
             let export_path = self.export_dir.join(&filename);
             
             // Collect data based on export type
@@ -505,15 +1126,16 @@ impl DataPersistenceService {
             };
             
             // Format data according to requested format
-            let formatted_data = match request.format {
+            let mut final_data = match request.format {
                 ExportFormat::Json => serde_json::to_string_pretty(&export_data)
-                    .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?,
-                ExportFormat::Csv => self.format_as_csv(&export_data)?,
-                ExportFormat::Sql => self.format_as_sql(&export_data)?,
+                    .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?
+                    .into_bytes(),
+                ExportFormat::Csv => self.format_as_csv(&export_data)?.into_bytes(),
+                ExportFormat::Sql => self.format_as_sql(&export_data)?.into_bytes(),
+                ExportFormat::Xlsx => Self::format_as_xlsx(&request.export_type, &export_data),
+                ExportFormat::Pdf => unreachable!("rejected before collecting data"),
             };
             
-            let mut final_data = formatted_data.into_bytes();
-            
             // Compress if requested
             if request.compress {
                 final_data = self.compress_data(&final_data)?;
@@ -551,6 +1173,450 @@ impl DataPersistenceService {
         .await
     }
     
+    /// Export a backtest's summary metrics, trade list and equity curve
+    pub async fn export_backtest(&self, result: &BacktestResult, format: ExportFormat) -> Result<PathBuf> {
+        let summary = serde_json::json!({
+            "backtest_id": result.id,
+            "strategy_id": result.params.strategy_id,
+            "symbol": result.params.symbol,
+            "exchange": result.params.exchange,
+            "timeframe": result.params.timeframe.to_string(),
+            "start_date": result.params.start_date,
+            "end_date": result.params.end_date,
+            "initial_capital": result.params.initial_capital,
+            "total_trades": result.total_trades,
+            "winning_trades": result.winning_trades,
+            "losing_trades": result.losing_trades,
+            "final_pnl": result.final_pnl,
+            "max_drawdown": result.max_drawdown,
+            "sharpe_ratio": result.sharpe_ratio,
+            "win_rate": result.win_rate,
+            "profit_factor": result.profit_factor,
+            "total_charges": result.total_charges,
+        });
+        let trades = serde_json::to_value(&result.trades)
+            .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+        let equity_curve = serde_json::to_value(&result.equity_curve)
+            .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+        
+        let (extension, contents) = match format {
+            ExportFormat::Json => {
+                let data = serde_json::json!({
+                    "summary": summary,
+                    "trades": trades,
+                    "equity_curve": equity_curve,
+                });
+                let json = serde_json::to_string_pretty(&data)
+                    .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+                ("json", json)
+            }
+            ExportFormat::Csv => {
+                // One file with a section per table, separated by blank lines
+                let mut csv = String::from("# Summary\n");
+                csv.push_str(&self.format_as_csv(&serde_json::Value::Array(vec![summary]))?);
+                csv.push_str("\n# Trades\n");
+                csv.push_str(&self.format_as_csv(&trades)?);
+                csv.push_str("\n# Equity curve\n");
+                csv.push_str(&self.format_as_csv(&equity_curve)?);
+                ("csv", csv)
+            }
+            ExportFormat::Sql | ExportFormat::Pdf | ExportFormat::Xlsx => {
+                return Err(HedgeXError::ValidationError("Backtests can only be exported as JSON or CSV".to_string()));
+            }
+        };
+        
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+        let export_path = self.export_dir.join(format!("hedgex_backtest_{}_{}.{}", result.id, timestamp, extension));
+        
+        tokio::fs::write(&export_path, contents).await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to write export file: {}", e)))?;
+        
+        info!("Backtest {} exported to {:?}", result.id, export_path);
+        Ok(export_path)
+    }
+    
+    /// Export a tax report as JSON or as CSV sections for the user's accountant
+    pub async fn export_tax_report(&self, report: &TaxReport, format: ExportFormat) -> Result<PathBuf> {
+        let summary = serde_json::to_value(&report.categories)
+            .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+        let tax = serde_json::to_value(&report.tax)
+            .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+        let positions = serde_json::to_value(&report.positions)
+            .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+        
+        let (extension, contents) = match format {
+            ExportFormat::Json => {
+                let json = serde_json::to_string_pretty(report)
+                    .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+                ("json", json)
+            }
+            ExportFormat::Csv => {
+                let mut csv = format!("# Financial year {} ({} to {})\n", report.financial_year, report.from, report.to);
+                csv.push_str("\n# Summary\n");
+                csv.push_str(&self.format_as_csv(&summary)?);
+                csv.push_str("\n# Estimated tax\n");
+                csv.push_str(&self.format_as_csv(&serde_json::Value::Array(vec![tax]))?);
+                csv.push_str("\n# Closed positions\n");
+                csv.push_str(&self.format_as_csv(&positions)?);
+                ("csv", csv)
+            }
+            ExportFormat::Sql | ExportFormat::Pdf | ExportFormat::Xlsx => {
+                return Err(HedgeXError::ValidationError("Tax reports can only be exported as JSON or CSV".to_string()));
+            }
+        };
+        
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+        let export_path = self.export_dir.join(format!("hedgex_tax_report_{}_{}.{}", report.financial_year, timestamp, extension));
+        
+        tokio::fs::write(&export_path, contents).await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to write export file: {}", e)))?;
+        
+        info!("Tax report for {} exported to {:?}", report.financial_year, export_path);
+        Ok(export_path)
+    }
+    
+    /// Write a P&L statement as JSON, CSV sections, an Excel workbook or a printable PDF
+    pub async fn export_pnl_statement(&self, statement: &PnlStatement, format: ExportFormat) -> Result<PathBuf> {
+        let (extension, contents) = match format {
+            ExportFormat::Json => {
+                let json = serde_json::to_string_pretty(statement)
+                    .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+                ("json", json.into_bytes())
+            }
+            ExportFormat::Csv => {
+                let section = |value: serde_json::Result<serde_json::Value>| {
+                    value.map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))
+                };
+                let mut csv = format!("# P&L statement {} to {}\n", statement.from, statement.to);
+                csv.push_str("\n# Trades\n");
+                csv.push_str(&self.format_as_csv(&section(serde_json::to_value(&statement.trades))?)?);
+                csv.push_str("\n# Daily subtotals\n");
+                csv.push_str(&self.format_as_csv(&section(serde_json::to_value(&statement.days))?)?);
+                csv.push_str("\n# Strategy subtotals\n");
+                csv.push_str(&self.format_as_csv(&section(serde_json::to_value(&statement.strategies))?)?);
+                csv.push_str("\n# Total\n");
+                csv.push_str(&self.format_as_csv(&section(serde_json::to_value(vec![&statement.totals]))?)?);
+                ("csv", csv.into_bytes())
+            }
+            ExportFormat::Xlsx => {
+                let sheet = |name: &str, value: serde_json::Result<serde_json::Value>| {
+                    value
+                        .map(|records| XlsxSheet::from_records(name, &records))
+                        .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))
+                };
+                let sheets = vec![
+                    sheet("Trades", serde_json::to_value(&statement.trades))?,
+                    sheet("Daily P&L", serde_json::to_value(&statement.days))?,
+                    sheet("Strategies", serde_json::to_value(&statement.strategies))?,
+                    sheet("Open positions", serde_json::to_value(&statement.open_positions))?,
+                    sheet("Total", serde_json::to_value(&statement.totals))?,
+                ];
+                ("xlsx", render_xlsx(&sheets))
+            }
+            ExportFormat::Pdf => {
+                let title = format!("HedgeX P&L statement, {} to {}", statement.from, statement.to);
+                ("pdf", render_text_pdf(&title, &statement.to_text_lines()))
+            }
+            ExportFormat::Sql => {
+                return Err(HedgeXError::ValidationError("P&L statements can be exported as JSON, CSV, XLSX or PDF".to_string()));
+            }
+        };
+        
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+        let export_path = self.export_dir.join(format!(
+            "hedgex_pnl_statement_{}_{}_{}.{}",
+            statement.from, statement.to, timestamp, extension
+        ));
+        
+        tokio::fs::write(&export_path, contents).await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to write export file: {}", e)))?;
+        
+        info!("P&L statement {} to {} exported to {:?}", statement.from, statement.to, export_path);
+        Ok(export_path)
+    }
+    
+    /// List a user's market data recording sessions, newest first
+    pub async fn list_recording_sessions(&self, user_id: &str) -> Result<Vec<RecordingSessionInfo>> {
+        let query = r#"
+            SELECT id, name, symbols, started_at, stopped_at, tick_count
+            FROM recording_sessions
+            WHERE user_id = ?
+            ORDER BY started_at DESC
+        "#;
+        
+        let rows = sqlx::query(query)
+            .bind(user_id)
+            .fetch_all(self.database.get_read_pool())
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+        
+        rows.iter().map(Self::recording_session_from_row).collect()
+    }
+    
+    /// Export a recording session's ticks and depth snapshots as one gzipped JSON bundle
+    pub async fn export_recording_session(&self, session_id: &str) -> Result<PathBuf> {
+        let row = sqlx::query(
+            "SELECT id, name, symbols, started_at, stopped_at, tick_count FROM recording_sessions WHERE id = ?"
+        )
+        .bind(session_id)
+        .fetch_optional(self.database.get_read_pool())
+        .await
+        .map_err(HedgeXError::DatabaseError)?
+        .ok_or_else(|| HedgeXError::NotFoundError(format!("Recording session {} not found", session_id)))?;
+        let session = Self::recording_session_from_row(&row)?;
+        
+        let tick_rows = sqlx::query(
+            "SELECT symbol, timestamp, ltp, bid, ask, volume, depth FROM recording_session_ticks WHERE session_id = ? ORDER BY id"
+        )
+        .bind(session_id)
+        .fetch_all(self.database.get_read_pool())
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+        
+        let mut ticks = Vec::with_capacity(tick_rows.len());
+        for tick in &tick_rows {
+            let depth = match tick.get::<Option<String>, _>("depth") {
+                Some(depth) => serde_json::from_str(&depth)?,
+                None => serde_json::Value::Null,
+            };
+            ticks.push(serde_json::json!({
+                "symbol": tick.get::<String, _>("symbol"),
+                "timestamp": tick.get::<DateTime<Utc>, _>("timestamp"),
+                "ltp": tick.get::<f64, _>("ltp"),
+                "bid": tick.get::<f64, _>("bid"),
+                "ask": tick.get::<f64, _>("ask"),
+                "volume": tick.get::<i64, _>("volume"),
+                "depth": depth,
+            }));
+        }
+        
+        let bundle = serde_json::json!({
+            "session": session,
+            "ticks": ticks,
+        });
+        let json = serde_json::to_vec(&bundle)
+            .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+        
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+        let export_path = self.export_dir.join(format!("hedgex_recording_{}_{}.json.gz", session_id, timestamp));
+        tokio::fs::write(&export_path, self.compress_data(&json)?).await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to write export file: {}", e)))?;
+        
+        info!("Recording session {} exported to {:?} with {} ticks", session_id, export_path, ticks.len());
+        Ok(export_path)
+    }
+    
+    fn recording_session_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<RecordingSessionInfo> {
+        Ok(RecordingSessionInfo {
+            id: row.get("id"),
+            name: row.get("name"),
+            symbols: serde_json::from_str(&row.get::<String, _>("symbols"))?,
+            started_at: row.get("started_at"),
+            stopped_at: row.get("stopped_at"),
+            tick_count: row.get("tick_count"),
+        })
+    }
+    
+    /// Export a user's strategies, stock selections, settings and trade history into one versioned bundle
+    ///
+    /// Credentials are only included when a passphrase is given: they are decrypted and sealed
+    /// with it, so the bundle opens on a machine with different local keys.
+    pub async fn export_profile(&self, user_id: &str, passphrase: Option<&str>) -> Result<PathBuf> {
+        let tables = profile_bundle::export_tables(self.database.get_read_pool(), user_id).await?;
+        let credentials = match passphrase.filter(|passphrase| !passphrase.is_empty()) {
+            Some(passphrase) => {
+                let credentials = self.load_profile_credentials(user_id).await?;
+                let json = serde_json::to_vec(&credentials)
+                    .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+                Some(general_purpose::STANDARD.encode(seal_backup(&json, passphrase)?))
+            }
+            None => None,
+        };
+        
+        let bundle = ProfileBundle {
+            version: PROFILE_BUNDLE_VERSION,
+            created_at: Utc::now(),
+            user_id: user_id.to_string(),
+            tables,
+            credentials,
+        };
+        let json = serde_json::to_vec(&bundle)
+            .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+        
+        let timestamp = Utc::now().format("%Y%m%d_%H%M%S");
+        let export_path = self.export_dir.join(format!("hedgex_profile_{}_{}.json.gz", user_id, timestamp));
+        tokio::fs::write(&export_path, self.compress_data(&json)?).await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to write export file: {}", e)))?;
+        
+        let rows: usize = bundle.tables.iter().map(|table| table.rows.len()).sum();
+        info!("Profile of {} exported to {:?} with {} rows", user_id, export_path, rows);
+        Ok(export_path)
+    }
+    
+    /// Import a bundle written by `export_profile` into an existing user
+    ///
+    /// Everything is written in one transaction after a backup of the current database. Sealed
+    /// credentials are skipped without a passphrase and re-encrypted with this machine's keys.
+    pub async fn import_profile(
+        &self,
+        bundle_path: &Path,
+        user_id: &str,
+        passphrase: Option<&str>,
+        resolution: ConflictResolution,
+    ) -> Result<ProfileImportReport> {
+        let data = tokio::fs::read(bundle_path).await
+            .map_err(|e| HedgeXError::InternalError(format!("Failed to read profile bundle: {}", e)))?;
+        let bundle: ProfileBundle = serde_json::from_slice(&self.decompress_data(&data)?)
+            .map_err(|e| HedgeXError::ValidationError(format!("Not a HedgeX profile bundle: {}", e)))?;
+        if bundle.version > PROFILE_BUNDLE_VERSION {
+            return Err(HedgeXError::ValidationError(format!(
+                "Profile bundle version {} is newer than this build supports ({})",
+                bundle.version, PROFILE_BUNDLE_VERSION
+            )));
+        }
+        
+        let user_exists = sqlx::query("SELECT 1 FROM users WHERE id = ?")
+            .bind(user_id)
+            .fetch_optional(self.database.get_pool())
+            .await
+            .map_err(HedgeXError::DatabaseError)?
+            .is_some();
+        if !user_exists {
+            return Err(HedgeXError::NotFoundError(format!("User {} not found", user_id)));
+        }
+        
+        let credentials = match (&bundle.credentials, passphrase.filter(|passphrase| !passphrase.is_empty())) {
+            (Some(sealed), Some(passphrase)) => {
+                let sealed = general_purpose::STANDARD.decode(sealed)
+                    .map_err(|e| HedgeXError::ValidationError(format!("Corrupted credentials in profile bundle: {}", e)))?;
+                let json = open_backup(&sealed, passphrase)
+                    .map_err(|_| HedgeXError::CryptoError("Wrong passphrase for the profile bundle's credentials".to_string()))?;
+                Some(serde_json::from_slice::<ProfileCredentials>(&json)?)
+            }
+            (Some(_), None) => {
+                warn!("Profile bundle holds credentials but no passphrase was given; skipping them");
+                None
+            }
+            (None, _) => None,
+        };
+        let credentials = match credentials {
+            Some(credentials) => Some(self.encrypt_profile_credentials(&credentials).await?),
+            None => None,
+        };
+        
+        self.create_backup("pre_profile_import", BackupType::Manual).await?;
+        
+        let mut tx = self.database.get_pool().begin().await.map_err(HedgeXError::DatabaseError)?;
+        let tables = profile_bundle::import_tables(&mut tx, &bundle.tables, user_id, resolution).await?;
+        let mut credentials_imported = false;
+        if let Some(credentials) = credentials {
+            for (table, stored) in [("kite_credentials", credentials.kite), ("api_credentials", credentials.api)] {
+                let Some(stored) = stored else { continue };
+                let on_conflict = match resolution {
+                    ConflictResolution::Skip => "DO NOTHING",
+                    ConflictResolution::Overwrite => "DO UPDATE SET api_key = excluded.api_key, api_secret = excluded.api_secret,
+                        access_token = excluded.access_token, access_token_expiry = excluded.access_token_expiry",
+                };
+                let sql = format!(
+                    "INSERT INTO {} (user_id, api_key, api_secret, access_token, access_token_expiry) VALUES (?, ?, ?, ?, ?)
+                     ON CONFLICT(user_id) {}",
+                    table, on_conflict
+                );
+                let written = sqlx::query(&sql)
+                    .bind(user_id)
+                    .bind(&stored.api_key)
+                    .bind(&stored.api_secret)
+                    .bind(&stored.access_token)
+                    .bind(stored.access_token_expiry)
+                    .execute(&mut *tx)
+                    .await
+                    .map_err(HedgeXError::DatabaseError)?;
+                credentials_imported |= written.rows_affected() > 0;
+            }
+        }
+        tx.commit().await.map_err(HedgeXError::DatabaseError)?;
+        
+        info!("Imported profile of {} exported {} into {}", bundle.user_id, bundle.created_at, user_id);
+        Ok(ProfileImportReport {
+            source_user_id: bundle.user_id,
+            bundle_created_at: bundle.created_at,
+            tables,
+            credentials_imported,
+        })
+    }
+    
+    /// A user's stored credentials, decrypted
+    async fn load_profile_credentials(&self, user_id: &str) -> Result<ProfileCredentials> {
+        let mut credentials = ProfileCredentials::default();
+        
+        let kite = sqlx::query("SELECT api_key, api_secret, access_token, access_token_expiry FROM kite_credentials WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(self.database.get_read_pool())
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+        if let Some(row) = kite {
+            let access_token = match row.get::<Option<String>, _>("access_token") {
+                Some(token) => Some(self.crypto_service.decrypt_sensitive("kite_access_token", &token).await?),
+                None => None,
+            };
+            credentials.kite = Some(StoredCredentials {
+                api_key: row.get("api_key"),
+                api_secret: self.crypto_service.decrypt_sensitive("kite_api_secret", &row.get::<String, _>("api_secret")).await?,
+                access_token,
+                access_token_expiry: row.get("access_token_expiry"),
+            });
+        }
+        
+        let api = sqlx::query("SELECT api_key, api_secret, access_token, access_token_expiry FROM api_credentials WHERE user_id = ?")
+            .bind(user_id)
+            .fetch_optional(self.database.get_read_pool())
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+        if let Some(row) = api {
+            let (api_key, api_secret) = self.crypto_service
+                .decrypt_api_credentials(&row.get::<String, _>("api_key"), &row.get::<String, _>("api_secret"))
+                .await?;
+            credentials.api = Some(StoredCredentials {
+                api_key,
+                api_secret,
+                access_token: row.get("access_token"),
+                access_token_expiry: row.get("access_token_expiry"),
+            });
+        }
+        
+        Ok(credentials)
+    }
+    
+    /// Credentials encrypted the way each table stores them on this machine
+    async fn encrypt_profile_credentials(&self, credentials: &ProfileCredentials) -> Result<ProfileCredentials> {
+        let mut encrypted = ProfileCredentials::default();
+        
+        if let Some(kite) = &credentials.kite {
+            let access_token = match &kite.access_token {
+                Some(token) => Some(self.crypto_service.encrypt_sensitive("kite_access_token", token).await?),
+                None => None,
+            };
+            encrypted.kite = Some(StoredCredentials {
+                api_key: kite.api_key.clone(),
+                api_secret: self.crypto_service.encrypt_sensitive("kite_api_secret", &kite.api_secret).await?,
+                access_token,
+                access_token_expiry: kite.access_token_expiry,
+            });
+        }
+        
+        if let Some(api) = &credentials.api {
+            let (api_key, api_secret) = self.crypto_service.encrypt_api_credentials(&api.api_key, &api.api_secret).await?;
+            encrypted.api = Some(StoredCredentials {
+                api_key,
+                api_secret,
+                access_token: api.access_token.clone(),
+                access_token_expiry: api.access_token_expiry,
+            });
+        }
+        
+        Ok(encrypted)
+    }
+    
     /// Save user settings
     pub async fn save_user_settings(&self, settings: &UserSettings) -> Result<()> {
         let query = r#"
@@ -634,9 +1700,10 @@ impl DataPersistenceService {
         let span = span!(Level::INFO, "cleanup_old_logs");
         
         async move {
-            info!("Cleaning up logs older than {} days", self.config.log_retention_days);
+            let retention_days = self.get_config().log_retention_days;
+            info!("Cleaning up logs older than {} days", retention_days);
             
-            let cutoff_date = Utc::now() - ChronoDuration::days(self.config.log_retention_days);
+            let cutoff_date = Utc::now() - ChronoDuration::days(retention_days);
             
             let query = "DELETE FROM system_logs WHERE created_at < ?";
             let result = sqlx::query(query)
@@ -652,7 +1719,7 @@ impl DataPersistenceService {
                 let mut logger_guard = self.logger.lock().await;
                 let mut data = std::collections::HashMap::new();
                 data.insert("deleted_count".to_string(), serde_json::Value::Number(serde_json::Number::from(deleted_count)));
-                data.insert("retention_days".to_string(), serde_json::Value::Number(serde_json::Number::from(self.config.log_retention_days)));
+                data.insert("retention_days".to_string(), serde_json::Value::Number(serde_json::Number::from(retention_days)));
                 
                 let _ = logger_guard.info_structured(
                     "Old logs cleaned up successfully",
@@ -668,96 +1735,72 @@ impl DataPersistenceService {
         .await
     }
     
-    /// Archive old trade data
+    /// Move trades older than the retention period into the monthly archives
     pub async fn archive_old_trade_data(&self) -> Result<usize> {
         let span = span!(Level::INFO, "archive_old_trade_data");
         
         async move {
-            info!("Archiving trade data older than {} days", self.config.trade_data_retention_days);
+            let retention_days = self.get_config().trade_data_retention_days;
+            info!("Archiving trade data older than {} days", retention_days);
             
-            let cutoff_date = Utc::now() - ChronoDuration::days(self.config.trade_data_retention_days);
+            let cutoff_date = Utc::now() - ChronoDuration::days(retention_days);
+            let archived_count = self.archive.archive_before(self.database.get_pool(), "trades", cutoff_date).await?;
             
-            // First, export old trade data to archive
-            let archive_filename = format!("archived_trades_{}.json", Utc::now().format("%Y%m%d"));
-            let archive_path = self.export_dir.join(&archive_filename);
-            
-            let query = r#"
-                SELECT id, user_id, symbol, exchange, order_id, trade_type, quantity, price, 
-                       status, executed_at, strategy_id
-                FROM trades 
-                WHERE executed_at < ?
-                ORDER BY executed_at
-            "#;
-            
-            let rows = sqlx::query(query)
-                .bind(cutoff_date)
-                .fetch_all(self.database.get_pool())
-                .await
-                .map_err(|e| HedgeXError::DatabaseError(e))?;
-            
-            if rows.is_empty() {
+            if archived_count == 0 {
                 debug!("No old trade data to archive");
                 return Ok(0);
             }
             
-            // Convert to JSON and save archive
-            let mut archived_trades = Vec::new();
-            for row in &rows {
-                archived_trades.push(serde_json::json!({
-                    "id": row.get::<String, _>("id"),
-                    "user_id": row.get::<String, _>("user_id"),
-                    "symbol": row.get::<String, _>("symbol"),
-                    "exchange": row.get::<String, _>("exchange"),
-                    "order_id": row.get::<Option<String>, _>("order_id"),
-                    "trade_type": row.get::<String, _>("trade_type"),
-                    "quantity": row.get::<i32, _>("quantity"),
-                    "price": row.get::<f64, _>("price"),
-                    "status": row.get::<String, _>("status"),
-                    "executed_at": row.get::<DateTime<Utc>, _>("executed_at").to_rfc3339(),
-                    "strategy_id": row.get::<String, _>("strategy_id"),
-                }));
-            }
-            
-            let archive_data = serde_json::to_string_pretty(&archived_trades)
-                .map_err(|e| HedgeXError::InternalError(format!("Failed to serialize archive data: {}", e)))?;
-            
-            // Compress archive data
-            let compressed_data = self.compress_data(archive_data.as_bytes())?;
-            
-            tokio::fs::write(&archive_path, compressed_data).await
-                .map_err(|e| HedgeXError::InternalError(format!("Failed to write archive file: {}", e)))?;
-            
-            // Delete archived trades from main table
-            let delete_query = "DELETE FROM trades WHERE executed_at < ?";
-            let result = sqlx::query(delete_query)
-                .bind(cutoff_date)
-                .execute(self.database.get_pool())
-                .await
-                .map_err(|e| HedgeXError::DatabaseError(e))?;
+            self.log_archival("trades", archived_count).await;
+            Ok(archived_count)
+        }
+        .instrument(span)
+        .await
+    }
+    
+    /// Move recorded ticks older than the hot period into the monthly archives
+    pub async fn archive_old_tick_data(&self) -> Result<usize> {
+        let span = span!(Level::INFO, "archive_old_tick_data");
+        
+        async move {
+            let hot_days = self.get_config().tick_data_hot_days;
+            info!("Archiving ticks older than {} days", hot_days);
             
-            let archived_count = result.rows_affected() as usize;
+            let cutoff_date = Utc::now() - ChronoDuration::days(hot_days);
+            let archived_count = self.archive.archive_before(self.database.get_pool(), "market_ticks", cutoff_date).await?;
             
-            // Log archival results
-            {
-                let mut logger_guard = self.logger.lock().await;
-                let mut data = std::collections::HashMap::new();
-                data.insert("archived_count".to_string(), serde_json::Value::Number(serde_json::Number::from(archived_count)));
-                data.insert("archive_file".to_string(), serde_json::Value::String(archive_filename));
-                
-                let _ = logger_guard.info_structured(
-                    "Old trade data archived successfully",
-                    Some("persistence"),
-                    data
-                ).await;
+            if archived_count > 0 {
+                self.log_archival("market_ticks", archived_count).await;
             }
-            
-            info!("Archived {} old trade records to: {:?}", archived_count, archive_path);
             Ok(archived_count)
         }
         .instrument(span)
         .await
     }
     
+    async fn log_archival(&self, table: &str, archived_count: usize) {
+        let logger_guard = self.logger.lock().await;
+        let mut data = std::collections::HashMap::new();
+        data.insert("table".to_string(), serde_json::Value::String(table.to_string()));
+        data.insert("archived_count".to_string(), serde_json::Value::Number(serde_json::Number::from(archived_count)));
+        
+        let _ = logger_guard.info_structured(
+            "Old data moved to the archives",
+            Some("persistence"),
+            data
+        ).await;
+    }
+    
+    /// Monthly archive databases, oldest first
+    pub async fn list_archive_partitions(&self) -> Result<Vec<ArchivePartition>> {
+        self.archive.list_partitions().await
+    }
+    
+    /// Archive holding rows moved out of the hot tables
+    pub fn get_archive(&self) -> &DataArchive {
+        &self.archive
+    }
+    
     /// Perform secure data deletion for application uninstall
     pub async fn secure_delete_all_data(&self) -> Result<()> {
         let span = span!(Level::WARN, "secure_delete_all_data");
@@ -823,13 +1866,24 @@ impl DataPersistenceService {
     }
     
     /// Get data persistence configuration
-    pub fn get_config(&self) -> &DataPersistenceConfig {
-        &self.config
+    pub fn get_config(&self) -> DataPersistenceConfig {
+        self.config.read().unwrap_or_else(|e| e.into_inner()).clone()
     }
     
-    /// Update data persistence configuration
-    pub async fn update_config(&mut self, config: DataPersistenceConfig) -> Result<()> {
-        self.config = config;
+    /// Validate and store the configuration, which then applies from the next backup or cleanup
+    pub async fn update_config(&self, config: DataPersistenceConfig) -> Result<()> {
+        config.validate()?;
+        if config.encrypt_backups && self.load_backup_passphrase().await?.is_none() {
+            return Err(HedgeXError::ValidationError("Set a backup passphrase before turning on backup encryption".to_string()));
+        }
+        
+        sqlx::query("INSERT OR REPLACE INTO persistence_settings (id, settings, updated_at) VALUES (1, ?, ?)")
+            .bind(serde_json::to_string(&config)?)
+            .bind(Utc::now())
+            .execute(self.database.get_pool())
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+        *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
         
         // Log configuration update
         {
@@ -840,6 +1894,37 @@ impl DataPersistenceService {
         Ok(())
     }
     
+    /// Replace the configuration given at startup with the one last saved, if any
+    async fn load_stored_config(&self) {
+        let stored = sqlx::query_scalar::<_, String>("SELECT settings FROM persistence_settings WHERE id = 1")
+            .fetch_optional(self.database.get_pool())
+            .await;
+        match stored {
+            Ok(Some(settings)) => match serde_json::from_str::<DataPersistenceConfig>(&settings) {
+                Ok(config) if config.validate().is_ok() => {
+                    *self.config.write().unwrap_or_else(|e| e.into_inner()) = config;
+                    info!("Loaded saved data persistence configuration");
+                }
+                Ok(_) => warn!("Ignoring invalid saved data persistence configuration"),
+                Err(e) => warn!("Ignoring unreadable saved data persistence configuration: {}", e),
+            },
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load saved data persistence configuration: {}", e),
+        }
+    }
+    
+    /// Clean up logs, archive old trades and ticks, and delete backups beyond the retention
+    pub async fn run_cleanup(&self) -> Result<CleanupReport> {
+        let report = CleanupReport {
+            logs_cleaned: self.cleanup_old_logs().await?,
+            trades_archived: self.archive_old_trade_data().await?,
+            ticks_archived: self.archive_old_tick_data().await?,
+            backups_cleaned: self.cleanup_old_backups().await?,
+        };
+        info!("Cleanup finished: {:?}", report);
+        Ok(report)
+    }
+    
     // Private helper methods
     
     /// Save backup metadata to database
@@ -947,6 +2032,180 @@ impl DataPersistenceService {
         Ok(())
     }
     
+    /// Replace every table of the open database with those of a database file
+    async fn replace_database_contents(
+        &self,
+        source: &Path,
+        backup_id: &str,
+        progress: &(dyn Fn(RestoreProgress) + Send + Sync),
+    ) -> Result<()> {
+        let mut conn = self.database.get_pool().acquire().await
+            .map_err(|e| HedgeXError::DatabaseError(e))?;
+        
+        // Foreign keys can only be switched outside a transaction
+        let foreign_keys: i64 = sqlx::query_scalar("PRAGMA foreign_keys")
+            .fetch_one(&mut *conn)
+            .await
+            .map_err(|e| HedgeXError::DatabaseError(e))?;
+        sqlx::query("PRAGMA foreign_keys = OFF").execute(&mut *conn).await
+            .map_err(|e| HedgeXError::DatabaseError(e))?;
+        sqlx::query("ATTACH DATABASE ? AS restored")
+            .bind(source.to_string_lossy().to_string())
+            .execute(&mut *conn)
+            .await
+            .map_err(|e| HedgeXError::DatabaseError(e))?;
+        
+        let result = async {
+            // Writers on other connections wait until the copy commits
+            sqlx::query("BEGIN IMMEDIATE").execute(&mut *conn).await?;
+            
+            let copied = async {
+                let current = sqlx::query(
+                    "SELECT type, name FROM main.sqlite_master
+                     WHERE type IN ('table', 'view') AND name NOT LIKE 'sqlite_%'"
+                )
+                    .fetch_all(&mut *conn)
+                    .await?;
+                for row in &current {
+                    let name: String = row.get("name");
+                    if RESTORE_PRESERVED_TABLES.contains(&name.as_str()) {
+                        continue;
+                    }
+                    let kind = if row.get::<String, _>("type") == "view" { "VIEW" } else { "TABLE" };
+                    sqlx::query(&format!("DROP {} IF EXISTS main.{}", kind, quote_identifier(&name)))
+                        .execute(&mut *conn)
+                        .await?;
+                }
+                
+                let schema = sqlx::query(
+                    "SELECT type, name, tbl_name, sql FROM restored.sqlite_master
+                     WHERE sql IS NOT NULL AND name NOT LIKE 'sqlite_%'
+                     ORDER BY rowid"
+                )
+                    .fetch_all(&mut *conn)
+                    .await?;
+                let schema: Vec<(String, String, String)> = schema.iter()
+                    .map(|row| (row.get::<String, _>("type"), row.get::<String, _>("tbl_name"), row.get::<String, _>("sql")))
+                    .filter(|(_, table, _)| !RESTORE_PRESERVED_TABLES.contains(&table.as_str()) && table != BACKUP_PARTITIONS_TABLE)
+                    .collect();
+                let tables: Vec<&str> = schema.iter()
+                    .filter(|(kind, _, _)| kind == "table")
+                    .map(|(_, table, _)| table.as_str())
+                    .collect();
+                
+                // Tables first and their rows, then indexes, triggers and views over them
+                for (kind, _, sql) in &schema {
+                    if kind == "table" {
+                        sqlx::query(sql).execute(&mut *conn).await?;
+                    }
+                }
+                for (copied, table) in tables.iter().enumerate() {
+                    let percent = 25 + (60 * copied / tables.len().max(1)) as u8;
+                    progress(RestoreProgress::new(backup_id, RestoreStage::Copying, percent, format!("Restoring {}", table)));
+                    let table = quote_identifier(table);
+                    sqlx::query(&format!("INSERT INTO main.{} SELECT * FROM restored.{}", table, table))
+                        .execute(&mut *conn)
+                        .await?;
+                }
+                for (kind, _, sql) in &schema {
+                    if kind != "table" {
+                        sqlx::query(sql).execute(&mut *conn).await?;
+                    }
+                }
+                
+                // Keep AUTOINCREMENT counters of the restored tables
+                let sequences: i64 = sqlx::query_scalar(
+                    "SELECT COUNT(*) FROM restored.sqlite_master WHERE name = 'sqlite_sequence'"
+                )
+                    .fetch_one(&mut *conn)
+                    .await?;
+                if sequences > 0 {
+                    sqlx::query("DELETE FROM main.sqlite_sequence").execute(&mut *conn).await?;
+                    sqlx::query("INSERT INTO main.sqlite_sequence SELECT * FROM restored.sqlite_sequence")
+                        .execute(&mut *conn)
+                        .await?;
+                }
+                Ok::<_, sqlx::Error>(())
+            }.await;
+            
+            match copied {
+                Ok(()) => {
+                    sqlx::query("COMMIT").execute(&mut *conn).await?;
+                    Ok(())
+                }
+                Err(e) => {
+                    let _ = sqlx::query("ROLLBACK").execute(&mut *conn).await;
+                    Err(e)
+                }
+            }
+        }.await;
+        
+        let _ = sqlx::query("DETACH DATABASE restored").execute(&mut *conn).await;
+        let _ = sqlx::query(&format!("PRAGMA foreign_keys = {}", foreign_keys)).execute(&mut *conn).await;
+        result.map_err(|e| HedgeXError::DatabaseError(e))
+    }
+    
+    /// Cloud backup settings, failing when no destination is configured
+    async fn cloud_backup_settings(&self) -> Result<CloudBackupSettings> {
+        self.load_cloud_backup_settings().await?
+            .ok_or_else(|| HedgeXError::ValidationError("Cloud backup is not configured".to_string()))
+    }
+    
+    /// Record an upload attempt
+    async fn save_cloud_upload(&self, upload: &CloudUpload) -> Result<()> {
+        sqlx::query(
+            "INSERT INTO cloud_backup_uploads
+                (id, backup_id, destination, remote_id, remote_name, size, status, error, created_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+        )
+            .bind(&upload.id)
+            .bind(&upload.backup_id)
+            .bind(&upload.destination)
+            .bind(&upload.remote_id)
+            .bind(&upload.remote_name)
+            .bind(upload.size as i64)
+            .bind(format!("{:?}", upload.status))
+            .bind(&upload.error)
+            .bind(upload.created_at)
+            .execute(self.database.get_pool())
+            .await
+            .map_err(|e| HedgeXError::DatabaseError(e))?;
+        Ok(())
+    }
+    
+    /// Delete uploads to a destination beyond the newest `keep`, returning how many were deleted
+    ///
+    /// Only backups this app uploaded are considered, so other files at the destination are left alone.
+    async fn prune_cloud_backups(&self, destination: &dyn BackupDestination, keep: usize) -> Result<usize> {
+        let rows = sqlx::query(
+            "SELECT id, remote_id FROM cloud_backup_uploads
+             WHERE destination = ? AND status = 'Uploaded'
+             ORDER BY created_at DESC"
+        )
+            .bind(destination.name())
+            .fetch_all(self.database.get_pool())
+            .await
+            .map_err(|e| HedgeXError::DatabaseError(e))?;
+        
+        let mut deleted_count = 0;
+        for row in rows.iter().skip(keep) {
+            let remote_id: String = row.get("remote_id");
+            if let Err(e) = destination.delete(&remote_id).await {
+                warn!("Failed to delete cloud backup {}: {}", remote_id, e);
+                continue;
+            }
+            
+            sqlx::query("UPDATE cloud_backup_uploads SET status = 'Deleted' WHERE id = ?")
+                .bind(row.get::<String, _>("id"))
+                .execute(self.database.get_pool())
+                .await
+                .map_err(|e| HedgeXError::DatabaseError(e))?;
+            deleted_count += 1;
+        }
+        
+        Ok(deleted_count)
+    }
+    
     /// Compress data using gzip
     fn compress_data(&self, data: &[u8]) -> Result<Vec<u8>> {
         let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
@@ -1019,7 +2278,7 @@ impl DataPersistenceService {
         }
         
         let rows = sqlx_query
-            .fetch_all(self.database.get_pool())
+            .fetch_all(self.database.get_read_pool())
             .await
             .map_err(|e| HedgeXError::DatabaseError(e))?;
         
@@ -1057,7 +2316,7 @@ impl DataPersistenceService {
         
         let rows = sqlx::query(query)
             .bind(&request.user_id)
-            .fetch_all(self.database.get_pool())
+            .fetch_all(self.database.get_read_pool())
             .await
             .map_err(|e| HedgeXError::DatabaseError(e))?;
         
@@ -1111,7 +2370,7 @@ impl DataPersistenceService {
         }
         
         let rows = sqlx_query
-            .fetch_all(self.database.get_pool())
+            .fetch_all(self.database.get_read_pool())
             .await
             .map_err(|e| HedgeXError::DatabaseError(e))?;
         
@@ -1177,6 +2436,20 @@ impl DataPersistenceService {
         }
     }
     
+    /// Format data as an Excel workbook, with a sheet per table of a full export
+    fn format_as_xlsx(export_type: &ExportType, data: &serde_json::Value) -> Vec<u8> {
+        let sheets = match (export_type, data) {
+            (ExportType::AllData, serde_json::Value::Object(tables)) => tables.iter()
+                .map(|(name, records)| XlsxSheet::from_records(name, records))
+                .collect(),
+            (ExportType::TradeHistory, _) => vec![XlsxSheet::from_records("Trades", data)],
+            (ExportType::StrategyData, _) => vec![XlsxSheet::from_records("Strategies", data)],
+            (ExportType::UserSettings, _) => vec![XlsxSheet::from_records("Settings", data)],
+            _ => vec![XlsxSheet::from_records("Logs", data)],
+        };
+        render_xlsx(&sheets)
+    }
+    
     /// Format data as SQL
     fn format_as_sql(&self, data: &serde_json::Value) -> Result<String> {
         // This is a simplified SQL formatter
@@ -1249,4 +2522,9 @@ impl DataPersistenceService {
             Ok(())
         })
     }
-}
\ No newline at end of file
+}
+
+/// Quote an SQLite identifier
+pub(crate) fn quote_identifier(name: &str) -> String {
+    format!("\"{}\"", name.replace('"', "\"\""))
+}
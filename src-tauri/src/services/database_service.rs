@@ -20,7 +20,7 @@ impl DatabaseService {
         logger: Arc<Mutex<Logger>>,
         crypto_service: Arc<CryptoService>,
     ) -> Result<Self> {
-        Self::new_with_config(app_data_dir, DatabaseConfig::default(), logger, crypto_service).await
+        Self::new_with_config(app_data_dir, DatabaseConfig::from_env(), logger, crypto_service).await
     }
     
     /// Create a new database service with custom configuration
@@ -68,6 +68,7 @@ impl DatabaseService {
             let logger_guard = self.logger.lock().await;
             let mut data = std::collections::HashMap::new();
             data.insert("pool_size".to_string(), serde_json::Value::Number(stats.pool_size.into()));
+            data.insert("read_pool_size".to_string(), serde_json::Value::Number(stats.read_pool_size.into()));
             data.insert("database_size_bytes".to_string(), serde_json::Value::Number(stats.database_size_bytes.into()));
             data.insert("table_count".to_string(), serde_json::Value::Number(stats.table_count.into()));
             
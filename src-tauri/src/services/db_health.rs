@@ -0,0 +1,190 @@
+use crate::error::{HedgeXError, Result};
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite};
+use std::time::Instant;
+use tracing::{info, warn};
+
+/// Problems from `integrity_check` kept in a result
+const INTEGRITY_MESSAGE_LIMIT: i64 = 10;
+
+/// How long maintenance results are kept
+const DB_HEALTH_RETENTION_DAYS: i64 = 90;
+
+/// Outcome of one maintenance run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbHealthCheck {
+    pub checked_at: DateTime<Utc>,
+    pub integrity_ok: bool,
+    pub integrity_message: String,
+    pub size_bytes: i64,
+    pub free_bytes: i64,
+    /// Pages moved from the WAL into the database, -1 outside WAL mode
+    pub wal_pages_checkpointed: i64,
+    pub duration_ms: i64,
+}
+
+/// Database size at a maintenance run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbSizePoint {
+    pub checked_at: DateTime<Utc>,
+    pub size_bytes: i64,
+}
+
+/// Latest maintenance result and how the database size has moved
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DbHealthSummary {
+    /// Unset until the first maintenance run
+    pub latest: Option<DbHealthCheck>,
+    pub size_trend: Vec<DbSizePoint>,
+    /// Growth between the first and last point of the trend
+    pub growth_bytes_per_day: Option<f64>,
+}
+
+impl DbHealthSummary {
+    /// Healthy unless the latest integrity check found problems
+    pub fn healthy(&self) -> bool {
+        self.latest.as_ref().is_none_or(|check| check.integrity_ok)
+    }
+}
+
+/// Check integrity, checkpoint the WAL and refresh planner statistics, recording the result in `db_health`
+pub async fn run_maintenance(pool: &Pool<Sqlite>) -> Result<DbHealthCheck> {
+    let started = Instant::now();
+
+    let problems: Vec<String> = sqlx::query_scalar(&format!("PRAGMA integrity_check({})", INTEGRITY_MESSAGE_LIMIT))
+        .fetch_all(pool)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+    let integrity_ok = problems.len() == 1 && problems[0] == "ok";
+    if !integrity_ok {
+        warn!("Database integrity check failed: {}", problems.join("; "));
+    }
+
+    // Columns are busy, WAL frames and frames checkpointed; both counts are -1 outside WAL mode
+    let checkpoint = sqlx::query("PRAGMA wal_checkpoint(TRUNCATE)")
+        .fetch_one(pool)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+    let wal_pages_checkpointed: i64 = checkpoint.get(2);
+
+    sqlx::query("ANALYZE").execute(pool).await.map_err(HedgeXError::DatabaseError)?;
+
+    let sizes = sqlx::query(
+        "SELECT page_count * page_size AS size_bytes, freelist_count * page_size AS free_bytes
+         FROM pragma_page_count(), pragma_freelist_count(), pragma_page_size()"
+    )
+        .fetch_one(pool)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+    let check = DbHealthCheck {
+        checked_at: Utc::now(),
+        integrity_ok,
+        integrity_message: problems.join("; "),
+        size_bytes: sizes.get("size_bytes"),
+        free_bytes: sizes.get("free_bytes"),
+        wal_pages_checkpointed,
+        duration_ms: started.elapsed().as_millis() as i64,
+    };
+
+    sqlx::query(
+        "INSERT INTO db_health (checked_at, integrity_ok, integrity_message, size_bytes, free_bytes, wal_pages_checkpointed, duration_ms)
+         VALUES (?, ?, ?, ?, ?, ?, ?)"
+    )
+        .bind(check.checked_at)
+        .bind(check.integrity_ok)
+        .bind(&check.integrity_message)
+        .bind(check.size_bytes)
+        .bind(check.free_bytes)
+        .bind(check.wal_pages_checkpointed)
+        .bind(check.duration_ms)
+        .execute(pool)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+    sqlx::query("DELETE FROM db_health WHERE checked_at < ?")
+        .bind(check.checked_at - Duration::days(DB_HEALTH_RETENTION_DAYS))
+        .execute(pool)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+    info!("Database maintenance finished in {} ms", check.duration_ms);
+    Ok(check)
+}
+
+/// Latest maintenance result and the size trend over the last `days`
+pub async fn health_summary(pool: &Pool<Sqlite>, days: i64) -> Result<DbHealthSummary> {
+    let rows = sqlx::query(
+        "SELECT checked_at, integrity_ok, integrity_message, size_bytes, free_bytes, wal_pages_checkpointed, duration_ms
+         FROM db_health WHERE checked_at >= ? ORDER BY checked_at"
+    )
+        .bind(Utc::now() - Duration::days(days))
+        .fetch_all(pool)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+    let checks: Vec<DbHealthCheck> = rows.iter()
+        .map(|row| DbHealthCheck {
+            checked_at: row.get("checked_at"),
+            integrity_ok: row.get("integrity_ok"),
+            integrity_message: row.get("integrity_message"),
+            size_bytes: row.get("size_bytes"),
+            free_bytes: row.get("free_bytes"),
+            wal_pages_checkpointed: row.get("wal_pages_checkpointed"),
+            duration_ms: row.get("duration_ms"),
+        })
+        .collect();
+
+    let size_trend: Vec<DbSizePoint> = checks.iter()
+        .map(|check| DbSizePoint { checked_at: check.checked_at, size_bytes: check.size_bytes })
+        .collect();
+
+    Ok(DbHealthSummary {
+        growth_bytes_per_day: growth_per_day(&size_trend),
+        size_trend,
+        latest: checks.into_iter().last(),
+    })
+}
+
+/// Size change per day between the first and last points, unset over less than an hour
+fn growth_per_day(trend: &[DbSizePoint]) -> Option<f64> {
+    let (first, last) = (trend.first()?, trend.last()?);
+    let elapsed = last.checked_at - first.checked_at;
+    if elapsed < Duration::hours(1) {
+        return None;
+    }
+    Some((last.size_bytes - first.size_bytes) as f64 / (elapsed.num_seconds() as f64 / 86_400.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Executor;
+
+    #[test]
+    fn test_growth_per_day() {
+        let start = Utc::now();
+        let point = |hours: i64, size_bytes: i64| DbSizePoint { checked_at: start + Duration::hours(hours), size_bytes };
+
+        assert_eq!(growth_per_day(&[]), None);
+        assert_eq!(growth_per_day(&[point(0, 1000)]), None);
+        assert_eq!(growth_per_day(&[point(0, 1000), point(12, 1500), point(48, 3000)]), Some(1000.0));
+    }
+
+    #[tokio::test]
+    async fn test_run_maintenance_records_result() {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        pool.execute(include_str!("../../migrations/20250828_add_db_health.sql")).await.unwrap();
+
+        let check = run_maintenance(&pool).await.unwrap();
+        assert!(check.integrity_ok);
+        assert_eq!(check.integrity_message, "ok");
+        assert!(check.size_bytes > 0);
+
+        let summary = health_summary(&pool, 30).await.unwrap();
+        assert!(summary.healthy());
+        assert_eq!(summary.size_trend.len(), 1);
+        assert_eq!(summary.latest.unwrap().size_bytes, check.size_bytes);
+    }
+}
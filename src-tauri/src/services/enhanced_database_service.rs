@@ -22,7 +22,7 @@ impl EnhancedDatabaseService {
         app_data_dir: &Path,
         master_password: &str,
     ) -> Result<Self> {
-        Self::new_with_config(app_data_dir, DatabaseConfig::default(), master_password).await
+        Self::new_with_config(app_data_dir, DatabaseConfig::from_env(), master_password).await
     }
     
     /// Create a new enhanced database service with custom configuration
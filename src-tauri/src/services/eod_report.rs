@@ -0,0 +1,329 @@
+use crate::error::Result;
+use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use crate::services::notifications::NotificationService;
+use crate::services::pnl_statement::{PnlStatement, PnlStatementService, PnlSubtotal, StatementTrade, StrategyPnl};
+use crate::trading::session::{is_trading_day, session_bounds, session_date};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Asia::Kolkata;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{error, info};
+
+/// How often the scheduler checks whether the day's report is due
+const CHECK_INTERVAL: Duration = Duration::from_secs(300);
+
+/// Minutes after the regular close before the report is generated, so the closing session is included
+const REPORT_DELAY_MINUTES: i64 = 30;
+
+/// A risk manager action logged during the day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RiskEvent {
+    pub at: DateTime<Utc>,
+    pub message: String,
+}
+
+/// Summary of one trading day
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EodReport {
+    pub user_id: String,
+    /// IST trading date
+    pub date: NaiveDate,
+    pub totals: PnlSubtotal,
+    /// Trades that closed quantity at a profit or a loss
+    pub winning_trades: usize,
+    pub losing_trades: usize,
+    /// Closing trades with the highest and lowest net P&L
+    pub best_trade: Option<StatementTrade>,
+    pub worst_trade: Option<StatementTrade>,
+    pub strategies: Vec<StrategyPnl>,
+    pub risk_events: Vec<RiskEvent>,
+    /// Notification channels the report reached
+    #[serde(default)]
+    pub delivered_to: Vec<String>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl EodReport {
+    /// Summarize a statement covering the single day `date`
+    pub fn from_statement(date: NaiveDate, statement: &PnlStatement, risk_events: Vec<RiskEvent>) -> Self {
+        // Only trades that closed some quantity have an outcome
+        let closing: Vec<&StatementTrade> = statement.trades.iter().filter(|trade| trade.realized_pnl != 0.0).collect();
+        let by_net_pnl = |a: &&&StatementTrade, b: &&&StatementTrade| a.net_pnl.total_cmp(&b.net_pnl);
+
+        Self {
+            user_id: statement.user_id.clone(),
+            date,
+            totals: statement.totals.clone(),
+            winning_trades: closing.iter().filter(|trade| trade.realized_pnl > 0.0).count(),
+            losing_trades: closing.iter().filter(|trade| trade.realized_pnl < 0.0).count(),
+            best_trade: closing.iter().max_by(by_net_pnl).map(|trade| (*trade).clone()),
+            worst_trade: closing.iter().min_by(by_net_pnl).map(|trade| (*trade).clone()),
+            strategies: statement.strategies.clone(),
+            risk_events,
+            delivered_to: Vec::new(),
+            generated_at: Utc::now(),
+        }
+    }
+
+    /// Subject line for notifications
+    pub fn subject(&self) -> String {
+        format!("HedgeX EOD {}: net P&L {:.2}", self.date, self.totals.net_pnl)
+    }
+
+    /// The report as plain text for notifications
+    pub fn to_text(&self) -> String {
+        let mut lines = vec![
+            format!("Trades: {} ({} won, {} lost)", self.totals.trades, self.winning_trades, self.losing_trades),
+            format!("Turnover: {:.2}", self.totals.turnover),
+            format!("Realized P&L: {:.2}", self.totals.realized_pnl),
+            format!("Charges: {:.2}", self.totals.charges),
+            format!("Net P&L: {:.2}", self.totals.net_pnl),
+        ];
+
+        let highlight = |label: &str, trade: &StatementTrade| {
+            format!(
+                "{}: {} {} x{} at {:.2} ({}), net {:.2}",
+                label,
+                trade.executed_at.with_timezone(&Kolkata).format("%H:%M"),
+                trade.symbol,
+                trade.quantity,
+                trade.price,
+                trade.strategy_name,
+                trade.net_pnl
+            )
+        };
+        if let Some(trade) = &self.best_trade {
+            lines.push(highlight("Best", trade));
+        }
+        if let Some(trade) = &self.worst_trade {
+            lines.push(highlight("Worst", trade));
+        }
+
+        if !self.strategies.is_empty() {
+            lines.push(String::new());
+            lines.push("Strategies:".to_string());
+            lines.extend(self.strategies.iter().map(|strategy| {
+                format!("  {}: {} trades, net {:.2}", strategy.strategy_name, strategy.totals.trades, strategy.totals.net_pnl)
+            }));
+        }
+
+        lines.push(String::new());
+        if self.risk_events.is_empty() {
+            lines.push("Risk events: none".to_string());
+        } else {
+            lines.push("Risk events:".to_string());
+            lines.extend(self.risk_events.iter().map(|event| {
+                format!("  {} {}", event.at.with_timezone(&Kolkata).format("%H:%M"), event.message)
+            }));
+        }
+
+        lines.join("\n")
+    }
+}
+
+/// Whether the report for the day of `now` should be generated, given whether one already exists
+pub fn is_report_due(now: DateTime<Utc>, already_generated: bool) -> bool {
+    let today = session_date(now);
+    if already_generated || !is_trading_day(today) {
+        return false;
+    }
+    session_bounds(today)
+        .is_some_and(|(_, close)| now >= close + chrono::Duration::minutes(REPORT_DELAY_MINUTES))
+}
+
+/// Generates, stores and delivers end-of-day reports
+pub struct EodReportService {
+    db_service: Arc<EnhancedDatabaseService>,
+    pnl_statement: Arc<PnlStatementService>,
+    notifications: Arc<NotificationService>,
+}
+
+impl EodReportService {
+    /// Create a new end-of-day report service
+    pub fn new(
+        db_service: Arc<EnhancedDatabaseService>,
+        pnl_statement: Arc<PnlStatementService>,
+        notifications: Arc<NotificationService>,
+    ) -> Self {
+        Self { db_service, pnl_statement, notifications }
+    }
+
+    /// Build the report of an IST trading date and store it, replacing any earlier one
+    pub async fn generate(&self, user_id: &str, date: NaiveDate) -> Result<EodReport> {
+        let statement = self.pnl_statement.generate(user_id, date, date).await?;
+        let risk_events = self.load_risk_events(user_id, date).await?;
+        let report = EodReport::from_statement(date, &statement, risk_events);
+
+        sqlx::query(
+            "INSERT OR REPLACE INTO eod_reports (user_id, report_date, report, delivered_to, created_at)
+             VALUES (?, ?, ?, NULL, ?)"
+        )
+            .bind(user_id)
+            .bind(date.format("%Y-%m-%d").to_string())
+            .bind(serde_json::to_string(&report)?)
+            .bind(report.generated_at)
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+
+        Ok(report)
+    }
+
+    /// Send a report on the user's configured notification channels and record where it went
+    pub async fn deliver(&self, report: &mut EodReport) -> Result<()> {
+        report.delivered_to = self.notifications.notify(&report.user_id, &report.subject(), &report.to_text()).await?;
+
+        sqlx::query("UPDATE eod_reports SET delivered_to = ? WHERE user_id = ? AND report_date = ?")
+            .bind(report.delivered_to.join(","))
+            .bind(&report.user_id)
+            .bind(report.date.format("%Y-%m-%d").to_string())
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+
+        Ok(())
+    }
+
+    /// The stored report of a date, if one was generated
+    pub async fn get(&self, user_id: &str, date: NaiveDate) -> Result<Option<EodReport>> {
+        let row = sqlx::query("SELECT report, delivered_to FROM eod_reports WHERE user_id = ? AND report_date = ?")
+            .bind(user_id)
+            .bind(date.format("%Y-%m-%d").to_string())
+            .fetch_optional(self.db_service.get_database().get_pool())
+            .await?;
+
+        row.map(|row| report_from_row(&row)).transpose()
+    }
+
+    /// The most recent stored reports, newest first
+    pub async fn list(&self, user_id: &str, limit: i64) -> Result<Vec<EodReport>> {
+        let rows = sqlx::query(
+            "SELECT report, delivered_to FROM eod_reports WHERE user_id = ? ORDER BY report_date DESC LIMIT ?"
+        )
+            .bind(user_id)
+            .bind(limit)
+            .fetch_all(self.db_service.get_database().get_pool())
+            .await?;
+
+        rows.iter().map(report_from_row).collect()
+    }
+
+    /// Risk manager log entries of an IST date, oldest first
+    async fn load_risk_events(&self, user_id: &str, date: NaiveDate) -> Result<Vec<RiskEvent>> {
+        let rows = sqlx::query(
+            "SELECT created_at, message FROM system_logs
+             WHERE user_id = ? AND context = 'risk_manager' AND date(created_at, '+330 minutes') = ?
+             ORDER BY created_at ASC"
+        )
+            .bind(user_id)
+            .bind(date.format("%Y-%m-%d").to_string())
+            .fetch_all(self.db_service.get_database().get_pool())
+            .await?;
+
+        Ok(rows.iter()
+            .map(|row| RiskEvent { at: row.get("created_at"), message: row.get("message") })
+            .collect())
+    }
+
+    /// Generate and deliver each trading day's report after the market closes, in the background
+    pub fn start_daily_schedule(self: Arc<Self>, user_id: String) {
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(CHECK_INTERVAL);
+
+            loop {
+                interval.tick().await;
+
+                let now = Utc::now();
+                let today = session_date(now);
+                let already_generated = match self.get(&user_id, today).await {
+                    Ok(report) => report.is_some(),
+                    Err(e) => {
+                        error!("Failed to check for today's EOD report: {}", e);
+                        continue;
+                    }
+                };
+                if !is_report_due(now, already_generated) {
+                    continue;
+                }
+
+                match self.generate(&user_id, today).await {
+                    Ok(mut report) => {
+                        if let Err(e) = self.deliver(&mut report).await {
+                            error!("Failed to deliver EOD report for {}: {}", today, e);
+                        }
+                        info!("Generated EOD report for {}, delivered to {:?}", today, report.delivered_to);
+                    }
+                    Err(e) => error!("Failed to generate EOD report for {}: {}", today, e),
+                }
+            }
+        });
+    }
+}
+
+fn report_from_row(row: &sqlx::sqlite::SqliteRow) -> Result<EodReport> {
+    let mut report: EodReport = serde_json::from_str(&row.get::<String, _>("report"))?;
+    report.delivered_to = row.get::<Option<String>, _>("delivered_to")
+        .map(|channels| channels.split(',').filter(|c| !c.is_empty()).map(str::to_string).collect())
+        .unwrap_or_default();
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::backtesting::CostModel;
+    use crate::models::trading::TradeType;
+    use crate::services::pnl_statement::{build_statement, StatementInput};
+    use chrono::TimeZone;
+
+    fn input(symbol: &str, trade_type: TradeType, price: f64, hour: u32) -> StatementInput {
+        StatementInput {
+            trade_id: format!("{}-{}", symbol, hour),
+            executed_at: Utc.with_ymd_and_hms(2024, 8, 5, hour, 0, 0).unwrap(),
+            symbol: symbol.to_string(),
+            exchange: "NSE".to_string(),
+            strategy_id: "s1".to_string(),
+            strategy_name: "Momentum".to_string(),
+            trade_type,
+            quantity: 10,
+            price,
+        }
+    }
+
+    #[test]
+    fn test_report_from_statement() {
+        let date = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+        let trades = vec![
+            input("INFY", TradeType::Buy, 100.0, 4),
+            input("INFY", TradeType::Sell, 110.0, 5),
+            input("TCS", TradeType::Buy, 200.0, 6),
+            input("TCS", TradeType::Sell, 195.0, 7),
+        ];
+        let statement = build_statement(&trades, date, date, &CostModel::disabled());
+        let event = RiskEvent { at: Utc.with_ymd_and_hms(2024, 8, 5, 8, 0, 0).unwrap(), message: "EMERGENCY STOP ACTIVATED".to_string() };
+
+        let report = EodReport::from_statement(date, &statement, vec![event]);
+        assert_eq!(report.totals.trades, 4);
+        assert_eq!((report.winning_trades, report.losing_trades), (1, 1));
+        assert_eq!(report.best_trade.as_ref().unwrap().symbol, "INFY");
+        assert_eq!(report.worst_trade.as_ref().unwrap().net_pnl, -50.0);
+
+        let text = report.to_text();
+        assert!(text.contains("Net P&L: 50.00"));
+        assert!(text.contains("Worst: 12:30 TCS x10 at 195.00 (Momentum), net -50.00"));
+        assert!(text.contains("  13:30 EMERGENCY STOP ACTIVATED"));
+    }
+
+    #[test]
+    fn test_report_due_after_close_on_trading_days() {
+        // Monday 5 August 2024; 10:30 UTC is 16:00 IST
+        let before = Utc.with_ymd_and_hms(2024, 8, 5, 10, 29, 0).unwrap();
+        let after = Utc.with_ymd_and_hms(2024, 8, 5, 10, 30, 0).unwrap();
+        assert!(!is_report_due(before, false));
+        assert!(is_report_due(after, false));
+        assert!(!is_report_due(after, true));
+
+        let saturday = Utc.with_ymd_and_hms(2024, 8, 10, 12, 0, 0).unwrap();
+        assert!(!is_report_due(saturday, false));
+    }
+}
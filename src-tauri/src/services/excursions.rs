@@ -0,0 +1,211 @@
+use crate::error::Result;
+use crate::models::trading::TradeType;
+use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use crate::services::monte_carlo::percentile;
+use crate::trading::analytics_filter::TradeFilter;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Share of winners a suggested stop should leave untouched
+const STOP_WINNER_COVERAGE: f64 = 0.9;
+
+/// A live trade that exited a position, with how far price moved against and for the position while open
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TradeExcursion {
+    pub trade_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub strategy_id: String,
+    /// Side of the exit; a sell exits a long position
+    pub trade_type: TradeType,
+    pub executed_at: DateTime<Utc>,
+    /// Average price of the position exited
+    pub entry_price: f64,
+    pub exit_price: f64,
+    /// Deepest loss while open, as a positive percentage of the entry price
+    pub mae_percentage: f64,
+    /// Highest profit while open, as a percentage of the entry price
+    pub mfe_percentage: f64,
+}
+
+impl TradeExcursion {
+    /// Return of the exit on the entry price, in percent
+    pub fn return_percentage(&self) -> f64 {
+        if self.entry_price <= 0.0 {
+            return 0.0;
+        }
+        let move_per_share = match self.trade_type {
+            TradeType::Sell => self.exit_price - self.entry_price,
+            TradeType::Buy => self.entry_price - self.exit_price,
+        };
+        move_per_share / self.entry_price * 100.0
+    }
+}
+
+/// Excursions of the exits in one strategy, or all of them
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExcursionSummary {
+    pub bucket: String,
+    pub trades: usize,
+    pub winners: usize,
+    pub average_mae_winners: f64,
+    pub average_mae_losers: f64,
+    pub average_mfe_winners: f64,
+    pub average_mfe_losers: f64,
+    /// Stop, in percent, beyond the adverse excursion of 90% of winners
+    pub suggested_stop_percentage: f64,
+    /// Target, in percent, that half of all trades reached while open
+    pub suggested_target_percentage: f64,
+}
+
+/// Maximum adverse and favorable excursions of live exits, to calibrate stops and targets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExcursionReport {
+    #[serde(flatten)]
+    pub filter: TradeFilter,
+    pub overall: ExcursionSummary,
+    pub by_strategy: Vec<ExcursionSummary>,
+    pub trades: Vec<TradeExcursion>,
+}
+
+/// Reads the excursions live trades recorded when they exited a position
+pub struct ExcursionService {
+    db_service: Arc<EnhancedDatabaseService>,
+}
+
+impl ExcursionService {
+    /// Create a new excursion service
+    pub fn new(db_service: Arc<EnhancedDatabaseService>) -> Self {
+        Self { db_service }
+    }
+
+    /// Excursions of the exits passing a filter
+    pub async fn report(&self, user_id: &str, filter: &TradeFilter) -> Result<ExcursionReport> {
+        let trades = self.load_excursions(user_id, filter).await?;
+        Ok(ExcursionReport {
+            filter: filter.clone(),
+            overall: summarize("all".to_string(), &trades.iter().collect::<Vec<_>>()),
+            by_strategy: by_strategy(&trades),
+            trades,
+        })
+    }
+
+    /// Executed exits with recorded excursions, oldest first
+    pub async fn load_excursions(&self, user_id: &str, filter: &TradeFilter) -> Result<Vec<TradeExcursion>> {
+        let (conditions, binds) = filter.sql_conditions("");
+        let sql = format!(
+            "SELECT id, symbol, exchange, strategy_id, trade_type, executed_at, entry_price, price,
+                    mae_percentage, mfe_percentage
+             FROM trades
+             WHERE user_id = ? AND status = 'Executed' AND mae_percentage IS NOT NULL{}
+             ORDER BY executed_at ASC",
+            conditions
+        );
+        let mut query = sqlx::query(&sql).bind(user_id);
+        for value in &binds {
+            query = query.bind(value);
+        }
+        let rows = query.fetch_all(self.db_service.get_database().get_read_pool()).await?;
+
+        Ok(rows.iter()
+            .map(|row| TradeExcursion {
+                trade_id: row.get("id"),
+                symbol: row.get("symbol"),
+                exchange: row.get("exchange"),
+                strategy_id: row.get("strategy_id"),
+                trade_type: if row.get::<String, _>("trade_type") == "Sell" { TradeType::Sell } else { TradeType::Buy },
+                executed_at: row.get("executed_at"),
+                entry_price: row.get::<Option<f64>, _>("entry_price").unwrap_or(0.0),
+                exit_price: row.get("price"),
+                mae_percentage: row.get("mae_percentage"),
+                mfe_percentage: row.get::<Option<f64>, _>("mfe_percentage").unwrap_or(0.0),
+            })
+            .collect())
+    }
+}
+
+/// Exits grouped by strategy, in strategy ID order
+pub fn by_strategy(trades: &[TradeExcursion]) -> Vec<ExcursionSummary> {
+    let mut groups: BTreeMap<&str, Vec<&TradeExcursion>> = BTreeMap::new();
+    for trade in trades {
+        groups.entry(trade.strategy_id.as_str()).or_default().push(trade);
+    }
+    groups.into_iter().map(|(strategy_id, trades)| summarize(strategy_id.to_string(), &trades)).collect()
+}
+
+/// Average excursions of winning and losing exits, with the stop and target they suggest
+pub fn summarize(label: String, trades: &[&TradeExcursion]) -> ExcursionSummary {
+    let (winners, losers): (Vec<&TradeExcursion>, Vec<&TradeExcursion>) =
+        trades.iter().partition(|trade| trade.return_percentage() > 0.0);
+    let average = |trades: &[&TradeExcursion], value: fn(&TradeExcursion) -> f64| {
+        if trades.is_empty() {
+            0.0
+        } else {
+            trades.iter().map(|trade| value(trade)).sum::<f64>() / trades.len() as f64
+        }
+    };
+    let sorted = |trades: &[&TradeExcursion], value: fn(&TradeExcursion) -> f64| {
+        let mut values: Vec<f64> = trades.iter().map(|trade| value(trade)).collect();
+        values.sort_by(|a, b| a.total_cmp(b));
+        values
+    };
+
+    ExcursionSummary {
+        bucket: label,
+        trades: trades.len(),
+        winners: winners.len(),
+        average_mae_winners: average(&winners, |trade| trade.mae_percentage),
+        average_mae_losers: average(&losers, |trade| trade.mae_percentage),
+        average_mfe_winners: average(&winners, |trade| trade.mfe_percentage),
+        average_mfe_losers: average(&losers, |trade| trade.mfe_percentage),
+        suggested_stop_percentage: percentile(&sorted(&winners, |trade| trade.mae_percentage), STOP_WINNER_COVERAGE),
+        suggested_target_percentage: percentile(&sorted(trades, |trade| trade.mfe_percentage), 0.5),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn exit(strategy: &str, trade_type: TradeType, exit_price: f64, mae: f64, mfe: f64) -> TradeExcursion {
+        TradeExcursion {
+            trade_id: format!("{}-{}", strategy, exit_price),
+            symbol: "INFY".to_string(),
+            exchange: "NSE".to_string(),
+            strategy_id: strategy.to_string(),
+            trade_type,
+            executed_at: Utc.with_ymd_and_hms(2024, 8, 5, 6, 0, 0).unwrap(),
+            entry_price: 1000.0,
+            exit_price,
+            mae_percentage: mae,
+            mfe_percentage: mfe,
+        }
+    }
+
+    #[test]
+    fn test_excursion_summaries() {
+        let trades = vec![
+            // Long exits
+            exit("a", TradeType::Sell, 1020.0, 0.5, 2.5),
+            exit("a", TradeType::Sell, 990.0, 1.5, 0.5),
+            // A short closed below its entry is a winner
+            exit("b", TradeType::Buy, 980.0, 1.0, 3.0),
+        ];
+        assert_eq!(trades[2].return_percentage(), 2.0);
+
+        let overall = summarize("all".to_string(), &trades.iter().collect::<Vec<_>>());
+        assert_eq!((overall.trades, overall.winners), (3, 2));
+        assert_eq!((overall.average_mae_winners, overall.average_mae_losers), (0.75, 1.5));
+        assert_eq!((overall.average_mfe_winners, overall.average_mfe_losers), (2.75, 0.5));
+        assert!((overall.suggested_stop_percentage - 0.95).abs() < 1e-9);
+        assert_eq!(overall.suggested_target_percentage, 2.5);
+
+        let strategies = by_strategy(&trades);
+        assert_eq!(strategies.iter().map(|s| (s.bucket.as_str(), s.trades)).collect::<Vec<_>>(), vec![("a", 2), ("b", 1)]);
+        assert_eq!(strategies[1].average_mae_losers, 0.0);
+    }
+}
@@ -0,0 +1,225 @@
+use crate::services::tick_channel::TickChannelStats;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::Mutex;
+
+/// Seconds of ticks averaged into the tick rate
+const RATE_WINDOW_SECONDS: i64 = 10;
+
+/// Seconds without a tick during market hours before the feed counts as stalled
+pub const DEFAULT_STALL_SECONDS: i64 = 30;
+
+/// Time since the last tick of one symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolTickAge {
+    pub symbol: String,
+    pub last_tick_at: DateTime<Utc>,
+    pub age_seconds: f64,
+}
+
+/// Point-in-time view of the market data feed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedHealth {
+    /// Ticks a second over the last ten seconds
+    pub ticks_per_second: f64,
+    pub total_ticks: u64,
+    pub parse_errors: u64,
+    /// Successful reconnections since start
+    pub reconnects: u64,
+    pub last_tick_at: Option<DateTime<Utc>>,
+    /// Symbols ordered by stalest first
+    pub symbols: Vec<SymbolTickAge>,
+    /// Whether a stall alert is open
+    pub stalled: bool,
+    /// Buffers of market data subscribers, with their dropped and coalesced ticks
+    #[serde(default)]
+    pub channels: Vec<TickChannelStats>,
+}
+
+/// Raised once when the feed stops ticking during market hours
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeedAlert {
+    pub message: String,
+    pub last_tick_at: Option<DateTime<Utc>>,
+    pub timestamp: DateTime<Utc>,
+}
+
+/// Counters and tick times kept by the WebSocket manager
+pub struct FeedMetrics {
+    stall_seconds: i64,
+    total_ticks: AtomicU64,
+    parse_errors: AtomicU64,
+    reconnects: AtomicU64,
+    state: Mutex<FeedState>,
+}
+
+#[derive(Default)]
+struct FeedState {
+    /// Tick counts by whole second, oldest first
+    buckets: VecDeque<(i64, u64)>,
+    last_tick: HashMap<String, DateTime<Utc>>,
+    /// When monitoring started or the feed last recovered; a stall is measured from here when nothing has ticked
+    watch_from: Option<DateTime<Utc>>,
+    stalled: bool,
+}
+
+impl Default for FeedMetrics {
+    fn default() -> Self {
+        Self::new(DEFAULT_STALL_SECONDS)
+    }
+}
+
+impl FeedMetrics {
+    /// Metrics that report a stall after `stall_seconds` without ticks
+    pub fn new(stall_seconds: i64) -> Self {
+        Self {
+            stall_seconds,
+            total_ticks: AtomicU64::new(0),
+            parse_errors: AtomicU64::new(0),
+            reconnects: AtomicU64::new(0),
+            state: Mutex::new(FeedState::default()),
+        }
+    }
+
+    /// Count a tick for a symbol; clears an open stall
+    pub async fn record_tick(&self, symbol: &str, at: DateTime<Utc>) {
+        self.total_ticks.fetch_add(1, Ordering::Relaxed);
+
+        let mut state = self.state.lock().await;
+        let second = at.timestamp();
+        match state.buckets.back_mut() {
+            Some((bucket, count)) if *bucket == second => *count += 1,
+            _ => state.buckets.push_back((second, 1)),
+        }
+        while state.buckets.front().map_or(false, |(bucket, _)| *bucket <= second - RATE_WINDOW_SECONDS) {
+            state.buckets.pop_front();
+        }
+
+        state.last_tick.insert(symbol.to_string(), at);
+        state.stalled = false;
+    }
+
+    /// Count a message that could not be parsed
+    pub fn record_parse_error(&self) {
+        self.parse_errors.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Count a successful reconnection
+    pub fn record_reconnect(&self) {
+        self.reconnects.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Current rates, counters and per-symbol tick ages
+    pub async fn snapshot(&self, now: DateTime<Utc>) -> FeedHealth {
+        let state = self.state.lock().await;
+
+        let window_start = now.timestamp() - RATE_WINDOW_SECONDS;
+        let recent: u64 = state.buckets.iter()
+            .filter(|(bucket, _)| *bucket > window_start)
+            .map(|(_, count)| count)
+            .sum();
+
+        let mut symbols: Vec<SymbolTickAge> = state.last_tick.iter()
+            .map(|(symbol, at)| SymbolTickAge {
+                symbol: symbol.clone(),
+                last_tick_at: *at,
+                age_seconds: (now - *at).num_milliseconds().max(0) as f64 / 1000.0,
+            })
+            .collect();
+        symbols.sort_by(|a, b| b.age_seconds.total_cmp(&a.age_seconds).then_with(|| a.symbol.cmp(&b.symbol)));
+
+        FeedHealth {
+            ticks_per_second: recent as f64 / RATE_WINDOW_SECONDS as f64,
+            total_ticks: self.total_ticks.load(Ordering::Relaxed),
+            parse_errors: self.parse_errors.load(Ordering::Relaxed),
+            reconnects: self.reconnects.load(Ordering::Relaxed),
+            last_tick_at: state.last_tick.values().max().cloned(),
+            symbols,
+            stalled: state.stalled,
+            channels: Vec::new(),
+        }
+    }
+
+    /// Check for a stall, returning an alert the first time one is seen
+    ///
+    /// Outside market hours nothing is expected, so the stall clock restarts.
+    pub async fn check_stall(&self, now: DateTime<Utc>, expecting_ticks: bool) -> Option<FeedAlert> {
+        let mut state = self.state.lock().await;
+
+        if !expecting_ticks {
+            state.watch_from = None;
+            state.stalled = false;
+            return None;
+        }
+
+        let watch_from = *state.watch_from.get_or_insert(now);
+        let last_tick = state.last_tick.values().max().cloned();
+        let quiet_since = last_tick.map_or(watch_from, |at| at.max(watch_from));
+
+        if state.stalled || (now - quiet_since).num_seconds() < self.stall_seconds {
+            return None;
+        }
+
+        state.stalled = true;
+        Some(FeedAlert {
+            message: format!("No ticks received for {} seconds during market hours", (now - quiet_since).num_seconds()),
+            last_tick_at: last_tick,
+            timestamp: now,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[tokio::test]
+    async fn test_tick_rate_and_ages() {
+        let metrics = FeedMetrics::default();
+        let start = Utc::now();
+
+        for i in 0..20 {
+            metrics.record_tick("INFY", start + Duration::milliseconds(i * 250)).await;
+        }
+        metrics.record_tick("TCS", start).await;
+        metrics.record_parse_error();
+        metrics.record_reconnect();
+
+        let health = metrics.snapshot(start + Duration::seconds(5)).await;
+        assert_eq!(health.total_ticks, 21);
+        assert!((health.ticks_per_second - 2.1).abs() < 1e-9);
+        assert_eq!(health.parse_errors, 1);
+        assert_eq!(health.reconnects, 1);
+        assert_eq!(health.symbols[0].symbol, "TCS");
+        assert!((health.symbols[0].age_seconds - 5.0).abs() < 1e-9);
+
+        // Ticks older than the window no longer count toward the rate
+        let later = metrics.snapshot(start + Duration::seconds(30)).await;
+        assert_eq!(later.ticks_per_second, 0.0);
+    }
+
+    #[tokio::test]
+    async fn test_stall_alert_fires_once_during_market_hours() {
+        let metrics = FeedMetrics::new(30);
+        let start = Utc::now();
+
+        // Outside market hours silence is expected
+        assert!(metrics.check_stall(start + Duration::seconds(60), false).await.is_none());
+
+        metrics.record_tick("INFY", start).await;
+        assert!(metrics.check_stall(start + Duration::seconds(10), true).await.is_none());
+
+        let alert = metrics.check_stall(start + Duration::seconds(45), true).await.unwrap();
+        assert_eq!(alert.last_tick_at, Some(start));
+        assert!(metrics.snapshot(start + Duration::seconds(45)).await.stalled);
+        assert!(metrics.check_stall(start + Duration::seconds(60), true).await.is_none());
+
+        // A tick clears the stall so the next one alerts again
+        metrics.record_tick("INFY", start + Duration::seconds(61)).await;
+        assert!(!metrics.snapshot(start + Duration::seconds(61)).await.stalled);
+        assert!(metrics.check_stall(start + Duration::seconds(100), true).await.is_some());
+    }
+}
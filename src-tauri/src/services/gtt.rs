@@ -0,0 +1,354 @@
+use crate::api::kite_client::KiteApiClient;
+use crate::error::{HedgeXError, Result};
+use crate::models::kite::{KiteGtt, KiteGttRequest, KiteGttStatus, KiteGttType};
+use crate::services::broker_sync::spawn_periodic_sync;
+use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use crate::services::portfolio_sync::BrokerPortfolio;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::{debug, info};
+
+/// How often GTT triggers are pulled from the broker
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A GTT trigger from the broker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GttTrigger {
+    #[serde(flatten)]
+    pub gtt: KiteGtt,
+    /// Whether HedgeX placed the trigger; ones set up in Kite directly are not
+    pub placed_by_hedgex: bool,
+}
+
+/// Creates, modifies and deletes GTT triggers, keeping a local copy of them
+pub struct GttService {
+    db_service: Arc<EnhancedDatabaseService>,
+}
+
+impl GttService {
+    /// Create a new GTT service
+    pub fn new(db_service: Arc<EnhancedDatabaseService>) -> Self {
+        Self { db_service }
+    }
+
+    /// Place a GTT trigger with the broker and keep a reference to it
+    pub async fn create(&self, client: &dyn KiteApiClient, request: KiteGttRequest) -> Result<GttTrigger> {
+        validate_request(&request)?;
+
+        let response = client.place_gtt(request).await?;
+        let gtt = client.get_gtt(response.trigger_id).await?;
+        self.store(&gtt, true).await?;
+
+        info!("GTT trigger {} placed on {}", gtt.id, gtt.condition.tradingsymbol);
+        Ok(GttTrigger { gtt, placed_by_hedgex: true })
+    }
+
+    /// Replace a GTT trigger's condition and orders
+    pub async fn modify(&self, client: &dyn KiteApiClient, trigger_id: u64, request: KiteGttRequest) -> Result<GttTrigger> {
+        validate_request(&request)?;
+
+        client.modify_gtt(trigger_id, request).await?;
+        let gtt = client.get_gtt(trigger_id).await?;
+        self.store(&gtt, false).await?;
+
+        info!("GTT trigger {} modified", trigger_id);
+        self.get(trigger_id).await?
+            .ok_or_else(|| HedgeXError::NotFoundError(format!("GTT trigger {} not found", trigger_id)))
+    }
+
+    /// Delete a GTT trigger with the broker and drop the local copy
+    pub async fn delete(&self, client: &dyn KiteApiClient, trigger_id: u64) -> Result<()> {
+        client.delete_gtt(trigger_id).await?;
+
+        sqlx::query("DELETE FROM gtt_triggers WHERE trigger_id = ?")
+            .bind(trigger_id as i64)
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+
+        info!("GTT trigger {} deleted", trigger_id);
+        Ok(())
+    }
+
+    /// Fetch every GTT trigger from the broker and replace the local copies
+    pub async fn sync(&self, client: &dyn KiteApiClient) -> Result<Vec<GttTrigger>> {
+        let gtts = client.get_gtts().await?;
+        self.store_all(&gtts).await?;
+        self.list().await
+    }
+
+    /// Replace the local copies with the broker's triggers
+    ///
+    /// Triggers the broker no longer lists were deleted outside HedgeX.
+    pub async fn store_all(&self, gtts: &[KiteGtt]) -> Result<()> {
+        let listed: HashSet<i64> = gtts.iter().map(|gtt| gtt.id as i64).collect();
+        let stored: Vec<i64> = sqlx::query("SELECT trigger_id FROM gtt_triggers")
+            .fetch_all(self.db_service.get_database().get_pool())
+            .await?
+            .iter()
+            .map(|row| row.get("trigger_id"))
+            .collect();
+
+        for trigger_id in stored.into_iter().filter(|id| !listed.contains(id)) {
+            sqlx::query("DELETE FROM gtt_triggers WHERE trigger_id = ?")
+                .bind(trigger_id)
+                .execute(self.db_service.get_database().get_pool())
+                .await?;
+        }
+        for gtt in gtts {
+            self.store(gtt, false).await?;
+        }
+
+        debug!("Stored {} GTT triggers", gtts.len());
+        Ok(())
+    }
+
+    /// Insert or update a trigger, keeping whether HedgeX placed it
+    async fn store(&self, gtt: &KiteGtt, placed_by_hedgex: bool) -> Result<()> {
+        let data = serde_json::to_string(gtt)
+            .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+
+        sqlx::query(
+            "INSERT INTO gtt_triggers (trigger_id, exchange, tradingsymbol, status, data, placed_by_hedgex, synced_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)
+             ON CONFLICT(trigger_id) DO UPDATE SET
+                exchange = excluded.exchange,
+                tradingsymbol = excluded.tradingsymbol,
+                status = excluded.status,
+                data = excluded.data,
+                placed_by_hedgex = placed_by_hedgex OR excluded.placed_by_hedgex,
+                synced_at = excluded.synced_at"
+        )
+        .bind(gtt.id as i64)
+        .bind(gtt.condition.exchange.to_string())
+        .bind(&gtt.condition.tradingsymbol)
+        .bind(gtt.status.to_string())
+        .bind(data)
+        .bind(placed_by_hedgex)
+        .bind(Utc::now())
+        .execute(self.db_service.get_database().get_pool())
+        .await?;
+
+        Ok(())
+    }
+
+    /// A stored trigger
+    pub async fn get(&self, trigger_id: u64) -> Result<Option<GttTrigger>> {
+        let row = sqlx::query("SELECT data, placed_by_hedgex FROM gtt_triggers WHERE trigger_id = ?")
+            .bind(trigger_id as i64)
+            .fetch_optional(self.db_service.get_database().get_pool())
+            .await?;
+
+        row.map(|row| to_trigger(&row)).transpose()
+    }
+
+    /// Every stored trigger, newest first
+    pub async fn list(&self) -> Result<Vec<GttTrigger>> {
+        sqlx::query("SELECT data, placed_by_hedgex FROM gtt_triggers ORDER BY trigger_id DESC")
+            .fetch_all(self.db_service.get_database().get_pool())
+            .await?
+            .iter()
+            .map(to_trigger)
+            .collect()
+    }
+
+    /// Attach the active triggers on each position's and holding's symbol
+    pub async fn attach(&self, portfolio: &mut BrokerPortfolio) -> Result<()> {
+        let mut active: HashMap<(String, String), Vec<GttTrigger>> = HashMap::new();
+        for trigger in self.list().await? {
+            if trigger.gtt.status == KiteGttStatus::Active {
+                let key = (trigger.gtt.condition.exchange.to_string(), trigger.gtt.condition.tradingsymbol.clone());
+                active.entry(key).or_default().push(trigger);
+            }
+        }
+
+        for position in portfolio.positions.iter_mut() {
+            let key = (position.exchange.clone(), position.tradingsymbol.clone());
+            position.gtts = active.get(&key).cloned().unwrap_or_default();
+        }
+        for holding in portfolio.holdings.iter_mut() {
+            let key = (holding.exchange.clone(), holding.tradingsymbol.clone());
+            holding.gtts = active.get(&key).cloned().unwrap_or_default();
+        }
+
+        Ok(())
+    }
+
+    /// Sync GTT triggers periodically in the background
+    pub fn start_periodic_sync(self: Arc<Self>, client: Arc<dyn KiteApiClient + Send + Sync>) {
+        spawn_periodic_sync("GTT sync", SYNC_INTERVAL, client, move |client| {
+            let service = Arc::clone(&self);
+            async move { service.sync(client.as_ref()).await.map(|_| ()) }
+        });
+    }
+}
+
+/// Check a trigger has one order per trigger value, all on the watched symbol
+pub fn validate_request(request: &KiteGttRequest) -> Result<()> {
+    let legs = match request.trigger_type {
+        KiteGttType::Single => 1,
+        KiteGttType::TwoLeg => 2,
+    };
+    let condition = &request.condition;
+
+    if condition.trigger_values.len() != legs || request.orders.len() != legs {
+        return Err(HedgeXError::ValidationError(format!(
+            "A {} GTT needs {} trigger value(s) and as many orders", request.trigger_type, legs
+        )));
+    }
+    if condition.trigger_values.iter().any(|value| *value <= 0.0) {
+        return Err(HedgeXError::ValidationError("Trigger values must be positive".to_string()));
+    }
+    // Kite expects the stoploss leg first
+    if legs == 2 && condition.trigger_values[0] >= condition.trigger_values[1] {
+        return Err(HedgeXError::ValidationError(
+            "Two-leg trigger values must be the stoploss then a higher target".to_string()
+        ));
+    }
+    for order in &request.orders {
+        if order.exchange != condition.exchange || order.tradingsymbol != condition.tradingsymbol {
+            return Err(HedgeXError::ValidationError(
+                "GTT orders must be on the symbol the trigger watches".to_string()
+            ));
+        }
+        if order.quantity == 0 {
+            return Err(HedgeXError::ValidationError("GTT order quantity must be positive".to_string()));
+        }
+    }
+
+    Ok(())
+}
+
+fn to_trigger(row: &sqlx::sqlite::SqliteRow) -> Result<GttTrigger> {
+    let gtt = serde_json::from_str(&row.get::<String, _>("data"))
+        .map_err(|e| HedgeXError::InternalError(format!("JSON deserialization failed: {}", e)))?;
+
+    Ok(GttTrigger {
+        gtt,
+        placed_by_hedgex: row.get("placed_by_hedgex"),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::kite::{
+        KiteExchange, KiteGttCondition, KiteGttOrder, KiteOrderType, KiteProduct, KiteTransactionType,
+    };
+    use crate::services::portfolio_sync::BrokerHolding;
+    use tempfile::tempdir;
+
+    async fn setup_test_service() -> (GttService, tempfile::TempDir) {
+        let temp_dir = tempdir().unwrap();
+        let db_service = Arc::new(
+            EnhancedDatabaseService::new(temp_dir.path(), "test_password").await.unwrap()
+        );
+
+        sqlx::query(include_str!("../../migrations/20250822_add_gtt_triggers.sql"))
+            .execute(db_service.get_database().get_pool())
+            .await
+            .unwrap();
+
+        (GttService::new(db_service), temp_dir)
+    }
+
+    fn request(trigger_type: KiteGttType, trigger_values: Vec<f64>) -> KiteGttRequest {
+        let order = |price: f64| KiteGttOrder {
+            exchange: KiteExchange::NSE,
+            tradingsymbol: "INFY".to_string(),
+            transaction_type: KiteTransactionType::Sell,
+            quantity: 10,
+            order_type: KiteOrderType::Limit,
+            product: KiteProduct::CNC,
+            price,
+        };
+        KiteGttRequest {
+            trigger_type,
+            orders: trigger_values.iter().map(|value| order(*value)).collect(),
+            condition: KiteGttCondition {
+                exchange: KiteExchange::NSE,
+                tradingsymbol: "INFY".to_string(),
+                trigger_values,
+                last_price: 1500.0,
+                instrument_token: None,
+            },
+        }
+    }
+
+    fn gtt(id: u64, status: KiteGttStatus) -> KiteGtt {
+        let request = request(KiteGttType::TwoLeg, vec![1400.0, 1600.0]);
+        KiteGtt {
+            id,
+            trigger_type: request.trigger_type,
+            status,
+            condition: request.condition,
+            orders: request.orders,
+            created_at: None,
+            updated_at: None,
+            expires_at: None,
+        }
+    }
+
+    #[test]
+    fn test_validate_request() {
+        assert!(validate_request(&request(KiteGttType::Single, vec![1400.0])).is_ok());
+        assert!(validate_request(&request(KiteGttType::TwoLeg, vec![1400.0, 1600.0])).is_ok());
+
+        assert!(validate_request(&request(KiteGttType::Single, vec![1400.0, 1600.0])).is_err());
+        assert!(validate_request(&request(KiteGttType::TwoLeg, vec![1600.0, 1400.0])).is_err());
+        assert!(validate_request(&request(KiteGttType::Single, vec![0.0])).is_err());
+
+        let mut other_symbol = request(KiteGttType::Single, vec![1400.0]);
+        other_symbol.orders[0].tradingsymbol = "TCS".to_string();
+        assert!(validate_request(&other_symbol).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_sync_keeps_references_and_attaches_active_triggers() {
+        let (service, _dir) = setup_test_service().await;
+
+        // One trigger placed through HedgeX, then a sync also lists a manual one
+        service.store(&gtt(1, KiteGttStatus::Active), true).await.unwrap();
+        service.store_all(&[gtt(1, KiteGttStatus::Active), gtt(2, KiteGttStatus::Triggered)]).await.unwrap();
+
+        let triggers = service.list().await.unwrap();
+        assert_eq!(triggers.len(), 2);
+        assert_eq!(triggers[0].gtt.id, 2);
+        assert!(!triggers[0].placed_by_hedgex);
+        assert!(triggers[1].placed_by_hedgex);
+
+        let now = Utc::now();
+        let mut portfolio = BrokerPortfolio {
+            positions: Vec::new(),
+            holdings: vec![BrokerHolding {
+                exchange: "NSE".to_string(),
+                tradingsymbol: "INFY".to_string(),
+                isin: "INE009A01021".to_string(),
+                quantity: 10,
+                t1_quantity: 0,
+                average_price: 1450.0,
+                last_price: 1500.0,
+                close_price: 1490.0,
+                pnl: 500.0,
+                day_change: 10.0,
+                synced_at: now,
+                gtts: Vec::new(),
+            }],
+            unrealized_pnl: 0.0,
+            holdings_pnl: 500.0,
+            synced_at: Some(now),
+        };
+        service.attach(&mut portfolio).await.unwrap();
+
+        // Only the resting trigger is an exit still in place
+        assert_eq!(portfolio.holdings[0].gtts.len(), 1);
+        assert_eq!(portfolio.holdings[0].gtts[0].gtt.id, 1);
+
+        // A trigger gone from the broker's list was deleted in Kite
+        service.store_all(&[gtt(2, KiteGttStatus::Triggered)]).await.unwrap();
+        assert!(service.get(1).await.unwrap().is_none());
+    }
+}
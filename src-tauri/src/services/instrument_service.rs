@@ -0,0 +1,497 @@
+use crate::api::kite_client::KiteApiClient;
+use crate::error::{HedgeXError, Result};
+use crate::models::kite::{KiteExchange, KiteInstrument};
+use crate::services::broker_sync::spawn_periodic_sync;
+use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use chrono::{DateTime, Duration as ChronoDuration, NaiveDate, TimeZone, Utc};
+use chrono_tz::Asia::Kolkata;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{info, warn};
+
+/// Default number of results returned by an instrument search
+const DEFAULT_SEARCH_LIMIT: i64 = 50;
+
+/// Maximum number of results returned by an instrument search
+const MAX_SEARCH_LIMIT: i64 = 500;
+
+/// How often the background task checks whether the dump needs refreshing
+const REFRESH_CHECK_INTERVAL: Duration = Duration::from_secs(900);
+
+/// Kite publishes the day's instruments dump by 08:30 IST, ahead of the open
+const DUMP_PUBLISH_HOUR: u32 = 8;
+const DUMP_PUBLISH_MINUTE: u32 = 30;
+
+/// Filters for searching the instruments table
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct InstrumentSearchQuery {
+    /// Free text matched against trading symbol and name
+    pub query: Option<String>,
+    /// Exact exchange filter (e.g. "NSE")
+    pub exchange: Option<String>,
+    /// Exact segment filter (e.g. "NSE", "NFO-OPT", "INDICES")
+    pub segment: Option<String>,
+    /// Exact instrument type filter (e.g. "EQ", "FUT", "CE")
+    pub instrument_type: Option<String>,
+    /// Look up a single instrument by token
+    pub instrument_token: Option<u64>,
+    pub limit: Option<i64>,
+}
+
+/// Service for the instruments master backed by the Kite instruments dump
+pub struct InstrumentService {
+    db_service: Arc<EnhancedDatabaseService>,
+    last_refresh: Arc<RwLock<Option<DateTime<Utc>>>>,
+}
+
+impl InstrumentService {
+    /// Create a new instrument service
+    pub async fn new(db_service: Arc<EnhancedDatabaseService>) -> Result<Self> {
+        let service = Self {
+            db_service,
+            last_refresh: Arc::new(RwLock::new(None)),
+        };
+
+        // Pick up the last refresh time from a previous run, if any
+        match service.load_last_refresh().await {
+            Ok(last) => *service.last_refresh.write().await = last,
+            Err(e) => warn!("Could not read instruments refresh time: {}", e),
+        }
+
+        info!("InstrumentService initialized successfully");
+        Ok(service)
+    }
+
+    /// Load the most recent refresh time from the instruments table
+    async fn load_last_refresh(&self) -> Result<Option<DateTime<Utc>>> {
+        let row = sqlx::query("SELECT MAX(updated_at) as last_refresh FROM instruments")
+            .fetch_one(self.db_service.get_database().get_pool())
+            .await?;
+
+        Ok(row.get::<Option<DateTime<Utc>>, _>("last_refresh"))
+    }
+
+    /// Read the last refresh time again, as after a backup restore replaced the instruments
+    pub async fn reload(&self) -> Result<()> {
+        *self.last_refresh.write().await = self.load_last_refresh().await?;
+        Ok(())
+    }
+
+    /// Get the time of the last successful refresh
+    pub async fn get_last_refresh(&self) -> Option<DateTime<Utc>> {
+        *self.last_refresh.read().await
+    }
+
+    /// Whether the stored dump predates the latest published one
+    pub async fn needs_refresh(&self) -> bool {
+        Self::is_refresh_due(self.get_last_refresh().await, Utc::now())
+    }
+
+    /// Whether a dump stored at `last` is older than the latest morning publication as of `now`
+    ///
+    /// Refreshing between midnight and the publication would only fetch the previous day's dump again.
+    pub fn is_refresh_due(last: Option<DateTime<Utc>>, now: DateTime<Utc>) -> bool {
+        let today = now.with_timezone(&Kolkata).date_naive();
+        let published_today = Self::publication_time(today);
+        let latest_publication = if now >= published_today {
+            published_today
+        } else {
+            Self::publication_time(today - ChronoDuration::days(1))
+        };
+
+        last.map_or(true, |last| last < latest_publication)
+    }
+
+    fn publication_time(date: NaiveDate) -> DateTime<Utc> {
+        let local = date.and_hms_opt(DUMP_PUBLISH_HOUR, DUMP_PUBLISH_MINUTE, 0).unwrap_or_default();
+        Kolkata.from_local_datetime(&local).single()
+            .map_or_else(|| Utc.from_utc_datetime(&local), |time| time.with_timezone(&Utc))
+    }
+
+    /// Download the instruments dump and replace the stored copy
+    pub async fn refresh_instruments(&self, client: &dyn KiteApiClient) -> Result<usize> {
+        info!("Refreshing instruments from Kite");
+        let instruments = client.get_instruments(None).await?;
+
+        if instruments.is_empty() {
+            return Err(HedgeXError::ExternalServiceError("Kite returned an empty instruments dump".to_string()));
+        }
+
+        self.store_instruments(&instruments).await
+    }
+
+    /// Replace the stored instruments with the given list
+    pub async fn store_instruments(&self, instruments: &[KiteInstrument]) -> Result<usize> {
+        let now = Utc::now();
+        let mut tx = self.db_service.get_database().get_pool().begin().await?;
+
+        // Expired contracts disappear from the dump, so start from a clean table
+        sqlx::query("DELETE FROM instruments")
+            .execute(&mut *tx)
+            .await?;
+
+        let query = "
+            INSERT OR REPLACE INTO instruments (
+                instrument_token, exchange_token, tradingsymbol, name, last_price,
+                expiry, strike, tick_size, lot_size, instrument_type, segment,
+                exchange, updated_at
+            ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ";
+
+        for instrument in instruments {
+            sqlx::query(query)
+                .bind(instrument.instrument_token as i64)
+                .bind(instrument.exchange_token as i64)
+                .bind(&instrument.tradingsymbol)
+                .bind(&instrument.name)
+                .bind(instrument.last_price)
+                .bind(instrument.expiry)
+                .bind(instrument.strike)
+                .bind(instrument.tick_size)
+                .bind(instrument.lot_size as i64)
+                .bind(&instrument.instrument_type)
+                .bind(&instrument.segment)
+                .bind(instrument.exchange.to_string())
+                .bind(now)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+
+        *self.last_refresh.write().await = Some(now);
+
+        info!("Stored {} instruments", instruments.len());
+        Ok(instruments.len())
+    }
+
+    /// Refresh the instruments dump once per trading day in the background
+    pub fn start_daily_refresh(self: Arc<Self>, client: Arc<dyn KiteApiClient + Send + Sync>) {
+        spawn_periodic_sync("instruments refresh", REFRESH_CHECK_INTERVAL, client, move |client| {
+            let service = Arc::clone(&self);
+            async move {
+                if service.needs_refresh().await {
+                    service.refresh_instruments(client.as_ref()).await?;
+                }
+                Ok(())
+            }
+        });
+    }
+
+    /// Search instruments by symbol/name with optional exchange, segment and type filters
+    pub async fn search_instruments(&self, search: &InstrumentSearchQuery) -> Result<Vec<KiteInstrument>> {
+        let mut query = String::from(
+            "SELECT instrument_token, exchange_token, tradingsymbol, name, last_price,
+                    expiry, strike, tick_size, lot_size, instrument_type, segment, exchange
+             FROM instruments WHERE 1 = 1"
+        );
+        let mut binds: Vec<String> = Vec::new();
+
+        if let Some(token) = search.instrument_token {
+            query.push_str(" AND instrument_token = ?");
+            binds.push(token.to_string());
+        }
+
+        let text = search.query.as_deref().map(str::trim).filter(|q| !q.is_empty());
+        if let Some(text) = text {
+            query.push_str(" AND (tradingsymbol LIKE ? OR name LIKE ?)");
+            let pattern = format!("%{}%", text.to_uppercase());
+            binds.push(pattern.clone());
+            binds.push(pattern);
+        }
+
+        if let Some(exchange) = &search.exchange {
+            query.push_str(" AND exchange = ?");
+            binds.push(exchange.to_uppercase());
+        }
+
+        if let Some(segment) = &search.segment {
+            query.push_str(" AND segment = ?");
+            binds.push(segment.to_uppercase());
+        }
+
+        if let Some(instrument_type) = &search.instrument_type {
+            query.push_str(" AND instrument_type = ?");
+            binds.push(instrument_type.to_uppercase());
+        }
+
+        // Exact and prefix symbol matches first, then shortest symbols
+        if let Some(text) = text {
+            query.push_str(
+                " ORDER BY CASE WHEN tradingsymbol = ? THEN 0 WHEN tradingsymbol LIKE ? THEN 1 ELSE 2 END, \
+                 LENGTH(tradingsymbol), tradingsymbol"
+            );
+            binds.push(text.to_uppercase());
+            binds.push(format!("{}%", text.to_uppercase()));
+        } else {
+            query.push_str(" ORDER BY tradingsymbol");
+        }
+
+        let limit = search.limit.unwrap_or(DEFAULT_SEARCH_LIMIT).clamp(1, MAX_SEARCH_LIMIT);
+        query.push_str(" LIMIT ?");
+
+        let mut sql_query = sqlx::query(&query);
+        for bind in &binds {
+            sql_query = sql_query.bind(bind);
+        }
+
+        let rows = sql_query
+            .bind(limit)
+            .fetch_all(self.db_service.get_database().get_pool())
+            .await?;
+
+        rows.iter().map(Self::row_to_instrument).collect()
+    }
+
+    /// Get an instrument by its token
+    pub async fn get_instrument_by_token(&self, instrument_token: u64) -> Result<Option<KiteInstrument>> {
+        let search = InstrumentSearchQuery {
+            instrument_token: Some(instrument_token),
+            limit: Some(1),
+            ..Default::default()
+        };
+
+        Ok(self.search_instruments(&search).await?.into_iter().next())
+    }
+
+    /// Look up the instrument token for a trading symbol
+    pub async fn get_instrument_token(&self, exchange: &str, tradingsymbol: &str) -> Result<Option<u64>> {
+        let row = sqlx::query("SELECT instrument_token FROM instruments WHERE exchange = ? AND tradingsymbol = ?")
+            .bind(exchange.to_uppercase())
+            .bind(tradingsymbol.to_uppercase())
+            .fetch_optional(self.db_service.get_database().get_pool())
+            .await?;
+
+        Ok(row.map(|r| r.get::<i64, _>("instrument_token") as u64))
+    }
+
+    /// Look up an instrument by exchange and trading symbol
+    pub async fn get_instrument(&self, exchange: &str, tradingsymbol: &str) -> Result<Option<KiteInstrument>> {
+        let row = sqlx::query("SELECT * FROM instruments WHERE exchange = ? AND tradingsymbol = ?")
+            .bind(exchange.to_uppercase())
+            .bind(tradingsymbol.to_uppercase())
+            .fetch_optional(self.db_service.get_database().get_pool())
+            .await?;
+
+        row.as_ref().map(Self::row_to_instrument).transpose()
+    }
+
+    /// Options on an underlying across the derivative exchanges, by expiry then strike
+    pub async fn get_options(&self, underlying: &str) -> Result<Vec<KiteInstrument>> {
+        let rows = sqlx::query(
+            "SELECT * FROM instruments
+             WHERE name = ? AND instrument_type IN ('CE', 'PE') AND exchange IN ('NFO', 'BFO')
+             ORDER BY expiry, strike, instrument_type"
+        )
+            .bind(underlying.to_uppercase())
+            .fetch_all(self.db_service.get_database().get_pool())
+            .await?;
+
+        rows.iter().map(Self::row_to_instrument).collect()
+    }
+
+    /// Look up tokens for several symbols, returning the (token, symbol) pairs found and the symbols that were not
+    pub async fn resolve_symbols(&self, exchange: &str, symbols: &[String]) -> Result<(Vec<(u64, String)>, Vec<String>)> {
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+
+        for symbol in symbols {
+            match self.get_instrument_token(exchange, symbol).await? {
+                Some(token) => found.push((token, symbol.to_uppercase())),
+                None => missing.push(symbol.clone()),
+            }
+        }
+
+        Ok((found, missing))
+    }
+
+    fn row_to_instrument(row: &sqlx::sqlite::SqliteRow) -> Result<KiteInstrument> {
+        let exchange_str: String = row.get("exchange");
+        let exchange = KiteExchange::from_str(&exchange_str)
+            .map_err(HedgeXError::DataIntegrityError)?;
+
+        Ok(KiteInstrument {
+            instrument_token: row.get::<i64, _>("instrument_token") as u64,
+            exchange_token: row.get::<i64, _>("exchange_token") as u64,
+            tradingsymbol: row.get("tradingsymbol"),
+            name: row.get::<Option<String>, _>("name").unwrap_or_default(),
+            last_price: row.get("last_price"),
+            expiry: row.get("expiry"),
+            strike: row.get("strike"),
+            tick_size: row.get("tick_size"),
+            lot_size: row.get::<i64, _>("lot_size") as u32,
+            instrument_type: row.get("instrument_type"),
+            segment: row.get("segment"),
+            exchange,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use tempfile::tempdir;
+
+    async fn setup_test_service() -> (InstrumentService, tempfile::TempDir) {
+        let temp_dir = tempdir().unwrap();
+        let db_service = Arc::new(
+            EnhancedDatabaseService::new(temp_dir.path(), "test_password").await.unwrap()
+        );
+
+        sqlx::query(include_str!("../../migrations/20250801_add_instruments.sql"))
+            .execute(db_service.get_database().get_pool())
+            .await
+            .unwrap();
+
+        let service = InstrumentService::new(db_service).await.unwrap();
+        (service, temp_dir)
+    }
+
+    fn instrument(token: u64, symbol: &str, name: &str, segment: &str, exchange: KiteExchange) -> KiteInstrument {
+        KiteInstrument {
+            instrument_token: token,
+            exchange_token: token / 256,
+            tradingsymbol: symbol.to_string(),
+            name: name.to_string(),
+            last_price: 0.0,
+            expiry: None,
+            strike: None,
+            tick_size: 0.05,
+            lot_size: 1,
+            instrument_type: "EQ".to_string(),
+            segment: segment.to_string(),
+            exchange,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_and_search_instruments() {
+        let (service, _dir) = setup_test_service().await;
+        assert!(service.needs_refresh().await);
+
+        let instruments = vec![
+            instrument(408065, "INFY", "INFOSYS", "NSE", KiteExchange::NSE),
+            instrument(500209, "INFY", "INFOSYS", "BSE", KiteExchange::BSE),
+            instrument(2953217, "TCS", "TATA CONSULTANCY SERV LT", "NSE", KiteExchange::NSE),
+            instrument(256265, "NIFTY 50", "NIFTY 50", "INDICES", KiteExchange::NSE),
+        ];
+        assert_eq!(service.store_instruments(&instruments).await.unwrap(), 4);
+        assert!(!service.needs_refresh().await);
+
+        // Text search matches symbol and name
+        let results = service.search_instruments(&InstrumentSearchQuery {
+            query: Some("infy".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(results.len(), 2);
+
+        let results = service.search_instruments(&InstrumentSearchQuery {
+            query: Some("tata".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].tradingsymbol, "TCS");
+
+        // Exchange and segment filters
+        let results = service.search_instruments(&InstrumentSearchQuery {
+            query: Some("INFY".to_string()),
+            exchange: Some("bse".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].instrument_token, 500209);
+
+        let results = service.search_instruments(&InstrumentSearchQuery {
+            segment: Some("INDICES".to_string()),
+            ..Default::default()
+        }).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].tradingsymbol, "NIFTY 50");
+
+        // Token lookups
+        let found = service.get_instrument_by_token(2953217).await.unwrap().unwrap();
+        assert_eq!(found.tradingsymbol, "TCS");
+        assert_eq!(service.get_instrument_token("NSE", "infy").await.unwrap(), Some(408065));
+        assert_eq!(service.get_instrument_token("NSE", "UNKNOWN").await.unwrap(), None);
+
+        let symbols = vec!["infy".to_string(), "UNKNOWN".to_string()];
+        let (found, missing) = service.resolve_symbols("NSE", &symbols).await.unwrap();
+        assert_eq!(found, vec![(408065, "INFY".to_string())]);
+        assert_eq!(missing, vec!["UNKNOWN".to_string()]);
+    }
+
+    #[test]
+    fn test_refresh_waits_for_the_morning_dump() {
+        let ist = |day: u32, hour: u32, minute: u32| {
+            Kolkata.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap().with_timezone(&Utc)
+        };
+        let refreshed = ist(25, 9, 0);
+
+        assert!(InstrumentService::is_refresh_due(None, ist(25, 3, 0)));
+        assert!(!InstrumentService::is_refresh_due(Some(refreshed), ist(25, 20, 0)));
+
+        // Past midnight the stored dump is still the latest published one
+        assert!(!InstrumentService::is_refresh_due(Some(refreshed), ist(26, 1, 0)));
+        assert!(!InstrumentService::is_refresh_due(Some(refreshed), ist(26, 8, 29)));
+        assert!(InstrumentService::is_refresh_due(Some(refreshed), ist(26, 8, 30)));
+
+        // A dump fetched before the publication is replaced once it is out
+        assert!(InstrumentService::is_refresh_due(Some(ist(26, 7, 0)), ist(26, 9, 0)));
+    }
+
+    #[tokio::test]
+    async fn test_order_lookup_by_exchange_and_symbol() {
+        let (service, _dir) = setup_test_service().await;
+        let mut future = instrument(13238786, "NIFTY24JANFUT", "NIFTY", "NFO-FUT", KiteExchange::NFO);
+        future.lot_size = 50;
+        service.store_instruments(&[future]).await.unwrap();
+
+        let found = service.get_instrument("nfo", "nifty24janfut").await.unwrap().unwrap();
+        assert_eq!(found.instrument_token, 13238786);
+        assert!(found.is_whole_lots(100));
+        assert!(!found.is_whole_lots(75));
+        assert_eq!(found.round_to_tick(Decimal::new(2150012, 2)), Decimal::new(2150010, 2));
+        assert!(service.get_instrument("NSE", "NIFTY24JANFUT").await.unwrap().is_none());
+    }
+
+    #[tokio::test]
+    async fn test_options_on_an_underlying() {
+        let (service, _dir) = setup_test_service().await;
+        let option = |token: u64, symbol: &str, day: u32, strike: f64, kind: &str| {
+            let mut option = instrument(token, symbol, "NIFTY", "NFO-OPT", KiteExchange::NFO);
+            option.expiry = NaiveDate::from_ymd_opt(2024, 2, day);
+            option.strike = Some(strike);
+            option.instrument_type = kind.to_string();
+            option
+        };
+        service.store_instruments(&[
+            option(3, "NIFTY24FEB21000CE", 29, 21000.0, "CE"),
+            option(2, "NIFTY2420121100PE", 1, 21100.0, "PE"),
+            option(1, "NIFTY2420121000CE", 1, 21000.0, "CE"),
+            instrument(13238786, "NIFTY24FEBFUT", "NIFTY", "NFO-FUT", KiteExchange::NFO),
+        ]).await.unwrap();
+
+        // Futures are left out, and the nearest expiry's strikes come first
+        let options = service.get_options("nifty").await.unwrap();
+        let symbols: Vec<&str> = options.iter().map(|o| o.tradingsymbol.as_str()).collect();
+        assert_eq!(symbols, vec!["NIFTY2420121000CE", "NIFTY2420121100PE", "NIFTY24FEB21000CE"]);
+        assert!(service.get_options("BANKNIFTY").await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_store_replaces_previous_dump() {
+        let (service, _dir) = setup_test_service().await;
+
+        service.store_instruments(&[instrument(1, "OLDFUT", "OLD", "NFO-FUT", KiteExchange::NFO)]).await.unwrap();
+        service.store_instruments(&[instrument(2, "NEWFUT", "NEW", "NFO-FUT", KiteExchange::NFO)]).await.unwrap();
+
+        let results = service.search_instruments(&InstrumentSearchQuery::default()).await.unwrap();
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].tradingsymbol, "NEWFUT");
+    }
+}
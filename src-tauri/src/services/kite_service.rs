@@ -2,9 +2,9 @@ use crate::api::kite_client::{KiteApiClient, KiteClient};
 use crate::error::{HedgeXError, Result, ResultExt};
 use crate::models::kite::{
     KiteApiCredentials, KiteOrderRequest, KiteOrderResponse, KitePosition, 
-    KiteOrder, KiteHolding, KiteMarginResponse, KiteProfile, KiteQuote,
+    KiteOrder, KiteTrade, KiteHolding, KiteMarginResponse, KiteProfile, KiteQuote,
     KiteHistoricalDataParams, KiteOHLCV, KiteInstrument, KiteExchange,
-    KiteOrderVariety,
+    KiteOrderVariety, KiteMarginOrder, KiteBasketMargin, KiteOhlcQuote, KiteLtpQuote,
 };
 use crate::services::enhanced_database_service::EnhancedDatabaseService;
 use sqlx::{Row, sqlite::SqliteRow};
@@ -17,6 +17,9 @@ use tracing::{debug, error, info, warn, instrument, Span, span, Level};
 use chrono::{DateTime, Utc};
 use std::fmt;
 
+/// How long fetched margins are served from cache
+const MARGINS_CACHE_TTL: Duration = Duration::from_secs(5);
+
 /// Service for managing Kite API operations
 pub struct KiteService {
     /// Database service for storing credentials
@@ -33,6 +36,9 @@ pub struct KiteService {
     
     /// User ID for the current session
     user_id: String,
+    
+    /// Margins last fetched and when; dropped whenever an order changes them
+    margins_cache: RwLock<Option<(Instant, KiteMarginResponse)>>,
 }
 
 impl KiteService {
@@ -47,6 +53,7 @@ impl KiteService {
             last_refresh: Mutex::new(Instant::now()),
             refresh_interval: Duration::from_secs(3600), // 1 hour
             user_id: user_id.to_string(),
+            margins_cache: RwLock::new(None),
         };
         
         // Initialize client with stored credentials
@@ -302,8 +309,14 @@ impl KiteService {
         client.get_profile().await
     }
     
-    /// Get account margins
+    /// Get account margins of the equity and commodity segments, cached briefly
     pub async fn get_margins(&self) -> Result<KiteMarginResponse> {
+        if let Some((fetched_at, margins)) = self.margins_cache.read().await.as_ref() {
+            if fetched_at.elapsed() < MARGINS_CACHE_TTL {
+                return Ok(margins.clone());
+            }
+        }
+        
         // Check if token needs refresh
         if self.check_token_refresh().await? {
             return Err(HedgeXError::SessionError);
@@ -313,7 +326,32 @@ impl KiteService {
         let client = self.get_client().await?;
         
         // Get margins
-        client.get_margins().await
+        let margins = client.get_margins().await?;
+        *self.margins_cache.write().await = Some((Instant::now(), margins.clone()));
+        Ok(margins)
+    }
+    
+    /// Get the combined margin of a basket of orders before placing them
+    pub async fn get_basket_margins(&self, orders: &[KiteMarginOrder], consider_positions: bool) -> Result<KiteBasketMargin> {
+        if orders.is_empty() {
+            return Err(HedgeXError::ValidationError("Basket has no orders".to_string()));
+        }
+        
+        // Check if token needs refresh
+        if self.check_token_refresh().await? {
+            return Err(HedgeXError::SessionError);
+        }
+        
+        // Get client
+        let client = self.get_client().await?;
+        
+        // Get basket margins
+        client.get_basket_margins(orders, consider_positions).await
+    }
+    
+    /// Forget cached margins so the next read fetches them again
+    pub async fn invalidate_margins(&self) {
+        *self.margins_cache.write().await = None;
     }
     
     /// Get order book
@@ -345,7 +383,7 @@ impl KiteService {
     }
     
     /// Get trades
-    pub async fn get_trades(&self) -> Result<Vec<KiteOrder>> {
+    pub async fn get_trades(&self) -> Result<Vec<KiteTrade>> {
         // Check if token needs refresh
         if self.check_token_refresh().await? {
             return Err(HedgeXError::SessionError);
@@ -414,6 +452,34 @@ impl KiteService {
         client.get_quote(instruments).await
     }
     
+    /// Get OHLC and last price quotes
+    pub async fn get_ohlc(&self, instruments: &[String]) -> Result<HashMap<String, KiteOhlcQuote>> {
+        // Check if token needs refresh
+        if self.check_token_refresh().await? {
+            return Err(HedgeXError::SessionError);
+        }
+        
+        // Get client
+        let client = self.get_client().await?;
+        
+        // Get quotes
+        client.get_ohlc(instruments).await
+    }
+    
+    /// Get last price quotes
+    pub async fn get_ltp(&self, instruments: &[String]) -> Result<HashMap<String, KiteLtpQuote>> {
+        // Check if token needs refresh
+        if self.check_token_refresh().await? {
+            return Err(HedgeXError::SessionError);
+        }
+        
+        // Get client
+        let client = self.get_client().await?;
+        
+        // Get quotes
+        client.get_ltp(instruments).await
+    }
+    
     /// Get historical data
     pub async fn get_historical_data(&self, params: KiteHistoricalDataParams) -> Result<Vec<KiteOHLCV>> {
         // Check if token needs refresh
@@ -440,6 +506,7 @@ impl KiteService {
         
         // Place order
         let response = client.place_order(order).await?;
+        self.invalidate_margins().await;
         
         // Log order placement
         info!("Order placed successfully: {}", response.order_id);
@@ -459,6 +526,7 @@ impl KiteService {
         
         // Modify order
         let response = client.modify_order(order_id, order).await?;
+        self.invalidate_margins().await;
         
         // Log order modification
         info!("Order modified successfully: {}", response.order_id);
@@ -478,6 +546,7 @@ impl KiteService {
         
         // Cancel order
         let response = client.cancel_order(order_id, variety).await?;
+        self.invalidate_margins().await;
         
         // Log order cancellation
         info!("Order cancelled successfully: {}", response.order_id);
@@ -490,6 +559,7 @@ impl KiteService {
 mod tests {
     use super::*;
     use crate::api::kite_client::KiteApiClient;
+    use crate::models::kite::{KiteGtt, KiteGttRequest, KiteGttResponse};
     use async_trait::async_trait;
     use mockall::mock;
     use mockall::predicate::*;
@@ -509,17 +579,25 @@ mod tests {
             async fn invalidate_session(&self) -> Result<()>;
             async fn get_profile(&self) -> Result<KiteProfile>;
             async fn get_margins(&self) -> Result<KiteMarginResponse>;
+            async fn get_basket_margins(&self, orders: &[KiteMarginOrder], consider_positions: bool) -> Result<KiteBasketMargin>;
             async fn get_orders(&self) -> Result<Vec<KiteOrder>>;
             async fn get_order_history(&self, order_id: &str) -> Result<Vec<KiteOrder>>;
-            async fn get_trades(&self) -> Result<Vec<KiteOrder>>;
+            async fn get_trades(&self) -> Result<Vec<KiteTrade>>;
             async fn get_positions(&self) -> Result<KitePosition>;
             async fn get_holdings(&self) -> Result<Vec<KiteHolding>>;
             async fn get_instruments(&self, exchange: Option<KiteExchange>) -> Result<Vec<KiteInstrument>>;
             async fn get_quote(&self, instruments: &[String]) -> Result<HashMap<String, KiteQuote>>;
+            async fn get_ohlc(&self, instruments: &[String]) -> Result<HashMap<String, KiteOhlcQuote>>;
+            async fn get_ltp(&self, instruments: &[String]) -> Result<HashMap<String, KiteLtpQuote>>;
             async fn get_historical_data(&self, params: KiteHistoricalDataParams) -> Result<Vec<KiteOHLCV>>;
             async fn place_order(&self, order: KiteOrderRequest) -> Result<KiteOrderResponse>;
             async fn modify_order(&self, order_id: &str, order: KiteOrderRequest) -> Result<KiteOrderResponse>;
             async fn cancel_order(&self, order_id: &str, variety: KiteOrderVariety) -> Result<KiteOrderResponse>;
+            async fn get_gtts(&self) -> Result<Vec<KiteGtt>>;
+            async fn get_gtt(&self, trigger_id: u64) -> Result<KiteGtt>;
+            async fn place_gtt(&self, gtt: KiteGttRequest) -> Result<KiteGttResponse>;
+            async fn modify_gtt(&self, trigger_id: u64, gtt: KiteGttRequest) -> Result<KiteGttResponse>;
+            async fn delete_gtt(&self, trigger_id: u64) -> Result<KiteGttResponse>;
         }
     }
     
@@ -6,6 +6,38 @@ pub mod auth_service;
 pub mod kite_service;
 pub mod websocket_manager;
 pub mod strategy_service;
+pub mod instrument_service;
+pub mod backtest_engine;
+pub mod backtest_queue;
+pub mod monte_carlo;
+pub mod tick_recorder;
+pub mod feed_health;
+pub mod tick_replay;
+pub mod ticker_shards;
+pub mod recording_session;
+pub mod tick_channel;
+pub mod portfolio_sync;
+pub mod order_book;
+pub mod gtt;
+pub mod broker_sync;
+pub mod option_chain;
+pub mod tax_report;
+pub mod pnl_statement;
+pub mod time_breakdown;
+pub mod strategy_correlation;
+pub mod trade_streaks;
+pub mod slippage;
+pub mod notifications;
+pub mod eod_report;
+pub mod report_builder;
+pub mod rolling_performance;
+pub mod pnl_split;
+pub mod excursions;
+pub mod cloud_backup;
+pub mod data_archive;
+pub mod db_health;
+pub mod data_integrity;
+pub mod profile_bundle;
 #[cfg(test)]
 mod auth_service_test;
 #[cfg(test)]
@@ -17,8 +49,38 @@ mod data_persistence_service_test;
 pub use app_service::AppService;
 pub use database_service::DatabaseService;
 pub use enhanced_database_service::EnhancedDatabaseService;
-pub use data_persistence_service::{DataPersistenceService, DataPersistenceConfig, UserSettings, BackupMetadata, DataExportRequest, ExportType, ExportFormat, BackupType};
+pub use data_persistence_service::{DataPersistenceService, DataPersistenceConfig, UserSettings, BackupMetadata, DataExportRequest, ExportType, ExportFormat, BackupType, BackupSchedule, CloudUpload, CloudUploadStatus, RestoreProgress, RestoreStage, CleanupReport};
 pub use auth_service::AuthService;
 pub use kite_service::KiteService;
-pub use websocket_manager::{WebSocketManager, MarketData, SubscriptionMode, ConnectionStatus};
-pub use strategy_service::{StrategyService, CreateStrategyRequest, UpdateStrategyRequest, StrategyPerformance};
\ No newline at end of file
+pub use websocket_manager::{WebSocketManager, MarketData, SubscriptionMode, ConnectionStatus, ConnectionEvent, RetryConfig, DisplayThrottle, SubscriptionInfo, IndexQuote, MARKET_INDICES, CacheLimits};
+pub use strategy_service::{StrategyService, CreateStrategyRequest, UpdateStrategyRequest, StrategyPerformance, StrategyListQuery, StrategySortField};
+pub use instrument_service::{InstrumentService, InstrumentSearchQuery};
+pub use backtest_engine::BacktestEngine;
+pub use backtest_queue::BacktestQueue;
+pub use tick_recorder::{TickRecorder, TickRecorderConfig, TickRecorderStats};
+pub use feed_health::{FeedAlert, FeedHealth, FeedMetrics};
+pub use tick_replay::{TickReplay, ReplayConfig, ReplaySource, ReplayProgress};
+pub use recording_session::{RecordingSession, RecordingSessionInfo};
+pub use tick_channel::{TickChannelStats, TickPolicy, TickReceiver, TickSender};
+pub use portfolio_sync::{PortfolioSyncService, BrokerPortfolio, BrokerPosition, BrokerHolding, PositionMismatch};
+pub use order_book::{OrderBookService, OrderBook, OrderBookEntry};
+pub use gtt::{GttService, GttTrigger};
+pub use option_chain::{OptionChainService, OptionChain, OptionChainRow, OptionQuote};
+pub use tax_report::{TaxReportService, TaxReport, TaxRates, TaxCategory};
+pub use pnl_statement::{PnlStatementService, PnlStatement, OpenPosition};
+pub use time_breakdown::{TimeBreakdown, PerformanceBucket};
+pub use strategy_correlation::{CorrelationMatrix, CorrelatedStrategy};
+pub use trade_streaks::{StreakReport, StreakStats};
+pub use slippage::{SlippageService, SlippageReport, SlippageBucket};
+pub use notifications::{NotificationService, NotificationChannels};
+pub use eod_report::{EodReportService, EodReport};
+pub use report_builder::{ReportSpec, ReportTable, ReportGrouping, ReportMetric};
+pub use rolling_performance::{RollingPerformance, RollingSeries, SharpeInputs};
+pub use pnl_split::{PnlSplit, MarkedPosition};
+pub use excursions::{ExcursionService, ExcursionReport, ExcursionSummary, TradeExcursion};
+pub use db_health::{DbHealthCheck, DbHealthSummary, DbSizePoint};
+pub use data_integrity::{BackupStatus, BackupVerification, DataIntegrityReport, OrphanedRows, RepairMode};
+pub use profile_bundle::{ConflictResolution, ProfileBundle, ProfileImportReport, TableImport, PROFILE_BUNDLE_VERSION};
+pub use data_archive::{DataArchive, ArchivePartition, PartitionedTickStore};
+pub use cloud_backup::{BackupDestination, BackupDestinationConfig, CloudBackupSettings, RemoteBackup, S3Destination, GoogleDriveDestination};
+pub use ticker_shards::{ShardAssignment, ConnectionHealth, ConnectionStats, MAX_TICKER_CONNECTIONS, MAX_INSTRUMENTS_PER_CONNECTION};
@@ -0,0 +1,242 @@
+use chrono::Utc;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+
+use crate::error::{HedgeXError, Result};
+use crate::trading::performance::max_drawdown;
+use crate::models::backtesting::{
+    BacktestTrade, DistributionSummary, HistogramBin, MonteCarloConfig, MonteCarloResult,
+};
+
+/// Number of histogram buckets per distribution
+const HISTOGRAM_BINS: usize = 20;
+
+/// Upper bound on simulations per run
+const MAX_ITERATIONS: usize = 20_000;
+
+/// Closed trade reduced to what the simulation perturbs
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct TradeOutcome {
+    pub pnl: f64,
+    pub entry_value: f64,
+    pub exit_value: f64,
+}
+
+impl TradeOutcome {
+    /// Outcome of a closed trade; open trades and entry records have none
+    pub fn from_trade(trade: &BacktestTrade) -> Option<Self> {
+        let quantity = Decimal::from(trade.quantity);
+        Some(Self {
+            pnl: trade.pnl?.to_f64()?,
+            entry_value: (trade.entry_price * quantity).to_f64()?,
+            exit_value: (trade.exit_price? * quantity).to_f64()?,
+        })
+    }
+}
+
+/// Check a Monte Carlo configuration
+pub fn validate_config(config: &MonteCarloConfig) -> Result<()> {
+    if config.iterations < 100 || config.iterations > MAX_ITERATIONS {
+        return Err(HedgeXError::ValidationError(format!(
+            "Iterations must be between 100 and {}", MAX_ITERATIONS
+        )));
+    }
+
+    if !config.slippage_bps.is_finite() || config.slippage_bps < 0.0 || config.slippage_bps > 100.0 {
+        return Err(HedgeXError::ValidationError(
+            "Slippage must be between 0 and 100 basis points".to_string()
+        ));
+    }
+
+    if !(config.confidence > 0.5 && config.confidence < 1.0) {
+        return Err(HedgeXError::ValidationError(
+            "Confidence must be between 0.5 and 1".to_string()
+        ));
+    }
+
+    Ok(())
+}
+
+/// Reorder or resample trades and perturb their fills, collecting final P&L and max drawdown
+pub fn run_simulation(
+    backtest_id: &str,
+    trades: &[TradeOutcome],
+    initial_capital: f64,
+    config: &MonteCarloConfig,
+) -> MonteCarloResult {
+    let mut rng = match config.seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy(),
+    };
+
+    let mut final_pnls = Vec::with_capacity(config.iterations);
+    let mut drawdowns = Vec::with_capacity(config.iterations);
+    let mut sequence = trades.to_vec();
+
+    for _ in 0..config.iterations {
+        if config.resample {
+            for slot in sequence.iter_mut() {
+                *slot = trades[rng.gen_range(0..trades.len())];
+            }
+        } else {
+            sequence.shuffle(&mut rng);
+        }
+
+        let pnls: Vec<f64> = sequence.iter()
+            .map(|trade| {
+                // Entry and exit each fill up to slippage_bps worse than recorded
+                let entry_cost = trade.entry_value * rng.gen_range(0.0..=config.slippage_bps) / 10_000.0;
+                let exit_cost = trade.exit_value * rng.gen_range(0.0..=config.slippage_bps) / 10_000.0;
+                trade.pnl - entry_cost - exit_cost
+            })
+            .collect();
+
+        final_pnls.push(pnls.iter().sum());
+        drawdowns.push(max_drawdown(initial_capital, &pnls).max_drawdown);
+    }
+
+    let losses = final_pnls.iter().filter(|pnl| **pnl < 0.0).count();
+
+    MonteCarloResult {
+        backtest_id: backtest_id.to_string(),
+        config: config.clone(),
+        trades: trades.len(),
+        probability_of_loss: losses as f64 / config.iterations.max(1) as f64,
+        final_pnl: summarize(final_pnls, config.confidence),
+        max_drawdown: summarize(drawdowns, config.confidence),
+        created_at: Utc::now(),
+    }
+}
+
+/// Percentiles, moments and histogram of a sample
+fn summarize(mut values: Vec<f64>, confidence: f64) -> DistributionSummary {
+    values.sort_by(f64::total_cmp);
+
+    let n = values.len().max(1) as f64;
+    let mean = values.iter().sum::<f64>() / n;
+    let variance = values.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / n;
+    let tail = (1.0 - confidence) / 2.0;
+
+    DistributionSummary {
+        mean,
+        std_dev: variance.sqrt(),
+        min: values.first().copied().unwrap_or(0.0),
+        max: values.last().copied().unwrap_or(0.0),
+        median: percentile(&values, 0.5),
+        lower: percentile(&values, tail),
+        upper: percentile(&values, 1.0 - tail),
+        histogram: histogram(&values),
+    }
+}
+
+/// Linearly interpolated percentile of sorted values
+pub(crate) fn percentile(sorted: &[f64], p: f64) -> f64 {
+    if sorted.is_empty() {
+        return 0.0;
+    }
+
+    let rank = p.clamp(0.0, 1.0) * (sorted.len() - 1) as f64;
+    let below = rank.floor() as usize;
+    let above = rank.ceil() as usize;
+    sorted[below] + (sorted[above] - sorted[below]) * (rank - below as f64)
+}
+
+/// Equal-width buckets between the smallest and largest of sorted values
+fn histogram(sorted: &[f64]) -> Vec<HistogramBin> {
+    let (min, max) = match (sorted.first(), sorted.last()) {
+        (Some(min), Some(max)) => (*min, *max),
+        _ => return Vec::new(),
+    };
+
+    if max - min <= f64::EPSILON {
+        return vec![HistogramBin { lower: min, upper: max, count: sorted.len() }];
+    }
+
+    let width = (max - min) / HISTOGRAM_BINS as f64;
+    let mut bins: Vec<HistogramBin> = (0..HISTOGRAM_BINS)
+        .map(|i| HistogramBin {
+            lower: min + width * i as f64,
+            upper: min + width * (i + 1) as f64,
+            count: 0,
+        })
+        .collect();
+
+    for value in sorted {
+        let index = (((value - min) / width) as usize).min(HISTOGRAM_BINS - 1);
+        bins[index].count += 1;
+    }
+    bins
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn outcome(pnl: f64) -> TradeOutcome {
+        TradeOutcome { pnl, entry_value: 10_000.0, exit_value: 10_000.0 }
+    }
+
+    fn config(resample: bool, slippage_bps: f64) -> MonteCarloConfig {
+        MonteCarloConfig {
+            iterations: 500,
+            resample,
+            slippage_bps,
+            confidence: 0.9,
+            seed: Some(42),
+        }
+    }
+
+    #[test]
+    fn test_reordering_preserves_final_pnl() {
+        let trades = [outcome(500.0), outcome(-300.0), outcome(200.0), outcome(-100.0)];
+        let result = run_simulation("bt", &trades, 100_000.0, &config(false, 0.0));
+
+        // Without resampling or slippage only the path changes, not the total
+        assert!((result.final_pnl.min - 300.0).abs() < 1e-9);
+        assert!((result.final_pnl.max - 300.0).abs() < 1e-9);
+        assert_eq!(result.probability_of_loss, 0.0);
+
+        // Worst ordering takes both losses back to back
+        assert!(result.max_drawdown.max <= 400.0 + 1e-9);
+        assert!(result.max_drawdown.min >= 100.0 - 1e-9);
+        assert!(result.max_drawdown.lower <= result.max_drawdown.upper);
+    }
+
+    #[test]
+    fn test_resampling_and_slippage_widen_distribution() {
+        let trades = [outcome(500.0), outcome(-300.0), outcome(200.0), outcome(-100.0)];
+        let result = run_simulation("bt", &trades, 100_000.0, &config(true, 10.0));
+
+        assert_eq!(result.trades, 4);
+        assert!(result.final_pnl.min < result.final_pnl.max);
+        assert!(result.final_pnl.lower <= result.final_pnl.median);
+        assert!(result.final_pnl.median <= result.final_pnl.upper);
+
+        // Slippage only ever costs money
+        assert!(result.final_pnl.max <= 2000.0);
+        assert_eq!(result.final_pnl.histogram.iter().map(|b| b.count).sum::<usize>(), 500);
+
+        // Same seed, same distribution
+        let again = run_simulation("bt", &trades, 100_000.0, &config(true, 10.0));
+        assert_eq!(again.final_pnl.mean, result.final_pnl.mean);
+    }
+
+    #[test]
+    fn test_validate_config() {
+        assert!(validate_config(&MonteCarloConfig::default()).is_ok());
+        assert!(validate_config(&MonteCarloConfig { iterations: 10, ..MonteCarloConfig::default() }).is_err());
+        assert!(validate_config(&MonteCarloConfig { slippage_bps: -1.0, ..MonteCarloConfig::default() }).is_err());
+        assert!(validate_config(&MonteCarloConfig { confidence: 1.0, ..MonteCarloConfig::default() }).is_err());
+    }
+
+    #[test]
+    fn test_percentile() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+        assert_eq!(percentile(&values, 0.5), 3.0);
+        assert_eq!(percentile(&values, 0.25), 2.0);
+        assert!((percentile(&values, 0.1) - 1.4).abs() < 1e-9);
+    }
+}
@@ -0,0 +1,223 @@
+use crate::error::{HedgeXError, Result};
+use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+/// Telegram Bot API
+const TELEGRAM_API_URL: &str = "https://api.telegram.org";
+
+/// Telegram rejects messages longer than this many characters
+const TELEGRAM_MAX_MESSAGE_CHARS: usize = 4096;
+
+/// Where a user's notifications are delivered; a channel is used once all its fields are set
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct NotificationChannels {
+    pub telegram_bot_token: Option<String>,
+    pub telegram_chat_id: Option<String>,
+    /// Transactional email HTTP API accepting `{to, subject, text}` with a bearer key
+    pub email_api_url: Option<String>,
+    pub email_api_key: Option<String>,
+    pub email_to: Option<String>,
+}
+
+impl NotificationChannels {
+    pub fn telegram(&self) -> Option<(&str, &str)> {
+        Some((self.telegram_bot_token.as_deref()?, self.telegram_chat_id.as_deref()?))
+    }
+
+    pub fn email(&self) -> Option<(&str, &str, &str)> {
+        Some((self.email_api_url.as_deref()?, self.email_api_key.as_deref()?, self.email_to.as_deref()?))
+    }
+}
+
+/// Delivers messages to a user's Telegram chat and email, keeping their secrets encrypted
+pub struct NotificationService {
+    db_service: Arc<EnhancedDatabaseService>,
+    client: reqwest::Client,
+}
+
+impl NotificationService {
+    /// Create a new notification service
+    pub fn new(db_service: Arc<EnhancedDatabaseService>) -> Self {
+        Self {
+            db_service,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Store a user's channels, encrypting the bot token and email API key
+    ///
+    /// The UI never reads secrets back, so an unset secret keeps the stored one and an empty one clears it.
+    pub async fn save_channels(&self, user_id: &str, channels: &NotificationChannels) -> Result<()> {
+        let existing = self.load_channels(user_id).await?;
+        let secret = |new: &Option<String>, stored: Option<String>| match new.as_deref() {
+            None => stored,
+            Some("") => None,
+            Some(value) => Some(value.to_string()),
+        };
+
+        let telegram_bot_token = match secret(&channels.telegram_bot_token, existing.telegram_bot_token) {
+            Some(token) => Some(self.db_service.encrypt_sensitive("telegram_bot_token", &token).await?),
+            None => None,
+        };
+        let email_api_key = match secret(&channels.email_api_key, existing.email_api_key) {
+            Some(key) => Some(self.db_service.encrypt_sensitive("email_api_key", &key).await?),
+            None => None,
+        };
+
+        sqlx::query(
+            "INSERT OR REPLACE INTO notification_channels
+                (user_id, telegram_bot_token, telegram_chat_id, email_api_url, email_api_key, email_to, updated_at)
+             VALUES (?, ?, ?, ?, ?, ?, ?)"
+        )
+            .bind(user_id)
+            .bind(telegram_bot_token)
+            .bind(&channels.telegram_chat_id)
+            .bind(&channels.email_api_url)
+            .bind(email_api_key)
+            .bind(&channels.email_to)
+            .bind(Utc::now())
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+
+        info!("Notification channels saved for user: {}", user_id);
+        Ok(())
+    }
+
+    /// A user's channels with their secrets decrypted; empty when none are saved
+    pub async fn load_channels(&self, user_id: &str) -> Result<NotificationChannels> {
+        let row = sqlx::query(
+            "SELECT telegram_bot_token, telegram_chat_id, email_api_url, email_api_key, email_to
+             FROM notification_channels WHERE user_id = ?"
+        )
+            .bind(user_id)
+            .fetch_optional(self.db_service.get_database().get_pool())
+            .await?;
+
+        let Some(row) = row else {
+            return Ok(NotificationChannels::default());
+        };
+
+        let telegram_bot_token = match row.get::<Option<String>, _>("telegram_bot_token") {
+            Some(token) => Some(self.db_service.decrypt_sensitive("telegram_bot_token", &token).await?),
+            None => None,
+        };
+        let email_api_key = match row.get::<Option<String>, _>("email_api_key") {
+            Some(key) => Some(self.db_service.decrypt_sensitive("email_api_key", &key).await?),
+            None => None,
+        };
+
+        Ok(NotificationChannels {
+            telegram_bot_token,
+            telegram_chat_id: row.get("telegram_chat_id"),
+            email_api_url: row.get("email_api_url"),
+            email_api_key,
+            email_to: row.get("email_to"),
+        })
+    }
+
+    /// Send a message on every configured channel, returning the channels it reached
+    ///
+    /// A failing channel is logged and skipped so it does not hold back the others.
+    pub async fn notify(&self, user_id: &str, subject: &str, text: &str) -> Result<Vec<String>> {
+        let channels = self.load_channels(user_id).await?;
+        let mut delivered = Vec::new();
+
+        if let Some((token, chat_id)) = channels.telegram() {
+            let message = format!("{}\n\n{}", subject, text);
+            match send_telegram(&self.client, TELEGRAM_API_URL, token, chat_id, &message).await {
+                Ok(()) => delivered.push("telegram".to_string()),
+                Err(e) => warn!("Failed to send Telegram notification: {}", e),
+            }
+        }
+
+        if let Some((url, api_key, to)) = channels.email() {
+            match send_email(&self.client, url, api_key, to, subject, text).await {
+                Ok(()) => delivered.push("email".to_string()),
+                Err(e) => warn!("Failed to send email notification: {}", e),
+            }
+        }
+
+        Ok(delivered)
+    }
+}
+
+/// Post a message to a Telegram chat through the Bot API, as preformatted text
+pub async fn send_telegram(client: &reqwest::Client, api_url: &str, token: &str, chat_id: &str, text: &str) -> Result<()> {
+    // Leave room for the <pre> tags
+    let escaped = escape_html(text);
+    let body: String = escaped.chars().take(TELEGRAM_MAX_MESSAGE_CHARS - 11).collect();
+
+    let response = client
+        .post(format!("{}/bot{}/sendMessage", api_url, token))
+        .json(&serde_json::json!({
+            "chat_id": chat_id,
+            "text": format!("<pre>{}</pre>", body),
+            "parse_mode": "HTML",
+        }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(HedgeXError::ExternalServiceError(format!("Telegram responded with {}", response.status())));
+    }
+    Ok(())
+}
+
+/// Send an email through a transactional email HTTP API
+pub async fn send_email(client: &reqwest::Client, url: &str, api_key: &str, to: &str, subject: &str, text: &str) -> Result<()> {
+    let response = client
+        .post(url)
+        .bearer_auth(api_key)
+        .json(&serde_json::json!({ "to": to, "subject": subject, "text": text }))
+        .send()
+        .await?;
+
+    if !response.status().is_success() {
+        return Err(HedgeXError::ExternalServiceError(format!("Email API responded with {}", response.status())));
+    }
+    Ok(())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_send_telegram() {
+        let mut server = mockito::Server::new_async().await;
+        let mock = server.mock("POST", "/botTOKEN/sendMessage")
+            .match_body(mockito::Matcher::PartialJson(serde_json::json!({
+                "chat_id": "42",
+                "text": "<pre>P&amp;L &lt; 0</pre>",
+            })))
+            .with_status(200)
+            .with_body(r#"{"ok":true}"#)
+            .create_async()
+            .await;
+
+        send_telegram(&reqwest::Client::new(), &server.url(), "TOKEN", "42", "P&L < 0").await.unwrap();
+        mock.assert_async().await;
+    }
+
+    #[tokio::test]
+    async fn test_send_email_reports_failure() {
+        let mut server = mockito::Server::new_async().await;
+        let _mock = server.mock("POST", "/send")
+            .match_header("authorization", "Bearer KEY")
+            .with_status(401)
+            .create_async()
+            .await;
+
+        let url = format!("{}/send", server.url());
+        let result = send_email(&reqwest::Client::new(), &url, "KEY", "me@example.com", "EOD", "text").await;
+        assert!(result.is_err());
+    }
+}
@@ -0,0 +1,322 @@
+use crate::api::kite_client::KiteApiClient;
+use crate::error::{HedgeXError, Result};
+use crate::models::kite::{KiteInstrument, KiteQuote};
+use crate::services::instrument_service::InstrumentService;
+use crate::trading::session::{session_bounds, session_date};
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::debug;
+
+/// How long a built option chain is served from cache
+const CHAIN_CACHE_TTL: Duration = Duration::from_secs(5);
+
+/// Instruments Kite returns from one quote call
+const QUOTE_BATCH_SIZE: usize = 500;
+
+/// Annual risk-free rate used to back out implied volatility
+pub const RISK_FREE_RATE: f64 = 0.07;
+
+/// Quote keys of index underlyings, whose option names differ from their index symbols
+const INDEX_QUOTE_KEYS: &[(&str, &str)] = &[
+    ("NIFTY", "NSE:NIFTY 50"),
+    ("BANKNIFTY", "NSE:NIFTY BANK"),
+    ("FINNIFTY", "NSE:NIFTY FIN SERVICE"),
+    ("MIDCPNIFTY", "NSE:NIFTY MID SELECT"),
+    ("SENSEX", "BSE:SENSEX"),
+    ("BANKEX", "BSE:BANKEX"),
+];
+
+/// One option of a strike
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptionQuote {
+    pub tradingsymbol: String,
+    pub instrument_token: u64,
+    pub last_price: f64,
+    pub net_change: f64,
+    pub volume: u64,
+    pub oi: u64,
+    /// Implied volatility in percent, unset when the price leaves none to solve for
+    pub iv: Option<f64>,
+}
+
+/// Call and put of one strike
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptionChainRow {
+    pub strike: f64,
+    pub call: Option<OptionQuote>,
+    pub put: Option<OptionQuote>,
+}
+
+/// Strike-wise quotes of an underlying's options for one expiry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OptionChain {
+    pub underlying: String,
+    /// Underlying's last price, unset if it could not be quoted
+    pub spot: Option<f64>,
+    pub expiry: NaiveDate,
+    /// Every listed expiry, nearest first
+    pub expiries: Vec<NaiveDate>,
+    pub lot_size: u32,
+    pub rows: Vec<OptionChainRow>,
+    pub fetched_at: DateTime<Utc>,
+}
+
+/// Builds option chains from the instruments master and live quotes
+pub struct OptionChainService {
+    instrument_service: Arc<InstrumentService>,
+    /// Chains last built and when, by underlying and expiry
+    cache: RwLock<HashMap<(String, NaiveDate), (Instant, OptionChain)>>,
+}
+
+impl OptionChainService {
+    /// Create a new option chain service
+    pub fn new(instrument_service: Arc<InstrumentService>) -> Self {
+        Self {
+            instrument_service,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Option chain of an underlying for an expiry, the nearest unexpired one by default
+    pub async fn get_chain(&self, client: &dyn KiteApiClient, underlying: &str, expiry: Option<NaiveDate>) -> Result<OptionChain> {
+        let underlying = underlying.trim().to_uppercase();
+        let options = self.instrument_service.get_options(&underlying).await?;
+
+        let today = session_date(Utc::now());
+        let mut expiries: Vec<NaiveDate> = options.iter()
+            .filter_map(|option| option.expiry)
+            .filter(|date| *date >= today)
+            .collect();
+        expiries.dedup();
+        if expiries.is_empty() {
+            return Err(HedgeXError::NotFoundError(format!("No listed options on {}", underlying)));
+        }
+
+        let expiry = match expiry {
+            Some(date) if expiries.contains(&date) => date,
+            Some(date) => return Err(HedgeXError::ValidationError(format!("{} has no options expiring {}", underlying, date))),
+            None => expiries[0],
+        };
+
+        let key = (underlying.clone(), expiry);
+        if let Some((built_at, chain)) = self.cache.read().await.get(&key) {
+            if built_at.elapsed() < CHAIN_CACHE_TTL {
+                return Ok(chain.clone());
+            }
+        }
+
+        let legs: Vec<&KiteInstrument> = options.iter()
+            .filter(|option| option.expiry == Some(expiry))
+            .collect();
+
+        // The underlying rides along in the first quote call
+        let spot_key = underlying_quote_key(&underlying);
+        let mut keys = vec![spot_key.clone()];
+        keys.extend(legs.iter().map(|leg| quote_key(leg)));
+
+        let mut quotes = HashMap::new();
+        for batch in keys.chunks(QUOTE_BATCH_SIZE) {
+            quotes.extend(client.get_quote(batch).await?);
+        }
+        debug!("Quoted {} of {} instruments for the {} {} chain", quotes.len(), keys.len(), underlying, expiry);
+
+        let spot = quotes.get(&spot_key).map(|quote| quote.last_price).filter(|price| *price > 0.0);
+        let years = years_to_expiry(expiry, Utc::now());
+        let chain = OptionChain {
+            underlying: underlying.clone(),
+            spot,
+            expiry,
+            expiries,
+            lot_size: legs.first().map_or(0, |leg| leg.lot_size),
+            rows: build_rows(&legs, &quotes, spot, years),
+            fetched_at: Utc::now(),
+        };
+
+        self.cache.write().await.insert(key, (Instant::now(), chain.clone()));
+        Ok(chain)
+    }
+}
+
+/// Quote key of an instrument
+fn quote_key(instrument: &KiteInstrument) -> String {
+    format!("{}:{}", instrument.exchange, instrument.tradingsymbol)
+}
+
+/// Quote key of an underlying, its NSE equity unless it is an index
+fn underlying_quote_key(underlying: &str) -> String {
+    INDEX_QUOTE_KEYS.iter()
+        .find(|(name, _)| *name == underlying)
+        .map(|(_, key)| key.to_string())
+        .unwrap_or_else(|| format!("NSE:{}", underlying))
+}
+
+/// Pair calls and puts by strike, in strike order
+fn build_rows(legs: &[&KiteInstrument], quotes: &HashMap<String, KiteQuote>, spot: Option<f64>, years: f64) -> Vec<OptionChainRow> {
+    // Strikes are whole paise, so they key exactly
+    let mut rows: BTreeMap<i64, OptionChainRow> = BTreeMap::new();
+
+    for leg in legs {
+        let strike = match leg.strike {
+            Some(strike) => strike,
+            None => continue,
+        };
+        let is_call = leg.instrument_type == "CE";
+        let quote = quotes.get(&quote_key(leg));
+
+        let option = OptionQuote {
+            tradingsymbol: leg.tradingsymbol.clone(),
+            instrument_token: leg.instrument_token,
+            last_price: quote.map_or(0.0, |q| q.last_price),
+            net_change: quote.map_or(0.0, |q| q.net_change),
+            volume: quote.map_or(0, |q| q.volume),
+            oi: quote.map_or(0, |q| q.oi),
+            iv: match (quote, spot) {
+                (Some(q), Some(spot)) => implied_volatility(is_call, q.last_price, spot, strike, years, RISK_FREE_RATE)
+                    .map(|iv| (iv * 10_000.0).round() / 100.0),
+                _ => None,
+            },
+        };
+
+        let row = rows.entry((strike * 100.0).round() as i64).or_insert(OptionChainRow { strike, call: None, put: None });
+        if is_call {
+            row.call = Some(option);
+        } else {
+            row.put = Some(option);
+        }
+    }
+
+    rows.into_values().collect()
+}
+
+/// Years until an expiry's 15:30 IST close
+pub fn years_to_expiry(expiry: NaiveDate, now: DateTime<Utc>) -> f64 {
+    let close = session_bounds(expiry).map_or(now, |(_, close)| close);
+    ((close - now).num_seconds().max(0) as f64) / (365.0 * 24.0 * 3600.0)
+}
+
+/// Black-Scholes price of a European option
+pub fn black_scholes_price(is_call: bool, spot: f64, strike: f64, years: f64, rate: f64, volatility: f64) -> f64 {
+    let discount = (-rate * years).exp();
+    if years <= 0.0 || volatility <= 0.0 {
+        let forward_intrinsic = if is_call { spot - strike * discount } else { strike * discount - spot };
+        return forward_intrinsic.max(0.0);
+    }
+
+    let sd = volatility * years.sqrt();
+    let d1 = ((spot / strike).ln() + (rate + volatility * volatility / 2.0) * years) / sd;
+    let d2 = d1 - sd;
+    if is_call {
+        spot * norm_cdf(d1) - strike * discount * norm_cdf(d2)
+    } else {
+        strike * discount * norm_cdf(-d2) - spot * norm_cdf(-d1)
+    }
+}
+
+/// Volatility at which Black-Scholes gives the price, found by bisection
+///
+/// None when the option has expired or its price is at or below intrinsic value.
+pub fn implied_volatility(is_call: bool, price: f64, spot: f64, strike: f64, years: f64, rate: f64) -> Option<f64> {
+    if price <= 0.0 || spot <= 0.0 || strike <= 0.0 || years <= 0.0 {
+        return None;
+    }
+
+    let (mut low, mut high) = (1e-4, 5.0);
+    if price <= black_scholes_price(is_call, spot, strike, years, rate, low)
+        || price >= black_scholes_price(is_call, spot, strike, years, rate, high)
+    {
+        return None;
+    }
+
+    // The price rises with volatility, so halving the bracket always converges
+    for _ in 0..100 {
+        let mid = (low + high) / 2.0;
+        if black_scholes_price(is_call, spot, strike, years, rate, mid) < price {
+            low = mid;
+        } else {
+            high = mid;
+        }
+        if high - low < 1e-6 {
+            break;
+        }
+    }
+    Some((low + high) / 2.0)
+}
+
+/// Standard normal cumulative distribution
+fn norm_cdf(x: f64) -> f64 {
+    0.5 * (1.0 + erf(x / std::f64::consts::SQRT_2))
+}
+
+/// Error function (Abramowitz and Stegun 7.1.26, accurate to 1.5e-7)
+fn erf(x: f64) -> f64 {
+    let sign = if x < 0.0 { -1.0 } else { 1.0 };
+    let x = x.abs();
+    let t = 1.0 / (1.0 + 0.327_591_1 * x);
+    let poly = t * (0.254_829_592 + t * (-0.284_496_736 + t * (1.421_413_741 + t * (-1.453_152_027 + t * 1.061_405_429))));
+    sign * (1.0 - poly * (-x * x).exp())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_implied_volatility_recovers_pricing_volatility() {
+        // At-the-money call, a month out, 15% volatility
+        let years = 30.0 / 365.0;
+        let price = black_scholes_price(true, 22000.0, 22000.0, years, RISK_FREE_RATE, 0.15);
+        assert!((price - 442.8).abs() < 0.1);
+
+        let iv = implied_volatility(true, price, 22000.0, 22000.0, years, RISK_FREE_RATE).unwrap();
+        assert!((iv - 0.15).abs() < 1e-4);
+
+        // Out-of-the-money puts solve the same way
+        let put = black_scholes_price(false, 22000.0, 22500.0, years, RISK_FREE_RATE, 0.2);
+        let iv = implied_volatility(false, put, 22000.0, 22500.0, years, RISK_FREE_RATE).unwrap();
+        assert!((iv - 0.2).abs() < 1e-4);
+
+        // A price under intrinsic value, or an expired option, has no volatility
+        assert!(implied_volatility(true, 100.0, 22000.0, 21000.0, years, RISK_FREE_RATE).is_none());
+        assert!(implied_volatility(true, 100.0, 22000.0, 22000.0, 0.0, RISK_FREE_RATE).is_none());
+    }
+
+    #[test]
+    fn test_rows_pair_calls_and_puts_by_strike() {
+        let leg = |symbol: &str, strike: f64, kind: &str| KiteInstrument {
+            instrument_token: strike as u64,
+            exchange_token: 0,
+            tradingsymbol: symbol.to_string(),
+            name: "NIFTY".to_string(),
+            last_price: 0.0,
+            expiry: NaiveDate::from_ymd_opt(2024, 1, 25),
+            strike: Some(strike),
+            tick_size: 0.05,
+            lot_size: 50,
+            instrument_type: kind.to_string(),
+            segment: "NFO-OPT".to_string(),
+            exchange: crate::models::kite::KiteExchange::NFO,
+        };
+        let legs = [
+            leg("NIFTY24JAN21100CE", 21100.0, "CE"),
+            leg("NIFTY24JAN21000PE", 21000.0, "PE"),
+            leg("NIFTY24JAN21000CE", 21000.0, "CE"),
+        ];
+        let legs: Vec<&KiteInstrument> = legs.iter().collect();
+
+        let rows = build_rows(&legs, &HashMap::new(), Some(21050.0), 0.05);
+        assert_eq!(rows.len(), 2);
+        assert_eq!(rows[0].strike, 21000.0);
+        assert_eq!(rows[0].call.as_ref().unwrap().tradingsymbol, "NIFTY24JAN21000CE");
+        assert_eq!(rows[0].put.as_ref().unwrap().tradingsymbol, "NIFTY24JAN21000PE");
+        assert!(rows[1].put.is_none());
+        // Unquoted legs have no price to solve a volatility from
+        assert!(rows[1].call.as_ref().unwrap().iv.is_none());
+
+        assert_eq!(underlying_quote_key("NIFTY"), "NSE:NIFTY 50");
+        assert_eq!(underlying_quote_key("INFY"), "NSE:INFY");
+    }
+}
@@ -0,0 +1,306 @@
+use crate::api::kite_client::KiteApiClient;
+use crate::db::bulk::insert_rows;
+use crate::error::{HedgeXError, Result};
+use crate::models::kite::{KiteOrder, KiteTrade};
+use crate::services::broker_sync::spawn_periodic_sync;
+use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use crate::trading::session::session_date;
+use chrono::{DateTime, NaiveDate, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::HashSet;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing::debug;
+
+/// How often the day's order book and trade book are pulled from the broker
+const SYNC_INTERVAL: Duration = Duration::from_secs(30);
+
+/// An order from the broker's order book
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBookEntry {
+    #[serde(flatten)]
+    pub order: KiteOrder,
+    /// Whether HedgeX placed the order; manual orders placed in Kite directly are not
+    pub placed_by_hedgex: bool,
+}
+
+/// A day's orders and trades as the broker reported them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OrderBook {
+    pub date: NaiveDate,
+    pub orders: Vec<OrderBookEntry>,
+    pub trades: Vec<KiteTrade>,
+    /// Unset when no snapshot was taken that day
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// Keeps a daily snapshot of the broker's order book and trade book
+pub struct OrderBookService {
+    db_service: Arc<EnhancedDatabaseService>,
+}
+
+impl OrderBookService {
+    /// Create a new order book service
+    pub fn new(db_service: Arc<EnhancedDatabaseService>) -> Self {
+        Self { db_service }
+    }
+
+    /// Fetch today's orders and trades from the broker and store them as today's snapshot
+    pub async fn sync(&self, client: &dyn KiteApiClient) -> Result<OrderBook> {
+        let orders = client.get_orders().await?;
+        let trades = client.get_trades().await?;
+
+        self.store(session_date(Utc::now()), &orders, &trades).await
+    }
+
+    /// Replace a day's snapshot with the given orders and trades
+    pub async fn store(&self, date: NaiveDate, orders: &[KiteOrder], trades: &[KiteTrade]) -> Result<OrderBook> {
+        let now = Utc::now();
+        let mut tx = self.db_service.get_database().get_pool().begin().await?;
+
+        // The broker returns the whole day each time, so the snapshot is rewritten
+        sqlx::query("DELETE FROM order_book_orders WHERE session_date = ?")
+            .bind(date)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("DELETE FROM order_book_trades WHERE session_date = ?")
+            .bind(date)
+            .execute(&mut *tx)
+            .await?;
+
+        let order_rows = orders.iter()
+            .map(|order| Ok((order, to_json(order)?)))
+            .collect::<Result<Vec<_>>>()?;
+        insert_rows(
+            &mut tx,
+            "INSERT OR REPLACE INTO order_book_orders (session_date, order_id, tradingsymbol, order_timestamp, data, synced_at) ",
+            6,
+            &order_rows,
+            |values, (order, data)| {
+                values.push_bind(date)
+                    .push_bind(&order.order_id)
+                    .push_bind(&order.tradingsymbol)
+                    .push_bind(order.order_timestamp)
+                    .push_bind(data)
+                    .push_bind(now);
+            },
+        ).await?;
+
+        let trade_rows = trades.iter()
+            .map(|trade| Ok((trade, to_json(trade)?)))
+            .collect::<Result<Vec<_>>>()?;
+        insert_rows(
+            &mut tx,
+            "INSERT OR REPLACE INTO order_book_trades (session_date, trade_id, order_id, fill_timestamp, data, synced_at) ",
+            6,
+            &trade_rows,
+            |values, (trade, data)| {
+                values.push_bind(date)
+                    .push_bind(&trade.trade_id)
+                    .push_bind(&trade.order_id)
+                    .push_bind(trade.fill_timestamp)
+                    .push_bind(data)
+                    .push_bind(now);
+            },
+        ).await?;
+
+        tx.commit().await?;
+
+        debug!("Stored order book of {}: {} orders, {} trades", date, orders.len(), trades.len());
+        self.get_order_book(date).await
+    }
+
+    /// The stored snapshot of a day, newest orders first
+    pub async fn get_order_book(&self, date: NaiveDate) -> Result<OrderBook> {
+        let order_rows = sqlx::query(
+            "SELECT data, synced_at FROM order_book_orders WHERE session_date = ?
+             ORDER BY order_timestamp DESC, order_id DESC"
+        )
+        .bind(date)
+        .fetch_all(self.db_service.get_database().get_pool())
+        .await?;
+
+        let trade_rows = sqlx::query(
+            "SELECT data, synced_at FROM order_book_trades WHERE session_date = ?
+             ORDER BY fill_timestamp DESC, trade_id DESC"
+        )
+        .bind(date)
+        .fetch_all(self.db_service.get_database().get_pool())
+        .await?;
+
+        let orders: Vec<KiteOrder> = order_rows.iter()
+            .map(|row| from_json(&row.get::<String, _>("data")))
+            .collect::<Result<_>>()?;
+        let trades: Vec<KiteTrade> = trade_rows.iter()
+            .map(|row| from_json(&row.get::<String, _>("data")))
+            .collect::<Result<_>>()?;
+
+        let hedgex_orders = self.hedgex_order_ids().await?;
+        let synced_at = order_rows.iter()
+            .chain(trade_rows.iter())
+            .map(|row| row.get::<DateTime<Utc>, _>("synced_at"))
+            .max();
+
+        Ok(OrderBook {
+            date,
+            orders: orders.into_iter()
+                .map(|order| OrderBookEntry {
+                    placed_by_hedgex: hedgex_orders.contains(&order.order_id),
+                    order,
+                })
+                .collect(),
+            trades,
+            synced_at,
+        })
+    }
+
+    /// Days with a stored snapshot, most recent first
+    pub async fn get_snapshot_dates(&self) -> Result<Vec<NaiveDate>> {
+        let rows = sqlx::query(
+            "SELECT session_date FROM order_book_orders
+             UNION SELECT session_date FROM order_book_trades
+             ORDER BY session_date DESC"
+        )
+        .fetch_all(self.db_service.get_database().get_pool())
+        .await?;
+
+        Ok(rows.iter().map(|row| row.get("session_date")).collect())
+    }
+
+    /// Broker order IDs of the orders HedgeX placed
+    async fn hedgex_order_ids(&self) -> Result<HashSet<String>> {
+        let rows = sqlx::query("SELECT order_id FROM trades WHERE order_id IS NOT NULL")
+            .fetch_all(self.db_service.get_database().get_pool())
+            .await?;
+
+        Ok(rows.iter().map(|row| row.get("order_id")).collect())
+    }
+
+    /// Snapshot the day's order book and trade book periodically in the background
+    pub fn start_periodic_sync(self: Arc<Self>, client: Arc<dyn KiteApiClient + Send + Sync>) {
+        spawn_periodic_sync("order book sync", SYNC_INTERVAL, client, move |client| {
+            let service = Arc::clone(&self);
+            async move { service.sync(client.as_ref()).await.map(|_| ()) }
+        });
+    }
+}
+
+fn to_json<T: Serialize>(value: &T) -> Result<String> {
+    serde_json::to_string(value)
+        .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))
+}
+
+fn from_json<T: for<'de> Deserialize<'de>>(data: &str) -> Result<T> {
+    serde_json::from_str(data)
+        .map_err(|e| HedgeXError::InternalError(format!("JSON deserialization failed: {}", e)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::kite::{
+        KiteExchange, KiteOrderStatus, KiteOrderType, KiteOrderVariety, KiteProduct,
+        KiteTransactionType, KiteValidity,
+    };
+    use chrono::Duration as ChronoDuration;
+    use tempfile::tempdir;
+
+    async fn setup_test_service() -> (OrderBookService, Arc<EnhancedDatabaseService>, tempfile::TempDir) {
+        let temp_dir = tempdir().unwrap();
+        let db_service = Arc::new(
+            EnhancedDatabaseService::new(temp_dir.path(), "test_password").await.unwrap()
+        );
+
+        sqlx::query(include_str!("../../migrations/20250821_add_order_book_snapshots.sql"))
+            .execute(db_service.get_database().get_pool())
+            .await
+            .unwrap();
+        sqlx::query("CREATE TABLE IF NOT EXISTS trades (id TEXT PRIMARY KEY, order_id TEXT)")
+            .execute(db_service.get_database().get_pool())
+            .await
+            .unwrap();
+
+        (OrderBookService::new(Arc::clone(&db_service)), db_service, temp_dir)
+    }
+
+    fn order(order_id: &str, minutes: i64) -> KiteOrder {
+        KiteOrder {
+            order_id: order_id.to_string(),
+            exchange_order_id: None,
+            parent_order_id: None,
+            status: KiteOrderStatus::Complete,
+            status_message: None,
+            order_timestamp: Some(Utc::now() + ChronoDuration::minutes(minutes)),
+            exchange_update_timestamp: None,
+            exchange_timestamp: None,
+            variety: KiteOrderVariety::Regular,
+            exchange: KiteExchange::NSE,
+            tradingsymbol: "INFY".to_string(),
+            instrument_token: 408065,
+            transaction_type: KiteTransactionType::Buy,
+            order_type: KiteOrderType::Market,
+            product: KiteProduct::MIS,
+            validity: KiteValidity::Day,
+            price: 0.0,
+            trigger_price: 0.0,
+            average_price: 1500.0,
+            filled_quantity: 10,
+            pending_quantity: 0,
+            cancelled_quantity: 0,
+            disclosed_quantity: 0,
+            market_protection: false,
+            tag: None,
+            tags: None,
+        }
+    }
+
+    fn trade(trade_id: &str, order_id: &str) -> KiteTrade {
+        KiteTrade {
+            trade_id: trade_id.to_string(),
+            order_id: order_id.to_string(),
+            exchange_order_id: None,
+            tradingsymbol: "INFY".to_string(),
+            exchange: KiteExchange::NSE,
+            instrument_token: 408065,
+            product: KiteProduct::MIS,
+            average_price: 1500.0,
+            quantity: 10,
+            transaction_type: KiteTransactionType::Buy,
+            fill_timestamp: Some(Utc::now()),
+            exchange_timestamp: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_snapshots_are_kept_per_day_and_flag_manual_orders() {
+        let (service, db_service, _dir) = setup_test_service().await;
+        sqlx::query("INSERT INTO trades (id, order_id) VALUES ('t1', 'ORD1')")
+            .execute(db_service.get_database().get_pool())
+            .await
+            .unwrap();
+
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 29).unwrap();
+        let tuesday = NaiveDate::from_ymd_opt(2024, 1, 30).unwrap();
+        service.store(monday, &[order("ORD0", 0)], &[]).await.unwrap();
+
+        let book = service.store(tuesday, &[order("ORD1", 0), order("ORD2", 5)], &[trade("TR1", "ORD1")]).await.unwrap();
+        assert_eq!(book.orders.len(), 2);
+        // Newest first; ORD2 was placed in Kite directly
+        assert_eq!(book.orders[0].order.order_id, "ORD2");
+        assert!(!book.orders[0].placed_by_hedgex);
+        assert!(book.orders[1].placed_by_hedgex);
+        assert_eq!(book.trades[0].order_id, "ORD1");
+        assert!(book.synced_at.is_some());
+
+        // Re-syncing a day replaces only that day's snapshot
+        let book = service.store(tuesday, &[order("ORD1", 0)], &[]).await.unwrap();
+        assert_eq!(book.orders.len(), 1);
+        assert!(book.trades.is_empty());
+        assert_eq!(service.get_order_book(monday).await.unwrap().orders.len(), 1);
+
+        assert_eq!(service.get_snapshot_dates().await.unwrap(), vec![tuesday, monday]);
+        let empty = service.get_order_book(NaiveDate::from_ymd_opt(2024, 1, 31).unwrap()).await.unwrap();
+        assert!(empty.orders.is_empty() && empty.synced_at.is_none());
+    }
+}
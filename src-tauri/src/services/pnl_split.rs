@@ -0,0 +1,137 @@
+use crate::error::Result;
+use crate::models::backtesting::CostModel;
+use crate::services::pnl_statement::{build_statement, OpenPosition, PnlStatement, PnlStatementService, StatementInput};
+use crate::trading::analytics_filter::TradeFilter;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// An open position marked to its live price
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MarkedPosition {
+    #[serde(flatten)]
+    pub position: OpenPosition,
+    /// Unset while no live price is known, leaving the position out of the unrealized P&L
+    pub mark_price: Option<f64>,
+    pub unrealized_pnl: f64,
+}
+
+/// P&L of closed quantity kept apart from the P&L still open in positions
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlSplit {
+    #[serde(flatten)]
+    pub filter: TradeFilter,
+    /// P&L of quantity closed within the range, against its average cost
+    pub realized_pnl: f64,
+    /// Estimated charges of the trades in the range
+    pub charges: f64,
+    pub net_realized_pnl: f64,
+    /// P&L of positions open at the end of the range, at live prices
+    pub unrealized_pnl: f64,
+    pub total_pnl: f64,
+    pub positions: Vec<MarkedPosition>,
+}
+
+impl PnlSplit {
+    /// Split a statement's P&L, marking the positions it leaves open to `prices`, by symbol
+    pub fn from_statement(filter: &TradeFilter, statement: &PnlStatement, prices: &HashMap<String, f64>) -> Self {
+        let positions = mark_positions(&statement.open_positions, prices);
+        let unrealized_pnl: f64 = positions.iter().map(|position| position.unrealized_pnl).sum();
+
+        Self {
+            filter: filter.clone(),
+            realized_pnl: statement.totals.realized_pnl,
+            charges: statement.totals.charges,
+            net_realized_pnl: statement.totals.net_pnl,
+            unrealized_pnl,
+            total_pnl: statement.totals.net_pnl + unrealized_pnl,
+            positions,
+        }
+    }
+}
+
+impl PnlStatementService {
+    /// Statement of the trades passing a filter, with the positions they leave open
+    ///
+    /// Strategy and symbol filters apply to the whole history, so a filtered position keeps
+    /// the average cost of its own trades.
+    pub async fn filtered_statement(&self, user_id: &str, filter: &TradeFilter) -> Result<PnlStatement> {
+        let trades: Vec<StatementInput> = self.load_trades(user_id, filter.to).await?
+            .into_iter()
+            .filter(|trade| filter.strategy_id.as_deref().is_none_or(|id| id == trade.strategy_id))
+            .filter(|trade| filter.symbol.as_deref().is_none_or(|symbol| symbol == trade.symbol))
+            .collect();
+
+        let mut statement = build_statement(&trades, filter.from.unwrap_or(NaiveDate::MIN), filter.to, &CostModel::default());
+        statement.user_id = user_id.to_string();
+        Ok(statement)
+    }
+
+    /// Realized P&L of the trades passing a filter and unrealized P&L of the positions they leave open
+    pub async fn pnl_split(&self, user_id: &str, filter: &TradeFilter, prices: &HashMap<String, f64>) -> Result<PnlSplit> {
+        let statement = self.filtered_statement(user_id, filter).await?;
+        Ok(PnlSplit::from_statement(filter, &statement, prices))
+    }
+}
+
+/// Mark open positions to live prices, by symbol
+pub fn mark_positions(positions: &[OpenPosition], prices: &HashMap<String, f64>) -> Vec<MarkedPosition> {
+    positions.iter()
+        .map(|position| {
+            let mark_price = prices.get(&position.symbol).copied();
+            MarkedPosition {
+                position: position.clone(),
+                mark_price,
+                unrealized_pnl: mark_price
+                    .map_or(0.0, |price| (price - position.average_price) * position.quantity as f64),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::trading::TradeType;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_open_positions_marked_to_live_prices() {
+        let input = |symbol: &str, trade_type, quantity, price, hour| StatementInput {
+            trade_id: format!("{}-{}", symbol, hour),
+            executed_at: Utc.with_ymd_and_hms(2024, 8, 5, hour, 0, 0).unwrap(),
+            symbol: symbol.to_string(),
+            exchange: "NSE".to_string(),
+            strategy_id: "s1".to_string(),
+            strategy_name: "S1".to_string(),
+            trade_type,
+            quantity,
+            price,
+        };
+        let trades = vec![
+            input("INFY", TradeType::Buy, 10, 1000.0, 4),
+            input("INFY", TradeType::Sell, 4, 1050.0, 5),
+            input("TCS", TradeType::Sell, 5, 3000.0, 5),
+            input("HDFC", TradeType::Buy, 1, 1500.0, 6),
+        ];
+        let date = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+        let statement = build_statement(&trades, date, date, &CostModel::disabled());
+
+        // Only the 4 sold were realized; the rest is still open
+        assert_eq!(statement.totals.realized_pnl, 200.0);
+        let prices = HashMap::from([("INFY".to_string(), 1020.0), ("TCS".to_string(), 2990.0)]);
+        let marked = mark_positions(&statement.open_positions, &prices);
+
+        let unrealized: Vec<(&str, i64, Option<f64>, f64)> = marked.iter()
+            .map(|m| (m.position.symbol.as_str(), m.position.quantity, m.mark_price, m.unrealized_pnl))
+            .collect();
+        assert_eq!(unrealized, vec![
+            ("HDFC", 1, None, 0.0),
+            ("INFY", 6, Some(1020.0), 120.0),
+            ("TCS", -5, Some(2990.0), 50.0),
+        ]);
+
+        let split = PnlSplit::from_statement(&TradeFilter::last_days(date, 1), &statement, &prices);
+        assert_eq!((split.realized_pnl, split.unrealized_pnl, split.total_pnl), (200.0, 170.0, 370.0));
+    }
+}
@@ -0,0 +1,481 @@
+use crate::error::{HedgeXError, Result};
+use crate::models::backtesting::CostModel;
+use crate::models::trading::TradeType;
+use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use crate::trading::session::{is_trading_day, session_date};
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+
+/// An executed trade with the P&L it realized
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatementTrade {
+    pub trade_id: String,
+    pub executed_at: DateTime<Utc>,
+    /// IST trading date
+    pub date: NaiveDate,
+    pub symbol: String,
+    pub exchange: String,
+    pub strategy_id: String,
+    pub strategy_name: String,
+    pub trade_type: TradeType,
+    pub quantity: i64,
+    pub price: f64,
+    pub value: f64,
+    /// P&L of the position quantity this trade closed, against its average cost
+    pub realized_pnl: f64,
+    /// Estimated brokerage, taxes and fees
+    pub charges: f64,
+    pub net_pnl: f64,
+}
+
+/// Totals of a group of trades
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PnlSubtotal {
+    pub trades: usize,
+    pub turnover: f64,
+    pub realized_pnl: f64,
+    pub charges: f64,
+    pub net_pnl: f64,
+}
+
+impl PnlSubtotal {
+    fn add(&mut self, trade: &StatementTrade) {
+        self.trades += 1;
+        self.turnover += trade.value;
+        self.realized_pnl += trade.realized_pnl;
+        self.charges += trade.charges;
+        self.net_pnl += trade.net_pnl;
+    }
+}
+
+/// A day's subtotal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DailyPnl {
+    pub date: NaiveDate,
+    #[serde(flatten)]
+    pub totals: PnlSubtotal,
+}
+
+/// A strategy's subtotal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyPnl {
+    pub strategy_id: String,
+    pub strategy_name: String,
+    #[serde(flatten)]
+    pub totals: PnlSubtotal,
+}
+
+/// Quantity of a symbol still open after the statement's last trade
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct OpenPosition {
+    pub symbol: String,
+    pub exchange: String,
+    /// Negative for a short position
+    pub quantity: i64,
+    pub average_price: f64,
+}
+
+/// Trades of a date range with their P&L, subtotalled by day and by strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlStatement {
+    pub user_id: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub trades: Vec<StatementTrade>,
+    pub days: Vec<DailyPnl>,
+    pub strategies: Vec<StrategyPnl>,
+    pub totals: PnlSubtotal,
+    /// Positions open at the end of the range, not yet part of the realized P&L
+    #[serde(default)]
+    pub open_positions: Vec<OpenPosition>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl PnlStatement {
+    /// The statement laid out as fixed-width text, for printing
+    pub fn to_text_lines(&self) -> Vec<String> {
+        let mut lines = vec![
+            format!("Period: {} to {}    Generated: {}", self.from, self.to, self.generated_at.format("%Y-%m-%d %H:%M UTC")),
+            String::new(),
+            "TRADES".to_string(),
+            format!(
+                "{:<10} {:<8} {:<16} {:<5} {:<18} {:<4} {:>8} {:>10} {:>14} {:>12} {:>10} {:>12}",
+                "Date", "Time", "Symbol", "Exch", "Strategy", "Side", "Qty", "Price", "Value", "Realized", "Charges", "Net"
+            ),
+        ];
+        for trade in &self.trades {
+            lines.push(format!(
+                "{:<10} {:<8} {:<16.16} {:<5.5} {:<18.18} {:<4} {:>8} {:>10.2} {:>14.2} {:>12.2} {:>10.2} {:>12.2}",
+                trade.date,
+                trade.executed_at.with_timezone(&chrono_tz::Asia::Kolkata).format("%H:%M:%S"),
+                trade.symbol,
+                trade.exchange,
+                trade.strategy_name,
+                if trade.trade_type == TradeType::Buy { "BUY" } else { "SELL" },
+                trade.quantity,
+                trade.price,
+                trade.value,
+                trade.realized_pnl,
+                trade.charges,
+                trade.net_pnl,
+            ));
+        }
+
+        let subtotal_header = format!(
+            "{:<30} {:>8} {:>16} {:>14} {:>12} {:>14}",
+            "", "Trades", "Turnover", "Realized", "Charges", "Net"
+        );
+        let subtotal_line = |label: &str, totals: &PnlSubtotal| {
+            format!(
+                "{:<30.30} {:>8} {:>16.2} {:>14.2} {:>12.2} {:>14.2}",
+                label, totals.trades, totals.turnover, totals.realized_pnl, totals.charges, totals.net_pnl
+            )
+        };
+
+        lines.extend([String::new(), "DAILY SUBTOTALS".to_string(), subtotal_header.clone()]);
+        lines.extend(self.days.iter().map(|day| subtotal_line(&day.date.to_string(), &day.totals)));
+        lines.extend([String::new(), "STRATEGY SUBTOTALS".to_string(), subtotal_header]);
+        lines.extend(self.strategies.iter().map(|strategy| subtotal_line(&strategy.strategy_name, &strategy.totals)));
+        lines.extend([String::new(), subtotal_line("TOTAL", &self.totals)]);
+        lines
+    }
+}
+
+/// One cell of a P&L calendar
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CalendarDay {
+    pub date: NaiveDate,
+    /// Whether the exchange was open, so closed days can be drawn apart from quiet ones
+    pub is_trading_day: bool,
+    pub trades: usize,
+    pub realized_pnl: f64,
+    pub net_pnl: f64,
+    /// Share of the day's closing trades that made money; unset when nothing was closed
+    pub win_rate: Option<f64>,
+}
+
+/// A month of daily P&L for a calendar heatmap
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PnlCalendar {
+    pub year: i32,
+    pub month: u32,
+    /// Every day of the month, in order
+    pub days: Vec<CalendarDay>,
+    pub realized_pnl: f64,
+    pub net_pnl: f64,
+    /// Largest absolute day's realized P&L, to scale the heatmap's colours
+    pub max_abs_pnl: f64,
+}
+
+impl PnlCalendar {
+    /// Lay a statement covering the month out as one cell per day
+    pub fn from_statement(year: i32, month: u32, statement: &PnlStatement) -> Self {
+        let days: Vec<CalendarDay> = month_days(year, month)
+            .map(|date| {
+                let trades: Vec<&StatementTrade> = statement.trades.iter().filter(|trade| trade.date == date).collect();
+                // Only trades that closed some quantity have an outcome
+                let closing: Vec<&&StatementTrade> = trades.iter().filter(|trade| trade.realized_pnl != 0.0).collect();
+                let winners = closing.iter().filter(|trade| trade.realized_pnl > 0.0).count();
+
+                CalendarDay {
+                    date,
+                    is_trading_day: is_trading_day(date),
+                    trades: trades.len(),
+                    realized_pnl: trades.iter().map(|trade| trade.realized_pnl).sum(),
+                    net_pnl: trades.iter().map(|trade| trade.net_pnl).sum(),
+                    win_rate: (!closing.is_empty()).then(|| winners as f64 / closing.len() as f64),
+                }
+            })
+            .collect();
+
+        Self {
+            year,
+            month,
+            realized_pnl: days.iter().map(|day| day.realized_pnl).sum(),
+            net_pnl: days.iter().map(|day| day.net_pnl).sum(),
+            max_abs_pnl: days.iter().map(|day| day.realized_pnl.abs()).fold(0.0, f64::max),
+            days,
+        }
+    }
+}
+
+/// First and last day of a calendar month, unset for an invalid month
+pub fn month_bounds(year: i32, month: u32) -> Option<(NaiveDate, NaiveDate)> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1)?;
+    let last = first.checked_add_months(Months::new(1))?.pred_opt()?;
+    Some((first, last))
+}
+
+fn month_days(year: i32, month: u32) -> impl Iterator<Item = NaiveDate> {
+    let first = NaiveDate::from_ymd_opt(year, month, 1);
+    first.into_iter().flat_map(|first| first.iter_days()).take_while(move |date| date.month() == month)
+}
+
+/// An executed trade as the statement reads it, oldest first
+#[derive(Debug, Clone)]
+pub struct StatementInput {
+    pub trade_id: String,
+    pub executed_at: DateTime<Utc>,
+    pub symbol: String,
+    pub exchange: String,
+    pub strategy_id: String,
+    pub strategy_name: String,
+    pub trade_type: TradeType,
+    pub quantity: i64,
+    pub price: f64,
+}
+
+/// Builds P&L statements from the user's executed trades
+pub struct PnlStatementService {
+    db_service: Arc<EnhancedDatabaseService>,
+}
+
+impl PnlStatementService {
+    /// Create a new P&L statement service
+    pub fn new(db_service: Arc<EnhancedDatabaseService>) -> Self {
+        Self { db_service }
+    }
+
+    /// Statement of trades executed on IST dates `from` to `to`, inclusive
+    pub async fn generate(&self, user_id: &str, from: NaiveDate, to: NaiveDate) -> Result<PnlStatement> {
+        if from > to {
+            return Err(HedgeXError::ValidationError("Statement start date is after its end date".to_string()));
+        }
+
+        // Trades before the range are read too, as they set the cost of positions closed in it
+        let trades = self.load_trades(user_id, to).await?;
+
+        let mut statement = build_statement(&trades, from, to, &CostModel::default());
+        statement.user_id = user_id.to_string();
+        Ok(statement)
+    }
+
+    /// A user's executed trades up to the end of an IST date, oldest first
+    pub async fn load_trades(&self, user_id: &str, until: NaiveDate) -> Result<Vec<StatementInput>> {
+        let rows = sqlx::query(
+            "SELECT t.id, t.executed_at, t.symbol, t.exchange, t.strategy_id, sp.name as strategy_name,
+                    t.trade_type, t.quantity, t.price
+             FROM trades t
+             LEFT JOIN strategy_params sp ON t.strategy_id = sp.id
+             WHERE t.user_id = ? AND t.status = 'Executed' AND date(t.executed_at, '+330 minutes') <= ?
+             ORDER BY t.executed_at ASC"
+        )
+            .bind(user_id)
+            .bind(until.format("%Y-%m-%d").to_string())
+            .fetch_all(self.db_service.get_database().get_read_pool())
+            .await?;
+
+        Ok(rows.iter()
+            .map(|row| StatementInput {
+                trade_id: row.get("id"),
+                executed_at: row.get("executed_at"),
+                symbol: row.get("symbol"),
+                exchange: row.get("exchange"),
+                strategy_id: row.get("strategy_id"),
+                strategy_name: row.get::<Option<String>, _>("strategy_name").unwrap_or_else(|| "Unknown".to_string()),
+                trade_type: if row.get::<String, _>("trade_type") == "Sell" { TradeType::Sell } else { TradeType::Buy },
+                quantity: row.get("quantity"),
+                price: row.get("price"),
+            })
+            .collect())
+    }
+
+    /// Daily P&L of a calendar month
+    pub async fn calendar(&self, user_id: &str, year: i32, month: u32) -> Result<PnlCalendar> {
+        let (from, to) = month_bounds(year, month)
+            .ok_or_else(|| HedgeXError::ValidationError(format!("Invalid month: {}-{}", year, month)))?;
+        let statement = self.generate(user_id, from, to).await?;
+        Ok(PnlCalendar::from_statement(year, month, &statement))
+    }
+}
+
+/// Realize each trade's P&L against the average cost of its symbol's open position
+///
+/// A trade against the position's direction closes up to the open quantity at the
+/// average price; any excess opens a position the other way at the trade's price.
+pub fn build_statement(trades: &[StatementInput], from: NaiveDate, to: NaiveDate, costs: &CostModel) -> PnlStatement {
+    // Signed open quantity and average price of each symbol
+    let mut positions: HashMap<(&str, &str), (i64, f64)> = HashMap::new();
+    let mut rows = Vec::new();
+
+    for trade in trades {
+        let signed = match trade.trade_type {
+            TradeType::Buy => trade.quantity,
+            TradeType::Sell => -trade.quantity,
+        };
+        let (open, average) = positions.entry((trade.exchange.as_str(), trade.symbol.as_str())).or_insert((0, 0.0));
+
+        let mut realized_pnl = 0.0;
+        if *open != 0 && open.signum() != signed.signum() {
+            let closed = open.abs().min(trade.quantity);
+            realized_pnl = (trade.price - *average) * closed as f64 * open.signum() as f64;
+        }
+        let next = *open + signed;
+        if next == 0 {
+            *average = 0.0;
+        } else if open.signum() != next.signum() {
+            // Flat or flipped: the remainder was opened at this trade's price
+            *average = trade.price;
+        } else if open.signum() == signed.signum() {
+            *average = (*average * open.abs() as f64 + trade.price * trade.quantity as f64) / next.abs() as f64;
+        }
+        *open = next;
+
+        let date = session_date(trade.executed_at);
+        if date < from || date > to {
+            continue;
+        }
+
+        let price = Decimal::from_f64(trade.price).unwrap_or_default();
+        let charges = costs.charges(trade.trade_type, price, trade.quantity as i32).to_f64().unwrap_or(0.0);
+        rows.push(StatementTrade {
+            trade_id: trade.trade_id.clone(),
+            executed_at: trade.executed_at,
+            date,
+            symbol: trade.symbol.clone(),
+            exchange: trade.exchange.clone(),
+            strategy_id: trade.strategy_id.clone(),
+            strategy_name: trade.strategy_name.clone(),
+            trade_type: trade.trade_type,
+            quantity: trade.quantity,
+            price: trade.price,
+            value: trade.price * trade.quantity as f64,
+            realized_pnl,
+            charges,
+            net_pnl: realized_pnl - charges,
+        });
+    }
+
+    let mut open_positions: Vec<OpenPosition> = positions.into_iter()
+        .filter(|(_, (open, _))| *open != 0)
+        .map(|((exchange, symbol), (quantity, average_price))| OpenPosition {
+            symbol: symbol.to_string(),
+            exchange: exchange.to_string(),
+            quantity,
+            average_price,
+        })
+        .collect();
+    open_positions.sort_by(|a, b| (&a.exchange, &a.symbol).cmp(&(&b.exchange, &b.symbol)));
+
+    let mut days: BTreeMap<NaiveDate, PnlSubtotal> = BTreeMap::new();
+    let mut strategies: BTreeMap<(&str, &str), PnlSubtotal> = BTreeMap::new();
+    let mut totals = PnlSubtotal::default();
+    for row in &rows {
+        days.entry(row.date).or_default().add(row);
+        strategies.entry((row.strategy_name.as_str(), row.strategy_id.as_str())).or_default().add(row);
+        totals.add(row);
+    }
+
+    PnlStatement {
+        user_id: String::new(),
+        from,
+        to,
+        days: days.into_iter().map(|(date, totals)| DailyPnl { date, totals }).collect(),
+        strategies: strategies.into_iter()
+            .map(|((strategy_name, strategy_id), totals)| StrategyPnl {
+                strategy_id: strategy_id.to_string(),
+                strategy_name: strategy_name.to_string(),
+                totals,
+            })
+            .collect(),
+        totals,
+        trades: rows,
+        open_positions,
+        generated_at: Utc::now(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn input(id: &str, strategy: &str, trade_type: TradeType, quantity: i64, price: f64, day: u32, hour: u32) -> StatementInput {
+        StatementInput {
+            trade_id: id.to_string(),
+            executed_at: Utc.with_ymd_and_hms(2024, 8, day, hour, 0, 0).unwrap(),
+            symbol: "INFY".to_string(),
+            exchange: "NSE".to_string(),
+            strategy_id: strategy.to_string(),
+            strategy_name: strategy.to_uppercase(),
+            trade_type,
+            quantity,
+            price,
+        }
+    }
+
+    #[test]
+    fn test_build_statement_realizes_against_average_cost() {
+        let trades = vec![
+            // Before the range, but sets the average cost to 1010
+            input("t1", "momentum", TradeType::Buy, 10, 1000.0, 2, 4),
+            input("t2", "momentum", TradeType::Buy, 10, 1020.0, 2, 5),
+            input("t3", "momentum", TradeType::Sell, 15, 1030.0, 5, 4),
+            // Closes the last 5 and opens a short of 5 at 1000
+            input("t4", "reversal", TradeType::Sell, 10, 1000.0, 6, 4),
+            input("t5", "reversal", TradeType::Buy, 5, 990.0, 6, 6),
+        ];
+        let from = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 8, 6).unwrap();
+        let statement = build_statement(&trades, from, to, &CostModel::disabled());
+
+        let realized: Vec<(&str, f64)> = statement.trades.iter()
+            .map(|trade| (trade.trade_id.as_str(), trade.realized_pnl))
+            .collect();
+        assert_eq!(realized, vec![("t3", 300.0), ("t4", -50.0), ("t5", 50.0)]);
+
+        assert_eq!(statement.days.len(), 2);
+        assert_eq!(statement.days[1].totals.trades, 2);
+        assert_eq!(statement.days[1].totals.realized_pnl, 0.0);
+
+        assert_eq!(statement.strategies[0].strategy_name, "MOMENTUM");
+        assert_eq!(statement.strategies[0].totals.realized_pnl, 300.0);
+        assert_eq!(statement.totals.realized_pnl, 300.0);
+        assert_eq!(statement.totals.turnover, 15_450.0 + 10_000.0 + 4950.0);
+        // The short opened by t4 was covered by t5
+        assert!(statement.open_positions.is_empty());
+
+        let text = statement.to_text_lines();
+        assert!(text.iter().any(|line| line.starts_with("TOTAL") && line.contains("300.00")));
+    }
+
+    #[test]
+    fn test_calendar_covers_every_day() {
+        let trades = vec![
+            input("t1", "momentum", TradeType::Buy, 10, 1000.0, 5, 4),
+            input("t2", "momentum", TradeType::Sell, 5, 1010.0, 5, 5),
+            input("t3", "momentum", TradeType::Sell, 5, 990.0, 5, 6),
+            input("t4", "momentum", TradeType::Buy, 10, 1000.0, 7, 4),
+        ];
+        let (from, to) = month_bounds(2024, 8).unwrap();
+        let statement = build_statement(&trades, from, to, &CostModel::disabled());
+        let calendar = PnlCalendar::from_statement(2024, 8, &statement);
+
+        assert_eq!(calendar.days.len(), 31);
+        let day = &calendar.days[4];
+        assert_eq!(day.date, NaiveDate::from_ymd_opt(2024, 8, 5).unwrap());
+        assert_eq!((day.trades, day.realized_pnl, day.win_rate), (3, 0.0, Some(0.5)));
+        assert_eq!((calendar.days[6].trades, calendar.days[6].win_rate), (1, None));
+        // 3 August 2024 was a Saturday
+        assert!(!calendar.days[2].is_trading_day);
+        assert_eq!(calendar.max_abs_pnl, 0.0);
+
+        assert_eq!(month_bounds(2024, 2).unwrap().1, NaiveDate::from_ymd_opt(2024, 2, 29).unwrap());
+        assert!(month_bounds(2024, 13).is_none());
+    }
+
+    #[test]
+    fn test_build_statement_estimates_charges() {
+        let trades = vec![input("t1", "momentum", TradeType::Buy, 10, 1000.0, 5, 4)];
+        let day = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+        let statement = build_statement(&trades, day, day, &CostModel::default());
+
+        assert!(statement.totals.charges > 0.0);
+        assert_eq!(statement.totals.net_pnl, -statement.totals.charges);
+    }
+}
@@ -0,0 +1,389 @@
+use crate::api::kite_client::KiteApiClient;
+use crate::error::Result;
+use crate::models::kite::{KiteHolding, KitePositionItem};
+use crate::models::trading::{Position, TradeType};
+use crate::services::broker_sync::spawn_periodic_sync;
+use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use crate::services::gtt::GttTrigger;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::{debug, info};
+
+/// How often positions and holdings are pulled from the broker
+const SYNC_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A net position as reported by the broker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokerPosition {
+    pub exchange: String,
+    pub tradingsymbol: String,
+    pub product: String,
+    /// Signed quantity; negative for a short position, zero once closed for the day
+    pub quantity: i32,
+    pub average_price: f64,
+    pub last_price: f64,
+    pub realized_pnl: f64,
+    pub unrealized_pnl: f64,
+    pub synced_at: DateTime<Utc>,
+    /// Active GTT triggers on the symbol, attached by `GttService::attach`
+    #[serde(default)]
+    pub gtts: Vec<GttTrigger>,
+}
+
+/// A delivery holding as reported by the broker
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokerHolding {
+    pub exchange: String,
+    pub tradingsymbol: String,
+    pub isin: String,
+    pub quantity: i32,
+    /// Bought but not yet settled
+    pub t1_quantity: i32,
+    pub average_price: f64,
+    pub last_price: f64,
+    pub close_price: f64,
+    pub pnl: f64,
+    pub day_change: f64,
+    pub synced_at: DateTime<Utc>,
+    /// Active GTT triggers on the symbol, attached by `GttService::attach`
+    #[serde(default)]
+    pub gtts: Vec<GttTrigger>,
+}
+
+/// Everything the broker holds for the account at the last sync
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrokerPortfolio {
+    pub positions: Vec<BrokerPosition>,
+    pub holdings: Vec<BrokerHolding>,
+    /// Unrealized P&L of open positions, whoever opened them
+    pub unrealized_pnl: f64,
+    /// P&L of holdings against their average price
+    pub holdings_pnl: f64,
+    pub synced_at: Option<DateTime<Utc>>,
+}
+
+/// A symbol whose quantity at the broker differs from what HedgeX tracks
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PositionMismatch {
+    pub exchange: String,
+    pub symbol: String,
+    pub broker_quantity: i32,
+    pub local_quantity: i32,
+}
+
+/// Keeps a local copy of the broker's positions and holdings
+pub struct PortfolioSyncService {
+    db_service: Arc<EnhancedDatabaseService>,
+    last_sync: Arc<RwLock<Option<DateTime<Utc>>>>,
+}
+
+impl PortfolioSyncService {
+    /// Create a new portfolio sync service
+    pub fn new(db_service: Arc<EnhancedDatabaseService>) -> Self {
+        Self {
+            db_service,
+            last_sync: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    /// Get the time of the last successful sync in this run
+    pub async fn get_last_sync(&self) -> Option<DateTime<Utc>> {
+        *self.last_sync.read().await
+    }
+
+    /// Fetch net positions and holdings from the broker and replace the stored copy
+    pub async fn sync(&self, client: &dyn KiteApiClient) -> Result<BrokerPortfolio> {
+        let positions = client.get_positions().await?;
+        let holdings = client.get_holdings().await?;
+
+        self.store(&positions.net, &holdings).await
+    }
+
+    /// Replace the stored positions and holdings with the given ones
+    pub async fn store(&self, positions: &[KitePositionItem], holdings: &[KiteHolding]) -> Result<BrokerPortfolio> {
+        let now = Utc::now();
+        let mut tx = self.db_service.get_database().get_pool().begin().await?;
+
+        // Anything missing from the broker's lists is no longer held
+        sqlx::query("DELETE FROM broker_positions").execute(&mut *tx).await?;
+        sqlx::query("DELETE FROM broker_holdings").execute(&mut *tx).await?;
+
+        for position in positions {
+            sqlx::query(
+                "INSERT OR REPLACE INTO broker_positions (
+                    exchange, tradingsymbol, product, quantity, average_price, last_price,
+                    realized_pnl, unrealized_pnl, synced_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            )
+            .bind(position.exchange.to_string())
+            .bind(&position.tradingsymbol)
+            .bind(position.product.to_string())
+            .bind(position.quantity)
+            .bind(position.average_price)
+            .bind(position.last_price)
+            .bind(position.realized_pnl)
+            .bind(position.unrealized_pnl)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        for holding in holdings {
+            sqlx::query(
+                "INSERT OR REPLACE INTO broker_holdings (
+                    exchange, tradingsymbol, isin, quantity, t1_quantity, average_price,
+                    last_price, close_price, pnl, day_change, synced_at
+                ) VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"
+            )
+            .bind(holding.exchange.to_string())
+            .bind(&holding.tradingsymbol)
+            .bind(&holding.isin)
+            .bind(holding.quantity)
+            .bind(holding.t1_quantity)
+            .bind(holding.average_price)
+            .bind(holding.last_price)
+            .bind(holding.close_price)
+            .bind(holding.pnl)
+            .bind(holding.day_change)
+            .bind(now)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        *self.last_sync.write().await = Some(now);
+
+        debug!("Stored {} broker positions and {} holdings", positions.len(), holdings.len());
+        self.get_portfolio().await
+    }
+
+    /// The positions and holdings stored by the last sync
+    pub async fn get_portfolio(&self) -> Result<BrokerPortfolio> {
+        let positions: Vec<BrokerPosition> = sqlx::query(
+            "SELECT * FROM broker_positions ORDER BY exchange, tradingsymbol, product"
+        )
+        .fetch_all(self.db_service.get_database().get_pool())
+        .await?
+        .iter()
+        .map(|row| BrokerPosition {
+            exchange: row.get("exchange"),
+            tradingsymbol: row.get("tradingsymbol"),
+            product: row.get("product"),
+            quantity: row.get("quantity"),
+            average_price: row.get("average_price"),
+            last_price: row.get("last_price"),
+            realized_pnl: row.get("realized_pnl"),
+            unrealized_pnl: row.get("unrealized_pnl"),
+            synced_at: row.get("synced_at"),
+            gtts: Vec::new(),
+        })
+        .collect();
+
+        let holdings: Vec<BrokerHolding> = sqlx::query(
+            "SELECT * FROM broker_holdings ORDER BY exchange, tradingsymbol"
+        )
+        .fetch_all(self.db_service.get_database().get_pool())
+        .await?
+        .iter()
+        .map(|row| BrokerHolding {
+            exchange: row.get("exchange"),
+            tradingsymbol: row.get("tradingsymbol"),
+            isin: row.get("isin"),
+            quantity: row.get("quantity"),
+            t1_quantity: row.get("t1_quantity"),
+            average_price: row.get("average_price"),
+            last_price: row.get("last_price"),
+            close_price: row.get("close_price"),
+            pnl: row.get("pnl"),
+            day_change: row.get("day_change"),
+            synced_at: row.get("synced_at"),
+            gtts: Vec::new(),
+        })
+        .collect();
+
+        let synced_at = positions.iter().map(|p| p.synced_at)
+            .chain(holdings.iter().map(|h| h.synced_at))
+            .max()
+            .or(*self.last_sync.read().await);
+
+        Ok(BrokerPortfolio {
+            unrealized_pnl: positions.iter().map(|p| p.unrealized_pnl).sum(),
+            holdings_pnl: holdings.iter().map(|h| h.pnl).sum(),
+            positions,
+            holdings,
+            synced_at,
+        })
+    }
+
+    /// Symbols whose broker net quantity differs from the locally tracked positions
+    ///
+    /// Broker quantities are summed across products; a local short counts as negative.
+    pub fn reconcile(broker: &[BrokerPosition], local: &[Position]) -> Vec<PositionMismatch> {
+        let mut quantities: BTreeMap<(String, String), (i32, i32)> = BTreeMap::new();
+
+        for position in broker {
+            let key = (position.exchange.clone(), position.tradingsymbol.clone());
+            quantities.entry(key).or_default().0 += position.quantity;
+        }
+        for position in local {
+            let signed = match position.trade_type {
+                TradeType::Buy => position.quantity,
+                TradeType::Sell => -position.quantity,
+            };
+            let key = (position.exchange.clone(), position.symbol.clone());
+            quantities.entry(key).or_default().1 += signed;
+        }
+
+        quantities
+            .into_iter()
+            .filter(|(_, (broker_quantity, local_quantity))| broker_quantity != local_quantity)
+            .map(|((exchange, symbol), (broker_quantity, local_quantity))| PositionMismatch {
+                exchange,
+                symbol,
+                broker_quantity,
+                local_quantity,
+            })
+            .collect()
+    }
+
+    /// Sync positions and holdings from the broker periodically in the background
+    pub fn start_periodic_sync(self: Arc<Self>, client: Arc<dyn KiteApiClient + Send + Sync>) {
+        spawn_periodic_sync("broker portfolio sync", SYNC_INTERVAL, client, move |client| {
+            let service = Arc::clone(&self);
+            async move {
+                let portfolio = service.sync(client.as_ref()).await?;
+                info!(
+                    "Synced {} broker positions and {} holdings",
+                    portfolio.positions.len(), portfolio.holdings.len()
+                );
+                Ok(())
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::kite::{KiteExchange, KiteProduct};
+    use rust_decimal::Decimal;
+    use tempfile::tempdir;
+
+    async fn setup_test_service() -> (PortfolioSyncService, tempfile::TempDir) {
+        let temp_dir = tempdir().unwrap();
+        let db_service = Arc::new(
+            EnhancedDatabaseService::new(temp_dir.path(), "test_password").await.unwrap()
+        );
+
+        sqlx::query(include_str!("../../migrations/20250820_add_broker_portfolio.sql"))
+            .execute(db_service.get_database().get_pool())
+            .await
+            .unwrap();
+
+        (PortfolioSyncService::new(db_service), temp_dir)
+    }
+
+    fn position(symbol: &str, product: KiteProduct, quantity: i32, unrealized_pnl: f64) -> KitePositionItem {
+        KitePositionItem {
+            tradingsymbol: symbol.to_string(),
+            exchange: KiteExchange::NSE,
+            product,
+            quantity,
+            average_price: 1500.0,
+            last_price: 1510.0,
+            pnl: unrealized_pnl,
+            realized_pnl: 0.0,
+            unrealized_pnl,
+            value: 0.0,
+            buy_quantity: quantity.max(0),
+            buy_price: 1500.0,
+            buy_value: 0.0,
+            sell_quantity: (-quantity).max(0),
+            sell_price: 0.0,
+            sell_value: 0.0,
+            day_buy_quantity: 0,
+            day_sell_quantity: 0,
+            day_buy_price: 0.0,
+            day_sell_price: 0.0,
+            day_buy_value: 0.0,
+            day_sell_value: 0.0,
+        }
+    }
+
+    fn holding(symbol: &str, quantity: i32, pnl: f64) -> KiteHolding {
+        KiteHolding {
+            tradingsymbol: symbol.to_string(),
+            exchange: KiteExchange::NSE,
+            instrument_token: 408065,
+            isin: "INE009A01021".to_string(),
+            product: KiteProduct::CNC,
+            price: 0.0,
+            quantity,
+            used_quantity: 0,
+            t1_quantity: 0,
+            realised_quantity: quantity,
+            authorised_quantity: 0,
+            collateral_quantity: 0,
+            collateral_type: None,
+            average_price: 1400.0,
+            last_price: 1510.0,
+            close_price: 1500.0,
+            pnl,
+            day_change: 10.0,
+            day_change_percentage: 0.67,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_store_replaces_the_broker_portfolio() {
+        let (service, _dir) = setup_test_service().await;
+        assert!(service.get_portfolio().await.unwrap().synced_at.is_none());
+
+        let portfolio = service.store(
+            &[position("INFY", KiteProduct::MIS, 10, 100.0), position("TCS", KiteProduct::NRML, -5, -50.0)],
+            &[holding("INFY", 20, 2200.0)],
+        ).await.unwrap();
+        assert_eq!(portfolio.positions.len(), 2);
+        assert_eq!(portfolio.positions[1].quantity, -5);
+        assert_eq!(portfolio.positions[0].product, "MIS");
+        assert!((portfolio.unrealized_pnl - 50.0).abs() < 1e-9);
+        assert!((portfolio.holdings_pnl - 2200.0).abs() < 1e-9);
+        assert!(service.get_last_sync().await.is_some());
+
+        // A position the broker no longer reports is gone after the next sync
+        let portfolio = service.store(&[position("INFY", KiteProduct::MIS, 10, 120.0)], &[]).await.unwrap();
+        assert_eq!(portfolio.positions.len(), 1);
+        assert!(portfolio.holdings.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_reconcile_reports_quantity_differences() {
+        let (service, _dir) = setup_test_service().await;
+        let portfolio = service.store(
+            &[
+                position("INFY", KiteProduct::MIS, 10, 0.0),
+                position("INFY", KiteProduct::CNC, 5, 0.0),
+                position("TCS", KiteProduct::MIS, -5, 0.0),
+                position("WIPRO", KiteProduct::MIS, 8, 0.0),
+            ],
+            &[],
+        ).await.unwrap();
+
+        let local = [
+            Position::new("INFY", "NSE", 15, Decimal::from(1500), TradeType::Buy),
+            Position::new("TCS", "NSE", 5, Decimal::from(3800), TradeType::Sell),
+            Position::new("HDFC", "NSE", 3, Decimal::from(1600), TradeType::Buy),
+        ];
+
+        let mismatches = PortfolioSyncService::reconcile(&portfolio.positions, &local);
+        assert_eq!(mismatches, vec![
+            PositionMismatch { exchange: "NSE".to_string(), symbol: "HDFC".to_string(), broker_quantity: 0, local_quantity: 3 },
+            PositionMismatch { exchange: "NSE".to_string(), symbol: "WIPRO".to_string(), broker_quantity: 8, local_quantity: 0 },
+        ]);
+    }
+}
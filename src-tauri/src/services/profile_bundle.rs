@@ -0,0 +1,272 @@
+use crate::error::{HedgeXError, Result};
+use crate::services::data_persistence_service::quote_identifier;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use sqlx::{Pool, Sqlite, SqliteConnection};
+use std::collections::HashSet;
+use tracing::{info, warn};
+
+/// Format version written into bundles; newer bundles are refused
+pub const PROFILE_BUNDLE_VERSION: u32 = 1;
+
+/// Tables in a profile as (table, condition selecting the user's rows), parents before children
+const PROFILE_TABLES: &[(&str, &str)] = &[
+    ("strategy_params", "user_id = ?"),
+    ("strategy_trend_filters", "strategy_id IN (SELECT id FROM strategy_params WHERE user_id = ?)"),
+    ("pair_strategies", "strategy_id IN (SELECT id FROM strategy_params WHERE user_id = ?)"),
+    ("stock_selection", "user_id = ?"),
+    ("user_settings", "user_id = ?"),
+    ("trades", "user_id = ?"),
+];
+
+/// Rows of one table, each a JSON object of its columns
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BundleTable {
+    pub name: String,
+    pub rows: Vec<Map<String, Value>>,
+}
+
+/// Everything needed to recreate a user's profile on another machine
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileBundle {
+    pub version: u32,
+    pub created_at: DateTime<Utc>,
+    pub user_id: String,
+    pub tables: Vec<BundleTable>,
+    /// `ProfileCredentials` sealed with the export passphrase, base64 encoded; unset when exported without one
+    pub credentials: Option<String>,
+}
+
+/// API credentials in the clear, only ever stored sealed inside a bundle
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredCredentials {
+    pub api_key: String,
+    pub api_secret: String,
+    pub access_token: Option<String>,
+    pub access_token_expiry: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProfileCredentials {
+    pub kite: Option<StoredCredentials>,
+    pub api: Option<StoredCredentials>,
+}
+
+/// What an import does with a row whose key already exists
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictResolution {
+    /// Keep the existing row
+    Skip,
+    /// Replace the existing row with the bundle's
+    Overwrite,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TableImport {
+    pub table: String,
+    pub imported: u64,
+    /// Rows left alone because they already existed
+    pub skipped: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProfileImportReport {
+    pub source_user_id: String,
+    pub bundle_created_at: DateTime<Utc>,
+    pub tables: Vec<TableImport>,
+    pub credentials_imported: bool,
+}
+
+/// Columns of a table, empty when it does not exist
+async fn table_columns(conn: &mut SqliteConnection, table: &str) -> Result<Vec<String>> {
+    sqlx::query_scalar("SELECT name FROM pragma_table_info(?) ORDER BY cid")
+        .bind(table)
+        .fetch_all(conn)
+        .await
+        .map_err(HedgeXError::DatabaseError)
+}
+
+/// The user's rows of every profile table present in the database
+pub async fn export_tables(pool: &Pool<Sqlite>, user_id: &str) -> Result<Vec<BundleTable>> {
+    let mut conn = pool.acquire().await.map_err(HedgeXError::DatabaseError)?;
+    let mut tables = Vec::new();
+
+    for (table, condition) in PROFILE_TABLES {
+        let columns = table_columns(&mut conn, table).await?;
+        if columns.is_empty() {
+            continue;
+        }
+
+        let fields: Vec<String> = columns.iter()
+            .map(|name| format!("'{}', {}", name.replace('\'', "''"), quote_identifier(name)))
+            .collect();
+        let sql = format!(
+            "SELECT json_object({}) FROM {} WHERE {} ORDER BY rowid",
+            fields.join(", "),
+            quote_identifier(table),
+            condition
+        );
+        let rows: Vec<String> = sqlx::query_scalar(&sql)
+            .bind(user_id)
+            .fetch_all(&mut *conn)
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+
+        tables.push(BundleTable {
+            name: table.to_string(),
+            rows: rows.iter().map(|row| serde_json::from_str(row)).collect::<std::result::Result<_, _>>()?,
+        });
+    }
+    Ok(tables)
+}
+
+/// Write the bundle's rows for `user_id`, whoever exported them
+///
+/// Columns the current schema lacks are dropped and missing ones take their defaults, so
+/// bundles from older or newer builds of the same format still import. Run it on a
+/// transaction so a failing row leaves nothing half-imported.
+pub async fn import_tables(
+    conn: &mut SqliteConnection,
+    tables: &[BundleTable],
+    user_id: &str,
+    resolution: ConflictResolution,
+) -> Result<Vec<TableImport>> {
+    let mut imported = Vec::new();
+
+    for (table, _) in PROFILE_TABLES {
+        let Some(bundle_table) = tables.iter().find(|t| t.name == *table) else {
+            continue;
+        };
+        let known: HashSet<String> = table_columns(conn, table).await?.into_iter().collect();
+        if known.is_empty() {
+            warn!("Skipping {} rows for {}, which this database does not have", bundle_table.rows.len(), table);
+            continue;
+        }
+
+        let mut result = TableImport { table: table.to_string(), imported: 0, skipped: 0 };
+        for row in &bundle_table.rows {
+            let columns: Vec<&String> = row.keys().filter(|name| known.contains(*name)).collect();
+            if columns.is_empty() {
+                continue;
+            }
+
+            let names: Vec<String> = columns.iter().map(|name| quote_identifier(name)).collect();
+            let on_conflict = match resolution {
+                ConflictResolution::Skip => "DO NOTHING".to_string(),
+                ConflictResolution::Overwrite => format!(
+                    "DO UPDATE SET {}",
+                    names.iter().map(|name| format!("{name} = excluded.{name}")).collect::<Vec<_>>().join(", ")
+                ),
+            };
+            let sql = format!(
+                "INSERT INTO {} ({}) VALUES ({}) ON CONFLICT {}",
+                quote_identifier(table),
+                names.join(", "),
+                vec!["?"; names.len()].join(", "),
+                on_conflict
+            );
+
+            let mut query = sqlx::query(&sql);
+            for name in &columns {
+                query = match (name.as_str(), &row[name.as_str()]) {
+                    ("user_id", _) => query.bind(user_id.to_string()),
+                    (_, Value::Null) => query.bind(None::<String>),
+                    (_, Value::Bool(value)) => query.bind(*value),
+                    (_, Value::Number(value)) => match value.as_i64() {
+                        Some(value) => query.bind(value),
+                        None => query.bind(value.as_f64()),
+                    },
+                    (_, Value::String(value)) => query.bind(value.clone()),
+                    (_, value) => query.bind(value.to_string()),
+                };
+            }
+
+            if query.execute(&mut *conn).await.map_err(HedgeXError::DatabaseError)?.rows_affected() > 0 {
+                result.imported += 1;
+            } else {
+                result.skipped += 1;
+            }
+        }
+
+        info!("Imported {} rows into {} ({} already present)", result.imported, table, result.skipped);
+        imported.push(result);
+    }
+    Ok(imported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sqlx::Executor;
+
+    const SCHEMA: &str = "
+        CREATE TABLE users (id TEXT PRIMARY KEY);
+        CREATE TABLE strategy_params (id TEXT PRIMARY KEY, user_id TEXT NOT NULL, name TEXT NOT NULL, enabled BOOLEAN NOT NULL DEFAULT 0,
+            risk_percentage REAL NOT NULL DEFAULT 1.0, FOREIGN KEY (user_id) REFERENCES users(id));
+        CREATE TABLE trades (id TEXT PRIMARY KEY, user_id TEXT NOT NULL, strategy_id TEXT NOT NULL, price REAL NOT NULL,
+            executed_at TIMESTAMP NOT NULL, FOREIGN KEY (strategy_id) REFERENCES strategy_params(id));";
+
+    async fn database(sql: &str) -> Pool<Sqlite> {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        pool.execute(SCHEMA).await.unwrap();
+        pool.execute(sql).await.unwrap();
+        pool
+    }
+
+    #[tokio::test]
+    async fn test_export_and_import_profile_tables() {
+        let source = database(
+            "INSERT INTO users VALUES ('alice'), ('bob');
+             INSERT INTO strategy_params VALUES ('s1', 'alice', 'Momentum', 1, 2.5), ('s2', 'bob', 'Other', 0, 1.0);
+             INSERT INTO trades VALUES ('t1', 'alice', 's1', 101.5, '2025-08-01T09:30:00+00:00');"
+        ).await;
+        let tables = export_tables(&source, "alice").await.unwrap();
+        let counts: Vec<(&str, usize)> = tables.iter().map(|t| (t.name.as_str(), t.rows.len())).collect();
+        assert_eq!(counts, vec![("strategy_params", 1), ("trades", 1)]);
+
+        // The same strategy already exists on the target machine under a different name
+        let target = database(
+            "INSERT INTO users VALUES ('carol');
+             INSERT INTO strategy_params VALUES ('s1', 'carol', 'Renamed', 0, 1.0);"
+        ).await;
+        let mut tx = target.begin().await.unwrap();
+        let report = import_tables(&mut tx, &tables, "carol", ConflictResolution::Skip).await.unwrap();
+        tx.commit().await.unwrap();
+        let results: Vec<(&str, u64, u64)> = report.iter().map(|t| (t.table.as_str(), t.imported, t.skipped)).collect();
+        assert_eq!(results, vec![("strategy_params", 0, 1), ("trades", 1, 0)]);
+
+        let name: String = sqlx::query_scalar("SELECT name FROM strategy_params WHERE id = 's1'").fetch_one(&target).await.unwrap();
+        assert_eq!(name, "Renamed");
+        let (user_id, price): (String, f64) = sqlx::query_as("SELECT user_id, price FROM trades WHERE id = 't1'").fetch_one(&target).await.unwrap();
+        assert_eq!((user_id.as_str(), price), ("carol", 101.5));
+
+        let mut tx = target.begin().await.unwrap();
+        import_tables(&mut tx, &tables, "carol", ConflictResolution::Overwrite).await.unwrap();
+        tx.commit().await.unwrap();
+        let (name, enabled, risk): (String, bool, f64) = sqlx::query_as("SELECT name, enabled, risk_percentage FROM strategy_params WHERE id = 's1'")
+            .fetch_one(&target)
+            .await
+            .unwrap();
+        assert_eq!((name.as_str(), enabled, risk), ("Momentum", true, 2.5));
+    }
+
+    #[tokio::test]
+    async fn test_import_ignores_unknown_columns() {
+        let target = database("INSERT INTO users VALUES ('alice');").await;
+        let row: Map<String, Value> = serde_json::from_str(r#"{"id": "s1", "user_id": "bob", "name": "Newer", "added_in_a_later_build": 3}"#).unwrap();
+        let tables = vec![BundleTable { name: "strategy_params".to_string(), rows: vec![row] }];
+
+        let mut tx = target.begin().await.unwrap();
+        let report = import_tables(&mut tx, &tables, "alice", ConflictResolution::Skip).await.unwrap();
+        tx.commit().await.unwrap();
+        assert_eq!(report[0].imported, 1);
+
+        let risk: f64 = sqlx::query_scalar("SELECT risk_percentage FROM strategy_params WHERE id = 's1' AND user_id = 'alice'")
+            .fetch_one(&target)
+            .await
+            .unwrap();
+        assert_eq!(risk, 1.0);
+    }
+}
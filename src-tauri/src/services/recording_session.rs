@@ -0,0 +1,322 @@
+use crate::error::{HedgeXError, Result};
+use crate::services::tick_channel::TickReceiver;
+use crate::services::websocket_manager::MarketData;
+use chrono::{DateTime, Utc};
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Sqlite};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::{oneshot, Mutex};
+use tokio::task::JoinHandle;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Ticks written per transaction
+const SESSION_BATCH_SIZE: usize = 200;
+
+/// Ticks a session may fall behind the feed by before it starts losing them
+pub const SESSION_QUEUE_CAPACITY: usize = 10_000;
+
+/// Longest a captured tick waits before it is written
+const SESSION_FLUSH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// A recording session as stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordingSessionInfo {
+    pub id: String,
+    pub name: String,
+    pub symbols: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    /// Unset while the session is still recording
+    pub stopped_at: Option<DateTime<Utc>>,
+    pub tick_count: i64,
+}
+
+/// Captures every tick and depth snapshot of chosen symbols until stopped
+///
+/// Ticks are stored in `recording_session_ticks` under the session, apart from
+/// the general tick history, so a session can be exported as one bundle.
+pub struct RecordingSession {
+    db: Arc<Pool<Sqlite>>,
+    info: RecordingSessionInfo,
+    captured: Arc<AtomicU64>,
+    stop_tx: Mutex<Option<oneshot::Sender<()>>>,
+    task: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl RecordingSession {
+    /// Store a new session and start capturing the symbols' ticks from the feed
+    pub async fn start(
+        db: Arc<Pool<Sqlite>>,
+        user_id: &str,
+        name: &str,
+        symbols: Vec<String>,
+        updates: TickReceiver,
+    ) -> Result<Arc<Self>> {
+        if name.trim().is_empty() {
+            return Err(HedgeXError::ValidationError("Session name is required".to_string()));
+        }
+        if symbols.is_empty() {
+            return Err(HedgeXError::ValidationError("At least one symbol is required".to_string()));
+        }
+
+        let info = RecordingSessionInfo {
+            id: Uuid::new_v4().to_string(),
+            name: name.trim().to_string(),
+            symbols,
+            started_at: Utc::now(),
+            stopped_at: None,
+            tick_count: 0,
+        };
+        let symbols_json = serde_json::to_string(&info.symbols)
+            .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?;
+
+        sqlx::query(
+            "INSERT INTO recording_sessions (id, user_id, name, symbols, started_at) VALUES (?, ?, ?, ?, ?)"
+        )
+        .bind(&info.id)
+        .bind(user_id)
+        .bind(&info.name)
+        .bind(symbols_json)
+        .bind(info.started_at)
+        .execute(&*db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+        let (stop_tx, stop_rx) = oneshot::channel();
+        let captured = Arc::new(AtomicU64::new(0));
+        let task = tokio::spawn(Self::run(
+            Arc::clone(&db),
+            info.id.clone(),
+            info.symbols.clone(),
+            updates,
+            stop_rx,
+            Arc::clone(&captured),
+        ));
+
+        info!("Recording session '{}' started for {} symbols", info.name, info.symbols.len());
+        Ok(Arc::new(Self {
+            db,
+            info,
+            captured,
+            stop_tx: Mutex::new(Some(stop_tx)),
+            task: Mutex::new(Some(task)),
+        }))
+    }
+
+    pub fn id(&self) -> &str {
+        &self.info.id
+    }
+
+    /// The session with the ticks captured so far
+    pub fn info(&self) -> RecordingSessionInfo {
+        RecordingSessionInfo {
+            tick_count: self.captured.load(Ordering::Relaxed) as i64,
+            ..self.info.clone()
+        }
+    }
+
+    /// Stop capturing, write what is still buffered and close the session
+    pub async fn stop(&self) -> Result<RecordingSessionInfo> {
+        if let Some(stop_tx) = self.stop_tx.lock().await.take() {
+            let _ = stop_tx.send(());
+        }
+        if let Some(task) = self.task.lock().await.take() {
+            task.await
+                .map_err(|e| HedgeXError::InternalError(format!("Recording session task failed: {}", e)))?;
+        }
+
+        let info = RecordingSessionInfo {
+            stopped_at: Some(Utc::now()),
+            ..self.info()
+        };
+        sqlx::query("UPDATE recording_sessions SET stopped_at = ?, tick_count = ? WHERE id = ?")
+            .bind(info.stopped_at)
+            .bind(info.tick_count)
+            .bind(&info.id)
+            .execute(&*self.db)
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+
+        info!("Recording session '{}' stopped after {} ticks", info.name, info.tick_count);
+        Ok(info)
+    }
+
+    /// Collect the symbols' ticks and write them in batches until stopped or the feed closes
+    async fn run(
+        db: Arc<Pool<Sqlite>>,
+        session_id: String,
+        symbols: Vec<String>,
+        mut updates: TickReceiver,
+        mut stop_rx: oneshot::Receiver<()>,
+        captured: Arc<AtomicU64>,
+    ) {
+        let mut interval = tokio::time::interval(SESSION_FLUSH_INTERVAL);
+        let mut batch: Vec<MarketData> = Vec::with_capacity(SESSION_BATCH_SIZE);
+
+        loop {
+            tokio::select! {
+                _ = &mut stop_rx => break,
+                update = updates.recv() => match update {
+                    Some(tick) => {
+                        if symbols.contains(&tick.symbol) {
+                            batch.push(tick);
+                            if batch.len() >= SESSION_BATCH_SIZE {
+                                Self::write(&db, &session_id, &mut batch, &captured).await;
+                            }
+                        }
+                    }
+                    None => break,
+                },
+                _ = interval.tick() => Self::write(&db, &session_id, &mut batch, &captured).await,
+            }
+        }
+
+        // Keep draining what the feed already delivered before the stop
+        while let Some(tick) = updates.try_recv() {
+            if symbols.contains(&tick.symbol) {
+                batch.push(tick);
+            }
+        }
+        Self::write(&db, &session_id, &mut batch, &captured).await;
+    }
+
+    /// Write a batch in one transaction and clear it; a failed batch is logged and dropped
+    async fn write(db: &Pool<Sqlite>, session_id: &str, batch: &mut Vec<MarketData>, captured: &AtomicU64) {
+        if batch.is_empty() {
+            return;
+        }
+
+        match Self::insert_batch(db, session_id, batch).await {
+            Ok(written) => {
+                captured.fetch_add(written, Ordering::Relaxed);
+            }
+            Err(e) => warn!("Failed to write {} ticks of recording session {}: {}", batch.len(), session_id, e),
+        }
+        batch.clear();
+    }
+
+    async fn insert_batch(db: &Pool<Sqlite>, session_id: &str, batch: &[MarketData]) -> Result<u64> {
+        let mut tx = db.begin().await.map_err(HedgeXError::DatabaseError)?;
+        let mut written = 0;
+
+        for tick in batch {
+            let (ltp, bid, ask) = match (tick.ltp.to_f64(), tick.bid.to_f64(), tick.ask.to_f64()) {
+                (Some(ltp), Some(bid), Some(ask)) => (ltp, bid, ask),
+                _ => continue,
+            };
+            let depth = match &tick.depth {
+                Some(depth) => Some(serde_json::to_string(depth)
+                    .map_err(|e| HedgeXError::InternalError(format!("JSON serialization failed: {}", e)))?),
+                None => None,
+            };
+
+            sqlx::query(
+                "INSERT INTO recording_session_ticks (session_id, symbol, timestamp, ltp, bid, ask, volume, depth)
+                 VALUES (?, ?, ?, ?, ?, ?, ?, ?)"
+            )
+            .bind(session_id)
+            .bind(&tick.symbol)
+            .bind(tick.timestamp)
+            .bind(ltp)
+            .bind(bid)
+            .bind(ask)
+            .bind(tick.volume as i64)
+            .bind(depth)
+            .execute(&mut *tx)
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+            written += 1;
+        }
+
+        tx.commit().await.map_err(HedgeXError::DatabaseError)?;
+        Ok(written)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::trading::{DepthLevel, MarketDepth};
+    use crate::services::tick_channel::{TickPolicy, TickSender};
+    use rust_decimal::Decimal;
+    use sqlx::{Executor, Row};
+    use sqlx::sqlite::SqlitePoolOptions;
+
+    async fn create_test_pool() -> Arc<Pool<Sqlite>> {
+        let pool = SqlitePoolOptions::new().max_connections(1).connect(":memory:").await.unwrap();
+        pool.execute(include_str!("../../migrations/20250819_add_recording_sessions.sql")).await.unwrap();
+        Arc::new(pool)
+    }
+
+    fn tick(symbol: &str, with_depth: bool) -> MarketData {
+        MarketData {
+            symbol: symbol.to_string(),
+            instrument_token: 408065,
+            ltp: Decimal::new(150050, 2),
+            volume: 1000,
+            bid: Decimal::new(150045, 2),
+            ask: Decimal::new(150055, 2),
+            ohlc: None,
+            timestamp: Utc::now(),
+            change: None,
+            change_percent: None,
+            depth: with_depth.then(|| MarketDepth {
+                bids: vec![DepthLevel { price: Decimal::new(150045, 2), quantity: 10, orders: 1 }],
+                asks: vec![DepthLevel { price: Decimal::new(150055, 2), quantity: 12, orders: 2 }],
+            }),
+            delayed: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_session_captures_chosen_symbols_until_stopped() {
+        let pool = create_test_pool().await;
+        let tx = TickSender::new();
+        let session = RecordingSession::start(
+            Arc::clone(&pool), "test_user", "Opening auction", vec!["INFY".to_string()], tx.subscribe("recording", TickPolicy::Queue(100)),
+        ).await.unwrap();
+
+        tx.send(tick("INFY", true)).unwrap();
+        tx.send(tick("TCS", true)).unwrap();
+        tx.send(tick("INFY", false)).unwrap();
+
+        let info = session.stop().await.unwrap();
+        assert_eq!(info.tick_count, 2);
+        assert!(info.stopped_at.is_some());
+
+        // Ticks after the stop are not captured
+        let _ = tx.send(tick("INFY", false));
+
+        let row = sqlx::query("SELECT tick_count, stopped_at FROM recording_sessions WHERE id = ?")
+            .bind(session.id())
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(row.get::<i64, _>("tick_count"), 2);
+
+        let depths: Vec<Option<String>> = sqlx::query("SELECT depth FROM recording_session_ticks ORDER BY id")
+            .fetch_all(&*pool)
+            .await
+            .unwrap()
+            .iter()
+            .map(|row| row.get("depth"))
+            .collect();
+        assert_eq!(depths.len(), 2);
+        let depth: MarketDepth = serde_json::from_str(depths[0].as_ref().unwrap()).unwrap();
+        assert_eq!(depth.asks[0].quantity, 12);
+        assert!(depths[1].is_none());
+    }
+
+    #[tokio::test]
+    async fn test_session_needs_a_name_and_symbols() {
+        let pool = create_test_pool().await;
+        let tx = TickSender::new();
+        let updates = || tx.subscribe("recording", TickPolicy::Queue(10));
+
+        assert!(RecordingSession::start(Arc::clone(&pool), "test_user", " ", vec!["INFY".to_string()], updates()).await.is_err());
+        assert!(RecordingSession::start(pool, "test_user", "Empty", Vec::new(), updates()).await.is_err());
+    }
+}
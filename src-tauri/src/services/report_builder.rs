@@ -0,0 +1,240 @@
+use crate::error::{HedgeXError, Result};
+use crate::services::pnl_statement::PnlStatementService;
+use crate::services::time_breakdown::{round_trips, RoundTrip};
+use crate::trading::analytics_filter::TradeFilter;
+use crate::trading::session::session_date;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// Most groupings a report can nest
+const MAX_GROUPINGS: usize = 3;
+
+/// A dimension report rows are grouped by
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportGrouping {
+    Strategy,
+    Symbol,
+    /// IST date the round trip closed on
+    Day,
+}
+
+/// A figure computed for each report row
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ReportMetric {
+    Pnl,
+    Trades,
+    WinRate,
+    AveragePnl,
+}
+
+impl ReportMetric {
+    fn column(self) -> &'static str {
+        match self {
+            ReportMetric::Pnl => "pnl",
+            ReportMetric::Trades => "trades",
+            ReportMetric::WinRate => "win_rate",
+            ReportMetric::AveragePnl => "average_pnl",
+        }
+    }
+
+    fn value(self, pnls: &[f64]) -> serde_json::Value {
+        let total: f64 = pnls.iter().sum();
+        let count = pnls.len() as f64;
+        match self {
+            ReportMetric::Pnl => total.into(),
+            ReportMetric::Trades => pnls.len().into(),
+            ReportMetric::WinRate if pnls.is_empty() => 0.0.into(),
+            ReportMetric::WinRate => (pnls.iter().filter(|pnl| **pnl > 0.0).count() as f64 / count).into(),
+            ReportMetric::AveragePnl if pnls.is_empty() => 0.0.into(),
+            ReportMetric::AveragePnl => (total / count).into(),
+        }
+    }
+}
+
+/// Date range and filters of a report; dates are IST `YYYY-MM-DD`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReportFilters {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub strategy_id: Option<String>,
+    pub symbol: Option<String>,
+}
+
+/// Declarative report over closed round trips
+///
+/// Without groupings the report is a single row of totals.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportSpec {
+    #[serde(default)]
+    pub group_by: Vec<ReportGrouping>,
+    pub metrics: Vec<ReportMetric>,
+    #[serde(default)]
+    pub filters: ReportFilters,
+}
+
+impl ReportSpec {
+    pub fn validate(&self) -> Result<()> {
+        if self.metrics.is_empty() {
+            return Err(HedgeXError::ValidationError("A report needs at least one metric".to_string()));
+        }
+        if self.group_by.len() > MAX_GROUPINGS {
+            return Err(HedgeXError::ValidationError(format!("A report can group by at most {} dimensions", MAX_GROUPINGS)));
+        }
+        if self.group_by.iter().enumerate().any(|(i, grouping)| self.group_by[..i].contains(grouping)) {
+            return Err(HedgeXError::ValidationError("A report cannot group by the same dimension twice".to_string()));
+        }
+        Ok(())
+    }
+}
+
+/// Tabular result of a report, one row per group in group order
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReportTable {
+    #[serde(flatten)]
+    pub filter: TradeFilter,
+    /// Grouping columns, then metric columns, as the spec lists them
+    pub columns: Vec<String>,
+    pub rows: Vec<Vec<serde_json::Value>>,
+}
+
+impl PnlStatementService {
+    /// Run a report spec over the round trips a user closed, up to `today` unless the spec ends earlier
+    pub async fn custom_report(&self, user_id: &str, spec: &ReportSpec, today: NaiveDate) -> Result<ReportTable> {
+        spec.validate()?;
+        let filters = &spec.filters;
+        let filter = TradeFilter::from_params(
+            today,
+            None,
+            filters.from.as_deref(),
+            filters.to.as_deref(),
+            filters.strategy_id.as_deref(),
+            filters.symbol.as_deref(),
+        )?;
+
+        let trades = self.load_trades(user_id, filter.to).await?;
+        let names: HashMap<&str, &str> = trades.iter()
+            .map(|trade| (trade.strategy_id.as_str(), trade.strategy_name.as_str()))
+            .collect();
+        let trips: Vec<RoundTrip> = round_trips(&trades)
+            .into_iter()
+            .filter(|trip| filter.matches(session_date(trip.exited_at), &trip.strategy_id, &trip.symbol))
+            .collect();
+
+        let (columns, rows) = build_report(&trips, &names, spec);
+        Ok(ReportTable { filter, columns, rows })
+    }
+}
+
+/// Group round trips by the spec's dimensions and compute its metrics for each group
+///
+/// A strategy grouping takes two columns, its id and its name.
+pub fn build_report(
+    trips: &[RoundTrip],
+    names: &HashMap<&str, &str>,
+    spec: &ReportSpec,
+) -> (Vec<String>, Vec<Vec<serde_json::Value>>) {
+    let mut columns: Vec<String> = Vec::new();
+    for grouping in &spec.group_by {
+        match grouping {
+            ReportGrouping::Strategy => columns.extend(["strategy_id".to_string(), "strategy_name".to_string()]),
+            ReportGrouping::Symbol => columns.push("symbol".to_string()),
+            ReportGrouping::Day => columns.push("day".to_string()),
+        }
+    }
+    columns.extend(spec.metrics.iter().map(|metric| metric.column().to_string()));
+
+    let mut groups: BTreeMap<Vec<String>, Vec<f64>> = BTreeMap::new();
+    for trip in trips {
+        let key = spec.group_by.iter()
+            .map(|grouping| match grouping {
+                ReportGrouping::Strategy => trip.strategy_id.clone(),
+                ReportGrouping::Symbol => format!("{}:{}", trip.exchange, trip.symbol),
+                ReportGrouping::Day => session_date(trip.exited_at).format("%Y-%m-%d").to_string(),
+            })
+            .collect();
+        groups.entry(key).or_default().push(trip.pnl);
+    }
+    // Totals still get a row when nothing closed
+    if spec.group_by.is_empty() && groups.is_empty() {
+        groups.insert(Vec::new(), Vec::new());
+    }
+
+    let rows = groups.into_iter()
+        .map(|(key, pnls)| {
+            let mut row: Vec<serde_json::Value> = Vec::new();
+            for (grouping, value) in spec.group_by.iter().zip(key) {
+                if *grouping == ReportGrouping::Strategy {
+                    let name = names.get(value.as_str()).copied().unwrap_or("Unknown").to_string();
+                    row.extend([value.into(), name.into()]);
+                } else {
+                    row.push(value.into());
+                }
+            }
+            row.extend(spec.metrics.iter().map(|metric| metric.value(&pnls)));
+            row
+        })
+        .collect();
+
+    (columns, rows)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+    use serde_json::json;
+
+    fn trip(strategy: &str, symbol: &str, day: u32, pnl: f64) -> RoundTrip {
+        let at = Utc.with_ymd_and_hms(2024, 8, day, 5, 0, 0).unwrap();
+        RoundTrip {
+            symbol: symbol.to_string(),
+            exchange: "NSE".to_string(),
+            strategy_id: strategy.to_string(),
+            quantity: 1,
+            entered_at: at,
+            exited_at: at,
+            pnl,
+        }
+    }
+
+    #[test]
+    fn test_build_report() {
+        let trips = vec![
+            trip("a", "INFY", 5, 100.0), trip("a", "INFY", 6, -40.0),
+            trip("a", "TCS", 6, 20.0), trip("b", "INFY", 6, -10.0),
+        ];
+        let names = HashMap::from([("a", "Momentum")]);
+        let spec: ReportSpec = serde_json::from_value(json!({
+            "group_by": ["strategy", "symbol"],
+            "metrics": ["pnl", "trades", "win_rate"],
+        })).unwrap();
+
+        let (columns, rows) = build_report(&trips, &names, &spec);
+        assert_eq!(columns, vec!["strategy_id", "strategy_name", "symbol", "pnl", "trades", "win_rate"]);
+        assert_eq!(rows, vec![
+            vec![json!("a"), json!("Momentum"), json!("NSE:INFY"), json!(60.0), json!(2), json!(0.5)],
+            vec![json!("a"), json!("Momentum"), json!("NSE:TCS"), json!(20.0), json!(1), json!(1.0)],
+            vec![json!("b"), json!("Unknown"), json!("NSE:INFY"), json!(-10.0), json!(1), json!(0.0)],
+        ]);
+
+        let totals = ReportSpec { group_by: Vec::new(), metrics: vec![ReportMetric::AveragePnl], filters: ReportFilters::default() };
+        assert_eq!(build_report(&trips, &names, &totals).1, vec![vec![json!(17.5)]]);
+        assert_eq!(build_report(&[], &names, &totals).1, vec![vec![json!(0.0)]]);
+    }
+
+    #[test]
+    fn test_spec_validation() {
+        let spec = |group_by: Vec<ReportGrouping>, metrics: Vec<ReportMetric>| ReportSpec {
+            group_by,
+            metrics,
+            filters: ReportFilters::default(),
+        };
+        assert!(spec(vec![ReportGrouping::Day], vec![ReportMetric::Pnl]).validate().is_ok());
+        assert!(spec(vec![ReportGrouping::Day], Vec::new()).validate().is_err());
+        assert!(spec(vec![ReportGrouping::Day, ReportGrouping::Day], vec![ReportMetric::Pnl]).validate().is_err());
+        assert!(serde_json::from_value::<ReportSpec>(json!({ "group_by": ["hour"], "metrics": ["pnl"] })).is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::error::{HedgeXError, Result};
+use crate::services::pnl_statement::PnlStatementService;
+use crate::services::time_breakdown::{round_trips, RoundTrip};
+use crate::trading::analytics_filter::TradeFilter;
+use crate::trading::performance::{rolling_metrics, RollingPoint};
+use crate::trading::session::{is_trading_day, session_date};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Windows, in trading days, charted when none are asked for
+pub const DEFAULT_ROLLING_WINDOWS: [usize; 3] = [7, 30, 90];
+
+/// Longest rolling window, about a trading year
+const MAX_ROLLING_WINDOW: usize = 252;
+
+/// Most windows one request can chart
+const MAX_ROLLING_SERIES: usize = 5;
+
+/// Rolling metrics of one window length
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollingSeries {
+    pub window_days: usize,
+    pub points: Vec<RollingPoint>,
+}
+
+/// Rolling P&L, win rate and Sharpe ratio of closed round trips, one series per window
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RollingPerformance {
+    #[serde(flatten)]
+    pub filter: TradeFilter,
+    pub series: Vec<RollingSeries>,
+}
+
+/// Annualization and risk-free inputs of the rolling Sharpe ratio
+#[derive(Debug, Clone, Copy)]
+pub struct SharpeInputs {
+    pub capital: f64,
+    pub risk_free_rate: f64,
+    pub periods_per_year: f64,
+}
+
+impl PnlStatementService {
+    /// Rolling metrics of the round trips passing a filter, with a point for each trading day in its range
+    ///
+    /// Windows reach back before the range start, so its first points already cover full windows.
+    pub async fn rolling_performance(
+        &self,
+        user_id: &str,
+        filter: &TradeFilter,
+        windows: &[usize],
+        sharpe: SharpeInputs,
+    ) -> Result<RollingPerformance> {
+        if windows.is_empty() || windows.len() > MAX_ROLLING_SERIES {
+            return Err(HedgeXError::ValidationError(format!("Between 1 and {} rolling windows are allowed", MAX_ROLLING_SERIES)));
+        }
+        if let Some(window) = windows.iter().find(|window| !(1..=MAX_ROLLING_WINDOW).contains(*window)) {
+            return Err(HedgeXError::ValidationError(format!("Rolling window of {} days is out of range", window)));
+        }
+
+        let trades = self.load_trades(user_id, filter.to).await?;
+        // The start date is applied to the points, so earlier trips can fill the first windows
+        let history = TradeFilter { from: None, ..filter.clone() };
+        let trips: Vec<RoundTrip> = round_trips(&trades)
+            .into_iter()
+            .filter(|trip| history.matches(session_date(trip.exited_at), &trip.strategy_id, &trip.symbol))
+            .collect();
+        let days = trading_days(&trips, filter.to);
+
+        Ok(RollingPerformance {
+            filter: filter.clone(),
+            series: windows.iter()
+                .map(|window| RollingSeries {
+                    window_days: *window,
+                    points: rolling_metrics(&days, *window, sharpe.capital, sharpe.risk_free_rate, sharpe.periods_per_year)
+                        .into_iter()
+                        .filter(|point| filter.from.is_none_or(|from| point.date >= from))
+                        .collect(),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// Every trading day from the first closed trip to `to`, with the P&L of the trips closed on it
+pub fn trading_days(trips: &[RoundTrip], to: NaiveDate) -> Vec<(NaiveDate, Vec<f64>)> {
+    let mut pnl_by_day: BTreeMap<NaiveDate, Vec<f64>> = BTreeMap::new();
+    for trip in trips {
+        pnl_by_day.entry(session_date(trip.exited_at)).or_default().push(trip.pnl);
+    }
+    let Some(first) = pnl_by_day.keys().next().copied() else {
+        return Vec::new();
+    };
+
+    first.iter_days()
+        .take_while(|day| *day <= to)
+        .filter(|day| is_trading_day(*day) || pnl_by_day.contains_key(day))
+        .map(|day| (day, pnl_by_day.get(&day).cloned().unwrap_or_default()))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    #[test]
+    fn test_trading_days_fill_quiet_days() {
+        let trip = |day: u32, pnl: f64| {
+            let at = Utc.with_ymd_and_hms(2024, 8, day, 5, 0, 0).unwrap();
+            RoundTrip {
+                symbol: "INFY".to_string(),
+                exchange: "NSE".to_string(),
+                strategy_id: "a".to_string(),
+                quantity: 1,
+                entered_at: at,
+                exited_at: at,
+                pnl,
+            }
+        };
+        // Friday 9 August, then Tuesday 13 August; the weekend is skipped
+        let days = trading_days(&[trip(9, 10.0), trip(13, -5.0), trip(13, 15.0)], NaiveDate::from_ymd_opt(2024, 8, 14).unwrap());
+
+        let dates: Vec<u32> = days.iter().map(|(day, _)| chrono::Datelike::day(day)).collect();
+        assert_eq!(dates, vec![9, 12, 13, 14]);
+        assert_eq!(days[2].1, vec![-5.0, 15.0]);
+        assert!(days[1].1.is_empty());
+        assert!(trading_days(&[], NaiveDate::from_ymd_opt(2024, 8, 14).unwrap()).is_empty());
+    }
+}
@@ -0,0 +1,217 @@
+use crate::error::Result;
+use crate::models::trading::TradeType;
+use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use crate::trading::analytics_filter::TradeFilter;
+use chrono::{DateTime, Timelike, Utc};
+use chrono_tz::Asia::Kolkata;
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+/// Hours of the NSE session, always present so charts keep a stable axis
+const SESSION_HOURS: std::ops::RangeInclusive<u32> = 9..=15;
+
+/// A live fill with the price its signal was generated at
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlippageFill {
+    pub trade_id: String,
+    pub symbol: String,
+    pub exchange: String,
+    pub trade_type: TradeType,
+    pub order_type: String,
+    pub quantity: i64,
+    pub signal_price: f64,
+    pub executed_price: f64,
+    pub executed_at: DateTime<Utc>,
+}
+
+impl SlippageFill {
+    /// Price moved against the order per share; negative when the fill beat the signal
+    pub fn slippage_per_share(&self) -> f64 {
+        match self.trade_type {
+            TradeType::Buy => self.executed_price - self.signal_price,
+            TradeType::Sell => self.signal_price - self.executed_price,
+        }
+    }
+
+    /// Slippage in basis points of the signal price
+    pub fn slippage_bps(&self) -> f64 {
+        if self.signal_price > 0.0 {
+            self.slippage_per_share() / self.signal_price * 10_000.0
+        } else {
+            0.0
+        }
+    }
+
+    /// Rupees lost to slippage on the whole fill
+    pub fn slippage_cost(&self) -> f64 {
+        self.slippage_per_share() * self.quantity as f64
+    }
+}
+
+/// Slippage of the fills in one symbol, hour or order type
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlippageBucket {
+    pub bucket: String,
+    pub fills: usize,
+    /// Fills worse than their signal price
+    pub adverse_fills: usize,
+    pub average_slippage_bps: f64,
+    pub worst_slippage_bps: f64,
+    pub total_slippage_cost: f64,
+}
+
+/// Execution quality of live fills against their signal prices
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlippageReport {
+    #[serde(flatten)]
+    pub filter: TradeFilter,
+    pub overall: SlippageBucket,
+    pub by_symbol: Vec<SlippageBucket>,
+    /// IST hour the fill executed in, including every session hour
+    pub by_hour: Vec<SlippageBucket>,
+    pub by_order_type: Vec<SlippageBucket>,
+}
+
+/// Measures live fills against the signal prices they were ordered at
+pub struct SlippageService {
+    db_service: Arc<EnhancedDatabaseService>,
+}
+
+impl SlippageService {
+    /// Create a new slippage service
+    pub fn new(db_service: Arc<EnhancedDatabaseService>) -> Self {
+        Self { db_service }
+    }
+
+    /// Slippage of the live fills passing a filter
+    pub async fn report(&self, user_id: &str, filter: &TradeFilter) -> Result<SlippageReport> {
+        let fills = self.load_fills(user_id, filter).await?;
+        Ok(SlippageReport {
+            filter: filter.clone(),
+            overall: bucket("all".to_string(), &fills.iter().collect::<Vec<_>>()),
+            by_symbol: group(&fills, |fill| format!("{}:{}", fill.exchange, fill.symbol)),
+            by_hour: by_hour(&fills),
+            by_order_type: group(&fills, |fill| fill.order_type.clone()),
+        })
+    }
+
+    /// Executed trades with a recorded signal price, oldest first
+    pub async fn load_fills(&self, user_id: &str, filter: &TradeFilter) -> Result<Vec<SlippageFill>> {
+        let (conditions, binds) = filter.sql_conditions("");
+        let sql = format!(
+            "SELECT id, symbol, exchange, trade_type, order_type, quantity, signal_price, price, executed_at
+             FROM trades
+             WHERE user_id = ? AND status = 'Executed' AND signal_price IS NOT NULL{}
+             ORDER BY executed_at ASC",
+            conditions
+        );
+        let mut query = sqlx::query(&sql).bind(user_id);
+        for value in &binds {
+            query = query.bind(value);
+        }
+        let rows = query.fetch_all(self.db_service.get_database().get_read_pool()).await?;
+
+        Ok(rows.iter()
+            .map(|row| SlippageFill {
+                trade_id: row.get("id"),
+                symbol: row.get("symbol"),
+                exchange: row.get("exchange"),
+                trade_type: if row.get::<String, _>("trade_type") == "Sell" { TradeType::Sell } else { TradeType::Buy },
+                order_type: row.get::<Option<String>, _>("order_type").unwrap_or_else(|| "Unknown".to_string()),
+                quantity: row.get("quantity"),
+                signal_price: row.get("signal_price"),
+                executed_price: row.get("price"),
+                executed_at: row.get("executed_at"),
+            })
+            .collect())
+    }
+}
+
+/// Fills grouped by a label, in label order
+pub fn group(fills: &[SlippageFill], label: impl Fn(&SlippageFill) -> String) -> Vec<SlippageBucket> {
+    let mut groups: BTreeMap<String, Vec<&SlippageFill>> = BTreeMap::new();
+    for fill in fills {
+        groups.entry(label(fill)).or_default().push(fill);
+    }
+    groups.into_iter().map(|(label, fills)| bucket(label, &fills)).collect()
+}
+
+/// Fills by IST hour of execution, including every session hour
+pub fn by_hour(fills: &[SlippageFill]) -> Vec<SlippageBucket> {
+    let mut hours: BTreeMap<u32, Vec<&SlippageFill>> = SESSION_HOURS.map(|hour| (hour, Vec::new())).collect();
+    for fill in fills {
+        hours.entry(fill.executed_at.with_timezone(&Kolkata).hour()).or_default().push(fill);
+    }
+    hours.into_iter().map(|(hour, fills)| bucket(hour.to_string(), &fills)).collect()
+}
+
+fn bucket(label: String, fills: &[&SlippageFill]) -> SlippageBucket {
+    if fills.is_empty() {
+        return SlippageBucket { bucket: label, ..SlippageBucket::default() };
+    }
+
+    let bps: Vec<f64> = fills.iter().map(|fill| fill.slippage_bps()).collect();
+    SlippageBucket {
+        bucket: label,
+        fills: fills.len(),
+        adverse_fills: fills.iter().filter(|fill| fill.slippage_per_share() > 0.0).count(),
+        average_slippage_bps: bps.iter().sum::<f64>() / bps.len() as f64,
+        worst_slippage_bps: bps.iter().copied().fold(f64::MIN, f64::max),
+        total_slippage_cost: fills.iter().map(|fill| fill.slippage_cost()).sum(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn fill(symbol: &str, trade_type: TradeType, order_type: &str, signal_price: f64, executed_price: f64, hour: u32) -> SlippageFill {
+        SlippageFill {
+            trade_id: format!("{}-{}", symbol, hour),
+            symbol: symbol.to_string(),
+            exchange: "NSE".to_string(),
+            trade_type,
+            order_type: order_type.to_string(),
+            quantity: 10,
+            signal_price,
+            executed_price,
+            // UTC; IST is 5:30 ahead
+            executed_at: Utc.with_ymd_and_hms(2024, 8, 5, hour, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_slippage_is_signed_by_side() {
+        let buy = fill("INFY", TradeType::Buy, "Market", 1000.0, 1001.0, 4);
+        assert_eq!(buy.slippage_bps(), 10.0);
+        assert_eq!(buy.slippage_cost(), 10.0);
+
+        // Selling above the signal price is favourable
+        let sell = fill("INFY", TradeType::Sell, "Limit", 1000.0, 1002.0, 4);
+        assert_eq!(sell.slippage_bps(), -20.0);
+        assert_eq!(sell.slippage_cost(), -20.0);
+    }
+
+    #[test]
+    fn test_slippage_buckets() {
+        let fills = vec![
+            fill("INFY", TradeType::Buy, "Market", 1000.0, 1001.0, 4),
+            fill("INFY", TradeType::Sell, "Market", 1000.0, 999.0, 4),
+            fill("TCS", TradeType::Buy, "Limit", 2000.0, 1998.0, 6),
+        ];
+
+        let symbols = group(&fills, |fill| fill.symbol.clone());
+        assert_eq!(symbols.len(), 2);
+        assert_eq!((symbols[0].fills, symbols[0].adverse_fills, symbols[0].average_slippage_bps), (2, 2, 10.0));
+        assert_eq!((symbols[1].worst_slippage_bps, symbols[1].total_slippage_cost), (-10.0, -20.0));
+
+        let hours = by_hour(&fills);
+        assert_eq!(hours.len(), 7);
+        assert_eq!(hours.iter().find(|bucket| bucket.bucket == "9").unwrap().fills, 2);
+        assert_eq!(hours.iter().find(|bucket| bucket.bucket == "11").unwrap().fills, 1);
+        assert_eq!(hours.iter().find(|bucket| bucket.bucket == "10").unwrap().worst_slippage_bps, 0.0);
+    }
+}
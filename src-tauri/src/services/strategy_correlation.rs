@@ -0,0 +1,137 @@
+use crate::error::Result;
+use crate::services::pnl_statement::PnlStatementService;
+use crate::services::time_breakdown::{round_trips, RoundTrip};
+use crate::trading::analytics_filter::TradeFilter;
+use crate::trading::performance::{correlation, daily_pnl_series};
+use crate::trading::session::session_date;
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// A strategy in the correlation matrix
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelatedStrategy {
+    pub strategy_id: String,
+    pub strategy_name: String,
+    /// Days in the range the strategy closed any trade
+    pub active_days: usize,
+    pub total_pnl: f64,
+}
+
+/// Pairwise correlation of strategies' daily realized P&L
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CorrelationMatrix {
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    /// Trading days each series covers, quiet days counting as flat
+    pub days: usize,
+    pub strategies: Vec<CorrelatedStrategy>,
+    /// `matrix[i][j]` correlates `strategies[i]` with `strategies[j]`; unset where a series is flat
+    pub matrix: Vec<Vec<Option<f64>>>,
+}
+
+impl PnlStatementService {
+    /// Correlate the daily P&L of every strategy that closed a trade within a filter
+    ///
+    /// Without a start date the range begins at the first closed trade.
+    pub async fn strategy_correlation(&self, user_id: &str, filter: &TradeFilter) -> Result<CorrelationMatrix> {
+        let trades = self.load_trades(user_id, filter.to).await?;
+        let names: HashMap<&str, &str> = trades.iter()
+            .map(|trade| (trade.strategy_id.as_str(), trade.strategy_name.as_str()))
+            .collect();
+
+        let trips: Vec<RoundTrip> = round_trips(&trades)
+            .into_iter()
+            .filter(|trip| filter.matches(session_date(trip.exited_at), &trip.strategy_id, &trip.symbol))
+            .collect();
+        let from = filter.from
+            .or_else(|| trips.iter().map(|trip| session_date(trip.exited_at)).min())
+            .unwrap_or(filter.to);
+
+        Ok(correlation_matrix(&trips, &names, from, filter.to))
+    }
+}
+
+/// Correlation matrix of round trips' P&L, summed by strategy and exit day
+pub fn correlation_matrix(
+    trips: &[RoundTrip],
+    names: &HashMap<&str, &str>,
+    from: NaiveDate,
+    to: NaiveDate,
+) -> CorrelationMatrix {
+    let mut pnl_by_strategy: BTreeMap<&str, HashMap<NaiveDate, f64>> = BTreeMap::new();
+    for trip in trips {
+        let day = session_date(trip.exited_at);
+        if day >= from && day <= to {
+            *pnl_by_strategy.entry(trip.strategy_id.as_str()).or_default().entry(day).or_default() += trip.pnl;
+        }
+    }
+
+    let series: Vec<Vec<f64>> = pnl_by_strategy.values()
+        .map(|pnl_by_day| daily_pnl_series(pnl_by_day, from, to))
+        .collect();
+    let matrix = series.iter()
+        .map(|a| series.iter().map(|b| correlation(a, b)).collect())
+        .collect();
+
+    CorrelationMatrix {
+        from,
+        to,
+        days: series.first().map_or(0, Vec::len),
+        strategies: pnl_by_strategy.iter()
+            .map(|(strategy_id, pnl_by_day)| CorrelatedStrategy {
+                strategy_id: strategy_id.to_string(),
+                strategy_name: names.get(strategy_id).copied().unwrap_or("Unknown").to_string(),
+                active_days: pnl_by_day.len(),
+                total_pnl: pnl_by_day.values().sum(),
+            })
+            .collect(),
+        matrix,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{TimeZone, Utc};
+
+    fn trip(strategy: &str, day: u32, pnl: f64) -> RoundTrip {
+        let at = Utc.with_ymd_and_hms(2024, 8, day, 5, 0, 0).unwrap();
+        RoundTrip {
+            symbol: "INFY".to_string(),
+            exchange: "NSE".to_string(),
+            strategy_id: strategy.to_string(),
+            quantity: 1,
+            entered_at: at,
+            exited_at: at,
+            pnl,
+        }
+    }
+
+    #[test]
+    fn test_correlation_matrix() {
+        // Monday 5 to Friday 9 August 2024
+        let trips = vec![
+            trip("a", 5, 100.0), trip("a", 6, -50.0), trip("a", 7, 200.0),
+            trip("b", 5, 200.0), trip("b", 6, -100.0), trip("b", 7, 400.0),
+            trip("c", 5, -100.0), trip("c", 6, 50.0), trip("c", 7, -200.0),
+            // Outside the range
+            trip("c", 12, 1000.0),
+        ];
+        let names = HashMap::from([("a", "Alpha")]);
+        let from = NaiveDate::from_ymd_opt(2024, 8, 5).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 8, 9).unwrap();
+
+        let result = correlation_matrix(&trips, &names, from, to);
+        assert_eq!(result.days, 5);
+        assert_eq!(result.strategies.len(), 3);
+        assert_eq!(result.strategies[0].strategy_name, "Alpha");
+        assert_eq!(result.strategies[2].total_pnl, -250.0);
+
+        let rounded = |value: Option<f64>| value.map(|v| (v * 1e9).round() / 1e9);
+        assert_eq!(rounded(result.matrix[0][0]), Some(1.0));
+        assert_eq!(rounded(result.matrix[0][1]), Some(1.0));
+        assert_eq!(rounded(result.matrix[0][2]), Some(-1.0));
+        assert_eq!(result.matrix[1][2], result.matrix[2][1]);
+    }
+}
@@ -1,6 +1,10 @@
 use crate::error::{HedgeXError, Result};
-use crate::models::trading::{StrategyParams, StockSelection, PerformanceMetrics};
+use crate::models::trading::{StrategyParams, StrategyRules, StrategyType, StockSelection, PerformanceMetrics, StrategyStatsSnapshot, TradeType};
+use crate::trading::strategy_stats::{StatsTrade, StrategyStatsTracker};
+use crate::models::backtesting::{EnableValidationConfig, StrategyValidationSummary};
 use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use crate::services::backtest_engine::BacktestEngine;
+use crate::trading::bars::MAX_BARS;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
 use std::collections::HashMap;
@@ -23,6 +27,12 @@ pub struct CreateStrategyRequest {
     pub stop_loss_percentage: f64,
     pub take_profit_percentage: f64,
     pub volume_threshold: i64,
+    pub strategy_type: Option<StrategyType>,
+    pub max_capital: Option<f64>,
+    pub max_quantity_per_trade: Option<i32>,
+    pub max_holding_minutes: Option<i64>,
+    pub tags: Option<Vec<String>>,
+    pub rules: Option<StrategyRules>,
 }
 
 /// Request model for updating a strategy
@@ -35,8 +45,43 @@ pub struct UpdateStrategyRequest {
     pub stop_loss_percentage: Option<f64>,
     pub take_profit_percentage: Option<f64>,
     pub volume_threshold: Option<i64>,
+    pub strategy_type: Option<StrategyType>,
+    pub max_capital: Option<f64>,
+    pub max_quantity_per_trade: Option<i32>,
+    pub max_holding_minutes: Option<i64>,
+    pub tags: Option<Vec<String>>,
+    pub rules: Option<StrategyRules>,
 }
 
+/// Field used to sort strategy listings
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub enum StrategySortField {
+    #[default]
+    Name,
+    CreatedAt,
+    UpdatedAt,
+    RiskPercentage,
+    MaxTradesPerDay,
+}
+
+/// Filters and sorting for strategy listings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct StrategyListQuery {
+    pub tag: Option<String>,
+    pub enabled: Option<bool>,
+    pub search: Option<String>,
+    #[serde(default)]
+    pub sort_by: StrategySortField,
+    #[serde(default)]
+    pub descending: bool,
+}
+
+/// Maximum number of tags per strategy
+const MAX_TAGS: usize = 20;
+
+/// Maximum length of a single tag
+const MAX_TAG_LENGTH: usize = 32;
+
 /// Strategy performance metrics
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct StrategyPerformance {
@@ -115,6 +160,8 @@ pub struct StrategyService {
     db_service: Arc<EnhancedDatabaseService>,
     strategies_cache: Arc<RwLock<HashMap<String, HashMap<String, StrategyParams>>>>, // user_id -> strategy_id -> strategy
     stock_selections_cache: Arc<RwLock<HashMap<String, Vec<StockSelection>>>>, // user_id -> selections
+    validation_config: Arc<RwLock<EnableValidationConfig>>,
+    stats_tracker: Arc<RwLock<StrategyStatsTracker>>,
 }
 
 impl StrategyService {
@@ -124,7 +171,10 @@ impl StrategyService {
             db_service,
             strategies_cache: Arc::new(RwLock::new(HashMap::new())),
             stock_selections_cache: Arc::new(RwLock::new(HashMap::new())),
+            validation_config: Arc::new(RwLock::new(EnableValidationConfig::default())),
+            stats_tracker: Arc::new(RwLock::new(StrategyStatsTracker::new())),
         };
+        service.load_stored_validation_config().await;
         
         info!("StrategyService initialized successfully");
         Ok(service)
@@ -135,7 +185,8 @@ impl StrategyService {
         let query = "
             SELECT id, user_id, name, description, enabled, max_trades_per_day,
                    risk_percentage, stop_loss_percentage, take_profit_percentage,
-                   volume_threshold, created_at, updated_at
+                   volume_threshold, strategy_type, max_capital, max_quantity_per_trade, max_holding_minutes, tags, rules,
+                   archived_at, created_at, updated_at
             FROM strategy_params 
             WHERE user_id = ?
         ";
@@ -160,6 +211,13 @@ impl StrategyService {
                 stop_loss_percentage: row.get("stop_loss_percentage"),
                 take_profit_percentage: row.get("take_profit_percentage"),
                 volume_threshold: row.get("volume_threshold"),
+                strategy_type: row.get::<String, _>("strategy_type").parse().unwrap_or_default(),
+                max_capital: row.get("max_capital"),
+                max_quantity_per_trade: row.get("max_quantity_per_trade"),
+                max_holding_minutes: row.get("max_holding_minutes"),
+                tags: serde_json::from_str(&row.get::<String, _>("tags")).unwrap_or_default(),
+                rules: row.get::<Option<String>, _>("rules").and_then(|json| serde_json::from_str(&json).ok()),
+                archived_at: row.get("archived_at"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             };
@@ -207,8 +265,49 @@ impl StrategyService {
         Ok(())
     }
     
-    /// Get all strategies for a user
+    /// Drop cached strategies and stock selections so they are read from the database again
+    pub async fn clear_cache(&self) {
+        self.strategies_cache.write().await.clear();
+        self.stock_selections_cache.write().await.clear();
+    }
+    
+    /// Get all active (non-archived) strategies for a user
     pub async fn get_strategies(&self, user_id: &str) -> Result<Vec<StrategyParams>> {
+        self.list_strategies(user_id, false).await
+    }
+    
+    /// Get active strategies matching the query's filters, sorted as requested
+    pub async fn query_strategies(&self, user_id: &str, query: &StrategyListQuery) -> Result<Vec<StrategyParams>> {
+        let search = query.search.as_deref().map(str::trim).filter(|s| !s.is_empty());
+        let tag = query.tag.as_deref().map(str::trim).filter(|t| !t.is_empty());
+        
+        let mut strategies: Vec<StrategyParams> = self.get_strategies(user_id).await?
+            .into_iter()
+            .filter(|s| query.enabled.map_or(true, |enabled| s.enabled == enabled))
+            .filter(|s| tag.map_or(true, |tag| s.has_tag(tag)))
+            .filter(|s| search.map_or(true, |text| s.matches_search(text)))
+            .collect();
+            
+        strategies.sort_by(|a, b| {
+            let ordering = match query.sort_by {
+                StrategySortField::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+                StrategySortField::CreatedAt => a.created_at.cmp(&b.created_at),
+                StrategySortField::UpdatedAt => a.updated_at.cmp(&b.updated_at),
+                StrategySortField::RiskPercentage => a.risk_percentage.total_cmp(&b.risk_percentage),
+                StrategySortField::MaxTradesPerDay => a.max_trades_per_day.cmp(&b.max_trades_per_day),
+            };
+            if query.descending { ordering.reverse() } else { ordering }
+        });
+        
+        Ok(strategies)
+    }
+    
+    /// Get archived strategies for a user
+    pub async fn get_archived_strategies(&self, user_id: &str) -> Result<Vec<StrategyParams>> {
+        self.list_strategies(user_id, true).await
+    }
+    
+    async fn list_strategies(&self, user_id: &str, archived: bool) -> Result<Vec<StrategyParams>> {
         // Load from database if not in cache
         {
             let cache = self.strategies_cache.read().await;
@@ -220,7 +319,10 @@ impl StrategyService {
         
         let cache = self.strategies_cache.read().await;
         let strategies = cache.get(user_id)
-            .map(|user_strategies| user_strategies.values().cloned().collect())
+            .map(|user_strategies| user_strategies.values()
+                .filter(|s| s.is_archived() == archived)
+                .cloned()
+                .collect())
             .unwrap_or_default();
             
         Ok(strategies)
@@ -255,8 +357,13 @@ impl StrategyService {
             request.take_profit_percentage,
             request.volume_threshold,
         )?;
+        self.validate_position_caps(request.max_capital, request.max_quantity_per_trade)?;
+        self.validate_max_holding_minutes(request.max_holding_minutes)?;
+        let tags = normalize_tags(request.tags.unwrap_or_default())?;
+        let strategy_type = request.strategy_type.unwrap_or_default();
+        self.validate_rules(strategy_type, request.rules.as_ref())?;
         
-        let strategy = StrategyParams::new(
+        let mut strategy = StrategyParams::new(
             user_id,
             &request.name,
             request.description,
@@ -266,14 +373,21 @@ impl StrategyService {
             request.take_profit_percentage,
             request.volume_threshold,
         );
+        strategy.strategy_type = strategy_type;
+        strategy.rules = request.rules;
+        strategy.max_capital = request.max_capital;
+        strategy.max_quantity_per_trade = request.max_quantity_per_trade;
+        strategy.max_holding_minutes = request.max_holding_minutes;
+        strategy.tags = tags;
         
         // Insert into database
         let query = "
             INSERT INTO strategy_params 
             (id, user_id, name, description, enabled, max_trades_per_day,
              risk_percentage, stop_loss_percentage, take_profit_percentage,
-             volume_threshold, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             volume_threshold, strategy_type, max_capital, max_quantity_per_trade,
+             max_holding_minutes, tags, rules, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ";
         
         sqlx::query(query)
@@ -287,6 +401,12 @@ impl StrategyService {
             .bind(strategy.stop_loss_percentage)
             .bind(strategy.take_profit_percentage)
             .bind(strategy.volume_threshold)
+            .bind(strategy.strategy_type.to_string())
+            .bind(strategy.max_capital)
+            .bind(strategy.max_quantity_per_trade)
+            .bind(strategy.max_holding_minutes)
+            .bind(serde_json::to_string(&strategy.tags)?)
+            .bind(strategy.rules.as_ref().map(serde_json::to_string).transpose()?)
             .bind(strategy.created_at)
             .bind(strategy.updated_at)
             .execute(self.db_service.get_database().get_pool())
@@ -315,6 +435,9 @@ impl StrategyService {
         ) {
             self.validate_strategy_params(max_trades, risk, stop_loss, take_profit, volume)?;
         }
+        self.validate_position_caps(request.max_capital, request.max_quantity_per_trade)?;
+        self.validate_max_holding_minutes(request.max_holding_minutes)?;
+        let tags = request.tags.map(normalize_tags).transpose()?;
         
         // Load from database if not in cache
         {
@@ -333,6 +456,10 @@ impl StrategyService {
                 .ok_or_else(|| HedgeXError::NotFoundError(format!("Strategy not found: {}", strategy_id)))?
         };
         
+        if strategy.is_archived() {
+            return Err(HedgeXError::ValidationError(format!("Strategy is archived: {}", strategy_id)));
+        }
+        
         // Update strategy parameters
         strategy.update(
             request.name,
@@ -343,13 +470,34 @@ impl StrategyService {
             request.take_profit_percentage,
             request.volume_threshold,
         );
+        if let Some(strategy_type) = request.strategy_type {
+            strategy.strategy_type = strategy_type;
+        }
+        if let Some(max_capital) = request.max_capital {
+            strategy.max_capital = Some(max_capital);
+        }
+        if let Some(max_quantity) = request.max_quantity_per_trade {
+            strategy.max_quantity_per_trade = Some(max_quantity);
+        }
+        if let Some(max_holding) = request.max_holding_minutes {
+            strategy.max_holding_minutes = Some(max_holding);
+        }
+        if let Some(tags) = tags {
+            strategy.tags = tags;
+        }
+        if let Some(rules) = request.rules {
+            strategy.rules = Some(rules);
+        }
+        self.validate_rules(strategy.strategy_type, strategy.rules.as_ref())?;
         
         // Update in database
         let query = "
             UPDATE strategy_params 
             SET name = ?, description = ?, max_trades_per_day = ?,
                 risk_percentage = ?, stop_loss_percentage = ?, 
-                take_profit_percentage = ?, volume_threshold = ?, updated_at = ?
+                take_profit_percentage = ?, volume_threshold = ?, strategy_type = ?,
+                max_capital = ?, max_quantity_per_trade = ?, max_holding_minutes = ?,
+                tags = ?, rules = ?, updated_at = ?
             WHERE id = ? AND user_id = ?
         ";
         
@@ -361,6 +509,12 @@ impl StrategyService {
             .bind(strategy.stop_loss_percentage)
             .bind(strategy.take_profit_percentage)
             .bind(strategy.volume_threshold)
+            .bind(strategy.strategy_type.to_string())
+            .bind(strategy.max_capital)
+            .bind(strategy.max_quantity_per_trade)
+            .bind(strategy.max_holding_minutes)
+            .bind(serde_json::to_string(&strategy.tags)?)
+            .bind(strategy.rules.as_ref().map(serde_json::to_string).transpose()?)
             .bind(strategy.updated_at)
             .bind(strategy_id)
             .bind(user_id)
@@ -385,8 +539,14 @@ impl StrategyService {
     
     /// Enable a strategy
     pub async fn enable_strategy(&self, user_id: &str, strategy_id: &str) -> Result<()> {
+        if let Some(strategy) = self.get_strategy(user_id, strategy_id).await? {
+            if strategy.is_archived() {
+                return Err(HedgeXError::ValidationError(format!("Strategy is archived: {}", strategy_id)));
+            }
+        }
+        
         // Update in database
-        let query = "UPDATE strategy_params SET enabled = true, updated_at = ? WHERE id = ? AND user_id = ?";
+        let query = "UPDATE strategy_params SET enabled = true, updated_at = ? WHERE id = ? AND user_id = ? AND archived_at IS NULL";
         
         let result = sqlx::query(query)
             .bind(Utc::now())
@@ -413,6 +573,85 @@ impl StrategyService {
         Ok(())
     }
     
+    /// Enable a strategy after a validation backtest over recent sessions
+    ///
+    /// Returns the validation summary, or None when validation is turned off.
+    /// A blocked strategy is left disabled.
+    pub async fn enable_strategy_with_validation(
+        &self,
+        user_id: &str,
+        strategy_id: &str,
+        engine: &BacktestEngine,
+    ) -> Result<Option<StrategyValidationSummary>> {
+        let config = self.get_validation_config().await;
+        if !config.enabled {
+            self.enable_strategy(user_id, strategy_id).await?;
+            return Ok(None);
+        }
+        
+        let strategy = self.get_strategy(user_id, strategy_id).await?
+            .ok_or_else(|| HedgeXError::NotFoundError(format!("Strategy not found: {}", strategy_id)))?;
+        if strategy.is_archived() {
+            return Err(HedgeXError::ValidationError(format!("Strategy is archived: {}", strategy_id)));
+        }
+        
+        let symbols: Vec<(String, String)> = self.get_active_stock_selections(user_id).await?
+            .into_iter()
+            .map(|s| (s.symbol, s.exchange))
+            .collect();
+            
+        let summary = engine.validate_strategy(&strategy, &symbols, &config).await?;
+        if summary.blocked {
+            warn!("Strategy {} failed validation and was not enabled: {}", strategy_id, summary.issues.join("; "));
+            return Ok(Some(summary));
+        }
+        
+        if !summary.passed {
+            warn!("Strategy {} enabled with validation issues: {}", strategy_id, summary.issues.join("; "));
+        }
+        
+        self.enable_strategy(user_id, strategy_id).await?;
+        Ok(Some(summary))
+    }
+    
+    /// Get the validation settings used when enabling strategies
+    pub async fn get_validation_config(&self) -> EnableValidationConfig {
+        self.validation_config.read().await.clone()
+    }
+    
+    /// Update the validation settings used when enabling strategies
+    pub async fn set_validation_config(&self, config: EnableValidationConfig) -> Result<EnableValidationConfig> {
+        check_validation_config(&config)?;
+        
+        sqlx::query("INSERT OR REPLACE INTO strategy_validation_settings (id, settings, updated_at) VALUES (1, ?, ?)")
+            .bind(serde_json::to_string(&config)?)
+            .bind(Utc::now())
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+        *self.validation_config.write().await = config.clone();
+        info!("Updated strategy validation config: {:?}", config);
+        Ok(config)
+    }
+    
+    /// Replace the default validation settings with the ones last saved, if any
+    async fn load_stored_validation_config(&self) {
+        let stored = sqlx::query_scalar::<_, String>("SELECT settings FROM strategy_validation_settings WHERE id = 1")
+            .fetch_optional(self.db_service.get_database().get_pool())
+            .await;
+        match stored {
+            Ok(Some(settings)) => match serde_json::from_str::<EnableValidationConfig>(&settings) {
+                Ok(config) if check_validation_config(&config).is_ok() => {
+                    *self.validation_config.write().await = config;
+                    info!("Loaded saved strategy validation config");
+                }
+                Ok(_) => warn!("Ignoring invalid saved strategy validation config"),
+                Err(e) => warn!("Ignoring unreadable saved strategy validation config: {}", e),
+            },
+            Ok(None) => {}
+            Err(e) => warn!("Failed to load saved strategy validation config: {}", e),
+        }
+    }
+    
     /// Disable a strategy
     pub async fn disable_strategy(&self, user_id: &str, strategy_id: &str) -> Result<()> {
         // Update in database
@@ -443,12 +682,20 @@ impl StrategyService {
         Ok(())
     }
     
-    /// Delete a strategy
+    /// Delete a strategy by archiving it
+    ///
+    /// Trades keep referencing the strategy, so it is disabled and hidden
+    /// from active lists instead of being removed.
     pub async fn delete_strategy(&self, user_id: &str, strategy_id: &str) -> Result<()> {
-        // Remove from database
-        let query = "DELETE FROM strategy_params WHERE id = ? AND user_id = ?";
+        let now = Utc::now();
+        let query = "
+            UPDATE strategy_params SET enabled = false, archived_at = ?, updated_at = ?
+            WHERE id = ? AND user_id = ? AND archived_at IS NULL
+        ";
         
         let result = sqlx::query(query)
+            .bind(now)
+            .bind(now)
             .bind(strategy_id)
             .bind(user_id)
             .execute(self.db_service.get_database().get_pool())
@@ -458,18 +705,53 @@ impl StrategyService {
             return Err(HedgeXError::NotFoundError(format!("Strategy not found: {}", strategy_id)));
         }
         
-        // Remove from cache
+        // Update cache
         {
             let mut cache = self.strategies_cache.write().await;
             if let Some(user_strategies) = cache.get_mut(user_id) {
-                user_strategies.remove(strategy_id);
+                if let Some(strategy) = user_strategies.get_mut(strategy_id) {
+                    strategy.archive();
+                }
             }
         }
         
-        info!("Deleted strategy {} for user {}", strategy_id, user_id);
+        info!("Archived strategy {} for user {}", strategy_id, user_id);
         Ok(())
     }
     
+    /// Restore an archived strategy; it stays disabled until enabled again
+    pub async fn restore_strategy(&self, user_id: &str, strategy_id: &str) -> Result<StrategyParams> {
+        let query = "
+            UPDATE strategy_params SET archived_at = NULL, updated_at = ?
+            WHERE id = ? AND user_id = ? AND archived_at IS NOT NULL
+        ";
+        
+        let result = sqlx::query(query)
+            .bind(Utc::now())
+            .bind(strategy_id)
+            .bind(user_id)
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+            
+        if result.rows_affected() == 0 {
+            return Err(HedgeXError::NotFoundError(format!("Archived strategy not found: {}", strategy_id)));
+        }
+        
+        // Update cache
+        {
+            let mut cache = self.strategies_cache.write().await;
+            if let Some(user_strategies) = cache.get_mut(user_id) {
+                if let Some(strategy) = user_strategies.get_mut(strategy_id) {
+                    strategy.restore();
+                }
+            }
+        }
+        
+        info!("Restored strategy {} for user {}", strategy_id, user_id);
+        self.get_strategy(user_id, strategy_id).await?
+            .ok_or_else(|| HedgeXError::NotFoundError(format!("Strategy not found: {}", strategy_id)))
+    }
+    
     /// Get NIFTY 50 stock list
     pub fn get_nifty_50_stocks(&self) -> Vec<(String, String)> {
         NIFTY_50_STOCKS.iter()
@@ -502,10 +784,11 @@ impl StrategyService {
     
     /// Add stock to selection
     pub async fn add_stock_selection(&self, user_id: &str, symbol: &str, exchange: &str) -> Result<StockSelection> {
-        // Validate that the symbol is in NIFTY 50
-        let is_valid_symbol = NIFTY_50_STOCKS.iter().any(|(s, _)| s == &symbol);
+        // Validate that the symbol is in NIFTY 50 or the instruments master
+        let is_valid_symbol = NIFTY_50_STOCKS.iter().any(|(s, _)| s == &symbol)
+            || self.is_known_instrument(symbol, exchange).await;
         if !is_valid_symbol {
-            return Err(HedgeXError::ValidationError(format!("Symbol {} is not in NIFTY 50", symbol)));
+            return Err(HedgeXError::ValidationError(format!("Symbol {} is not in NIFTY 50 or the instruments list", symbol)));
         }
         
         let stock = StockSelection::new(user_id, symbol, exchange);
@@ -544,6 +827,17 @@ impl StrategyService {
         Ok(stock)
     }
     
+    /// Check whether a symbol exists in the instruments master
+    async fn is_known_instrument(&self, symbol: &str, exchange: &str) -> bool {
+        sqlx::query("SELECT 1 FROM instruments WHERE tradingsymbol = ? AND exchange = ?")
+            .bind(symbol)
+            .bind(exchange)
+            .fetch_optional(self.db_service.get_database().get_pool())
+            .await
+            .map(|row| row.is_some())
+            .unwrap_or(false)
+    }
+    
     /// Remove stock from selection
     pub async fn remove_stock_selection(&self, user_id: &str, symbol: &str) -> Result<()> {
         // Update in database
@@ -755,6 +1049,134 @@ impl StrategyService {
         Ok(())
     }
     
+    /// Fold trades recorded since the last refresh into the live stats
+    ///
+    /// Returns the strategy snapshots that changed since the previous refresh.
+    pub async fn refresh_live_stats(&self, user_id: &str) -> Result<Vec<StrategyStatsSnapshot>> {
+        let strategies = self.get_strategies(user_id).await?;
+        let database = self.db_service.get_database();
+        let pool = database.get_pool();
+        let today = Utc::now().date_naive();
+        
+        let mut tracker = self.stats_tracker.write().await;
+        tracker.roll_day(today);
+        
+        // New trades since the last refresh
+        let mut rows = sqlx::query(
+            "SELECT rowid, id, strategy_id, symbol, trade_type, quantity, price, status
+             FROM trades
+             WHERE user_id = ? AND DATE(executed_at) = ? AND rowid > ?
+             ORDER BY rowid"
+        )
+        .bind(user_id)
+        .bind(today)
+        .bind(tracker.last_rowid())
+        .fetch_all(pool)
+        .await?;
+        
+        // Earlier trades that were still pending
+        let pending = tracker.pending_trade_ids();
+        if !pending.is_empty() {
+            let placeholders = vec!["?"; pending.len()].join(", ");
+            let query = format!(
+                "SELECT rowid, id, strategy_id, symbol, trade_type, quantity, price, status
+                 FROM trades WHERE id IN ({}) AND status != 'Pending'",
+                placeholders
+            );
+            let mut pending_query = sqlx::query(&query);
+            for id in &pending {
+                pending_query = pending_query.bind(id);
+            }
+            rows.extend(pending_query.fetch_all(pool).await?);
+        }
+        
+        for row in rows {
+            let trade_type = match TradeType::from_str(&row.get::<String, _>("trade_type")) {
+                Ok(trade_type) => trade_type,
+                Err(e) => {
+                    warn!("Skipping trade in live stats: {}", e);
+                    continue;
+                }
+            };
+            
+            let trade = StatsTrade {
+                id: row.get("id"),
+                strategy_id: row.get("strategy_id"),
+                symbol: row.get("symbol"),
+                trade_type,
+                quantity: row.get("quantity"),
+                price: row.get("price"),
+                executed: row.get::<String, _>("status") == "Executed",
+            };
+            tracker.record(row.get("rowid"), &trade);
+        }
+        
+        Ok(tracker.changed_snapshots(&strategies))
+    }
+    
+    /// Get the live stats of every active strategy
+    pub async fn get_live_stats(&self, user_id: &str) -> Result<Vec<StrategyStatsSnapshot>> {
+        let strategies = self.get_strategies(user_id).await?;
+        let tracker = self.stats_tracker.read().await;
+        Ok(strategies.iter().map(|strategy| tracker.snapshot(strategy)).collect())
+    }
+    
+    /// Validate optional per-order capital and quantity caps
+    pub fn validate_position_caps(&self, max_capital: Option<f64>, max_quantity_per_trade: Option<i32>) -> Result<()> {
+        if let Some(max_capital) = max_capital {
+            if !max_capital.is_finite() || max_capital <= 0.0 || max_capital > 100_000_000.0 {
+                return Err(HedgeXError::ValidationError(
+                    "Max capital must be between 0 and 100000000".to_string()
+                ));
+            }
+        }
+        
+        if let Some(max_quantity) = max_quantity_per_trade {
+            if max_quantity <= 0 || max_quantity > 100_000 {
+                return Err(HedgeXError::ValidationError(
+                    "Max quantity per trade must be between 1 and 100000".to_string()
+                ));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Validate the maximum holding period (up to one week)
+    pub fn validate_max_holding_minutes(&self, max_holding_minutes: Option<i64>) -> Result<()> {
+        if let Some(minutes) = max_holding_minutes {
+            if minutes <= 0 || minutes > 10_080 {
+                return Err(HedgeXError::ValidationError(
+                    "Max holding period must be between 1 and 10080 minutes".to_string()
+                ));
+            }
+        }
+        
+        Ok(())
+    }
+    
+    /// Validate custom rules; custom strategies must have them
+    pub fn validate_rules(&self, strategy_type: StrategyType, rules: Option<&StrategyRules>) -> Result<()> {
+        let rules = match rules {
+            Some(rules) => rules,
+            None if strategy_type == StrategyType::Custom => {
+                return Err(HedgeXError::ValidationError("Custom strategies need entry rules".to_string()));
+            }
+            None => return Ok(()),
+        };
+        
+        rules.validate().map_err(HedgeXError::ValidationError)?;
+        
+        // Live evaluation only keeps MAX_BARS bars per symbol
+        if rules.warm_up() > MAX_BARS {
+            return Err(HedgeXError::ValidationError(format!(
+                "Rules need {} bars of history, more than the {} available", rules.warm_up(), MAX_BARS
+            )));
+        }
+        
+        Ok(())
+    }
+    
     /// Get strategy statistics
     pub async fn get_strategy_stats(&self, user_id: &str, strategy_id: &str) -> Result<HashMap<String, serde_json::Value>> {
         let today = Utc::now().date_naive();
@@ -819,9 +1241,48 @@ impl StrategyService {
     }
 }
 
+/// Trim, lowercase and de-duplicate tags, rejecting oversized ones
+fn normalize_tags(tags: Vec<String>) -> Result<Vec<String>> {
+    let mut normalized: Vec<String> = Vec::new();
+    
+    for tag in tags {
+        let tag = tag.trim().to_lowercase();
+        if tag.is_empty() || normalized.contains(&tag) {
+            continue;
+        }
+        if tag.chars().count() > MAX_TAG_LENGTH {
+            return Err(HedgeXError::ValidationError(format!(
+                "Tags must be at most {} characters: {}", MAX_TAG_LENGTH, tag
+            )));
+        }
+        normalized.push(tag);
+    }
+    
+    if normalized.len() > MAX_TAGS {
+        return Err(HedgeXError::ValidationError(format!("A strategy can have at most {} tags", MAX_TAGS)));
+    }
+    
+    Ok(normalized)
+}
+
+/// Reject validation settings outside the supported session and loss ranges
+fn check_validation_config(config: &EnableValidationConfig) -> Result<()> {
+    if config.sessions == 0 || config.sessions > 30 {
+        return Err(HedgeXError::ValidationError("Validation sessions must be between 1 and 30".to_string()));
+    }
+    
+    if config.max_loss_percentage <= 0.0 || config.max_loss_percentage > 100.0 {
+        return Err(HedgeXError::ValidationError("Maximum validation loss must be between 0 and 100 percent".to_string()));
+    }
+    
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::backtesting::ValidationMode;
+    use crate::models::trading::{IndicatorSource, RuleComparison, RuleCondition};
     use crate::services::enhanced_database_service::EnhancedDatabaseService;
     use tempfile::tempdir;
     use std::path::PathBuf;
@@ -866,6 +1327,13 @@ mod tests {
                 stop_loss_percentage REAL NOT NULL DEFAULT 0.5,
                 take_profit_percentage REAL NOT NULL DEFAULT 1.5,
                 volume_threshold INTEGER NOT NULL DEFAULT 100000,
+                strategy_type TEXT NOT NULL DEFAULT 'BollingerMeanReversion',
+                max_capital REAL,
+                max_quantity_per_trade INTEGER,
+                max_holding_minutes INTEGER,
+                tags TEXT NOT NULL DEFAULT '[]',
+                rules TEXT,
+                archived_at TIMESTAMP,
                 created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
                 updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
                 FOREIGN KEY (user_id) REFERENCES users(id) ON DELETE CASCADE
@@ -940,6 +1408,18 @@ mod tests {
         .await
         .unwrap();
         
+        // Strategy validation settings table
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS strategy_validation_settings (
+                id INTEGER PRIMARY KEY CHECK (id = 1),
+                settings TEXT NOT NULL,
+                updated_at TIMESTAMP NOT NULL
+            )"
+        )
+        .execute(pool)
+        .await
+        .unwrap();
+        
         // Insert test user
         sqlx::query("INSERT INTO users (id, username, password_hash) VALUES (?, ?, ?)")
             .bind("test_user")
@@ -975,6 +1455,12 @@ mod tests {
             stop_loss_percentage: 1.0,
             take_profit_percentage: 3.0,
             volume_threshold: 100000,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
         };
         
         let strategy = service.create_strategy("test_user", request).await.unwrap();
@@ -1000,6 +1486,12 @@ mod tests {
             stop_loss_percentage: 0.8,
             take_profit_percentage: 2.0,
             volume_threshold: 50000,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
         };
         
         let created_strategy = service.create_strategy("test_user", request).await.unwrap();
@@ -1026,6 +1518,12 @@ mod tests {
             stop_loss_percentage: 0.8,
             take_profit_percentage: 2.0,
             volume_threshold: 50000,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
         };
         
         let strategy = service.create_strategy("test_user", create_request).await.unwrap();
@@ -1039,6 +1537,12 @@ mod tests {
             stop_loss_percentage: None,
             take_profit_percentage: None,
             volume_threshold: None,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
         };
         
         let updated_strategy = service.update_strategy("test_user", &strategy.id, update_request).await.unwrap();
@@ -1067,6 +1571,12 @@ mod tests {
             stop_loss_percentage: 0.8,
             take_profit_percentage: 2.0,
             volume_threshold: 50000,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
         };
         
         let strategy = service.create_strategy("test_user", request).await.unwrap();
@@ -1099,6 +1609,12 @@ mod tests {
             stop_loss_percentage: 0.8,
             take_profit_percentage: 2.0,
             volume_threshold: 50000,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
         };
         
         let strategy = service.create_strategy("test_user", request).await.unwrap();
@@ -1108,11 +1624,170 @@ mod tests {
         assert!(found_strategy.is_some());
         
         // Delete the strategy
+        service.enable_strategy("test_user", &strategy.id).await.unwrap();
+        service.delete_strategy("test_user", &strategy.id).await.unwrap();
+        
+        // Verify strategy is archived and hidden from the active list
+        let deleted_strategy = service.get_strategy("test_user", &strategy.id).await.unwrap().unwrap();
+        assert!(deleted_strategy.is_archived());
+        assert!(!deleted_strategy.enabled);
+        assert!(service.get_strategies("test_user").await.unwrap().is_empty());
+        assert_eq!(service.get_archived_strategies("test_user").await.unwrap().len(), 1);
+        
+        // Archived strategies can't be enabled or deleted again
+        assert!(service.enable_strategy("test_user", &strategy.id).await.is_err());
+        assert!(service.delete_strategy("test_user", &strategy.id).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_enable_strategy_with_validation() {
+        let (db_service, _) = setup_test_db().await;
+        let engine = BacktestEngine::new(Arc::new(db_service.get_database().get_pool().clone()));
+        let service = StrategyService::new(db_service).await.unwrap();
+        
+        let request = CreateStrategyRequest {
+            name: "Test Strategy".to_string(),
+            description: None,
+            max_trades_per_day: 5,
+            risk_percentage: 1.5,
+            stop_loss_percentage: 0.8,
+            take_profit_percentage: 2.0,
+            volume_threshold: 50000,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
+        };
+        let strategy = service.create_strategy("test_user", request).await.unwrap();
+        
+        // Without recent data the run is reported but never blocks
+        let mut config = EnableValidationConfig::default();
+        config.mode = ValidationMode::Block;
+        service.set_validation_config(config.clone()).await.unwrap();
+        
+        let summary = service.enable_strategy_with_validation("test_user", &strategy.id, &engine).await.unwrap().unwrap();
+        assert!(!summary.blocked);
+        assert!(!summary.issues.is_empty());
+        assert!(service.get_strategy("test_user", &strategy.id).await.unwrap().unwrap().enabled);
+        
+        // Turning validation off skips the backtest
+        service.disable_strategy("test_user", &strategy.id).await.unwrap();
+        config.enabled = false;
+        service.set_validation_config(config.clone()).await.unwrap();
+        assert!(service.enable_strategy_with_validation("test_user", &strategy.id, &engine).await.unwrap().is_none());
+        
+        config.sessions = 0;
+        assert!(service.set_validation_config(config).await.is_err());
+        
+        // The saved settings outlive the service
+        let reloaded = StrategyService::new(service.db_service.clone()).await.unwrap();
+        let saved = reloaded.get_validation_config().await;
+        assert!(!saved.enabled);
+        assert_eq!(saved.sessions, 5);
+    }
+    
+    #[tokio::test]
+    async fn test_query_strategies() {
+        let (db_service, _) = setup_test_db().await;
+        let service = StrategyService::new(db_service).await.unwrap();
+        
+        for (name, risk, tags) in [
+            ("Banks Breakout", 2.0, vec!["Banking", " intraday ", "banking"]),
+            ("IT Reversion", 1.0, vec!["it"]),
+            ("Auto Swing", 3.0, vec![]),
+        ] {
+            let request = CreateStrategyRequest {
+                name: name.to_string(),
+                description: Some(format!("{} strategy", name)),
+                max_trades_per_day: 5,
+                risk_percentage: risk,
+                stop_loss_percentage: 0.8,
+                take_profit_percentage: 2.0,
+                volume_threshold: 50000,
+                strategy_type: None,
+                max_capital: None,
+                max_quantity_per_trade: None,
+                tags: Some(tags.into_iter().map(String::from).collect()),
+                rules: None,
+            };
+            service.create_strategy("test_user", request).await.unwrap();
+        }
+        
+        // Tags are normalized on save
+        let all = service.query_strategies("test_user", &StrategyListQuery::default()).await.unwrap();
+        let names: Vec<&str> = all.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["Auto Swing", "Banks Breakout", "IT Reversion"]);
+        assert_eq!(all[1].tags, vec!["banking".to_string(), "intraday".to_string()]);
+        
+        let query = StrategyListQuery { tag: Some("BANKING".to_string()), ..Default::default() };
+        assert_eq!(service.query_strategies("test_user", &query).await.unwrap().len(), 1);
+        
+        let query = StrategyListQuery { search: Some("reversion".to_string()), ..Default::default() };
+        assert_eq!(service.query_strategies("test_user", &query).await.unwrap()[0].name, "IT Reversion");
+        
+        let query = StrategyListQuery {
+            sort_by: StrategySortField::RiskPercentage,
+            descending: true,
+            ..Default::default()
+        };
+        assert_eq!(service.query_strategies("test_user", &query).await.unwrap()[0].name, "Auto Swing");
+        
+        service.enable_strategy("test_user", &all[2].id).await.unwrap();
+        let query = StrategyListQuery { enabled: Some(true), ..Default::default() };
+        assert_eq!(service.query_strategies("test_user", &query).await.unwrap().len(), 1);
+        
+        // Tags can be replaced on update, and oversized tags are rejected
+        let update = UpdateStrategyRequest {
+            name: None,
+            description: None,
+            max_trades_per_day: None,
+            risk_percentage: None,
+            stop_loss_percentage: None,
+            take_profit_percentage: None,
+            volume_threshold: None,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            tags: Some(vec!["x".repeat(40)]),
+            rules: None,
+        };
+        assert!(service.update_strategy("test_user", &all[0].id, update).await.is_err());
+    }
+    
+    #[tokio::test]
+    async fn test_restore_strategy() {
+        let (db_service, _) = setup_test_db().await;
+        let service = StrategyService::new(db_service).await.unwrap();
+        
+        let request = CreateStrategyRequest {
+            name: "Test Strategy".to_string(),
+            description: None,
+            max_trades_per_day: 5,
+            risk_percentage: 1.5,
+            stop_loss_percentage: 0.8,
+            take_profit_percentage: 2.0,
+            volume_threshold: 50000,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
+        };
+        
+        let strategy = service.create_strategy("test_user", request).await.unwrap();
+        assert!(service.restore_strategy("test_user", &strategy.id).await.is_err());
+        
         service.delete_strategy("test_user", &strategy.id).await.unwrap();
         
-        // Verify strategy is deleted
-        let deleted_strategy = service.get_strategy("test_user", &strategy.id).await.unwrap();
-        assert!(deleted_strategy.is_none());
+        // Restored strategies come back disabled
+        let restored = service.restore_strategy("test_user", &strategy.id).await.unwrap();
+        assert!(!restored.is_archived());
+        assert!(!restored.enabled);
+        assert_eq!(service.get_strategies("test_user").await.unwrap().len(), 1);
+        assert!(service.get_archived_strategies("test_user").await.unwrap().is_empty());
     }
     
     #[tokio::test]
@@ -1208,6 +1883,33 @@ mod tests {
         // Invalid volume threshold
         assert!(service.validate_strategy_params(10, 2.0, 1.0, 3.0, 0).is_err());
         assert!(service.validate_strategy_params(10, 2.0, 1.0, 3.0, -1000).is_err());
+        
+        // Position caps are optional but must be positive
+        assert!(service.validate_position_caps(None, None).is_ok());
+        assert!(service.validate_position_caps(Some(50000.0), Some(100)).is_ok());
+        assert!(service.validate_position_caps(Some(0.0), None).is_err());
+        assert!(service.validate_position_caps(Some(f64::NAN), None).is_err());
+        assert!(service.validate_position_caps(None, Some(0)).is_err());
+        assert!(service.validate_position_caps(None, Some(100_001)).is_err());
+        
+        // Holding period is optional, up to one week
+        assert!(service.validate_max_holding_minutes(None).is_ok());
+        assert!(service.validate_max_holding_minutes(Some(60)).is_ok());
+        assert!(service.validate_max_holding_minutes(Some(0)).is_err());
+        assert!(service.validate_max_holding_minutes(Some(10_081)).is_err());
+        
+        // Custom strategies need rules with sensible indicator settings
+        let rule = |right| RuleCondition { left: IndicatorSource::Close, comparison: RuleComparison::Above, right };
+        let rules = |right| StrategyRules { entry: vec![rule(right)], exit: Vec::new() };
+        assert!(service.validate_rules(StrategyType::MomentumBreakout, None).is_ok());
+        assert!(service.validate_rules(StrategyType::Custom, None).is_err());
+        assert!(service.validate_rules(StrategyType::Custom, Some(&rules(IndicatorSource::Sma { period: 20 }))).is_ok());
+        assert!(service.validate_rules(StrategyType::Custom, Some(&rules(IndicatorSource::Sma { period: 0 }))).is_err());
+        assert!(service.validate_rules(StrategyType::Custom, Some(&rules(IndicatorSource::Constant { value: f64::NAN }))).is_err());
+        assert!(service.validate_rules(StrategyType::Custom, Some(&StrategyRules { entry: Vec::new(), exit: Vec::new() })).is_err());
+        
+        // An EMA this long could never warm up in the live bar buffer
+        assert!(service.validate_rules(StrategyType::Custom, Some(&rules(IndicatorSource::Ema { period: 200 }))).is_err());
     }
     
     #[tokio::test]
@@ -1241,6 +1943,12 @@ mod tests {
             stop_loss_percentage: 0.8,
             take_profit_percentage: 2.0,
             volume_threshold: 50000,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
         };
         
         let strategy = service.create_strategy("test_user", request).await.unwrap();
@@ -1253,6 +1961,77 @@ mod tests {
         assert_eq!(stats.get("total_trades").unwrap().as_i64().unwrap(), 0);
     }
     
+    #[tokio::test]
+    async fn test_live_strategy_stats() {
+        let (db_service, _) = setup_test_db().await;
+        let pool = db_service.get_database().get_pool().clone();
+        let service = StrategyService::new(db_service).await.unwrap();
+        
+        let request = CreateStrategyRequest {
+            name: "Test Strategy".to_string(),
+            description: None,
+            max_trades_per_day: 5,
+            risk_percentage: 1.5,
+            stop_loss_percentage: 0.8,
+            take_profit_percentage: 2.0,
+            volume_threshold: 50000,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
+        };
+        let strategy = service.create_strategy("test_user", request).await.unwrap();
+        
+        let insert_trade = |id: &'static str, trade_type: &'static str, price: f64, status: &'static str| {
+            let pool = pool.clone();
+            let strategy_id = strategy.id.clone();
+            async move {
+                sqlx::query(
+                    "INSERT INTO trades (id, user_id, symbol, exchange, trade_type, quantity, price, status, executed_at, strategy_id)
+                     VALUES (?, 'test_user', 'RELIANCE', 'NSE', ?, 10, ?, ?, ?, ?)"
+                )
+                .bind(id)
+                .bind(trade_type)
+                .bind(price)
+                .bind(status)
+                .bind(Utc::now())
+                .bind(strategy_id)
+                .execute(&pool)
+                .await
+                .unwrap();
+            }
+        };
+        
+        // First refresh reports every strategy, later ones only changes
+        assert_eq!(service.refresh_live_stats("test_user").await.unwrap().len(), 1);
+        assert!(service.refresh_live_stats("test_user").await.unwrap().is_empty());
+        
+        insert_trade("t1", "Buy", 100.0, "Executed").await;
+        insert_trade("t2", "Sell", 104.0, "Pending").await;
+        
+        let changed = service.refresh_live_stats("test_user").await.unwrap();
+        assert_eq!(changed.len(), 1);
+        assert_eq!(changed[0].trades_today, 2);
+        assert_eq!(changed[0].remaining_trades, 3);
+        assert_eq!(changed[0].open_positions, 1);
+        
+        // Pending trades are picked up once they fill
+        sqlx::query("UPDATE trades SET status = 'Executed' WHERE id = 't2'")
+            .execute(&pool)
+            .await
+            .unwrap();
+        
+        let changed = service.refresh_live_stats("test_user").await.unwrap();
+        assert_eq!(changed[0].trades_today, 2);
+        assert_eq!(changed[0].open_positions, 0);
+        assert!((changed[0].pnl_today - 40.0).abs() < 1e-9);
+        
+        let live = service.get_live_stats("test_user").await.unwrap();
+        assert_eq!(live, changed);
+    }
+    
     #[tokio::test]
     async fn test_strategy_performance_metrics() {
         let (db_service, _) = setup_test_db().await;
@@ -1267,6 +2046,12 @@ mod tests {
             stop_loss_percentage: 0.8,
             take_profit_percentage: 2.0,
             volume_threshold: 50000,
+            strategy_type: None,
+            max_capital: None,
+            max_quantity_per_trade: None,
+            max_holding_minutes: None,
+            tags: None,
+            rules: None,
         };
         
         let strategy = service.create_strategy("test_user", request).await.unwrap();
@@ -1296,4 +2081,4 @@ mod tests {
         assert_eq!(performance[0].profitable_trades, 6);
         assert_eq!(performance[0].win_rate, 60.0);
     }
-}
\ No newline at end of file
+}
@@ -0,0 +1,464 @@
+use crate::error::Result;
+use crate::models::backtesting::{CostModel, Slippage};
+use crate::models::trading::TradeType;
+use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use crate::trading::session::session_date;
+use chrono::{DateTime, Datelike, Months, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use serde::{Deserialize, Serialize};
+use sqlx::Row;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::Arc;
+
+/// STT on equity delivery, charged on both the buy and the sell, percentage of turnover
+const DELIVERY_STT_PERCENTAGE: f64 = 0.1;
+
+/// How a closed position's gain is taxed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TaxCategory {
+    /// Bought and sold the same day; business income taxed at slab rates
+    SpeculativeIntraday,
+    /// Delivery held twelve months or less; section 111A
+    ShortTermDelivery,
+    /// Delivery held over twelve months; section 112A
+    LongTermDelivery,
+}
+
+/// Rates the tax estimate uses, in percent
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxRates {
+    /// Marginal slab rate applied to speculative income
+    pub slab_rate: f64,
+    pub short_term_rate: f64,
+    pub long_term_rate: f64,
+    /// Long-term gains exempt each year, in rupees
+    pub long_term_exemption: f64,
+    /// Health and education cess on the tax
+    pub cess_rate: f64,
+}
+
+impl Default for TaxRates {
+    fn default() -> Self {
+        Self {
+            slab_rate: 30.0,
+            short_term_rate: 20.0,
+            long_term_rate: 12.5,
+            long_term_exemption: 125_000.0,
+            cess_rate: 4.0,
+        }
+    }
+}
+
+/// An executed trade as the report reads it
+#[derive(Debug, Clone)]
+pub struct ReportTrade {
+    pub symbol: String,
+    pub exchange: String,
+    pub trade_type: TradeType,
+    pub quantity: i64,
+    pub price: f64,
+    pub executed_at: DateTime<Utc>,
+}
+
+/// Quantity bought and later sold, matched first in first out
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ClosedPosition {
+    pub symbol: String,
+    pub exchange: String,
+    pub category: TaxCategory,
+    pub quantity: i64,
+    pub buy_date: NaiveDate,
+    pub sell_date: NaiveDate,
+    pub buy_value: f64,
+    pub sell_value: f64,
+    /// Estimated brokerage, taxes and fees of both legs
+    pub charges: f64,
+    pub gross_pnl: f64,
+    pub net_pnl: f64,
+}
+
+/// Totals of one tax category
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CategorySummary {
+    pub category: TaxCategory,
+    pub positions: usize,
+    /// Sum of absolute P&L for speculative trades, sale value for delivery
+    pub turnover: f64,
+    pub gross_profit: f64,
+    pub charges: f64,
+    pub net_profit: f64,
+}
+
+/// Tax the year's profits would attract, before carried-forward losses
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct TaxEstimate {
+    pub speculative_tax: f64,
+    pub short_term_tax: f64,
+    pub long_term_tax: f64,
+    pub cess: f64,
+    pub total: f64,
+}
+
+/// Classified trades, turnover and estimated tax of one financial year
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxReport {
+    pub user_id: String,
+    /// Financial year label, such as "2024-25"
+    pub financial_year: String,
+    pub from: NaiveDate,
+    pub to: NaiveDate,
+    pub categories: Vec<CategorySummary>,
+    pub tax: TaxEstimate,
+    pub rates: TaxRates,
+    pub positions: Vec<ClosedPosition>,
+    /// Quantity sold with no recorded purchase, such as holdings bought outside HedgeX
+    pub unmatched_sell_quantity: i64,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// First and last day of the financial year starting in April of `start_year`
+pub fn financial_year_bounds(start_year: i32) -> (NaiveDate, NaiveDate) {
+    (
+        NaiveDate::from_ymd_opt(start_year, 4, 1).unwrap(),
+        NaiveDate::from_ymd_opt(start_year + 1, 3, 31).unwrap(),
+    )
+}
+
+/// Start year of the financial year a date falls in
+pub fn financial_year_of(date: NaiveDate) -> i32 {
+    if date.month() >= 4 { date.year() } else { date.year() - 1 }
+}
+
+/// Builds tax and turnover reports from the user's executed trades
+pub struct TaxReportService {
+    db_service: Arc<EnhancedDatabaseService>,
+}
+
+impl TaxReportService {
+    /// Create a new tax report service
+    pub fn new(db_service: Arc<EnhancedDatabaseService>) -> Self {
+        Self { db_service }
+    }
+
+    /// Report on the financial year starting in April of `start_year`
+    pub async fn generate(&self, user_id: &str, start_year: i32, rates: TaxRates) -> Result<TaxReport> {
+        let (from, to) = financial_year_bounds(start_year);
+
+        // Earlier years' trades are read too, as they hold the cost of lots sold this year
+        let rows = sqlx::query(
+            "SELECT symbol, exchange, trade_type, quantity, price, executed_at
+             FROM trades
+             WHERE user_id = ? AND status = 'Executed' AND date(executed_at, '+330 minutes') <= ?
+             ORDER BY executed_at ASC"
+        )
+            .bind(user_id)
+            .bind(to.format("%Y-%m-%d").to_string())
+            .fetch_all(self.db_service.get_database().get_read_pool())
+            .await?;
+
+        let trades: Vec<ReportTrade> = rows.iter()
+            .map(|row| ReportTrade {
+                symbol: row.get("symbol"),
+                exchange: row.get("exchange"),
+                trade_type: if row.get::<String, _>("trade_type") == "Sell" { TradeType::Sell } else { TradeType::Buy },
+                quantity: row.get("quantity"),
+                price: row.get("price"),
+                executed_at: row.get("executed_at"),
+            })
+            .collect();
+
+        let mut report = build_report(&trades, from, to, rates);
+        report.user_id = user_id.to_string();
+        Ok(report)
+    }
+}
+
+/// Match trades into closed positions and total those closed between `from` and `to`
+///
+/// Each symbol's buys and sells on the same day are squared off against each other
+/// first as speculative trades. What is left of the day's buys is carried as delivery
+/// lots, and what is left of its sells closes the oldest lots.
+pub fn build_report(trades: &[ReportTrade], from: NaiveDate, to: NaiveDate, rates: TaxRates) -> TaxReport {
+    let mut by_symbol_day: BTreeMap<(&str, &str), BTreeMap<NaiveDate, Vec<&ReportTrade>>> = BTreeMap::new();
+    for trade in trades {
+        by_symbol_day.entry((trade.exchange.as_str(), trade.symbol.as_str()))
+            .or_default()
+            .entry(session_date(trade.executed_at))
+            .or_default()
+            .push(trade);
+    }
+
+    let intraday_costs = CostModel::default();
+    let delivery_costs = CostModel {
+        brokerage_percentage: 0.0,
+        stt_sell_percentage: DELIVERY_STT_PERCENTAGE,
+        stamp_duty_buy_percentage: 0.015,
+        slippage: Slippage::Ticks(0),
+        ..CostModel::default()
+    };
+
+    let mut positions = Vec::new();
+    let mut unmatched_sell_quantity = 0;
+
+    for ((exchange, symbol), days) in by_symbol_day {
+        // Delivery lots of (bought on, quantity, price), oldest first
+        let mut lots: VecDeque<(NaiveDate, i64, f64)> = VecDeque::new();
+
+        for (day, day_trades) in days {
+            let mut buys: VecDeque<(i64, f64)> = VecDeque::new();
+            let mut sells: VecDeque<(i64, f64)> = VecDeque::new();
+            for trade in day_trades {
+                match trade.trade_type {
+                    TradeType::Buy => buys.push_back((trade.quantity, trade.price)),
+                    TradeType::Sell => sells.push_back((trade.quantity, trade.price)),
+                }
+            }
+
+            let squared_off = total_quantity(&buys).min(total_quantity(&sells));
+            if squared_off > 0 {
+                let buy_value = take(&mut buys, squared_off);
+                let sell_value = take(&mut sells, squared_off);
+                let charges = leg_charges(&intraday_costs, TradeType::Buy, buy_value, squared_off)
+                    + leg_charges(&intraday_costs, TradeType::Sell, sell_value, squared_off);
+                positions.push(closed_position(
+                    symbol, exchange, TaxCategory::SpeculativeIntraday, squared_off, (day, day), (buy_value, sell_value), charges,
+                ));
+            }
+
+            lots.extend(buys.into_iter().map(|(quantity, price)| (day, quantity, price)));
+
+            for (mut quantity, sell_price) in sells {
+                while quantity > 0 {
+                    let Some(lot) = lots.front_mut() else {
+                        unmatched_sell_quantity += quantity;
+                        break;
+                    };
+                    let (bought_on, lot_quantity, buy_price) = *lot;
+                    let matched = quantity.min(lot_quantity);
+                    if matched == lot_quantity {
+                        lots.pop_front();
+                    } else {
+                        lot.1 -= matched;
+                    }
+                    quantity -= matched;
+
+                    let held_long = bought_on.checked_add_months(Months::new(12)).is_some_and(|year_on| day > year_on);
+                    let category = if held_long { TaxCategory::LongTermDelivery } else { TaxCategory::ShortTermDelivery };
+                    let buy_value = buy_price * matched as f64;
+                    let sell_value = sell_price * matched as f64;
+                    // CostModel only charges STT on sells; delivery pays it on the buy too
+                    let charges = leg_charges(&delivery_costs, TradeType::Buy, buy_value, matched)
+                        + buy_value * DELIVERY_STT_PERCENTAGE / 100.0
+                        + leg_charges(&delivery_costs, TradeType::Sell, sell_value, matched);
+                    positions.push(closed_position(
+                        symbol, exchange, category, matched, (bought_on, day), (buy_value, sell_value), charges,
+                    ));
+                }
+            }
+        }
+    }
+
+    positions.retain(|position| position.sell_date >= from && position.sell_date <= to);
+    positions.sort_by(|a, b| a.sell_date.cmp(&b.sell_date).then_with(|| a.symbol.cmp(&b.symbol)));
+
+    let categories: Vec<CategorySummary> = [
+        TaxCategory::SpeculativeIntraday,
+        TaxCategory::ShortTermDelivery,
+        TaxCategory::LongTermDelivery,
+    ]
+        .into_iter()
+        .map(|category| summarize(category, &positions))
+        .collect();
+
+    TaxReport {
+        user_id: String::new(),
+        financial_year: format!("{}-{:02}", from.year(), (from.year() + 1) % 100),
+        from,
+        to,
+        tax: estimate_tax(&categories, &rates),
+        categories,
+        rates,
+        positions,
+        unmatched_sell_quantity,
+        generated_at: Utc::now(),
+    }
+}
+
+fn total_quantity(fills: &VecDeque<(i64, f64)>) -> i64 {
+    fills.iter().map(|(quantity, _)| quantity).sum()
+}
+
+/// Remove `quantity` from the front of a fill queue, returning its value
+fn take(fills: &mut VecDeque<(i64, f64)>, mut quantity: i64) -> f64 {
+    let mut value = 0.0;
+    while quantity > 0 {
+        let Some(fill) = fills.front_mut() else { break };
+        let matched = quantity.min(fill.0);
+        value += matched as f64 * fill.1;
+        quantity -= matched;
+        if matched == fill.0 {
+            fills.pop_front();
+        } else {
+            fill.0 -= matched;
+        }
+    }
+    value
+}
+
+fn leg_charges(costs: &CostModel, side: TradeType, value: f64, quantity: i64) -> f64 {
+    let price = Decimal::from_f64(value / quantity as f64).unwrap_or_default();
+    costs.charges(side, price, quantity as i32).to_f64().unwrap_or(0.0)
+}
+
+/// `dates` and `values` are each the buy's then the sell's
+fn closed_position(
+    symbol: &str,
+    exchange: &str,
+    category: TaxCategory,
+    quantity: i64,
+    (buy_date, sell_date): (NaiveDate, NaiveDate),
+    (buy_value, sell_value): (f64, f64),
+    charges: f64,
+) -> ClosedPosition {
+    let gross_pnl = sell_value - buy_value;
+    ClosedPosition {
+        symbol: symbol.to_string(),
+        exchange: exchange.to_string(),
+        category,
+        quantity,
+        buy_date,
+        sell_date,
+        buy_value,
+        sell_value,
+        charges,
+        gross_pnl,
+        net_pnl: gross_pnl - charges,
+    }
+}
+
+fn summarize(category: TaxCategory, positions: &[ClosedPosition]) -> CategorySummary {
+    let matching: Vec<&ClosedPosition> = positions.iter().filter(|position| position.category == category).collect();
+    let turnover = match category {
+        TaxCategory::SpeculativeIntraday => matching.iter().map(|position| position.gross_pnl.abs()).sum(),
+        _ => matching.iter().map(|position| position.sell_value).sum(),
+    };
+
+    CategorySummary {
+        category,
+        positions: matching.len(),
+        turnover,
+        gross_profit: matching.iter().map(|position| position.gross_pnl).sum(),
+        charges: matching.iter().map(|position| position.charges).sum(),
+        net_profit: matching.iter().map(|position| position.net_pnl).sum(),
+    }
+}
+
+/// Speculative losses only offset speculative income; short-term losses also offset long-term gains
+fn estimate_tax(categories: &[CategorySummary], rates: &TaxRates) -> TaxEstimate {
+    let net = |category: TaxCategory| {
+        categories.iter().find(|summary| summary.category == category).map_or(0.0, |summary| summary.net_profit)
+    };
+    let speculative = net(TaxCategory::SpeculativeIntraday);
+    let mut short_term = net(TaxCategory::ShortTermDelivery);
+    let mut long_term = net(TaxCategory::LongTermDelivery);
+    if short_term < 0.0 {
+        long_term += short_term;
+        short_term = 0.0;
+    }
+
+    let speculative_tax = speculative.max(0.0) * rates.slab_rate / 100.0;
+    let short_term_tax = short_term * rates.short_term_rate / 100.0;
+    let long_term_tax = (long_term - rates.long_term_exemption).max(0.0) * rates.long_term_rate / 100.0;
+    let tax = speculative_tax + short_term_tax + long_term_tax;
+    let cess = tax * rates.cess_rate / 100.0;
+
+    TaxEstimate {
+        speculative_tax,
+        short_term_tax,
+        long_term_tax,
+        cess,
+        total: tax + cess,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn trade(symbol: &str, trade_type: TradeType, quantity: i64, price: f64, day: (i32, u32, u32), hour: u32) -> ReportTrade {
+        ReportTrade {
+            symbol: symbol.to_string(),
+            exchange: "NSE".to_string(),
+            trade_type,
+            quantity,
+            price,
+            // Hours are UTC, all within the IST trading day
+            executed_at: Utc.with_ymd_and_hms(day.0, day.1, day.2, hour, 0, 0).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_classifies_intraday_and_delivery() {
+        let trades = vec![
+            // Held over a year, then sold along with part of a newer lot
+            trade("INFY", TradeType::Buy, 10, 1000.0, (2023, 5, 2), 4),
+            trade("INFY", TradeType::Buy, 10, 1400.0, (2024, 6, 3), 4),
+            trade("INFY", TradeType::Sell, 15, 1500.0, (2024, 7, 1), 5),
+            // Bought and sold the same day, with 50 carried overnight
+            trade("TCS", TradeType::Buy, 100, 3000.0, (2024, 8, 5), 4),
+            trade("TCS", TradeType::Sell, 50, 3010.0, (2024, 8, 5), 6),
+            trade("TCS", TradeType::Sell, 80, 2990.0, (2024, 8, 6), 6),
+        ];
+        let (from, to) = financial_year_bounds(2024);
+        let report = build_report(&trades, from, to, TaxRates::default());
+
+        assert_eq!(report.financial_year, "2024-25");
+        assert_eq!(report.unmatched_sell_quantity, 30);
+        assert_eq!(report.positions.len(), 4);
+
+        let intraday = &report.categories[0];
+        assert_eq!(intraday.category, TaxCategory::SpeculativeIntraday);
+        assert_eq!(intraday.gross_profit, 500.0);
+        assert_eq!(intraday.turnover, 500.0);
+        assert!(intraday.net_profit < intraday.gross_profit);
+
+        let short_term = &report.categories[1];
+        assert_eq!(short_term.positions, 2);
+        // 5 INFY at 1400 sold at 1500, and 50 TCS at 3000 sold at 2990
+        assert_eq!(short_term.gross_profit, 500.0 - 500.0);
+        assert_eq!(short_term.turnover, 7500.0 + 149_500.0);
+
+        let long_term = &report.categories[2];
+        assert_eq!(long_term.positions, 1);
+        assert_eq!(long_term.gross_profit, 5000.0);
+        assert_eq!(long_term.turnover, 15_000.0);
+    }
+
+    #[test]
+    fn test_estimate_tax_sets_off_losses() {
+        let summary = |category, net_profit| CategorySummary {
+            category,
+            positions: 1,
+            turnover: 0.0,
+            gross_profit: net_profit,
+            charges: 0.0,
+            net_profit,
+        };
+        let categories = vec![
+            summary(TaxCategory::SpeculativeIntraday, 10_000.0),
+            summary(TaxCategory::ShortTermDelivery, -20_000.0),
+            summary(TaxCategory::LongTermDelivery, 245_000.0),
+        ];
+
+        let tax = estimate_tax(&categories, &TaxRates::default());
+        assert_eq!(tax.speculative_tax, 3000.0);
+        assert_eq!(tax.short_term_tax, 0.0);
+        // 245000 less the 20000 short-term loss and the 125000 exemption, at 12.5%
+        assert_eq!(tax.long_term_tax, 12_500.0);
+        assert!((tax.total - 15_500.0 * 1.04).abs() < 1e-9);
+        assert_eq!(financial_year_of(NaiveDate::from_ymd_opt(2025, 3, 31).unwrap()), 2024);
+    }
+}
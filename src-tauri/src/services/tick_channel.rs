@@ -0,0 +1,276 @@
+use crate::error::{HedgeXError, Result};
+use crate::services::websocket_manager::MarketData;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex, Weak};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use tokio::sync::Notify;
+
+/// Ticks a default subscriber may fall behind by before new ones are dropped
+pub const DEFAULT_TICK_QUEUE_CAPACITY: usize = 1000;
+
+/// What a subscriber's buffer does when the subscriber falls behind
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum TickPolicy {
+    /// Hold up to this many ticks in order, dropping new ones once full
+    Queue(usize),
+    /// Hold only the latest tick of each symbol, for up to this many symbols
+    KeepLatest(usize),
+}
+
+/// Counters of one subscriber's buffer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickChannelStats {
+    pub consumer: String,
+    pub policy: TickPolicy,
+    /// Ticks waiting to be received
+    pub pending: usize,
+    pub delivered: u64,
+    /// Ticks lost because the buffer was full
+    pub dropped: u64,
+    /// Ticks replaced by a newer tick of the same symbol before being received
+    pub coalesced: u64,
+}
+
+/// Fans market data out to subscribers, each with its own bounded buffer
+///
+/// Sending never waits: a subscriber that falls behind loses or coalesces its own
+/// ticks, so a slow UI or writer cannot hold up the feed or other subscribers.
+#[derive(Clone, Default)]
+pub struct TickSender {
+    inner: Arc<SenderInner>,
+}
+
+#[derive(Default)]
+struct SenderInner {
+    queues: Mutex<Vec<Weak<TickQueue>>>,
+}
+
+/// Receiving end of one subscriber
+pub struct TickReceiver {
+    queue: Arc<TickQueue>,
+}
+
+struct TickQueue {
+    consumer: String,
+    policy: TickPolicy,
+    state: Mutex<QueueState>,
+    notify: Notify,
+    closed: AtomicBool,
+    delivered: AtomicU64,
+    dropped: AtomicU64,
+    coalesced: AtomicU64,
+}
+
+#[derive(Default)]
+struct QueueState {
+    /// Ticks in arrival order, for queue subscribers
+    ticks: VecDeque<MarketData>,
+    /// Symbols waiting in arrival order and their latest tick, for keep-latest subscribers
+    symbols: VecDeque<String>,
+    latest: HashMap<String, MarketData>,
+}
+
+impl TickSender {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a subscriber whose buffer follows the policy
+    pub fn subscribe(&self, consumer: &str, policy: TickPolicy) -> TickReceiver {
+        let queue = Arc::new(TickQueue {
+            consumer: consumer.to_string(),
+            policy,
+            state: Mutex::new(QueueState::default()),
+            notify: Notify::new(),
+            closed: AtomicBool::new(false),
+            delivered: AtomicU64::new(0),
+            dropped: AtomicU64::new(0),
+            coalesced: AtomicU64::new(0),
+        });
+        self.inner.queues.lock().unwrap().push(Arc::downgrade(&queue));
+        TickReceiver { queue }
+    }
+
+    /// Hand a tick to every subscriber, returning how many there are
+    ///
+    /// Fails when nobody is subscribed, like a broadcast channel.
+    pub fn send(&self, market_data: MarketData) -> Result<usize> {
+        let queues = self.live_queues();
+        if queues.is_empty() {
+            return Err(HedgeXError::InternalError("No market data subscribers".to_string()));
+        }
+
+        for queue in &queues {
+            queue.push(market_data.clone());
+        }
+        Ok(queues.len())
+    }
+
+    /// Buffer counters of each subscriber still listening
+    pub fn stats(&self) -> Vec<TickChannelStats> {
+        self.live_queues().iter().map(|queue| queue.stats()).collect()
+    }
+
+    /// Subscribers still listening; dropped ones are forgotten
+    fn live_queues(&self) -> Vec<Arc<TickQueue>> {
+        let mut queues = self.inner.queues.lock().unwrap();
+        queues.retain(|queue| queue.strong_count() > 0);
+        queues.iter().filter_map(Weak::upgrade).collect()
+    }
+}
+
+impl Drop for SenderInner {
+    fn drop(&mut self) {
+        for queue in self.queues.get_mut().unwrap().iter().filter_map(Weak::upgrade) {
+            queue.closed.store(true, Ordering::SeqCst);
+            queue.notify.notify_one();
+        }
+    }
+}
+
+impl TickReceiver {
+    /// Wait for the next tick; `None` once every sender is gone and the buffer is empty
+    pub async fn recv(&mut self) -> Option<MarketData> {
+        loop {
+            if let Some(tick) = self.try_recv() {
+                return Some(tick);
+            }
+            if self.queue.closed.load(Ordering::SeqCst) {
+                return None;
+            }
+            self.queue.notify.notified().await;
+        }
+    }
+
+    /// Take the next tick if one is waiting
+    pub fn try_recv(&mut self) -> Option<MarketData> {
+        let tick = self.queue.pop();
+        if tick.is_some() {
+            self.queue.delivered.fetch_add(1, Ordering::Relaxed);
+        }
+        tick
+    }
+}
+
+impl TickQueue {
+    fn push(&self, market_data: MarketData) {
+        {
+            let mut state = self.state.lock().unwrap();
+            match self.policy {
+                TickPolicy::Queue(capacity) => {
+                    if state.ticks.len() >= capacity {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    state.ticks.push_back(market_data);
+                }
+                TickPolicy::KeepLatest(capacity) => {
+                    if let Some(pending) = state.latest.get_mut(&market_data.symbol) {
+                        *pending = market_data;
+                        self.coalesced.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    if state.symbols.len() >= capacity {
+                        self.dropped.fetch_add(1, Ordering::Relaxed);
+                        return;
+                    }
+                    state.symbols.push_back(market_data.symbol.clone());
+                    state.latest.insert(market_data.symbol.clone(), market_data);
+                }
+            }
+        }
+        self.notify.notify_one();
+    }
+
+    fn pop(&self) -> Option<MarketData> {
+        let mut state = self.state.lock().unwrap();
+        match self.policy {
+            TickPolicy::Queue(_) => state.ticks.pop_front(),
+            TickPolicy::KeepLatest(_) => {
+                let symbol = state.symbols.pop_front()?;
+                state.latest.remove(&symbol)
+            }
+        }
+    }
+
+    fn stats(&self) -> TickChannelStats {
+        let state = self.state.lock().unwrap();
+        TickChannelStats {
+            consumer: self.consumer.clone(),
+            policy: self.policy,
+            pending: state.ticks.len() + state.symbols.len(),
+            delivered: self.delivered.load(Ordering::Relaxed),
+            dropped: self.dropped.load(Ordering::Relaxed),
+            coalesced: self.coalesced.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+
+    fn tick(symbol: &str, ltp: i64) -> MarketData {
+        MarketData {
+            symbol: symbol.to_string(),
+            instrument_token: 408065,
+            ltp: Decimal::new(ltp, 0),
+            volume: 1000,
+            bid: Decimal::ZERO,
+            ask: Decimal::ZERO,
+            ohlc: None,
+            timestamp: Utc::now(),
+            change: None,
+            change_percent: None,
+            depth: None,
+            delayed: false,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_slow_subscribers_drop_or_coalesce_their_own_ticks() {
+        let sender = TickSender::new();
+        let mut queue = sender.subscribe("recorder", TickPolicy::Queue(2));
+        let mut latest = sender.subscribe("ui", TickPolicy::KeepLatest(2));
+
+        for (symbol, ltp) in [("INFY", 1500), ("INFY", 1501), ("TCS", 3800), ("WIPRO", 450)] {
+            assert_eq!(sender.send(tick(symbol, ltp)).unwrap(), 2);
+        }
+
+        // The queue kept the first two ticks in order
+        assert_eq!(queue.recv().await.unwrap().ltp, Decimal::new(1500, 0));
+        assert_eq!(queue.recv().await.unwrap().ltp, Decimal::new(1501, 0));
+        assert!(queue.try_recv().is_none());
+
+        // Keep-latest folded INFY into its newest tick and had no room for WIPRO
+        assert_eq!(latest.recv().await.unwrap().ltp, Decimal::new(1501, 0));
+        assert_eq!(latest.recv().await.unwrap().symbol, "TCS");
+        assert!(latest.try_recv().is_none());
+
+        let stats = sender.stats();
+        assert_eq!((stats[0].consumer.as_str(), stats[0].delivered, stats[0].dropped), ("recorder", 2, 2));
+        assert_eq!((stats[1].dropped, stats[1].coalesced, stats[1].pending), (1, 1, 0));
+    }
+
+    #[tokio::test]
+    async fn test_receivers_close_with_the_sender() {
+        let sender = TickSender::new();
+        assert!(sender.send(tick("INFY", 1500)).is_err());
+
+        let mut receiver = sender.subscribe("ui", TickPolicy::KeepLatest(10));
+        sender.send(tick("INFY", 1500)).unwrap();
+        drop(sender);
+
+        // What was buffered is still delivered before the end
+        assert!(receiver.recv().await.is_some());
+        assert!(receiver.recv().await.is_none());
+
+        // Dropped subscribers are no longer counted
+        let sender = TickSender::new();
+        drop(sender.subscribe("gone", TickPolicy::Queue(1)));
+        assert!(sender.stats().is_empty());
+    }
+}
@@ -0,0 +1,280 @@
+use crate::db::TickStore;
+use crate::error::{HedgeXError, Result};
+use crate::services::websocket_manager::MarketData;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+use tracing::{error, info};
+
+/// Settings for recording market ticks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickRecorderConfig {
+    pub enabled: bool,
+    /// Longest a tick waits in the buffer before it is written
+    pub flush_interval_ms: u64,
+    /// Ticks written per transaction; a full batch is written straight away
+    pub batch_size: usize,
+    /// Ticks held before new ones are dropped rather than slowing the feed
+    pub max_buffered: usize,
+}
+
+impl Default for TickRecorderConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            flush_interval_ms: 1000,
+            batch_size: 500,
+            max_buffered: 50_000,
+        }
+    }
+}
+
+impl TickRecorderConfig {
+    /// Check the flush interval and sizes
+    pub fn validate(&self) -> Result<()> {
+        if !(100..=60_000).contains(&self.flush_interval_ms) {
+            return Err(HedgeXError::ValidationError(
+                "Flush interval must be between 100 and 60000 milliseconds".to_string()
+            ));
+        }
+
+        if !(1..=10_000).contains(&self.batch_size) {
+            return Err(HedgeXError::ValidationError(
+                "Batch size must be between 1 and 10000 ticks".to_string()
+            ));
+        }
+
+        if self.max_buffered < self.batch_size || self.max_buffered > 1_000_000 {
+            return Err(HedgeXError::ValidationError(
+                "Buffer must hold at least one batch and at most 1000000 ticks".to_string()
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Counters reported by a running recorder
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TickRecorderStats {
+    pub config: TickRecorderConfig,
+    pub recorded: u64,
+    /// Ticks dropped because the buffer was full
+    pub dropped: u64,
+    pub batches: u64,
+}
+
+enum RecorderMessage {
+    Tick(MarketData),
+    Flush(oneshot::Sender<()>),
+}
+
+/// Buffers ticks from the market feed and writes them to `market_ticks` in batches
+///
+/// Recording a tick only queues it, so the feed never waits on the database.
+/// Dropping the recorder writes whatever is still buffered.
+pub struct TickRecorder {
+    config: TickRecorderConfig,
+    tx: mpsc::Sender<RecorderMessage>,
+    counters: Arc<RecorderCounters>,
+}
+
+#[derive(Default)]
+struct RecorderCounters {
+    recorded: AtomicU64,
+    dropped: AtomicU64,
+    batches: AtomicU64,
+}
+
+impl TickRecorder {
+    /// Start a recorder writing to a tick store in the background
+    pub fn start(db: Arc<dyn TickStore>, config: TickRecorderConfig) -> Result<Arc<Self>> {
+        config.validate()?;
+
+        let (tx, rx) = mpsc::channel(config.max_buffered);
+        let counters = Arc::new(RecorderCounters::default());
+        tokio::spawn(Self::run(db, config.clone(), rx, Arc::clone(&counters)));
+
+        info!("Tick recorder started: batches of {} every {} ms", config.batch_size, config.flush_interval_ms);
+        Ok(Arc::new(Self { config, tx, counters }))
+    }
+
+    /// Queue a tick for writing, dropping it if the buffer is full
+    pub fn record(&self, market_data: &MarketData) {
+        if self.tx.try_send(RecorderMessage::Tick(market_data.clone())).is_err() {
+            self.counters.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    /// Write everything queued so far and wait until it is stored
+    pub async fn flush(&self) -> Result<()> {
+        let (done_tx, done_rx) = oneshot::channel();
+        self.tx.send(RecorderMessage::Flush(done_tx)).await
+            .map_err(|_| HedgeXError::InternalError("Tick recorder has stopped".to_string()))?;
+        done_rx.await
+            .map_err(|_| HedgeXError::InternalError("Tick recorder has stopped".to_string()))
+    }
+
+    /// Configuration and counters
+    pub fn stats(&self) -> TickRecorderStats {
+        TickRecorderStats {
+            config: self.config.clone(),
+            recorded: self.counters.recorded.load(Ordering::Relaxed),
+            dropped: self.counters.dropped.load(Ordering::Relaxed),
+            batches: self.counters.batches.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Collect ticks and write them when a batch fills or the interval passes
+    async fn run(
+        db: Arc<dyn TickStore>,
+        config: TickRecorderConfig,
+        mut rx: mpsc::Receiver<RecorderMessage>,
+        counters: Arc<RecorderCounters>,
+    ) {
+        let mut interval = tokio::time::interval(Duration::from_millis(config.flush_interval_ms));
+        let mut batch: Vec<MarketData> = Vec::with_capacity(config.batch_size);
+
+        loop {
+            tokio::select! {
+                message = rx.recv() => match message {
+                    Some(RecorderMessage::Tick(tick)) => {
+                        batch.push(tick);
+                        if batch.len() >= config.batch_size {
+                            Self::write(&db, &mut batch, &counters).await;
+                        }
+                    }
+                    Some(RecorderMessage::Flush(done)) => {
+                        Self::write(&db, &mut batch, &counters).await;
+                        let _ = done.send(());
+                    }
+                    None => {
+                        Self::write(&db, &mut batch, &counters).await;
+                        break;
+                    }
+                },
+                _ = interval.tick() => Self::write(&db, &mut batch, &counters).await,
+            }
+        }
+
+        info!("Tick recorder stopped");
+    }
+
+    /// Write a batch in one transaction and clear it; a failed batch is logged and dropped
+    async fn write(db: &Arc<dyn TickStore>, batch: &mut Vec<MarketData>, counters: &RecorderCounters) {
+        if batch.is_empty() {
+            return;
+        }
+
+        match db.insert_ticks(batch).await {
+            Ok(_) => {
+                counters.recorded.fetch_add(batch.len() as u64, Ordering::Relaxed);
+                counters.batches.fetch_add(1, Ordering::Relaxed);
+            }
+            Err(e) => {
+                error!("Failed to write {} ticks: {}", batch.len(), e);
+                counters.dropped.fetch_add(batch.len() as u64, Ordering::Relaxed);
+            }
+        }
+        batch.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use rust_decimal::Decimal;
+    use sqlx::{Pool, Row, Sqlite, SqlitePool};
+
+    async fn create_test_pool() -> Arc<Pool<Sqlite>> {
+        let pool = SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query(r#"
+            CREATE TABLE market_ticks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                exchange TEXT NOT NULL DEFAULT 'NSE',
+                timestamp TIMESTAMP NOT NULL,
+                ltp REAL NOT NULL,
+                bid REAL NOT NULL,
+                ask REAL NOT NULL,
+                volume INTEGER NOT NULL
+            )
+        "#).execute(&pool).await.unwrap();
+        Arc::new(pool)
+    }
+
+    fn tick(volume: u64) -> MarketData {
+        MarketData {
+            symbol: "RELIANCE".to_string(),
+            instrument_token: 738561,
+            ltp: Decimal::new(250050, 2),
+            volume,
+            bid: Decimal::new(250000, 2),
+            ask: Decimal::new(250100, 2),
+            ohlc: None,
+            timestamp: Utc::now(),
+            change: None,
+            change_percent: None,
+            depth: None,
+            delayed: false,
+        }
+    }
+
+    async fn stored_ticks(pool: &Pool<Sqlite>) -> i64 {
+        sqlx::query("SELECT COUNT(*) as count FROM market_ticks")
+            .fetch_one(pool)
+            .await
+            .unwrap()
+            .get("count")
+    }
+
+    #[tokio::test]
+    async fn test_ticks_are_written_in_batches() {
+        let pool = create_test_pool().await;
+        let config = TickRecorderConfig { flush_interval_ms: 60_000, batch_size: 3, ..TickRecorderConfig::default() };
+        let recorder = TickRecorder::start(Arc::clone(&pool), config).unwrap();
+
+        for volume in 0..7 {
+            recorder.record(&tick(volume));
+        }
+
+        // Two full batches go out on their own; the last tick waits for a flush
+        recorder.flush().await.unwrap();
+        assert_eq!(stored_ticks(&pool).await, 7);
+
+        let stats = recorder.stats();
+        assert_eq!(stats.recorded, 7);
+        assert_eq!(stats.batches, 3);
+        assert_eq!(stats.dropped, 0);
+
+        let row = sqlx::query("SELECT ltp, volume FROM market_ticks ORDER BY id DESC LIMIT 1")
+            .fetch_one(&*pool)
+            .await
+            .unwrap();
+        assert_eq!(row.get::<f64, _>("ltp"), 2500.5);
+        assert_eq!(row.get::<i64, _>("volume"), 6);
+    }
+
+    #[tokio::test]
+    async fn test_interval_flushes_partial_batches() {
+        let pool = create_test_pool().await;
+        let config = TickRecorderConfig { flush_interval_ms: 100, batch_size: 100, ..TickRecorderConfig::default() };
+        let recorder = TickRecorder::start(Arc::clone(&pool), config).unwrap();
+
+        recorder.record(&tick(1));
+        recorder.record(&tick(2));
+        tokio::time::sleep(Duration::from_millis(300)).await;
+        assert_eq!(stored_ticks(&pool).await, 2);
+    }
+
+    #[test]
+    fn test_validate_config() {
+        assert!(TickRecorderConfig::default().validate().is_ok());
+        assert!(TickRecorderConfig { flush_interval_ms: 10, ..TickRecorderConfig::default() }.validate().is_err());
+        assert!(TickRecorderConfig { batch_size: 0, ..TickRecorderConfig::default() }.validate().is_err());
+        assert!(TickRecorderConfig { max_buffered: 10, ..TickRecorderConfig::default() }.validate().is_err());
+    }
+}
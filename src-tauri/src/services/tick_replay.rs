@@ -0,0 +1,360 @@
+use crate::db::TickStore;
+use crate::error::{HedgeXError, Result};
+use crate::services::websocket_manager::{ConnectionStatus, MarketData, WebSocketManager};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::FromPrimitive;
+use serde::{Deserialize, Serialize};
+use sqlx::{Pool, Row, Sqlite};
+use std::io::{BufRead, BufReader};
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::time::Duration;
+use tokio::sync::RwLock;
+use tracing::info;
+
+/// Fastest replay speed as a multiple of real time
+const MAX_REPLAY_SPEED: f64 = 1000.0;
+
+/// Where replayed ticks come from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ReplaySource {
+    /// Ticks stored by the tick recorder
+    Database {
+        symbols: Vec<String>,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    },
+    /// CSV file with a `symbol,timestamp,ltp,bid,ask,volume` header and RFC 3339 timestamps
+    File { path: String },
+}
+
+/// What to replay and how fast
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayConfig {
+    pub source: ReplaySource,
+    /// Multiple of real time; 0 replays without waiting between ticks
+    pub speed: f64,
+}
+
+impl ReplayConfig {
+    /// Check the speed and the database range
+    pub fn validate(&self) -> Result<()> {
+        if !(self.speed == 0.0 || (0.1..=MAX_REPLAY_SPEED).contains(&self.speed)) {
+            return Err(HedgeXError::ValidationError(
+                "Replay speed must be 0 or between 0.1 and 1000".to_string()
+            ));
+        }
+
+        if let ReplaySource::Database { symbols, start, end } = &self.source {
+            if symbols.is_empty() {
+                return Err(HedgeXError::ValidationError("At least one symbol is required".to_string()));
+            }
+            if start >= end {
+                return Err(HedgeXError::ValidationError("Start must be before end".to_string()));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Progress of a running or finished replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayProgress {
+    pub config: ReplayConfig,
+    pub total: u64,
+    pub sent: u64,
+    /// Timestamp of the last tick sent
+    pub replay_time: Option<DateTime<Utc>>,
+    pub running: bool,
+}
+
+/// Feeds recorded ticks through the WebSocket manager as if they came from Kite
+///
+/// Everything downstream of the feed sees the ticks: the cache, live candles,
+/// and every market data subscriber. Nothing is written back to the tick history.
+pub struct TickReplay {
+    config: ReplayConfig,
+    total: u64,
+    sent: Arc<AtomicU64>,
+    replay_time: Arc<RwLock<Option<DateTime<Utc>>>>,
+    running: Arc<AtomicBool>,
+    handle: tokio::task::JoinHandle<()>,
+}
+
+impl TickReplay {
+    /// Load the ticks and start replaying them in the background
+    pub async fn start(
+        db: &dyn TickStore,
+        ws_manager: Arc<WebSocketManager>,
+        config: ReplayConfig,
+    ) -> Result<Arc<Self>> {
+        config.validate()?;
+
+        // Live and replayed ticks must not mix
+        if ws_manager.get_status().await == ConnectionStatus::Connected {
+            return Err(HedgeXError::ValidationError(
+                "Disconnect from the live feed before replaying ticks".to_string()
+            ));
+        }
+
+        let ticks = match &config.source {
+            ReplaySource::Database { symbols, start, end } => db.load_ticks(symbols, *start, *end).await?,
+            ReplaySource::File { path } => read_ticks_file(path)?,
+        };
+        if ticks.is_empty() {
+            return Err(HedgeXError::NotFoundError("No ticks found to replay".to_string()));
+        }
+
+        let sent = Arc::new(AtomicU64::new(0));
+        let replay_time = Arc::new(RwLock::new(None));
+        let running = Arc::new(AtomicBool::new(true));
+        let total = ticks.len() as u64;
+        info!("Replaying {} ticks at {}x", total, config.speed);
+
+        let handle = tokio::spawn(Self::run(
+            ws_manager,
+            ticks,
+            config.speed,
+            Arc::clone(&sent),
+            Arc::clone(&replay_time),
+            Arc::clone(&running),
+        ));
+
+        Ok(Arc::new(Self { config, total, sent, replay_time, running, handle }))
+    }
+
+    /// Stop replaying; ticks already sent stay sent
+    pub fn stop(&self) {
+        self.handle.abort();
+        self.running.store(false, Ordering::SeqCst);
+    }
+
+    /// How far the replay has got
+    pub async fn progress(&self) -> ReplayProgress {
+        ReplayProgress {
+            config: self.config.clone(),
+            total: self.total,
+            sent: self.sent.load(Ordering::Relaxed),
+            replay_time: *self.replay_time.read().await,
+            running: self.running.load(Ordering::SeqCst),
+        }
+    }
+
+    /// Send each tick after the recorded gap to the previous one, scaled by the speed
+    async fn run(
+        ws_manager: Arc<WebSocketManager>,
+        ticks: Vec<MarketData>,
+        speed: f64,
+        sent: Arc<AtomicU64>,
+        replay_time: Arc<RwLock<Option<DateTime<Utc>>>>,
+        running: Arc<AtomicBool>,
+    ) {
+        let mut previous: Option<DateTime<Utc>> = None;
+
+        for tick in ticks {
+            if let Some(previous) = previous {
+                let gap = (tick.timestamp - previous).to_std().unwrap_or_default();
+                if speed > 0.0 && !gap.is_zero() {
+                    tokio::time::sleep(Duration::from_secs_f64(gap.as_secs_f64() / speed)).await;
+                }
+            }
+            previous = Some(tick.timestamp);
+
+            *replay_time.write().await = Some(tick.timestamp);
+            ws_manager.inject_market_data(tick).await;
+            sent.fetch_add(1, Ordering::Relaxed);
+
+            // Without delays, let the consumers of the broadcast keep up
+            if speed == 0.0 {
+                tokio::task::yield_now().await;
+            }
+        }
+
+        running.store(false, Ordering::SeqCst);
+        info!("Tick replay finished after {} ticks", sent.load(Ordering::Relaxed));
+    }
+}
+
+/// Recorded ticks for symbols in a time range, oldest first
+pub async fn load_ticks(
+    db: &Pool<Sqlite>,
+    symbols: &[String],
+    start: DateTime<Utc>,
+    end: DateTime<Utc>,
+) -> Result<Vec<MarketData>> {
+    let placeholders = vec!["?"; symbols.len()].join(", ");
+    let query = format!(
+        "SELECT symbol, timestamp, ltp, bid, ask, volume FROM market_ticks
+         WHERE symbol IN ({}) AND timestamp >= ? AND timestamp <= ?
+         ORDER BY timestamp, id",
+        placeholders
+    );
+
+    let mut statement = sqlx::query(&query);
+    for symbol in symbols {
+        statement = statement.bind(symbol);
+    }
+    let rows = statement
+        .bind(start)
+        .bind(end)
+        .fetch_all(db)
+        .await
+        .map_err(HedgeXError::DatabaseError)?;
+
+    Ok(rows.iter()
+        .map(|row| replay_tick(
+            row.get("symbol"),
+            row.get("timestamp"),
+            row.get("ltp"),
+            row.get("bid"),
+            row.get("ask"),
+            row.get("volume"),
+        ))
+        .collect())
+}
+
+/// Ticks from a CSV file, sorted by time
+pub fn read_ticks_file(path: &str) -> Result<Vec<MarketData>> {
+    let file = std::fs::File::open(path)
+        .map_err(|e| HedgeXError::ValidationError(format!("Cannot open {}: {}", path, e)))?;
+    let mut lines = BufReader::new(file).lines();
+
+    let header = match lines.next() {
+        Some(line) => line.map_err(|e| HedgeXError::ValidationError(format!("Cannot read {}: {}", path, e)))?,
+        None => return Ok(Vec::new()),
+    };
+    let columns: Vec<String> = header.split(',').map(|c| c.trim().to_lowercase()).collect();
+    let position = |name: &str| {
+        columns.iter().position(|c| c == name)
+            .ok_or_else(|| HedgeXError::ValidationError(format!("Missing column: {}", name)))
+    };
+    let (symbol, timestamp, ltp) = (position("symbol")?, position("timestamp")?, position("ltp")?);
+    let (bid, ask, volume) = (position("bid")?, position("ask")?, position("volume")?);
+
+    let mut ticks = Vec::new();
+    for (index, line) in lines.enumerate() {
+        let line_number = index + 2;
+        let line = line.map_err(|e| HedgeXError::ValidationError(format!("Cannot read {}: {}", path, e)))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let fields: Vec<&str> = line.split(',').map(str::trim).collect();
+        let field = |at: usize| {
+            fields.get(at).copied()
+                .ok_or_else(|| HedgeXError::ValidationError(format!("Line {} has too few fields", line_number)))
+        };
+        let number = |at: usize| -> Result<f64> {
+            field(at)?.parse::<f64>()
+                .map_err(|_| HedgeXError::ValidationError(format!("Line {} has an invalid number", line_number)))
+        };
+
+        let time = DateTime::parse_from_rfc3339(field(timestamp)?)
+            .map_err(|_| HedgeXError::ValidationError(format!("Line {} has an invalid timestamp", line_number)))?
+            .with_timezone(&Utc);
+
+        ticks.push(replay_tick(
+            field(symbol)?.to_string(),
+            time,
+            number(ltp)?,
+            number(bid)?,
+            number(ask)?,
+            number(volume)? as i64,
+        ));
+    }
+
+    ticks.sort_by_key(|tick| tick.timestamp);
+    Ok(ticks)
+}
+
+pub(crate) fn replay_tick(symbol: String, timestamp: DateTime<Utc>, ltp: f64, bid: f64, ask: f64, volume: i64) -> MarketData {
+    let price = |value: f64| Decimal::from_f64(value).unwrap_or_default();
+    MarketData {
+        symbol,
+        instrument_token: 0,
+        ltp: price(ltp),
+        volume: volume.max(0) as u64,
+        bid: price(bid),
+        ask: price(ask),
+        ohlc: None,
+        timestamp,
+        change: None,
+        change_percent: None,
+        depth: None,
+        delayed: false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_read_ticks_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ticks.csv");
+        let mut file = std::fs::File::create(&path).unwrap();
+        writeln!(file, "symbol,timestamp,ltp,bid,ask,volume").unwrap();
+        writeln!(file, "INFY,2024-01-25T04:00:02Z,1500.5,1500.4,1500.6,1200").unwrap();
+        writeln!(file, "INFY,2024-01-25T04:00:01Z,1500.0,1499.9,1500.1,1000").unwrap();
+        writeln!(file).unwrap();
+        drop(file);
+
+        let ticks = read_ticks_file(path.to_str().unwrap()).unwrap();
+        assert_eq!(ticks.len(), 2);
+        assert_eq!(ticks[0].ltp, Decimal::new(15000, 1));
+        assert_eq!(ticks[1].volume, 1200);
+
+        let bad = dir.path().join("bad.csv");
+        std::fs::write(&bad, "symbol,timestamp,ltp\nINFY,2024-01-25T04:00:01Z,1500\n").unwrap();
+        assert!(read_ticks_file(bad.to_str().unwrap()).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_load_ticks_in_range() {
+        let pool = sqlx::SqlitePool::connect(":memory:").await.unwrap();
+        sqlx::query(r#"
+            CREATE TABLE market_ticks (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                symbol TEXT NOT NULL,
+                exchange TEXT NOT NULL DEFAULT 'NSE',
+                timestamp TIMESTAMP NOT NULL,
+                ltp REAL NOT NULL,
+                bid REAL NOT NULL,
+                ask REAL NOT NULL,
+                volume INTEGER NOT NULL
+            )
+        "#).execute(&pool).await.unwrap();
+
+        let start = Utc::now() - chrono::Duration::hours(1);
+        for (symbol, minute) in [("INFY", 2), ("TCS", 1), ("INFY", 0), ("WIPRO", 1), ("INFY", 90)] {
+            sqlx::query("INSERT INTO market_ticks (symbol, timestamp, ltp, bid, ask, volume) VALUES (?, ?, 100.0, 99.9, 100.1, 10)")
+                .bind(symbol)
+                .bind(start + chrono::Duration::minutes(minute))
+                .execute(&pool)
+                .await
+                .unwrap();
+        }
+
+        let symbols = vec!["INFY".to_string(), "TCS".to_string()];
+        let ticks = load_ticks(&pool, &symbols, start, start + chrono::Duration::minutes(30)).await.unwrap();
+        let order: Vec<&str> = ticks.iter().map(|tick| tick.symbol.as_str()).collect();
+        assert_eq!(order, vec!["INFY", "TCS", "INFY"]);
+    }
+
+    #[test]
+    fn test_validate_config() {
+        let file = ReplaySource::File { path: "ticks.csv".to_string() };
+        assert!(ReplayConfig { source: file.clone(), speed: 0.0 }.validate().is_ok());
+        assert!(ReplayConfig { source: file.clone(), speed: 60.0 }.validate().is_ok());
+        assert!(ReplayConfig { source: file, speed: 5000.0 }.validate().is_err());
+
+        let now = Utc::now();
+        let empty = ReplaySource::Database { symbols: Vec::new(), start: now - chrono::Duration::hours(1), end: now };
+        assert!(ReplayConfig { source: empty, speed: 1.0 }.validate().is_err());
+    }
+}
@@ -0,0 +1,247 @@
+use crate::error::{HedgeXError, Result};
+use crate::services::websocket_manager::ConnectionStatus;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::atomic::{AtomicU64, Ordering};
+use tokio::sync::Mutex;
+
+/// Ticker connections Kite allows per API key
+pub const MAX_TICKER_CONNECTIONS: usize = 3;
+
+/// Instruments Kite allows on one ticker connection
+pub const MAX_INSTRUMENTS_PER_CONNECTION: usize = 3000;
+
+/// Which ticker connection each subscribed instrument is carried on
+///
+/// New instruments fill the lowest connection with room, so small universes use a
+/// single connection and further ones are only opened when needed.
+#[derive(Debug, Clone)]
+pub struct ShardAssignment {
+    max_connections: usize,
+    per_connection: usize,
+    shard_of: HashMap<u64, usize>,
+    loads: Vec<usize>,
+}
+
+impl Default for ShardAssignment {
+    fn default() -> Self {
+        Self::new(MAX_TICKER_CONNECTIONS, MAX_INSTRUMENTS_PER_CONNECTION)
+    }
+}
+
+impl ShardAssignment {
+    pub fn new(max_connections: usize, per_connection: usize) -> Self {
+        Self {
+            max_connections,
+            per_connection,
+            shard_of: HashMap::new(),
+            loads: vec![0; max_connections],
+        }
+    }
+
+    /// Place instruments not yet assigned, returning every given token grouped by connection
+    ///
+    /// Fails without assigning anything when the instruments don't all fit.
+    pub fn assign(&mut self, tokens: &[u64]) -> Result<BTreeMap<usize, Vec<u64>>> {
+        let mut new_tokens: Vec<u64> = tokens.iter()
+            .filter(|token| !self.shard_of.contains_key(token))
+            .cloned()
+            .collect();
+        new_tokens.sort_unstable();
+        new_tokens.dedup();
+
+        let capacity = self.max_connections * self.per_connection;
+        if self.shard_of.len() + new_tokens.len() > capacity {
+            return Err(HedgeXError::ValidationError(format!(
+                "Cannot subscribe to more than {} instruments across {} connections",
+                capacity, self.max_connections
+            )));
+        }
+
+        let mut shard = 0;
+        for token in new_tokens {
+            while self.loads[shard] >= self.per_connection {
+                shard += 1;
+            }
+            self.loads[shard] += 1;
+            self.shard_of.insert(token, shard);
+        }
+
+        Ok(self.group(tokens))
+    }
+
+    /// Free the connections' slots of instruments, returning the released tokens grouped by connection
+    pub fn release(&mut self, tokens: &[u64]) -> BTreeMap<usize, Vec<u64>> {
+        let released = self.group(tokens);
+        for (shard, shard_tokens) in &released {
+            for token in shard_tokens {
+                self.shard_of.remove(token);
+            }
+            self.loads[*shard] -= shard_tokens.len();
+        }
+        released
+    }
+
+    /// Assigned tokens grouped by connection; unassigned ones are left out
+    pub fn group(&self, tokens: &[u64]) -> BTreeMap<usize, Vec<u64>> {
+        let mut groups: BTreeMap<usize, Vec<u64>> = BTreeMap::new();
+        for token in tokens {
+            if let Some(shard) = self.shard_of.get(token) {
+                let group = groups.entry(*shard).or_default();
+                if !group.contains(token) {
+                    group.push(*token);
+                }
+            }
+        }
+        for group in groups.values_mut() {
+            group.sort_unstable();
+        }
+        groups
+    }
+
+    pub fn shard_of(&self, token: u64) -> Option<usize> {
+        self.shard_of.get(&token).cloned()
+    }
+
+    /// Instruments assigned to one connection, ordered by token
+    pub fn tokens(&self, shard: usize) -> Vec<u64> {
+        let mut tokens: Vec<u64> = self.shard_of.iter()
+            .filter(|(_, assigned)| **assigned == shard)
+            .map(|(token, _)| *token)
+            .collect();
+        tokens.sort_unstable();
+        tokens
+    }
+
+    /// Instruments assigned to each connection
+    pub fn loads(&self) -> &[usize] {
+        &self.loads
+    }
+
+    /// Connections to keep open: the first one always, and any other carrying instruments
+    pub fn active_shards(&self) -> Vec<usize> {
+        (0..self.max_connections)
+            .filter(|shard| *shard == 0 || self.loads[*shard] > 0)
+            .collect()
+    }
+}
+
+/// Health of one ticker connection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionHealth {
+    pub connection: usize,
+    pub status: ConnectionStatus,
+    pub instruments: usize,
+    pub messages_received: u64,
+    pub last_message_at: Option<DateTime<Utc>>,
+    pub connected_since: Option<DateTime<Utc>>,
+    /// Times the connection dropped without being asked to
+    pub drops: u64,
+}
+
+/// Counters kept by one connection task
+pub struct ConnectionStats {
+    messages: AtomicU64,
+    drops: AtomicU64,
+    state: Mutex<ConnectionState>,
+}
+
+struct ConnectionState {
+    status: ConnectionStatus,
+    last_message_at: Option<DateTime<Utc>>,
+    connected_since: Option<DateTime<Utc>>,
+}
+
+impl Default for ConnectionStats {
+    fn default() -> Self {
+        Self {
+            messages: AtomicU64::new(0),
+            drops: AtomicU64::new(0),
+            state: Mutex::new(ConnectionState {
+                status: ConnectionStatus::Disconnected,
+                last_message_at: None,
+                connected_since: None,
+            }),
+        }
+    }
+}
+
+impl ConnectionStats {
+    pub async fn record_connected(&self, at: DateTime<Utc>) {
+        let mut state = self.state.lock().await;
+        state.status = ConnectionStatus::Connected;
+        state.connected_since = Some(at);
+    }
+
+    pub async fn record_message(&self, at: DateTime<Utc>) {
+        self.messages.fetch_add(1, Ordering::Relaxed);
+        self.state.lock().await.last_message_at = Some(at);
+    }
+
+    /// Mark the connection closed, counting it as a drop unless it was asked to close
+    pub async fn record_closed(&self, dropped: bool) {
+        if dropped {
+            self.drops.fetch_add(1, Ordering::Relaxed);
+        }
+        let mut state = self.state.lock().await;
+        state.status = ConnectionStatus::Disconnected;
+        state.connected_since = None;
+    }
+
+    pub async fn snapshot(&self, connection: usize, instruments: usize) -> ConnectionHealth {
+        let state = self.state.lock().await;
+        ConnectionHealth {
+            connection,
+            status: state.status.clone(),
+            instruments,
+            messages_received: self.messages.load(Ordering::Relaxed),
+            last_message_at: state.last_message_at,
+            connected_since: state.connected_since,
+            drops: self.drops.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_instruments_fill_connections_in_order() {
+        let mut assignment = ShardAssignment::new(3, 2);
+
+        let groups = assignment.assign(&[10, 11, 12]).unwrap();
+        assert_eq!(groups.get(&0), Some(&vec![10, 11]));
+        assert_eq!(groups.get(&1), Some(&vec![12]));
+        assert_eq!(assignment.active_shards(), vec![0, 1]);
+
+        // Already assigned tokens keep their connection
+        let groups = assignment.assign(&[12, 13, 14]).unwrap();
+        assert_eq!(groups.get(&1), Some(&vec![12, 13]));
+        assert_eq!(groups.get(&2), Some(&vec![14]));
+        assert_eq!(assignment.loads(), &[2, 2, 1]);
+
+        // Freed slots are reused before anything past them
+        let released = assignment.release(&[11, 99]);
+        assert_eq!(released.get(&0), Some(&vec![11]));
+        assignment.assign(&[15]).unwrap();
+        assert_eq!(assignment.shard_of(15), Some(0));
+        assert_eq!(assignment.tokens(0), vec![10, 15]);
+    }
+
+    #[test]
+    fn test_assignment_rejects_universes_over_capacity() {
+        let mut assignment = ShardAssignment::new(2, 2);
+        assignment.assign(&[1, 2, 3]).unwrap();
+
+        assert!(assignment.assign(&[4, 5]).is_err());
+        assert_eq!(assignment.shard_of(4), None);
+        assert_eq!(assignment.loads(), &[2, 1]);
+
+        let mut full = ShardAssignment::default();
+        let universe: Vec<u64> = (0..(MAX_TICKER_CONNECTIONS * MAX_INSTRUMENTS_PER_CONNECTION) as u64).collect();
+        full.assign(&universe).unwrap();
+        assert_eq!(full.loads(), &[MAX_INSTRUMENTS_PER_CONNECTION; MAX_TICKER_CONNECTIONS]);
+    }
+}
@@ -0,0 +1,226 @@
+use crate::error::Result;
+use crate::models::trading::TradeType;
+use crate::services::pnl_statement::{PnlStatementService, StatementInput};
+use crate::trading::analytics_filter::TradeFilter;
+use crate::trading::session::session_date;
+use chrono::{DateTime, Datelike, Timelike, Utc, Weekday};
+use chrono_tz::Asia::Kolkata;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+
+/// Hours of the NSE session, always present so charts keep a stable axis
+const SESSION_HOURS: std::ops::RangeInclusive<u32> = 9..=15;
+
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon, Weekday::Tue, Weekday::Wed, Weekday::Thu, Weekday::Fri, Weekday::Sat, Weekday::Sun,
+];
+
+/// Quantity opened at one time and closed at another
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RoundTrip {
+    pub symbol: String,
+    pub exchange: String,
+    pub strategy_id: String,
+    pub quantity: i64,
+    pub entered_at: DateTime<Utc>,
+    pub exited_at: DateTime<Utc>,
+    pub pnl: f64,
+}
+
+/// Outcome of the round trips entered in one hour or on one weekday
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PerformanceBucket {
+    /// Hour of day in IST, 0 to 23, or weekday as "Mon" to "Sun"
+    pub bucket: String,
+    pub trades: usize,
+    pub winning_trades: usize,
+    pub win_rate: f64,
+    pub total_pnl: f64,
+    pub average_pnl: f64,
+}
+
+/// Performance by entry hour and entry weekday
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TimeBreakdown {
+    #[serde(flatten)]
+    pub filter: TradeFilter,
+    pub by_hour: Vec<PerformanceBucket>,
+    pub by_weekday: Vec<PerformanceBucket>,
+}
+
+impl PnlStatementService {
+    /// Bucket round trips closed within a filter by when they were entered
+    pub async fn time_breakdown(&self, user_id: &str, filter: &TradeFilter) -> Result<TimeBreakdown> {
+        let trades = self.load_trades(user_id, filter.to).await?;
+        let round_trips: Vec<RoundTrip> = round_trips(&trades)
+            .into_iter()
+            .filter(|trip| filter.matches(session_date(trip.exited_at), &trip.strategy_id, &trip.symbol))
+            .collect();
+
+        Ok(TimeBreakdown {
+            filter: filter.clone(),
+            by_hour: by_hour(&round_trips),
+            by_weekday: by_weekday(&round_trips),
+        })
+    }
+}
+
+/// Part of a fill not yet closed, with quantity signed by direction
+struct OpenEntry {
+    quantity: i64,
+    price: f64,
+    entered_at: DateTime<Utc>,
+}
+
+/// Pair each symbol's closing quantity with the entries it closes, first in first out
+///
+/// Entries and exits are matched within a strategy, so one strategy's fills do not close
+/// another's positions.
+pub fn round_trips(trades: &[StatementInput]) -> Vec<RoundTrip> {
+    // Open entries of each strategy's symbols, oldest first
+    let mut open: HashMap<(&str, &str, &str), VecDeque<OpenEntry>> = HashMap::new();
+    let mut trips = Vec::new();
+
+    for trade in trades {
+        let signed = match trade.trade_type {
+            TradeType::Buy => trade.quantity,
+            TradeType::Sell => -trade.quantity,
+        };
+        let entries = open
+            .entry((trade.strategy_id.as_str(), trade.exchange.as_str(), trade.symbol.as_str()))
+            .or_default();
+
+        let mut remaining = trade.quantity;
+        while remaining > 0 {
+            let Some(entry) = entries.front_mut() else { break };
+            if entry.quantity.signum() == signed.signum() {
+                break;
+            }
+
+            let matched = remaining.min(entry.quantity.abs());
+            trips.push(RoundTrip {
+                symbol: trade.symbol.clone(),
+                exchange: trade.exchange.clone(),
+                strategy_id: trade.strategy_id.clone(),
+                quantity: matched,
+                entered_at: entry.entered_at,
+                exited_at: trade.executed_at,
+                pnl: (trade.price - entry.price) * matched as f64 * entry.quantity.signum() as f64,
+            });
+
+            remaining -= matched;
+            entry.quantity -= matched * entry.quantity.signum();
+            if entry.quantity == 0 {
+                entries.pop_front();
+            }
+        }
+
+        if remaining > 0 {
+            entries.push_back(OpenEntry {
+                quantity: remaining * signed.signum(),
+                price: trade.price,
+                entered_at: trade.executed_at,
+            });
+        }
+    }
+
+    trips
+}
+
+/// Round trips by IST hour of entry, including every session hour
+pub fn by_hour(trips: &[RoundTrip]) -> Vec<PerformanceBucket> {
+    let mut buckets: BTreeMap<u32, Vec<&RoundTrip>> = SESSION_HOURS.map(|hour| (hour, Vec::new())).collect();
+    for trip in trips {
+        buckets.entry(trip.entered_at.with_timezone(&Kolkata).hour()).or_default().push(trip);
+    }
+    buckets.into_iter().map(|(hour, trips)| bucket(hour.to_string(), &trips)).collect()
+}
+
+/// Round trips by IST weekday of entry, including every weekday
+pub fn by_weekday(trips: &[RoundTrip]) -> Vec<PerformanceBucket> {
+    let mut buckets: BTreeMap<u32, Vec<&RoundTrip>> = (0..5).map(|day| (day, Vec::new())).collect();
+    for trip in trips {
+        let weekday = trip.entered_at.with_timezone(&Kolkata).weekday();
+        buckets.entry(weekday.num_days_from_monday()).or_default().push(trip);
+    }
+    buckets
+        .into_iter()
+        .map(|(day, trips)| bucket(WEEKDAYS[day as usize].to_string(), &trips))
+        .collect()
+}
+
+fn bucket(label: String, trips: &[&RoundTrip]) -> PerformanceBucket {
+    let winning_trades = trips.iter().filter(|trip| trip.pnl > 0.0).count();
+    let total_pnl: f64 = trips.iter().map(|trip| trip.pnl).sum();
+    let count = trips.len();
+
+    PerformanceBucket {
+        bucket: label,
+        trades: count,
+        winning_trades,
+        win_rate: if count > 0 { winning_trades as f64 / count as f64 } else { 0.0 },
+        total_pnl,
+        average_pnl: if count > 0 { total_pnl / count as f64 } else { 0.0 },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn input(strategy: &str, trade_type: TradeType, quantity: i64, price: f64, day: u32, hour: u32, minute: u32) -> StatementInput {
+        StatementInput {
+            trade_id: format!("{}-{}-{}", day, hour, minute),
+            // UTC; IST is 5:30 ahead
+            executed_at: Utc.with_ymd_and_hms(2024, 8, day, hour, minute, 0).unwrap(),
+            symbol: "INFY".to_string(),
+            exchange: "NSE".to_string(),
+            strategy_id: strategy.to_string(),
+            strategy_name: strategy.to_string(),
+            trade_type,
+            quantity,
+            price,
+        }
+    }
+
+    #[test]
+    fn test_round_trips_split_across_entries() {
+        let trades = vec![
+            // Monday 09:30 and 10:30 IST entries, closed together on Tuesday
+            input("a", TradeType::Buy, 10, 100.0, 5, 4, 0),
+            input("a", TradeType::Buy, 10, 110.0, 5, 5, 0),
+            // Another strategy's short does not close strategy a's longs
+            input("b", TradeType::Sell, 5, 120.0, 5, 6, 0),
+            input("a", TradeType::Sell, 15, 105.0, 6, 8, 0),
+            input("b", TradeType::Buy, 5, 115.0, 6, 9, 0),
+        ];
+
+        let trips = round_trips(&trades);
+        let pnls: Vec<(&str, i64, f64)> = trips.iter().map(|trip| (trip.strategy_id.as_str(), trip.quantity, trip.pnl)).collect();
+        assert_eq!(pnls, vec![("a", 10, 50.0), ("a", 5, -25.0), ("b", 5, 25.0)]);
+    }
+
+    #[test]
+    fn test_buckets_by_entry_hour_and_weekday() {
+        let trades = vec![
+            input("a", TradeType::Buy, 10, 100.0, 5, 4, 0),
+            input("a", TradeType::Buy, 10, 110.0, 5, 5, 0),
+            input("a", TradeType::Sell, 15, 105.0, 6, 8, 0),
+        ];
+        let trips = round_trips(&trades);
+
+        let hours = by_hour(&trips);
+        assert_eq!(hours.len(), 7);
+        let nine = hours.iter().find(|bucket| bucket.bucket == "9").unwrap();
+        assert_eq!((nine.trades, nine.win_rate, nine.total_pnl), (1, 1.0, 50.0));
+        let ten = hours.iter().find(|bucket| bucket.bucket == "10").unwrap();
+        assert_eq!((ten.trades, ten.win_rate, ten.average_pnl), (1, 0.0, -25.0));
+
+        let weekdays = by_weekday(&trips);
+        assert_eq!(weekdays.len(), 5);
+        assert_eq!(weekdays[0].bucket, "Mon");
+        assert_eq!((weekdays[0].trades, weekdays[0].total_pnl), (2, 25.0));
+        assert_eq!(weekdays[1].trades, 0);
+    }
+}
@@ -0,0 +1,207 @@
+use crate::error::Result;
+use crate::services::pnl_statement::PnlStatementService;
+use crate::services::time_breakdown::{round_trips, RoundTrip};
+use crate::trading::analytics_filter::TradeFilter;
+use crate::trading::session::session_date;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+/// How many winning and losing streaks ran to a given length
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StreakLength {
+    pub length: usize,
+    pub winning_streaks: usize,
+    pub losing_streaks: usize,
+}
+
+/// Runs of consecutive winning or losing closed trades
+///
+/// A breakeven trade ends the running streak without starting one.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct StreakStats {
+    pub closed_trades: usize,
+    /// Streak still running at the latest closed trade; at most one of the two is non-zero
+    pub current_winning_streak: usize,
+    pub current_losing_streak: usize,
+    pub max_winning_streak: usize,
+    pub max_losing_streak: usize,
+    /// Every streak, including the running one, counted by length
+    pub distribution: Vec<StreakLength>,
+}
+
+/// Streaks of one strategy's closed trades
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StrategyStreaks {
+    pub strategy_id: String,
+    pub strategy_name: String,
+    #[serde(flatten)]
+    pub streaks: StreakStats,
+}
+
+/// Streaks of the trades closed within a filter, overall and by strategy
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreakReport {
+    #[serde(flatten)]
+    pub filter: TradeFilter,
+    pub overall: StreakStats,
+    pub by_strategy: Vec<StrategyStreaks>,
+}
+
+impl PnlStatementService {
+    /// Win and loss streaks of the trades closed within a filter, running up to its end date
+    pub async fn streaks(&self, user_id: &str, filter: &TradeFilter) -> Result<StreakReport> {
+        let trades = self.load_trades(user_id, filter.to).await?;
+        let names: HashMap<&str, &str> = trades.iter()
+            .map(|trade| (trade.strategy_id.as_str(), trade.strategy_name.as_str()))
+            .collect();
+
+        // Round trips are built from the whole history so positions opened earlier still close
+        let trips: Vec<RoundTrip> = round_trips(&trades)
+            .into_iter()
+            .filter(|trip| filter.matches(session_date(trip.exited_at), &trip.strategy_id, &trip.symbol))
+            .collect();
+        let closed = closed_trades(&trips);
+        let mut pnl_by_strategy: BTreeMap<&str, Vec<f64>> = BTreeMap::new();
+        for trade in &closed {
+            pnl_by_strategy.entry(trade.strategy_id).or_default().push(trade.pnl);
+        }
+
+        Ok(StreakReport {
+            filter: filter.clone(),
+            overall: streak_stats(&closed.iter().map(|trade| trade.pnl).collect::<Vec<_>>()),
+            by_strategy: pnl_by_strategy.into_iter()
+                .map(|(strategy_id, pnls)| StrategyStreaks {
+                    strategy_id: strategy_id.to_string(),
+                    strategy_name: names.get(strategy_id).copied().unwrap_or("Unknown").to_string(),
+                    streaks: streak_stats(&pnls),
+                })
+                .collect(),
+        })
+    }
+}
+
+/// A closing fill's P&L, summed over the round trips it closed
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClosedTrade<'a> {
+    pub strategy_id: &'a str,
+    pub exited_at: DateTime<Utc>,
+    pub pnl: f64,
+}
+
+/// Merge round trips closed by the same fill, so a fill closing several entries counts once
+///
+/// Round trips of one fill are adjacent, as `round_trips` emits them together.
+pub fn closed_trades(trips: &[RoundTrip]) -> Vec<ClosedTrade<'_>> {
+    let mut closed: Vec<ClosedTrade> = Vec::new();
+    let mut previous: Option<&RoundTrip> = None;
+
+    for trip in trips {
+        let same_fill = previous.is_some_and(|last| {
+            last.exited_at == trip.exited_at
+                && last.strategy_id == trip.strategy_id
+                && last.exchange == trip.exchange
+                && last.symbol == trip.symbol
+        });
+        match closed.last_mut() {
+            Some(last) if same_fill => last.pnl += trip.pnl,
+            _ => closed.push(ClosedTrade {
+                strategy_id: &trip.strategy_id,
+                exited_at: trip.exited_at,
+                pnl: trip.pnl,
+            }),
+        }
+        previous = Some(trip);
+    }
+
+    closed
+}
+
+/// Streaks of a P&L sequence, oldest first
+pub fn streak_stats(pnls: &[f64]) -> StreakStats {
+    let mut distribution: BTreeMap<usize, (usize, usize)> = BTreeMap::new();
+    let (mut wins, mut losses) = (0, 0);
+    let (mut max_wins, mut max_losses) = (0, 0);
+
+    // Count a finished run and reset it
+    let mut finish = |run: &mut usize, winning: bool| {
+        if *run > 0 {
+            let counts = distribution.entry(*run).or_default();
+            if winning { counts.0 += 1 } else { counts.1 += 1 }
+        }
+        *run = 0;
+    };
+
+    for pnl in pnls {
+        if *pnl > 0.0 {
+            finish(&mut losses, false);
+            wins += 1;
+            max_wins = max_wins.max(wins);
+        } else if *pnl < 0.0 {
+            finish(&mut wins, true);
+            losses += 1;
+            max_losses = max_losses.max(losses);
+        } else {
+            finish(&mut wins, true);
+            finish(&mut losses, false);
+        }
+    }
+
+    // The running streak counts too, without ending it
+    let (current_wins, current_losses) = (wins, losses);
+    finish(&mut wins, true);
+    finish(&mut losses, false);
+
+    StreakStats {
+        closed_trades: pnls.len(),
+        current_winning_streak: current_wins,
+        current_losing_streak: current_losses,
+        max_winning_streak: max_wins,
+        max_losing_streak: max_losses,
+        distribution: distribution.into_iter()
+            .map(|(length, (winning_streaks, losing_streaks))| StreakLength { length, winning_streaks, losing_streaks })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_streak_stats() {
+        let pnls = [100.0, 50.0, -20.0, 30.0, 40.0, 10.0, 0.0, -5.0, -5.0];
+        let stats = streak_stats(&pnls);
+
+        assert_eq!(stats.closed_trades, 9);
+        assert_eq!((stats.current_winning_streak, stats.current_losing_streak), (0, 2));
+        assert_eq!((stats.max_winning_streak, stats.max_losing_streak), (3, 2));
+        assert_eq!(stats.distribution, vec![
+            StreakLength { length: 1, winning_streaks: 0, losing_streaks: 1 },
+            StreakLength { length: 2, winning_streaks: 1, losing_streaks: 1 },
+            StreakLength { length: 3, winning_streaks: 1, losing_streaks: 0 },
+        ]);
+        assert_eq!(streak_stats(&[]), StreakStats::default());
+    }
+
+    #[test]
+    fn test_closed_trades_merge_one_fill() {
+        let at = |hour: u32| Utc.with_ymd_and_hms(2024, 8, 5, hour, 0, 0).unwrap();
+        let trip = |strategy: &str, exited_at, pnl| RoundTrip {
+            symbol: "INFY".to_string(),
+            exchange: "NSE".to_string(),
+            strategy_id: strategy.to_string(),
+            quantity: 1,
+            entered_at: at(4),
+            exited_at,
+            pnl,
+        };
+        // One sell closing two entries at a net loss, then another strategy's win at the same time
+        let trips = vec![trip("a", at(6), 50.0), trip("a", at(6), -80.0), trip("b", at(6), 10.0)];
+
+        let closed = closed_trades(&trips);
+        let pnls: Vec<(&str, f64)> = closed.iter().map(|trade| (trade.strategy_id, trade.pnl)).collect();
+        assert_eq!(pnls, vec![("a", -30.0), ("b", 10.0)]);
+    }
+}
@@ -1,17 +1,60 @@
+use crate::api::kite_client::KiteApiClient;
+use crate::api::ticker::{parse_packets, DepthSide, TickPacket, DEPTH_LEVELS};
 use crate::error::{HedgeXError, Result};
 use crate::models::kite::*;
 use crate::services::EnhancedDatabaseService;
+use crate::services::tick_recorder::TickRecorder;
+use crate::services::feed_health::{FeedAlert, FeedHealth, FeedMetrics};
+use crate::services::tick_channel::{TickPolicy, TickReceiver, TickSender, DEFAULT_TICK_QUEUE_CAPACITY};
+use crate::services::ticker_shards::{ConnectionHealth, ConnectionStats, ShardAssignment, MAX_TICKER_CONNECTIONS};
+use crate::models::backtesting::{Timeframe, OHLCV};
+use crate::models::trading::{DepthLevel, MarketDepth};
+use crate::trading::bars::LiveCandles;
+use crate::trading::freshness::PriceFreshness;
+use crate::trading::live_metrics::{LiveMetrics, SymbolMetrics};
+use crate::trading::session::is_in_session;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::time::{Duration, Instant};
-use tokio::sync::{broadcast, RwLock, Mutex};
+use rand::Rng;
+use tokio::sync::{broadcast, Notify, RwLock, Mutex};
 use tokio::time::{sleep, timeout};
 use tracing::{debug, error, info, warn};
 use websocket::{ClientBuilder, OwnedMessage, WebSocketError};
 use url::Url;
 use chrono::{DateTime, Utc};
 use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+
+/// Coarser timeframes built from live ticks unless configured otherwise
+pub const DEFAULT_CANDLE_TIMEFRAMES: [Timeframe; 3] = [Timeframe::Minute5, Timeframe::Minute15, Timeframe::Hour1];
+
+/// Most `market_data_update` events pushed per displayed symbol each second unless configured otherwise
+pub const DEFAULT_DISPLAY_UPDATES_PER_SECOND: f64 = 4.0;
+
+/// Seconds between REST quote polls while the ticker is down; Kite allows one quote call a second
+const QUOTE_POLL_INTERVAL_SECONDS: u64 = 2;
+
+/// Instruments Kite returns from one quote call
+const QUOTE_BATCH_SIZE: usize = 500;
+
+/// Seconds a cached instrument is kept without an update
+pub const DEFAULT_CACHE_TTL_SECONDS: i64 = 1800;
+
+/// Instruments kept in the market data cache
+pub const DEFAULT_CACHE_MAX_ENTRIES: usize = 5000;
+
+/// How often the market data cache is swept for expired entries
+const CACHE_SWEEP_INTERVAL_SECONDS: u64 = 30;
+
+/// Indices followed for the dashboard header, regime filters and hedging, with their NSE instrument tokens
+pub const MARKET_INDICES: [(&str, u64); 3] = [
+    ("NIFTY 50", 256265),
+    ("NIFTY BANK", 260105),
+    ("INDIA VIX", 264969),
+];
 
 /// Market data structure for real-time updates
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +69,25 @@ pub struct MarketData {
     pub timestamp: DateTime<Utc>,
     pub change: Option<Decimal>,
     pub change_percent: Option<Decimal>,
+    /// Order book levels, present for instruments subscribed in full mode
+    #[serde(default)]
+    pub depth: Option<MarketDepth>,
+    /// Polled from the REST quote API while the ticker was down rather than streamed
+    #[serde(default)]
+    pub delayed: bool,
+}
+
+/// Latest level of a followed index
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexQuote {
+    pub symbol: String,
+    pub instrument_token: u64,
+    pub ltp: Decimal,
+    /// Previous session's close
+    pub close: Option<Decimal>,
+    pub change: Option<Decimal>,
+    pub change_percent: Option<Decimal>,
+    pub timestamp: DateTime<Utc>,
 }
 
 /// OHLC data structure
@@ -38,7 +100,8 @@ pub struct OHLC {
 }
 
 /// WebSocket connection status
-#[derive(Debug, Clone, PartialEq)]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum ConnectionStatus {
     Disconnected,
     Connecting,
@@ -47,17 +110,91 @@ pub enum ConnectionStatus {
     Failed,
 }
 
-/// WebSocket subscription mode
+/// Connection status change pushed to listeners such as the UI
 #[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConnectionEvent {
+    pub status: ConnectionStatus,
+    /// Reconnection attempt in progress, 0 outside reconnection
+    pub attempt: u32,
+    /// Delay before the next reconnection attempt, when one is scheduled
+    pub retry_in_ms: Option<u64>,
+    pub message: Option<String>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ConnectionEvent {
+    /// Event for a status outside reconnection
+    pub fn new(status: ConnectionStatus, message: Option<String>) -> Self {
+        Self {
+            status,
+            attempt: 0,
+            retry_in_ms: None,
+            message,
+            timestamp: Utc::now(),
+        }
+    }
+}
+
+/// WebSocket subscription mode
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 pub enum SubscriptionMode {
     /// LTP (Last Traded Price) only
     LTP,
-    /// Quote (LTP + market depth)
+    /// Quote (LTP + volume + OHLC)
     Quote,
-    /// Full (Quote + OHLC + other data)
+    /// Full (Quote + five levels of market depth)
     Full,
 }
 
+impl std::str::FromStr for SubscriptionMode {
+    type Err = String;
+    
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ltp" => Ok(SubscriptionMode::LTP),
+            "quote" => Ok(SubscriptionMode::Quote),
+            "full" => Ok(SubscriptionMode::Full),
+            _ => Err("Invalid subscription mode. Must be one of: ltp, quote, full".to_string()),
+        }
+    }
+}
+
+/// One subscribed instrument
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubscriptionInfo {
+    pub instrument_token: u64,
+    /// Trading symbol, when subscribed by symbol
+    pub symbol: Option<String>,
+    pub mode: SubscriptionMode,
+}
+
+impl SubscriptionMode {
+    /// Mode name used in Kite ticker messages
+    pub fn as_kite_mode(&self) -> &'static str {
+        match self {
+            SubscriptionMode::LTP => "ltp",
+            SubscriptionMode::Quote => "quote",
+            SubscriptionMode::Full => "full",
+        }
+    }
+}
+
+/// Writing half of a live ticker connection
+type TickerWriter = Arc<Mutex<Option<websocket::sender::Writer<std::net::TcpStream>>>>;
+
+/// One of the ticker connections instruments are spread across
+#[derive(Default)]
+struct TickerConnection {
+    /// Writing half of the live connection, used to change subscriptions while connected
+    writer: TickerWriter,
+    
+    /// Connection task handle for cleanup
+    handle: Mutex<Option<tokio::task::JoinHandle<()>>>,
+    
+    /// Messages received, last message time and drops
+    stats: Arc<ConnectionStats>,
+}
+
 /// WebSocket manager for handling real-time market data
 pub struct WebSocketManager {
     /// Database service for caching market data
@@ -75,11 +212,29 @@ pub struct WebSocketManager {
     /// Subscribed instrument tokens
     subscriptions: Arc<RwLock<HashMap<u64, SubscriptionMode>>>,
     
+    /// Trading symbols of instruments subscribed by symbol
+    instrument_symbols: Arc<RwLock<HashMap<u64, String>>>,
+    
+    /// Ticker connections; Kite limits how many instruments each one carries
+    connections: Vec<TickerConnection>,
+    
+    /// Which connection carries each subscribed instrument
+    shard_assignment: Arc<RwLock<ShardAssignment>>,
+    
     /// Market data cache
     market_data_cache: Arc<RwLock<HashMap<u64, MarketData>>>,
     
-    /// Broadcast channel for market data updates
-    market_data_tx: broadcast::Sender<MarketData>,
+    /// How long and how many instruments the cache keeps
+    cache_limits: Arc<RwLock<CacheLimits>>,
+    
+    /// When each symbol's price last arrived, used to flag stale cached prices
+    price_freshness: Arc<RwLock<PriceFreshness>>,
+    
+    /// Market data updates, buffered separately for each subscriber so none can slow the feed
+    market_data_tx: TickSender,
+    
+    /// Broadcast channel for order updates pushed by Kite
+    order_update_tx: broadcast::Sender<KiteOrderUpdate>,
     
     /// Connection retry configuration
     retry_config: RetryConfig,
@@ -87,8 +242,156 @@ pub struct WebSocketManager {
     /// Last connection attempt time
     last_connection_attempt: Arc<Mutex<Option<Instant>>>,
     
-    /// Connection handle for cleanup
-    connection_handle: Arc<Mutex<Option<tokio::task::JoinHandle<()>>>>,
+    /// Broadcast channel for connection status changes
+    status_tx: broadcast::Sender<ConnectionEvent>,
+    
+    /// Whether a dropped connection should be re-established; cleared by `disconnect`
+    auto_reconnect: Arc<AtomicBool>,
+    
+    /// Signalled when the connection task ends without being asked to
+    connection_lost: Arc<Notify>,
+    
+    /// Writes received ticks to the tick history when recording is on
+    tick_recorder: Arc<RwLock<Option<Arc<TickRecorder>>>>,
+    
+    /// Candles built from received ticks by symbol
+    candles: Arc<RwLock<LiveCandles>>,
+    
+    /// Spread, VWAP and rolling volume derived from received ticks by symbol
+    live_metrics: Arc<RwLock<LiveMetrics>>,
+    
+    /// Which symbols the UI shows and how often their updates are pushed
+    display_throttle: Arc<Mutex<DisplayThrottle>>,
+    
+    /// Tick rate, tick ages, parse errors and reconnects
+    feed_metrics: Arc<FeedMetrics>,
+    
+    /// Broadcast channel for feed stall alerts
+    feed_alert_tx: broadcast::Sender<FeedAlert>,
+}
+
+/// Limits updates pushed to the UI to the displayed symbols, at most a set rate per symbol
+///
+/// Ticks arriving inside the interval are dropped; the next tick after it carries the latest state.
+#[derive(Debug, Clone)]
+pub struct DisplayThrottle {
+    symbols: HashSet<String>,
+    min_interval: Duration,
+    last_pushed: HashMap<String, Instant>,
+}
+
+impl Default for DisplayThrottle {
+    fn default() -> Self {
+        Self {
+            symbols: HashSet::new(),
+            min_interval: Duration::from_secs_f64(1.0 / DEFAULT_DISPLAY_UPDATES_PER_SECOND),
+            last_pushed: HashMap::new(),
+        }
+    }
+}
+
+impl DisplayThrottle {
+    /// Throttle with no displayed symbols
+    pub fn new(max_per_second: f64) -> Result<Self> {
+        let mut throttle = Self::default();
+        throttle.set_max_per_second(max_per_second)?;
+        Ok(throttle)
+    }
+    
+    /// Replace the displayed symbols
+    pub fn set_symbols(&mut self, symbols: &[String]) {
+        self.symbols = symbols.iter().cloned().collect();
+        self.last_pushed.retain(|symbol, _| self.symbols.contains(symbol));
+    }
+    
+    /// Displayed symbols, sorted
+    pub fn symbols(&self) -> Vec<String> {
+        let mut symbols: Vec<String> = self.symbols.iter().cloned().collect();
+        symbols.sort();
+        symbols
+    }
+    
+    /// Change the per-symbol rate, between 0.1 and 50 updates a second
+    pub fn set_max_per_second(&mut self, max_per_second: f64) -> Result<()> {
+        if !(0.1..=50.0).contains(&max_per_second) {
+            return Err(HedgeXError::ValidationError(
+                "Update rate must be between 0.1 and 50 per second".to_string()
+            ));
+        }
+        
+        self.min_interval = Duration::from_secs_f64(1.0 / max_per_second);
+        Ok(())
+    }
+    
+    /// Per-symbol rate currently applied
+    pub fn max_per_second(&self) -> f64 {
+        1.0 / self.min_interval.as_secs_f64()
+    }
+    
+    /// Whether an update for the symbol should be pushed now, recording it if so
+    pub fn should_push(&mut self, symbol: &str, now: Instant) -> bool {
+        if !self.symbols.contains(symbol) {
+            return false;
+        }
+        
+        match self.last_pushed.get(symbol) {
+            Some(last) if now.duration_since(*last) < self.min_interval => false,
+            _ => {
+                self.last_pushed.insert(symbol.to_string(), now);
+                true
+            }
+        }
+    }
+}
+
+/// How long and how many instruments the market data cache keeps
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheLimits {
+    pub ttl_seconds: i64,
+    pub max_entries: usize,
+}
+
+impl Default for CacheLimits {
+    fn default() -> Self {
+        Self {
+            ttl_seconds: DEFAULT_CACHE_TTL_SECONDS,
+            max_entries: DEFAULT_CACHE_MAX_ENTRIES,
+        }
+    }
+}
+
+impl CacheLimits {
+    /// Limits of at least a minute and one instrument
+    pub fn new(ttl_seconds: i64, max_entries: usize) -> Result<Self> {
+        if ttl_seconds < 60 {
+            return Err(HedgeXError::ValidationError("Cache TTL must be at least 60 seconds".to_string()));
+        }
+        if max_entries == 0 {
+            return Err(HedgeXError::ValidationError("Cache must keep at least one instrument".to_string()));
+        }
+        
+        Ok(Self { ttl_seconds, max_entries })
+    }
+    
+    /// Drop instruments not updated within the TTL, then the least recently updated past the maximum
+    ///
+    /// Returns how many were dropped.
+    pub fn evict(&self, cache: &mut HashMap<u64, MarketData>, now: DateTime<Utc>) -> usize {
+        let before = cache.len();
+        cache.retain(|_, data| (now - data.timestamp).num_seconds() <= self.ttl_seconds);
+        
+        if cache.len() > self.max_entries {
+            let mut by_age: Vec<(DateTime<Utc>, u64)> = cache.iter()
+                .map(|(token, data)| (data.timestamp, *token))
+                .collect();
+            by_age.sort_unstable();
+            for (_, token) in by_age.iter().take(cache.len() - self.max_entries) {
+                cache.remove(token);
+            }
+        }
+        
+        before - cache.len()
+    }
 }
 
 /// Retry configuration for connection recovery
@@ -98,6 +401,8 @@ pub struct RetryConfig {
     pub initial_delay: Duration,
     pub max_delay: Duration,
     pub backoff_multiplier: f64,
+    /// Fraction of each delay randomized either way, between 0 and 1
+    pub jitter: f64,
 }
 
 impl Default for RetryConfig {
@@ -107,14 +412,34 @@ impl Default for RetryConfig {
             initial_delay: Duration::from_secs(1),
             max_delay: Duration::from_secs(60),
             backoff_multiplier: 2.0,
+            jitter: 0.2,
         }
     }
 }
 
+impl RetryConfig {
+    /// Delay after a failed attempt, counting attempts from 1
+    ///
+    /// Grows by `backoff_multiplier` per attempt up to `max_delay`, then moves by
+    /// up to `jitter` of itself so clients dropped together do not retry in step.
+    pub fn delay_for_attempt<R: Rng>(&self, attempt: u32, rng: &mut R) -> Duration {
+        let max_delay = self.max_delay.as_secs_f64();
+        let exponent = attempt.saturating_sub(1).min(64) as i32;
+        let delay = (self.initial_delay.as_secs_f64() * self.backoff_multiplier.powi(exponent)).min(max_delay);
+        
+        let jitter = self.jitter.clamp(0.0, 1.0);
+        let factor = if jitter > 0.0 { 1.0 + rng.gen_range(-jitter..=jitter) } else { 1.0 };
+        Duration::from_secs_f64((delay * factor).clamp(0.0, max_delay))
+    }
+}
+
 impl WebSocketManager {
     /// Create a new WebSocket manager
     pub fn new(db_service: Arc<EnhancedDatabaseService>) -> Self {
-        let (market_data_tx, _) = broadcast::channel(1000);
+        let market_data_tx = TickSender::new();
+        let (status_tx, _) = broadcast::channel(100);
+        let (order_update_tx, _) = broadcast::channel(100);
+        let (feed_alert_tx, _) = broadcast::channel(16);
         
         Self {
             db_service,
@@ -122,11 +447,25 @@ impl WebSocketManager {
             ws_url: "wss://ws.kite.trade".to_string(),
             api_credentials: Arc::new(RwLock::new(None)),
             subscriptions: Arc::new(RwLock::new(HashMap::new())),
+            instrument_symbols: Arc::new(RwLock::new(HashMap::new())),
+            connections: (0..MAX_TICKER_CONNECTIONS).map(|_| TickerConnection::default()).collect(),
+            shard_assignment: Arc::new(RwLock::new(ShardAssignment::default())),
             market_data_cache: Arc::new(RwLock::new(HashMap::new())),
+            cache_limits: Arc::new(RwLock::new(CacheLimits::default())),
+            price_freshness: Arc::new(RwLock::new(PriceFreshness::default())),
             market_data_tx,
+            order_update_tx,
             retry_config: RetryConfig::default(),
             last_connection_attempt: Arc::new(Mutex::new(None)),
-            connection_handle: Arc::new(Mutex::new(None)),
+            status_tx,
+            auto_reconnect: Arc::new(AtomicBool::new(false)),
+            connection_lost: Arc::new(Notify::new()),
+            tick_recorder: Arc::new(RwLock::new(None)),
+            candles: Arc::new(RwLock::new(LiveCandles::new(&DEFAULT_CANDLE_TIMEFRAMES))),
+            live_metrics: Arc::new(RwLock::new(LiveMetrics::default())),
+            display_throttle: Arc::new(Mutex::new(DisplayThrottle::default())),
+            feed_metrics: Arc::new(FeedMetrics::default()),
+            feed_alert_tx,
         }
     }
     
@@ -136,7 +475,7 @@ impl WebSocketManager {
     }
     
     /// Get the market data sender for testing
-    pub fn get_market_data_sender(&self) -> broadcast::Sender<MarketData> {
+    pub fn get_market_data_sender(&self) -> TickSender {
         self.market_data_tx.clone()
     }
     
@@ -148,7 +487,70 @@ impl WebSocketManager {
         Ok(())
     }
     
+    /// Start or stop recording ticks; a replaced recorder writes what it still holds
+    pub async fn set_tick_recorder(&self, recorder: Option<Arc<TickRecorder>>) {
+        let mut current = self.tick_recorder.write().await;
+        *current = recorder;
+    }
+    
+    /// Recorder currently writing ticks, if recording is on
+    pub async fn get_tick_recorder(&self) -> Option<Arc<TickRecorder>> {
+        self.tick_recorder.read().await.clone()
+    }
+    
+    /// Latest `count` candles of a symbol built from live ticks, oldest first
+    pub async fn get_recent_candles(&self, symbol: &str, timeframe: Timeframe, count: usize) -> Result<Vec<OHLCV>> {
+        if timeframe == Timeframe::Tick {
+            return Err(HedgeXError::ValidationError("Candles need a timeframe of at least one minute".to_string()));
+        }
+        
+        Ok(self.candles.read().await.recent(symbol, timeframe, count))
+    }
+    
+    /// Spread, VWAP and rolling volume of a symbol as of its latest tick
+    pub async fn get_live_metrics(&self, symbol: &str) -> Option<SymbolMetrics> {
+        self.live_metrics.read().await.get(symbol)
+    }
+    
+    /// Spread, VWAP and rolling volume of every symbol that has ticked, in symbol order
+    pub async fn get_all_live_metrics(&self) -> Vec<SymbolMetrics> {
+        self.live_metrics.read().await.all()
+    }
+    
+    /// Choose the coarser timeframes kept and stored; candles built so far are discarded
+    pub async fn set_candle_timeframes(&self, timeframes: &[Timeframe]) {
+        let mut candles = self.candles.write().await;
+        *candles = LiveCandles::new(timeframes);
+        info!("Building live candles for {:?}", candles.timeframes());
+    }
+    
+    /// Choose the symbols whose updates are pushed to the UI
+    pub async fn set_displayed_symbols(&self, symbols: &[String]) {
+        self.display_throttle.lock().await.set_symbols(symbols);
+        debug!("Pushing market data for {} displayed symbols", symbols.len());
+    }
+    
+    /// Change how many updates a second are pushed per displayed symbol
+    pub async fn set_display_update_rate(&self, max_per_second: f64) -> Result<()> {
+        self.display_throttle.lock().await.set_max_per_second(max_per_second)
+    }
+    
+    /// Displayed symbols and the per-symbol update rate
+    pub async fn get_display_settings(&self) -> (Vec<String>, f64) {
+        let throttle = self.display_throttle.lock().await;
+        (throttle.symbols(), throttle.max_per_second())
+    }
+    
+    /// Whether an update should be pushed to the UI now
+    pub async fn should_push_to_display(&self, market_data: &MarketData) -> bool {
+        self.display_throttle.lock().await.should_push(&market_data.symbol, Instant::now())
+    }
+    
     /// Connect to Kite WebSocket API
+    ///
+    /// Opens one connection per group of up to 3000 subscribed instruments. Once
+    /// connected, a dropped connection is re-established by the reconnection
+    /// monitor until `disconnect` is called.
     pub async fn connect(&self) -> Result<()> {
         let mut status = self.status.write().await;
         if *status == ConnectionStatus::Connected || *status == ConnectionStatus::Connecting {
@@ -157,6 +559,8 @@ impl WebSocketManager {
         
         *status = ConnectionStatus::Connecting;
         drop(status);
+        let _ = self.status_tx.send(ConnectionEvent::new(ConnectionStatus::Connecting, None));
+        self.auto_reconnect.store(true, Ordering::SeqCst);
         
         // Update last connection attempt
         {
@@ -167,6 +571,27 @@ impl WebSocketManager {
         info!("Attempting to connect to Kite WebSocket");
         
         // Get credentials
+        let connection_result = match self.connection_url().await {
+            Ok(ws_url) => self.connect_shards(&ws_url).await,
+            Err(e) => Err(e),
+        };
+        
+        match connection_result {
+            Ok(_) => {
+                self.set_status(ConnectionEvent::new(ConnectionStatus::Connected, None)).await;
+                info!("Successfully connected to Kite WebSocket");
+                Ok(())
+            }
+            Err(e) => {
+                self.set_status(ConnectionEvent::new(ConnectionStatus::Failed, Some(e.to_string()))).await;
+                error!("Failed to connect to Kite WebSocket: {}", e);
+                Err(e)
+            }
+        }
+    }
+    
+    /// Ticker URL authenticated with the current credentials
+    async fn connection_url(&self) -> Result<String> {
         let credentials = {
             let creds = self.api_credentials.read().await;
             creds.clone().ok_or_else(|| {
@@ -178,33 +603,45 @@ impl WebSocketManager {
             HedgeXError::WebSocketError("No access token available".to_string())
         })?;
         
-        // Build WebSocket URL with authentication
-        let ws_url = format!(
+        Ok(format!(
             "{}?api_key={}&access_token={}",
             self.ws_url, credentials.api_key, access_token
-        );
-        
-        // Start connection in background task
-        let connection_result = self.start_connection_task(ws_url).await;
+        ))
+    }
+    
+    /// Record a status change and notify status listeners
+    async fn set_status(&self, event: ConnectionEvent) {
+        Self::publish_status(&self.status, &self.status_tx, event).await;
+    }
+    
+    /// Store the status from an event and broadcast the event
+    async fn publish_status(
+        status: &Arc<RwLock<ConnectionStatus>>,
+        status_tx: &broadcast::Sender<ConnectionEvent>,
+        event: ConnectionEvent,
+    ) {
+        {
+            let mut status_guard = status.write().await;
+            *status_guard = event.status.clone();
+        }
         
-        match connection_result {
-            Ok(_) => {
-                let mut status = self.status.write().await;
-                *status = ConnectionStatus::Connected;
-                info!("Successfully connected to Kite WebSocket");
-                Ok(())
-            }
-            Err(e) => {
-                let mut status = self.status.write().await;
-                *status = ConnectionStatus::Failed;
-                error!("Failed to connect to Kite WebSocket: {}", e);
-                Err(e)
+        // Nobody listening is not an error
+        let _ = status_tx.send(event);
+    }
+    
+    /// Open every connection that carries instruments and isn't already live
+    async fn connect_shards(&self, ws_url: &str) -> Result<()> {
+        let shards = self.shard_assignment.read().await.active_shards();
+        for shard in shards {
+            if self.connections[shard].writer.lock().await.is_none() {
+                self.start_connection_task(shard, ws_url.to_string()).await?;
             }
         }
+        Ok(())
     }
     
-    /// Start the WebSocket connection task
-    async fn start_connection_task(&self, ws_url: String) -> Result<()> {
+    /// Start the task of one ticker connection
+    async fn start_connection_task(&self, shard: usize, ws_url: String) -> Result<()> {
         let url = Url::parse(&ws_url)
             .map_err(|e| HedgeXError::WebSocketError(format!("Invalid WebSocket URL: {}", e)))?;
         
@@ -214,55 +651,86 @@ impl WebSocketManager {
             .connect_insecure()
             .map_err(|e| HedgeXError::WebSocketError(format!("Failed to connect to WebSocket: {}", e)))?;
         
-        let (mut receiver, mut sender) = client.split()
+        let (mut receiver, sender) = client.split()
             .map_err(|e| HedgeXError::WebSocketError(format!("Failed to split WebSocket connection: {}", e)))?;
         
+        let connection = &self.connections[shard];
+        
+        // Subscription changes made while connected are written straight to the connection
+        {
+            let mut writer = connection.writer.lock().await;
+            *writer = Some(sender);
+        }
+        connection.stats.record_connected(Utc::now()).await;
+        
         // Clone necessary data for the connection task
         let status = Arc::clone(&self.status);
         let subscriptions = Arc::clone(&self.subscriptions);
+        let shard_assignment = Arc::clone(&self.shard_assignment);
+        let instrument_symbols = Arc::clone(&self.instrument_symbols);
+        let ticker_writer = Arc::clone(&connection.writer);
+        let connection_stats = Arc::clone(&connection.stats);
         let market_data_cache = Arc::clone(&self.market_data_cache);
+        let price_freshness = Arc::clone(&self.price_freshness);
         let market_data_tx = self.market_data_tx.clone();
+        let order_update_tx = self.order_update_tx.clone();
         let db_service = Arc::clone(&self.db_service);
-        let retry_config = self.retry_config.clone();
-        let last_connection_attempt = Arc::clone(&self.last_connection_attempt);
+        let status_tx = self.status_tx.clone();
+        let connection_lost = Arc::clone(&self.connection_lost);
+        let tick_recorder = Arc::clone(&self.tick_recorder);
+        let candles = Arc::clone(&self.candles);
+        let live_metrics = Arc::clone(&self.live_metrics);
+        let feed_metrics = Arc::clone(&self.feed_metrics);
         
         // Start the connection handling task
         let handle = tokio::spawn(async move {
-            info!("WebSocket connection task started");
+            info!("WebSocket connection {} task started", shard);
             
-            // Send initial subscription messages for existing subscriptions
-            let subs = subscriptions.read().await;
+            // Restore every subscription of this connection in the mode it had before the connection dropped
+            let subs: HashMap<u64, SubscriptionMode> = {
+                let tokens = shard_assignment.read().await.tokens(shard);
+                let subscriptions = subscriptions.read().await;
+                tokens.into_iter()
+                    .filter_map(|token| subscriptions.get(&token).map(|mode| (token, mode.clone())))
+                    .collect()
+            };
             if !subs.is_empty() {
-                let tokens: Vec<u64> = subs.keys().cloned().collect();
-                drop(subs);
-                
-                if let Err(e) = Self::send_subscribe_message(&mut sender, &tokens).await {
-                    error!("Failed to send initial subscription: {}", e);
+                if let Err(e) = Self::send_ticker_messages(&ticker_writer, Self::resubscribe_messages(&subs)).await {
+                    error!("Failed to restore subscriptions: {}", e);
+                } else {
+                    info!("Restored subscriptions for {} instruments", subs.len());
                 }
-            } else {
-                drop(subs);
             }
             
             // Main message processing loop
             loop {
                 match receiver.recv_message() {
                     Ok(message) => {
+                        connection_stats.record_message(Utc::now()).await;
                         match message {
                             OwnedMessage::Binary(data) => {
                                 // Process binary market data message
                                 if let Err(e) = Self::process_binary_message(
                                     &data,
+                                    &instrument_symbols,
                                     &market_data_cache,
+                                    &price_freshness,
                                     &market_data_tx,
                                     &db_service,
+                                    &tick_recorder,
+                                    &candles,
+                                    &live_metrics,
+                                    &feed_metrics,
                                 ).await {
+                                    feed_metrics.record_parse_error();
                                     warn!("Failed to process binary message: {}", e);
                                 }
                             }
                             OwnedMessage::Text(text) => {
                                 debug!("Received text message: {}", text);
                                 // Handle text messages (usually control messages)
-                                if let Err(e) = Self::process_text_message(&text).await {
+                                if let Err(e) = Self::process_text_message(&text, &order_update_tx).await {
+                                    feed_metrics.record_parse_error();
                                     warn!("Failed to process text message: {}", e);
                                 }
                             }
@@ -272,8 +740,12 @@ impl WebSocketManager {
                             }
                             OwnedMessage::Ping(data) => {
                                 // Respond to ping with pong
-                                if let Err(e) = sender.send_message(&OwnedMessage::Pong(data)) {
-                                    error!("Failed to send pong: {}", e);
+                                let pong = match ticker_writer.lock().await.as_mut() {
+                                    Some(writer) => writer.send_message(&OwnedMessage::Pong(data)).is_ok(),
+                                    None => false,
+                                };
+                                if !pong {
+                                    error!("Failed to send pong");
                                     break;
                                 }
                             }
@@ -294,24 +766,62 @@ impl WebSocketManager {
                 }
             }
             
-            // Connection lost, update status
-            {
-                let mut status_guard = status.write().await;
-                *status_guard = ConnectionStatus::Disconnected;
-            }
+            // Connection lost; the reconnection monitor reopens it along with any other dropped one
+            ticker_writer.lock().await.take();
+            connection_stats.record_closed(true).await;
+            Self::publish_status(
+                &status,
+                &status_tx,
+                ConnectionEvent::new(
+                    ConnectionStatus::Disconnected,
+                    Some(format!("Connection {} lost", shard)),
+                ),
+            ).await;
+            connection_lost.notify_one();
             
-            info!("WebSocket connection task ended");
+            info!("WebSocket connection {} task ended", shard);
         });
         
         // Store the connection handle
         {
-            let mut connection_handle = self.connection_handle.lock().await;
-            *connection_handle = Some(handle);
+            let mut connection_handle = connection.handle.lock().await;
+            if let Some(previous) = connection_handle.replace(handle) {
+                previous.abort();
+            }
         }
         
         Ok(())
     }
     
+    /// Stop one connection's task and mark it closed
+    async fn close_connection(&self, shard: usize) {
+        let connection = &self.connections[shard];
+        if let Some(handle) = connection.handle.lock().await.take() {
+            handle.abort();
+        }
+        
+        // The aborted task no longer owns the connection
+        if connection.writer.lock().await.take().is_some() {
+            connection.stats.record_closed(false).await;
+        }
+    }
+    
+    /// Send messages on one connection, opening it if the manager is connected but this connection isn't yet
+    async fn send_to_shard(&self, shard: usize, messages: Vec<String>) -> Result<()> {
+        let writer = &self.connections[shard].writer;
+        if writer.lock().await.is_none() {
+            if self.get_status().await == ConnectionStatus::Connected {
+                // A new connection subscribes to everything assigned to it on start
+                let ws_url = self.connection_url().await?;
+                info!("Opening ticker connection {} for more instruments", shard);
+                return self.start_connection_task(shard, ws_url).await;
+            }
+            return Ok(());
+        }
+        
+        Self::send_ticker_messages(writer, messages).await
+    }
+    
     /// Send subscribe message to WebSocket
     async fn send_subscribe_message(
         sender: &mut websocket::sender::Writer<std::net::TcpStream>,
@@ -330,37 +840,139 @@ impl WebSocketManager {
         Ok(())
     }
     
+    /// Send text messages on the live connection; does nothing while disconnected
+    async fn send_ticker_messages(
+        ticker_writer: &TickerWriter,
+        messages: Vec<String>,
+    ) -> Result<()> {
+        let mut writer = ticker_writer.lock().await;
+        if let Some(sender) = writer.as_mut() {
+            for message in messages {
+                sender.send_message(&OwnedMessage::Text(message))
+                    .map_err(|e| HedgeXError::WebSocketError(format!("Failed to send subscription message: {}", e)))?;
+            }
+        }
+        Ok(())
+    }
+    
+    /// Subscribe message for every token followed by one mode message per mode
+    pub fn resubscribe_messages(subscriptions: &HashMap<u64, SubscriptionMode>) -> Vec<String> {
+        if subscriptions.is_empty() {
+            return Vec::new();
+        }
+        
+        let mut tokens: Vec<u64> = subscriptions.keys().cloned().collect();
+        tokens.sort_unstable();
+        let mut messages = vec![serde_json::json!({ "a": "subscribe", "v": tokens }).to_string()];
+        
+        for mode in [SubscriptionMode::LTP, SubscriptionMode::Quote, SubscriptionMode::Full] {
+            let mode_tokens: Vec<u64> = tokens.iter()
+                .filter(|token| subscriptions.get(token) == Some(&mode))
+                .cloned()
+                .collect();
+            if !mode_tokens.is_empty() {
+                messages.push(Self::mode_message(&mode, &mode_tokens));
+            }
+        }
+        messages
+    }
+    
+    /// Message switching instruments to a mode
+    pub fn mode_message(mode: &SubscriptionMode, tokens: &[u64]) -> String {
+        serde_json::json!({ "a": "mode", "v": [mode.as_kite_mode(), tokens] }).to_string()
+    }
+    
     /// Process binary market data message
     async fn process_binary_message(
         data: &[u8],
+        instrument_symbols: &Arc<RwLock<HashMap<u64, String>>>,
         market_data_cache: &Arc<RwLock<HashMap<u64, MarketData>>>,
-        market_data_tx: &broadcast::Sender<MarketData>,
+        price_freshness: &Arc<RwLock<PriceFreshness>>,
+        market_data_tx: &TickSender,
         db_service: &Arc<EnhancedDatabaseService>,
+        tick_recorder: &Arc<RwLock<Option<Arc<TickRecorder>>>>,
+        candles: &Arc<RwLock<LiveCandles>>,
+        live_metrics: &Arc<RwLock<LiveMetrics>>,
+        feed_metrics: &FeedMetrics,
     ) -> Result<()> {
-        // Parse binary data according to Kite's protocol
-        let market_data = Self::parse_kite_binary_data(data)?;
-        
-        // Update cache
-        {
-            let mut cache = market_data_cache.write().await;
-            cache.insert(market_data.instrument_token, market_data.clone());
-        }
-        
-        // Cache in database for persistence
-        if let Err(e) = Self::cache_market_data_in_db(db_service, &market_data).await {
-            warn!("Failed to cache market data in database: {}", e);
-        }
-        
-        // Broadcast to subscribers
-        if let Err(e) = market_data_tx.send(market_data) {
-            warn!("Failed to broadcast market data: {}", e);
+        // A message holds one or more length-prefixed packets; a one-byte heartbeat holds none
+        for packet in parse_packets(data) {
+            let packet = packet.map_err(|e| HedgeXError::WebSocketError(e.to_string()))?;
+            let token = packet.instrument_token() as u64;
+            let symbol = instrument_symbols.read().await.get(&token)
+                .cloned()
+                .unwrap_or_else(|| format!("SYMBOL_{}", token));
+            let market_data = match MarketData::from_packet(symbol, &packet) {
+                Ok(market_data) => market_data,
+                Err(e) => {
+                    warn!("Skipping tick for instrument {}: {}", token, e);
+                    continue;
+                }
+            };
+            feed_metrics.record_tick(&market_data.symbol, market_data.timestamp).await;
+            
+            // Update cache
+            {
+                let mut cache = market_data_cache.write().await;
+                cache.insert(market_data.instrument_token, market_data.clone());
+            }
+            price_freshness.write().await.record(&market_data.symbol, Utc::now());
+            
+            // Cache in database for persistence
+            if let Err(e) = Self::cache_market_data_in_db(db_service, &market_data).await {
+                warn!("Failed to cache market data in database: {}", e);
+            }
+            
+            // Keep the tick history for tick-level backtests; writes are batched off the feed
+            if let Some(recorder) = tick_recorder.read().await.as_ref() {
+                recorder.record(&market_data);
+            }
+            
+            Self::update_live_metrics(live_metrics, &market_data).await;
+            
+            // Completed candles are stored off the feed, at most a few per symbol a minute
+            let completed = candles.write().await.update(
+                &market_data.symbol,
+                market_data.ltp,
+                market_data.volume as i64,
+                market_data.timestamp,
+            );
+            if !completed.is_empty() {
+                let db_service = Arc::clone(db_service);
+                let symbol = market_data.symbol.clone();
+                tokio::spawn(async move {
+                    if let Err(e) = Self::store_candles_in_db(&db_service, &symbol, &completed).await {
+                        warn!("Failed to store candles for {}: {}", symbol, e);
+                    }
+                });
+            }
+            
+            // Broadcast to subscribers
+            if let Err(e) = market_data_tx.send(market_data) {
+                warn!("Failed to broadcast market data: {}", e);
+            }
         }
-        
+            
         Ok(())
     }
     
+    /// Fold a tick into its symbol's spread, VWAP and rolling volume
+    async fn update_live_metrics(live_metrics: &Arc<RwLock<LiveMetrics>>, market_data: &MarketData) {
+        live_metrics.write().await.update(
+            &market_data.symbol,
+            market_data.ltp,
+            market_data.bid,
+            market_data.ask,
+            market_data.volume as i64,
+            market_data.timestamp,
+        );
+    }
+    
     /// Process text message from WebSocket
-    async fn process_text_message(text: &str) -> Result<()> {
+    async fn process_text_message(
+        text: &str,
+        order_update_tx: &broadcast::Sender<KiteOrderUpdate>,
+    ) -> Result<()> {
         debug!("Processing text message: {}", text);
         
         // Parse JSON message
@@ -379,6 +991,15 @@ impl WebSocketManager {
                         .unwrap_or("Unknown error");
                     error!("WebSocket error: {}", error_msg);
                 }
+                "order" => {
+                    let data = message.get("data").cloned().unwrap_or_default();
+                    let update: KiteOrderUpdate = serde_json::from_value(data)
+                        .map_err(|e| HedgeXError::WebSocketError(format!("Failed to parse order update: {}", e)))?;
+                    
+                    debug!("Order update for {}: {:?}", update.order_id, update.status);
+                    // Nobody listening just means no trading engine is running
+                    let _ = order_update_tx.send(update);
+                }
                 _ => {
                     debug!("Unknown message type: {}", msg_type);
                 }
@@ -388,120 +1009,6 @@ impl WebSocketManager {
         Ok(())
     }
     
-    /// Parse Kite binary data format
-    fn parse_kite_binary_data(data: &[u8]) -> Result<MarketData> {
-        // Implementation based on Kite's binary protocol
-        // Reference: https://kite.trade/docs/connect/v3/websocket/#binary-message-format
-        
-        if data.len() < 8 {
-            return Err(HedgeXError::WebSocketError("Invalid binary data length".to_string()));
-        }
-        
-        // Extract instrument token (first 4 bytes, big endian)
-        let instrument_token = u32::from_be_bytes([data[0], data[1], data[2], data[3]]) as u64;
-        
-        // Determine packet length and mode
-        let packet_length = data.len();
-        
-        // Get symbol from instrument token mapping
-        // In a real implementation, we would look up the symbol from a mapping table
-        let symbol = format!("SYMBOL_{}", instrument_token);
-        
-        // Initialize market data with default values
-        let mut market_data = MarketData {
-            symbol,
-            instrument_token,
-            ltp: Decimal::ZERO,
-            volume: 0,
-            bid: Decimal::ZERO,
-            ask: Decimal::ZERO,
-            ohlc: None,
-            timestamp: Utc::now(),
-            change: None,
-            change_percent: None,
-        };
-        
-        // Parse based on packet length
-        if packet_length == 8 {
-            // Mode: LTP
-            let ltp_raw = f32::from_be_bytes([data[4], data[5], data[6], data[7]]);
-            market_data.ltp = Decimal::try_from(ltp_raw as f64)
-                .map_err(|e| HedgeXError::WebSocketError(format!("Failed to convert LTP: {}", e)))?;
-        } else if packet_length >= 28 {
-            // Mode: Quote or Full
-            
-            // Last traded price
-            let ltp_raw = f32::from_be_bytes([data[4], data[5], data[6], data[7]]);
-            market_data.ltp = Decimal::try_from(ltp_raw as f64)
-                .map_err(|e| HedgeXError::WebSocketError(format!("Failed to convert LTP: {}", e)))?;
-            
-            // Last traded quantity
-            let ltq = u32::from_be_bytes([data[8], data[9], data[10], data[11]]);
-            
-            // Average traded price
-            let atp_raw = f32::from_be_bytes([data[12], data[13], data[14], data[15]]);
-            
-            // Volume
-            let volume = u32::from_be_bytes([data[16], data[17], data[18], data[19]]) as u64;
-            market_data.volume = volume;
-            
-            // Best bid price
-            let bid_raw = f32::from_be_bytes([data[20], data[21], data[22], data[23]]);
-            market_data.bid = Decimal::try_from(bid_raw as f64)
-                .map_err(|e| HedgeXError::WebSocketError(format!("Failed to convert bid: {}", e)))?;
-            
-            // Best ask price
-            let ask_raw = f32::from_be_bytes([data[24], data[25], data[26], data[27]]);
-            market_data.ask = Decimal::try_from(ask_raw as f64)
-                .map_err(|e| HedgeXError::WebSocketError(format!("Failed to convert ask: {}", e)))?;
-            
-            // If we have full quote data (44 bytes or more)
-            if packet_length >= 44 {
-                // Open price
-                let open_raw = f32::from_be_bytes([data[28], data[29], data[30], data[31]]);
-                let open = Decimal::try_from(open_raw as f64)
-                    .map_err(|e| HedgeXError::WebSocketError(format!("Failed to convert open: {}", e)))?;
-                
-                // High price
-                let high_raw = f32::from_be_bytes([data[32], data[33], data[34], data[35]]);
-                let high = Decimal::try_from(high_raw as f64)
-                    .map_err(|e| HedgeXError::WebSocketError(format!("Failed to convert high: {}", e)))?;
-                
-                // Low price
-                let low_raw = f32::from_be_bytes([data[36], data[37], data[38], data[39]]);
-                let low = Decimal::try_from(low_raw as f64)
-                    .map_err(|e| HedgeXError::WebSocketError(format!("Failed to convert low: {}", e)))?;
-                
-                // Close price
-                let close_raw = f32::from_be_bytes([data[40], data[41], data[42], data[43]]);
-                let close = Decimal::try_from(close_raw as f64)
-                    .map_err(|e| HedgeXError::WebSocketError(format!("Failed to convert close: {}", e)))?;
-                
-                // Set OHLC data
-                market_data.ohlc = Some(OHLC {
-                    open,
-                    high,
-                    low,
-                    close,
-                });
-                
-                // Calculate change and change percent
-                if close > Decimal::ZERO {
-                    let change = market_data.ltp - close;
-                    let change_percent = (change * Decimal::new(100, 0)) / close;
-                    
-                    market_data.change = Some(change);
-                    market_data.change_percent = Some(change_percent);
-                }
-            }
-        }
-        
-        // Validate the market data
-        market_data.validate()?;
-        
-        Ok(market_data)
-    }
-    
     /// Cache market data in database
     async fn cache_market_data_in_db(
         db_service: &Arc<EnhancedDatabaseService>,
@@ -530,12 +1037,49 @@ impl WebSocketManager {
         Ok(())
     }
     
+    /// Store completed candles with the historical data used for backtests
+    async fn store_candles_in_db(
+        db_service: &Arc<EnhancedDatabaseService>,
+        symbol: &str,
+        candles: &[(Timeframe, OHLCV)],
+    ) -> Result<()> {
+        let db = db_service.get_database();
+        let mut tx = db.get_pool().begin().await.map_err(HedgeXError::DatabaseError)?;
+        
+        for (timeframe, candle) in candles {
+            sqlx::query(
+                r#"
+                INSERT OR REPLACE INTO historical_data 
+                (symbol, exchange, timestamp, open, high, low, close, volume, timeframe)
+                VALUES (?, 'NSE', ?, ?, ?, ?, ?, ?, ?)
+                "#
+            )
+            .bind(symbol)
+            .bind(candle.timestamp)
+            .bind(candle.open.to_f64().unwrap_or(0.0))
+            .bind(candle.high.to_f64().unwrap_or(0.0))
+            .bind(candle.low.to_f64().unwrap_or(0.0))
+            .bind(candle.close.to_f64().unwrap_or(0.0))
+            .bind(candle.volume)
+            .bind(timeframe.to_string())
+            .execute(&mut *tx)
+            .await
+            .map_err(HedgeXError::DatabaseError)?;
+        }
+        
+        tx.commit().await.map_err(HedgeXError::DatabaseError)?;
+        Ok(())
+    }
+    
     /// Subscribe to market data for specific instruments
     pub async fn subscribe_to_instruments(
         &self,
         tokens: Vec<u64>,
         mode: SubscriptionMode,
     ) -> Result<()> {
+        // Spread new instruments across connections, refusing what doesn't fit
+        let groups = self.shard_assignment.write().await.assign(&tokens)?;
+        
         // Update subscriptions
         {
             let mut subs = self.subscriptions.write().await;
@@ -544,35 +1088,172 @@ impl WebSocketManager {
             }
         }
         
-        // If connected, send subscription message
-        let status = self.status.read().await;
-        if *status == ConnectionStatus::Connected {
-            // This would need to be implemented with a proper message sender
-            info!("Subscribed to {} instruments with mode {:?}", tokens.len(), mode);
+        // If connected, subscribe right away; otherwise the connection task does on connect
+        for (shard, shard_tokens) in groups {
+            let added: HashMap<u64, SubscriptionMode> = shard_tokens.into_iter()
+                .map(|token| (token, mode.clone()))
+                .collect();
+            self.send_to_shard(shard, Self::resubscribe_messages(&added)).await?;
         }
+        info!("Subscribed to {} instruments with mode {:?}", tokens.len(), mode);
         
         Ok(())
     }
     
     /// Unsubscribe from market data for specific instruments
     pub async fn unsubscribe_from_instruments(&self, tokens: Vec<u64>) -> Result<()> {
+        // Free the instruments' connection slots, noting connections left with nothing to carry
+        let (released, idle) = {
+            let mut assignment = self.shard_assignment.write().await;
+            let released = assignment.release(&tokens);
+            let active = assignment.active_shards();
+            let idle: Vec<usize> = released.keys()
+                .filter(|shard| !active.contains(shard))
+                .cloned()
+                .collect();
+            (released, idle)
+        };
+        
         // Update subscriptions
         {
             let mut subs = self.subscriptions.write().await;
+            let mut symbols = self.instrument_symbols.write().await;
             for token in &tokens {
                 subs.remove(token);
+                symbols.remove(token);
             }
         }
         
-        // If connected, send unsubscription message
-        let status = self.status.read().await;
-        if *status == ConnectionStatus::Connected {
-            info!("Unsubscribed from {} instruments", tokens.len());
+        // If connected, send unsubscription messages and close connections no longer needed
+        for (shard, shard_tokens) in released {
+            if idle.contains(&shard) {
+                self.close_connection(shard).await;
+                info!("Closed idle ticker connection {}", shard);
+                continue;
+            }
+            
+            let message = serde_json::json!({ "a": "unsubscribe", "v": shard_tokens }).to_string();
+            Self::send_ticker_messages(&self.connections[shard].writer, vec![message]).await?;
         }
+        info!("Unsubscribed from {} instruments", tokens.len());
         
         Ok(())
     }
     
+    /// Subscribe to instruments by trading symbol, so their updates carry the symbol
+    pub async fn subscribe_to_symbols(
+        &self,
+        instruments: Vec<(u64, String)>,
+        mode: SubscriptionMode,
+    ) -> Result<()> {
+        let tokens: Vec<u64> = {
+            let mut symbols = self.instrument_symbols.write().await;
+            instruments.into_iter()
+                .map(|(token, symbol)| {
+                    symbols.insert(token, symbol);
+                    token
+                })
+                .collect()
+        };
+        
+        self.subscribe_to_instruments(tokens, mode).await
+    }
+    
+    /// Unsubscribe from instruments by trading symbol, returning the tokens dropped
+    pub async fn unsubscribe_from_symbols(&self, symbols: &[String]) -> Result<Vec<u64>> {
+        let tokens = self.tokens_for_symbols(symbols).await;
+        self.unsubscribe_from_instruments(tokens.clone()).await?;
+        Ok(tokens)
+    }
+    
+    /// Switch subscribed instruments to another mode, returning the tokens that changed
+    ///
+    /// Instruments that are not subscribed are left alone.
+    pub async fn set_instruments_mode(&self, tokens: &[u64], mode: SubscriptionMode) -> Result<Vec<u64>> {
+        let changed: Vec<u64> = {
+            let mut subs = self.subscriptions.write().await;
+            tokens.iter()
+                .filter(|token| match subs.get_mut(token) {
+                    Some(current) if *current != mode => {
+                        *current = mode.clone();
+                        true
+                    }
+                    _ => false,
+                })
+                .cloned()
+                .collect()
+        };
+        
+        if !changed.is_empty() {
+            let groups = self.shard_assignment.read().await.group(&changed);
+            for (shard, shard_tokens) in groups {
+                let message = Self::mode_message(&mode, &shard_tokens);
+                Self::send_ticker_messages(&self.connections[shard].writer, vec![message]).await?;
+            }
+            info!("Switched {} instruments to mode {:?}", changed.len(), mode);
+        }
+        
+        Ok(changed)
+    }
+    
+    /// Switch instruments subscribed by symbol to another mode, returning the tokens that changed
+    pub async fn set_symbols_mode(&self, symbols: &[String], mode: SubscriptionMode) -> Result<Vec<u64>> {
+        let tokens = self.tokens_for_symbols(symbols).await;
+        self.set_instruments_mode(&tokens, mode).await
+    }
+    
+    /// Tokens of instruments subscribed under any of the symbols
+    async fn tokens_for_symbols(&self, symbols: &[String]) -> Vec<u64> {
+        let mut tokens: Vec<u64> = self.instrument_symbols.read().await.iter()
+            .filter(|(_, symbol)| symbols.contains(symbol))
+            .map(|(token, _)| *token)
+            .collect();
+        tokens.sort_unstable();
+        tokens
+    }
+    
+    /// Subscribe to the followed indices under their names
+    ///
+    /// Quote mode is enough: index packets carry the previous close either way.
+    pub async fn subscribe_to_indices(&self) -> Result<()> {
+        let indices: Vec<(u64, String)> = MARKET_INDICES.iter()
+            .map(|(symbol, token)| (*token, symbol.to_string()))
+            .collect();
+        self.subscribe_to_symbols(indices, SubscriptionMode::Quote).await
+    }
+    
+    /// Latest levels of the followed indices that have ticked, in `MARKET_INDICES` order
+    pub async fn get_index_quotes(&self) -> Vec<IndexQuote> {
+        let cache = self.market_data_cache.read().await;
+        MARKET_INDICES.iter()
+            .filter_map(|(symbol, token)| cache.get(token).map(|data| IndexQuote {
+                symbol: symbol.to_string(),
+                instrument_token: *token,
+                ltp: data.ltp,
+                close: data.ohlc.as_ref().map(|ohlc| ohlc.close),
+                change: data.change,
+                change_percent: data.change_percent,
+                timestamp: data.timestamp,
+            }))
+            .collect()
+    }
+    
+    /// Current subscriptions with their symbols, ordered by token
+    pub async fn list_subscriptions(&self) -> Vec<SubscriptionInfo> {
+        let subs = self.subscriptions.read().await;
+        let symbols = self.instrument_symbols.read().await;
+        
+        let mut list: Vec<SubscriptionInfo> = subs.iter()
+            .map(|(token, mode)| SubscriptionInfo {
+                instrument_token: *token,
+                symbol: symbols.get(token).cloned(),
+                mode: mode.clone(),
+            })
+            .collect();
+        list.sort_by_key(|info| info.instrument_token);
+        list
+    }
+    
     /// Get current connection status
     pub async fn get_status(&self) -> ConnectionStatus {
         let status = self.status.read().await;
@@ -580,8 +1261,28 @@ impl WebSocketManager {
     }
     
     /// Get market data receiver for real-time updates
-    pub fn subscribe_to_market_data(&self) -> broadcast::Receiver<MarketData> {
-        self.market_data_tx.subscribe()
+    pub fn subscribe_to_market_data(&self) -> TickReceiver {
+        self.market_data_tx.subscribe("subscriber", TickPolicy::Queue(DEFAULT_TICK_QUEUE_CAPACITY))
+    }
+    
+    /// Get a named market data receiver whose buffer follows a policy when it falls behind
+    pub fn subscribe_to_market_data_as(&self, consumer: &str, policy: TickPolicy) -> TickReceiver {
+        self.market_data_tx.subscribe(consumer, policy)
+    }
+    
+    /// Get a receiver for order status changes pushed on the ticker connection
+    pub fn subscribe_to_order_updates(&self) -> broadcast::Receiver<KiteOrderUpdate> {
+        self.order_update_tx.subscribe()
+    }
+    
+    /// Get a sender for order updates arriving by other routes, such as Kite postbacks
+    pub fn order_update_sender(&self) -> broadcast::Sender<KiteOrderUpdate> {
+        self.order_update_tx.clone()
+    }
+    
+    /// Get a receiver for connection status changes
+    pub fn subscribe_to_status(&self) -> broadcast::Receiver<ConnectionEvent> {
+        self.status_tx.subscribe()
     }
     
     /// Get cached market data for an instrument
@@ -590,103 +1291,301 @@ impl WebSocketManager {
         cache.get(&instrument_token).cloned()
     }
     
+    /// Latest order book depth for a symbol, if it is subscribed in full mode
+    pub async fn get_market_depth(&self, symbol: &str) -> Option<MarketDepth> {
+        let cache = self.market_data_cache.read().await;
+        cache.values()
+            .find(|data| data.symbol == symbol)
+            .and_then(|data| data.depth.clone())
+    }
+    
+    /// Last update times of cached prices and the age at which they count as stale
+    pub async fn get_price_freshness(&self) -> PriceFreshness {
+        self.price_freshness.read().await.clone()
+    }
+    
+    /// Change how old a cached price may get before it is flagged as stale
+    pub async fn set_stale_data_threshold(&self, seconds: i64) -> Result<()> {
+        self.price_freshness.write().await.set_max_age_seconds(seconds)
+    }
+    
     /// Get all cached market data
     pub async fn get_all_cached_market_data(&self) -> HashMap<u64, MarketData> {
         let cache = self.market_data_cache.read().await;
         cache.clone()
     }
     
+    /// Last traded price of every cached symbol
+    pub async fn get_last_prices(&self) -> HashMap<String, f64> {
+        let cache = self.market_data_cache.read().await;
+        cache.values()
+            .filter_map(|md| Some((md.symbol.clone(), md.ltp.to_f64()?)))
+            .collect()
+    }
+    
+    /// Change how long and how many instruments the cache keeps, applying the limits right away
+    pub async fn set_cache_limits(&self, limits: CacheLimits) -> usize {
+        *self.cache_limits.write().await = limits;
+        self.evict_cache(Utc::now()).await
+    }
+    
+    pub async fn get_cache_limits(&self) -> CacheLimits {
+        self.cache_limits.read().await.clone()
+    }
+    
+    /// Apply the cache limits as of a time, returning how many instruments were dropped
+    pub async fn evict_cache(&self, now: DateTime<Utc>) -> usize {
+        let limits = self.cache_limits.read().await.clone();
+        limits.evict(&mut *self.market_data_cache.write().await, now)
+    }
+    
+    /// Empty the market data cache, returning how many instruments it held
+    pub async fn clear_cache(&self) -> usize {
+        let mut cache = self.market_data_cache.write().await;
+        let cleared = cache.len();
+        cache.clear();
+        cleared
+    }
+    
     /// Get all current subscriptions
     pub async fn get_subscriptions(&self) -> HashMap<u64, SubscriptionMode> {
         let subs = self.subscriptions.read().await;
         subs.clone()
     }
     
-    /// Disconnect from WebSocket
+    /// Disconnect from WebSocket and stop reconnecting
     pub async fn disconnect(&self) -> Result<()> {
-        // Cancel connection task
-        {
-            let mut handle = self.connection_handle.lock().await;
-            if let Some(task_handle) = handle.take() {
-                task_handle.abort();
-            }
-        }
+        self.auto_reconnect.store(false, Ordering::SeqCst);
         
-        // Update status
-        {
-            let mut status = self.status.write().await;
-            *status = ConnectionStatus::Disconnected;
+        // Cancel every connection task
+        for shard in 0..self.connections.len() {
+            self.close_connection(shard).await;
         }
         
+        self.set_status(ConnectionEvent::new(ConnectionStatus::Disconnected, None)).await;
+        
         info!("WebSocket disconnected");
         Ok(())
     }
     
-    /// Reconnect with exponential backoff
+    /// Reconnect with exponential backoff and jitter
     pub async fn reconnect_with_backoff(&self) -> Result<()> {
-        let mut delay = self.retry_config.initial_delay;
         let mut attempts = 0;
         
         while attempts < self.retry_config.max_retries {
-            attempts += 1;
-            
-            // Update status to reconnecting
-            {
-                let mut status = self.status.write().await;
-                *status = ConnectionStatus::Reconnecting;
+            if !self.auto_reconnect.load(Ordering::SeqCst) {
+                info!("Reconnection cancelled after disconnect");
+                return Ok(());
             }
             
+            attempts += 1;
+            self.set_status(ConnectionEvent {
+                attempt: attempts,
+                ..ConnectionEvent::new(ConnectionStatus::Reconnecting, None)
+            }).await;
+            
             info!("Reconnection attempt {} of {}", attempts, self.retry_config.max_retries);
             
             match self.connect().await {
                 Ok(_) => {
                     info!("Successfully reconnected to WebSocket");
+                    self.feed_metrics.record_reconnect();
                     return Ok(());
                 }
                 Err(e) => {
                     warn!("Reconnection attempt {} failed: {}", attempts, e);
                     
                     if attempts < self.retry_config.max_retries {
+                        let delay = self.retry_config.delay_for_attempt(attempts, &mut rand::thread_rng());
+                        self.set_status(ConnectionEvent {
+                            attempt: attempts,
+                            retry_in_ms: Some(delay.as_millis() as u64),
+                            ..ConnectionEvent::new(ConnectionStatus::Reconnecting, Some(e.to_string()))
+                        }).await;
+                        
                         info!("Waiting {:?} before next reconnection attempt", delay);
                         sleep(delay).await;
-                        
-                        // Exponential backoff
-                        delay = std::cmp::min(
-                            Duration::from_secs_f64(delay.as_secs_f64() * self.retry_config.backoff_multiplier),
-                            self.retry_config.max_delay,
-                        );
                     }
                 }
             }
         }
         
         // All reconnection attempts failed
+        let message = format!("Failed to reconnect after {} attempts", self.retry_config.max_retries);
+        self.set_status(ConnectionEvent {
+            attempt: attempts,
+            ..ConnectionEvent::new(ConnectionStatus::Failed, Some(message.clone()))
+        }).await;
+        
+        Err(HedgeXError::WebSocketError(message))
+    }
+    
+    /// Feed a tick from outside the live connection, such as a replay
+    ///
+    /// It reaches the cache, live candles and market data subscribers, but is not
+    /// recorded, stored or counted in the feed metrics.
+    pub async fn inject_market_data(&self, market_data: MarketData) {
         {
-            let mut status = self.status.write().await;
-            *status = ConnectionStatus::Failed;
+            let mut cache = self.market_data_cache.write().await;
+            cache.insert(market_data.instrument_token, market_data.clone());
         }
+        self.price_freshness.write().await.record(&market_data.symbol, Utc::now());
         
-        Err(HedgeXError::WebSocketError(
-            format!("Failed to reconnect after {} attempts", self.retry_config.max_retries)
-        ))
+        self.candles.write().await.update(
+            &market_data.symbol,
+            market_data.ltp,
+            market_data.volume as i64,
+            market_data.timestamp,
+        );
+        Self::update_live_metrics(&self.live_metrics, &market_data).await;
+        
+        // Nobody listening is not an error
+        let _ = self.market_data_tx.send(market_data);
+    }
+    
+    /// Poll Kite's quote API for subscribed instruments whenever the ticker is not connected
+    ///
+    /// Polled quotes are marked as delayed and reach the cache and market data
+    /// subscribers like replayed ticks. Universes larger than one quote call are
+    /// polled a batch at a time, in turn.
+    pub fn start_quote_fallback(self: Arc<Self>, client: Arc<dyn KiteApiClient + Send + Sync>) {
+        tokio::spawn(async move {
+            let mut poll_interval = tokio::time::interval(Duration::from_secs(QUOTE_POLL_INTERVAL_SECONDS));
+            let mut next_batch = 0;
+            
+            loop {
+                poll_interval.tick().await;
+                
+                if self.get_status().await == ConnectionStatus::Connected {
+                    next_batch = 0;
+                    continue;
+                }
+                
+                let mut tokens: Vec<u64> = self.subscriptions.read().await.keys().cloned().collect();
+                if tokens.is_empty() || client.get_access_token().await.is_none() {
+                    continue;
+                }
+                tokens.sort_unstable();
+                
+                let batches: Vec<&[u64]> = tokens.chunks(QUOTE_BATCH_SIZE).collect();
+                if next_batch >= batches.len() {
+                    next_batch = 0;
+                }
+                
+                match self.poll_quotes(client.as_ref(), batches[next_batch]).await {
+                    Ok(count) => debug!("Polled delayed quotes for {} instruments", count),
+                    Err(e) => warn!("Failed to poll quotes while the ticker is down: {}", e),
+                }
+                next_batch += 1;
+            }
+        });
+    }
+    
+    /// Fetch quotes for instruments and feed them in as delayed market data, returning how many arrived
+    async fn poll_quotes(&self, client: &dyn KiteApiClient, tokens: &[u64]) -> Result<usize> {
+        let instruments: Vec<String> = tokens.iter().map(|token| token.to_string()).collect();
+        let quotes = client.get_quote(&instruments).await?;
+        
+        let now = Utc::now();
+        let mut count = 0;
+        for quote in quotes.values() {
+            let symbol = self.instrument_symbols.read().await.get(&quote.instrument_token).cloned()
+                .unwrap_or_else(|| format!("SYMBOL_{}", quote.instrument_token));
+            
+            match MarketData::from_quote(symbol, quote, now) {
+                Ok(market_data) => {
+                    self.inject_market_data(market_data).await;
+                    count += 1;
+                }
+                Err(e) => warn!("Skipping quote for {}: {}", quote.instrument_token, e),
+            }
+        }
+        
+        Ok(count)
+    }
+    
+    /// Tick rate, tick ages, parse errors and reconnects of the market data feed
+    pub async fn get_feed_health(&self) -> FeedHealth {
+        let mut health = self.feed_metrics.snapshot(Utc::now()).await;
+        health.channels = self.market_data_tx.stats();
+        health
+    }
+    
+    /// Health of each ticker connection in use, with the instruments it carries
+    pub async fn get_connection_health(&self) -> Vec<ConnectionHealth> {
+        let (shards, loads) = {
+            let assignment = self.shard_assignment.read().await;
+            (assignment.active_shards(), assignment.loads().to_vec())
+        };
+        
+        let mut health = Vec::with_capacity(shards.len());
+        for shard in shards {
+            health.push(self.connections[shard].stats.snapshot(shard, loads[shard]).await);
+        }
+        health
+    }
+    
+    /// Get a receiver for feed stall alerts
+    pub fn subscribe_to_feed_alerts(&self) -> broadcast::Receiver<FeedAlert> {
+        self.feed_alert_tx.subscribe()
+    }
+    
+    /// Start checking every few seconds that subscribed instruments keep ticking during market hours
+    pub fn start_feed_watchdog(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut check_interval = tokio::time::interval(Duration::from_secs(5));
+            
+            loop {
+                check_interval.tick().await;
+                
+                let now = Utc::now();
+                let expecting_ticks = is_in_session(now) && !self.subscriptions.read().await.is_empty();
+                if let Some(alert) = self.feed_metrics.check_stall(now, expecting_ticks).await {
+                    warn!("Market data feed stalled: {}", alert.message);
+                    // Nobody listening is not an error
+                    let _ = self.feed_alert_tx.send(alert);
+                }
+            }
+        });
+    }
+    
+    /// Sweep the market data cache periodically so long sessions don't grow it without bound
+    pub fn start_cache_eviction(self: Arc<Self>) {
+        tokio::spawn(async move {
+            let mut sweep_interval = tokio::time::interval(Duration::from_secs(CACHE_SWEEP_INTERVAL_SECONDS));
+            
+            loop {
+                sweep_interval.tick().await;
+                
+                let evicted = self.evict_cache(Utc::now()).await;
+                if evicted > 0 {
+                    debug!("Evicted {} instruments from the market data cache", evicted);
+                }
+            }
+        });
     }
     
     /// Start automatic reconnection monitoring
+    ///
+    /// Reconnects as soon as the connection task reports a drop, and retries
+    /// failed connections every 30 seconds, until `disconnect` is called.
     pub async fn start_reconnection_monitor(self: Arc<Self>) {
-        let status = Arc::clone(&self.status);
         let ws_manager = Arc::clone(&self);
         
         tokio::spawn(async move {
             let mut check_interval = tokio::time::interval(Duration::from_secs(30));
             
             loop {
-                check_interval.tick().await;
+                tokio::select! {
+                    _ = check_interval.tick() => {}
+                    _ = ws_manager.connection_lost.notified() => {}
+                }
                 
-                let current_status = {
-                    let status_guard = status.read().await;
-                    status_guard.clone()
-                };
+                if !ws_manager.auto_reconnect.load(Ordering::SeqCst) {
+                    continue;
+                }
                 
+                let current_status = ws_manager.get_status().await;
                 if current_status == ConnectionStatus::Disconnected || current_status == ConnectionStatus::Failed {
                     warn!("WebSocket connection lost, attempting to reconnect");
                     
@@ -701,6 +1600,114 @@ impl WebSocketManager {
 
 /// Validation functions for market data
 impl MarketData {
+    /// Streamed market data from one ticker packet, prices already in rupees
+    ///
+    /// Quote packets carry no best bid or ask; those come from the book of a full-mode packet.
+    pub fn from_packet(symbol: String, packet: &TickPacket<'_>) -> Result<Self> {
+        let decimal = |value: f64| Decimal::try_from(value)
+            .map_err(|e| HedgeXError::WebSocketError(format!("Invalid tick price {}: {}", value, e)));
+        let levels = |side: DepthSide| -> Result<Vec<DepthLevel>> {
+            (0..DEPTH_LEVELS)
+                .filter_map(|level| packet.depth(side, level))
+                .map(|entry| Ok(DepthLevel {
+                    price: decimal(entry.price)?,
+                    quantity: entry.quantity as u64,
+                    orders: entry.orders as u32,
+                }))
+                .collect()
+        };
+        
+        let ltp = decimal(packet.last_price())?;
+        let ohlc = match packet.ohlc() {
+            Some(ohlc) => Some(OHLC {
+                open: decimal(ohlc.open)?,
+                high: decimal(ohlc.high)?,
+                low: decimal(ohlc.low)?,
+                close: decimal(ohlc.close)?,
+            }),
+            None => None,
+        };
+        let change = ohlc.as_ref()
+            .filter(|ohlc| ohlc.close > Decimal::ZERO)
+            .map(|ohlc| (ltp - ohlc.close, (ltp - ohlc.close) * Decimal::new(100, 0) / ohlc.close));
+        let depth = if packet.depth(DepthSide::Bid, 0).is_some() {
+            Some(MarketDepth { bids: levels(DepthSide::Bid)?, asks: levels(DepthSide::Ask)? })
+        } else {
+            None
+        };
+        // A one-sided book leaves both unset rather than showing a crossed quote
+        let (bid, ask) = match depth.as_ref().map(|depth| (depth.best_bid(), depth.best_ask())) {
+            Some((Some(bid), Some(ask))) => (bid.price, ask.price),
+            _ => (Decimal::ZERO, Decimal::ZERO),
+        };
+        
+        let market_data = Self {
+            symbol,
+            instrument_token: packet.instrument_token() as u64,
+            ltp,
+            volume: packet.volume().unwrap_or(0) as u64,
+            bid,
+            ask,
+            ohlc,
+            timestamp: packet.exchange_timestamp()
+                .and_then(|seconds| DateTime::from_timestamp(seconds as i64, 0))
+                .unwrap_or_else(Utc::now),
+            change: change.map(|(change, _)| change),
+            change_percent: change.map(|(_, percent)| percent),
+            depth,
+            delayed: false,
+        };
+        market_data.validate()?;
+        Ok(market_data)
+    }
+    
+    /// Delayed market data from a REST quote
+    pub fn from_quote(symbol: String, quote: &KiteQuote, timestamp: DateTime<Utc>) -> Result<Self> {
+        let decimal = |value: f64| Decimal::try_from(value)
+            .map_err(|e| HedgeXError::ValidationError(format!("Invalid quote price {}: {}", value, e)));
+        let levels = |items: &[KiteDepthItem]| -> Result<Vec<DepthLevel>> {
+            items.iter()
+                .map(|item| Ok(DepthLevel {
+                    price: decimal(item.price)?,
+                    quantity: item.quantity as u64,
+                    orders: item.orders,
+                }))
+                .collect()
+        };
+        
+        let depth = MarketDepth {
+            bids: levels(&quote.depth.buy)?,
+            asks: levels(&quote.depth.sell)?,
+        };
+        let ltp = decimal(quote.last_price)?;
+        let close = decimal(quote.ohlc.close)?;
+        let change = decimal(quote.net_change)?;
+        
+        Ok(Self {
+            symbol,
+            instrument_token: quote.instrument_token,
+            ltp,
+            volume: quote.volume,
+            bid: depth.best_bid().map_or(Decimal::ZERO, |level| level.price),
+            ask: depth.best_ask().map_or(Decimal::ZERO, |level| level.price),
+            ohlc: Some(OHLC {
+                open: decimal(quote.ohlc.open)?,
+                high: decimal(quote.ohlc.high)?,
+                low: decimal(quote.ohlc.low)?,
+                close,
+            }),
+            timestamp,
+            change: Some(change),
+            change_percent: if close > Decimal::ZERO {
+                Some(change * Decimal::new(100, 0) / close)
+            } else {
+                None
+            },
+            depth: Some(depth),
+            delayed: true,
+        })
+    }
+    
     /// Validate market data integrity
     pub fn validate(&self) -> Result<()> {
         if self.ltp <= Decimal::ZERO {
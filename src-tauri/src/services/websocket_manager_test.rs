@@ -101,6 +101,8 @@ async fn test_market_data_validation() {
         timestamp: chrono::Utc::now(),
         change: None,
         change_percent: None,
+        depth: None,
+        delayed: false,
     };
     
     // Test valid data
@@ -149,6 +151,8 @@ async fn test_market_data_broadcast() {
         timestamp: chrono::Utc::now(),
         change: None,
         change_percent: None,
+        depth: None,
+        delayed: false,
     };
     
     // Broadcast market data
@@ -195,6 +199,8 @@ async fn test_market_data_caching() -> Result<()> {
         timestamp: chrono::Utc::now(),
         change: None,
         change_percent: None,
+        depth: None,
+        delayed: false,
     };
     
     // Cache market data
@@ -214,4 +220,320 @@ async fn test_market_data_caching() -> Result<()> {
     assert_eq!(cached_data.volume, 1000);
     
     Ok(())
-}
\ No newline at end of file
+}
+#[test]
+fn test_reconnect_delay_backoff_and_jitter() {
+    use crate::services::websocket_manager::RetryConfig;
+    use rand::rngs::StdRng;
+    use rand::SeedableRng;
+    
+    let mut rng = StdRng::seed_from_u64(7);
+    
+    // Without jitter the delay doubles from one second up to the cap
+    let config = RetryConfig { jitter: 0.0, ..RetryConfig::default() };
+    assert_eq!(config.delay_for_attempt(1, &mut rng), Duration::from_secs(1));
+    assert_eq!(config.delay_for_attempt(3, &mut rng), Duration::from_secs(4));
+    assert_eq!(config.delay_for_attempt(10, &mut rng), Duration::from_secs(60));
+    assert_eq!(config.delay_for_attempt(100, &mut rng), Duration::from_secs(60));
+    
+    // Jitter stays within 20% either way and never exceeds the cap
+    let config = RetryConfig::default();
+    let delays: Vec<Duration> = (0..50).map(|_| config.delay_for_attempt(3, &mut rng)).collect();
+    assert!(delays.iter().all(|d| *d >= Duration::from_secs_f64(3.2) && *d <= Duration::from_secs_f64(4.8)));
+    assert!(delays.iter().any(|d| *d != delays[0]));
+    assert!((0..50).all(|_| config.delay_for_attempt(20, &mut rng) <= Duration::from_secs(60)));
+}
+
+#[test]
+fn test_resubscribe_messages_restore_modes() {
+    use crate::services::websocket_manager::SubscriptionMode;
+    use std::collections::HashMap;
+    
+    let subscriptions = HashMap::from([
+        (256265, SubscriptionMode::Full),
+        (738561, SubscriptionMode::LTP),
+        (408065, SubscriptionMode::Full),
+    ]);
+    
+    let messages = WebSocketManager::resubscribe_messages(&subscriptions);
+    assert_eq!(messages, vec![
+        r#"{"a":"subscribe","v":[256265,408065,738561]}"#.to_string(),
+        r#"{"a":"mode","v":["ltp",[738561]]}"#.to_string(),
+        r#"{"a":"mode","v":["full",[256265,408065]]}"#.to_string(),
+    ]);
+    
+    assert!(WebSocketManager::resubscribe_messages(&HashMap::new()).is_empty());
+}
+
+#[test]
+fn test_display_throttle_limits_rate_per_symbol() {
+    use crate::services::DisplayThrottle;
+    use std::time::Instant;
+    
+    assert!(DisplayThrottle::new(0.0).is_err());
+    assert!(DisplayThrottle::new(100.0).is_err());
+    
+    let mut throttle = DisplayThrottle::new(2.0).unwrap();
+    let start = Instant::now();
+    
+    // Nothing is pushed until the UI shows the symbol
+    assert!(!throttle.should_push("INFY", start));
+    throttle.set_symbols(&["INFY".to_string(), "TCS".to_string()]);
+    
+    assert!(throttle.should_push("INFY", start));
+    assert!(!throttle.should_push("INFY", start + Duration::from_millis(200)));
+    assert!(throttle.should_push("TCS", start + Duration::from_millis(200)));
+    assert!(throttle.should_push("INFY", start + Duration::from_millis(500)));
+    assert!(!throttle.should_push("RELIANCE", start + Duration::from_millis(500)));
+    
+    throttle.set_symbols(&["TCS".to_string()]);
+    assert!(!throttle.should_push("INFY", start + Duration::from_secs(2)));
+    assert_eq!(throttle.symbols(), vec!["TCS".to_string()]);
+}
+
+#[tokio::test]
+async fn test_subscriptions_by_symbol() {
+    use crate::services::websocket_manager::SubscriptionMode;
+    
+    let mock_db_service = Arc::new(MockEnhancedDatabaseService::new());
+    let ws_manager = WebSocketManager::new(mock_db_service);
+    
+    // Subscribing while disconnected only records the subscriptions
+    let instruments = vec![(408065, "INFY".to_string()), (2953217, "TCS".to_string())];
+    ws_manager.subscribe_to_symbols(instruments, SubscriptionMode::Full).await.unwrap();
+    ws_manager.subscribe_to_instruments(vec![256265], SubscriptionMode::LTP).await.unwrap();
+    
+    let subscriptions = ws_manager.list_subscriptions().await;
+    let listed: Vec<(u64, Option<String>)> = subscriptions.iter()
+        .map(|info| (info.instrument_token, info.symbol.clone()))
+        .collect();
+    assert_eq!(listed, vec![
+        (256265, None),
+        (408065, Some("INFY".to_string())),
+        (2953217, Some("TCS".to_string())),
+    ]);
+    
+    let dropped = ws_manager.unsubscribe_from_symbols(&["TCS".to_string()]).await.unwrap();
+    assert_eq!(dropped, vec![2953217]);
+    assert_eq!(ws_manager.list_subscriptions().await.len(), 2);
+    
+    assert_eq!("Quote".parse::<SubscriptionMode>().unwrap(), SubscriptionMode::Quote);
+    assert!("depth".parse::<SubscriptionMode>().is_err());
+}
+
+#[tokio::test]
+async fn test_switch_subscription_mode_per_symbol() {
+    use crate::services::websocket_manager::SubscriptionMode;
+    
+    let mock_db_service = Arc::new(MockEnhancedDatabaseService::new());
+    let ws_manager = WebSocketManager::new(mock_db_service);
+    
+    let instruments = vec![(408065, "INFY".to_string()), (2953217, "TCS".to_string())];
+    ws_manager.subscribe_to_symbols(instruments, SubscriptionMode::LTP).await.unwrap();
+    
+    // Only the traded symbol moves to full mode; unknown symbols are ignored
+    let changed = ws_manager.set_symbols_mode(&["INFY".to_string(), "WIPRO".to_string()], SubscriptionMode::Full).await.unwrap();
+    assert_eq!(changed, vec![408065]);
+    
+    let subscriptions = ws_manager.get_subscriptions().await;
+    assert_eq!(subscriptions.get(&408065), Some(&SubscriptionMode::Full));
+    assert_eq!(subscriptions.get(&2953217), Some(&SubscriptionMode::LTP));
+    
+    // Already in that mode, so nothing changes
+    assert!(ws_manager.set_instruments_mode(&[408065], SubscriptionMode::Full).await.unwrap().is_empty());
+    
+    assert_eq!(
+        WebSocketManager::mode_message(&SubscriptionMode::Full, &[408065]),
+        r#"{"a":"mode","v":["full",[408065]]}"#
+    );
+}
+
+#[tokio::test]
+async fn test_large_universes_are_split_across_connections() {
+    use crate::services::websocket_manager::SubscriptionMode;
+    use crate::services::ticker_shards::{MAX_INSTRUMENTS_PER_CONNECTION, MAX_TICKER_CONNECTIONS};
+    
+    let mock_db_service = Arc::new(MockEnhancedDatabaseService::new());
+    let ws_manager = WebSocketManager::new(mock_db_service);
+    
+    let per_connection = MAX_INSTRUMENTS_PER_CONNECTION as u64;
+    let universe: Vec<u64> = (1..=per_connection + 10).collect();
+    ws_manager.subscribe_to_instruments(universe, SubscriptionMode::LTP).await.unwrap();
+    
+    let health = ws_manager.get_connection_health().await;
+    let loads: Vec<(usize, usize)> = health.iter().map(|h| (h.connection, h.instruments)).collect();
+    assert_eq!(loads, vec![(0, MAX_INSTRUMENTS_PER_CONNECTION), (1, 10)]);
+    
+    // Emptying the second connection takes it out of use
+    let overflow: Vec<u64> = (per_connection + 1..=per_connection + 10).collect();
+    ws_manager.unsubscribe_from_instruments(overflow).await.unwrap();
+    assert_eq!(ws_manager.get_connection_health().await.len(), 1);
+    
+    // More than every connection together can carry is refused outright
+    let too_many: Vec<u64> = (1_000_000..1_000_000 + (MAX_TICKER_CONNECTIONS as u64) * per_connection).collect();
+    assert!(ws_manager.subscribe_to_instruments(too_many, SubscriptionMode::LTP).await.is_err());
+    assert_eq!(ws_manager.get_subscriptions().await.len(), MAX_INSTRUMENTS_PER_CONNECTION);
+}
+
+#[test]
+fn test_rest_quotes_become_delayed_market_data() {
+    use crate::models::kite::{KiteDepthItem, KiteMarketDepth, KiteOHLC, KiteQuote};
+    use crate::services::websocket_manager::MarketData;
+    use rust_decimal::Decimal;
+    
+    let quote = KiteQuote {
+        instrument_token: 408065,
+        last_price: 1510.0,
+        last_quantity: 5,
+        average_price: 1505.0,
+        volume: 250000,
+        buy_quantity: 1200,
+        sell_quantity: 900,
+        ohlc: KiteOHLC { open: 1490.0, high: 1515.0, low: 1488.0, close: 1500.0 },
+        net_change: 10.0,
+        lower_circuit_limit: 1350.0,
+        upper_circuit_limit: 1650.0,
+        oi: 0,
+        depth: KiteMarketDepth {
+            buy: vec![KiteDepthItem { price: 1509.5, quantity: 100, orders: 2 }],
+            sell: vec![KiteDepthItem { price: 1510.5, quantity: 80, orders: 1 }],
+        },
+    };
+    
+    let market_data = MarketData::from_quote("INFY".to_string(), &quote, chrono::Utc::now()).unwrap();
+    assert!(market_data.delayed);
+    assert_eq!(market_data.ltp, Decimal::new(1510, 0));
+    assert_eq!(market_data.bid, Decimal::new(15095, 1));
+    assert_eq!(market_data.ask, Decimal::new(15105, 1));
+    assert_eq!(market_data.change_percent, Some(Decimal::new(10, 0) * Decimal::new(100, 0) / Decimal::new(1500, 0)));
+    assert!(market_data.validate().is_ok());
+}
+
+#[tokio::test]
+async fn test_index_quotes_follow_subscribed_indices() {
+    use crate::services::websocket_manager::{MarketData, OHLC, SubscriptionMode, MARKET_INDICES};
+    use rust_decimal::Decimal;
+    
+    let mock_db_service = Arc::new(MockEnhancedDatabaseService::new());
+    let ws_manager = WebSocketManager::new(mock_db_service);
+    
+    ws_manager.subscribe_to_indices().await.unwrap();
+    let subscriptions = ws_manager.list_subscriptions().await;
+    assert_eq!(subscriptions.len(), MARKET_INDICES.len());
+    assert!(subscriptions.iter().all(|info| info.mode == SubscriptionMode::Quote && info.symbol.is_some()));
+    
+    let tick = |symbol: &str, token: u64, ltp: Decimal, close: Decimal| MarketData {
+        symbol: symbol.to_string(),
+        instrument_token: token,
+        ltp,
+        volume: 0,
+        bid: Decimal::ZERO,
+        ask: Decimal::ZERO,
+        ohlc: Some(OHLC { open: close, high: ltp, low: close, close }),
+        timestamp: chrono::Utc::now(),
+        change: Some(ltp - close),
+        change_percent: Some((ltp - close) * Decimal::new(100, 0) / close),
+        depth: None,
+        delayed: false,
+    };
+    ws_manager.inject_market_data(tick("INDIA VIX", 264969, Decimal::new(1350, 2), Decimal::new(1400, 2))).await;
+    ws_manager.inject_market_data(tick("NIFTY 50", 256265, Decimal::new(2250000, 2), Decimal::new(2240000, 2))).await;
+    ws_manager.inject_market_data(tick("INFY", 408065, Decimal::new(1510, 0), Decimal::new(1500, 0))).await;
+    
+    // Only indices that have ticked are listed, in the followed order
+    let quotes = ws_manager.get_index_quotes().await;
+    let symbols: Vec<&str> = quotes.iter().map(|quote| quote.symbol.as_str()).collect();
+    assert_eq!(symbols, vec!["NIFTY 50", "INDIA VIX"]);
+    assert_eq!(quotes[0].change, Some(Decimal::new(10000, 2)));
+    assert_eq!(quotes[1].close, Some(Decimal::new(1400, 2)));
+}
+
+#[tokio::test]
+async fn test_cache_expires_and_evicts_oldest_entries() {
+    use crate::services::websocket_manager::{CacheLimits, MarketData};
+    use rust_decimal::Decimal;
+    
+    let mock_db_service = Arc::new(MockEnhancedDatabaseService::new());
+    let ws_manager = WebSocketManager::new(mock_db_service);
+    let now = chrono::Utc::now();
+    
+    for (token, age_seconds) in [(1u64, 600i64), (2, 30), (3, 10), (4, 5)] {
+        ws_manager.inject_market_data(MarketData {
+            symbol: format!("SYM{}", token),
+            instrument_token: token,
+            ltp: Decimal::new(100, 0),
+            volume: 0,
+            bid: Decimal::ZERO,
+            ask: Decimal::ZERO,
+            ohlc: None,
+            timestamp: now - chrono::Duration::seconds(age_seconds),
+            change: None,
+            change_percent: None,
+            depth: None,
+            delayed: false,
+        }).await;
+    }
+    
+    // The defaults keep everything this recent
+    assert_eq!(ws_manager.evict_cache(now).await, 0);
+    
+    // Entries past the TTL go first, then the least recently updated over the maximum
+    ws_manager.set_cache_limits(CacheLimits::new(120, 2).unwrap()).await;
+    let mut kept: Vec<u64> = ws_manager.get_all_cached_market_data().await.into_keys().collect();
+    kept.sort_unstable();
+    assert_eq!(kept, vec![3, 4]);
+    
+    assert!(CacheLimits::new(10, 2).is_err());
+    assert!(CacheLimits::new(120, 0).is_err());
+    
+    assert_eq!(ws_manager.clear_cache().await, 2);
+    assert!(ws_manager.get_all_cached_market_data().await.is_empty());
+}
+
+#[test]
+fn test_market_data_from_packets() {
+    use crate::api::ticker::parse_packets;
+    use crate::services::websocket_manager::MarketData;
+    use rust_decimal::Decimal;
+    
+    fn put(packet: &mut [u8], offset: usize, value: u32) {
+        packet[offset..offset + 4].copy_from_slice(&value.to_be_bytes());
+    }
+    
+    // A full-mode equity packet and an index quote packet in one message, prices in paise
+    let mut full = vec![0u8; 184];
+    put(&mut full, 0, 408065);
+    put(&mut full, 4, 150_050);
+    put(&mut full, 16, 1_000_000);
+    put(&mut full, 20, 40_000); // total buy quantity, not a price
+    put(&mut full, 40, 149_500);
+    put(&mut full, 64, 100);
+    put(&mut full, 68, 150_045);
+    put(&mut full, 124, 200);
+    put(&mut full, 128, 150_055);
+    let mut index = vec![0u8; 28];
+    put(&mut index, 0, 256265);
+    put(&mut index, 4, 2_450_010);
+    put(&mut index, 8, 2_460_000);
+    put(&mut index, 20, 2_440_000);
+    
+    let mut data = 2u16.to_be_bytes().to_vec();
+    for packet in [&full, &index] {
+        data.extend_from_slice(&(packet.len() as u16).to_be_bytes());
+        data.extend_from_slice(packet);
+    }
+    let ticks: Vec<MarketData> = parse_packets(&data)
+        .map(|packet| MarketData::from_packet("SYM".to_string(), &packet.unwrap()).unwrap())
+        .collect();
+    assert_eq!(ticks.len(), 2);
+    
+    assert_eq!(ticks[0].ltp, Decimal::new(150050, 2));
+    assert_eq!(ticks[0].volume, 1_000_000);
+    assert_eq!((ticks[0].bid, ticks[0].ask), (Decimal::new(150045, 2), Decimal::new(150055, 2)));
+    assert_eq!(ticks[0].change, Some(Decimal::new(550, 2)));
+    assert_eq!(ticks[0].depth.as_ref().unwrap().bids.len(), 5);
+    
+    assert_eq!(ticks[1].ltp, Decimal::new(2450010, 2));
+    assert_eq!(ticks[1].ohlc.as_ref().unwrap().high, Decimal::new(2460000, 2));
+    assert!(ticks[1].depth.is_none());
+}
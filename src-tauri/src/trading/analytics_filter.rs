@@ -0,0 +1,201 @@
+use crate::error::{HedgeXError, Result};
+use chrono::NaiveDate;
+use serde::{Deserialize, Serialize};
+
+/// Default page size of analytics lists
+pub const DEFAULT_PAGE_LIMIT: i64 = 100;
+
+/// Largest page an analytics list returns
+pub const MAX_PAGE_LIMIT: i64 = 1000;
+
+/// Executed trades an analytics request covers, by IST date, strategy and symbol
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct TradeFilter {
+    /// First IST date included; unset covers the whole history
+    pub from: Option<NaiveDate>,
+    /// Last IST date included
+    pub to: NaiveDate,
+    pub strategy_id: Option<String>,
+    pub symbol: Option<String>,
+}
+
+impl TradeFilter {
+    /// The `days` IST dates ending with `to`
+    pub fn last_days(to: NaiveDate, days: i64) -> Self {
+        Self {
+            from: Some(to - chrono::Duration::days(days.max(1) - 1)),
+            to,
+            strategy_id: None,
+            symbol: None,
+        }
+    }
+
+    /// Build a filter from request parameters
+    ///
+    /// `to` defaults to `today`. Without `from` the range spans `days` dates ending at `to`,
+    /// or the whole history when `days` is unset. Dates are `YYYY-MM-DD` and blank filters are ignored.
+    pub fn from_params(
+        today: NaiveDate,
+        days: Option<i64>,
+        from: Option<&str>,
+        to: Option<&str>,
+        strategy_id: Option<&str>,
+        symbol: Option<&str>,
+    ) -> Result<Self> {
+        let to = parse_date("to", to)?.unwrap_or(today);
+        let from = match parse_date("from", from)? {
+            Some(from) => Some(from),
+            None => days.and_then(|days| Self::last_days(to, days).from),
+        };
+        if from.is_some_and(|from| from > to) {
+            return Err(HedgeXError::ValidationError("Start date is after the end date".to_string()));
+        }
+
+        let non_blank = |value: Option<&str>| value.map(str::trim).filter(|value| !value.is_empty()).map(str::to_string);
+        Ok(Self {
+            from,
+            to,
+            strategy_id: non_blank(strategy_id),
+            symbol: non_blank(symbol),
+        })
+    }
+
+    /// Whether a trade executed on an IST date passes the filter
+    pub fn matches(&self, date: NaiveDate, strategy_id: &str, symbol: &str) -> bool {
+        self.from.is_none_or(|from| date >= from)
+            && date <= self.to
+            && self.strategy_id.as_deref().is_none_or(|id| id == strategy_id)
+            && self.symbol.as_deref().is_none_or(|s| s == symbol)
+    }
+
+    /// SQL conditions on a `trades` table, each starting with ` AND`, and the values to bind in order
+    ///
+    /// `alias` prefixes the columns, e.g. `"t."`, or is empty.
+    pub fn sql_conditions(&self, alias: &str) -> (String, Vec<String>) {
+        let mut sql = String::new();
+        let mut binds = Vec::new();
+
+        if let Some(from) = self.from {
+            sql.push_str(&format!(" AND date({}executed_at, '+330 minutes') >= ?", alias));
+            binds.push(from.format("%Y-%m-%d").to_string());
+        }
+        sql.push_str(&format!(" AND date({}executed_at, '+330 minutes') <= ?", alias));
+        binds.push(self.to.format("%Y-%m-%d").to_string());
+        if let Some(strategy_id) = &self.strategy_id {
+            sql.push_str(&format!(" AND {}strategy_id = ?", alias));
+            binds.push(strategy_id.clone());
+        }
+        if let Some(symbol) = &self.symbol {
+            sql.push_str(&format!(" AND {}symbol = ?", alias));
+            binds.push(symbol.clone());
+        }
+
+        (sql, binds)
+    }
+}
+
+fn parse_date(name: &str, value: Option<&str>) -> Result<Option<NaiveDate>> {
+    match value.map(str::trim).filter(|value| !value.is_empty()) {
+        Some(value) => NaiveDate::parse_from_str(value, "%Y-%m-%d")
+            .map(Some)
+            .map_err(|_| HedgeXError::ValidationError(format!("Invalid {} date: {}", name, value))),
+        None => Ok(None),
+    }
+}
+
+/// Where a page sits in a filtered list
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PageInfo {
+    /// Items matching the filter across all pages
+    pub total: i64,
+    pub limit: i64,
+    pub offset: i64,
+    pub has_more: bool,
+}
+
+impl PageInfo {
+    pub fn new(total: i64, limit: i64, offset: i64) -> Self {
+        Self {
+            total,
+            limit,
+            offset,
+            has_more: offset + limit < total,
+        }
+    }
+}
+
+/// Clamp requested page bounds to a valid limit and a non-negative offset
+pub fn page_bounds(limit: Option<i64>, offset: Option<i64>, default_limit: i64) -> (i64, i64) {
+    (
+        limit.unwrap_or(default_limit).clamp(1, MAX_PAGE_LIMIT),
+        offset.unwrap_or(0).max(0),
+    )
+}
+
+/// Days an analytics `timeframe` of day, week, month or year covers, `default_days` for any other
+pub fn timeframe_days(timeframe: Option<&str>, default_days: i64) -> i64 {
+    match timeframe {
+        Some("day") => 1,
+        Some("week") => 7,
+        Some("month") => 30,
+        Some("year") => 365,
+        _ => default_days,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 8, day).unwrap()
+    }
+
+    #[test]
+    fn test_filter_from_params() {
+        let filter = TradeFilter::from_params(date(30), Some(7), None, None, Some(" "), Some("INFY")).unwrap();
+        assert_eq!((filter.from, filter.to), (Some(date(24)), date(30)));
+        assert_eq!((filter.strategy_id, filter.symbol.as_deref()), (None, Some("INFY")));
+
+        // An explicit end date moves the window; an explicit start overrides it
+        let filter = TradeFilter::from_params(date(30), Some(7), None, Some("2024-08-10"), None, None).unwrap();
+        assert_eq!((filter.from, filter.to), (Some(date(4)), date(10)));
+        let filter = TradeFilter::from_params(date(30), Some(7), Some("2024-08-01"), None, None, None).unwrap();
+        assert_eq!(filter.from, Some(date(1)));
+
+        assert_eq!(TradeFilter::from_params(date(30), None, None, None, None, None).unwrap().from, None);
+        assert!(TradeFilter::from_params(date(30), None, Some("2024-08-31"), None, None, None).is_err());
+        assert!(TradeFilter::from_params(date(30), None, Some("08/01/2024"), None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_filter_conditions() {
+        let filter = TradeFilter {
+            from: Some(date(1)),
+            to: date(31),
+            strategy_id: Some("s1".to_string()),
+            symbol: None,
+        };
+        assert!(filter.matches(date(5), "s1", "INFY"));
+        assert!(!filter.matches(date(5), "s2", "INFY"));
+
+        let (sql, binds) = filter.sql_conditions("t.");
+        assert_eq!(sql, " AND date(t.executed_at, '+330 minutes') >= ? AND date(t.executed_at, '+330 minutes') <= ? AND t.strategy_id = ?");
+        assert_eq!(binds, vec!["2024-08-01", "2024-08-31", "s1"]);
+    }
+
+    #[test]
+    fn test_page_info() {
+        assert!(PageInfo::new(250, 100, 100).has_more);
+        assert!(!PageInfo::new(250, 100, 200).has_more);
+        assert_eq!(page_bounds(Some(0), Some(-5), 100), (1, 0));
+        assert_eq!(page_bounds(None, None, 10), (10, 0));
+    }
+
+    #[test]
+    fn test_timeframe_days() {
+        assert_eq!(timeframe_days(Some("week"), 30), 7);
+        assert_eq!(timeframe_days(Some("quarter"), 30), 30);
+        assert_eq!(timeframe_days(None, 1), 1);
+    }
+}
@@ -0,0 +1,346 @@
+use crate::models::backtesting::{Timeframe, OHLCV};
+use crate::trading::session;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use rust_decimal::Decimal;
+use std::collections::{HashMap, VecDeque};
+
+/// Maximum number of bars retained per symbol
+pub const MAX_BARS: usize = 500;
+
+/// OHLCV bars built from a stream of ticks
+#[derive(Debug, Clone)]
+pub struct BarSeries {
+    /// Bar size in seconds
+    bar_seconds: i64,
+
+    /// Completed and in-progress bars, oldest first
+    bars: VecDeque<OHLCV>,
+
+    /// Cumulative day volume at the start of the current bar
+    bar_start_volume: i64,
+}
+
+impl BarSeries {
+    /// Create a series sampling ticks into bars of the given size
+    pub fn new(bar_seconds: i64) -> Self {
+        Self {
+            bar_seconds: bar_seconds.max(1),
+            bars: VecDeque::new(),
+            bar_start_volume: 0,
+        }
+    }
+
+    /// Record a tick with the cumulative traded volume for the day
+    pub fn update(&mut self, price: Decimal, cumulative_volume: i64, timestamp: DateTime<Utc>) {
+        let bucket = timestamp.timestamp().div_euclid(self.bar_seconds) * self.bar_seconds;
+        let bar_start = Utc.timestamp_opt(bucket, 0).single().unwrap_or(timestamp);
+
+        match self.bars.back_mut() {
+            Some(bar) if bar.timestamp == bar_start => {
+                bar.high = bar.high.max(price);
+                bar.low = bar.low.min(price);
+                bar.close = price;
+                bar.volume = (cumulative_volume - self.bar_start_volume).max(0);
+            }
+            Some(bar) if bar.timestamp > bar_start => {} // Ignore out-of-order ticks
+            last => {
+                // Volume resets at the start of a session
+                let previous_volume = last.map(|bar| self.bar_start_volume + bar.volume).unwrap_or(cumulative_volume);
+                self.bar_start_volume = if cumulative_volume >= previous_volume { previous_volume } else { 0 };
+
+                let volume = (cumulative_volume - self.bar_start_volume).max(0);
+                self.bars.push_back(OHLCV::new(bar_start, price, price, price, price, volume));
+                if self.bars.len() > MAX_BARS {
+                    self.bars.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Number of bars held
+    pub fn len(&self) -> usize {
+        self.bars.len()
+    }
+
+    /// Whether no ticks have been recorded
+    pub fn is_empty(&self) -> bool {
+        self.bars.is_empty()
+    }
+
+    /// Bars ordered oldest to newest, including the bar in progress
+    pub fn bars(&self) -> Vec<OHLCV> {
+        self.bars.iter().cloned().collect()
+    }
+    
+    /// Bar in progress, or the last completed one if no tick has arrived since
+    pub fn last(&self) -> Option<&OHLCV> {
+        self.bars.back()
+    }
+}
+
+/// 1-minute candles built from live ticks for each symbol, plus coarser timeframes
+///
+/// Coarser candles are built from completed minutes, so one is reported
+/// complete when the first minute of the next bucket completes.
+#[derive(Debug, Clone, Default)]
+pub struct LiveCandles {
+    /// Coarser timeframes kept alongside the 1-minute candles
+    timeframes: Vec<Timeframe>,
+    symbols: HashMap<String, SymbolCandles>,
+}
+
+#[derive(Debug, Clone)]
+struct SymbolCandles {
+    minutes: BarSeries,
+    coarser: HashMap<Timeframe, VecDeque<OHLCV>>,
+}
+
+impl LiveCandles {
+    /// Keep candles for the given coarser timeframes as well as 1-minute ones
+    pub fn new(timeframes: &[Timeframe]) -> Self {
+        let mut kept: Vec<Timeframe> = Vec::new();
+        for timeframe in timeframes {
+            if !matches!(timeframe, Timeframe::Minute1 | Timeframe::Tick) && !kept.contains(timeframe) {
+                kept.push(*timeframe);
+            }
+        }
+        
+        Self { timeframes: kept, symbols: HashMap::new() }
+    }
+    
+    /// Coarser timeframes being kept
+    pub fn timeframes(&self) -> &[Timeframe] {
+        &self.timeframes
+    }
+    
+    /// Record a tick with the cumulative day volume, returning candles it completed
+    pub fn update(&mut self, symbol: &str, price: Decimal, cumulative_volume: i64, timestamp: DateTime<Utc>) -> Vec<(Timeframe, OHLCV)> {
+        let timeframes = &self.timeframes;
+        let candles = self.symbols.entry(symbol.to_string()).or_insert_with(|| SymbolCandles {
+            minutes: BarSeries::new(60),
+            coarser: timeframes.iter().map(|timeframe| (*timeframe, VecDeque::new())).collect(),
+        });
+        
+        let previous = candles.minutes.last().cloned();
+        candles.minutes.update(price, cumulative_volume, timestamp);
+        
+        let minute = match (previous, candles.minutes.last()) {
+            (Some(previous), Some(current)) if current.timestamp > previous.timestamp => previous,
+            _ => return Vec::new(),
+        };
+        
+        let mut completed = Vec::new();
+        for (timeframe, series) in candles.coarser.iter_mut() {
+            if let Some(candle) = fold_minute(series, &minute, *timeframe) {
+                completed.push((*timeframe, candle));
+            }
+            if series.len() > MAX_BARS {
+                series.pop_front();
+            }
+        }
+        completed.push((Timeframe::Minute1, minute));
+        completed
+    }
+    
+    /// Latest `count` candles of a timeframe, oldest first, including the one in progress
+    ///
+    /// Timeframes not being kept are resampled from the 1-minute candles held.
+    pub fn recent(&self, symbol: &str, timeframe: Timeframe, count: usize) -> Vec<OHLCV> {
+        let candles = match self.symbols.get(symbol) {
+            Some(candles) => candles,
+            None => return Vec::new(),
+        };
+        
+        let series: Vec<OHLCV> = match (timeframe, candles.coarser.get(&timeframe)) {
+            (Timeframe::Tick, _) => Vec::new(),
+            (Timeframe::Minute1, _) => candles.minutes.bars(),
+            (_, Some(series)) => {
+                let mut series = series.clone();
+                if let Some(minute) = candles.minutes.last() {
+                    fold_minute(&mut series, minute, timeframe);
+                }
+                series.into()
+            }
+            (_, None) => resample(&candles.minutes.bars(), timeframe),
+        };
+        
+        let skip = series.len().saturating_sub(count);
+        series.into_iter().skip(skip).collect()
+    }
+}
+
+/// Merge a minute candle into coarser candles, returning the candle it closed, if any
+fn fold_minute(series: &mut VecDeque<OHLCV>, minute: &OHLCV, timeframe: Timeframe) -> Option<OHLCV> {
+    let start = bucket_start(minute.timestamp, timeframe);
+    match series.back_mut() {
+        Some(bar) if bar.timestamp == start => {
+            bar.high = bar.high.max(minute.high);
+            bar.low = bar.low.min(minute.low);
+            bar.close = minute.close;
+            bar.volume += minute.volume;
+            None
+        }
+        Some(bar) if bar.timestamp > start => None,
+        last => {
+            let closed = last.cloned();
+            series.push_back(OHLCV::new(start, minute.open, minute.high, minute.low, minute.close, minute.volume));
+            closed
+        }
+    }
+}
+
+/// Aggregate ordered candles into candles of a coarser timeframe
+///
+/// Intraday buckets are aligned to the 09:15 IST session open, as NSE
+/// candles are, and daily candles are stamped at the open. 1-minute and
+/// tick timeframes are returned unchanged.
+pub fn resample(candles: &[OHLCV], timeframe: Timeframe) -> Vec<OHLCV> {
+    if matches!(timeframe, Timeframe::Minute1 | Timeframe::Tick) {
+        return candles.to_vec();
+    }
+
+    let mut resampled: Vec<OHLCV> = Vec::new();
+    for candle in candles {
+        let start = bucket_start(candle.timestamp, timeframe);
+        match resampled.last_mut() {
+            Some(bar) if bar.timestamp == start => {
+                bar.high = bar.high.max(candle.high);
+                bar.low = bar.low.min(candle.low);
+                bar.close = candle.close;
+                bar.volume += candle.volume;
+            }
+            _ => resampled.push(OHLCV::new(start, candle.open, candle.high, candle.low, candle.close, candle.volume)),
+        }
+    }
+    resampled
+}
+
+/// Start of the timeframe bucket containing a timestamp
+fn bucket_start(time: DateTime<Utc>, timeframe: Timeframe) -> DateTime<Utc> {
+    let open = match session::session_bounds(session::session_date(time)) {
+        Some((open, _)) => open,
+        None => return time,
+    };
+    if timeframe == Timeframe::Day1 {
+        return open;
+    }
+
+    let minutes = timeframe.duration_minutes();
+    open + Duration::minutes((time - open).num_minutes().div_euclid(minutes) * minutes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ticks_aggregate_into_bars() {
+        let mut series = BarSeries::new(60);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 9, 15, 0).unwrap();
+
+        series.update(Decimal::from(100), 1000, start);
+        series.update(Decimal::from(103), 1500, start + Duration::seconds(20));
+        series.update(Decimal::from(99), 1800, start + Duration::seconds(40));
+        series.update(Decimal::from(101), 2000, start + Duration::seconds(70));
+
+        let bars = series.bars();
+        assert_eq!(bars.len(), 2);
+
+        assert_eq!(bars[0].open, Decimal::from(100));
+        assert_eq!(bars[0].high, Decimal::from(103));
+        assert_eq!(bars[0].low, Decimal::from(99));
+        assert_eq!(bars[0].close, Decimal::from(99));
+        assert_eq!(bars[0].volume, 800);
+
+        assert_eq!(bars[1].open, Decimal::from(101));
+        assert_eq!(bars[1].volume, 200);
+    }
+
+    #[test]
+    fn test_out_of_order_ticks_are_ignored() {
+        let mut series = BarSeries::new(60);
+        let start = Utc.with_ymd_and_hms(2024, 1, 1, 9, 15, 0).unwrap();
+
+        series.update(Decimal::from(100), 1000, start + Duration::minutes(1));
+        series.update(Decimal::from(90), 900, start);
+
+        assert_eq!(series.len(), 1);
+        assert_eq!(series.bars()[0].close, Decimal::from(100));
+    }
+
+    #[test]
+    fn test_live_candles_complete_minutes_and_coarser_bars() {
+        // 09:15 IST on Thursday 25 Jan 2024, one tick every 30 seconds for 16 minutes
+        let open = Utc.with_ymd_and_hms(2024, 1, 25, 3, 45, 0).unwrap();
+        let mut candles = LiveCandles::new(&[Timeframe::Minute5, Timeframe::Minute1]);
+        assert_eq!(candles.timeframes(), &[Timeframe::Minute5]);
+        
+        let mut completed = Vec::new();
+        for i in 0..32 {
+            let price = Decimal::from(100 + i);
+            completed.extend(candles.update("RELIANCE", price, 1000 * (i + 1), open + Duration::seconds(30 * i)));
+        }
+        
+        // 15 minutes completed, with three 5-minute candles closed by the minutes after them
+        let minutes: Vec<_> = completed.iter().filter(|(timeframe, _)| *timeframe == Timeframe::Minute1).collect();
+        let fives: Vec<_> = completed.iter().filter(|(timeframe, _)| *timeframe == Timeframe::Minute5).map(|(_, c)| c).collect();
+        assert_eq!(minutes.len(), 15);
+        assert_eq!(fives.len(), 2);
+        assert_eq!(fives[0].timestamp, open);
+        assert_eq!(fives[0].open, Decimal::from(100));
+        assert_eq!(fives[0].close, Decimal::from(109));
+        assert_eq!(fives[1].timestamp, open + Duration::minutes(5));
+        
+        // Recent candles include the ones in progress
+        let recent = candles.recent("RELIANCE", Timeframe::Minute5, 2);
+        assert_eq!(recent.len(), 2);
+        assert_eq!(recent[1].timestamp, open + Duration::minutes(15));
+        assert_eq!(recent[1].close, Decimal::from(131));
+        assert_eq!(recent[0].close, Decimal::from(129));
+        assert_eq!(candles.recent("RELIANCE", Timeframe::Minute1, 100).len(), 16);
+        
+        // Timeframes not kept are resampled from the minutes
+        assert_eq!(candles.recent("RELIANCE", Timeframe::Minute15, 10).len(), 2);
+        assert!(candles.recent("TCS", Timeframe::Minute1, 10).is_empty());
+    }
+
+    #[test]
+    fn test_resample_aligns_to_session_open() {
+        // 09:15 IST on Thursday 25 Jan 2024, then 35 one-minute candles
+        let open = Utc.with_ymd_and_hms(2024, 1, 25, 3, 45, 0).unwrap();
+        let candles: Vec<OHLCV> = (0..35)
+            .map(|i| {
+                let price = Decimal::from(100 + i);
+                OHLCV::new(open + Duration::minutes(i), price, price + Decimal::ONE, price - Decimal::ONE, price, 10)
+            })
+            .collect();
+
+        let fifteen = resample(&candles, Timeframe::Minute15);
+        assert_eq!(fifteen.len(), 3);
+        assert_eq!(fifteen[1].timestamp, open + Duration::minutes(15));
+        assert_eq!(fifteen[1].open, Decimal::from(115));
+        assert_eq!(fifteen[1].high, Decimal::from(130));
+        assert_eq!(fifteen[1].low, Decimal::from(114));
+        assert_eq!(fifteen[1].close, Decimal::from(129));
+        assert_eq!(fifteen[1].volume, 150);
+        assert_eq!(fifteen[2].volume, 50);
+
+        // Hourly candles start at quarter past, and a day collapses to one candle at the open
+        let later = OHLCV::new(open + Duration::hours(5), Decimal::from(90), Decimal::from(90), Decimal::from(80), Decimal::from(85), 5);
+        let mut day = candles.clone();
+        day.push(later);
+
+        let hourly = resample(&day, Timeframe::Hour1);
+        assert_eq!(hourly.len(), 2);
+        assert_eq!(hourly[1].timestamp, open + Duration::hours(5));
+
+        let daily = resample(&day, Timeframe::Day1);
+        assert_eq!(daily.len(), 1);
+        assert_eq!(daily[0].timestamp, open);
+        assert_eq!(daily[0].low, Decimal::from(80));
+        assert_eq!(daily[0].close, Decimal::from(85));
+        assert_eq!(daily[0].volume, 355);
+
+        assert_eq!(resample(&candles, Timeframe::Minute1).len(), 35);
+    }
+}
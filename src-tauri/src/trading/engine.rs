@@ -1,27 +1,35 @@
 use crate::error::{HedgeXError, Result};
 use crate::models::trading::{
     Trade, TradeStatus, TradeType, Position, OrderRequest, OrderResponse, OrderType,
-    MarketData, TradingSignal, SignalType, PerformanceMetrics
+    MarketData, TradingSignal, SignalType, PerformanceMetrics,
+    PairPosition, PairSignal, PairSignalType, PairSide, StrategyParams, TIME_EXIT_REASON,
 };
+use crate::models::backtesting::CostModel;
 use crate::models::kite::{
     KiteOrderRequest, KiteOrderResponse, KiteTransactionType, KiteOrderType,
-    KiteProduct, KiteValidity, KiteOrderVariety, KiteExchange
+    KiteProduct, KiteValidity, KiteOrderVariety, KiteExchange, KiteOrderStatus, KiteOrderUpdate
 };
 use crate::services::enhanced_database_service::EnhancedDatabaseService;
+use crate::services::instrument_service::InstrumentService;
 use crate::services::kite_service::KiteService;
+use crate::trading::freshness::PriceFreshness;
+use crate::trading::pre_open::{PreOpenQuote, PreOpenSession};
 use crate::trading::risk_manager::RiskManager;
 use crate::trading::strategy_manager::StrategyManager;
 use rust_decimal::{Decimal, prelude::{ToPrimitive, FromPrimitive}};
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 use std::sync::Arc;
 use std::time::{Duration, Instant};
-use tokio::sync::{RwLock, Mutex, mpsc};
+use tokio::sync::{RwLock, Mutex, broadcast, mpsc};
 use tokio::time::{sleep, timeout};
 use tracing::{debug, error, info, warn, instrument};
 use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use sqlx::Row;
 
+/// Exchange, symbol and strategy of a position a time exit was queued for
+type TimeExitKey = (String, String, String);
+
 /// The core high-frequency trading engine
 pub struct TradingEngine {
     /// Database service for persistence
@@ -36,6 +44,9 @@ pub struct TradingEngine {
     /// Strategy manager for signal generation
     strategy_manager: Arc<StrategyManager>,
     
+    /// Instruments master, for tick and lot sizes at order placement
+    instrument_service: Arc<InstrumentService>,
+    
     /// Active trades by ID
     active_trades: Arc<RwLock<HashMap<String, Trade>>>,
     
@@ -45,6 +56,18 @@ pub struct TradingEngine {
     /// Market data cache
     market_data_cache: Arc<RwLock<HashMap<String, MarketData>>>,
     
+    /// When each cached price last updated; older prices are not acted on
+    price_freshness: Arc<RwLock<PriceFreshness>>,
+    
+    /// Pre-open auction prices and the hold on strategies until after the open
+    pre_open: Arc<RwLock<PreOpenSession>>,
+    
+    /// Open pair positions by strategy ID
+    pair_positions: Arc<RwLock<HashMap<String, PairPosition>>>,
+    
+    /// Positions with a time exit queued that has not failed
+    pending_time_exits: Arc<RwLock<HashSet<TimeExitKey>>>,
+    
     /// Trading state
     is_running: Arc<RwLock<bool>>,
     
@@ -71,6 +94,8 @@ impl TradingEngine {
         // Initialize strategy manager
         let strategy_manager = Arc::new(StrategyManager::new(db_service.clone(), user_id).await?);
         
+        let instrument_service = Arc::new(InstrumentService::new(db_service.clone()).await?);
+        
         // Create order execution channel
         let (order_sender, order_receiver) = mpsc::unbounded_channel();
         
@@ -79,9 +104,14 @@ impl TradingEngine {
             kite_service,
             risk_manager,
             strategy_manager,
+            instrument_service,
             active_trades: Arc::new(RwLock::new(HashMap::new())),
             order_queue: Arc::new(Mutex::new(order_sender)),
             market_data_cache: Arc::new(RwLock::new(HashMap::new())),
+            price_freshness: Arc::new(RwLock::new(PriceFreshness::default())),
+            pre_open: Arc::new(RwLock::new(PreOpenSession::default())),
+            pair_positions: Arc::new(RwLock::new(HashMap::new())),
+            pending_time_exits: Arc::new(RwLock::new(HashSet::new())),
             is_running: Arc::new(RwLock::new(false)),
             performance_metrics: Arc::new(RwLock::new(PerformanceMetrics::new(user_id))),
             user_id: user_id.to_string(),
@@ -93,6 +123,7 @@ impl TradingEngine {
         
         // Load existing active trades
         engine.load_active_trades().await?;
+        engine.load_pair_positions().await?;
         
         info!("Trading engine initialized for user: {}", user_id);
         Ok(engine)
@@ -102,7 +133,7 @@ impl TradingEngine {
     async fn load_active_trades(&self) -> Result<()> {
         let query = "
             SELECT id, user_id, symbol, exchange, order_id, trade_type, quantity, 
-                   price, status, executed_at, strategy_id, created_at, updated_at
+                   price, status, executed_at, strategy_id, exit_reason, created_at, updated_at
             FROM trades 
             WHERE user_id = ? AND status IN ('Pending', 'PartiallyFilled')
         ";
@@ -143,6 +174,7 @@ impl TradingEngine {
                 status,
                 executed_at: row.get("executed_at"),
                 strategy_id: row.get("strategy_id"),
+                exit_reason: row.get("exit_reason"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             };
@@ -154,18 +186,89 @@ impl TradingEngine {
         Ok(())
     }
     
+    /// Load open pair positions from database
+    async fn load_pair_positions(&self) -> Result<()> {
+        let query = "
+            SELECT id, strategy_id, symbol_a, symbol_b, exchange, side, quantity_a, quantity_b,
+                   entry_price_a, entry_price_b, entry_z, opened_at
+            FROM pair_positions
+            WHERE user_id = ?
+        ";
+        
+        let rows = sqlx::query(query)
+            .bind(&self.user_id)
+            .fetch_all(self.db_service.get_database().get_pool())
+            .await?;
+            
+        let mut pair_positions = self.pair_positions.write().await;
+        
+        for row in rows {
+            let side_str: String = row.get("side");
+            let side = match side_str.parse::<PairSide>() {
+                Ok(side) => side,
+                Err(e) => {
+                    warn!("Skipping stored pair position: {}", e);
+                    continue;
+                }
+            };
+            
+            let position = PairPosition {
+                id: row.get("id"),
+                strategy_id: row.get("strategy_id"),
+                symbol_a: row.get("symbol_a"),
+                symbol_b: row.get("symbol_b"),
+                exchange: row.get("exchange"),
+                side,
+                quantity_a: row.get("quantity_a"),
+                quantity_b: row.get("quantity_b"),
+                entry_price_a: Decimal::from_f64_retain(row.get("entry_price_a")).unwrap_or(Decimal::ZERO),
+                entry_price_b: Decimal::from_f64_retain(row.get("entry_price_b")).unwrap_or(Decimal::ZERO),
+                entry_z: row.get("entry_z"),
+                opened_at: row.get("opened_at"),
+            };
+            
+            pair_positions.insert(position.strategy_id.clone(), position);
+        }
+        
+        info!("Loaded {} pair positions", pair_positions.len());
+        Ok(())
+    }
+    
+    /// Reload every cache kept from the database, after a backup restore replaced it
+    ///
+    /// Only called while trading is stopped, so no order is in flight against the old trades.
+    pub async fn reload(&self) -> Result<()> {
+        self.strategy_manager.reload().await?;
+        self.risk_manager.reload().await?;
+        self.instrument_service.reload().await?;
+        
+        self.active_trades.write().await.clear();
+        self.pair_positions.write().await.clear();
+        self.pending_time_exits.write().await.clear();
+        self.load_active_trades().await?;
+        self.load_pair_positions().await?;
+        
+        info!("Trading engine reloaded for user: {}", self.user_id);
+        Ok(())
+    }
+    
     /// Start the order processing task
     async fn start_order_processor(&self, mut order_receiver: mpsc::UnboundedReceiver<OrderRequest>) {
         let kite_service = Arc::clone(&self.kite_service);
         let db_service = Arc::clone(&self.db_service);
         let risk_manager = Arc::clone(&self.risk_manager);
+        let instrument_service = Arc::clone(&self.instrument_service);
         let active_trades = Arc::clone(&self.active_trades);
+        let pending_time_exits = Arc::clone(&self.pending_time_exits);
         let last_execution_time = Arc::clone(&self.last_execution_time);
         let user_id = self.user_id.clone();
         
         tokio::spawn(async move {
             while let Some(order_request) = order_receiver.recv().await {
                 let start_time = Instant::now();
+                let time_exit = (order_request.exit_reason.as_deref() == Some(TIME_EXIT_REASON)).then(|| {
+                    (order_request.exchange.clone(), order_request.symbol.clone(), order_request.strategy_id.clone())
+                });
                 
                 // Process order with timeout for sub-100ms execution
                 let result = timeout(
@@ -174,6 +277,7 @@ impl TradingEngine {
                         &kite_service,
                         &db_service,
                         &risk_manager,
+                        &instrument_service,
                         &active_trades,
                         order_request,
                         &user_id,
@@ -182,6 +286,11 @@ impl TradingEngine {
                 
                 let execution_time = start_time.elapsed();
                 
+                // A failed time exit is queued again on the position's next tick
+                if let Some(key) = time_exit.filter(|_| !matches!(result, Ok(Ok(_)))) {
+                    pending_time_exits.write().await.remove(&key);
+                }
+                
                 // Update last execution time
                 {
                     let mut last_time = last_execution_time.lock().await;
@@ -214,8 +323,9 @@ impl TradingEngine {
         kite_service: &Arc<KiteService>,
         db_service: &Arc<EnhancedDatabaseService>,
         risk_manager: &Arc<RiskManager>,
+        instrument_service: &Arc<InstrumentService>,
         active_trades: &Arc<RwLock<HashMap<String, Trade>>>,
-        order_request: OrderRequest,
+        mut order_request: OrderRequest,
         user_id: &str,
     ) -> Result<OrderResponse> {
         // Validate order with risk manager
@@ -223,6 +333,20 @@ impl TradingEngine {
             return Err(HedgeXError::TradingError("Order rejected by risk manager".to_string()));
         }
         
+        // Price the signal saw, before tick rounding, to measure the fill's slippage against
+        let signal_price = order_request.price;
+        
+        // Quantities must be whole lots and limit prices sit on the instrument's tick
+        if let Some(instrument) = instrument_service.get_instrument(&order_request.exchange, &order_request.symbol).await? {
+            if !instrument.is_whole_lots(order_request.quantity as u32) {
+                return Err(HedgeXError::TradingError(format!(
+                    "Quantity {} of {} is not a multiple of its lot size {}",
+                    order_request.quantity, order_request.symbol, instrument.lot_size
+                )));
+            }
+            order_request.price = order_request.price.map(|price| instrument.round_to_tick(price));
+        }
+        
         // Create trade record
         let mut trade = Trade::new(
             &order_request.user_id,
@@ -233,6 +357,10 @@ impl TradingEngine {
             order_request.price.unwrap_or(Decimal::ZERO),
             &order_request.strategy_id,
         );
+        trade.exit_reason = order_request.exit_reason.clone();
+        
+        // Excursions of the position this trade exits are kept with it, to calibrate stops and targets
+        let exited_position = risk_manager.position_reduced_by(&trade).await;
         
         // Convert to Kite order request
         let kite_order = KiteOrderRequest {
@@ -273,8 +401,10 @@ impl TradingEngine {
         // Store trade in database
         let query = "
             INSERT INTO trades (id, user_id, symbol, exchange, order_id, trade_type, 
-                               quantity, price, status, executed_at, strategy_id, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                               quantity, price, status, executed_at, strategy_id, exit_reason,
+                               signal_price, order_type, entry_price, mae_percentage, mfe_percentage,
+                               created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ";
         
         sqlx::query(query)
@@ -289,10 +419,17 @@ impl TradingEngine {
             .bind(trade.status.to_string())
             .bind(trade.executed_at)
             .bind(&trade.strategy_id)
+            .bind(&trade.exit_reason)
+            .bind(signal_price.and_then(|price| price.to_f64()))
+            .bind(order_request.order_type.to_string())
+            .bind(exited_position.as_ref().and_then(|position| position.average_price.to_f64()))
+            .bind(exited_position.as_ref().and_then(|position| position.max_adverse_excursion.to_f64()))
+            .bind(exited_position.as_ref().and_then(|position| position.max_favorable_excursion.to_f64()))
             .bind(trade.created_at)
             .bind(trade.updated_at)
             .execute(db_service.get_database().get_pool())
             .await?;
+        Self::record_charges(db_service, &trade.id).await?;
             
         // Update risk manager with new trade first
         risk_manager.update_position(&trade).await?;
@@ -331,6 +468,7 @@ impl TradingEngine {
         // Start monitoring tasks
         self.start_position_monitoring().await;
         self.start_order_status_monitoring().await;
+        self.start_margin_monitoring().await;
         
         Ok(())
     }
@@ -399,6 +537,21 @@ impl TradingEngine {
             let mut cache = self.market_data_cache.write().await;
             cache.insert(market_data.symbol.clone(), market_data.clone());
         }
+        self.price_freshness.write().await.record(&market_data.symbol, Utc::now());
+        
+        // Pre-open ticks are the auction's indicative prices: keep them as the expected open, never trade on them
+        let held = {
+            let mut pre_open = self.pre_open.write().await;
+            if pre_open.observe(&market_data.symbol, market_data.ltp, market_data.close_price, market_data.timestamp) {
+                return Ok(());
+            }
+            pre_open.holds_strategies(market_data.timestamp)
+        };
+        
+        // Index ticks only feed the market regime filter
+        if self.strategy_manager.update_index_tick(&market_data).await {
+            return Ok(());
+        }
         
         // Update risk manager with current prices
         self.risk_manager.update_market_prices(&market_data.symbol, market_data.ltp).await?;
@@ -408,15 +561,37 @@ impl TradingEngine {
             return Ok(());
         }
         
+        // Until the release time only exits are checked
+        if held {
+            return self.check_exit_conditions(&market_data.symbol).await;
+        }
+        
         // Generate signals for all enabled strategies
         let strategies = self.strategy_manager.get_enabled_strategies().await?;
+        let mut pair_strategy_ids: Vec<String> = self.pair_positions.read().await.keys().cloned().collect();
         
         for strategy in strategies {
+            if self.strategy_manager.is_pair_strategy(&strategy.id).await {
+                if !pair_strategy_ids.contains(&strategy.id) {
+                    pair_strategy_ids.push(strategy.id.clone());
+                }
+                continue;
+            }
+            
             if let Some(signal) = self.strategy_manager.generate_signal(&market_data, &strategy.id).await? {
                 self.process_trading_signal(signal).await?;
             }
         }
         
+        // Pair strategies, including disabled ones that still hold a position
+        for strategy_id in pair_strategy_ids {
+            let open_side = self.pair_positions.read().await.get(&strategy_id).map(|p| p.side);
+            
+            if let Some(signal) = self.strategy_manager.generate_pair_signal(&market_data, &strategy_id, open_side).await? {
+                self.process_pair_signal(signal).await?;
+            }
+        }
+        
         // Check stop loss and take profit conditions
         self.check_exit_conditions(&market_data.symbol).await?;
         
@@ -457,6 +632,7 @@ impl TradingEngine {
             order_type: OrderType::Limit,
             strategy_id: signal.strategy_id.clone(),
             user_id: self.user_id.clone(),
+            exit_reason: None,
         };
         
         // Submit order to queue
@@ -468,6 +644,204 @@ impl TradingEngine {
         Ok(())
     }
     
+    /// Process a pair trading signal, placing both legs together
+    async fn process_pair_signal(&self, signal: PairSignal) -> Result<()> {
+        // One leg's price comes from the cache, and a stale one misjudges the spread
+        if let Some(config) = self.strategy_manager.get_pair_config(&signal.strategy_id).await {
+            if let Err(e) = self.check_fresh_prices(&[&config.symbol_a, &config.symbol_b]).await {
+                warn!("Ignoring pair signal for {}: {}", signal.strategy_id, e);
+                return Ok(());
+            }
+        }
+        
+        match signal.signal_type {
+            PairSignalType::Enter(side) => self.open_pair_position(&signal, side).await,
+            PairSignalType::Exit | PairSignalType::StopLoss => {
+                self.close_pair_position(&signal.strategy_id, signal.price_a, signal.price_b).await
+            }
+        }
+    }
+    
+    /// Open a pair position with both legs sized from the hedge ratio
+    async fn open_pair_position(&self, signal: &PairSignal, side: PairSide) -> Result<()> {
+        if self.pair_positions.read().await.contains_key(&signal.strategy_id) {
+            return Ok(());
+        }
+        
+        let config = self.strategy_manager.get_pair_config(&signal.strategy_id).await
+            .ok_or_else(|| HedgeXError::NotFoundError("Pair configuration not found".to_string()))?;
+        let strategy = self.strategy_manager.get_strategy(&signal.strategy_id).await?
+            .ok_or_else(|| HedgeXError::NotFoundError("Strategy not found".to_string()))?;
+            
+        if signal.price_a <= Decimal::ZERO || signal.price_b <= Decimal::ZERO {
+            return Ok(());
+        }
+        
+        // Leg A gets the risk allocation, leg B is sized for the hedge ratio in notional terms
+        let capital = self.risk_manager.sizing_capital().await;
+        let risk_fraction = Decimal::from_f64(strategy.risk_percentage / 100.0).unwrap_or(Decimal::ONE / Decimal::from(100));
+        let hedge_ratio = Decimal::from_f64(config.hedge_ratio).unwrap_or(Decimal::ONE);
+        let lot_a = self.lot_size(&config.exchange, &config.symbol_a).await?;
+        let lot_b = self.lot_size(&config.exchange, &config.symbol_b).await?;
+        
+        // Leg B's own caps bound leg A through the hedge ratio
+        let max_b = self.cap_leg(&strategy, i32::MAX, signal.price_b, 1).await;
+        let max_a_for_b = (Decimal::from(max_b) * signal.price_b / (signal.price_a * hedge_ratio))
+            .to_i32()
+            .unwrap_or(0);
+        let wanted_a = (capital * risk_fraction / signal.price_a).to_i32().unwrap_or(0);
+        let quantity_a = self.cap_leg(&strategy, wanted_a.min(max_a_for_b), signal.price_a, lot_a).await;
+        let quantity_b = (Decimal::from(quantity_a) * signal.price_a * hedge_ratio / signal.price_b)
+            .round()
+            .to_i32()
+            .unwrap_or(0)
+            .min(max_b) / lot_b * lot_b;
+            
+        if quantity_a == 0 || quantity_b == 0 {
+            warn!("Skipping pair entry for {}: legs do not fit the position limits in whole lots", signal.strategy_id);
+            return Ok(());
+        }
+        
+        let (trade_type_a, trade_type_b) = side.entry_trade_types();
+        
+        let legs = [
+            (&config.symbol_a, trade_type_a, quantity_a, signal.price_a),
+            (&config.symbol_b, trade_type_b, quantity_b, signal.price_b),
+        ];
+        self.submit_pair_orders(&signal.strategy_id, &config.exchange, &legs).await?;
+        
+        let position = PairPosition {
+            id: Uuid::new_v4().to_string(),
+            strategy_id: signal.strategy_id.clone(),
+            symbol_a: config.symbol_a.clone(),
+            symbol_b: config.symbol_b.clone(),
+            exchange: config.exchange.clone(),
+            side,
+            quantity_a,
+            quantity_b,
+            entry_price_a: signal.price_a,
+            entry_price_b: signal.price_b,
+            entry_z: signal.z_score,
+            opened_at: signal.timestamp,
+        };
+        
+        info!("Opened pair position {:?} on {}/{} (z={:.2})", 
+              side, position.symbol_a, position.symbol_b, signal.z_score);
+        
+        self.save_pair_position(&position).await?;
+        self.pair_positions.write().await.insert(signal.strategy_id.clone(), position);
+        Ok(())
+    }
+    
+    /// Largest quantity up to the one given within the strategy and risk caps, in whole lots
+    async fn cap_leg(&self, strategy: &StrategyParams, quantity: i32, price: Decimal, lot_size: i32) -> i32 {
+        let quantity = self.risk_manager.cap_quantity(strategy.cap_quantity(quantity, price), price).await;
+        quantity / lot_size * lot_size
+    }
+    
+    /// Lot size of an instrument, or one if it is not in the instruments master
+    async fn lot_size(&self, exchange: &str, symbol: &str) -> Result<i32> {
+        Ok(self.instrument_service.get_instrument(exchange, symbol).await?
+            .map_or(1, |instrument| instrument.lot_size.max(1) as i32))
+    }
+    
+    /// Persist an open pair position so it survives a restart
+    async fn save_pair_position(&self, position: &PairPosition) -> Result<()> {
+        let query = "
+            INSERT OR REPLACE INTO pair_positions 
+            (id, strategy_id, user_id, symbol_a, symbol_b, exchange, side, quantity_a, quantity_b,
+             entry_price_a, entry_price_b, entry_z, opened_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        ";
+        
+        sqlx::query(query)
+            .bind(&position.id)
+            .bind(&position.strategy_id)
+            .bind(&self.user_id)
+            .bind(&position.symbol_a)
+            .bind(&position.symbol_b)
+            .bind(&position.exchange)
+            .bind(position.side.to_string())
+            .bind(position.quantity_a)
+            .bind(position.quantity_b)
+            .bind(position.entry_price_a.to_f64().unwrap_or(0.0))
+            .bind(position.entry_price_b.to_f64().unwrap_or(0.0))
+            .bind(position.entry_z)
+            .bind(position.opened_at)
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+            
+        Ok(())
+    }
+    
+    /// Close both legs of a pair position at once
+    async fn close_pair_position(&self, strategy_id: &str, price_a: Decimal, price_b: Decimal) -> Result<()> {
+        let position = match self.pair_positions.write().await.remove(strategy_id) {
+            Some(position) => position,
+            None => return Ok(()),
+        };
+        
+        let (trade_type_a, trade_type_b) = position.side.exit_trade_types();
+        let legs = [
+            (&position.symbol_a, trade_type_a, position.quantity_a, price_a),
+            (&position.symbol_b, trade_type_b, position.quantity_b, price_b),
+        ];
+        self.submit_pair_orders(strategy_id, &position.exchange, &legs).await?;
+        
+        sqlx::query("DELETE FROM pair_positions WHERE id = ?")
+            .bind(&position.id)
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+            
+        info!("Closed pair position on {}/{} with combined P&L {}", 
+              position.symbol_a, position.symbol_b, position.combined_pnl(price_a, price_b));
+        Ok(())
+    }
+    
+    /// Queue the leg orders of a pair back to back
+    async fn submit_pair_orders(
+        &self,
+        strategy_id: &str,
+        exchange: &str,
+        legs: &[(&String, TradeType, i32, Decimal)],
+    ) -> Result<()> {
+        // Hold the queue lock so no other order is interleaved between the legs
+        let order_queue = self.order_queue.lock().await;
+        
+        for (symbol, trade_type, quantity, price) in legs {
+            let order_request = OrderRequest {
+                symbol: symbol.to_string(),
+                exchange: exchange.to_string(),
+                trade_type: *trade_type,
+                quantity: *quantity,
+                price: Some(*price),
+                order_type: OrderType::Market, // Market orders keep the legs in step
+                strategy_id: strategy_id.to_string(),
+                user_id: self.user_id.clone(),
+                exit_reason: None,
+            };
+            
+            order_queue.send(order_request)
+                .map_err(|e| HedgeXError::TradingError(format!("Failed to queue pair leg for {}: {}", symbol, e)))?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Get open pair positions with their combined unrealized P&L
+    pub async fn get_pair_positions(&self) -> Result<Vec<(PairPosition, Decimal)>> {
+        let positions = self.pair_positions.read().await;
+        let cache = self.market_data_cache.read().await;
+        
+        Ok(positions.values()
+            .map(|position| {
+                let price_a = cache.get(&position.symbol_a).map(|d| d.ltp).unwrap_or(position.entry_price_a);
+                let price_b = cache.get(&position.symbol_b).map(|d| d.ltp).unwrap_or(position.entry_price_b);
+                (position.clone(), position.combined_pnl(price_a, price_b))
+            })
+            .collect())
+    }
+    
     /// Calculate position size based on signal and risk parameters
     async fn calculate_position_size(&self, signal: &TradingSignal) -> Result<i32> {
         let strategy = self.strategy_manager.get_strategy(&signal.strategy_id).await?
@@ -487,12 +861,33 @@ impl TradingEngine {
         // Ensure minimum and maximum position sizes
         let min_quantity = 1;
         let max_quantity = 1000; // Maximum 1000 shares per trade
+        let position_size = position_size.max(min_quantity).min(max_quantity);
         
-        Ok(position_size.max(min_quantity).min(max_quantity))
+        // Per-strategy caps may reduce the order to zero, which skips it
+        Ok(strategy.cap_quantity(position_size, signal.price))
     }
     
     /// Handle exit signals (stop loss, take profit)
     async fn handle_exit_signal(&self, signal: &TradingSignal) -> Result<()> {
+        // A leg of a pair is never closed on its own
+        let pair_strategy = self.pair_positions.read().await.values()
+            .find(|p| p.symbol_a == signal.symbol || p.symbol_b == signal.symbol)
+            .map(|p| p.strategy_id.clone());
+            
+        if let Some(strategy_id) = pair_strategy {
+            let position = self.pair_positions.read().await.get(&strategy_id).cloned();
+            if let Some(position) = position {
+                if let Err(e) = self.check_fresh_prices(&[&position.symbol_a, &position.symbol_b]).await {
+                    warn!("Not closing pair position {}: {}", strategy_id, e);
+                    return Ok(());
+                }
+                
+                let price_a = self.get_current_price(&position.symbol_a).await.unwrap_or(position.entry_price_a);
+                let price_b = self.get_current_price(&position.symbol_b).await.unwrap_or(position.entry_price_b);
+                return self.close_pair_position(&strategy_id, price_a, price_b).await;
+            }
+        }
+        
         let positions = self.risk_manager.get_positions().await?;
         
         for position in positions {
@@ -511,6 +906,11 @@ impl TradingEngine {
                     order_type: OrderType::Market, // Use market order for quick exit
                     strategy_id: signal.strategy_id.clone(),
                     user_id: self.user_id.clone(),
+                    exit_reason: Some(match signal.signal_type {
+                        SignalType::StopLoss => "Stop loss",
+                        SignalType::TakeProfit => "Take profit",
+                        _ => "Signal exit",
+                    }.to_string()),
                 };
                 
                 let order_queue = self.order_queue.lock().await;
@@ -557,11 +957,87 @@ impl TradingEngine {
             self.handle_exit_signal(&signal).await?;
         }
         
+        self.check_time_exits(symbol).await
+    }
+    
+    /// Close positions on a symbol held longer than their strategy allows
+    async fn check_time_exits(&self, symbol: &str) -> Result<()> {
+        let now = Utc::now();
+        let positions: Vec<Position> = self.risk_manager.get_positions().await?
+            .into_iter()
+            .filter(|position| position.symbol == symbol)
+            .collect();
+        
+        // Positions that have closed need no exit any more
+        {
+            let open: HashSet<TimeExitKey> = positions.iter()
+                .filter_map(|position| {
+                    let strategy_id = position.strategy_id.clone()?;
+                    Some((position.exchange.clone(), position.symbol.clone(), strategy_id))
+                })
+                .collect();
+            self.pending_time_exits.write().await
+                .retain(|key| key.1 != symbol || open.contains(key));
+        }
+        
+        for position in positions {
+            let strategy_id = match &position.strategy_id {
+                Some(strategy_id) => strategy_id.clone(),
+                None => continue,
+            };
+            
+            // One exit per position, however many ticks arrive before it fills
+            let key = (position.exchange.clone(), position.symbol.clone(), strategy_id.clone());
+            if self.pending_time_exits.read().await.contains(&key) {
+                continue;
+            }
+            
+            // Pair legs are managed by the spread, not per leg
+            if self.pair_positions.read().await.contains_key(&strategy_id) {
+                continue;
+            }
+            
+            let strategy = match self.strategy_manager.get_strategy(&strategy_id).await? {
+                Some(strategy) => strategy,
+                None => continue,
+            };
+            
+            if !strategy.holding_period_exceeded(position.entry_time, now) {
+                continue;
+            }
+            
+            info!("Closing {} after exceeding max holding period of {:?} minutes", 
+                  symbol, strategy.max_holding_minutes);
+            
+            let order_request = OrderRequest {
+                symbol: position.symbol.clone(),
+                exchange: position.exchange.clone(),
+                trade_type: match position.trade_type {
+                    TradeType::Buy => TradeType::Sell,
+                    TradeType::Sell => TradeType::Buy,
+                },
+                quantity: position.quantity,
+                price: Some(position.current_price),
+                order_type: OrderType::Market,
+                strategy_id,
+                user_id: self.user_id.clone(),
+                exit_reason: Some(TIME_EXIT_REASON.to_string()),
+            };
+            
+            self.pending_time_exits.write().await.insert(key.clone());
+            let order_queue = self.order_queue.lock().await;
+            if let Err(e) = order_queue.send(order_request) {
+                error!("Failed to queue time exit for {}: {}", symbol, e);
+                self.pending_time_exits.write().await.remove(&key);
+            }
+        }
+        
         Ok(())
     }
     
-    /// Get current price from market data cache
+    /// Get current price from market data cache, refusing prices older than the configured limit
     async fn get_current_price(&self, symbol: &str) -> Result<Decimal> {
+        self.price_freshness.read().await.check(symbol, Utc::now())?;
         let cache = self.market_data_cache.read().await;
         
         match cache.get(symbol) {
@@ -570,6 +1046,36 @@ impl TradingEngine {
         }
     }
     
+    /// Fail when any of the symbols' cached prices is older than the configured limit
+    async fn check_fresh_prices(&self, symbols: &[&String]) -> Result<()> {
+        let freshness = self.price_freshness.read().await;
+        let now = Utc::now();
+        for symbol in symbols {
+            freshness.check(symbol, now)?;
+        }
+        Ok(())
+    }
+    
+    /// Change how old a cached price may get before the engine stops acting on it
+    pub async fn set_max_price_age(&self, seconds: i64) -> Result<()> {
+        self.price_freshness.write().await.set_max_age_seconds(seconds)
+    }
+    
+    /// Symbols whose cached price is older than the configured limit
+    pub async fn get_stale_symbols(&self) -> Vec<String> {
+        self.price_freshness.read().await.stale_symbols(Utc::now())
+    }
+    
+    /// Change how many minutes after the open strategies are held
+    pub async fn set_strategy_release_delay(&self, minutes: i64) -> Result<()> {
+        self.pre_open.write().await.set_release_delay_minutes(minutes)
+    }
+    
+    /// Expected opens collected in the latest pre-open window
+    pub async fn get_pre_open_quotes(&self) -> Vec<PreOpenQuote> {
+        self.pre_open.read().await.quotes()
+    }
+    
     /// Start position monitoring task
     async fn start_position_monitoring(&self) {
         let risk_manager = Arc::clone(&self.risk_manager);
@@ -618,6 +1124,30 @@ impl TradingEngine {
         });
     }
     
+    /// Start margin monitoring task, keeping the risk manager's available margin current
+    async fn start_margin_monitoring(&self) {
+        let kite_service = Arc::clone(&self.kite_service);
+        let risk_manager = Arc::clone(&self.risk_manager);
+        let is_running = Arc::clone(&self.is_running);
+        
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(Duration::from_secs(15));
+            
+            loop {
+                interval.tick().await;
+                
+                if !*is_running.read().await {
+                    break;
+                }
+                
+                match kite_service.get_margins().await {
+                    Ok(margins) => risk_manager.update_margins(&margins).await,
+                    Err(e) => warn!("Failed to refresh margins: {}", e),
+                }
+            }
+        });
+    }
+    
     /// Update order statuses from Kite API
     async fn update_order_statuses(
         kite_service: &Arc<KiteService>,
@@ -633,12 +1163,7 @@ impl TradingEngine {
             for trade in trades.values() {
                 if let Some(order_id) = &trade.order_id {
                     if let Some(order) = orders.iter().find(|o| o.order_id == *order_id) {
-                        let new_status = match order.status {
-                            crate::models::kite::KiteOrderStatus::Complete => TradeStatus::Executed,
-                            crate::models::kite::KiteOrderStatus::Cancelled => TradeStatus::Cancelled,
-                            crate::models::kite::KiteOrderStatus::Rejected => TradeStatus::Failed,
-                            _ => TradeStatus::Pending,
-                        };
+                        let new_status = Self::trade_status_for(order.status);
                         
                         if new_status != trade.status {
                             trades_to_update.push((trade.id.clone(), new_status));
@@ -650,31 +1175,153 @@ impl TradingEngine {
         
         // Update trades with new statuses
         for (trade_id, new_status) in trades_to_update {
-            {
-                let mut trades = active_trades.write().await;
-                if let Some(trade) = trades.get_mut(&trade_id) {
-                    trade.update_status(new_status, trade.order_id.clone());
-                    
-                    // Remove from active trades if completed
-                    if new_status == TradeStatus::Executed || 
-                       new_status == TradeStatus::Cancelled || 
-                       new_status == TradeStatus::Failed {
-                        trades.remove(&trade_id);
+            Self::set_trade_status(active_trades, db_service, &trade_id, new_status, None).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Apply order updates pushed on the ticker connection as they arrive
+    ///
+    /// Polling keeps running alongside to catch updates missed while the ticker was down.
+    pub fn start_order_update_listener(&self, mut updates: broadcast::Receiver<KiteOrderUpdate>) {
+        let active_trades = Arc::clone(&self.active_trades);
+        let db_service = Arc::clone(&self.db_service);
+        
+        tokio::spawn(async move {
+            loop {
+                match updates.recv().await {
+                    Ok(update) => {
+                        if let Err(e) = Self::apply_order_update(&active_trades, &db_service, &update).await {
+                            error!("Failed to apply update for order {}: {}", update.order_id, e);
+                        }
+                    }
+                    Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                        warn!("Skipped {} order updates; polling will pick them up", skipped);
                     }
+                    Err(broadcast::error::RecvError::Closed) => break,
                 }
             }
-            
-            // Update in database
-            let query = "UPDATE trades SET status = ?, updated_at = ? WHERE id = ?";
-            
-            sqlx::query(query)
-                .bind(new_status.to_string())
-                .bind(Utc::now())
-                .bind(&trade_id)
-                .execute(db_service.get_database().get_pool())
-                .await?;
+        });
+    }
+    
+    /// Apply one pushed order update; returns whether an active trade changed
+    async fn apply_order_update(
+        active_trades: &Arc<RwLock<HashMap<String, Trade>>>,
+        db_service: &Arc<EnhancedDatabaseService>,
+        update: &KiteOrderUpdate,
+    ) -> Result<bool> {
+        let trade_id = {
+            let trades = active_trades.read().await;
+            trades.values()
+                .find(|trade| trade.order_id.as_deref() == Some(update.order_id.as_str()))
+                .filter(|trade| trade.status != Self::trade_status_for(update.status))
+                .map(|trade| trade.id.clone())
+        };
+        
+        let trade_id = match trade_id {
+            Some(id) => id,
+            None => return Ok(false),
+        };
+        
+        let new_status = Self::trade_status_for(update.status);
+        if new_status == TradeStatus::Failed {
+            warn!("Order {} rejected: {}", update.order_id,
+                  update.status_message.as_deref().unwrap_or("no reason given"));
         }
         
+        // Record the actual fill price rather than the price the order was sent at
+        let fill_price = match new_status {
+            TradeStatus::Executed if update.average_price > 0.0 => Decimal::from_f64(update.average_price),
+            _ => None,
+        };
+        
+        Self::set_trade_status(active_trades, db_service, &trade_id, new_status, fill_price).await?;
+        Ok(true)
+    }
+    
+    /// Trade status for a Kite order status
+    fn trade_status_for(status: KiteOrderStatus) -> TradeStatus {
+        match status {
+            KiteOrderStatus::Complete => TradeStatus::Executed,
+            KiteOrderStatus::Cancelled => TradeStatus::Cancelled,
+            KiteOrderStatus::Rejected => TradeStatus::Failed,
+            _ => TradeStatus::Pending,
+        }
+    }
+    
+    /// Update a trade's status in memory and in the database, dropping it from active trades once final
+    async fn set_trade_status(
+        active_trades: &Arc<RwLock<HashMap<String, Trade>>>,
+        db_service: &Arc<EnhancedDatabaseService>,
+        trade_id: &str,
+        new_status: TradeStatus,
+        fill_price: Option<Decimal>,
+    ) -> Result<()> {
+        {
+            let mut trades = active_trades.write().await;
+            if let Some(trade) = trades.get_mut(trade_id) {
+                trade.update_status(new_status, trade.order_id.clone());
+                if let Some(price) = fill_price {
+                    trade.price = price;
+                }
+                
+                // Remove from active trades if completed
+                if new_status == TradeStatus::Executed || 
+                   new_status == TradeStatus::Cancelled || 
+                   new_status == TradeStatus::Failed {
+                    trades.remove(trade_id);
+                }
+            }
+        }
+        
+        // Update in database
+        let query = "UPDATE trades SET status = ?, price = COALESCE(?, price), updated_at = ? WHERE id = ?";
+        
+        sqlx::query(query)
+            .bind(new_status.to_string())
+            .bind(fill_price.and_then(|price| price.to_f64()))
+            .bind(Utc::now())
+            .bind(trade_id)
+            .execute(db_service.get_database().get_pool())
+            .await?;
+        
+        // Re-estimate charges at the price the order actually filled at
+        if new_status == TradeStatus::Executed {
+            Self::record_charges(db_service, trade_id).await?;
+        }
+        
+        Ok(())
+    }
+    
+    /// Store the charges the backtesting cost model estimates for a trade at its recorded price
+    async fn record_charges(db_service: &Arc<EnhancedDatabaseService>, trade_id: &str) -> Result<()> {
+        let database = db_service.get_database();
+        let pool = database.get_pool();
+        let row = sqlx::query("SELECT trade_type, quantity, price FROM trades WHERE id = ?")
+            .bind(trade_id)
+            .fetch_one(pool)
+            .await?;
+        
+        let side = if row.get::<String, _>("trade_type") == "Sell" { TradeType::Sell } else { TradeType::Buy };
+        let price = Decimal::from_f64(row.get::<f64, _>("price")).unwrap_or(Decimal::ZERO);
+        let charges = CostModel::default().breakdown(side, price, row.get("quantity"));
+        
+        sqlx::query(
+            "UPDATE trades SET brokerage = ?, stt = ?, exchange_charges = ?, sebi_fee = ?, gst = ?, stamp_duty = ?, charges = ?
+             WHERE id = ?"
+        )
+            .bind(charges.brokerage.to_f64().unwrap_or(0.0))
+            .bind(charges.stt.to_f64().unwrap_or(0.0))
+            .bind(charges.exchange_charges.to_f64().unwrap_or(0.0))
+            .bind(charges.sebi_fee.to_f64().unwrap_or(0.0))
+            .bind(charges.gst.to_f64().unwrap_or(0.0))
+            .bind(charges.stamp_duty.to_f64().unwrap_or(0.0))
+            .bind(charges.total.to_f64().unwrap_or(0.0))
+            .bind(trade_id)
+            .execute(pool)
+            .await?;
+        
         Ok(())
     }
     
@@ -0,0 +1,123 @@
+use crate::error::{HedgeXError, Result};
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+
+/// Seconds a cached price may go without an update before it is too old to act on
+pub const DEFAULT_MAX_PRICE_AGE_SECONDS: i64 = 10;
+
+/// When each symbol's price last updated, and how old a price may get before it is stale
+#[derive(Debug, Clone)]
+pub struct PriceFreshness {
+    max_age_seconds: i64,
+    updated_at: HashMap<String, DateTime<Utc>>,
+}
+
+impl Default for PriceFreshness {
+    fn default() -> Self {
+        Self {
+            max_age_seconds: DEFAULT_MAX_PRICE_AGE_SECONDS,
+            updated_at: HashMap::new(),
+        }
+    }
+}
+
+impl PriceFreshness {
+    /// Change the age limit; between 1 second and 1 hour
+    pub fn set_max_age_seconds(&mut self, max_age_seconds: i64) -> Result<()> {
+        if !(1..=3600).contains(&max_age_seconds) {
+            return Err(HedgeXError::ValidationError(
+                "Stale data threshold must be between 1 and 3600 seconds".to_string()
+            ));
+        }
+
+        self.max_age_seconds = max_age_seconds;
+        Ok(())
+    }
+
+    pub fn max_age_seconds(&self) -> i64 {
+        self.max_age_seconds
+    }
+
+    /// Note that a symbol's price updated
+    pub fn record(&mut self, symbol: &str, at: DateTime<Utc>) {
+        self.updated_at.insert(symbol.to_string(), at);
+    }
+
+    pub fn updated_at(&self, symbol: &str) -> Option<DateTime<Utc>> {
+        self.updated_at.get(symbol).cloned()
+    }
+
+    /// Seconds since a symbol's price last updated
+    pub fn age_seconds(&self, symbol: &str, now: DateTime<Utc>) -> Option<f64> {
+        self.updated_at.get(symbol)
+            .map(|at| (now - *at).num_milliseconds().max(0) as f64 / 1000.0)
+    }
+
+    /// Whether a symbol's price is older than the limit; a symbol never updated is not stale
+    pub fn is_stale(&self, symbol: &str, now: DateTime<Utc>) -> bool {
+        self.age_seconds(symbol, now)
+            .is_some_and(|age| age > self.max_age_seconds as f64)
+    }
+
+    /// Symbols whose price is older than the limit, in name order
+    pub fn stale_symbols(&self, now: DateTime<Utc>) -> Vec<String> {
+        let mut symbols: Vec<String> = self.updated_at.keys()
+            .filter(|symbol| self.is_stale(symbol, now))
+            .cloned()
+            .collect();
+        symbols.sort();
+        symbols
+    }
+
+    /// Fail when a symbol's price is too old to act on
+    pub fn check(&self, symbol: &str, now: DateTime<Utc>) -> Result<()> {
+        match self.age_seconds(symbol, now) {
+            Some(age) if age > self.max_age_seconds as f64 => Err(HedgeXError::TradingError(format!(
+                "Price of {} is {:.1}s old, over the {}s limit",
+                symbol, age, self.max_age_seconds
+            ))),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    #[test]
+    fn test_prices_go_stale_after_the_limit() {
+        let mut freshness = PriceFreshness::default();
+        let start = Utc::now();
+        freshness.record("INFY", start);
+        freshness.record("TCS", start + Duration::seconds(8));
+
+        let now = start + Duration::seconds(12);
+        assert_eq!(freshness.age_seconds("INFY", now), Some(12.0));
+        assert!(freshness.is_stale("INFY", now));
+        assert!(!freshness.is_stale("TCS", now));
+        assert!(!freshness.is_stale("WIPRO", now));
+        assert_eq!(freshness.stale_symbols(now), vec!["INFY".to_string()]);
+        assert!(freshness.check("INFY", now).is_err());
+        assert!(freshness.check("TCS", now).is_ok());
+
+        // A fresh update clears the flag
+        freshness.record("INFY", now);
+        assert!(freshness.stale_symbols(now).is_empty());
+    }
+
+    #[test]
+    fn test_threshold_is_configurable() {
+        let mut freshness = PriceFreshness::default();
+        let start = Utc::now();
+        freshness.record("INFY", start);
+
+        freshness.set_max_age_seconds(60).unwrap();
+        assert!(!freshness.is_stale("INFY", start + Duration::seconds(30)));
+
+        assert!(freshness.set_max_age_seconds(0).is_err());
+        assert!(freshness.set_max_age_seconds(7200).is_err());
+        assert_eq!(freshness.max_age_seconds(), 60);
+    }
+}
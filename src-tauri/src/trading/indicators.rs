@@ -0,0 +1,194 @@
+// Indicators take values ordered oldest to newest, evaluate at the most
+// recent point and return None when there is not enough history.
+
+/// Simple moving average of the last `period` values
+pub fn sma(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+
+    let window = &values[values.len() - period..];
+    Some(window.iter().sum::<f64>() / period as f64)
+}
+
+/// Exponential moving average seeded with the SMA of the first `period` values
+pub fn ema(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+
+    let alpha = 2.0 / (period as f64 + 1.0);
+    let seed = values[..period].iter().sum::<f64>() / period as f64;
+    Some(values[period..].iter().fold(seed, |ema, value| alpha * value + (1.0 - alpha) * ema))
+}
+
+/// Population standard deviation of the last `period` values
+pub fn std_dev(values: &[f64], period: usize) -> Option<f64> {
+    let mean = sma(values, period)?;
+    let window = &values[values.len() - period..];
+    let variance = window.iter().map(|v| (v - mean).powi(2)).sum::<f64>() / period as f64;
+    Some(variance.sqrt())
+}
+
+/// Bollinger bands at the most recent value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct BollingerBands {
+    pub upper: f64,
+    pub middle: f64,
+    pub lower: f64,
+}
+
+impl BollingerBands {
+    /// Position of a price within the bands (0.0 at lower, 1.0 at upper)
+    pub fn percent_b(&self, price: f64) -> Option<f64> {
+        let width = self.upper - self.lower;
+        if width <= f64::EPSILON {
+            return None;
+        }
+        Some((price - self.lower) / width)
+    }
+}
+
+/// Bollinger bands of `period` values with bands `num_std` deviations wide
+pub fn bollinger_bands(values: &[f64], period: usize, num_std: f64) -> Option<BollingerBands> {
+    let middle = sma(values, period)?;
+    let deviation = std_dev(values, period)?;
+
+    Some(BollingerBands {
+        upper: middle + num_std * deviation,
+        middle,
+        lower: middle - num_std * deviation,
+    })
+}
+
+/// Highest of the last `period` values
+pub fn highest(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+
+    values[values.len() - period..].iter().copied().reduce(f64::max)
+}
+
+/// Lowest of the last `period` values
+pub fn lowest(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() < period {
+        return None;
+    }
+
+    values[values.len() - period..].iter().copied().reduce(f64::min)
+}
+
+/// Percentage change over the last `period` values
+pub fn rate_of_change(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() <= period {
+        return None;
+    }
+
+    let past = values[values.len() - 1 - period];
+    if past == 0.0 {
+        return None;
+    }
+    Some((values[values.len() - 1] - past) / past * 100.0)
+}
+
+/// Relative strength index using Wilder's smoothing
+pub fn rsi(values: &[f64], period: usize) -> Option<f64> {
+    if period == 0 || values.len() <= period {
+        return None;
+    }
+
+    let changes: Vec<f64> = values.windows(2).map(|w| w[1] - w[0]).collect();
+    let mut avg_gain = changes[..period].iter().filter(|c| **c > 0.0).sum::<f64>() / period as f64;
+    let mut avg_loss = changes[..period].iter().filter(|c| **c < 0.0).map(|c| -c).sum::<f64>() / period as f64;
+
+    for change in &changes[period..] {
+        avg_gain = (avg_gain * (period as f64 - 1.0) + change.max(0.0)) / period as f64;
+        avg_loss = (avg_loss * (period as f64 - 1.0) + (-change).max(0.0)) / period as f64;
+    }
+
+    if avg_loss == 0.0 {
+        return Some(100.0);
+    }
+    Some(100.0 - 100.0 / (1.0 + avg_gain / avg_loss))
+}
+
+/// Average true range using Wilder's smoothing
+pub fn atr(highs: &[f64], lows: &[f64], closes: &[f64], period: usize) -> Option<f64> {
+    let len = closes.len();
+    if period == 0 || highs.len() != len || lows.len() != len || len <= period {
+        return None;
+    }
+
+    let true_ranges: Vec<f64> = (1..len)
+        .map(|i| {
+            let prev_close = closes[i - 1];
+            (highs[i] - lows[i])
+                .max((highs[i] - prev_close).abs())
+                .max((lows[i] - prev_close).abs())
+        })
+        .collect();
+
+    let seed = true_ranges[..period].iter().sum::<f64>() / period as f64;
+    Some(true_ranges[period..].iter().fold(seed, |atr, tr| (atr * (period as f64 - 1.0) + tr) / period as f64))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn approx(a: f64, b: f64) -> bool {
+        (a - b).abs() < 1e-6
+    }
+
+    #[test]
+    fn test_moving_averages() {
+        let values = [1.0, 2.0, 3.0, 4.0, 5.0];
+
+        assert_eq!(sma(&values, 3), Some(4.0));
+        assert_eq!(sma(&values, 6), None);
+        assert_eq!(sma(&values, 0), None);
+
+        // Seed 2.0 from the first three, then 0.5 * 4 + 0.5 * 2 = 3, 0.5 * 5 + 0.5 * 3 = 4
+        assert!(approx(ema(&values, 3).unwrap(), 4.0));
+    }
+
+    #[test]
+    fn test_bollinger_bands() {
+        let values = [2.0, 4.0, 4.0, 4.0, 5.0, 5.0, 7.0, 9.0];
+        let bands = bollinger_bands(&values, 8, 2.0).unwrap();
+
+        assert!(approx(bands.middle, 5.0));
+        assert!(approx(bands.upper, 9.0));
+        assert!(approx(bands.lower, 1.0));
+        assert!(approx(bands.percent_b(5.0).unwrap(), 0.5));
+
+        assert!(bollinger_bands(&[3.0, 3.0], 2, 2.0).unwrap().percent_b(3.0).is_none());
+    }
+
+    #[test]
+    fn test_range_and_momentum() {
+        let values = [10.0, 12.0, 9.0, 11.0, 15.0];
+
+        assert_eq!(highest(&values, 3), Some(15.0));
+        assert_eq!(lowest(&values, 3), Some(9.0));
+        assert_eq!(lowest(&values, 6), None);
+        assert!(approx(rate_of_change(&values, 4).unwrap(), 50.0));
+    }
+
+    #[test]
+    fn test_rsi_and_atr() {
+        let rising: Vec<f64> = (0..20).map(|i| 100.0 + i as f64).collect();
+        assert_eq!(rsi(&rising, 14), Some(100.0));
+
+        let alternating: Vec<f64> = (0..30).map(|i| if i % 2 == 0 { 100.0 } else { 101.0 }).collect();
+        let value = rsi(&alternating, 14).unwrap();
+        assert!(value > 40.0 && value < 60.0);
+
+        let highs = [11.0, 12.0, 13.0, 14.0];
+        let lows = [9.0, 10.0, 11.0, 12.0];
+        let closes = [10.0, 11.0, 12.0, 13.0];
+        assert!(approx(atr(&highs, &lows, &closes, 2).unwrap(), 2.0));
+        assert_eq!(atr(&highs, &lows, &closes, 4), None);
+    }
+}
@@ -0,0 +1,194 @@
+use crate::trading::session::session_date;
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+
+/// Minutes of traded volume summed into the rolling volume
+pub const ROLLING_VOLUME_MINUTES: i64 = 5;
+
+/// Metrics derived from a symbol's ticks, kept current on every tick
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SymbolMetrics {
+    pub symbol: String,
+    /// Best ask less best bid; unset when the tick has no quote
+    pub spread: Option<Decimal>,
+    /// Spread in basis points of the mid price
+    pub spread_bps: Option<f64>,
+    /// Volume-weighted average price since the session's first tick
+    pub vwap: Option<Decimal>,
+    /// Volume traded over the last five minutes
+    pub rolling_volume: i64,
+    /// Average volume of a five-minute window this session
+    pub average_rolling_volume: f64,
+    /// Rolling volume over its session average; unset until anything has traded
+    pub relative_volume: Option<f64>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Spread, intraday VWAP and rolling volume of each symbol
+#[derive(Debug, Clone, Default)]
+pub struct LiveMetrics {
+    symbols: HashMap<String, SymbolState>,
+}
+
+#[derive(Debug, Clone)]
+struct SymbolState {
+    session: NaiveDate,
+    first_tick_at: DateTime<Utc>,
+    last_cumulative_volume: i64,
+    price_volume: Decimal,
+    traded_volume: i64,
+    /// Volume traded in each minute of the rolling window, oldest first
+    minutes: VecDeque<(i64, i64)>,
+    metrics: SymbolMetrics,
+}
+
+impl LiveMetrics {
+    /// Record a tick with the cumulative day volume, returning the symbol's updated metrics
+    ///
+    /// Volume traded since the previous tick is weighted at this tick's price. A new
+    /// session date or a drop in cumulative volume starts the session over.
+    pub fn update(
+        &mut self,
+        symbol: &str,
+        ltp: Decimal,
+        bid: Decimal,
+        ask: Decimal,
+        cumulative_volume: i64,
+        timestamp: DateTime<Utc>,
+    ) -> SymbolMetrics {
+        let session = session_date(timestamp);
+        let state = self.symbols.entry(symbol.to_string()).or_insert_with(|| SymbolState::new(symbol, session, timestamp));
+        if state.session != session || cumulative_volume < state.last_cumulative_volume {
+            *state = SymbolState::new(symbol, session, timestamp);
+        }
+
+        let traded = cumulative_volume - state.last_cumulative_volume;
+        state.last_cumulative_volume = cumulative_volume;
+        if traded > 0 && ltp > Decimal::ZERO {
+            state.price_volume += ltp * Decimal::from(traded);
+            state.traded_volume += traded;
+        }
+
+        let minute = timestamp.timestamp().div_euclid(60);
+        match state.minutes.back_mut() {
+            Some((last, volume)) if *last == minute => *volume += traded.max(0),
+            _ => state.minutes.push_back((minute, traded.max(0))),
+        }
+        while state.minutes.front().is_some_and(|(start, _)| *start <= minute - ROLLING_VOLUME_MINUTES) {
+            state.minutes.pop_front();
+        }
+
+        let rolling_volume: i64 = state.minutes.iter().map(|(_, volume)| volume).sum();
+        let elapsed_minutes = ((timestamp - state.first_tick_at).num_seconds() as f64 / 60.0).max(ROLLING_VOLUME_MINUTES as f64);
+        let average_rolling_volume = state.traded_volume as f64 * ROLLING_VOLUME_MINUTES as f64 / elapsed_minutes;
+
+        let quoted = bid > Decimal::ZERO && ask >= bid;
+        let mid = (bid + ask) / Decimal::from(2);
+        state.metrics = SymbolMetrics {
+            symbol: symbol.to_string(),
+            spread: quoted.then(|| ask - bid),
+            spread_bps: if quoted { ((ask - bid) / mid * Decimal::from(10_000)).to_f64() } else { None },
+            vwap: (state.traded_volume > 0).then(|| state.price_volume / Decimal::from(state.traded_volume)),
+            rolling_volume,
+            average_rolling_volume,
+            relative_volume: (average_rolling_volume > 0.0).then(|| rolling_volume as f64 / average_rolling_volume),
+            updated_at: timestamp,
+        };
+        state.metrics.clone()
+    }
+
+    /// Latest metrics of a symbol
+    pub fn get(&self, symbol: &str) -> Option<SymbolMetrics> {
+        self.symbols.get(symbol).map(|state| state.metrics.clone())
+    }
+
+    /// Latest metrics of every symbol, in symbol order
+    pub fn all(&self) -> Vec<SymbolMetrics> {
+        let mut metrics: Vec<SymbolMetrics> = self.symbols.values().map(|state| state.metrics.clone()).collect();
+        metrics.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        metrics
+    }
+}
+
+impl SymbolState {
+    fn new(symbol: &str, session: NaiveDate, timestamp: DateTime<Utc>) -> Self {
+        Self {
+            session,
+            first_tick_at: timestamp,
+            last_cumulative_volume: 0,
+            price_volume: Decimal::ZERO,
+            traded_volume: 0,
+            minutes: VecDeque::new(),
+            metrics: SymbolMetrics {
+                symbol: symbol.to_string(),
+                spread: None,
+                spread_bps: None,
+                vwap: None,
+                rolling_volume: 0,
+                average_rolling_volume: 0.0,
+                relative_volume: None,
+                updated_at: timestamp,
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+    use chrono_tz::Asia::Kolkata;
+
+    fn ist(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Kolkata.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_spread_and_vwap_follow_ticks() {
+        let mut metrics = LiveMetrics::default();
+        let start = ist(25, 9, 15);
+
+        // The first tick's volume traded before it, at its price
+        metrics.update("INFY", Decimal::new(100, 0), Decimal::new(9995, 2), Decimal::new(10005, 2), 100, start);
+        let latest = metrics.update("INFY", Decimal::new(110, 0), Decimal::ZERO, Decimal::ZERO, 400, start + Duration::seconds(10));
+
+        // (100 * 100 + 110 * 300) / 400
+        assert_eq!(latest.vwap, Some(Decimal::new(10750, 2)));
+        assert!(latest.spread.is_none());
+
+        let quoted = metrics.update("INFY", Decimal::new(110, 0), Decimal::new(10995, 2), Decimal::new(11005, 2), 400, start + Duration::seconds(20));
+        assert_eq!(quoted.spread, Some(Decimal::new(10, 2)));
+        assert!((quoted.spread_bps.unwrap() - 9.0909).abs() < 1e-3);
+
+        // A new session starts the VWAP over
+        let next_day = metrics.update("INFY", Decimal::new(120, 0), Decimal::ZERO, Decimal::ZERO, 50, ist(29, 9, 15));
+        assert_eq!(next_day.vwap, Some(Decimal::new(120, 0)));
+        assert!(metrics.get("TCS").is_none());
+    }
+
+    #[test]
+    fn test_rolling_volume_against_session_average() {
+        let mut metrics = LiveMetrics::default();
+        let start = ist(25, 10, 0);
+        let price = Decimal::new(100, 0);
+
+        // 100 shares a minute for 20 minutes, then a 2,000 share burst
+        let mut cumulative = 0;
+        for minute in 0..20 {
+            cumulative += 100;
+            metrics.update("INFY", price, Decimal::ZERO, Decimal::ZERO, cumulative, start + Duration::minutes(minute));
+        }
+        let steady = metrics.get("INFY").unwrap();
+        assert_eq!(steady.rolling_volume, 500);
+
+        let burst = metrics.update("INFY", price, Decimal::ZERO, Decimal::ZERO, cumulative + 2000, start + Duration::minutes(20));
+        assert_eq!(burst.rolling_volume, 2400);
+        // 4,000 shares over 20 minutes is 1,000 per five-minute window
+        assert!((burst.average_rolling_volume - 1000.0).abs() < 1e-9);
+        assert!((burst.relative_volume.unwrap() - 2.4).abs() < 1e-9);
+        assert_eq!(metrics.all().len(), 1);
+    }
+}
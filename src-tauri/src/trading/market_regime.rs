@@ -0,0 +1,177 @@
+use crate::models::trading::{SignalType, TrendFilterConfig};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use tokio::sync::RwLock;
+
+/// Default bar size used to sample index ticks (1 minute)
+const DEFAULT_BAR_SECONDS: i64 = 60;
+
+/// Maximum number of bars retained per index
+const MAX_BARS: usize = 500;
+
+/// Market regime of an index relative to its moving average
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum MarketRegime {
+    Bullish,
+    Bearish,
+    Unknown,
+}
+
+/// Tracks index prices from ticks and derives the market regime
+pub struct MarketRegimeTracker {
+    /// Bar size in seconds used to sample ticks into closes
+    bar_seconds: i64,
+
+    /// Closing prices per index symbol as (bar start, close)
+    closes: RwLock<HashMap<String, VecDeque<(i64, Decimal)>>>,
+}
+
+impl Default for MarketRegimeTracker {
+    fn default() -> Self {
+        Self::new(DEFAULT_BAR_SECONDS)
+    }
+}
+
+impl MarketRegimeTracker {
+    /// Create a new tracker sampling ticks into bars of the given size
+    pub fn new(bar_seconds: i64) -> Self {
+        Self {
+            bar_seconds: bar_seconds.max(1),
+            closes: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Record an index tick
+    pub async fn update(&self, symbol: &str, price: Decimal, timestamp: DateTime<Utc>) {
+        let bar = timestamp.timestamp() / self.bar_seconds;
+        let mut closes = self.closes.write().await;
+        let series = closes.entry(symbol.to_string()).or_insert_with(VecDeque::new);
+
+        match series.back_mut() {
+            Some((last_bar, close)) if *last_bar == bar => *close = price,
+            Some((last_bar, _)) if *last_bar > bar => {} // Ignore out-of-order ticks
+            _ => {
+                series.push_back((bar, price));
+                if series.len() > MAX_BARS {
+                    series.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Latest price seen for an index
+    pub async fn last_price(&self, symbol: &str) -> Option<Decimal> {
+        let closes = self.closes.read().await;
+        closes.get(symbol).and_then(|s| s.back()).map(|(_, close)| *close)
+    }
+
+    /// Simple moving average over the last `period` bars
+    pub async fn moving_average(&self, symbol: &str, period: usize) -> Option<Decimal> {
+        if period == 0 {
+            return None;
+        }
+
+        let closes = self.closes.read().await;
+        let series = closes.get(symbol)?;
+        if series.len() < period {
+            return None;
+        }
+
+        let sum: Decimal = series.iter().rev().take(period).map(|(_, close)| *close).sum();
+        Some(sum / Decimal::from(period))
+    }
+
+    /// Current regime of an index against its moving average
+    pub async fn regime(&self, symbol: &str, period: usize) -> MarketRegime {
+        let (price, average) = match (self.last_price(symbol).await, self.moving_average(symbol, period).await) {
+            (Some(price), Some(average)) => (price, average),
+            _ => return MarketRegime::Unknown,
+        };
+
+        if price > average {
+            MarketRegime::Bullish
+        } else if price < average {
+            MarketRegime::Bearish
+        } else {
+            MarketRegime::Unknown
+        }
+    }
+
+    /// Whether a signal is allowed under the given trend filter
+    ///
+    /// Exits are never blocked; entries are blocked until the index has
+    /// enough history to establish a regime.
+    pub async fn allows_signal(&self, filter: &TrendFilterConfig, signal_type: SignalType) -> bool {
+        if !filter.enabled {
+            return true;
+        }
+
+        match signal_type {
+            SignalType::Buy => self.regime(&filter.index_symbol, filter.ma_period).await == MarketRegime::Bullish,
+            SignalType::Sell => self.regime(&filter.index_symbol, filter.ma_period).await == MarketRegime::Bearish,
+            SignalType::Hold | SignalType::StopLoss | SignalType::TakeProfit => true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Duration;
+
+    fn filter(period: usize) -> TrendFilterConfig {
+        TrendFilterConfig::new("strategy", "NIFTY 50", period)
+    }
+
+    async fn feed(tracker: &MarketRegimeTracker, prices: &[i64]) {
+        let start = Utc::now();
+        for (i, price) in prices.iter().enumerate() {
+            tracker.update("NIFTY 50", Decimal::from(*price), start + Duration::minutes(i as i64)).await;
+        }
+    }
+
+    #[tokio::test]
+    async fn test_ticks_within_bar_update_close() {
+        let tracker = MarketRegimeTracker::default();
+        let now = Utc::now();
+
+        tracker.update("NIFTY 50", Decimal::from(100), now).await;
+        tracker.update("NIFTY 50", Decimal::from(101), now).await;
+
+        assert_eq!(tracker.last_price("NIFTY 50").await, Some(Decimal::from(101)));
+        assert_eq!(tracker.moving_average("NIFTY 50", 1).await, Some(Decimal::from(101)));
+        assert_eq!(tracker.moving_average("NIFTY 50", 2).await, None);
+    }
+
+    #[tokio::test]
+    async fn test_regime_and_signal_filtering() {
+        let tracker = MarketRegimeTracker::default();
+
+        // Not enough history yet: entries blocked, exits allowed
+        assert!(!tracker.allows_signal(&filter(3), SignalType::Buy).await);
+        assert!(tracker.allows_signal(&filter(3), SignalType::StopLoss).await);
+
+        // Rising index: above its average
+        feed(&tracker, &[100, 101, 102, 110]).await;
+        assert_eq!(tracker.regime("NIFTY 50", 3).await, MarketRegime::Bullish);
+        assert!(tracker.allows_signal(&filter(3), SignalType::Buy).await);
+        assert!(!tracker.allows_signal(&filter(3), SignalType::Sell).await);
+
+        // Disabled filters never block
+        let mut disabled = filter(3);
+        disabled.enabled = false;
+        assert!(tracker.allows_signal(&disabled, SignalType::Sell).await);
+    }
+
+    #[tokio::test]
+    async fn test_bearish_regime() {
+        let tracker = MarketRegimeTracker::default();
+        feed(&tracker, &[110, 108, 105, 100]).await;
+
+        assert_eq!(tracker.regime("NIFTY 50", 3).await, MarketRegime::Bearish);
+        assert!(tracker.allows_signal(&filter(3), SignalType::Sell).await);
+        assert!(!tracker.allows_signal(&filter(3), SignalType::Buy).await);
+    }
+}
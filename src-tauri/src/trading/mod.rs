@@ -1,8 +1,29 @@
 pub mod engine;
 pub mod risk_manager;
 pub mod strategy_manager;
+pub mod market_regime;
+pub mod pair_trading;
+pub mod indicators;
+pub mod bars;
+pub mod strategies;
+pub mod strategy_stats;
+pub mod session;
+pub mod freshness;
+pub mod pre_open;
+pub mod live_metrics;
+pub mod performance;
+pub mod analytics_filter;
 
 // Re-export for easier access
 pub use engine::TradingEngine;
 pub use risk_manager::RiskManager;
 pub use strategy_manager::StrategyManager;
+pub use market_regime::{MarketRegime, MarketRegimeTracker};
+pub use pair_trading::SpreadTracker;
+pub use bars::{BarSeries, LiveCandles};
+pub use strategies::StrategyEvaluation;
+pub use strategy_stats::StrategyStatsTracker;
+pub use freshness::{PriceFreshness, DEFAULT_MAX_PRICE_AGE_SECONDS};
+pub use pre_open::{PreOpenQuote, PreOpenSession};
+pub use live_metrics::{LiveMetrics, SymbolMetrics};
+pub use analytics_filter::{PageInfo, TradeFilter};
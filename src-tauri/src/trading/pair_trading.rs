@@ -0,0 +1,183 @@
+use crate::models::trading::{PairSide, PairSignalType, PairTradingConfig};
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+use rust_decimal::prelude::ToPrimitive;
+use std::collections::VecDeque;
+
+/// Bar size used to sample the spread (1 minute)
+const SPREAD_BAR_SECONDS: i64 = 60;
+
+/// Rolling spread and z-score for a pair of instruments
+#[derive(Debug, Clone)]
+pub struct SpreadTracker {
+    hedge_ratio: f64,
+    lookback: usize,
+    price_a: Option<Decimal>,
+    price_b: Option<Decimal>,
+    /// Spread samples as (bar, spread)
+    spreads: VecDeque<(i64, f64)>,
+}
+
+impl SpreadTracker {
+    /// Create a tracker for the given pair configuration
+    pub fn new(config: &PairTradingConfig) -> Self {
+        Self {
+            hedge_ratio: config.hedge_ratio,
+            lookback: config.lookback.max(2),
+            price_a: None,
+            price_b: None,
+            spreads: VecDeque::new(),
+        }
+    }
+
+    /// Latest prices for both legs, if known
+    pub fn prices(&self) -> Option<(Decimal, Decimal)> {
+        Some((self.price_a?, self.price_b?))
+    }
+
+    /// Update the price of leg A
+    pub fn update_a(&mut self, price: Decimal, timestamp: DateTime<Utc>) {
+        self.price_a = Some(price);
+        self.sample(timestamp);
+    }
+
+    /// Update the price of leg B
+    pub fn update_b(&mut self, price: Decimal, timestamp: DateTime<Utc>) {
+        self.price_b = Some(price);
+        self.sample(timestamp);
+    }
+
+    /// Current log spread
+    pub fn spread(&self) -> Option<f64> {
+        let (a, b) = self.prices()?;
+        let a = a.to_f64()?;
+        let b = b.to_f64()?;
+        if a <= 0.0 || b <= 0.0 {
+            return None;
+        }
+        Some(a.ln() - self.hedge_ratio * b.ln())
+    }
+
+    fn sample(&mut self, timestamp: DateTime<Utc>) {
+        let spread = match self.spread() {
+            Some(spread) => spread,
+            None => return,
+        };
+
+        let bar = timestamp.timestamp() / SPREAD_BAR_SECONDS;
+        match self.spreads.back_mut() {
+            Some((last_bar, last)) if *last_bar == bar => *last = spread,
+            Some((last_bar, _)) if *last_bar > bar => {}
+            _ => {
+                self.spreads.push_back((bar, spread));
+                if self.spreads.len() > self.lookback {
+                    self.spreads.pop_front();
+                }
+            }
+        }
+    }
+
+    /// Z-score of the current spread over the lookback window
+    pub fn z_score(&self) -> Option<f64> {
+        if self.spreads.len() < self.lookback {
+            return None;
+        }
+
+        let n = self.spreads.len() as f64;
+        let mean = self.spreads.iter().map(|(_, s)| s).sum::<f64>() / n;
+        let variance = self.spreads.iter().map(|(_, s)| (s - mean).powi(2)).sum::<f64>() / (n - 1.0);
+        let std_dev = variance.sqrt();
+
+        if std_dev <= f64::EPSILON {
+            return None;
+        }
+
+        Some((self.spread()? - mean) / std_dev)
+    }
+}
+
+/// Decide the pair action for a z-score given the currently open side
+pub fn evaluate_pair_signal(
+    config: &PairTradingConfig,
+    z_score: f64,
+    open_side: Option<PairSide>,
+) -> Option<PairSignalType> {
+    match open_side {
+        None if z_score >= config.entry_z => Some(PairSignalType::Enter(PairSide::ShortSpread)),
+        None if z_score <= -config.entry_z => Some(PairSignalType::Enter(PairSide::LongSpread)),
+        None => None,
+        Some(_) if z_score.abs() >= config.stop_z => Some(PairSignalType::StopLoss),
+        Some(_) if z_score.abs() <= config.exit_z => Some(PairSignalType::Exit),
+        Some(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::trading::PairPosition;
+    use chrono::Duration;
+
+    fn config() -> PairTradingConfig {
+        let mut config = PairTradingConfig::new("pair", "HDFCBANK", "ICICIBANK");
+        config.lookback = 5;
+        config
+    }
+
+    #[test]
+    fn test_z_score_requires_full_window() {
+        let mut tracker = SpreadTracker::new(&config());
+        let start = Utc::now();
+
+        for i in 0..4 {
+            let t = start + Duration::minutes(i);
+            tracker.update_a(Decimal::from(100 + i), t);
+            tracker.update_b(Decimal::from(100), t);
+        }
+        assert!(tracker.z_score().is_none());
+
+        let t = start + Duration::minutes(4);
+        tracker.update_a(Decimal::from(120), t);
+        tracker.update_b(Decimal::from(100), t);
+
+        // Leg A jumped relative to B, so the spread is rich
+        assert!(tracker.z_score().unwrap() > 1.0);
+    }
+
+    #[test]
+    fn test_evaluate_pair_signal() {
+        let config = config();
+
+        assert_eq!(evaluate_pair_signal(&config, 2.5, None), Some(PairSignalType::Enter(PairSide::ShortSpread)));
+        assert_eq!(evaluate_pair_signal(&config, -2.5, None), Some(PairSignalType::Enter(PairSide::LongSpread)));
+        assert_eq!(evaluate_pair_signal(&config, 1.0, None), None);
+
+        assert_eq!(evaluate_pair_signal(&config, 0.2, Some(PairSide::LongSpread)), Some(PairSignalType::Exit));
+        assert_eq!(evaluate_pair_signal(&config, -4.5, Some(PairSide::LongSpread)), Some(PairSignalType::StopLoss));
+        assert_eq!(evaluate_pair_signal(&config, 1.5, Some(PairSide::ShortSpread)), None);
+    }
+
+    #[test]
+    fn test_combined_pnl() {
+        let position = PairPosition {
+            id: "p1".to_string(),
+            strategy_id: "pair".to_string(),
+            symbol_a: "HDFCBANK".to_string(),
+            symbol_b: "ICICIBANK".to_string(),
+            exchange: "NSE".to_string(),
+            side: PairSide::LongSpread,
+            quantity_a: 10,
+            quantity_b: 15,
+            entry_price_a: Decimal::from(1500),
+            entry_price_b: Decimal::from(1000),
+            entry_z: -2.1,
+            opened_at: Utc::now(),
+        };
+
+        // A up 10 (+100), B up 5 on a short leg (-75)
+        assert_eq!(position.combined_pnl(Decimal::from(1510), Decimal::from(1005)), Decimal::from(25));
+
+        let short = PairPosition { side: PairSide::ShortSpread, ..position };
+        assert_eq!(short.combined_pnl(Decimal::from(1510), Decimal::from(1005)), Decimal::from(-25));
+    }
+}
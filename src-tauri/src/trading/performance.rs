@@ -0,0 +1,390 @@
+use crate::error::Result;
+use crate::models::backtesting::EquityPoint;
+use crate::trading::analytics_filter::TradeFilter;
+use crate::trading::session::{is_trading_day, session_bounds};
+use chrono::NaiveDate;
+use rust_decimal::{prelude::FromPrimitive, Decimal};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+
+/// Trading days a year, used to annualize daily figures
+pub const TRADING_DAYS_PER_YEAR: f64 = 252.0;
+
+/// Largest peak-to-trough fall of an equity curve
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct Drawdown {
+    pub max_drawdown: f64,
+    /// Largest fall as a percentage of the peak it fell from
+    pub max_drawdown_percent: f64,
+}
+
+/// Risk-adjusted return ratios of a daily P&L series, all annualized
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+pub struct RiskRatios {
+    pub sharpe_ratio: f64,
+    pub sortino_ratio: f64,
+    pub calmar_ratio: f64,
+}
+
+/// P&L of every trading day from `from` to `to` inclusive, zero on days without trades
+pub fn daily_pnl_series(pnl_by_day: &HashMap<NaiveDate, f64>, from: NaiveDate, to: NaiveDate) -> Vec<f64> {
+    from.iter_days()
+        .take_while(|day| *day <= to)
+        .filter(|day| is_trading_day(*day) || pnl_by_day.contains_key(day))
+        .map(|day| pnl_by_day.get(&day).copied().unwrap_or(0.0))
+        .collect()
+}
+
+/// Net P&L of a user's executed trades passing a filter, on each IST day
+pub async fn load_daily_pnl(pool: &SqlitePool, user_id: &str, filter: &TradeFilter) -> Result<HashMap<NaiveDate, f64>> {
+    let (conditions, binds) = filter.sql_conditions("");
+    let sql = format!(
+        "SELECT date(executed_at, '+330 minutes') as day,
+                SUM(price * quantity * CASE WHEN trade_type = 'Sell' THEN 1 ELSE -1 END - charges) as pnl
+         FROM trades
+         WHERE user_id = ?
+         AND status = 'Executed'{}
+         GROUP BY day",
+        conditions
+    );
+    let mut query = sqlx::query(&sql).bind(user_id);
+    for value in &binds {
+        query = query.bind(value);
+    }
+    let rows = query.fetch_all(pool).await?;
+
+    Ok(rows.iter()
+        .filter_map(|row| {
+            let day: Option<String> = row.get("day");
+            let day = NaiveDate::parse_from_str(&day?, "%Y-%m-%d").ok()?;
+            Some((day, row.get::<Option<f64>, _>("pnl").unwrap_or(0.0)))
+        })
+        .collect())
+}
+
+/// Strategy and net P&L of a user's executed trades passing a filter, oldest first
+pub async fn load_trade_pnl(pool: &SqlitePool, user_id: &str, filter: &TradeFilter) -> Result<Vec<(String, f64)>> {
+    let (conditions, binds) = filter.sql_conditions("");
+    let sql = format!(
+        "SELECT strategy_id,
+                price * quantity * CASE WHEN trade_type = 'Sell' THEN 1 ELSE -1 END - charges as pnl
+         FROM trades
+         WHERE user_id = ?
+         AND status = 'Executed'{}
+         ORDER BY executed_at ASC",
+        conditions
+    );
+    let mut query = sqlx::query(&sql).bind(user_id);
+    for value in &binds {
+        query = query.bind(value);
+    }
+    let rows = query.fetch_all(pool).await?;
+
+    Ok(rows.iter()
+        .map(|row| (row.get("strategy_id"), row.get::<Option<f64>, _>("pnl").unwrap_or(0.0)))
+        .collect())
+}
+
+/// Drawdown of the equity built by adding a P&L sequence to `initial_capital`
+///
+/// The percentage is only taken while the peak is positive, so with no capital it
+/// measures falls from the highest cumulative profit.
+pub fn max_drawdown(initial_capital: f64, pnls: &[f64]) -> Drawdown {
+    let mut equity = initial_capital;
+    let mut peak = initial_capital;
+    let mut result = Drawdown::default();
+
+    for pnl in pnls {
+        equity += pnl;
+        peak = peak.max(equity);
+        result.max_drawdown = result.max_drawdown.max(peak - equity);
+        if peak > 0.0 {
+            result.max_drawdown_percent = result.max_drawdown_percent.max((peak - equity) / peak * 100.0);
+        }
+    }
+    result
+}
+
+/// Drawdown of each strategy's own equity curve, from trades in execution order
+pub fn drawdowns_by_strategy(trades: &[(String, f64)], initial_capital: f64) -> HashMap<String, Drawdown> {
+    let mut pnls_by_strategy: HashMap<&str, Vec<f64>> = HashMap::new();
+    for (strategy_id, pnl) in trades {
+        pnls_by_strategy.entry(strategy_id.as_str()).or_default().push(*pnl);
+    }
+
+    pnls_by_strategy.into_iter()
+        .map(|(strategy_id, pnls)| (strategy_id.to_string(), max_drawdown(initial_capital, &pnls)))
+        .collect()
+}
+
+/// Annualized Sharpe ratio of a daily P&L series
+///
+/// `risk_free_rate` is annual and is charged on `capital` evenly over `periods_per_year`;
+/// with no capital only the P&L's own mean and volatility count. Fewer than two days or
+/// a series with no volatility gives zero.
+pub fn sharpe_ratio(daily_pnl: &[f64], capital: f64, risk_free_rate: f64, periods_per_year: f64) -> f64 {
+    if daily_pnl.len() < 2 || periods_per_year <= 0.0 {
+        return 0.0;
+    }
+
+    let risk_free_per_day = capital.max(0.0) * risk_free_rate / periods_per_year;
+    let n = daily_pnl.len() as f64;
+    let mean = daily_pnl.iter().sum::<f64>() / n;
+    // Sample standard deviation, as the series is a sample of the strategy's days
+    let variance = daily_pnl.iter()
+        .map(|pnl| (pnl - mean).powi(2))
+        .sum::<f64>() / (n - 1.0);
+    let std_dev = variance.sqrt();
+
+    if std_dev <= f64::EPSILON {
+        return 0.0;
+    }
+
+    (mean - risk_free_per_day) / std_dev * periods_per_year.sqrt()
+}
+
+/// Annualized Sortino ratio of a daily P&L series
+///
+/// Like [`sharpe_ratio`], but only days below the risk-free return count towards the
+/// deviation. Zero when there are fewer than two days or none fell short.
+pub fn sortino_ratio(daily_pnl: &[f64], capital: f64, risk_free_rate: f64, periods_per_year: f64) -> f64 {
+    if daily_pnl.len() < 2 || periods_per_year <= 0.0 {
+        return 0.0;
+    }
+
+    let risk_free_per_day = capital.max(0.0) * risk_free_rate / periods_per_year;
+    let n = daily_pnl.len() as f64;
+    let mean_excess = daily_pnl.iter().map(|pnl| pnl - risk_free_per_day).sum::<f64>() / n;
+    let downside = (daily_pnl.iter()
+        .map(|pnl| (pnl - risk_free_per_day).min(0.0).powi(2))
+        .sum::<f64>() / n)
+        .sqrt();
+
+    if downside <= f64::EPSILON {
+        return 0.0;
+    }
+
+    mean_excess / downside * periods_per_year.sqrt()
+}
+
+/// Calmar ratio of a daily P&L series: annualized P&L over the worst drawdown of daily equity
+pub fn calmar_ratio(daily_pnl: &[f64], periods_per_year: f64) -> f64 {
+    if daily_pnl.is_empty() {
+        return 0.0;
+    }
+
+    let drawdown = max_drawdown(0.0, daily_pnl).max_drawdown;
+    if drawdown <= f64::EPSILON {
+        return 0.0;
+    }
+
+    let annual_pnl = daily_pnl.iter().sum::<f64>() / daily_pnl.len() as f64 * periods_per_year;
+    annual_pnl / drawdown
+}
+
+/// Sharpe, Sortino and Calmar ratios of a daily P&L series
+pub fn risk_ratios(daily_pnl: &[f64], capital: f64, risk_free_rate: f64, periods_per_year: f64) -> RiskRatios {
+    RiskRatios {
+        sharpe_ratio: sharpe_ratio(daily_pnl, capital, risk_free_rate, periods_per_year),
+        sortino_ratio: sortino_ratio(daily_pnl, capital, risk_free_rate, periods_per_year),
+        calmar_ratio: calmar_ratio(daily_pnl, periods_per_year),
+    }
+}
+
+/// Pearson correlation of two equally long series; unset when either is flat or too short
+pub fn correlation(a: &[f64], b: &[f64]) -> Option<f64> {
+    if a.len() != b.len() || a.len() < 2 {
+        return None;
+    }
+
+    let n = a.len() as f64;
+    let mean_a = a.iter().sum::<f64>() / n;
+    let mean_b = b.iter().sum::<f64>() / n;
+    let (mut covariance, mut variance_a, mut variance_b) = (0.0, 0.0, 0.0);
+    for (x, y) in a.iter().zip(b) {
+        covariance += (x - mean_a) * (y - mean_b);
+        variance_a += (x - mean_a).powi(2);
+        variance_b += (y - mean_b).powi(2);
+    }
+
+    if variance_a <= f64::EPSILON || variance_b <= f64::EPSILON {
+        return None;
+    }
+    Some((covariance / (variance_a * variance_b).sqrt()).clamp(-1.0, 1.0))
+}
+
+/// Equity at the session close of each day, starting from `starting_capital` at the close of `start`
+///
+/// `daily_pnl` is expected oldest first, with every day after `start`.
+pub fn daily_equity_curve(starting_capital: f64, start: NaiveDate, daily_pnl: &[(NaiveDate, f64)]) -> Vec<EquityPoint> {
+    let point = |day: NaiveDate, equity: f64| {
+        let (_, close) = session_bounds(day)?;
+        Some(EquityPoint::new(close, Decimal::from_f64(equity).unwrap_or_default().round_dp(2)))
+    };
+
+    let mut equity = starting_capital;
+    let mut curve: Vec<EquityPoint> = point(start, equity).into_iter().collect();
+    for (day, pnl) in daily_pnl {
+        equity += pnl;
+        curve.extend(point(*day, equity));
+    }
+    curve
+}
+
+/// Metrics of the trading days in a rolling window, as of its last day
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct RollingPoint {
+    pub date: NaiveDate,
+    pub pnl: f64,
+    pub trades: usize,
+    pub win_rate: f64,
+    pub sharpe_ratio: f64,
+}
+
+/// Metrics over each run of `window` consecutive days, from the first full window on
+///
+/// `days` holds each trading day, oldest first, with the P&L of every trade closed on it.
+pub fn rolling_metrics(
+    days: &[(NaiveDate, Vec<f64>)],
+    window: usize,
+    capital: f64,
+    risk_free_rate: f64,
+    periods_per_year: f64,
+) -> Vec<RollingPoint> {
+    if window == 0 {
+        return Vec::new();
+    }
+
+    let daily_pnl: Vec<f64> = days.iter().map(|(_, pnls)| pnls.iter().sum()).collect();
+    days.windows(window)
+        .zip(daily_pnl.windows(window))
+        .map(|(days, daily_pnl)| {
+            let trades: usize = days.iter().map(|(_, pnls)| pnls.len()).sum();
+            let wins = days.iter().flat_map(|(_, pnls)| pnls).filter(|pnl| **pnl > 0.0).count();
+            RollingPoint {
+                date: days[window - 1].0,
+                pnl: daily_pnl.iter().sum(),
+                trades,
+                win_rate: if trades > 0 { wins as f64 / trades as f64 } else { 0.0 },
+                sharpe_ratio: sharpe_ratio(daily_pnl, capital, risk_free_rate, periods_per_year),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sharpe_ratio() {
+        let pnl = [100.0, -50.0, 200.0, 0.0, 50.0];
+        // Mean 60, sample standard deviation 96.18
+        let sharpe = sharpe_ratio(&pnl, 0.0, 0.0, TRADING_DAYS_PER_YEAR);
+        assert!((sharpe - 60.0 / 96.1769 * TRADING_DAYS_PER_YEAR.sqrt()).abs() < 1e-3);
+
+        // 7% a year on 2.52 lakh is 70 a day, leaving a mean excess of -10
+        let sharpe = sharpe_ratio(&pnl, 252_000.0, 0.07, TRADING_DAYS_PER_YEAR);
+        assert!((sharpe + 10.0 / 96.1769 * TRADING_DAYS_PER_YEAR.sqrt()).abs() < 1e-3);
+
+        assert_eq!(sharpe_ratio(&[100.0], 0.0, 0.0, TRADING_DAYS_PER_YEAR), 0.0);
+        assert_eq!(sharpe_ratio(&[100.0, 100.0, 100.0], 0.0, 0.0, TRADING_DAYS_PER_YEAR), 0.0);
+    }
+
+    #[test]
+    fn test_sortino_and_calmar_ratios() {
+        let pnl = [100.0, -50.0, 200.0, 0.0, 50.0];
+        // Downside deviation is sqrt(50^2 / 5) and the worst drawdown is the 50 lost on day two
+        let ratios = risk_ratios(&pnl, 0.0, 0.0, TRADING_DAYS_PER_YEAR);
+        assert!((ratios.sortino_ratio - 60.0 / 500.0_f64.sqrt() * TRADING_DAYS_PER_YEAR.sqrt()).abs() < 1e-9);
+        assert!((ratios.calmar_ratio - 60.0 * TRADING_DAYS_PER_YEAR / 50.0).abs() < 1e-9);
+        assert_eq!(ratios.sharpe_ratio, sharpe_ratio(&pnl, 0.0, 0.0, TRADING_DAYS_PER_YEAR));
+
+        // Nothing lost, so neither ratio has a denominator
+        let ratios = risk_ratios(&[100.0, 0.0, 50.0], 0.0, 0.0, TRADING_DAYS_PER_YEAR);
+        assert_eq!(ratios.sortino_ratio, 0.0);
+        assert_eq!(ratios.calmar_ratio, 0.0);
+    }
+
+    #[test]
+    fn test_rolling_metrics() {
+        let day = |d: u32| NaiveDate::from_ymd_opt(2024, 8, d).unwrap();
+        let days = vec![
+            (day(5), vec![100.0, -20.0]),
+            (day(6), vec![]),
+            (day(7), vec![-50.0]),
+            (day(8), vec![30.0, 30.0]),
+        ];
+
+        let points = rolling_metrics(&days, 3, 0.0, 0.0, TRADING_DAYS_PER_YEAR);
+        assert_eq!(points.len(), 2);
+        assert_eq!((points[0].date, points[0].pnl, points[0].trades, points[0].win_rate), (day(7), 30.0, 3, 1.0 / 3.0));
+        assert_eq!((points[1].date, points[1].pnl, points[1].trades, points[1].win_rate), (day(8), 10.0, 3, 2.0 / 3.0));
+        assert_eq!(points[1].sharpe_ratio, sharpe_ratio(&[0.0, -50.0, 60.0], 0.0, 0.0, TRADING_DAYS_PER_YEAR));
+
+        // Too short for a full window
+        assert!(rolling_metrics(&days, 5, 0.0, 0.0, TRADING_DAYS_PER_YEAR).is_empty());
+    }
+
+    #[test]
+    fn test_max_drawdown() {
+        // Equity 10000 -> 11000 -> 9900 -> 10400 -> 9350 -> 12000
+        let pnls = [1000.0, -1100.0, 500.0, -1050.0, 2650.0];
+        let drawdown = max_drawdown(10_000.0, &pnls);
+        assert_eq!(drawdown.max_drawdown, 1650.0);
+        assert!((drawdown.max_drawdown_percent - 15.0).abs() < 1e-9);
+
+        let trades = vec![
+            ("a".to_string(), 100.0),
+            ("b".to_string(), -40.0),
+            ("a".to_string(), -60.0),
+            ("b".to_string(), -10.0),
+        ];
+        let by_strategy = drawdowns_by_strategy(&trades, 0.0);
+        assert_eq!(by_strategy["a"].max_drawdown, 60.0);
+        assert_eq!(by_strategy["a"].max_drawdown_percent, 60.0);
+        // Never above zero, so there is no peak to take a percentage of
+        assert_eq!(by_strategy["b"], Drawdown { max_drawdown: 50.0, max_drawdown_percent: 0.0 });
+    }
+
+    #[test]
+    fn test_correlation() {
+        let a = [100.0, -50.0, 200.0, 0.0];
+        let doubled: Vec<f64> = a.iter().map(|x| x * 2.0).collect();
+        let opposite: Vec<f64> = a.iter().map(|x| -x).collect();
+
+        assert!((correlation(&a, &doubled).unwrap() - 1.0).abs() < 1e-12);
+        assert!((correlation(&a, &opposite).unwrap() + 1.0).abs() < 1e-12);
+        assert!(correlation(&a, &[10.0, 10.0, 10.0, 10.0]).is_none());
+        assert!(correlation(&a, &[1.0, 2.0]).is_none());
+    }
+
+    #[test]
+    fn test_daily_pnl_series_fills_quiet_days() {
+        // Monday 2024-01-08 to Monday 2024-01-15, trades on the Tuesday and the Saturday
+        let from = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        let to = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let mut pnl_by_day = HashMap::new();
+        pnl_by_day.insert(NaiveDate::from_ymd_opt(2024, 1, 9).unwrap(), 500.0);
+        pnl_by_day.insert(NaiveDate::from_ymd_opt(2024, 1, 13).unwrap(), -200.0);
+
+        let series = daily_pnl_series(&pnl_by_day, from, to);
+        assert_eq!(series, vec![0.0, 500.0, 0.0, 0.0, 0.0, -200.0, 0.0]);
+    }
+
+    #[test]
+    fn test_daily_equity_curve_at_session_close() {
+        let start = NaiveDate::from_ymd_opt(2024, 1, 8).unwrap();
+        let daily_pnl = [
+            (NaiveDate::from_ymd_opt(2024, 1, 9).unwrap(), 500.0),
+            (NaiveDate::from_ymd_opt(2024, 1, 11).unwrap(), -200.25),
+        ];
+
+        let curve = daily_equity_curve(100_000.0, start, &daily_pnl);
+        let equity: Vec<Decimal> = curve.iter().map(|point| point.equity).collect();
+        assert_eq!(equity, vec![Decimal::from(100_000), Decimal::from(100_500), Decimal::new(10_029_975, 2)]);
+        // 15:30 IST
+        assert_eq!(curve[1].timestamp.to_rfc3339(), "2024-01-09T10:00:00+00:00");
+    }
+}
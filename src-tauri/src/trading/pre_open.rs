@@ -0,0 +1,155 @@
+use crate::error::{HedgeXError, Result};
+use crate::trading::session::{is_in_session, is_pre_open, minutes_since_open, session_date};
+use chrono::{DateTime, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Minutes after the open before strategies may trade, unless configured otherwise
+pub const DEFAULT_RELEASE_DELAY_MINUTES: i64 = 0;
+
+/// What the pre-open call auction showed for one symbol
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PreOpenQuote {
+    pub symbol: String,
+    pub previous_close: Option<Decimal>,
+    /// Latest indicative price of the auction
+    pub expected_open: Decimal,
+    /// Expected gap from the previous close, in percent
+    pub gap_percent: Option<Decimal>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The 09:00–09:15 pre-open window and the hold on strategies around it
+///
+/// Pre-open ticks are indicative auction prices, so they are collected but never
+/// traded on. Strategies stay held after the open until the release delay passes;
+/// outside the session nothing is held.
+#[derive(Debug, Clone)]
+pub struct PreOpenSession {
+    release_delay_minutes: i64,
+    session: Option<NaiveDate>,
+    quotes: HashMap<String, PreOpenQuote>,
+}
+
+impl Default for PreOpenSession {
+    fn default() -> Self {
+        Self {
+            release_delay_minutes: DEFAULT_RELEASE_DELAY_MINUTES,
+            session: None,
+            quotes: HashMap::new(),
+        }
+    }
+}
+
+impl PreOpenSession {
+    /// Change how long after the open strategies are held; up to an hour
+    pub fn set_release_delay_minutes(&mut self, minutes: i64) -> Result<()> {
+        if !(0..=60).contains(&minutes) {
+            return Err(HedgeXError::ValidationError(
+                "Strategy release delay must be between 0 and 60 minutes".to_string()
+            ));
+        }
+
+        self.release_delay_minutes = minutes;
+        Ok(())
+    }
+
+    pub fn release_delay_minutes(&self) -> i64 {
+        self.release_delay_minutes
+    }
+
+    /// Note a tick, keeping its price as the expected open when it falls in the pre-open window
+    ///
+    /// Returns whether it did; such a tick must not be traded on. Quotes of an
+    /// earlier day are dropped when the next pre-open starts.
+    pub fn observe(&mut self, symbol: &str, ltp: Decimal, previous_close: Option<Decimal>, at: DateTime<Utc>) -> bool {
+        if !is_pre_open(at) {
+            return false;
+        }
+
+        let date = session_date(at);
+        if self.session != Some(date) {
+            self.session = Some(date);
+            self.quotes.clear();
+        }
+
+        let previous_close = previous_close
+            .filter(|close| *close > Decimal::ZERO)
+            .or_else(|| self.quotes.get(symbol).and_then(|quote| quote.previous_close));
+        self.quotes.insert(symbol.to_string(), PreOpenQuote {
+            symbol: symbol.to_string(),
+            previous_close,
+            expected_open: ltp,
+            gap_percent: previous_close.map(|close| (ltp - close) * Decimal::new(100, 0) / close),
+            updated_at: at,
+        });
+        true
+    }
+
+    /// Whether strategies may not open positions at a time
+    pub fn holds_strategies(&self, at: DateTime<Utc>) -> bool {
+        is_pre_open(at) || (is_in_session(at) && minutes_since_open(at) < self.release_delay_minutes)
+    }
+
+    pub fn quote(&self, symbol: &str) -> Option<&PreOpenQuote> {
+        self.quotes.get(symbol)
+    }
+
+    /// Pre-open quotes of the latest session, in symbol order
+    pub fn quotes(&self) -> Vec<PreOpenQuote> {
+        let mut quotes: Vec<PreOpenQuote> = self.quotes.values().cloned().collect();
+        quotes.sort_by(|a, b| a.symbol.cmp(&b.symbol));
+        quotes
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use chrono_tz::Asia::Kolkata;
+
+    fn ist(day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Kolkata.with_ymd_and_hms(2024, 1, day, hour, minute, 0).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_pre_open_prices_seed_the_expected_open() {
+        let mut pre_open = PreOpenSession::default();
+
+        assert!(!pre_open.observe("INFY", Decimal::new(1500, 0), Some(Decimal::new(1500, 0)), ist(25, 8, 55)));
+        assert!(pre_open.observe("INFY", Decimal::new(1510, 0), Some(Decimal::new(1500, 0)), ist(25, 9, 2)));
+        assert!(pre_open.observe("INFY", Decimal::new(1530, 0), None, ist(25, 9, 7)));
+
+        let quote = pre_open.quote("INFY").unwrap();
+        assert_eq!(quote.expected_open, Decimal::new(1530, 0));
+        assert_eq!(quote.previous_close, Some(Decimal::new(1500, 0)));
+        assert_eq!(quote.gap_percent, Some(Decimal::new(2, 0)));
+
+        // Regular session ticks leave the auction's quote alone
+        assert!(!pre_open.observe("INFY", Decimal::new(1520, 0), None, ist(25, 9, 16)));
+        assert_eq!(pre_open.quote("INFY").unwrap().expected_open, Decimal::new(1530, 0));
+
+        // The next day's pre-open starts afresh
+        pre_open.observe("TCS", Decimal::new(3800, 0), None, ist(29, 9, 1));
+        assert_eq!(pre_open.quotes().len(), 1);
+        assert!(pre_open.quote("TCS").unwrap().gap_percent.is_none());
+    }
+
+    #[test]
+    fn test_strategies_are_released_after_the_delay() {
+        let mut pre_open = PreOpenSession::default();
+        assert!(pre_open.holds_strategies(ist(25, 9, 10)));
+        assert!(!pre_open.holds_strategies(ist(25, 9, 15)));
+        assert!(!pre_open.holds_strategies(ist(25, 8, 30)));
+
+        pre_open.set_release_delay_minutes(5).unwrap();
+        assert!(pre_open.holds_strategies(ist(25, 9, 19)));
+        assert!(!pre_open.holds_strategies(ist(25, 9, 20)));
+
+        assert!(pre_open.set_release_delay_minutes(-1).is_err());
+        assert!(pre_open.set_release_delay_minutes(90).is_err());
+        assert_eq!(pre_open.release_delay_minutes(), 5);
+    }
+}
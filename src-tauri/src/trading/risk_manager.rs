@@ -2,6 +2,7 @@ use crate::error::{HedgeXError, Result};
 use crate::models::trading::{
     Position, Trade, TradeType, OrderRequest, RiskLimits, PerformanceMetrics
 };
+use crate::models::kite::KiteMarginResponse;
 use crate::services::enhanced_database_service::EnhancedDatabaseService;
 use rust_decimal::Decimal;
 use rust_decimal::prelude::ToPrimitive;
@@ -12,6 +13,9 @@ use tracing::{debug, error, info, warn};
 use chrono::{DateTime, Utc};
 use sqlx::Row;
 
+/// Seconds after which known margins are too old to check orders against
+const MARGIN_MAX_AGE_SECONDS: i64 = 60;
+
 /// Risk manager for controlling trading risk
 pub struct RiskManager {
     /// Database service for storing risk data
@@ -32,6 +36,9 @@ pub struct RiskManager {
     /// Emergency stop flag
     emergency_stop: Arc<RwLock<bool>>,
     
+    /// Equity margin available to trade and when it was fetched from the broker
+    available_margin: Arc<RwLock<Option<(Decimal, DateTime<Utc>)>>>,
+    
     /// User ID
     user_id: String,
 }
@@ -49,6 +56,7 @@ impl RiskManager {
             daily_trade_count: Arc::new(RwLock::new(HashMap::new())),
             daily_pnl: Arc::new(RwLock::new(HashMap::new())),
             emergency_stop: Arc::new(RwLock::new(false)),
+            available_margin: Arc::new(RwLock::new(None)),
             user_id: user_id.to_string(),
         };
         
@@ -66,7 +74,8 @@ impl RiskManager {
             SELECT symbol, exchange, 
                    SUM(CASE WHEN trade_type = 'Buy' THEN quantity ELSE -quantity END) as net_quantity,
                    AVG(price) as avg_price,
-                   MIN(executed_at) as entry_time
+                   MIN(executed_at) as entry_time,
+                   MIN(strategy_id) as strategy_id
             FROM trades 
             WHERE user_id = ? AND status = 'Executed'
             GROUP BY symbol, exchange
@@ -98,6 +107,7 @@ impl RiskManager {
                 trade_type,
             );
             position.entry_time = entry_time;
+            position.strategy_id = row.get("strategy_id");
             
             positions.insert(format!("{}:{}", exchange, symbol), position);
         }
@@ -166,6 +176,15 @@ impl RiskManager {
         Ok(())
     }
     
+    /// Load positions and daily metrics again, as after a backup restore replaced the trades
+    pub async fn reload(&self) -> Result<()> {
+        self.positions.write().await.clear();
+        self.daily_trade_count.write().await.clear();
+        self.daily_pnl.write().await.clear();
+        self.load_positions().await?;
+        self.load_daily_metrics().await
+    }
+    
     /// Check if order passes risk validation
     pub async fn validate_order(&self, order: &OrderRequest) -> Result<bool> {
         // Check emergency stop
@@ -204,6 +223,16 @@ impl RiskManager {
             return Ok(false);
         }
         
+        // Check the broker has margin for new positions; exits free margin rather than use it
+        if order.exit_reason.is_none() {
+            if let Some(available) = self.get_available_margin().await {
+                if order_value > available {
+                    warn!("Order rejected: Insufficient margin ({} > {} available)", order_value, available);
+                    return Ok(false);
+                }
+            }
+        }
+        
         // Check symbol-specific trade limit
         let symbol_trades = self.get_symbol_trade_count(&order.symbol).await?;
         if symbol_trades >= risk_limits.max_trades_per_symbol {
@@ -223,6 +252,43 @@ impl RiskManager {
         Ok(true)
     }
     
+    /// Record margins fetched from the broker for the pre-order margin check
+    pub async fn update_margins(&self, margins: &KiteMarginResponse) {
+        let available = Decimal::try_from(margins.equity.net).unwrap_or(Decimal::ZERO);
+        *self.available_margin.write().await = Some((available, Utc::now()));
+    }
+    
+    /// Equity margin available to trade, if fetched within the last minute
+    pub async fn get_available_margin(&self) -> Option<Decimal> {
+        let margin = self.available_margin.read().await;
+        margin.and_then(|(available, updated_at)| {
+            ((Utc::now() - updated_at).num_seconds() <= MARGIN_MAX_AGE_SECONDS).then_some(available)
+        })
+    }
+    
+    /// Capital to size new positions from: the available margin when known, else the position size limit
+    pub async fn sizing_capital(&self) -> Decimal {
+        match self.get_available_margin().await {
+            Some(available) => available,
+            None => self.risk_limits.read().await.max_position_size,
+        }
+    }
+    
+    /// Largest quantity up to the one given whose order value passes the position size and margin checks
+    pub async fn cap_quantity(&self, quantity: i32, price: Decimal) -> i32 {
+        if price <= Decimal::ZERO {
+            return 0;
+        }
+        
+        let mut max_value = self.risk_limits.read().await.max_position_size;
+        if let Some(available) = self.get_available_margin().await {
+            max_value = max_value.min(available);
+        }
+        
+        let max_quantity = (max_value / price).floor().to_i32().unwrap_or(0);
+        quantity.min(max_quantity).max(0)
+    }
+    
     /// Get trade count for a specific symbol today
     async fn get_symbol_trade_count(&self, symbol: &str) -> Result<i32> {
         let today = Utc::now().date_naive();
@@ -306,13 +372,14 @@ impl RiskManager {
             },
             None => {
                 // Create new position
-                let position = Position::new(
+                let mut position = Position::new(
                     &trade.symbol,
                     &trade.exchange,
                     trade.quantity,
                     trade.price,
                     trade.trade_type,
                 );
+                position.strategy_id = Some(trade.strategy_id.clone());
                 positions.insert(position_key, position);
             }
         }
@@ -325,6 +392,14 @@ impl RiskManager {
         Ok(())
     }
     
+    /// The open position a trade reduces or closes, if it trades against one
+    pub async fn position_reduced_by(&self, trade: &Trade) -> Option<Position> {
+        let positions = self.positions.read().await;
+        positions.get(&format!("{}:{}", trade.exchange, trade.symbol))
+            .filter(|position| position.trade_type != trade.trade_type)
+            .cloned()
+    }
+    
     /// Update daily counters after trade
     async fn update_daily_counters(&self, trade: &Trade) -> Result<()> {
         // Update trade count
@@ -612,12 +687,61 @@ mod tests {
             order_type: crate::models::trading::OrderType::Market,
             strategy_id: "test_strategy".to_string(),
             user_id: "test_user".to_string(),
+            exit_reason: None,
         };
         
         let is_valid = risk_manager.validate_order(&order).await.unwrap();
         assert!(is_valid);
     }
     
+    #[tokio::test]
+    async fn test_margin_check() {
+        use crate::models::kite::{KiteAvailableMargin, KiteMargin, KiteUsedMargin};
+        
+        let (db_service, _) = setup_test_db().await;
+        let risk_manager = RiskManager::new(db_service, "test_user")
+            .await
+            .unwrap();
+        
+        let margin = |net: f64| KiteMargin {
+            enabled: true,
+            net,
+            available: KiteAvailableMargin { adhoc_margin: 0.0, cash: net, collateral: 0.0, intraday_payin: 0.0 },
+            used: KiteUsedMargin {
+                debits: 0.0, exposure: 0.0, m2m_realised: 0.0, m2m_unrealised: 0.0,
+                option_premium: 0.0, payout: 0.0, span: 0.0, holding_sales: 0.0, turnover: 0.0,
+            },
+        };
+        risk_manager.update_margins(&KiteMarginResponse { equity: margin(10000.0), commodity: margin(0.0) }).await;
+        assert_eq!(risk_manager.get_available_margin().await, Some(Decimal::from(10000)));
+        
+        let mut order = OrderRequest {
+            symbol: "INFY".to_string(),
+            exchange: "NSE".to_string(),
+            trade_type: TradeType::Buy,
+            quantity: 10,
+            price: Some(Decimal::from(1500)),
+            order_type: crate::models::trading::OrderType::Limit,
+            strategy_id: "test_strategy".to_string(),
+            user_id: "test_user".to_string(),
+            exit_reason: None,
+        };
+        assert!(!risk_manager.validate_order(&order).await.unwrap());
+        
+        // Exits are never held back by margin
+        order.exit_reason = Some("stop_loss".to_string());
+        assert!(risk_manager.validate_order(&order).await.unwrap());
+        
+        order.exit_reason = None;
+        order.quantity = 6;
+        assert!(risk_manager.validate_order(&order).await.unwrap());
+        
+        // Sizing stays within the margin
+        assert_eq!(risk_manager.sizing_capital().await, Decimal::from(10000));
+        assert_eq!(risk_manager.cap_quantity(10, Decimal::from(1500)).await, 6);
+        assert_eq!(risk_manager.cap_quantity(4, Decimal::from(1500)).await, 4);
+    }
+    
     #[tokio::test]
     async fn test_emergency_stop() {
         let (db_service, _) = setup_test_db().await;
@@ -640,6 +764,7 @@ mod tests {
             order_type: crate::models::trading::OrderType::Market,
             strategy_id: "test_strategy".to_string(),
             user_id: "test_user".to_string(),
+            exit_reason: None,
         };
         
         let is_valid = risk_manager.validate_order(&order).await.unwrap();
@@ -675,4 +800,35 @@ mod tests {
         assert_eq!(positions[0].symbol, "INFY");
         assert_eq!(positions[0].quantity, 10);
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_position_excursions() {
+        let (db_service, _) = setup_test_db().await;
+        
+        let risk_manager = RiskManager::new(db_service, "test_user")
+            .await
+            .unwrap();
+            
+        let trade = |trade_type| Trade::new(
+            "test_user",
+            "INFY",
+            "NSE",
+            trade_type,
+            10,
+            Decimal::from(1500),
+            "test_strategy",
+        );
+        
+        risk_manager.update_position(&trade(TradeType::Buy)).await.unwrap();
+        for price in [1470, 1530, 1515] {
+            risk_manager.update_market_prices("INFY", Decimal::from(price)).await.unwrap();
+        }
+        
+        // Only a trade against the position exits it
+        assert!(risk_manager.position_reduced_by(&trade(TradeType::Buy)).await.is_none());
+        let position = risk_manager.position_reduced_by(&trade(TradeType::Sell)).await.unwrap();
+        assert_eq!(position.max_adverse_excursion, Decimal::from(2));
+        assert_eq!(position.max_favorable_excursion, Decimal::from(2));
+        assert_eq!(position.pnl_percentage, Decimal::from(1));
+    }
+}
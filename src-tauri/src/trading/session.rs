@@ -0,0 +1,272 @@
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc, Weekday};
+use chrono_tz::Asia::Kolkata;
+use serde::{Deserialize, Serialize};
+
+/// Pre-open call auction start, in minutes after midnight IST (09:00)
+const PRE_OPEN_START_MINUTE: u32 = 9 * 60;
+
+/// Regular session open, in minutes after midnight IST (09:15)
+const SESSION_OPEN_MINUTE: u32 = 9 * 60 + 15;
+
+/// Regular session close, in minutes after midnight IST (15:30)
+const SESSION_CLOSE_MINUTE: u32 = 15 * 60 + 30;
+
+/// Intraday positions are squared off from this minute IST (15:20)
+const SQUARE_OFF_MINUTE: u32 = 15 * 60 + 20;
+
+/// Closing session, trading at the closing price, in minutes after midnight IST (15:40–16:00)
+const CLOSING_SESSION_START_MINUTE: u32 = 15 * 60 + 40;
+const CLOSING_SESSION_END_MINUTE: u32 = 16 * 60;
+
+/// Longest run of non-trading days searched for the next open
+const MAX_CLOSED_DAYS: usize = 15;
+
+/// NSE trading holidays that fall on weekdays, as (year, month, day)
+///
+/// Extend when the exchange publishes the next year's calendar.
+const NSE_HOLIDAYS: &[(i32, u32, u32)] = &[
+    (2024, 1, 22), (2024, 1, 26), (2024, 3, 8), (2024, 3, 25), (2024, 3, 29),
+    (2024, 4, 11), (2024, 4, 17), (2024, 5, 1), (2024, 5, 20), (2024, 6, 17),
+    (2024, 7, 17), (2024, 8, 15), (2024, 10, 2), (2024, 11, 1), (2024, 11, 15),
+    (2024, 11, 20), (2024, 12, 25),
+    (2025, 2, 26), (2025, 3, 14), (2025, 3, 31), (2025, 4, 10), (2025, 4, 14),
+    (2025, 4, 18), (2025, 5, 1), (2025, 8, 15), (2025, 8, 27), (2025, 10, 2),
+    (2025, 10, 21), (2025, 10, 22), (2025, 11, 5), (2025, 12, 25),
+    (2026, 1, 26), (2026, 3, 3), (2026, 3, 26), (2026, 3, 31), (2026, 4, 3),
+    (2026, 4, 14), (2026, 5, 1), (2026, 5, 28), (2026, 6, 26), (2026, 9, 14),
+    (2026, 10, 2), (2026, 10, 20), (2026, 11, 10), (2026, 11, 24), (2026, 12, 25),
+];
+
+/// Whether NSE holds a regular session on a date
+pub fn is_trading_day(date: NaiveDate) -> bool {
+    !matches!(date.weekday(), Weekday::Sat | Weekday::Sun)
+        && !NSE_HOLIDAYS.contains(&(date.year(), date.month(), date.day()))
+}
+
+/// IST calendar date of a timestamp
+pub fn session_date(time: DateTime<Utc>) -> NaiveDate {
+    time.with_timezone(&Kolkata).date_naive()
+}
+
+/// Whether a timestamp falls inside the regular session of a trading day
+pub fn is_in_session(time: DateTime<Utc>) -> bool {
+    let minute = minute_of_day(time);
+    is_trading_day(session_date(time)) && (SESSION_OPEN_MINUTE..SESSION_CLOSE_MINUTE).contains(&minute)
+}
+
+/// Whether a timestamp falls in the 09:00–09:15 pre-open window of a trading day
+pub fn is_pre_open(time: DateTime<Utc>) -> bool {
+    let minute = minute_of_day(time);
+    is_trading_day(session_date(time)) && (PRE_OPEN_START_MINUTE..SESSION_OPEN_MINUTE).contains(&minute)
+}
+
+/// Minutes from the regular open to a timestamp on its IST day; negative before the open
+pub fn minutes_since_open(time: DateTime<Utc>) -> i64 {
+    minute_of_day(time) as i64 - SESSION_OPEN_MINUTE as i64
+}
+
+/// Whether intraday positions should be squared off at a timestamp
+pub fn is_square_off_time(time: DateTime<Utc>) -> bool {
+    minute_of_day(time) >= SQUARE_OFF_MINUTE
+}
+
+/// Open and close of the regular session on a date, in UTC
+pub fn session_bounds(date: NaiveDate) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let at = |minute: u32| {
+        let local = date.and_hms_opt(minute / 60, minute % 60, 0)?;
+        Kolkata.from_local_datetime(&local).single().map(|time| time.with_timezone(&Utc))
+    };
+    Some((at(SESSION_OPEN_MINUTE)?, at(SESSION_CLOSE_MINUTE)?))
+}
+
+/// Part of the trading day a timestamp falls in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MarketSession {
+    /// 09:00–09:15 call auction
+    PreOpen,
+    /// 09:15–15:30 continuous trading
+    Normal,
+    /// 15:40–16:00 trading at the closing price
+    Closing,
+    Closed,
+}
+
+/// Whether the market is open and how long until that changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketStatus {
+    pub session: MarketSession,
+    /// Whether the regular session is running
+    pub is_open: bool,
+    pub is_trading_day: bool,
+    /// Next regular open, unset while the session runs
+    pub next_open: Option<DateTime<Utc>>,
+    /// Close of the running regular session
+    pub next_close: Option<DateTime<Utc>>,
+    pub seconds_to_open: Option<i64>,
+    pub seconds_to_close: Option<i64>,
+    pub as_of: DateTime<Utc>,
+}
+
+/// Session a timestamp falls in
+pub fn market_session(time: DateTime<Utc>) -> MarketSession {
+    if !is_trading_day(session_date(time)) {
+        return MarketSession::Closed;
+    }
+
+    match minute_of_day(time) {
+        minute if (PRE_OPEN_START_MINUTE..SESSION_OPEN_MINUTE).contains(&minute) => MarketSession::PreOpen,
+        minute if (SESSION_OPEN_MINUTE..SESSION_CLOSE_MINUTE).contains(&minute) => MarketSession::Normal,
+        minute if (CLOSING_SESSION_START_MINUTE..CLOSING_SESSION_END_MINUTE).contains(&minute) => MarketSession::Closing,
+        _ => MarketSession::Closed,
+    }
+}
+
+/// First regular open after a timestamp
+pub fn next_session_open(time: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    session_date(time).iter_days()
+        .take(MAX_CLOSED_DAYS)
+        .filter(|day| is_trading_day(*day))
+        .filter_map(session_bounds)
+        .map(|(open, _)| open)
+        .find(|open| *open > time)
+}
+
+/// Market status at a timestamp, from the session hours and the holiday calendar
+pub fn market_status(time: DateTime<Utc>) -> MarketStatus {
+    let session = market_session(time);
+    let is_open = session == MarketSession::Normal;
+
+    let next_close = if is_open {
+        session_bounds(session_date(time)).map(|(_, close)| close)
+    } else {
+        None
+    };
+    let next_open = if is_open { None } else { next_session_open(time) };
+
+    MarketStatus {
+        session,
+        is_open,
+        is_trading_day: is_trading_day(session_date(time)),
+        next_open,
+        next_close,
+        seconds_to_open: next_open.map(|open| (open - time).num_seconds()),
+        seconds_to_close: next_close.map(|close| (close - time).num_seconds()),
+        as_of: time,
+    }
+}
+
+/// Trading days between two timestamps, both ends included
+pub fn trading_days_between(from: DateTime<Utc>, to: DateTime<Utc>) -> usize {
+    if to < from {
+        return 0;
+    }
+
+    let last = session_date(to);
+    session_date(from).iter_days()
+        .take_while(|day| *day <= last)
+        .filter(|day| is_trading_day(*day))
+        .count()
+}
+
+/// Minutes of regular session time between two timestamps
+///
+/// Overnight gaps, weekends and holidays do not count.
+pub fn session_minutes_between(from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+    if to <= from {
+        return 0;
+    }
+
+    let last = session_date(to);
+    session_date(from).iter_days()
+        .take_while(|day| *day <= last)
+        .filter(|day| is_trading_day(*day))
+        .filter_map(session_bounds)
+        .map(|(open, close)| (close.min(to) - open.max(from)).num_minutes().max(0))
+        .sum()
+}
+
+fn minute_of_day(time: DateTime<Utc>) -> u32 {
+    let local = time.with_timezone(&Kolkata);
+    local.hour() * 60 + local.minute()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ist(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Kolkata.with_ymd_and_hms(year, month, day, hour, minute, 0).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_trading_days() {
+        // Friday, Saturday, Republic Day 2024 (a Friday) and the following Monday
+        assert!(is_trading_day(NaiveDate::from_ymd_opt(2024, 1, 25).unwrap()));
+        assert!(!is_trading_day(NaiveDate::from_ymd_opt(2024, 1, 27).unwrap()));
+        assert!(!is_trading_day(NaiveDate::from_ymd_opt(2024, 1, 26).unwrap()));
+        assert!(is_trading_day(NaiveDate::from_ymd_opt(2024, 1, 29).unwrap()));
+
+        // Thursday to the next Tuesday skips the holiday and the weekend
+        assert_eq!(trading_days_between(ist(2024, 1, 25, 10, 0), ist(2024, 1, 30, 10, 0)), 3);
+    }
+
+    #[test]
+    fn test_session_hours() {
+        assert!(!is_in_session(ist(2024, 1, 25, 9, 14)));
+        assert!(is_in_session(ist(2024, 1, 25, 9, 15)));
+        assert!(is_in_session(ist(2024, 1, 25, 15, 29)));
+        assert!(!is_in_session(ist(2024, 1, 25, 15, 30)));
+        assert!(!is_in_session(ist(2024, 1, 26, 11, 0)));
+
+        assert!(!is_pre_open(ist(2024, 1, 25, 8, 59)));
+        assert!(is_pre_open(ist(2024, 1, 25, 9, 0)));
+        assert!(is_pre_open(ist(2024, 1, 25, 9, 14)));
+        assert!(!is_pre_open(ist(2024, 1, 25, 9, 15)));
+        assert!(!is_pre_open(ist(2024, 1, 26, 9, 5)));
+        assert_eq!(minutes_since_open(ist(2024, 1, 25, 9, 5)), -10);
+        assert_eq!(minutes_since_open(ist(2024, 1, 25, 9, 20)), 5);
+
+        assert!(!is_square_off_time(ist(2024, 1, 25, 15, 19)));
+        assert!(is_square_off_time(ist(2024, 1, 25, 15, 20)));
+    }
+
+    #[test]
+    fn test_market_status() {
+        assert_eq!(market_session(ist(2024, 1, 25, 9, 5)), MarketSession::PreOpen);
+        assert_eq!(market_session(ist(2024, 1, 25, 15, 35)), MarketSession::Closed);
+        assert_eq!(market_session(ist(2024, 1, 25, 15, 45)), MarketSession::Closing);
+
+        let status = market_status(ist(2024, 1, 25, 15, 0));
+        assert!(status.is_open);
+        assert_eq!(status.session, MarketSession::Normal);
+        assert_eq!(status.seconds_to_close, Some(30 * 60));
+        assert_eq!(status.next_open, None);
+
+        // Thursday evening, the next open is past Republic Day and the weekend
+        let status = market_status(ist(2024, 1, 25, 18, 0));
+        assert!(!status.is_open);
+        assert_eq!(status.next_open, Some(ist(2024, 1, 29, 9, 15)));
+        assert_eq!(status.seconds_to_close, None);
+
+        let status = market_status(ist(2024, 1, 26, 10, 0));
+        assert!(!status.is_trading_day);
+        assert_eq!(status.session, MarketSession::Closed);
+
+        // Before the open the same day's session is next
+        assert_eq!(market_status(ist(2024, 1, 29, 8, 0)).seconds_to_open, Some(75 * 60));
+    }
+
+    #[test]
+    fn test_session_minutes_skip_gaps() {
+        assert_eq!(session_minutes_between(ist(2024, 1, 25, 10, 0), ist(2024, 1, 25, 11, 30)), 90);
+
+        // Thursday 15:00 to Monday 09:45: 30 minutes on Thursday, none over the
+        // holiday and weekend, 30 minutes on Monday
+        assert_eq!(session_minutes_between(ist(2024, 1, 25, 15, 0), ist(2024, 1, 29, 9, 45)), 60);
+
+        // Time outside the session is ignored at both ends
+        assert_eq!(session_minutes_between(ist(2024, 1, 25, 8, 0), ist(2024, 1, 25, 18, 0)), 375);
+        assert_eq!(session_minutes_between(ist(2024, 1, 25, 11, 0), ist(2024, 1, 25, 10, 0)), 0);
+    }
+}
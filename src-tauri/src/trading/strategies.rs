@@ -0,0 +1,362 @@
+use crate::models::backtesting::OHLCV;
+use crate::models::trading::{
+    IndicatorSource, RuleComparison, RuleCondition, SignalType, StrategyParams, StrategyType,
+};
+use crate::trading::indicators;
+use rust_decimal::prelude::ToPrimitive;
+
+/// Bollinger band period for mean reversion
+pub const BOLLINGER_PERIOD: usize = 20;
+
+/// Bollinger band width in standard deviations
+pub const BOLLINGER_STD_DEV: f64 = 2.0;
+
+/// Lookback in bars for the momentum breakout channel
+pub const BREAKOUT_LOOKBACK: usize = 20;
+
+/// Outcome of evaluating a built-in strategy on the latest bar
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StrategyEvaluation {
+    pub signal_type: SignalType,
+    pub strength: f64,
+}
+
+impl StrategyEvaluation {
+    fn hold() -> Self {
+        Self { signal_type: SignalType::Hold, strength: 0.0 }
+    }
+}
+
+/// Number of bars a strategy needs before it can signal
+pub fn required_bars(strategy: &StrategyParams) -> usize {
+    match strategy.strategy_type {
+        StrategyType::BollingerMeanReversion => BOLLINGER_PERIOD + 1,
+        StrategyType::MomentumBreakout => BREAKOUT_LOOKBACK + 1,
+        StrategyType::Custom => strategy.rules.as_ref().map(|rules| rules.warm_up()).unwrap_or(1),
+    }
+}
+
+/// Evaluate a strategy on bars ordered oldest to newest
+///
+/// Volume threshold, stop loss and take profit are applied by the caller,
+/// since live ticks and backtest candles measure volume differently.
+pub fn evaluate_strategy(strategy: &StrategyParams, bars: &[OHLCV]) -> StrategyEvaluation {
+    if bars.len() < required_bars(strategy) {
+        return StrategyEvaluation::hold();
+    }
+
+    match strategy.strategy_type {
+        StrategyType::BollingerMeanReversion => bollinger_mean_reversion(bars),
+        StrategyType::MomentumBreakout => momentum_breakout(bars),
+        StrategyType::Custom => custom_rules(strategy, bars),
+    }
+}
+
+/// Sell when every exit condition holds, buy when every entry condition holds
+fn custom_rules(strategy: &StrategyParams, bars: &[OHLCV]) -> StrategyEvaluation {
+    let rules = match &strategy.rules {
+        Some(rules) => rules,
+        None => return StrategyEvaluation::hold(),
+    };
+
+    let all_hold = |conditions: &[RuleCondition]| {
+        !conditions.is_empty() && conditions.iter().all(|condition| condition_holds(condition, bars))
+    };
+
+    if all_hold(&rules.exit) {
+        StrategyEvaluation { signal_type: SignalType::Sell, strength: 1.0 }
+    } else if all_hold(&rules.entry) {
+        StrategyEvaluation { signal_type: SignalType::Buy, strength: 1.0 }
+    } else {
+        StrategyEvaluation::hold()
+    }
+}
+
+/// Whether a condition holds on the latest bar; missing values never hold
+fn condition_holds(condition: &RuleCondition, bars: &[OHLCV]) -> bool {
+    let current = match (indicator_value(&condition.left, bars), indicator_value(&condition.right, bars)) {
+        (Some(left), Some(right)) => left - right,
+        _ => return false,
+    };
+
+    match condition.comparison {
+        RuleComparison::Above => current > 0.0,
+        RuleComparison::Below => current < 0.0,
+        RuleComparison::CrossesAbove | RuleComparison::CrossesBelow => {
+            let previous = &bars[..bars.len().saturating_sub(1)];
+            let previous = match (
+                indicator_value(&condition.left, previous),
+                indicator_value(&condition.right, previous),
+            ) {
+                (Some(left), Some(right)) => left - right,
+                _ => return false,
+            };
+
+            if condition.comparison == RuleComparison::CrossesAbove {
+                previous <= 0.0 && current > 0.0
+            } else {
+                previous >= 0.0 && current < 0.0
+            }
+        }
+    }
+}
+
+/// Value of an indicator at the latest bar
+fn indicator_value(source: &IndicatorSource, bars: &[OHLCV]) -> Option<f64> {
+    let latest = bars.last()?;
+    if bars.len() < source.warm_up() {
+        return None;
+    }
+
+    match *source {
+        IndicatorSource::Close => latest.close.to_f64(),
+        IndicatorSource::Volume => Some(latest.volume as f64),
+        IndicatorSource::Constant { value } => Some(value),
+        IndicatorSource::Sma { period } => indicators::sma(&closes(bars), period),
+        IndicatorSource::Ema { period } => indicators::ema(&closes(bars), period),
+        IndicatorSource::Rsi { period } => indicators::rsi(&closes(bars), period),
+        IndicatorSource::Atr { period } => {
+            let highs: Vec<f64> = bars.iter().map(|bar| bar.high.to_f64().unwrap_or(0.0)).collect();
+            let lows: Vec<f64> = bars.iter().map(|bar| bar.low.to_f64().unwrap_or(0.0)).collect();
+            indicators::atr(&highs, &lows, &closes(bars), period)
+        }
+        IndicatorSource::BollingerUpper { period, std_dev } => {
+            indicators::bollinger_bands(&closes(bars), period, std_dev).map(|bands| bands.upper)
+        }
+        IndicatorSource::BollingerLower { period, std_dev } => {
+            indicators::bollinger_bands(&closes(bars), period, std_dev).map(|bands| bands.lower)
+        }
+        IndicatorSource::Highest { period } => {
+            let highs: Vec<f64> = bars[..bars.len() - 1].iter().filter_map(|bar| bar.high.to_f64()).collect();
+            indicators::highest(&highs, period)
+        }
+        IndicatorSource::Lowest { period } => {
+            let lows: Vec<f64> = bars[..bars.len() - 1].iter().filter_map(|bar| bar.low.to_f64()).collect();
+            indicators::lowest(&lows, period)
+        }
+        IndicatorSource::RateOfChange { period } => indicators::rate_of_change(&closes(bars), period),
+    }
+}
+
+/// Buy when price closes back inside the lower band, sell when it closes back inside the upper band
+fn bollinger_mean_reversion(bars: &[OHLCV]) -> StrategyEvaluation {
+    let closes = closes(bars);
+    let previous = &closes[..closes.len() - 1];
+
+    let (bands, previous_bands) = match (
+        indicators::bollinger_bands(&closes, BOLLINGER_PERIOD, BOLLINGER_STD_DEV),
+        indicators::bollinger_bands(previous, BOLLINGER_PERIOD, BOLLINGER_STD_DEV),
+    ) {
+        (Some(bands), Some(previous_bands)) => (bands, previous_bands),
+        _ => return StrategyEvaluation::hold(),
+    };
+
+    let close = closes[closes.len() - 1];
+    let previous_close = previous[previous.len() - 1];
+    let percent_b = match bands.percent_b(close) {
+        Some(percent_b) => percent_b,
+        None => return StrategyEvaluation::hold(),
+    };
+
+    if previous_close < previous_bands.lower && close >= bands.lower {
+        StrategyEvaluation {
+            signal_type: SignalType::Buy,
+            strength: (0.5 - percent_b).clamp(0.0, 0.5) * 2.0,
+        }
+    } else if previous_close > previous_bands.upper && close <= bands.upper {
+        StrategyEvaluation {
+            signal_type: SignalType::Sell,
+            strength: (percent_b - 0.5).clamp(0.0, 0.5) * 2.0,
+        }
+    } else {
+        StrategyEvaluation::hold()
+    }
+}
+
+/// Buy on a close above the prior N-bar high, sell on a close below the prior N-bar low
+fn momentum_breakout(bars: &[OHLCV]) -> StrategyEvaluation {
+    let prior = &bars[bars.len() - 1 - BREAKOUT_LOOKBACK..bars.len() - 1];
+    let highs: Vec<f64> = prior.iter().filter_map(|bar| bar.high.to_f64()).collect();
+    let lows: Vec<f64> = prior.iter().filter_map(|bar| bar.low.to_f64()).collect();
+    let volumes: Vec<f64> = prior.iter().map(|bar| bar.volume as f64).collect();
+
+    let latest = &bars[bars.len() - 1];
+    let close = latest.close.to_f64().unwrap_or(0.0);
+
+    let (channel_high, channel_low) = match (
+        indicators::highest(&highs, BREAKOUT_LOOKBACK),
+        indicators::lowest(&lows, BREAKOUT_LOOKBACK),
+    ) {
+        (Some(high), Some(low)) if high > low => (high, low),
+        _ => return StrategyEvaluation::hold(),
+    };
+
+    // Breakouts on below-average volume are ignored
+    let average_volume = indicators::sma(&volumes, BREAKOUT_LOOKBACK).unwrap_or(0.0);
+    if (latest.volume as f64) < average_volume {
+        return StrategyEvaluation::hold();
+    }
+
+    let width = channel_high - channel_low;
+    if close > channel_high {
+        StrategyEvaluation {
+            signal_type: SignalType::Buy,
+            strength: (0.5 + (close - channel_high) / width).min(1.0),
+        }
+    } else if close < channel_low {
+        StrategyEvaluation {
+            signal_type: SignalType::Sell,
+            strength: (0.5 + (channel_low - close) / width).min(1.0),
+        }
+    } else {
+        StrategyEvaluation::hold()
+    }
+}
+
+fn closes(bars: &[OHLCV]) -> Vec<f64> {
+    bars.iter().map(|bar| bar.close.to_f64().unwrap_or(0.0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::trading::StrategyRules;
+    use chrono::{Duration, TimeZone, Utc};
+    use rust_decimal::Decimal;
+    use rust_decimal::prelude::FromPrimitive;
+
+    fn strategy(strategy_type: StrategyType) -> StrategyParams {
+        let mut strategy = StrategyParams::new("user", "Test", None, 10, 1.0, 1.0, 2.0, 100);
+        strategy.strategy_type = strategy_type;
+        strategy
+    }
+
+    fn bar(i: i64, close: f64, volume: i64) -> OHLCV {
+        let close = Decimal::from_f64(close).unwrap();
+        OHLCV::new(
+            Utc.with_ymd_and_hms(2024, 1, 1, 0, 0, 0).unwrap() + Duration::days(i),
+            close,
+            close + Decimal::ONE,
+            close - Decimal::ONE,
+            close,
+            volume,
+        )
+    }
+
+    fn oscillating(count: i64) -> Vec<OHLCV> {
+        (0..count).map(|i| bar(i, if i % 2 == 0 { 100.0 } else { 101.0 }, 1000)).collect()
+    }
+
+    #[test]
+    fn test_bollinger_buys_on_reentry_into_bands() {
+        let strategy = strategy(StrategyType::BollingerMeanReversion);
+        let mut bars = oscillating(20);
+
+        // Sharp drop below the lower band, then a close back inside
+        bars.push(bar(20, 95.0, 1000));
+        assert_eq!(evaluate_strategy(&strategy, &bars).signal_type, SignalType::Hold);
+
+        bars.push(bar(21, 100.0, 1000));
+        let evaluation = evaluate_strategy(&strategy, &bars);
+        assert_eq!(evaluation.signal_type, SignalType::Buy);
+        assert!(evaluation.strength > 0.0);
+    }
+
+    #[test]
+    fn test_bollinger_sells_on_reentry_from_above() {
+        let strategy = strategy(StrategyType::BollingerMeanReversion);
+        let mut bars = oscillating(20);
+
+        bars.push(bar(20, 106.0, 1000));
+        bars.push(bar(21, 101.0, 1000));
+        assert_eq!(evaluate_strategy(&strategy, &bars).signal_type, SignalType::Sell);
+    }
+
+    #[test]
+    fn test_momentum_breakout() {
+        let strategy = strategy(StrategyType::MomentumBreakout);
+        let mut bars = oscillating(20);
+
+        bars.push(bar(20, 103.0, 2000));
+        let evaluation = evaluate_strategy(&strategy, &bars);
+        assert_eq!(evaluation.signal_type, SignalType::Buy);
+        assert!(evaluation.strength >= 0.5);
+
+        // Same breakout on thin volume is ignored
+        bars.pop();
+        bars.push(bar(20, 103.0, 500));
+        assert_eq!(evaluate_strategy(&strategy, &bars).signal_type, SignalType::Hold);
+
+        bars.pop();
+        bars.push(bar(20, 97.0, 2000));
+        assert_eq!(evaluate_strategy(&strategy, &bars).signal_type, SignalType::Sell);
+    }
+
+    fn custom(entry: Vec<RuleCondition>, exit: Vec<RuleCondition>) -> StrategyParams {
+        let mut strategy = strategy(StrategyType::Custom);
+        strategy.rules = Some(StrategyRules { entry, exit });
+        strategy
+    }
+
+    #[test]
+    fn test_custom_rules() {
+        let strategy = custom(
+            vec![RuleCondition {
+                left: IndicatorSource::Close,
+                comparison: RuleComparison::CrossesAbove,
+                right: IndicatorSource::Sma { period: 5 },
+            }],
+            vec![RuleCondition {
+                left: IndicatorSource::Close,
+                comparison: RuleComparison::Below,
+                right: IndicatorSource::Constant { value: 95.0 },
+            }],
+        );
+        assert_eq!(required_bars(&strategy), 6);
+
+        let mut bars: Vec<OHLCV> = (0..6).map(|i| bar(i, 100.0, 1000)).collect();
+        bars.push(bar(6, 104.0, 1000));
+        assert_eq!(evaluate_strategy(&strategy, &bars).signal_type, SignalType::Buy);
+
+        // Still above the average, but no longer crossing it
+        bars.push(bar(7, 105.0, 1000));
+        assert_eq!(evaluate_strategy(&strategy, &bars).signal_type, SignalType::Hold);
+
+        bars.push(bar(8, 90.0, 1000));
+        assert_eq!(evaluate_strategy(&strategy, &bars).signal_type, SignalType::Sell);
+
+        // Without rules a custom strategy never trades
+        let mut unruled = strategy.clone();
+        unruled.rules = None;
+        assert_eq!(evaluate_strategy(&unruled, &bars).signal_type, SignalType::Hold);
+    }
+
+    #[test]
+    fn test_custom_rules_wait_for_warm_up() {
+        let strategy = custom(
+            vec![RuleCondition {
+                left: IndicatorSource::Close,
+                comparison: RuleComparison::Above,
+                right: IndicatorSource::Ema { period: 5 },
+            }],
+            Vec::new(),
+        );
+        assert_eq!(required_bars(&strategy), 15);
+
+        // The EMA has a value after five bars but is not trusted until warmed up
+        let mut bars: Vec<OHLCV> = (0..14).map(|i| bar(i, 100.0 + i as f64, 1000)).collect();
+        assert_eq!(evaluate_strategy(&strategy, &bars).signal_type, SignalType::Hold);
+
+        bars.push(bar(14, 120.0, 1000));
+        assert_eq!(evaluate_strategy(&strategy, &bars).signal_type, SignalType::Buy);
+    }
+
+    #[test]
+    fn test_history_is_required() {
+        for strategy_type in [StrategyType::BollingerMeanReversion, StrategyType::MomentumBreakout] {
+            let mut bars = oscillating(10);
+            bars.push(bar(10, 110.0, 2000));
+            assert_eq!(evaluate_strategy(&strategy(strategy_type), &bars).signal_type, SignalType::Hold);
+        }
+    }
+}
@@ -1,10 +1,15 @@
 use crate::error::{HedgeXError, Result};
 use crate::models::trading::{
-    StrategyParams, StockSelection, MarketData, TradingSignal, SignalType, TradeType
+    StrategyParams, StockSelection, MarketData, TradingSignal, SignalType, TradeType,
+    TrendFilterConfig, PairTradingConfig, PairSide, PairSignal, MarketDepth,
 };
+use crate::models::backtesting::{Timeframe, OHLCV};
 use crate::services::enhanced_database_service::EnhancedDatabaseService;
-use rust_decimal::{Decimal, prelude::FromStr};
-use num_traits::ToPrimitive;
+use crate::trading::market_regime::MarketRegimeTracker;
+use crate::trading::pair_trading::{SpreadTracker, evaluate_pair_signal};
+use crate::trading::bars::{LiveCandles, MAX_BARS};
+use crate::trading::live_metrics::{LiveMetrics, SymbolMetrics};
+use crate::trading::strategies::evaluate_strategy;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::RwLock;
@@ -13,6 +18,87 @@ use chrono::{DateTime, Utc};
 use uuid::Uuid;
 use sqlx::Row;
 
+/// Bar size used to evaluate strategies on live ticks (1 minute)
+pub const LIVE_BAR_SECONDS: i64 = 60;
+
+/// Widest bid-ask spread, in basis points of the mid price, at which entries are taken
+pub const MAX_ENTRY_SPREAD_BPS: f64 = 50.0;
+
+/// Market conditions a strategy signal is checked against before it is acted on
+///
+/// Live trading and backtests both gate signals through this, so they hold back the same entries.
+pub struct SignalGate<'a> {
+    /// Traded volume: the day's cumulative volume live, the candle's in a candle backtest
+    pub volume: i64,
+    
+    /// Order book at the signal, when the feed carries depth
+    pub depth: Option<&'a MarketDepth>,
+    
+    /// The strategy's index trend filter and the tracker holding the index regime
+    pub trend_filter: Option<(&'a TrendFilterConfig, &'a MarketRegimeTracker)>,
+    
+    /// Whether acting on the signal opens a position rather than closing one
+    pub opens_position: bool,
+}
+
+impl SignalGate<'_> {
+    /// Whether a strategy's signal may be acted on; exits are never held back
+    pub async fn allows(&self, strategy: &StrategyParams, signal_type: SignalType) -> bool {
+        if signal_type == SignalType::Hold {
+            return false;
+        }
+        
+        if !self.opens_position {
+            return true;
+        }
+        
+        // Check volume threshold
+        if self.volume < strategy.volume_threshold {
+            debug!("Volume below threshold for strategy {}: {} < {}", 
+                   strategy.id, self.volume, strategy.volume_threshold);
+            return false;
+        }
+        
+        // Respect the market regime filter if one is configured
+        if let Some((filter, tracker)) = self.trend_filter {
+            if !tracker.allows_signal(filter, signal_type).await {
+                debug!("Trend filter blocked {:?} signal on strategy {}", signal_type, strategy.id);
+                return false;
+            }
+        }
+        
+        // Skip entries into a wide or empty book when depth is available
+        if !self.has_entry_liquidity(signal_type) {
+            debug!("Insufficient liquidity for {:?} signal on strategy {}", signal_type, strategy.id);
+            return false;
+        }
+        
+        true
+    }
+    
+    /// Whether the order book can take an entry: a tight spread and resting quantity on the far side
+    ///
+    /// Ticks without depth (LTP and quote mode) always pass.
+    fn has_entry_liquidity(&self, signal_type: SignalType) -> bool {
+        let depth = match self.depth {
+            Some(depth) => depth,
+            None => return true,
+        };
+        
+        let trade_type = match signal_type {
+            SignalType::Buy => TradeType::Buy,
+            SignalType::Sell => TradeType::Sell,
+            // Exits are never held back
+            _ => return true,
+        };
+        
+        match depth.spread_bps() {
+            Some(spread) if spread <= MAX_ENTRY_SPREAD_BPS => depth.available_quantity(trade_type) > 0,
+            _ => false,
+        }
+    }
+}
+
 /// Strategy manager for loading and validating trading strategies
 pub struct StrategyManager {
     /// Database service for storing strategy data
@@ -24,6 +110,24 @@ pub struct StrategyManager {
     /// Active stock selections by user
     stock_selections: Arc<RwLock<HashMap<String, Vec<StockSelection>>>>,
     
+    /// Index trend filters by strategy ID
+    trend_filters: Arc<RwLock<HashMap<String, TrendFilterConfig>>>,
+    
+    /// Index regime tracker fed from subscribed index ticks
+    regime_tracker: Arc<MarketRegimeTracker>,
+    
+    /// Pair trading configurations by strategy ID
+    pair_configs: Arc<RwLock<HashMap<String, PairTradingConfig>>>,
+    
+    /// Spread trackers for pair strategies by strategy ID
+    spread_trackers: Arc<RwLock<HashMap<String, SpreadTracker>>>,
+    
+    /// Candles built from ticks by symbol
+    candles: Arc<RwLock<LiveCandles>>,
+    
+    /// Spread, VWAP and rolling volume derived from ticks by symbol
+    live_metrics: Arc<RwLock<LiveMetrics>>,
+    
     /// User ID
     user_id: String,
 }
@@ -38,24 +142,49 @@ impl StrategyManager {
             db_service,
             strategies: Arc::new(RwLock::new(HashMap::new())),
             stock_selections: Arc::new(RwLock::new(HashMap::new())),
+            trend_filters: Arc::new(RwLock::new(HashMap::new())),
+            regime_tracker: Arc::new(MarketRegimeTracker::default()),
+            pair_configs: Arc::new(RwLock::new(HashMap::new())),
+            spread_trackers: Arc::new(RwLock::new(HashMap::new())),
+            candles: Arc::new(RwLock::new(LiveCandles::default())),
+            live_metrics: Arc::new(RwLock::new(LiveMetrics::default())),
             user_id: user_id.to_string(),
         };
         
-        // Load existing strategies and stock selections
+        // Load existing strategies, stock selections, trend filters and pair configs
         manager.load_strategies().await?;
         manager.load_stock_selections().await?;
+        manager.load_trend_filters().await?;
+        manager.load_pair_configs().await?;
         
         Ok(manager)
     }
     
+    /// Drop the cached strategies, selections, trend filters and pair configs and load them again
+    ///
+    /// Used after a backup restore replaced the database underneath the manager.
+    pub async fn reload(&self) -> Result<()> {
+        self.strategies.write().await.clear();
+        self.stock_selections.write().await.clear();
+        self.trend_filters.write().await.clear();
+        self.pair_configs.write().await.clear();
+        self.spread_trackers.write().await.clear();
+        
+        self.load_strategies().await?;
+        self.load_stock_selections().await?;
+        self.load_trend_filters().await?;
+        self.load_pair_configs().await
+    }
+    
     /// Load strategies from database
     async fn load_strategies(&self) -> Result<()> {
         let query = "
             SELECT id, user_id, name, description, enabled, max_trades_per_day,
                    risk_percentage, stop_loss_percentage, take_profit_percentage,
-                   volume_threshold, created_at, updated_at
+                   volume_threshold, strategy_type, max_capital, max_quantity_per_trade, max_holding_minutes, tags, rules,
+                   archived_at, created_at, updated_at
             FROM strategy_params 
-            WHERE user_id = ?
+            WHERE user_id = ? AND archived_at IS NULL
         ";
         
         let rows = sqlx::query(query)
@@ -77,6 +206,13 @@ impl StrategyManager {
                 stop_loss_percentage: row.get("stop_loss_percentage"),
                 take_profit_percentage: row.get("take_profit_percentage"),
                 volume_threshold: row.get("volume_threshold"),
+                strategy_type: row.get::<String, _>("strategy_type").parse().unwrap_or_default(),
+                max_capital: row.get("max_capital"),
+                max_quantity_per_trade: row.get("max_quantity_per_trade"),
+                max_holding_minutes: row.get("max_holding_minutes"),
+                tags: serde_json::from_str(&row.get::<String, _>("tags")).unwrap_or_default(),
+                rules: row.get::<Option<String>, _>("rules").and_then(|json| serde_json::from_str(&json).ok()),
+                archived_at: row.get("archived_at"),
                 created_at: row.get("created_at"),
                 updated_at: row.get("updated_at"),
             };
@@ -124,6 +260,78 @@ impl StrategyManager {
         Ok(())
     }
     
+    /// Load index trend filters from database
+    async fn load_trend_filters(&self) -> Result<()> {
+        let query = "
+            SELECT f.strategy_id, f.index_symbol, f.ma_period, f.enabled, f.updated_at
+            FROM strategy_trend_filters f
+            JOIN strategy_params s ON s.id = f.strategy_id
+            WHERE s.user_id = ?
+        ";
+        
+        let rows = sqlx::query(query)
+            .bind(&self.user_id)
+            .fetch_all(self.db_service.get_database().get_pool())
+            .await?;
+            
+        let mut filters = self.trend_filters.write().await;
+        
+        for row in rows {
+            let filter = TrendFilterConfig {
+                strategy_id: row.get("strategy_id"),
+                index_symbol: row.get("index_symbol"),
+                ma_period: row.get::<i64, _>("ma_period") as usize,
+                enabled: row.get("enabled"),
+                updated_at: row.get("updated_at"),
+            };
+            
+            filters.insert(filter.strategy_id.clone(), filter);
+        }
+        
+        info!("Loaded {} trend filters", filters.len());
+        Ok(())
+    }
+    
+    /// Load pair trading configurations from database
+    async fn load_pair_configs(&self) -> Result<()> {
+        let query = "
+            SELECT p.strategy_id, p.symbol_a, p.symbol_b, p.exchange, p.hedge_ratio,
+                   p.lookback, p.entry_z, p.exit_z, p.stop_z, p.updated_at
+            FROM pair_strategies p
+            JOIN strategy_params s ON s.id = p.strategy_id
+            WHERE s.user_id = ?
+        ";
+        
+        let rows = sqlx::query(query)
+            .bind(&self.user_id)
+            .fetch_all(self.db_service.get_database().get_pool())
+            .await?;
+            
+        let mut configs = self.pair_configs.write().await;
+        let mut trackers = self.spread_trackers.write().await;
+        
+        for row in rows {
+            let config = PairTradingConfig {
+                strategy_id: row.get("strategy_id"),
+                symbol_a: row.get("symbol_a"),
+                symbol_b: row.get("symbol_b"),
+                exchange: row.get("exchange"),
+                hedge_ratio: row.get("hedge_ratio"),
+                lookback: row.get::<i64, _>("lookback") as usize,
+                entry_z: row.get("entry_z"),
+                exit_z: row.get("exit_z"),
+                stop_z: row.get("stop_z"),
+                updated_at: row.get("updated_at"),
+            };
+            
+            trackers.insert(config.strategy_id.clone(), SpreadTracker::new(&config));
+            configs.insert(config.strategy_id.clone(), config);
+        }
+        
+        info!("Loaded {} pair strategies", configs.len());
+        Ok(())
+    }
+    
     /// Get all strategies for user
     pub async fn get_strategies(&self) -> Result<Vec<StrategyParams>> {
         let strategies = self.strategies.read().await;
@@ -172,8 +380,8 @@ impl StrategyManager {
             INSERT INTO strategy_params 
             (id, user_id, name, description, enabled, max_trades_per_day,
              risk_percentage, stop_loss_percentage, take_profit_percentage,
-             volume_threshold, created_at, updated_at)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+             volume_threshold, strategy_type, created_at, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ";
         
         sqlx::query(query)
@@ -187,6 +395,7 @@ impl StrategyManager {
             .bind(strategy.stop_loss_percentage)
             .bind(strategy.take_profit_percentage)
             .bind(strategy.volume_threshold)
+            .bind(strategy.strategy_type.to_string())
             .bind(strategy.created_at)
             .bind(strategy.updated_at)
             .execute(self.db_service.get_database().get_pool())
@@ -300,12 +509,17 @@ impl StrategyManager {
         Ok(())
     }
     
-    /// Delete strategy
+    /// Delete strategy by archiving it so trade history keeps its reference
     pub async fn delete_strategy(&self, strategy_id: &str) -> Result<()> {
-        // Remove from database
-        let query = "DELETE FROM strategy_params WHERE id = ?";
+        let now = Utc::now();
+        let query = "
+            UPDATE strategy_params SET enabled = false, archived_at = ?, updated_at = ?
+            WHERE id = ? AND archived_at IS NULL
+        ";
         
         let result = sqlx::query(query)
+            .bind(now)
+            .bind(now)
             .bind(strategy_id)
             .execute(self.db_service.get_database().get_pool())
             .await?;
@@ -320,7 +534,7 @@ impl StrategyManager {
             strategies.remove(strategy_id);
         }
         
-        info!("Deleted strategy: {}", strategy_id);
+        info!("Archived strategy: {}", strategy_id);
         Ok(())
     }
     
@@ -392,6 +606,273 @@ impl StrategyManager {
         Ok(())
     }
     
+    /// Set or replace the index trend filter for a strategy
+    pub async fn set_trend_filter(&self, filter: TrendFilterConfig) -> Result<TrendFilterConfig> {
+        if self.get_strategy(&filter.strategy_id).await?.is_none() {
+            return Err(HedgeXError::NotFoundError(format!("Strategy not found: {}", filter.strategy_id)));
+        }
+        
+        if filter.index_symbol.trim().is_empty() {
+            return Err(HedgeXError::ValidationError("Index symbol is required".to_string()));
+        }
+        
+        if filter.ma_period == 0 || filter.ma_period > 200 {
+            return Err(HedgeXError::ValidationError(
+                "Moving average period must be between 1 and 200".to_string()
+            ));
+        }
+        
+        let query = "
+            INSERT INTO strategy_trend_filters (strategy_id, index_symbol, ma_period, enabled, updated_at)
+            VALUES (?, ?, ?, ?, ?)
+            ON CONFLICT(strategy_id) DO UPDATE SET
+                index_symbol = excluded.index_symbol,
+                ma_period = excluded.ma_period,
+                enabled = excluded.enabled,
+                updated_at = excluded.updated_at
+        ";
+        
+        sqlx::query(query)
+            .bind(&filter.strategy_id)
+            .bind(&filter.index_symbol)
+            .bind(filter.ma_period as i64)
+            .bind(filter.enabled)
+            .bind(filter.updated_at)
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+            
+        {
+            let mut filters = self.trend_filters.write().await;
+            filters.insert(filter.strategy_id.clone(), filter.clone());
+        }
+        
+        info!("Set trend filter for strategy {}: {} / {} bars", 
+              filter.strategy_id, filter.index_symbol, filter.ma_period);
+        Ok(filter)
+    }
+    
+    /// Remove the index trend filter from a strategy
+    pub async fn remove_trend_filter(&self, strategy_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM strategy_trend_filters WHERE strategy_id = ?")
+            .bind(strategy_id)
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+            
+        let mut filters = self.trend_filters.write().await;
+        filters.remove(strategy_id);
+        
+        Ok(())
+    }
+    
+    /// Get the index trend filter for a strategy
+    pub async fn get_trend_filter(&self, strategy_id: &str) -> Option<TrendFilterConfig> {
+        let filters = self.trend_filters.read().await;
+        filters.get(strategy_id).cloned()
+    }
+    
+    /// Index symbols referenced by enabled trend filters
+    pub async fn get_tracked_indices(&self) -> Vec<String> {
+        let filters = self.trend_filters.read().await;
+        let mut indices: Vec<String> = filters.values()
+            .filter(|f| f.enabled)
+            .map(|f| f.index_symbol.clone())
+            .collect();
+        indices.sort();
+        indices.dedup();
+        indices
+    }
+    
+    /// Feed an index tick into the regime tracker
+    ///
+    /// Returns true if the tick belonged to a tracked index.
+    pub async fn update_index_tick(&self, market_data: &MarketData) -> bool {
+        let is_tracked = {
+            let filters = self.trend_filters.read().await;
+            filters.values().any(|f| f.index_symbol == market_data.symbol)
+        };
+        
+        if is_tracked {
+            self.regime_tracker.update(&market_data.symbol, market_data.ltp, market_data.timestamp).await;
+        }
+        
+        is_tracked
+    }
+    
+    /// Get the regime tracker
+    pub fn get_regime_tracker(&self) -> Arc<MarketRegimeTracker> {
+        Arc::clone(&self.regime_tracker)
+    }
+    
+    /// Turn a strategy into a two-leg pair strategy
+    pub async fn set_pair_config(&self, config: PairTradingConfig) -> Result<PairTradingConfig> {
+        if self.get_strategy(&config.strategy_id).await?.is_none() {
+            return Err(HedgeXError::NotFoundError(format!("Strategy not found: {}", config.strategy_id)));
+        }
+        
+        self.validate_pair_config(&config)?;
+        
+        let query = "
+            INSERT INTO pair_strategies 
+            (strategy_id, symbol_a, symbol_b, exchange, hedge_ratio, lookback,
+             entry_z, exit_z, stop_z, updated_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            ON CONFLICT(strategy_id) DO UPDATE SET
+                symbol_a = excluded.symbol_a,
+                symbol_b = excluded.symbol_b,
+                exchange = excluded.exchange,
+                hedge_ratio = excluded.hedge_ratio,
+                lookback = excluded.lookback,
+                entry_z = excluded.entry_z,
+                exit_z = excluded.exit_z,
+                stop_z = excluded.stop_z,
+                updated_at = excluded.updated_at
+        ";
+        
+        sqlx::query(query)
+            .bind(&config.strategy_id)
+            .bind(&config.symbol_a)
+            .bind(&config.symbol_b)
+            .bind(&config.exchange)
+            .bind(config.hedge_ratio)
+            .bind(config.lookback as i64)
+            .bind(config.entry_z)
+            .bind(config.exit_z)
+            .bind(config.stop_z)
+            .bind(config.updated_at)
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+            
+        {
+            let mut trackers = self.spread_trackers.write().await;
+            trackers.insert(config.strategy_id.clone(), SpreadTracker::new(&config));
+        }
+        {
+            let mut configs = self.pair_configs.write().await;
+            configs.insert(config.strategy_id.clone(), config.clone());
+        }
+        
+        info!("Configured pair strategy {}: {} / {}", config.strategy_id, config.symbol_a, config.symbol_b);
+        Ok(config)
+    }
+    
+    /// Remove the pair configuration from a strategy
+    pub async fn remove_pair_config(&self, strategy_id: &str) -> Result<()> {
+        sqlx::query("DELETE FROM pair_strategies WHERE strategy_id = ?")
+            .bind(strategy_id)
+            .execute(self.db_service.get_database().get_pool())
+            .await?;
+            
+        self.pair_configs.write().await.remove(strategy_id);
+        self.spread_trackers.write().await.remove(strategy_id);
+        
+        Ok(())
+    }
+    
+    /// Get the pair configuration for a strategy
+    pub async fn get_pair_config(&self, strategy_id: &str) -> Option<PairTradingConfig> {
+        let configs = self.pair_configs.read().await;
+        configs.get(strategy_id).cloned()
+    }
+    
+    /// Check if a strategy trades a pair
+    pub async fn is_pair_strategy(&self, strategy_id: &str) -> bool {
+        self.pair_configs.read().await.contains_key(strategy_id)
+    }
+    
+    /// Validate pair trading parameters
+    pub fn validate_pair_config(&self, config: &PairTradingConfig) -> Result<()> {
+        if config.symbol_a.is_empty() || config.symbol_b.is_empty() || config.symbol_a == config.symbol_b {
+            return Err(HedgeXError::ValidationError(
+                "Pair strategy requires two different symbols".to_string()
+            ));
+        }
+        
+        if config.hedge_ratio <= 0.0 {
+            return Err(HedgeXError::ValidationError(
+                "Hedge ratio must be greater than 0".to_string()
+            ));
+        }
+        
+        if config.lookback < 10 || config.lookback > 1000 {
+            return Err(HedgeXError::ValidationError(
+                "Lookback must be between 10 and 1000 bars".to_string()
+            ));
+        }
+        
+        if config.entry_z <= config.exit_z || config.exit_z < 0.0 {
+            return Err(HedgeXError::ValidationError(
+                "Entry z-score must be greater than exit z-score".to_string()
+            ));
+        }
+        
+        if config.stop_z <= config.entry_z {
+            return Err(HedgeXError::ValidationError(
+                "Stop z-score must be greater than entry z-score".to_string()
+            ));
+        }
+        
+        Ok(())
+    }
+    
+    /// Generate a pair signal for a tick on either leg
+    ///
+    /// `open_side` is the side of the currently open pair position, if any.
+    pub async fn generate_pair_signal(
+        &self,
+        market_data: &MarketData,
+        strategy_id: &str,
+        open_side: Option<PairSide>,
+    ) -> Result<Option<PairSignal>> {
+        let config = match self.get_pair_config(strategy_id).await {
+            Some(config) if config.has_leg(&market_data.symbol) => config,
+            _ => return Ok(None),
+        };
+        
+        let enabled = self.get_strategy(strategy_id).await?
+            .map(|s| s.enabled)
+            .unwrap_or(false);
+        
+        let (z_score, price_a, price_b) = {
+            let mut trackers = self.spread_trackers.write().await;
+            let tracker = trackers.entry(strategy_id.to_string())
+                .or_insert_with(|| SpreadTracker::new(&config));
+                
+            if market_data.symbol == config.symbol_a {
+                tracker.update_a(market_data.ltp, market_data.timestamp);
+            } else {
+                tracker.update_b(market_data.ltp, market_data.timestamp);
+            }
+            
+            match (tracker.z_score(), tracker.prices()) {
+                (Some(z), Some((a, b))) => (z, a, b),
+                _ => return Ok(None),
+            }
+        };
+        
+        // Disabled strategies may still close what they have open
+        if !enabled && open_side.is_none() {
+            return Ok(None);
+        }
+        
+        let signal_type = match evaluate_pair_signal(&config, z_score, open_side) {
+            Some(signal_type) => signal_type,
+            None => return Ok(None),
+        };
+        
+        debug!("Pair signal for {} ({}/{}): {:?} at z={:.2}", 
+               strategy_id, config.symbol_a, config.symbol_b, signal_type, z_score);
+        
+        Ok(Some(PairSignal {
+            strategy_id: strategy_id.to_string(),
+            signal_type,
+            z_score,
+            price_a,
+            price_b,
+            timestamp: market_data.timestamp,
+        }))
+    }
+    
+    /// Check a signal against the strategy's trend filter, if any
     /// Validate strategy parameters
     pub fn validate_strategy_params(
         &self,
@@ -460,6 +941,13 @@ impl StrategyManager {
             None => return Err(HedgeXError::NotFoundError(format!("Strategy not found: {}", strategy_id))),
         };
         
+        // Pair strategies are driven through generate_pair_signal
+        if self.is_pair_strategy(strategy_id).await {
+            return Ok(None);
+        }
+        
+        let bars = self.record_tick(market_data).await;
+        
         // Check if symbol is in active stock selection
         let selections = self.stock_selections.read().await;
         let empty_vec = Vec::new();
@@ -472,25 +960,25 @@ impl StrategyManager {
             return Ok(None);
         }
         
-        // Check volume threshold
-        if market_data.volume < strategy.volume_threshold {
-            debug!("Volume below threshold for {}: {} < {}", 
-                   market_data.symbol, market_data.volume, strategy.volume_threshold);
-            return Ok(None);
-        }
+        let evaluation = evaluate_strategy(strategy, &bars);
+        let signal_type = evaluation.signal_type;
         
-        // Simple momentum-based signal generation
-        // This is a basic implementation - in practice, you'd implement more sophisticated strategies
-        let signal_type = self.calculate_momentum_signal(market_data)?;
-        
-        if signal_type == SignalType::Hold {
+        // Buy and sell signals both open positions live; exits come from stops, targets and time limits
+        let trend_filter = self.get_trend_filter(strategy_id).await;
+        let gate = SignalGate {
+            volume: market_data.volume,
+            depth: market_data.depth.as_ref(),
+            trend_filter: trend_filter.as_ref().map(|filter| (filter, self.regime_tracker.as_ref())),
+            opens_position: true,
+        };
+        if !gate.allows(strategy, signal_type).await {
             return Ok(None);
         }
         
         let signal = TradingSignal {
             symbol: market_data.symbol.clone(),
             signal_type,
-            strength: self.calculate_signal_strength(market_data)?,
+            strength: evaluation.strength,
             price: market_data.ltp,
             volume: market_data.volume,
             timestamp: Utc::now(),
@@ -503,41 +991,30 @@ impl StrategyManager {
         Ok(Some(signal))
     }
     
-    /// Calculate momentum-based signal
-    fn calculate_momentum_signal(&self, market_data: &MarketData) -> Result<SignalType> {
-        // Simple momentum calculation based on bid-ask spread and price change
-        let spread = market_data.ask - market_data.bid;
-        let mid_price = (market_data.bid + market_data.ask) / Decimal::from(2);
-        
-        // If current price is significantly above mid price, consider selling
-        // If current price is significantly below mid price, consider buying
-        let price_deviation = (market_data.ltp - mid_price) / mid_price;
+    /// Record a tick into the symbol's bars and live metrics, returning the bars
+    async fn record_tick(&self, market_data: &MarketData) -> Vec<OHLCV> {
+        self.live_metrics.write().await.update(
+            &market_data.symbol,
+            market_data.ltp,
+            market_data.bid,
+            market_data.ask,
+            market_data.volume,
+            market_data.timestamp,
+        );
         
-        if price_deviation > Decimal::from_str("0.002").unwrap() { // 0.2% above mid
-            Ok(SignalType::Sell)
-        } else if price_deviation < Decimal::from_str("-0.002").unwrap() { // 0.2% below mid
-            Ok(SignalType::Buy)
-        } else {
-            Ok(SignalType::Hold)
-        }
+        let mut candles = self.candles.write().await;
+        candles.update(&market_data.symbol, market_data.ltp, market_data.volume, market_data.timestamp);
+        candles.recent(&market_data.symbol, Timeframe::Minute1, MAX_BARS)
     }
     
-    /// Calculate signal strength
-    fn calculate_signal_strength(&self, market_data: &MarketData) -> Result<f64> {
-        // Calculate strength based on volume and price volatility
-        let volume_factor = (market_data.volume as f64).log10() / 10.0; // Normalize volume
-        let spread = market_data.ask - market_data.bid;
-        let spread_factor = if market_data.ltp > Decimal::ZERO {
-            ToPrimitive::to_f64(&(spread / market_data.ltp)).unwrap_or(0.0)
-        } else {
-            0.0
-        };
-        
-        // Combine factors (higher volume = stronger signal, lower spread = stronger signal)
-        let strength = (volume_factor * 0.7) + ((1.0 - spread_factor) * 0.3);
-        
-        // Clamp between 0.0 and 1.0
-        Ok(strength.max(0.0).min(1.0))
+    /// Latest `count` candles of a timeframe built from the ticks seen so far, oldest first
+    pub async fn get_recent_candles(&self, symbol: &str, timeframe: Timeframe, count: usize) -> Vec<OHLCV> {
+        self.candles.read().await.recent(symbol, timeframe, count)
+    }
+    
+    /// Spread, VWAP and rolling volume of a symbol as of the latest tick seen
+    pub async fn get_live_metrics(&self, symbol: &str) -> Option<SymbolMetrics> {
+        self.live_metrics.read().await.get(symbol)
     }
     
     /// Check if symbol should be traded based on strategy
@@ -611,7 +1088,9 @@ impl StrategyManager {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::trading::{DepthLevel, MarketDepth};
     use crate::services::enhanced_database_service::EnhancedDatabaseService;
+    use rust_decimal::Decimal;
     use tempfile::tempdir;
     use std::path::PathBuf;
     
@@ -636,6 +1115,13 @@ mod tests {
                 stop_loss_percentage REAL NOT NULL DEFAULT 0.5,
                 take_profit_percentage REAL NOT NULL DEFAULT 1.5,
                 volume_threshold INTEGER NOT NULL DEFAULT 100000,
+                strategy_type TEXT NOT NULL DEFAULT 'BollingerMeanReversion',
+                max_capital REAL,
+                max_quantity_per_trade INTEGER,
+                max_holding_minutes INTEGER,
+                tags TEXT NOT NULL DEFAULT '[]',
+                rules TEXT,
+                archived_at TIMESTAMP,
                 created_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP,
                 updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
             )"
@@ -678,6 +1164,37 @@ mod tests {
         .await
         .unwrap();
         
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS pair_strategies (
+                strategy_id TEXT PRIMARY KEY,
+                symbol_a TEXT NOT NULL,
+                symbol_b TEXT NOT NULL,
+                exchange TEXT NOT NULL DEFAULT 'NSE',
+                hedge_ratio REAL NOT NULL DEFAULT 1.0,
+                lookback INTEGER NOT NULL DEFAULT 60,
+                entry_z REAL NOT NULL DEFAULT 2.0,
+                exit_z REAL NOT NULL DEFAULT 0.5,
+                stop_z REAL NOT NULL DEFAULT 4.0,
+                updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )"
+        )
+        .execute(db_service.get_database().get_pool())
+        .await
+        .unwrap();
+        
+        sqlx::query(
+            "CREATE TABLE IF NOT EXISTS strategy_trend_filters (
+                strategy_id TEXT PRIMARY KEY,
+                index_symbol TEXT NOT NULL DEFAULT 'NIFTY 50',
+                ma_period INTEGER NOT NULL DEFAULT 20,
+                enabled BOOLEAN NOT NULL DEFAULT true,
+                updated_at TIMESTAMP NOT NULL DEFAULT CURRENT_TIMESTAMP
+            )"
+        )
+        .execute(db_service.get_database().get_pool())
+        .await
+        .unwrap();
+        
         (Arc::new(db_service), db_path)
     }
     
@@ -751,4 +1268,145 @@ mod tests {
         assert!(manager.validate_strategy_params(10, 2.0, 0.0, 3.0, 100000).is_err()); // Invalid stop loss
         assert!(manager.validate_strategy_params(10, 2.0, 3.0, 1.0, 100000).is_err()); // Take profit < stop loss
     }
-}
\ No newline at end of file
+    
+    #[tokio::test]
+    async fn test_trend_filter_blocks_counter_trend_entries() {
+        let (db_service, _) = setup_test_db().await;
+        
+        let manager = StrategyManager::new(db_service, "test_user")
+            .await
+            .unwrap();
+            
+        let strategy = manager.create_strategy("Trend", None, 10, 2.0, 1.0, 3.0, 1).await.unwrap();
+        manager.enable_strategy(&strategy.id).await.unwrap();
+        manager.add_stock("INFY", "NSE").await.unwrap();
+        
+        manager.set_trend_filter(TrendFilterConfig::new(&strategy.id, "NIFTY 50", 2)).await.unwrap();
+        assert_eq!(manager.get_tracked_indices().await, vec!["NIFTY 50".to_string()]);
+        
+        // Falling index puts the market in a bearish regime
+        let start = Utc::now();
+        for (i, price) in [22000, 21800].iter().enumerate() {
+            let mut tick = MarketData::new("NIFTY 50", 256265, Decimal::from(*price), 0, Decimal::ZERO, Decimal::ZERO);
+            tick.timestamp = start + chrono::Duration::minutes(i as i64);
+            assert!(manager.update_index_tick(&tick).await);
+        }
+        
+        // Oscillating prices, a flush below the lower band and a close back inside produce a long entry
+        let start = Utc::now() - chrono::Duration::minutes(30);
+        let mut prices: Vec<i64> = (0..20).map(|i| 100 + i % 2).collect();
+        prices.push(95);
+        for (i, price) in prices.iter().enumerate() {
+            let mut tick = MarketData::new("INFY", 408065, Decimal::from(*price), 1000 + i as i64 * 100, Decimal::ZERO, Decimal::ZERO);
+            tick.timestamp = start + chrono::Duration::minutes(i as i64);
+            assert!(manager.generate_signal(&tick, &strategy.id).await.unwrap().is_none());
+        }
+        
+        let mut data = MarketData::new("INFY", 408065, Decimal::from(100), 4000, Decimal::ZERO, Decimal::ZERO);
+        data.timestamp = start + chrono::Duration::minutes(prices.len() as i64);
+        assert!(manager.generate_signal(&data, &strategy.id).await.unwrap().is_none());
+        
+        // Without the filter the same tick produces a signal
+        manager.remove_trend_filter(&strategy.id).await.unwrap();
+        let signal = manager.generate_signal(&data, &strategy.id).await.unwrap().unwrap();
+        assert_eq!(signal.signal_type, SignalType::Buy);
+    }
+    
+    #[tokio::test]
+    async fn test_trend_filter_operations() {
+        let (db_service, _) = setup_test_db().await;
+        
+        let manager = StrategyManager::new(db_service.clone(), "test_user")
+            .await
+            .unwrap();
+            
+        let strategy = manager.create_strategy("Trend", None, 10, 2.0, 1.0, 3.0, 1).await.unwrap();
+        
+        // Invalid period or index is rejected
+        assert!(manager.set_trend_filter(TrendFilterConfig::new(&strategy.id, "NIFTY 50", 0)).await.is_err());
+        assert!(manager.set_trend_filter(TrendFilterConfig::new(&strategy.id, " ", 20)).await.is_err());
+        
+        let filter = manager.set_trend_filter(TrendFilterConfig::new(&strategy.id, "NIFTY BANK", 50)).await.unwrap();
+        assert_eq!(filter.ma_period, 50);
+        
+        // A new manager loads the stored filter
+        let reloaded = StrategyManager::new(db_service, "test_user").await.unwrap();
+        let stored = reloaded.get_trend_filter(&strategy.id).await.unwrap();
+        assert_eq!(stored.index_symbol, "NIFTY BANK");
+        assert!(stored.enabled);
+        
+        manager.remove_trend_filter(&strategy.id).await.unwrap();
+        assert!(manager.get_trend_filter(&strategy.id).await.is_none());
+        assert!(manager.get_tracked_indices().await.is_empty());
+    }
+    
+    #[tokio::test]
+    async fn test_entries_need_liquidity() {
+        let (db_service, _) = setup_test_db().await;
+        
+        let manager = StrategyManager::new(db_service, "test_user")
+            .await
+            .unwrap();
+            
+        let strategy = manager.create_strategy("Trend", None, 10, 2.0, 1.0, 3.0, 1).await.unwrap();
+        manager.enable_strategy(&strategy.id).await.unwrap();
+        manager.add_stock("INFY", "NSE").await.unwrap();
+        
+        let start = Utc::now() - chrono::Duration::minutes(30);
+        let mut prices: Vec<i64> = (0..20).map(|i| 100 + i % 2).collect();
+        prices.push(95);
+        for (i, price) in prices.iter().enumerate() {
+            let mut tick = MarketData::new("INFY", 408065, Decimal::from(*price), 1000 + i as i64 * 100, Decimal::ZERO, Decimal::ZERO);
+            tick.timestamp = start + chrono::Duration::minutes(i as i64);
+            manager.generate_signal(&tick, &strategy.id).await.unwrap();
+        }
+        
+        let level = |price: i64, quantity: u64| DepthLevel { price: Decimal::from(price), quantity, orders: 1 };
+        let mut data = MarketData::new("INFY", 408065, Decimal::from(100), 4000, Decimal::from(99), Decimal::from(101));
+        data.timestamp = start + chrono::Duration::minutes(prices.len() as i64);
+        
+        // A 2% spread is too wide to enter
+        let wide = MarketDepth { bids: vec![level(99, 500)], asks: vec![level(101, 500)] };
+        assert!(manager.generate_signal(&data.clone().with_depth(wide), &strategy.id).await.unwrap().is_none());
+        
+        // Nothing offered to buy from
+        let no_asks = MarketDepth { bids: vec![level(100, 500)], asks: vec![level(100, 0)] };
+        assert!(manager.generate_signal(&data.clone().with_depth(no_asks), &strategy.id).await.unwrap().is_none());
+        
+        let tight = MarketDepth { bids: vec![level(100, 500)], asks: vec![DepthLevel { price: Decimal::new(10005, 2), quantity: 300, orders: 2 }] };
+        assert!(tight.spread_bps().unwrap() < MAX_ENTRY_SPREAD_BPS);
+        let signal = manager.generate_signal(&data.with_depth(tight), &strategy.id).await.unwrap().unwrap();
+        assert_eq!(signal.signal_type, SignalType::Buy);
+    }
+    
+    #[tokio::test]
+    async fn test_pair_strategy_configuration() {
+        let (db_service, _) = setup_test_db().await;
+        
+        let manager = StrategyManager::new(db_service, "test_user")
+            .await
+            .unwrap();
+            
+        let strategy = manager.create_strategy("Banks pair", None, 10, 2.0, 1.0, 3.0, 1).await.unwrap();
+        
+        // Same symbol on both legs is rejected
+        let invalid = PairTradingConfig::new(&strategy.id, "HDFCBANK", "HDFCBANK");
+        assert!(manager.set_pair_config(invalid).await.is_err());
+        
+        let config = PairTradingConfig::new(&strategy.id, "HDFCBANK", "ICICIBANK");
+        manager.set_pair_config(config).await.unwrap();
+        assert!(manager.is_pair_strategy(&strategy.id).await);
+        
+        // Single-leg signal generation ignores pair strategies
+        manager.enable_strategy(&strategy.id).await.unwrap();
+        manager.add_stock("HDFCBANK", "NSE").await.unwrap();
+        let data = MarketData::new("HDFCBANK", 341249, Decimal::from(95), 1000, Decimal::from(99), Decimal::from(101));
+        assert!(manager.generate_signal(&data, &strategy.id).await.unwrap().is_none());
+        
+        // Not enough spread history for a pair signal yet
+        assert!(manager.generate_pair_signal(&data, &strategy.id, None).await.unwrap().is_none());
+        
+        manager.remove_pair_config(&strategy.id).await.unwrap();
+        assert!(!manager.is_pair_strategy(&strategy.id).await);
+    }
+}
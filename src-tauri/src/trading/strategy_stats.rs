@@ -0,0 +1,195 @@
+use crate::models::trading::{StrategyParams, StrategyStatsSnapshot, TradeType};
+use chrono::NaiveDate;
+use std::collections::{HashMap, HashSet};
+
+/// Trade row folded into the live strategy stats
+#[derive(Debug, Clone)]
+pub struct StatsTrade {
+    pub id: String,
+    pub strategy_id: String,
+    pub symbol: String,
+    pub trade_type: TradeType,
+    pub quantity: i64,
+    pub price: f64,
+    pub executed: bool,
+}
+
+/// Running totals for one strategy
+#[derive(Debug, Default, Clone)]
+struct StrategyTally {
+    /// Trades counted towards today's quota
+    counted: HashSet<String>,
+    /// Executed trades folded into cash flow and positions
+    settled: HashSet<String>,
+    cash_flow: f64,
+    net_quantity: HashMap<String, i64>,
+    last_price: HashMap<String, f64>,
+}
+
+impl StrategyTally {
+    fn pnl(&self) -> f64 {
+        // Open quantity is marked at the last traded price
+        let open_value: f64 = self.net_quantity.iter()
+            .map(|(symbol, quantity)| *quantity as f64 * self.last_price.get(symbol).copied().unwrap_or(0.0))
+            .sum();
+        self.cash_flow + open_value
+    }
+
+    fn open_positions(&self) -> i32 {
+        self.net_quantity.values().filter(|quantity| **quantity != 0).count() as i32
+    }
+}
+
+/// Per-strategy stats for the current day, updated from new trades only
+#[derive(Debug, Default)]
+pub struct StrategyStatsTracker {
+    day: Option<NaiveDate>,
+    last_rowid: i64,
+    tallies: HashMap<String, StrategyTally>,
+    last_emitted: HashMap<String, StrategyStatsSnapshot>,
+}
+
+impl StrategyStatsTracker {
+    /// Create an empty tracker
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Start a fresh tally when the trading day changes
+    pub fn roll_day(&mut self, day: NaiveDate) {
+        if self.day != Some(day) {
+            self.day = Some(day);
+            self.tallies.clear();
+        }
+    }
+
+    /// Highest trades rowid folded so far
+    pub fn last_rowid(&self) -> i64 {
+        self.last_rowid
+    }
+
+    /// Trades counted today that have not executed yet
+    pub fn pending_trade_ids(&self) -> Vec<String> {
+        self.tallies.values()
+            .flat_map(|tally| tally.counted.difference(&tally.settled).cloned())
+            .collect()
+    }
+
+    /// Fold a trade into the tally; repeated calls for the same trade are ignored
+    pub fn record(&mut self, rowid: i64, trade: &StatsTrade) {
+        self.last_rowid = self.last_rowid.max(rowid);
+
+        let tally = self.tallies.entry(trade.strategy_id.clone()).or_default();
+        tally.counted.insert(trade.id.clone());
+
+        if !trade.executed || !tally.settled.insert(trade.id.clone()) {
+            return;
+        }
+
+        let signed_quantity = match trade.trade_type {
+            TradeType::Buy => trade.quantity,
+            TradeType::Sell => -trade.quantity,
+        };
+        tally.cash_flow -= signed_quantity as f64 * trade.price;
+        *tally.net_quantity.entry(trade.symbol.clone()).or_insert(0) += signed_quantity;
+        tally.last_price.insert(trade.symbol.clone(), trade.price);
+    }
+
+    /// Current stats for a strategy
+    pub fn snapshot(&self, strategy: &StrategyParams) -> StrategyStatsSnapshot {
+        let tally = self.tallies.get(&strategy.id);
+        let trades_today = tally.map(|t| t.counted.len() as i32).unwrap_or(0);
+
+        StrategyStatsSnapshot {
+            strategy_id: strategy.id.clone(),
+            enabled: strategy.enabled,
+            trades_today,
+            pnl_today: tally.map(|t| t.pnl()).unwrap_or(0.0),
+            open_positions: tally.map(|t| t.open_positions()).unwrap_or(0),
+            max_trades_per_day: strategy.max_trades_per_day,
+            remaining_trades: (strategy.max_trades_per_day - trades_today).max(0),
+        }
+    }
+
+    /// Snapshots that differ from the ones returned by the previous call
+    pub fn changed_snapshots(&mut self, strategies: &[StrategyParams]) -> Vec<StrategyStatsSnapshot> {
+        let mut changed = Vec::new();
+
+        for strategy in strategies {
+            let snapshot = self.snapshot(strategy);
+            if self.last_emitted.get(&strategy.id) != Some(&snapshot) {
+                self.last_emitted.insert(strategy.id.clone(), snapshot.clone());
+                changed.push(snapshot);
+            }
+        }
+
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn trade(id: &str, trade_type: TradeType, quantity: i64, price: f64, executed: bool) -> StatsTrade {
+        StatsTrade {
+            id: id.to_string(),
+            strategy_id: "strategy".to_string(),
+            symbol: "RELIANCE".to_string(),
+            trade_type,
+            quantity,
+            price,
+            executed,
+        }
+    }
+
+    fn strategy() -> StrategyParams {
+        let mut strategy = StrategyParams::new("user", "Test", None, 3, 1.0, 1.0, 2.0, 100);
+        strategy.id = "strategy".to_string();
+        strategy
+    }
+
+    #[test]
+    fn test_trades_fold_incrementally() {
+        let mut tracker = StrategyStatsTracker::new();
+        tracker.roll_day(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+
+        tracker.record(1, &trade("t1", TradeType::Buy, 10, 100.0, true));
+        tracker.record(2, &trade("t2", TradeType::Sell, 10, 103.0, false));
+
+        let snapshot = tracker.snapshot(&strategy());
+        assert_eq!(snapshot.trades_today, 2);
+        assert_eq!(snapshot.remaining_trades, 1);
+        assert_eq!(snapshot.open_positions, 1);
+        assert_eq!(snapshot.pnl_today, 0.0);
+        assert_eq!(tracker.pending_trade_ids(), vec!["t2".to_string()]);
+        assert_eq!(tracker.last_rowid(), 2);
+
+        // The pending exit fills; re-reading it doesn't double count
+        tracker.record(2, &trade("t2", TradeType::Sell, 10, 103.0, true));
+        tracker.record(2, &trade("t2", TradeType::Sell, 10, 103.0, true));
+
+        let snapshot = tracker.snapshot(&strategy());
+        assert_eq!(snapshot.trades_today, 2);
+        assert_eq!(snapshot.open_positions, 0);
+        assert!((snapshot.pnl_today - 30.0).abs() < 1e-9);
+        assert!(tracker.pending_trade_ids().is_empty());
+    }
+
+    #[test]
+    fn test_only_changed_snapshots_are_returned() {
+        let mut tracker = StrategyStatsTracker::new();
+        tracker.roll_day(NaiveDate::from_ymd_opt(2024, 1, 1).unwrap());
+        let strategies = vec![strategy()];
+
+        assert_eq!(tracker.changed_snapshots(&strategies).len(), 1);
+        assert!(tracker.changed_snapshots(&strategies).is_empty());
+
+        tracker.record(1, &trade("t1", TradeType::Buy, 5, 100.0, true));
+        assert_eq!(tracker.changed_snapshots(&strategies)[0].trades_today, 1);
+
+        // A new day starts from zero
+        tracker.roll_day(NaiveDate::from_ymd_opt(2024, 1, 2).unwrap());
+        assert_eq!(tracker.changed_snapshots(&strategies)[0].trades_today, 0);
+    }
+}
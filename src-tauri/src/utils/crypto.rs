@@ -17,6 +17,9 @@ const NONCE_LEN: usize = 12; // 96 bits for ChaCha20-Poly1305
 const SALT_LEN: usize = 16; // 128 bits
 const PBKDF2_ITERATIONS: u32 = 100_000;
 
+/// Leading bytes of data encrypted with a passphrase, followed by the salt
+const PASSPHRASE_MAGIC: &[u8] = b"HXPE1";
+
 /// Enhanced secure encryption service for sensitive data
 pub struct CryptoService {
     master_key: Option<[u8; KEY_LEN]>,
@@ -93,6 +96,22 @@ impl CryptoService {
     
     /// Encrypt data with a specific key
     pub fn encrypt_with_key(&self, plaintext: &str, key: &[u8; KEY_LEN]) -> Result<String> {
+        let sealed = self.encrypt_bytes_with_key(plaintext.as_bytes(), key)?;
+        Ok(general_purpose::STANDARD.encode(&sealed))
+    }
+    
+    /// Decrypt data with a specific key
+    pub fn decrypt_with_key(&self, ciphertext_base64: &str, key: &[u8; KEY_LEN]) -> Result<String> {
+        let ciphertext = general_purpose::STANDARD.decode(ciphertext_base64)
+            .map_err(|e| HedgeXError::CryptoError(format!("Invalid ciphertext format: {}", e)))?;
+        let plaintext = self.decrypt_bytes_with_key(&ciphertext, key)?;
+        
+        String::from_utf8(plaintext)
+            .map_err(|e| HedgeXError::CryptoError(format!("Invalid UTF-8: {}", e)))
+    }
+    
+    /// Encrypt binary data with a specific key, returning the nonce followed by the ciphertext
+    pub fn encrypt_bytes_with_key(&self, plaintext: &[u8], key: &[u8; KEY_LEN]) -> Result<Vec<u8>> {
         debug!("Encrypting data");
         
         let unbound_key = aead::UnboundKey::new(&aead::CHACHA20_POLY1305, key)
@@ -105,7 +124,7 @@ impl CryptoService {
             .map_err(|_| HedgeXError::CryptoError("Failed to generate nonce".to_string()))?;
         
         let nonce = aead::Nonce::assume_unique_for_key(nonce_bytes);
-        let mut in_out = plaintext.as_bytes().to_vec();
+        let mut in_out = plaintext.to_vec();
         let aad = aead::Aad::empty();
         
         sealing_key.seal_in_place_append_tag(nonce, aad, &mut in_out)
@@ -116,16 +135,13 @@ impl CryptoService {
         result.extend_from_slice(&in_out);
         
         debug!("Data encrypted successfully");
-        Ok(general_purpose::STANDARD.encode(&result))
+        Ok(result)
     }
     
-    /// Decrypt data with a specific key
-    pub fn decrypt_with_key(&self, ciphertext_base64: &str, key: &[u8; KEY_LEN]) -> Result<String> {
+    /// Decrypt binary data sealed by `encrypt_bytes_with_key`
+    pub fn decrypt_bytes_with_key(&self, ciphertext: &[u8], key: &[u8; KEY_LEN]) -> Result<Vec<u8>> {
         debug!("Decrypting data");
         
-        let ciphertext = general_purpose::STANDARD.decode(ciphertext_base64)
-            .map_err(|e| HedgeXError::CryptoError(format!("Invalid ciphertext format: {}", e)))?;
-        
         if ciphertext.len() < NONCE_LEN {
             return Err(HedgeXError::CryptoError("Ciphertext too short".to_string()));
         }
@@ -144,12 +160,39 @@ impl CryptoService {
         let plaintext = opening_key.open_in_place(nonce, aad, &mut in_out)
             .map_err(|_| HedgeXError::CryptoError("Decryption failed".to_string()))?;
         
-        let result = str::from_utf8(plaintext)
-            .map_err(|e| HedgeXError::CryptoError(format!("Invalid UTF-8: {}", e)))?
-            .to_string();
-        
         debug!("Data decrypted successfully");
-        Ok(result)
+        Ok(plaintext.to_vec())
+    }
+    
+    /// Encrypt data with a key derived from a passphrase, laid out as `magic`, the salt, then
+    /// the nonce and ciphertext
+    pub fn seal_with_passphrase(&self, magic: &[u8], data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+        let salt = Self::generate_salt()?;
+        let key = Self::derive_key_from_password(passphrase, &salt)?;
+        let ciphertext = self.encrypt_bytes_with_key(data, &key)?;
+        
+        let mut sealed = Vec::with_capacity(magic.len() + salt.len() + ciphertext.len());
+        sealed.extend_from_slice(magic);
+        sealed.extend_from_slice(&salt);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+    
+    /// Decrypt data sealed by `seal_with_passphrase` under the same `magic`
+    pub fn open_with_passphrase(&self, magic: &[u8], sealed: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+        if !Self::is_sealed_with(magic, sealed) {
+            return Err(HedgeXError::DataIntegrityError("Data is not sealed with a passphrase".to_string()));
+        }
+        
+        let header_len = magic.len() + SALT_LEN;
+        let key = Self::derive_key_from_password(passphrase, &sealed[magic.len()..header_len])?;
+        self.decrypt_bytes_with_key(&sealed[header_len..], &key)
+            .map_err(|_| HedgeXError::CryptoError("Wrong passphrase or corrupted data".to_string()))
+    }
+    
+    /// Whether data starts with `magic` and is long enough to hold a salt after it
+    pub fn is_sealed_with(magic: &[u8], data: &[u8]) -> bool {
+        data.len() >= magic.len() + SALT_LEN && data.starts_with(magic)
     }
     
     /// Generate a secure random salt
@@ -449,6 +492,21 @@ impl EnhancedCryptoService {
         Ok(bytes)
     }
     
+    /// Encrypt data with a key derived from a passphrase, prefixed with the salt it used
+    pub fn encrypt_with_passphrase(&self, data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+        self.inner.seal_with_passphrase(PASSPHRASE_MAGIC, data, passphrase)
+    }
+    
+    /// Decrypt data encrypted by `encrypt_with_passphrase`
+    pub fn decrypt_with_passphrase(&self, data: &[u8], passphrase: &str) -> Result<Vec<u8>> {
+        self.inner.open_with_passphrase(PASSPHRASE_MAGIC, data, passphrase)
+    }
+    
+    /// Whether data looks like the output of `encrypt_with_passphrase`
+    pub fn is_passphrase_encrypted(data: &[u8]) -> bool {
+        CryptoService::is_sealed_with(PASSPHRASE_MAGIC, data)
+    }
+    
     /// Get the inner CryptoService
     pub fn get_inner(&self) -> Arc<CryptoService> {
         Arc::clone(&self.inner)
@@ -5,7 +5,7 @@ use chrono::{DateTime, Utc, TimeZone, NaiveDateTime};
 use chrono_tz::Tz;
 use rust_decimal::Decimal;
 use std::str::FromStr;
-use crate::models::backtesting::{OHLCV, CsvValidationResult, CsvImportConfig};
+use crate::models::backtesting::{OHLCV, CsvValidationResult, CsvImportConfig, CsvColumn, CsvPreview, DecimalFormat};
 use crate::error::{HedgeXError, Result};
 use tracing::{info, warn, error, debug};
 
@@ -14,6 +14,29 @@ pub struct CsvParser {
     config: CsvImportConfig,
 }
 
+/// Column mapping resolved to field positions
+struct ColumnPositions {
+    timestamp: usize,
+    open: usize,
+    high: usize,
+    low: usize,
+    close: usize,
+    volume: Option<usize>,
+}
+
+impl ColumnPositions {
+    /// Fields a line needs to hold every mapped column
+    fn width(&self) -> usize {
+        [self.timestamp, self.open, self.high, self.low, self.close, self.volume.unwrap_or(0)]
+            .iter()
+            .max()
+            .map_or(0, |max| max + 1)
+    }
+}
+
+/// Data lines of a CSV file with their 1-based line numbers
+type CsvLines = Box<dyn Iterator<Item = (usize, std::io::Result<String>)>>;
+
 impl CsvParser {
     /// Create new CSV parser with configuration
     pub fn new(config: CsvImportConfig) -> Self {
@@ -35,29 +58,31 @@ impl CsvParser {
             });
         }
         
-        let file = File::open(path)
-            .map_err(|e| HedgeXError::ConfigError(format!("Failed to open CSV file: {}", e)))?;
-        let reader = BufReader::new(file);
+        let (header, lines) = self.open(file_path)?;
+        let columns = match self.resolve_columns(header.as_deref()) {
+            Ok(columns) => columns,
+            Err(e) => {
+                return Ok(CsvValidationResult {
+                    is_valid: false,
+                    errors: vec![e.to_string()],
+                    warnings: vec![],
+                    total_rows: 0,
+                    valid_rows: 0,
+                });
+            }
+        };
         
         let mut errors = Vec::new();
         let mut warnings = Vec::new();
         let mut total_rows = 0;
         let mut valid_rows = 0;
-        let mut line_number = 0;
         
-        for line in reader.lines() {
-            line_number += 1;
-            
-            // Skip header if configured
-            if line_number == 1 && self.config.has_header {
-                continue;
-            }
-            
+        for (line_number, line) in lines {
             total_rows += 1;
             
             match line {
                 Ok(line_content) => {
-                    match self.parse_csv_line(&line_content, line_number) {
+                    match self.parse_csv_line(&line_content, &columns) {
                         Ok(_) => valid_rows += 1,
                         Err(e) => {
                             errors.push(format!("Line {}: {}", line_number, e));
@@ -76,7 +101,7 @@ impl CsvParser {
         
         // Add warnings for common issues
         if valid_rows == 0 && total_rows > 0 {
-            warnings.push("No valid data rows found. Check date format and column order.".to_string());
+            warnings.push("No valid data rows found. Check date format and column mapping.".to_string());
         }
         
         if valid_rows < total_rows / 2 {
@@ -96,30 +121,43 @@ impl CsvParser {
         })
     }
     
+    /// Parse the first `rows` data lines and validate the whole file without importing anything
+    pub fn preview(&self, file_path: &str, rows: usize) -> Result<CsvPreview> {
+        let validation = self.validate_csv(file_path)?;
+        if !Path::new(file_path).exists() {
+            return Ok(CsvPreview { header: None, rows: Vec::new(), validation });
+        }
+        
+        let (header, lines) = self.open(file_path)?;
+        let parsed = match self.resolve_columns(header.as_deref()) {
+            Ok(columns) => lines
+                .take(rows)
+                .filter_map(|(_, line)| self.parse_csv_line(&line.ok()?, &columns).ok())
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        
+        Ok(CsvPreview {
+            header: header.map(|line| self.split(&line).into_iter().map(str::to_string).collect()),
+            rows: parsed,
+            validation,
+        })
+    }
+    
     /// Parse CSV file and return OHLCV data
     pub fn parse_csv(&self, file_path: &str) -> Result<Vec<OHLCV>> {
         info!("Parsing CSV file: {}", file_path);
         
-        let path = Path::new(file_path);
-        let file = File::open(path)
-            .map_err(|e| HedgeXError::ConfigError(format!("Failed to open CSV file: {}", e)))?;
-        let reader = BufReader::new(file);
+        let (header, lines) = self.open(file_path)?;
+        let columns = self.resolve_columns(header.as_deref())?;
         
         let mut ohlcv_data = Vec::new();
-        let mut line_number = 0;
         let mut errors = Vec::new();
         
-        for line in reader.lines() {
-            line_number += 1;
-            
-            // Skip header if configured
-            if line_number == 1 && self.config.has_header {
-                continue;
-            }
-            
+        for (line_number, line) in lines {
             match line {
                 Ok(line_content) => {
-                    match self.parse_csv_line(&line_content, line_number) {
+                    match self.parse_csv_line(&line_content, &columns) {
                         Ok(ohlcv) => ohlcv_data.push(ohlcv),
                         Err(e) => {
                             errors.push(format!("Line {}: {}", line_number, e));
@@ -150,27 +188,83 @@ impl CsvParser {
         Ok(ohlcv_data)
     }
     
+    /// Open a CSV file, splitting off the header line if the file has one
+    fn open(&self, file_path: &str) -> Result<(Option<String>, CsvLines)> {
+        if self.config.decimal_format == DecimalFormat::Comma && self.config.delimiter == ',' {
+            return Err(HedgeXError::ConfigError(
+                "Comma decimals need a delimiter other than a comma".to_string()
+            ));
+        }
+        
+        let file = File::open(Path::new(file_path))
+            .map_err(|e| HedgeXError::ConfigError(format!("Failed to open CSV file: {}", e)))?;
+        let mut lines = BufReader::new(file).lines().enumerate().map(|(index, line)| (index + 1, line));
+        
+        let header = if self.config.has_header {
+            match lines.next() {
+                Some((_, line)) => Some(line.map_err(|e| HedgeXError::ConfigError(format!("Failed to read CSV header: {}", e)))?),
+                None => None,
+            }
+        } else {
+            None
+        };
+        
+        Ok((header, Box::new(lines)))
+    }
+    
+    /// Work out the position of each mapped column, looking names up in the header
+    fn resolve_columns(&self, header: Option<&str>) -> Result<ColumnPositions> {
+        let names: Vec<String> = header
+            .map(|line| self.split(line).into_iter().map(str::to_lowercase).collect())
+            .unwrap_or_default();
+        
+        let position = |column: &CsvColumn| match column {
+            CsvColumn::Index(index) => Ok(*index),
+            CsvColumn::Name(name) => names.iter()
+                .position(|field| *field == name.trim().to_lowercase())
+                .ok_or_else(|| HedgeXError::ConfigError(format!("Column '{}' not found in CSV header", name))),
+        };
+        
+        let mapping = &self.config.columns;
+        Ok(ColumnPositions {
+            timestamp: position(&mapping.timestamp)?,
+            open: position(&mapping.open)?,
+            high: position(&mapping.high)?,
+            low: position(&mapping.low)?,
+            close: position(&mapping.close)?,
+            volume: mapping.volume.as_ref().map(position).transpose()?,
+        })
+    }
+    
+    /// Split a line on the configured delimiter, trimming whitespace and quotes
+    fn split<'a>(&self, line: &'a str) -> Vec<&'a str> {
+        line.split(self.config.delimiter).map(|s| s.trim().trim_matches('"').trim()).collect()
+    }
+    
     /// Parse a single CSV line into OHLCV data
-    fn parse_csv_line(&self, line: &str, line_number: usize) -> Result<OHLCV> {
-        let fields: Vec<&str> = line.split(',').map(|s| s.trim()).collect();
+    fn parse_csv_line(&self, line: &str, columns: &ColumnPositions) -> Result<OHLCV> {
+        let fields = self.split(line);
         
-        // Expected format: timestamp,open,high,low,close,volume
-        if fields.len() < 6 {
+        if fields.len() < columns.width() {
             return Err(HedgeXError::ConfigError(format!(
-                "Invalid CSV format: expected 6 columns, found {}",
+                "Invalid CSV format: expected {} columns, found {}",
+                columns.width(),
                 fields.len()
             )));
         }
         
         // Parse timestamp
-        let timestamp = self.parse_timestamp(fields[0])?;
+        let timestamp = self.parse_timestamp(fields[columns.timestamp])?;
         
         // Parse OHLCV values
-        let open = self.parse_decimal(fields[1], "open")?;
-        let high = self.parse_decimal(fields[2], "high")?;
-        let low = self.parse_decimal(fields[3], "low")?;
-        let close = self.parse_decimal(fields[4], "close")?;
-        let volume = self.parse_volume(fields[5])?;
+        let open = self.parse_decimal(fields[columns.open], "open")?;
+        let high = self.parse_decimal(fields[columns.high], "high")?;
+        let low = self.parse_decimal(fields[columns.low], "low")?;
+        let close = self.parse_decimal(fields[columns.close], "close")?;
+        let volume = match columns.volume {
+            Some(index) => self.parse_volume(fields[index])?,
+            None => 0,
+        };
         
         // Validate OHLCV data
         self.validate_ohlcv_data(open, high, low, close, volume)?;
@@ -197,16 +291,24 @@ impl CsvParser {
     
     /// Parse decimal value from string
     fn parse_decimal(&self, value_str: &str, field_name: &str) -> Result<Decimal> {
-        Decimal::from_str(value_str)
+        Decimal::from_str(&self.normalize_number(value_str))
             .map_err(|e| HedgeXError::ConfigError(format!("Failed to parse {} '{}': {}", field_name, value_str, e)))
     }
     
     /// Parse volume from string
     fn parse_volume(&self, volume_str: &str) -> Result<i64> {
-        volume_str.parse::<i64>()
+        self.normalize_number(volume_str).parse::<i64>()
             .map_err(|e| HedgeXError::ConfigError(format!("Failed to parse volume '{}': {}", volume_str, e)))
     }
     
+    /// Rewrite a number in the configured format with a point decimal and no grouping
+    fn normalize_number(&self, value: &str) -> String {
+        match self.config.decimal_format {
+            DecimalFormat::Point => value.replace(',', ""),
+            DecimalFormat::Comma => value.replace('.', "").replace(',', "."),
+        }
+    }
+    
     /// Validate OHLCV data for consistency
     fn validate_ohlcv_data(&self, open: Decimal, high: Decimal, low: Decimal, close: Decimal, volume: i64) -> Result<()> {
         // Check that high is the highest value
@@ -286,6 +388,7 @@ impl CsvParser {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::backtesting::CsvColumnMapping;
     use tempfile::NamedTempFile;
     use std::io::Write;
     
@@ -297,6 +400,7 @@ mod tests {
             has_header: true,
             date_format: "%Y-%m-%d %H:%M:%S".to_string(),
             timezone: "Asia/Kolkata".to_string(),
+            ..CsvImportConfig::default()
         }
     }
     
@@ -351,6 +455,69 @@ mod tests {
         assert!(!result.errors.is_empty());
     }
     
+    #[test]
+    fn test_column_mapping_and_number_format() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "Symbol;Close;Date;High;Low;Open").unwrap();
+        writeln!(temp_file, "RELIANCE;\"1.003,50\";2024-01-01 09:15:00;1.005,00;999,25;1.000,00").unwrap();
+        
+        let config = CsvImportConfig {
+            columns: CsvColumnMapping {
+                timestamp: CsvColumn::Name("date".to_string()),
+                open: CsvColumn::Name("Open".to_string()),
+                high: CsvColumn::Name("High".to_string()),
+                low: CsvColumn::Name("Low".to_string()),
+                close: CsvColumn::Index(1),
+                volume: None,
+            },
+            delimiter: ';',
+            decimal_format: DecimalFormat::Comma,
+            ..create_test_csv_config()
+        };
+        let parser = CsvParser::new(config.clone());
+        
+        let ohlcv_data = parser.parse_csv(temp_file.path().to_str().unwrap()).unwrap();
+        assert_eq!(ohlcv_data.len(), 1);
+        assert_eq!(ohlcv_data[0].open, Decimal::from(1000));
+        assert_eq!(ohlcv_data[0].close, Decimal::from_str("1003.5").unwrap());
+        assert_eq!(ohlcv_data[0].low, Decimal::from_str("999.25").unwrap());
+        assert_eq!(ohlcv_data[0].volume, 0);
+        
+        // Unknown column names and ambiguous delimiters are rejected
+        let missing = CsvImportConfig {
+            columns: CsvColumnMapping { open: CsvColumn::Name("Opening".to_string()), ..config.columns.clone() },
+            ..config.clone()
+        };
+        let result = CsvParser::new(missing).validate_csv(temp_file.path().to_str().unwrap()).unwrap();
+        assert!(!result.is_valid);
+        assert!(result.errors[0].contains("Opening"));
+        
+        let ambiguous = CsvImportConfig { delimiter: ',', ..config };
+        assert!(CsvParser::new(ambiguous).parse_csv(temp_file.path().to_str().unwrap()).is_err());
+    }
+    
+    #[test]
+    fn test_preview_reports_rows_and_errors() {
+        let mut temp_file = NamedTempFile::new().unwrap();
+        writeln!(temp_file, "timestamp,open,high,low,close,volume").unwrap();
+        writeln!(temp_file, "2024-01-01 09:15:00,100.0,105.0,99.0,103.0,1000").unwrap();
+        writeln!(temp_file, "2024-01-01 09:16:00,103.0,106.0,102.0,105.0,1500").unwrap();
+        writeln!(temp_file, "2024-01-01 09:17:00,105.0,104.0,102.0,103.0,900").unwrap(); // high < open
+        
+        let parser = CsvParser::new(create_test_csv_config());
+        let preview = parser.preview(temp_file.path().to_str().unwrap(), 1).unwrap();
+        
+        assert_eq!(preview.header.unwrap()[4], "close");
+        assert_eq!(preview.rows.len(), 1);
+        assert_eq!(preview.rows[0].close, Decimal::from(103));
+        
+        // Validation still covers the whole file
+        assert!(!preview.validation.is_valid);
+        assert_eq!(preview.validation.total_rows, 3);
+        assert_eq!(preview.validation.valid_rows, 2);
+        assert!(preview.validation.errors[0].starts_with("Line 4"));
+    }
+    
     #[test]
     fn test_date_format_detection() {
         let sample_lines = vec![
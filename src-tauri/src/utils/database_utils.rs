@@ -22,7 +22,7 @@ pub struct DatabaseManager {
 impl DatabaseManager {
     /// Create a new database manager
     pub async fn new(app_data_dir: &Path) -> Result<Self> {
-        Self::new_with_config(app_data_dir, DatabaseConfig::default()).await
+        Self::new_with_config(app_data_dir, DatabaseConfig::from_env()).await
     }
     
     /// Create a new database manager with custom configuration
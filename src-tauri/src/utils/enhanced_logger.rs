@@ -97,9 +97,12 @@ impl EnhancedLogger {
                     .with_timer(ChronoUtc::rfc_3339())
             )
             .with(filter)
-            .init();
+            .try_init()
+            .map_or_else(
+                |_| debug!("Tracing already initialized"),
+                |_| info!("Tracing initialized successfully"),
+            );
             
-        info!("Tracing initialized successfully");
         Ok(())
     }
     
@@ -69,7 +69,7 @@ impl CircuitBreaker {
                 ])
             ).await.ok();
             
-            return Err(HedgeXError::ExternalServiceError(error_msg.to_string()));
+            return Err(HedgeXError::CircuitOpen("operation blocked".to_string()));
         }
 
         // Execute the operation
@@ -277,6 +277,11 @@ impl ExponentialBackoff {
                 }
                 Err(e) => {
                     let hedgex_error = e.into();
+                    
+                    // Retrying against an open circuit would only be blocked again
+                    if matches!(hedgex_error, HedgeXError::CircuitOpen(_)) {
+                        return Err(hedgex_error);
+                    }
                     last_error = Some(hedgex_error);
                     
                     if attempt < self.max_retries {
@@ -5,6 +5,8 @@ pub mod database_utils;
 pub mod error_recovery;
 pub mod performance_monitor;
 pub mod csv_parser;
+pub mod text_pdf;
+pub mod xlsx;
 
 #[cfg(test)]
 mod tests {
@@ -19,3 +21,5 @@ pub use enhanced_logger::EnhancedLogger;
 pub use error_recovery::{ErrorRecoveryManager, CircuitBreaker, ExponentialBackoff, HealthCheckManager, HealthCheck, HealthStatus};
 pub use performance_monitor::{PerformanceMonitor, PerformanceMetrics, RequestTimer, PerformanceAlert, AlertThreshold};
 pub use csv_parser::CsvParser;
+pub use text_pdf::render_text_pdf;
+pub use xlsx::{render_xlsx, XlsxCell, XlsxSheet};
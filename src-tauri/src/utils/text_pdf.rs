@@ -0,0 +1,116 @@
+/// Landscape A4 in points
+const PAGE_WIDTH: f64 = 842.0;
+const PAGE_HEIGHT: f64 = 595.0;
+const MARGIN: f64 = 36.0;
+const FONT_SIZE: f64 = 8.0;
+const LEADING: f64 = 10.0;
+
+/// Lines of monospaced text that fit on a page below the title
+pub const LINES_PER_PAGE: usize = ((PAGE_HEIGHT - 2.0 * MARGIN) / LEADING) as usize - 2;
+
+/// Characters of Courier at the font size that fit across a page
+pub const CHARS_PER_LINE: usize = ((PAGE_WIDTH - 2.0 * MARGIN) / (FONT_SIZE * 0.6)) as usize;
+
+/// Render preformatted lines as a PDF in Courier, with the title and page number on each page
+///
+/// Only ASCII is drawn; other characters are replaced, and lines are cut at the page width.
+pub fn render_text_pdf(title: &str, lines: &[String]) -> Vec<u8> {
+    let pages: Vec<&[String]> = if lines.is_empty() {
+        vec![&[]]
+    } else {
+        lines.chunks(LINES_PER_PAGE).collect()
+    };
+
+    // Objects 1 to 3 are the catalog, page tree and font; each page adds a page and its content
+    let page_ids: Vec<usize> = (0..pages.len()).map(|i| 4 + i * 2).collect();
+    let mut objects = vec![
+        "<< /Type /Catalog /Pages 2 0 R >>".to_string(),
+        format!(
+            "<< /Type /Pages /Kids [{}] /Count {} >>",
+            page_ids.iter().map(|id| format!("{} 0 R", id)).collect::<Vec<_>>().join(" "),
+            pages.len()
+        ),
+        "<< /Type /Font /Subtype /Type1 /BaseFont /Courier /Encoding /WinAnsiEncoding >>".to_string(),
+    ];
+
+    for (index, page_lines) in pages.iter().enumerate() {
+        let mut content = format!("BT\n/F1 {} Tf\n{} TL\n{} {} Td\n", FONT_SIZE, LEADING, MARGIN, PAGE_HEIGHT - MARGIN);
+        let heading = format!("{}  (page {} of {})", title, index + 1, pages.len());
+        content.push_str(&format!("({}) Tj\nT*\nT*\n", escape(&heading)));
+        for line in page_lines.iter() {
+            content.push_str(&format!("({}) Tj\nT*\n", escape(line)));
+        }
+        content.push_str("ET");
+
+        objects.push(format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 3 0 R >> >> /Contents {} 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT, page_ids[index] + 1
+        ));
+        objects.push(format!("<< /Length {} >>\nstream\n{}\nendstream", content.len(), content));
+    }
+
+    let mut pdf = String::from("%PDF-1.4\n");
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (index, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.push_str(&format!("{} 0 obj\n{}\nendobj\n", index + 1, object));
+    }
+
+    let xref_offset = pdf.len();
+    pdf.push_str(&format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1));
+    for offset in offsets {
+        pdf.push_str(&format!("{:010} 00000 n \n", offset));
+    }
+    pdf.push_str(&format!(
+        "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+        objects.len() + 1,
+        xref_offset
+    ));
+
+    pdf.into_bytes()
+}
+
+/// Escape a line for a PDF string literal, keeping it within the page width
+fn escape(line: &str) -> String {
+    let mut escaped = String::with_capacity(line.len());
+    for c in line.chars().take(CHARS_PER_LINE) {
+        match c {
+            '(' | ')' | '\\' => {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            ' '..='~' => escaped.push(c),
+            _ => escaped.push('?'),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_text_pdf_paginates() {
+        let lines: Vec<String> = (0..LINES_PER_PAGE + 5).map(|i| format!("Line {} (of rows)", i)).collect();
+        let pdf = String::from_utf8(render_text_pdf("Statement", &lines)).unwrap();
+
+        assert!(pdf.starts_with("%PDF-1.4\n"));
+        assert!(pdf.ends_with("%%EOF\n"));
+        assert!(pdf.contains("/Count 2"));
+        assert!(pdf.contains("(Statement  \\(page 2 of 2\\)) Tj"));
+        assert!(pdf.contains("(Line 0 \\(of rows\\)) Tj"));
+
+        // The cross-reference table points at each object
+        let xref = &pdf[pdf.find("xref\n").unwrap()..];
+        let first = xref.lines().nth(3).unwrap();
+        let offset: usize = first[..10].parse().unwrap();
+        assert!(pdf[offset..].starts_with("1 0 obj"));
+    }
+
+    #[test]
+    fn test_escape_replaces_non_ascii() {
+        assert_eq!(escape("₹100 (net)"), "?100 \\(net\\)");
+        assert_eq!(escape(&"x".repeat(CHARS_PER_LINE + 10)).len(), CHARS_PER_LINE);
+    }
+}
@@ -0,0 +1,354 @@
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use std::io::Write;
+
+/// Excel rejects sheet names longer than this
+const MAX_SHEET_NAME_CHARS: usize = 31;
+
+/// A worksheet cell
+#[derive(Debug, Clone, PartialEq)]
+pub enum XlsxCell {
+    Empty,
+    Number(f64),
+    Text(String),
+}
+
+impl From<&serde_json::Value> for XlsxCell {
+    /// Numbers stay numeric; strings are kept as text even when they look numeric, so order IDs keep their digits
+    fn from(value: &serde_json::Value) -> Self {
+        match value {
+            serde_json::Value::Null => XlsxCell::Empty,
+            serde_json::Value::Number(number) => number.as_f64().map_or(XlsxCell::Empty, XlsxCell::Number),
+            serde_json::Value::String(text) => XlsxCell::Text(text.clone()),
+            other => XlsxCell::Text(other.to_string()),
+        }
+    }
+}
+
+/// A named worksheet of rows; the first row is written in bold as its header
+#[derive(Debug, Clone, PartialEq)]
+pub struct XlsxSheet {
+    pub name: String,
+    pub rows: Vec<Vec<XlsxCell>>,
+}
+
+impl XlsxSheet {
+    /// A sheet with a row per JSON record under a header of the first record's keys
+    ///
+    /// A value that is not an array of objects is written as a single record.
+    pub fn from_records(name: &str, records: &serde_json::Value) -> Self {
+        let records: Vec<&serde_json::Value> = match records {
+            serde_json::Value::Array(items) => items.iter().collect(),
+            other => vec![other],
+        };
+        let headers: Vec<&String> = match records.first() {
+            Some(serde_json::Value::Object(first)) => first.keys().collect(),
+            _ => Vec::new(),
+        };
+
+        let mut rows = Vec::with_capacity(records.len() + 1);
+        if headers.is_empty() {
+            rows.extend(records.iter().map(|record| vec![XlsxCell::from(*record)]));
+        } else {
+            rows.push(headers.iter().map(|header| XlsxCell::Text(header.to_string())).collect());
+            rows.extend(records.iter().map(|record| {
+                headers.iter()
+                    .map(|header| record.get(header.as_str()).map_or(XlsxCell::Empty, XlsxCell::from))
+                    .collect()
+            }));
+        }
+
+        Self { name: name.to_string(), rows }
+    }
+}
+
+/// Render worksheets as an Excel workbook, in order
+///
+/// Sheet names are cut to Excel's limit with the characters it forbids replaced, and numbered when repeated.
+pub fn render_xlsx(sheets: &[XlsxSheet]) -> Vec<u8> {
+    let mut names: Vec<String> = Vec::with_capacity(sheets.len());
+    for sheet in sheets {
+        let mut name = sheet_name(&sheet.name);
+        let base = name.clone();
+        let mut copy = 1;
+        while names.iter().any(|existing| existing.eq_ignore_ascii_case(&name)) {
+            copy += 1;
+            let suffix = format!(" ({})", copy);
+            name = format!("{}{}", base.chars().take(MAX_SHEET_NAME_CHARS - suffix.len()).collect::<String>(), suffix);
+        }
+        names.push(name);
+    }
+
+    let mut content_types = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<Types xmlns="http://schemas.openxmlformats.org/package/2006/content-types">"#,
+        r#"<Default Extension="rels" ContentType="application/vnd.openxmlformats-package.relationships+xml"/>"#,
+        r#"<Default Extension="xml" ContentType="application/xml"/>"#,
+        r#"<Override PartName="/xl/workbook.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.sheet.main+xml"/>"#,
+        r#"<Override PartName="/xl/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.styles+xml"/>"#,
+    ));
+    let mut workbook = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<workbook xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main" "#,
+        r#"xmlns:r="http://schemas.openxmlformats.org/officeDocument/2006/relationships"><sheets>"#,
+    ));
+    let mut workbook_rels = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+    ));
+    for (index, name) in names.iter().enumerate() {
+        let id = index + 1;
+        content_types.push_str(&format!(
+            r#"<Override PartName="/xl/worksheets/sheet{}.xml" ContentType="application/vnd.openxmlformats-officedocument.spreadsheetml.worksheet+xml"/>"#,
+            id
+        ));
+        workbook.push_str(&format!(r#"<sheet name="{}" sheetId="{}" r:id="rId{}"/>"#, escape_xml(name), id, id));
+        workbook_rels.push_str(&format!(
+            r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/worksheet" Target="worksheets/sheet{}.xml"/>"#,
+            id, id
+        ));
+    }
+    content_types.push_str("</Types>");
+    workbook.push_str("</sheets></workbook>");
+    // The styles part follows the sheets
+    workbook_rels.push_str(&format!(
+        r#"<Relationship Id="rId{}" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/styles" Target="styles.xml"/></Relationships>"#,
+        names.len() + 1
+    ));
+
+    let root_rels = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">"#,
+        r#"<Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="xl/workbook.xml"/>"#,
+        r#"</Relationships>"#,
+    );
+    // Style 1 is the bold header
+    let styles = concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<styleSheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main">"#,
+        r#"<fonts count="2"><font><sz val="11"/><name val="Calibri"/></font><font><b/><sz val="11"/><name val="Calibri"/></font></fonts>"#,
+        r#"<fills count="2"><fill><patternFill patternType="none"/></fill><fill><patternFill patternType="gray125"/></fill></fills>"#,
+        r#"<borders count="1"><border><left/><right/><top/><bottom/><diagonal/></border></borders>"#,
+        r#"<cellStyleXfs count="1"><xf numFmtId="0" fontId="0" fillId="0" borderId="0"/></cellStyleXfs>"#,
+        r#"<cellXfs count="2"><xf numFmtId="0" fontId="0" fillId="0" borderId="0" xfId="0"/>"#,
+        r#"<xf numFmtId="0" fontId="1" fillId="0" borderId="0" xfId="0" applyFont="1"/></cellXfs>"#,
+        r#"</styleSheet>"#,
+    );
+
+    let mut zip = ZipWriter::default();
+    zip.add("[Content_Types].xml", content_types.as_bytes());
+    zip.add("_rels/.rels", root_rels.as_bytes());
+    zip.add("xl/workbook.xml", workbook.as_bytes());
+    zip.add("xl/_rels/workbook.xml.rels", workbook_rels.as_bytes());
+    zip.add("xl/styles.xml", styles.as_bytes());
+    for (index, sheet) in sheets.iter().enumerate() {
+        zip.add(&format!("xl/worksheets/sheet{}.xml", index + 1), worksheet(&sheet.rows).as_bytes());
+    }
+    zip.finish()
+}
+
+fn worksheet(rows: &[Vec<XlsxCell>]) -> String {
+    let mut xml = String::from(concat!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>"#,
+        r#"<worksheet xmlns="http://schemas.openxmlformats.org/spreadsheetml/2006/main"><sheetData>"#,
+    ));
+    for (row_index, row) in rows.iter().enumerate() {
+        let row_number = row_index + 1;
+        let style = if row_index == 0 { r#" s="1""# } else { "" };
+        xml.push_str(&format!(r#"<row r="{}">"#, row_number));
+        for (column, cell) in row.iter().enumerate() {
+            let reference = format!("{}{}", column_name(column), row_number);
+            match cell {
+                XlsxCell::Empty => {}
+                XlsxCell::Number(value) if value.is_finite() => {
+                    xml.push_str(&format!(r#"<c r="{}"{}><v>{}</v></c>"#, reference, style, value));
+                }
+                XlsxCell::Number(_) => {}
+                XlsxCell::Text(text) => {
+                    xml.push_str(&format!(
+                        r#"<c r="{}"{} t="inlineStr"><is><t xml:space="preserve">{}</t></is></c>"#,
+                        reference, style, escape_xml(text)
+                    ));
+                }
+            }
+        }
+        xml.push_str("</row>");
+    }
+    xml.push_str("</sheetData></worksheet>");
+    xml
+}
+
+/// Spreadsheet column letters of a zero-based index: A to Z, then AA
+fn column_name(mut index: usize) -> String {
+    let mut letters = Vec::new();
+    loop {
+        letters.push(b'A' + (index % 26) as u8);
+        if index < 26 {
+            break;
+        }
+        index = index / 26 - 1;
+    }
+    letters.iter().rev().map(|letter| *letter as char).collect()
+}
+
+fn sheet_name(name: &str) -> String {
+    let cleaned: String = name.chars()
+        .map(|c| if matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\') { '_' } else { c })
+        .take(MAX_SHEET_NAME_CHARS)
+        .collect();
+    let trimmed = cleaned.trim_matches('\'').trim();
+    if trimmed.is_empty() { "Sheet".to_string() } else { trimmed.to_string() }
+}
+
+/// Escape text for XML, dropping the control characters XML cannot hold
+fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\t' | '\n' | '\r' => escaped.push(c),
+            c if c < ' ' => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Minimal zip archive writer with deflated entries, as XLSX packages are zip files
+#[derive(Default)]
+struct ZipWriter {
+    data: Vec<u8>,
+    central_directory: Vec<u8>,
+    entries: u16,
+}
+
+impl ZipWriter {
+    fn add(&mut self, name: &str, contents: &[u8]) {
+        let mut crc = Crc::new();
+        crc.update(contents);
+        let mut encoder = DeflateEncoder::new(Vec::new(), Compression::default());
+        // Writing to a Vec cannot fail
+        encoder.write_all(contents).expect("deflate into memory");
+        let compressed = encoder.finish().expect("deflate into memory");
+
+        let offset = self.data.len() as u32;
+        // Version 2.0, no flags, deflated, dated 1980-01-01 00:00
+        let common = |out: &mut Vec<u8>| {
+            out.extend_from_slice(&20u16.to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+            out.extend_from_slice(&8u16.to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+            out.extend_from_slice(&0x21u16.to_le_bytes());
+            out.extend_from_slice(&crc.sum().to_le_bytes());
+            out.extend_from_slice(&(compressed.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(contents.len() as u32).to_le_bytes());
+            out.extend_from_slice(&(name.len() as u16).to_le_bytes());
+            out.extend_from_slice(&0u16.to_le_bytes());
+        };
+
+        self.data.extend_from_slice(&0x04034b50u32.to_le_bytes());
+        common(&mut self.data);
+        self.data.extend_from_slice(name.as_bytes());
+        self.data.extend_from_slice(&compressed);
+
+        self.central_directory.extend_from_slice(&0x02014b50u32.to_le_bytes());
+        self.central_directory.extend_from_slice(&20u16.to_le_bytes());
+        common(&mut self.central_directory);
+        // No comment, disk 0, no attributes
+        self.central_directory.extend_from_slice(&[0u8; 10]);
+        self.central_directory.extend_from_slice(&offset.to_le_bytes());
+        self.central_directory.extend_from_slice(name.as_bytes());
+        self.entries += 1;
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        let directory_offset = self.data.len() as u32;
+        self.data.extend_from_slice(&self.central_directory);
+        self.data.extend_from_slice(&0x06054b50u32.to_le_bytes());
+        self.data.extend_from_slice(&[0u8; 4]);
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&self.entries.to_le_bytes());
+        self.data.extend_from_slice(&(self.central_directory.len() as u32).to_le_bytes());
+        self.data.extend_from_slice(&directory_offset.to_le_bytes());
+        self.data.extend_from_slice(&0u16.to_le_bytes());
+        self.data
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::DeflateDecoder;
+    use serde_json::json;
+    use std::io::Read;
+
+    /// Entries of a zip archive written by `ZipWriter`, by walking its local headers
+    fn unzip(data: &[u8]) -> Vec<(String, String)> {
+        let read_u16 = |at: usize| u16::from_le_bytes([data[at], data[at + 1]]) as usize;
+        let read_u32 = |at: usize| u32::from_le_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+        let mut entries = Vec::new();
+        let mut at = 0;
+        while read_u32(at) == 0x04034b50 {
+            let (expected_crc, compressed_len, name_len) = (read_u32(at + 14), read_u32(at + 18) as usize, read_u16(at + 26));
+            let name = String::from_utf8(data[at + 30..at + 30 + name_len].to_vec()).unwrap();
+            let start = at + 30 + name_len;
+            let mut contents = String::new();
+            DeflateDecoder::new(&data[start..start + compressed_len]).read_to_string(&mut contents).unwrap();
+
+            let mut crc = Crc::new();
+            crc.update(contents.as_bytes());
+            assert_eq!(crc.sum(), expected_crc);
+            entries.push((name, contents));
+            at = start + compressed_len;
+        }
+        assert_eq!(read_u32(at), 0x02014b50);
+        entries
+    }
+
+    #[test]
+    fn test_render_xlsx_sheets() {
+        let trades = XlsxSheet::from_records("Trades", &json!([
+            { "order_id": "230405000012345", "price": 1500.5, "symbol": "M&M" },
+            { "order_id": null, "price": 10, "symbol": "INFY" },
+        ]));
+        assert_eq!(trades.rows[0], vec![
+            XlsxCell::Text("order_id".to_string()), XlsxCell::Text("price".to_string()), XlsxCell::Text("symbol".to_string()),
+        ]);
+        assert_eq!(trades.rows[2][0], XlsxCell::Empty);
+
+        let sheets = vec![trades, XlsxSheet::from_records("Daily P&L: 2024/08", &json!({ "net_pnl": -25.0 }))];
+        let entries = unzip(&render_xlsx(&sheets));
+        let names: Vec<&str> = entries.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, vec![
+            "[Content_Types].xml", "_rels/.rels", "xl/workbook.xml", "xl/_rels/workbook.xml.rels",
+            "xl/styles.xml", "xl/worksheets/sheet1.xml", "xl/worksheets/sheet2.xml",
+        ]);
+
+        assert!(entries[2].1.contains(r#"<sheet name="Daily P&amp;L_ 2024_08" sheetId="2" r:id="rId2"/>"#));
+        let sheet1 = &entries[5].1;
+        assert!(sheet1.contains(r#"<c r="A2" t="inlineStr"><is><t xml:space="preserve">230405000012345</t></is></c>"#));
+        assert!(sheet1.contains(r#"<c r="B2"><v>1500.5</v></c><c r="C2" t="inlineStr"><is><t xml:space="preserve">M&amp;M</t></is></c>"#));
+        assert!(sheet1.contains(r#"<row r="3"><c r="B3"><v>10</v></c>"#));
+        assert!(entries[6].1.contains(r#"<c r="A2"><v>-25</v></c>"#));
+    }
+
+    #[test]
+    fn test_sheet_and_column_names() {
+        assert_eq!(column_name(0), "A");
+        assert_eq!(column_name(25), "Z");
+        assert_eq!(column_name(26), "AA");
+        assert_eq!(column_name(701), "ZZ");
+        assert_eq!(column_name(702), "AAA");
+
+        assert_eq!(sheet_name(&"x".repeat(40)).len(), MAX_SHEET_NAME_CHARS);
+        assert_eq!(sheet_name("''"), "Sheet");
+
+        // Repeated names are numbered so the workbook stays valid
+        let sheets = vec![XlsxSheet::from_records("Trades", &json!([])), XlsxSheet::from_records("trades", &json!([]))];
+        let workbook = &unzip(&render_xlsx(&sheets))[2].1;
+        assert!(workbook.contains(r#"name="Trades""#) && workbook.contains(r#"name="trades (2)""#));
+    }
+}